@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+/// Supported wipe algorithms exposed to the frontend.
+/// Each variant maps to a specific pass count and pattern strategy enforced in the backend.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum WipeAlgorithm {
+    NistClear,      // NIST 800-88 Clear: 1 pass zeros (replaces Basic)
+    NistPurge,      // NIST 800-88 Purge: 3 pass overwrite (replaces DOD)
+    Gutmann,        // 35 pass: Gutmann pattern (kept for legacy/specific needs)
+    GutmannLite,    // 13 pass: Gutmann's random and 0x55/0xAA/0x92-series passes only
+    Random,         // N passes of random data (replaces DOD_E and custom needs)
+}
+
+impl WipeAlgorithm {
+    /// Pass count the UI uses for this algorithm; background jobs without a user choice use it too.
+    pub fn default_passes(&self) -> u32 {
+        match self {
+            WipeAlgorithm::NistClear => 1,
+            WipeAlgorithm::NistPurge => 3,
+            WipeAlgorithm::Gutmann => 35,
+            WipeAlgorithm::GutmannLite => GUTMANN_LITE_PASSES.len() as u32,
+            WipeAlgorithm::Random => 3,
+        }
+    }
+
+    /// Name shown in progress updates.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            WipeAlgorithm::NistClear => "NIST 800-88 Clear",
+            WipeAlgorithm::NistPurge => "NIST 800-88 Purge",
+            WipeAlgorithm::Gutmann => "Gutmann",
+            WipeAlgorithm::GutmannLite => "Gutmann Lite",
+            WipeAlgorithm::Random => "Random",
+        }
+    }
+
+    /// What each of `passes` passes writes; `passes` only matters for `Random`, as the engine
+    /// runs the other algorithms' fixed pass lists whatever count it is given.
+    pub fn pass_kinds(&self, passes: u32) -> Vec<PassKind> {
+        match self {
+            WipeAlgorithm::NistClear => vec![PassKind::Zeros],
+            WipeAlgorithm::NistPurge => vec![PassKind::Zeros, PassKind::Pattern, PassKind::Random],
+            WipeAlgorithm::Gutmann | WipeAlgorithm::GutmannLite => gutmann_passes(self)
+                .into_iter()
+                .map(|(pattern, is_random, _)| match (is_random, pattern.as_slice()) {
+                    (true, _) => PassKind::Random,
+                    (false, [0x00]) => PassKind::Zeros,
+                    (false, _) => PassKind::Pattern,
+                })
+                .collect(),
+            WipeAlgorithm::Random => vec![PassKind::Random; passes as usize],
+        }
+    }
+}
+
+/// The kinds of data a pass writes, which can run at very different speeds: drives that
+/// compress or deduplicate handle zeros and repeated patterns faster than random bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PassKind {
+    Zeros,
+    Pattern,
+    Random,
+}
+
+/// Gutmann's 35 passes; an empty pattern is a random pass.
+/// Reference: https://en.wikipedia.org/wiki/Gutmann_method
+const GUTMANN_PASSES: [&[u8]; 35] = [
+    // Passes 1-4: Random
+    &[], &[], &[], &[],
+    // Passes 5-31: Fixed patterns
+    &[0x55, 0xAA, 0x55, 0xAA], &[0xAA, 0x55, 0xAA, 0x55],
+    &[0x92, 0x49, 0x24], &[0x49, 0x24, 0x92], &[0x24, 0x92, 0x49],
+    &[0x00], &[0x11], &[0x22], &[0x33], &[0x44], &[0x55], &[0x66], &[0x77],
+    &[0x88], &[0x99], &[0xAA], &[0xBB], &[0xCC], &[0xDD], &[0xEE], &[0xFF],
+    &[0x92, 0x49, 0x24], &[0x49, 0x24, 0x92], &[0x24, 0x92, 0x49],
+    &[0x6D, 0xB6, 0xDB], &[0xB6, 0xDB, 0x6D], &[0xDB, 0x6D, 0xB6],
+    // Passes 32-35: Random
+    &[], &[], &[], &[],
+];
+
+/// Passes of `GUTMANN_PASSES` kept by Gutmann Lite: the random passes either side and the
+/// 0x55/0xAA and 0x92-series patterns. The 0x00-0xFF sweep and the (2,7) RLL patterns only
+/// targeted encodings that no drive built since the 1990s uses.
+const GUTMANN_LITE_PASSES: [usize; 13] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 31, 32, 33, 34];
+
+/// Pattern, randomness and progress label of each pass of a Gutmann variant, numbered within it.
+pub(crate) fn gutmann_passes(algorithm: &WipeAlgorithm) -> Vec<(Vec<u8>, bool, String)> {
+    let selected: Vec<&[u8]> = match algorithm {
+        WipeAlgorithm::GutmannLite => GUTMANN_LITE_PASSES.iter().map(|&i| GUTMANN_PASSES[i]).collect(),
+        _ => GUTMANN_PASSES.to_vec(),
+    };
+    let total = selected.len();
+    selected
+        .into_iter()
+        .enumerate()
+        .map(|(i, pattern)| {
+            if pattern.is_empty() {
+                (vec![0x00], true, format!("Random data (Pass {}/{})", i + 1, total))
+            } else {
+                // The label names one period of the pattern; 0x55 0xAA is stored doubled.
+                let period = if pattern.len() == 4 { &pattern[..2] } else { pattern };
+                let bytes: Vec<String> = period.iter().map(|b| format!("0x{:02X}", b)).collect();
+                (pattern.to_vec(), false, format!("Pattern {}/{}: {}", i + 1, total, bytes.join(" ")))
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,78 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::BUFFER_SIZE;
+
+/// How far a checkpointed wipe got: every pass before `completed_passes` is written and synced,
+/// and so are the first `offset` bytes of the pass after them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PassCheckpoint {
+    pub completed_passes: u32,
+    pub offset: u64,
+    /// Seed of each pass's random stream, so a resumed pass continues the bytes it was writing.
+    /// Passes with a fixed pattern ignore theirs.
+    pub seeds: Vec<u64>,
+    /// Seed of the verification sample the run drew, so a resumed run reads back the same chunks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_seed: Option<u64>,
+}
+
+impl PassCheckpoint {
+    /// Whether this checkpoint can belong to a run of `pass_count` passes over `size` bytes.
+    /// Offsets fall on chunk boundaries, where a random stream can be picked up again exactly.
+    pub fn fits(&self, pass_count: usize, size: u64) -> bool {
+        self.seeds.len() == pass_count
+            && (self.completed_passes as usize) < pass_count
+            && self.offset < size.max(1)
+            && self.offset.is_multiple_of(BUFFER_SIZE)
+    }
+}
+
+/// Checkpoints for `overwrite_target_with`.
+pub struct Checkpointing<'a> {
+    /// Bytes written between two checkpoints within a pass; every finished pass is one as well.
+    pub interval: u64,
+    /// Where an earlier run stopped; `None`, or a checkpoint that does not fit, starts at pass 1.
+    pub resume: Option<PassCheckpoint>,
+    /// Receives each checkpoint once the bytes it covers are synced.
+    pub on_checkpoint: &'a mut dyn FnMut(&PassCheckpoint),
+}
+
+/// The random stream of a pass seeded with `seed`, positioned `offset` bytes in.
+pub(crate) fn pass_rng(seed: u64, offset: u64) -> ChaCha12Rng {
+    let mut rng = ChaCha12Rng::seed_from_u64(seed);
+    // The stream advances one 32-bit word per four bytes handed out.
+    rng.set_word_pos(u128::from(offset / 4));
+    rng
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn a_pass_stream_picks_up_where_it_left_off() {
+        let chunk = BUFFER_SIZE as usize;
+        let mut whole = vec![0u8; 3 * chunk];
+        let mut rng = pass_rng(42, 0);
+        for part in whole.chunks_mut(chunk) {
+            rng.fill_bytes(part);
+        }
+
+        let mut resumed = vec![0u8; chunk];
+        pass_rng(42, 2 * BUFFER_SIZE).fill_bytes(&mut resumed);
+        assert_eq!(resumed, whole[2 * chunk..]);
+    }
+
+    #[test]
+    fn checkpoints_must_match_the_run_they_resume() {
+        let checkpoint = PassCheckpoint { completed_passes: 1, offset: BUFFER_SIZE, seeds: vec![1, 2, 3], verify_seed: None };
+        assert!(checkpoint.fits(3, 4 * BUFFER_SIZE));
+        assert!(!checkpoint.fits(1, 4 * BUFFER_SIZE), "a different pass count");
+        assert!(!checkpoint.fits(3, BUFFER_SIZE), "the file shrank");
+        assert!(!PassCheckpoint { offset: 100, ..checkpoint.clone() }.fits(3, 4 * BUFFER_SIZE));
+        assert!(!PassCheckpoint { completed_passes: 3, ..checkpoint }.fits(3, 4 * BUFFER_SIZE));
+    }
+}
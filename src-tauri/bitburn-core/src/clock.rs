@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How far the wall clock may run ahead of or behind the monotonic clock between two readings
+/// before the system is taken to have slept, or its clock to have been set.
+pub const CLOCK_JUMP_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// A gap between the wall clock and the monotonic clock, seen across two readings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockJump {
+    /// Wall-clock time that passed; negative when the clock was set back.
+    pub wall_ms: i64,
+    /// Monotonic time that passed, which on most platforms leaves out time spent asleep.
+    pub monotonic_ms: u64,
+}
+
+/// Compares the wall clock with the monotonic clock from one reading to the next, so a wipe
+/// notices when the machine slept under it or its clock was changed.
+#[derive(Debug, Clone)]
+pub struct ResumeDetector {
+    monotonic: Instant,
+    wall: SystemTime,
+}
+
+impl ResumeDetector {
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now(), SystemTime::now())
+    }
+
+    pub fn starting_at(monotonic: Instant, wall: SystemTime) -> Self {
+        ResumeDetector { monotonic, wall }
+    }
+
+    /// Read both clocks and report a jump since the last reading.
+    pub fn observe(&mut self) -> Option<ClockJump> {
+        self.observe_at(Instant::now(), SystemTime::now())
+    }
+
+    pub fn observe_at(&mut self, monotonic: Instant, wall: SystemTime) -> Option<ClockJump> {
+        let monotonic_ms = monotonic.saturating_duration_since(self.monotonic).as_millis() as u64;
+        let wall_ms = match wall.duration_since(self.wall) {
+            Ok(forward) => forward.as_millis() as i64,
+            Err(back) => -(back.duration().as_millis() as i64),
+        };
+        self.monotonic = monotonic;
+        self.wall = wall;
+        let skew = wall_ms.saturating_sub(monotonic_ms as i64).unsigned_abs();
+        (u128::from(skew) > CLOCK_JUMP_THRESHOLD.as_millis()).then_some(ClockJump { wall_ms, monotonic_ms })
+    }
+}
+
+impl Default for ResumeDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_a_gap_between_the_clocks_is_a_jump() {
+        let (start, wall) = (Instant::now(), SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        let mut detector = ResumeDetector::starting_at(start, wall);
+        // A long stretch on both clocks is just time passing.
+        assert_eq!(detector.observe_at(start + Duration::from_secs(600), wall + Duration::from_secs(600)), None);
+
+        // Two hours asleep: the wall clock moved on, the monotonic clock did not.
+        let asleep = detector.observe_at(start + Duration::from_secs(601), wall + Duration::from_secs(601 + 7200));
+        assert_eq!(asleep, Some(ClockJump { wall_ms: 7_201_000, monotonic_ms: 1_000 }));
+
+        // The clock set back an hour.
+        let set_back = detector.observe_at(start + Duration::from_secs(602), wall + Duration::from_secs(602 + 7200 - 3600));
+        assert_eq!(set_back, Some(ClockJump { wall_ms: -3_599_000, monotonic_ms: 1_000 }));
+        assert_eq!(detector.observe_at(start + Duration::from_secs(603), wall + Duration::from_secs(603 + 3600)), None);
+    }
+}
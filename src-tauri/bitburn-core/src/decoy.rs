@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::WipeError;
+
+/// Largest decoy source kept in memory; anything bigger only repeats less often.
+pub const MAX_DECOY_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Innocuous-looking data for a final pass, so a wiped file or free space does not read as
+/// zeros or noise. The source is repeated end to end across the target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WipePattern {
+    /// The contents of a file, e.g. an ordinary document.
+    FromFile(PathBuf),
+    /// Text such as lorem ipsum, written as UTF-8.
+    FromText(String),
+}
+
+impl WipePattern {
+    /// Read the source once, checking it is there, not empty and at most `MAX_DECOY_BYTES`.
+    pub fn load(&self) -> Result<DecoyData, WipeError> {
+        let bytes = match self {
+            WipePattern::FromText(text) => text.as_bytes().to_vec(),
+            WipePattern::FromFile(path) => {
+                let metadata = fs::metadata(path)
+                    .map_err(|e| WipeError::InvalidDecoy(format!("cannot read {}: {}", path.display(), e)))?;
+                if !metadata.is_file() {
+                    return Err(WipeError::InvalidDecoy(format!("{} is not a file", path.display())));
+                }
+                if metadata.len() > MAX_DECOY_BYTES {
+                    return Err(WipeError::InvalidDecoy(format!(
+                        "{} is {} bytes; decoy files can be at most {} bytes",
+                        path.display(),
+                        metadata.len(),
+                        MAX_DECOY_BYTES
+                    )));
+                }
+                fs::read(path).map_err(|e| WipeError::InvalidDecoy(format!("cannot read {}: {}", path.display(), e)))?
+            }
+        };
+        if bytes.is_empty() {
+            return Err(WipeError::InvalidDecoy("the decoy source is empty".to_string()));
+        }
+        if bytes.len() as u64 > MAX_DECOY_BYTES {
+            return Err(WipeError::InvalidDecoy(format!("decoy text can be at most {} bytes", MAX_DECOY_BYTES)));
+        }
+        Ok(DecoyData { bytes })
+    }
+}
+
+/// A loaded decoy source, never empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecoyData {
+    bytes: Vec<u8>,
+}
+
+impl DecoyData {
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Fill `buf` with the bytes that belong `position` bytes into the target, continuing the
+    /// source from wherever the previous chunk left off.
+    pub fn fill(&self, position: u64, buf: &mut [u8]) {
+        let mut start = (position % self.bytes.len() as u64) as usize;
+        let mut written = 0;
+        while written < buf.len() {
+            let copy = (self.bytes.len() - start).min(buf.len() - written);
+            buf[written..written + copy].copy_from_slice(&self.bytes[start..start + copy]);
+            written += copy;
+            start = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_fill(source: &[u8], position: usize, len: usize) -> Vec<u8> {
+        (position..position + len).map(|i| source[i % source.len()]).collect()
+    }
+
+    #[test]
+    fn chunks_cycle_the_source_across_their_boundaries() {
+        let decoy = WipePattern::FromText("Lorem ipsum dolor sit amet. ".to_string()).load().unwrap();
+        let source = decoy.bytes().to_vec();
+        let mut written = Vec::new();
+        let mut position = 0;
+        for len in [10, 1, 28, 57, 3, 100] {
+            let mut chunk = vec![0u8; len];
+            decoy.fill(position as u64, &mut chunk);
+            written.extend_from_slice(&chunk);
+            position += len;
+        }
+        assert_eq!(written, naive_fill(&source, 0, position));
+
+        // A chunk far into a large target picks the source up at the right byte.
+        let mut chunk = vec![0u8; 40];
+        decoy.fill(5 * 1024 * 1024 + 3, &mut chunk);
+        assert_eq!(chunk, naive_fill(&source, 5 * 1024 * 1024 + 3, 40));
+    }
+
+    #[test]
+    fn decoy_sources_must_exist_hold_something_and_fit_in_memory() {
+        let dir = std::env::temp_dir().join(format!("BitBurn_decoy_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let empty = dir.join("empty.txt");
+        fs::write(&empty, b"").unwrap();
+        let huge = dir.join("huge.bin");
+        fs::File::create(&huge).unwrap().set_len(MAX_DECOY_BYTES + 1).unwrap();
+        let letter = dir.join("letter.txt");
+        fs::write(&letter, b"Dear Sir or Madam,").unwrap();
+
+        for pattern in [
+            WipePattern::FromText(String::new()),
+            WipePattern::FromFile(empty),
+            WipePattern::FromFile(huge),
+            WipePattern::FromFile(dir.join("missing.txt")),
+            WipePattern::FromFile(dir.clone()),
+        ] {
+            assert!(matches!(pattern.load(), Err(WipeError::InvalidDecoy(_))), "{:?}", pattern);
+        }
+        assert_eq!(WipePattern::FromFile(letter).load().unwrap().bytes(), b"Dear Sir or Madam,");
+        let _ = fs::remove_dir_all(dir);
+    }
+}
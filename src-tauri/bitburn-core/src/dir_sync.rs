@@ -0,0 +1,152 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Makes a directory's entries durable, behind a trait so tests can observe the calls.
+pub trait DirectorySync {
+    fn sync_dir(&self, dir: &Path) -> io::Result<()>;
+}
+
+/// `fsync` on Unix. ext4 and APFS can bring a deleted file's entry back after a crash until its
+/// directory is synced; elsewhere nothing is done.
+pub struct PlatformDirectorySync;
+
+impl DirectorySync for PlatformDirectorySync {
+    #[cfg(unix)]
+    fn sync_dir(&self, dir: &Path) -> io::Result<()> {
+        std::fs::File::open(dir)?.sync_all()
+    }
+
+    #[cfg(not(unix))]
+    fn sync_dir(&self, _dir: &Path) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Directories files were deleted from, each synced once by `finish` however many files went
+/// from it, so a batch of small files does not pay for an `fsync` per file.
+pub struct DeletedParents<S: DirectorySync = PlatformDirectorySync> {
+    sync: S,
+    dirs: Vec<PathBuf>,
+}
+
+impl DeletedParents {
+    pub fn new() -> Self {
+        DeletedParents::with_sync(PlatformDirectorySync)
+    }
+}
+
+impl Default for DeletedParents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: DirectorySync> DeletedParents<S> {
+    pub fn with_sync(sync: S) -> Self {
+        DeletedParents { sync, dirs: Vec::new() }
+    }
+
+    /// Note that `path`, a file or a removed folder, was deleted. Returns whether its parent is
+    /// noted for the first time.
+    pub fn deleted(&mut self, path: &Path) -> bool {
+        match path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            Some(parent) => self.note(parent),
+            None => false,
+        }
+    }
+
+    /// Note that everything inside `dir` was removed while `dir` itself stays. Returns whether
+    /// `dir` is noted for the first time.
+    pub fn emptied(&mut self, dir: &Path) -> bool {
+        self.note(dir)
+    }
+
+    fn note(&mut self, dir: &Path) -> bool {
+        if self.dirs.iter().any(|known| known == dir) {
+            return false;
+        }
+        self.dirs.push(dir.to_path_buf());
+        true
+    }
+
+    /// The sync the directories go through, for callers keeping more about the same directories.
+    pub fn sync(&self) -> &S {
+        &self.sync
+    }
+
+    /// Sync every noted directory that still exists, deepest first. A directory that cannot be
+    /// synced does not undo the deletions in it, so failures come back as warnings.
+    pub fn finish(self) -> Vec<String> {
+        let mut dirs = self.dirs;
+        dirs.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+        dirs.iter()
+            .filter(|dir| dir.is_dir())
+            .filter_map(|dir| {
+                let e = self.sync.sync_dir(dir).err()?;
+                Some(format!("Could not sync the directory entries of {}: {}", dir.display(), e))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::fs;
+
+    #[derive(Default)]
+    struct RecordingSync {
+        synced: RefCell<Vec<PathBuf>>,
+        fail: bool,
+    }
+
+    impl DirectorySync for &RecordingSync {
+        fn sync_dir(&self, dir: &Path) -> io::Result<()> {
+            self.synced.borrow_mut().push(dir.to_path_buf());
+            if self.fail {
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, "read-only"));
+            }
+            Ok(())
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("BitBurn_core_dir_sync_{}_{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn deletions_in_one_directory_sync_it_once() {
+        let dir = temp_dir("coalesce");
+        let recording = RecordingSync::default();
+        let mut parents = DeletedParents::with_sync(&recording);
+        let first: Vec<bool> = ["a.txt", "b.txt", "nested/c.txt", "d.txt", "nested/e.txt", "gone/f.txt"]
+            .iter()
+            .map(|name| parents.deleted(&dir.join(name)))
+            .collect();
+        assert_eq!(first, [true, false, true, false, false, true]);
+        assert!(!parents.emptied(&dir), "an emptied folder is the same as one deleted from");
+        assert!(parents.finish().is_empty());
+        // The missing directory is skipped; the deeper one goes first.
+        assert_eq!(recording.synced.take(), vec![dir.join("nested"), dir.clone()]);
+
+        let failing = RecordingSync { fail: true, ..Default::default() };
+        let mut parents = DeletedParents::with_sync(&failing);
+        parents.deleted(&dir.join("a.txt"));
+        let warnings = parents.finish();
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].contains(&dir.display().to_string()));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn the_platform_sync_fsyncs_a_real_directory() {
+        let dir = temp_dir("platform");
+        assert!(PlatformDirectorySync.sync_dir(&dir).is_ok());
+        assert!(PlatformDirectorySync.sync_dir(&dir.join("gone")).is_err());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+
+/// `ERROR_NOT_READY`, `ERROR_DEV_NOT_EXIST`, `ERROR_NO_SUCH_DEVICE`, `ERROR_DEVICE_NOT_CONNECTED`
+/// and `ERROR_DEVICE_REMOVED`.
+#[cfg(windows)]
+const DEVICE_GONE_OS_ERRORS: &[i32] = &[21, 55, 433, 1167, 1617];
+#[cfg(unix)]
+const DEVICE_GONE_OS_ERRORS: &[i32] = &[libc::ENODEV, libc::ENXIO];
+
+/// Whether an I/O error means the device behind the file is gone: a drive pulled out or a volume
+/// dismounted. Every later operation on that volume fails the same way.
+pub fn is_device_gone(raw_os_error: Option<i32>) -> bool {
+    #[cfg(any(windows, unix))]
+    {
+        raw_os_error.is_some_and(|code| DEVICE_GONE_OS_ERRORS.contains(&code))
+    }
+    #[cfg(not(any(windows, unix)))]
+    {
+        let _ = raw_os_error;
+        false
+    }
+}
+
+/// Errors that can occur while securely wiping files.
+#[derive(Debug)]
+pub enum WipeError {
+    PathNotFound,
+    Io(std::io::Error),
+    /// `passes` is not a pass count a wipe can run, i.e. zero.
+    InvalidPasses { passes: u32 },
+    SymbolicLink,
+    /// A symbolic link followed under `SymlinkPolicy::FollowTarget` points at another link.
+    LinkChain,
+    /// No write completed for `seconds`; the file was abandoned.
+    Timeout { seconds: u64 },
+    /// A byte range to wipe starts at or past the end of a `size`-byte file, or is empty.
+    InvalidRange { offset: u64, length: u64, size: u64 },
+    /// A decoy source that is missing, empty or too big.
+    InvalidDecoy(String),
+    /// The file kept growing while it was wiped, from `initial_size` to `size` bytes, more than
+    /// `ACTIVE_USE_GROWTH`; another program is still writing it.
+    InActiveUse { initial_size: u64, size: u64 },
+    /// Pass `pass` (1-based) read back differently from what was written, in the chunks starting
+    /// at `offsets`.
+    VerificationFailed { pass: u32, offsets: Vec<u64> },
+}
+
+impl fmt::Display for WipeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WipeError::PathNotFound => write!(f, "Path not found"),
+            WipeError::Io(err) => write!(f, "IO error: {}", err),
+            WipeError::InvalidPasses { passes } => write!(f, "Invalid number of passes: {}", passes),
+            WipeError::SymbolicLink => write!(f, "Cannot wipe symbolic links"),
+            WipeError::LinkChain => write!(f, "The link points at another link"),
+            WipeError::Timeout { seconds } => write!(f, "No write progress for {} seconds", seconds),
+            WipeError::InvalidRange { offset, length, size } => {
+                write!(f, "Range of {} bytes at offset {} is outside the {}-byte file", length, offset, size)
+            }
+            WipeError::InvalidDecoy(reason) => write!(f, "Invalid decoy data: {}", reason),
+            WipeError::InActiveUse { initial_size, size } => {
+                write!(f, "File grew from {} to {} bytes while it was wiped", initial_size, size)
+            }
+            WipeError::VerificationFailed { pass, offsets } => {
+                write!(f, "Pass {} did not read back as written in {} chunks", pass, offsets.len())
+            }
+        }
+    }
+}
+
+impl WipeError {
+    /// Whether the wipe failed because the device holding the file disappeared.
+    pub fn is_device_gone(&self) -> bool {
+        matches!(self, WipeError::Io(e) if is_device_gone(e.raw_os_error()))
+    }
+}
+
+impl std::error::Error for WipeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WipeError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+/// Open `path` with `options` so other processes are kept out of the file while the handle is held.
+/// On Windows the file is opened without sharing, so any other open fails with a sharing
+/// violation. Unix has no mandatory sharing modes, so an exclusive `flock` is taken instead; it
+/// is advisory, keeping out only programs that lock the file themselves, such as another wipe,
+/// while a plain `open` and `read` still succeed. A file already locked elsewhere fails with
+/// `ResourceBusy`. Either way a file another program holds is reported like one that is in use,
+/// not opened shared.
+#[cfg(windows)]
+pub fn open_exclusive(options: &mut OpenOptions, path: &Path) -> io::Result<File> {
+    use std::os::windows::fs::OpenOptionsExt;
+    options.share_mode(0).open(path)
+}
+
+#[cfg(unix)]
+pub fn open_exclusive(options: &mut OpenOptions, path: &Path) -> io::Result<File> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = options.open(path)?;
+    // Released when the last handle to the open file is closed.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(libc::EWOULDBLOCK) {
+            return Err(io::Error::new(io::ErrorKind::ResourceBusy, "The file is locked by another process"));
+        }
+        return Err(err);
+    }
+    Ok(file)
+}
+
+#[cfg(not(any(windows, unix)))]
+pub fn open_exclusive(options: &mut OpenOptions, path: &Path) -> io::Result<File> {
+    options.open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn scratch_file(name: &str) -> std::path::PathBuf {
+        let unique = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let path = std::env::temp_dir().join(format!("bitburn_exclusive_{}_{}", name, unique));
+        std::fs::write(&path, b"secret").unwrap();
+        path
+    }
+
+    /// Whether a program that takes no lock can open and read the file.
+    fn can_read_concurrently(path: &Path) -> bool {
+        let mut contents = Vec::new();
+        File::open(path).and_then(|mut reader| reader.read_to_end(&mut contents)).is_ok()
+    }
+
+    #[test]
+    fn a_second_exclusive_open_is_shut_out_until_the_handle_is_closed() {
+        let path = scratch_file("reader");
+        let held = open_exclusive(OpenOptions::new().read(true).write(true), &path).unwrap();
+        // `flock` is advisory: only programs that lock the file themselves are kept out.
+        assert_eq!(can_read_concurrently(&path), cfg!(not(windows)));
+
+        let second = open_exclusive(OpenOptions::new().write(true), &path).unwrap_err();
+        #[cfg(unix)]
+        assert_eq!(second.kind(), io::ErrorKind::ResourceBusy);
+        #[cfg(windows)]
+        assert_eq!(second.raw_os_error(), Some(32));
+
+        drop(held);
+        assert!(can_read_concurrently(&path));
+        let _ = std::fs::remove_file(path);
+    }
+}
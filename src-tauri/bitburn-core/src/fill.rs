@@ -0,0 +1,588 @@
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::cancel::CancellationToken;
+use crate::decoy::DecoyData;
+use crate::engine::BUFFER_SIZE;
+use crate::error::is_device_gone;
+use crate::progress::{ProgressSink, WipeProgress};
+use crate::target::WipeTarget;
+use crate::throughput::ThroughputMeter;
+
+/// `ERROR_DISK_QUOTA_EXCEEDED`.
+#[cfg(windows)]
+const QUOTA_EXCEEDED_OS_ERROR: i32 = 1295;
+#[cfg(unix)]
+const QUOTA_EXCEEDED_OS_ERROR: i32 = libc::EDQUOT;
+
+/// How often the volume's free space is re-read while filling.
+const FREE_SPACE_REFRESH: Duration = Duration::from_millis(100);
+
+/// Least drift between free space and what the fill alone explains before the estimate is
+/// revised; written data reaches the free-space figure late, and the OS reclaims space lazily.
+const ESTIMATE_DRIFT_FLOOR: u64 = 64 * 1024 * 1024;
+
+/// Whether a fill write failed because the user's disk quota is used up.
+pub fn is_quota_exceeded(kind: io::ErrorKind, raw_os_error: Option<i32>) -> bool {
+    #[cfg(any(windows, unix))]
+    if raw_os_error == Some(QUOTA_EXCEEDED_OS_ERROR) {
+        return true;
+    }
+    #[cfg(not(any(windows, unix)))]
+    let _ = raw_os_error;
+    kind == io::ErrorKind::QuotaExceeded
+}
+
+/// How a failed write during the free-space fill phase should be treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillWriteOutcome {
+    /// The volume is full: the fill phase is complete.
+    DiskFilled,
+    /// A genuine write failure that must be reported.
+    Failed,
+}
+
+/// Classify a fill-phase write error. exFAT and SMB volumes often report a full disk as
+/// `PermissionDenied` or `Other`, so any error counts as "disk filled" once the refreshed free
+/// space is below one chunk. Explicit out-of-space kinds are always treated as full.
+pub fn classify_fill_write_error(kind: io::ErrorKind, available_after: Option<u64>, chunk_size: u64) -> FillWriteOutcome {
+    let explicit_full = matches!(
+        kind,
+        io::ErrorKind::StorageFull | io::ErrorKind::OutOfMemory | io::ErrorKind::WriteZero
+    );
+    let nearly_full = available_after.is_some_and(|available| available < chunk_size);
+
+    if explicit_full || nearly_full {
+        FillWriteOutcome::DiskFilled
+    } else {
+        FillWriteOutcome::Failed
+    }
+}
+
+/// Why the fill loop stopped writing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillEnd {
+    /// A write failed in a way `classify_fill_write_error` counts as a full disk.
+    DiskFilled { error_kind: io::ErrorKind, available: Option<u64> },
+    /// The user's disk quota is used up, though the volume may have more free space.
+    QuotaReached,
+    /// A periodic flush failed; what was written so far is kept.
+    SyncFailed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillOutcome {
+    pub bytes_written: u64,
+    pub end: FillEnd,
+}
+
+#[derive(Debug)]
+pub enum FillError {
+    Cancelled,
+    /// A write failed while the volume still had room.
+    Write(io::Error),
+    /// The device went away; nothing more can be written or removed on the volume.
+    DeviceGone(io::Error),
+}
+
+impl fmt::Display for FillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FillError::Cancelled => write!(f, "Operation cancelled by user"),
+            FillError::Write(err) => write!(f, "IO error: {}", err),
+            FillError::DeviceGone(err) => write!(f, "Device disconnected: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for FillError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FillError::Write(err) | FillError::DeviceGone(err) => Some(err),
+            FillError::Cancelled => None,
+        }
+    }
+}
+
+/// A fill's expected size, revised because other programs used or freed space while it ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EstimateAdjustment {
+    pub previous_bytes: u64,
+    pub estimate_bytes: u64,
+}
+
+/// Progress through a fill. It is the bytes written, clamped to the space the fill was expected
+/// to use, so it only moves forward however the free-space readings lag or jump. The readings
+/// only revise the expected size, for the time remaining, once free space strays from what the
+/// fill explains by more than 1% of it, or `ESTIMATE_DRIFT_FLOOR`.
+#[derive(Debug, Clone)]
+pub struct FillProgressModel {
+    available_bytes: u64,
+    initial_free: u64,
+    estimate: u64,
+    written: u64,
+}
+
+impl FillProgressModel {
+    pub fn new(available_bytes: u64, initial_free: u64) -> Self {
+        FillProgressModel { available_bytes, initial_free, estimate: available_bytes, written: 0 }
+    }
+
+    /// Record that `written` bytes are down and return the progress position.
+    pub fn wrote(&mut self, written: u64) -> u64 {
+        self.written = self.written.max(written);
+        self.position()
+    }
+
+    pub fn position(&self) -> u64 {
+        self.written.min(self.available_bytes)
+    }
+
+    /// Bytes the fill expects to write.
+    pub fn estimate(&self) -> u64 {
+        self.estimate
+    }
+
+    /// Bytes the fill expects still to write.
+    pub fn remaining(&self) -> u64 {
+        self.estimate.saturating_sub(self.written)
+    }
+
+    /// Compare a free-space reading with what the bytes written so far explain, revising the
+    /// estimate when something else has used or freed space meanwhile.
+    pub fn observe_free_space(&mut self, free: u64) -> Option<EstimateAdjustment> {
+        let expected_free = self.initial_free.saturating_sub(self.written);
+        let revised = if free >= expected_free {
+            self.available_bytes.saturating_add(free - expected_free)
+        } else {
+            self.available_bytes.saturating_sub(expected_free - free)
+        }
+        .max(self.written);
+        let tolerance = (self.estimate / 100).max(ESTIMATE_DRIFT_FLOOR);
+        if revised.abs_diff(self.estimate) <= tolerance {
+            return None;
+        }
+        let adjustment = EstimateAdjustment { previous_bytes: self.estimate, estimate_bytes: revised };
+        self.estimate = revised;
+        Some(adjustment)
+    }
+
+    /// The position once the fill has stopped: the whole fill is done, however much it wrote.
+    pub fn finish(&mut self) -> u64 {
+        self.written = self.written.max(self.available_bytes);
+        self.estimate = self.written;
+        self.position()
+    }
+}
+
+/// Append random chunks to `target` until the volume is full, reporting through `progress`.
+/// `available_bytes` is the expected total, `initial_free` the volume's free space before the fill
+/// and `free_space` re-reads it; progress follows `FillProgressModel`. The target is
+/// left in place on every outcome, so the caller decides whether to overwrite or remove it.
+pub fn fill_free_space<T, F, S>(
+    target: &mut T,
+    available_bytes: u64,
+    initial_free: u64,
+    free_space: F,
+    progress: &mut WipeProgress,
+    sink: S,
+    cancel: &CancellationToken,
+) -> Result<FillOutcome, FillError>
+where
+    T: WipeTarget,
+    F: FnMut() -> Option<u64>,
+    S: ProgressSink,
+{
+    let plan = FillPlan { available_bytes, initial_free, decoy: None, buffer_size: None };
+    fill_free_space_with(target, plan, free_space, progress, sink, cancel)
+}
+
+/// The space a fill expects to use up, and what it writes there.
+#[derive(Debug, Clone, Copy)]
+pub struct FillPlan<'a> {
+    pub available_bytes: u64,
+    pub initial_free: u64,
+    /// Written instead of random data, so the filled space holds innocuous-looking bytes.
+    pub decoy: Option<&'a DecoyData>,
+    /// Bytes written at a time, rounded by `chunk_size`; `BUFFER_SIZE` when `None`.
+    pub buffer_size: Option<u64>,
+}
+
+/// `fill_free_space` following `plan`.
+pub fn fill_free_space_with<T, F, S>(
+    target: &mut T,
+    plan: FillPlan<'_>,
+    mut free_space: F,
+    progress: &mut WipeProgress,
+    mut sink: S,
+    cancel: &CancellationToken,
+) -> Result<FillOutcome, FillError>
+where
+    T: WipeTarget,
+    F: FnMut() -> Option<u64>,
+    S: ProgressSink,
+{
+    let FillPlan { available_bytes, initial_free, decoy, buffer_size } = plan;
+    let filling = if decoy.is_some() { "Filling drive space with decoy data" } else { "Filling drive space" };
+    let chunk_size = crate::memory::chunk_size(buffer_size);
+    let mut buffer = vec![0u8; chunk_size as usize];
+    let mut rng = rand::thread_rng();
+    let mut total_written = 0u64;
+    let mut last_refresh = Instant::now();
+    let mut model = FillProgressModel::new(available_bytes, initial_free);
+    // The passes after the fill, which the caller counts in `estimated_total_bytes`.
+    let later_phases = progress.estimated_total_bytes.unwrap_or(available_bytes).saturating_sub(available_bytes);
+    let mut throughput = ThroughputMeter::new();
+
+    loop {
+        if cancel.is_cancelled() {
+            let _ = target.sync();
+            return Err(FillError::Cancelled);
+        }
+
+        if last_refresh.elapsed() >= FREE_SPACE_REFRESH {
+            if let Some(current_free) = free_space() {
+                // Reported with the next chunk, then cleared.
+                progress.estimate_adjusted = model.observe_free_space(current_free);
+            }
+            last_refresh = Instant::now();
+        }
+
+        match decoy {
+            Some(decoy) => decoy.fill(total_written, &mut buffer),
+            None => rng.fill_bytes(&mut buffer),
+        }
+        match target.write_chunk(&buffer) {
+            Ok(_) => {
+                total_written += chunk_size;
+                let position = model.wrote(total_written);
+                progress.clock_jump = throughput.record(chunk_size);
+                progress.bytes_per_second = throughput.bytes_per_second();
+                // Counting any passes still to come after the fill.
+                let remaining = later_phases + model.remaining();
+                progress.estimated_seconds_remaining = throughput.estimated_seconds_remaining(remaining);
+                let pattern = match throughput.megabytes_per_second() {
+                    Some(rate) => format!("{} ({} MB written, {:.1} MB/s)", filling, total_written / 1024 / 1024, rate),
+                    None => format!("{} ({} MB written)", filling, total_written / 1024 / 1024),
+                };
+                progress.update(position, &pattern);
+                sink.report(progress.clone());
+                progress.estimate_adjusted = None;
+                progress.clock_jump = None;
+
+                if total_written.is_multiple_of(10 * BUFFER_SIZE) {
+                    match target.sync() {
+                        Ok(()) => {}
+                        Err(e) if is_device_gone(e.raw_os_error()) => return Err(FillError::DeviceGone(e)),
+                        Err(_) => return Ok(FillOutcome { bytes_written: total_written, end: FillEnd::SyncFailed }),
+                    }
+                }
+            }
+            // Checked first: a vanished volume's free space reads as zero, which looks like a full disk.
+            Err(e) if is_device_gone(e.raw_os_error()) => return Err(FillError::DeviceGone(e)),
+            Err(e) if is_quota_exceeded(e.kind(), e.raw_os_error()) => {
+                // The quota is used up: everything this user may write has been filled.
+                progress.estimated_seconds_remaining = Some(0);
+                progress.update(model.finish(), "Disk quota reached");
+                sink.report(progress.clone());
+                return Ok(FillOutcome { bytes_written: total_written, end: FillEnd::QuotaReached });
+            }
+            Err(e) => {
+                let current_available = free_space();
+                if classify_fill_write_error(e.kind(), current_available, chunk_size) == FillWriteOutcome::Failed {
+                    return Err(FillError::Write(e));
+                }
+                if current_available.is_some() {
+                    progress.estimated_seconds_remaining = Some(0);
+                    progress.update(model.finish(), "Drive space filled");
+                    sink.report(progress.clone());
+                }
+                return Ok(FillOutcome {
+                    bytes_written: total_written,
+                    end: FillEnd::DiskFilled { error_kind: e.kind(), available: current_available },
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::target::MemoryTarget;
+
+    const CHUNK: u64 = 1024 * 1024;
+
+    #[test]
+    fn fill_errors_with_low_free_space_count_as_disk_filled() {
+        for kind in [
+            io::ErrorKind::StorageFull,
+            io::ErrorKind::WriteZero,
+            io::ErrorKind::OutOfMemory,
+            io::ErrorKind::PermissionDenied,
+            io::ErrorKind::Other,
+            io::ErrorKind::InvalidInput,
+        ] {
+            assert_eq!(
+                classify_fill_write_error(kind, Some(CHUNK - 1), CHUNK),
+                FillWriteOutcome::DiskFilled,
+                "{:?} with low free space",
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn fill_errors_with_high_free_space_are_failures_unless_explicitly_full() {
+        let high = Some(50 * CHUNK);
+        for kind in [io::ErrorKind::StorageFull, io::ErrorKind::WriteZero, io::ErrorKind::OutOfMemory] {
+            assert_eq!(classify_fill_write_error(kind, high, CHUNK), FillWriteOutcome::DiskFilled, "{:?}", kind);
+        }
+        for kind in [io::ErrorKind::PermissionDenied, io::ErrorKind::Other, io::ErrorKind::InvalidInput] {
+            assert_eq!(classify_fill_write_error(kind, high, CHUNK), FillWriteOutcome::Failed, "{:?}", kind);
+            assert_eq!(classify_fill_write_error(kind, None, CHUNK), FillWriteOutcome::Failed, "{:?} unknown space", kind);
+        }
+        // Blocks reserved for root stop everyone else with ENOSPC while the volume still reports them free.
+        #[cfg(unix)]
+        {
+            let enospc = io::Error::from_raw_os_error(libc::ENOSPC);
+            assert_eq!(classify_fill_write_error(enospc.kind(), high, CHUNK), FillWriteOutcome::DiskFilled);
+            assert!(!is_quota_exceeded(enospc.kind(), enospc.raw_os_error()));
+        }
+    }
+
+    #[test]
+    fn quota_errors_are_recognised_by_os_code_and_kind() {
+        #[cfg(windows)]
+        assert!(is_quota_exceeded(io::ErrorKind::Other, Some(1295)));
+        #[cfg(unix)]
+        assert!(is_quota_exceeded(io::ErrorKind::Other, Some(libc::EDQUOT)));
+        assert!(is_quota_exceeded(io::ErrorKind::QuotaExceeded, None));
+
+        assert!(!is_quota_exceeded(io::ErrorKind::StorageFull, None));
+        assert!(!is_quota_exceeded(io::ErrorKind::PermissionDenied, Some(5)));
+    }
+
+    #[test]
+    fn device_gone_errors_are_recognised_by_os_code() {
+        #[cfg(windows)]
+        for code in [21, 1167, 1617] {
+            assert!(is_device_gone(Some(code)), "{}", code);
+        }
+        #[cfg(unix)]
+        for code in [libc::ENODEV, libc::ENXIO] {
+            assert!(is_device_gone(Some(code)), "{}", code);
+        }
+        assert!(!is_device_gone(None));
+        assert!(!is_device_gone(Some(5)));
+        assert!(!is_device_gone(io::Error::new(io::ErrorKind::StorageFull, "full").raw_os_error()));
+    }
+
+    #[cfg(any(windows, unix))]
+    #[test]
+    fn fill_stops_at_once_when_the_device_is_pulled() {
+        #[cfg(windows)]
+        let code = 1167;
+        #[cfg(unix)]
+        let code = libc::ENODEV;
+        let mut target = MemoryTarget::new(Vec::new()).with_write_error(2 * CHUNK, code);
+        let mut progress = WipeProgress::new(1, 0, "Random");
+        // The volume reads as full once it is gone; that must not pass for a completed fill.
+        let result = fill_free_space(
+            &mut target,
+            u64::MAX,
+            u64::MAX,
+            || Some(0),
+            &mut progress,
+            |_: WipeProgress| {},
+            &CancellationToken::new(),
+        );
+
+        match result {
+            Err(FillError::DeviceGone(e)) => assert_eq!(e.raw_os_error(), Some(code)),
+            other => panic!("expected DeviceGone, got {:?}", other),
+        }
+        assert_eq!(target.data().len() as u64, 2 * CHUNK);
+    }
+
+    #[test]
+    fn fill_stops_when_the_target_is_full() {
+        // The memory target refuses writes with `WriteZero` after three chunks, like a full disk.
+        let mut target = MemoryTarget::new(Vec::new()).with_write_limit(3 * CHUNK);
+        let mut progress = WipeProgress::new(1, 0, "Random");
+        progress.estimated_total_bytes = Some(3 * CHUNK);
+        let mut reports = 0;
+        let outcome = fill_free_space(
+            &mut target,
+            3 * CHUNK,
+            3 * CHUNK,
+            || Some(0),
+            &mut progress,
+            |_: WipeProgress| reports += 1,
+            &CancellationToken::new(),
+        )
+        .expect("a full disk ends the fill");
+
+        assert_eq!(outcome.bytes_written, 3 * CHUNK);
+        assert!(matches!(outcome.end, FillEnd::DiskFilled { error_kind: io::ErrorKind::WriteZero, available: Some(0) }));
+        assert_eq!(target.data().len() as u64, 3 * CHUNK);
+        assert_eq!(reports, 4);
+        assert_eq!(progress.current_pattern, "Drive space filled");
+    }
+
+    #[test]
+    fn fill_progress_follows_the_bytes_written_whatever_free_space_reads() {
+        const MB: u64 = 1024 * 1024;
+        let mut model = FillProgressModel::new(1024 * MB, 1024 * MB);
+        // Free space lags the writes, jumps back up as the OS reclaims temp space, then catches up.
+        let readings = [
+            (100 * MB, 960 * MB),
+            (200 * MB, 870 * MB),
+            (300 * MB, 760 * MB),
+            (400 * MB, 680 * MB),
+            (900 * MB, 150 * MB),
+            (1000 * MB, 30 * MB),
+            (1060 * MB, 0),
+        ];
+        let mut positions = Vec::new();
+        for (written, free) in readings {
+            positions.push(model.wrote(written));
+            assert_eq!(model.observe_free_space(free), None, "drift at {} MB is only lag", written / MB);
+        }
+        assert!(positions.windows(2).all(|w| w[0] <= w[1]), "{:?}", positions);
+        assert_eq!(positions[..5], [100 * MB, 200 * MB, 300 * MB, 400 * MB, 900 * MB]);
+        assert_eq!(*positions.last().unwrap(), 1024 * MB, "clamped to the initial estimate");
+        assert_eq!(model.finish(), 1024 * MB);
+    }
+
+    #[test]
+    fn space_used_or_freed_by_others_revises_the_estimate_but_not_the_progress() {
+        const GB: u64 = 1024 * 1024 * 1024;
+        let mut model = FillProgressModel::new(10 * GB, 10 * GB);
+        assert_eq!(model.wrote(2 * GB), 2 * GB);
+        // Another program wrote 3 GB: the fill will only get to write 7.
+        assert_eq!(
+            model.observe_free_space(5 * GB),
+            Some(EstimateAdjustment { previous_bytes: 10 * GB, estimate_bytes: 7 * GB })
+        );
+        assert_eq!(model.remaining(), 5 * GB);
+        assert_eq!(model.observe_free_space(5 * GB), None, "reported once");
+        assert_eq!(model.wrote(3 * GB), 3 * GB);
+
+        // It deleted 5 GB, 2 more than it had taken.
+        assert_eq!(
+            model.observe_free_space(9 * GB),
+            Some(EstimateAdjustment { previous_bytes: 7 * GB, estimate_bytes: 12 * GB })
+        );
+        assert_eq!(model.estimate(), 12 * GB);
+        assert_eq!(model.wrote(11 * GB), 10 * GB, "progress stops at the initial estimate");
+
+        // Readings far below what was written never shrink the estimate under it.
+        assert_eq!(model.observe_free_space(0).map(|a| a.estimate_bytes), Some(11 * GB));
+        assert_eq!(model.remaining(), 0);
+    }
+
+    #[test]
+    fn a_decoy_fill_repeats_the_source_across_chunks() {
+        // Seven bytes never divide a chunk, so every chunk starts part-way through the source.
+        let decoy = crate::WipePattern::FromText("decoy. ".to_string()).load().unwrap();
+        let mut target = MemoryTarget::new(Vec::new()).with_write_limit(3 * CHUNK);
+        let mut progress = WipeProgress::new(1, 0, "Random");
+        let mut patterns = Vec::new();
+        let plan = FillPlan { available_bytes: 3 * CHUNK, initial_free: 3 * CHUNK, decoy: Some(&decoy), buffer_size: None };
+        fill_free_space_with(
+            &mut target,
+            plan,
+            || Some(0),
+            &mut progress,
+            |p: WipeProgress| patterns.push(p.current_pattern),
+            &CancellationToken::new(),
+        )
+        .expect("a full disk ends the fill");
+
+        let expected: Vec<u8> = b"decoy. ".iter().copied().cycle().take(3 * CHUNK as usize).collect();
+        assert_eq!(target.data(), &expected[..]);
+        assert!(patterns[0].starts_with("Filling drive space with decoy data"), "{:?}", patterns);
+    }
+
+    #[test]
+    fn fill_and_overwrite_passes_share_one_percentage() {
+        use crate::engine::overwrite_target;
+        use crate::progress::FileWipeProgressRecord;
+        use crate::WipeAlgorithm;
+        use rand::SeedableRng;
+
+        // Fill three chunks, then two random passes over them: three phases of equal size.
+        let passes = 2u64;
+        let mut target = MemoryTarget::new(Vec::new()).with_write_limit(3 * CHUNK);
+        let mut progress = WipeProgress::new(passes as u32 + 1, 0, "Random");
+        progress.estimated_total_bytes = Some(3 * CHUNK * (passes + 1));
+        let mut written = 0;
+        let mut percentages = Vec::new();
+        let fill = fill_free_space(
+            &mut target,
+            3 * CHUNK,
+            3 * CHUNK,
+            || {
+                written += CHUNK;
+                Some((3 * CHUNK).saturating_sub(written))
+            },
+            &mut progress,
+            |p: WipeProgress| percentages.push((p.current_pass, p.percentage)),
+            &CancellationToken::new(),
+        )
+        .expect("the fill ends at a full disk");
+        let filled = fill.bytes_written;
+        assert!(percentages.iter().all(|&(_, pct)| pct <= 34.0), "the fill is a third of the run: {:?}", percentages);
+
+        progress.total_bytes = filled;
+        progress.estimated_total_bytes = Some(filled * (passes + 1));
+        let mut overall = progress.clone();
+        overwrite_target(
+            &mut MemoryTarget::new(vec![0; filled as usize]),
+            passes as u32,
+            &WipeAlgorithm::Random,
+            &mut rand::rngs::StdRng::seed_from_u64(7),
+            &CancellationToken::new(),
+            |p: WipeProgress| {
+                overall.follow_pass(filled, &p);
+                percentages.push((overall.current_pass, overall.percentage));
+            },
+            &mut FileWipeProgressRecord::default(),
+        )
+        .expect("overwrite should succeed");
+
+        let start_of = |pass: u32| percentages.iter().find(|&&(p, _)| p == pass).map(|&(_, pct)| pct).unwrap();
+        assert!((start_of(2) - 100.0 / 3.0).abs() < 0.1, "pass 1 starts at a third: {}", start_of(2));
+        assert!((start_of(3) - 200.0 / 3.0).abs() < 0.1, "pass 2 starts at two thirds: {}", start_of(3));
+        assert!(percentages.windows(2).all(|w| w[0].1 <= w[1].1 + f32::EPSILON), "never goes backwards");
+        assert_eq!(percentages.last().unwrap(), &(3, 100.0));
+        assert_eq!(overall.total_passes, 3);
+    }
+
+    #[test]
+    fn cancelled_fill_returns_without_writing_more() {
+        let mut target = MemoryTarget::new(Vec::new());
+        let mut progress = WipeProgress::new(1, 0, "Random");
+        let cancel = CancellationToken::new();
+        let stopper = cancel.clone();
+        let result = fill_free_space(
+            &mut target,
+            u64::MAX,
+            u64::MAX,
+            || None,
+            &mut progress,
+            |progress: WipeProgress| {
+                if progress.current_pattern.starts_with("Filling drive space (2 MB") {
+                    stopper.cancel();
+                }
+            },
+            &cancel,
+        );
+        assert!(matches!(result, Err(FillError::Cancelled)));
+        assert_eq!(target.data().len() as u64, 2 * CHUNK);
+    }
+}
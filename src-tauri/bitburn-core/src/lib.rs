@@ -0,0 +1,63 @@
+//! BitBurn's wipe engine without the desktop app: the overwrite algorithms, the folder walker and
+//! the free-space filler. Progress goes to a `ProgressSink` and every long-running call stops
+//! between chunks once its `CancellationToken` is cancelled.
+//!
+//! ```no_run
+//! use bitburn_core::{secure_wipe_file, CancellationToken, WipeAlgorithm, WipeProgress};
+//! use std::path::Path;
+//!
+//! let cancel = CancellationToken::new();
+//! secure_wipe_file(Path::new("secret.txt"), 3, &WipeAlgorithm::NistPurge, &cancel, |progress: WipeProgress| {
+//!     println!("{:.0}% {}", progress.percentage, progress.current_pattern);
+//! })
+//! .expect("wipe failed");
+//! ```
+
+mod algorithm;
+mod cancel;
+mod checkpoint;
+mod clock;
+mod decoy;
+mod dir_sync;
+mod engine;
+mod error;
+mod exclusive;
+mod fill;
+mod memory;
+mod pattern_fill;
+mod progress;
+mod symlink;
+mod target;
+mod throughput;
+mod tree_removal;
+mod tree_scan;
+mod verify;
+mod walker;
+
+pub use algorithm::{PassKind, WipeAlgorithm};
+pub use cancel::CancellationToken;
+pub use checkpoint::{Checkpointing, PassCheckpoint};
+pub use clock::{ClockJump, ResumeDetector, CLOCK_JUMP_THRESHOLD};
+pub use decoy::{DecoyData, WipePattern, MAX_DECOY_BYTES};
+pub use dir_sync::{DeletedParents, DirectorySync, PlatformDirectorySync};
+pub use engine::{
+    cancelled_wipe_error, finish_wipe, open_wipe_target, open_wipe_target_with, overwrite_target, overwrite_target_with,
+    secure_wipe_file, secure_wipe_file_batched, secure_wipe_file_recorded, simulate_wipe, wipe_file_range,
+    OverwriteOptions, ACTIVE_USE_GROWTH, BUFFER_SIZE,
+};
+pub use error::{is_device_gone, WipeError};
+pub use exclusive::open_exclusive;
+pub use fill::{
+    classify_fill_write_error, fill_free_space, fill_free_space_with, is_quota_exceeded, EstimateAdjustment, FillEnd,
+    FillError, FillOutcome, FillPlan, FillProgressModel, FillWriteOutcome,
+};
+pub use memory::{chunk_size, plan_buffer, BufferPlan, COMFORTABLE_MB_PER_JOB, MIN_BUFFER_SIZE};
+pub use pattern_fill::PatternBuffer;
+pub use progress::{FileWipeProgressRecord, PassTiming, PassVerification, ProgressSink, WipeProgress};
+pub use symlink::{delete_link, is_symlink, link_target, SymlinkPolicy};
+pub use target::{MemoryTarget, PassRecord, RangeTarget, WipeTarget};
+pub use throughput::ThroughputMeter;
+pub use tree_removal::{empty_tree, remove_tree, RemovalCancelled, TreeRemoval, REMOVAL_BATCH};
+pub use tree_scan::{ScannedDirectory, TreeChunks, TreeCount, TreeScan, WalkPosition, WalkTracker, DEFAULT_CHUNK_FILES};
+pub use verify::{sampled_chunks, Verification, VerifyMode};
+pub use walker::{wipe_directory_files, DirectoryError, DirectoryOutcome, MAX_DIRECTORY_RESCANS};
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+use crate::engine::BUFFER_SIZE;
+
+/// The smallest buffer a wipe drops to when memory is short.
+pub const MIN_BUFFER_SIZE: u64 = 64 * 1024;
+
+/// Available memory per running wipe at or above which each keeps the buffer it asked for.
+pub const COMFORTABLE_MB_PER_JOB: u64 = 512;
+
+/// How big a buffer a wipe writes through, given the memory left for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BufferPlan {
+    pub buffer_size: u64,
+    /// The buffer is smaller than the one asked for because available memory is short.
+    pub constrained: bool,
+}
+
+/// The buffer each of `concurrency` wipes gets with `available_mb` of memory free. Below
+/// `COMFORTABLE_MB_PER_JOB` per wipe the requested buffer shrinks in proportion, down to
+/// `MIN_BUFFER_SIZE`, so several wipes on a low-memory machine do not push it into swapping.
+pub fn plan_buffer(available_mb: u64, requested_buffer: u64, concurrency: usize) -> BufferPlan {
+    let requested = chunk_size(Some(requested_buffer));
+    let per_job_mb = available_mb / concurrency.max(1) as u64;
+    if per_job_mb >= COMFORTABLE_MB_PER_JOB {
+        return BufferPlan { buffer_size: requested, constrained: false };
+    }
+    let scaled = (u128::from(requested) * u128::from(per_job_mb) / u128::from(COMFORTABLE_MB_PER_JOB)) as u64;
+    let buffer_size = chunk_size(Some(scaled)).min(requested);
+    BufferPlan { buffer_size, constrained: buffer_size < requested }
+}
+
+/// The chunk a wipe writes at a time for a requested buffer size: `BUFFER_SIZE` when none was
+/// asked for, otherwise rounded down to a power of two between `MIN_BUFFER_SIZE` and
+/// `BUFFER_SIZE`. A chunk then always divides `BUFFER_SIZE`, so the chunk boundaries still fall
+/// on every offset a `PassCheckpoint` may hold.
+pub fn chunk_size(requested: Option<u64>) -> u64 {
+    match requested {
+        None => BUFFER_SIZE,
+        Some(requested) => {
+            let clamped = requested.clamp(MIN_BUFFER_SIZE, BUFFER_SIZE);
+            1 << (63 - clamped.leading_zeros())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KB: u64 = 1024;
+
+    #[test]
+    fn the_buffer_shrinks_with_the_memory_left_for_each_wipe() {
+        // (available MB, requested buffer, concurrent wipes) -> (buffer, constrained)
+        let matrix = [
+            (16_384, BUFFER_SIZE, 1, BUFFER_SIZE, false),
+            (16_384, BUFFER_SIZE, 8, BUFFER_SIZE, false),
+            (1_024, BUFFER_SIZE, 2, BUFFER_SIZE, false),
+            (1_024, BUFFER_SIZE, 3, 512 * KB, true),
+            (700, BUFFER_SIZE, 1, BUFFER_SIZE, false),
+            (700, BUFFER_SIZE, 2, 512 * KB, true),
+            (300, BUFFER_SIZE, 1, 512 * KB, true),
+            (200, BUFFER_SIZE, 4, 64 * KB, true),
+            (100, BUFFER_SIZE, 1, 128 * KB, true),
+            (0, BUFFER_SIZE, 1, MIN_BUFFER_SIZE, true),
+            (0, BUFFER_SIZE, 0, MIN_BUFFER_SIZE, true),
+            // A smaller request is kept while memory allows it, and never grows.
+            (16_384, 256 * KB, 2, 256 * KB, false),
+            (300, 256 * KB, 1, 128 * KB, true),
+            // Already at the floor: nothing left to give up.
+            (0, MIN_BUFFER_SIZE, 4, MIN_BUFFER_SIZE, false),
+        ];
+        for (available_mb, requested, concurrency, buffer_size, constrained) in matrix {
+            assert_eq!(
+                plan_buffer(available_mb, requested, concurrency),
+                BufferPlan { buffer_size, constrained },
+                "{} MB free, {} byte buffer, {} wipes",
+                available_mb,
+                requested,
+                concurrency
+            );
+        }
+    }
+
+    #[test]
+    fn chunks_always_divide_the_full_buffer() {
+        assert_eq!(chunk_size(None), BUFFER_SIZE);
+        assert_eq!(chunk_size(Some(0)), MIN_BUFFER_SIZE);
+        assert_eq!(chunk_size(Some(100 * KB)), 64 * KB);
+        assert_eq!(chunk_size(Some(8 * BUFFER_SIZE)), BUFFER_SIZE);
+        for requested in (0..=BUFFER_SIZE).step_by(4099) {
+            let chunk = chunk_size(Some(requested));
+            assert!((MIN_BUFFER_SIZE..=BUFFER_SIZE).contains(&chunk));
+            assert!(BUFFER_SIZE.is_multiple_of(chunk), "{}", chunk);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+/// A fixed byte pattern repeated across a whole pass, precomputed once and handed out chunk by chunk.
+/// The rotation offset carries over between chunks, so the bytes written match a single repeating
+/// sequence from the start of the target even when the pattern length does not divide the chunk size.
+#[derive(Debug, Clone)]
+pub struct PatternBuffer {
+    /// `chunk_capacity + period - 1` bytes of the repeating pattern, so any rotation has a contiguous window.
+    buffer: Vec<u8>,
+    period: usize,
+    offset: usize,
+    chunk_capacity: usize,
+}
+
+impl PatternBuffer {
+    /// Precompute `pattern` for chunks of at most `chunk_capacity` bytes. An empty pattern writes zeros.
+    pub fn new(pattern: &[u8], chunk_capacity: usize) -> Self {
+        let pattern = if pattern.is_empty() { &[0u8][..] } else { pattern };
+        let period = pattern.len();
+        let len = chunk_capacity + period - 1;
+
+        let mut buffer = Vec::with_capacity(len);
+        buffer.extend_from_slice(&pattern[..period.min(len)]);
+        // Doubling copies keep the fill to a handful of memcpy calls instead of one store per byte.
+        while buffer.len() < len {
+            let copy = buffer.len().min(len - buffer.len());
+            buffer.extend_from_within(..copy);
+        }
+
+        PatternBuffer {
+            buffer,
+            period,
+            offset: 0,
+            chunk_capacity,
+        }
+    }
+
+    /// Continue the sequence from `position` bytes into the target, for a pass resumed part-way.
+    pub fn starting_at(mut self, position: u64) -> Self {
+        self.offset = (position % self.period as u64) as usize;
+        self
+    }
+
+    /// The next `len` bytes of the sequence. `len` must not exceed the capacity given to `new`.
+    pub fn next_chunk(&mut self, len: usize) -> &[u8] {
+        assert!(len <= self.chunk_capacity, "chunk of {} bytes exceeds capacity {}", len, self.chunk_capacity);
+        let start = self.offset;
+        self.offset = (self.offset + len) % self.period;
+        &self.buffer[start..start + len]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn naive_fill(pattern: &[u8], len: usize) -> Vec<u8> {
+        (0..len).map(|i| pattern[i % pattern.len()]).collect()
+    }
+
+    #[test]
+    fn consecutive_chunks_continue_the_pattern() {
+        let pattern = [0x92, 0x49, 0x24];
+        let chunk = 1024 * 1024;
+        let mut fill = PatternBuffer::new(&pattern, chunk);
+
+        let mut written = fill.next_chunk(chunk).to_vec();
+        written.extend_from_slice(fill.next_chunk(chunk));
+        written.extend_from_slice(fill.next_chunk(7));
+
+        assert_eq!(written, naive_fill(&pattern, 2 * chunk + 7));
+        // 1 MiB is not a multiple of 3, so the second chunk must not restart at 0x92.
+        assert_eq!(&written[chunk..chunk + 3], &[0x49, 0x24, 0x92]);
+    }
+
+    #[test]
+    fn odd_chunk_sizes_and_short_patterns_match_naive_fill() {
+        for pattern in [&[0xAAu8][..], &[0x55, 0xAA, 0x55, 0xAA], &[0x6D, 0xB6, 0xDB], &[]] {
+            let mut fill = PatternBuffer::new(pattern, 10);
+            let mut written = Vec::new();
+            for len in [10, 1, 7, 10, 3] {
+                written.extend_from_slice(fill.next_chunk(len));
+            }
+            let expected = if pattern.is_empty() { vec![0; 31] } else { naive_fill(pattern, 31) };
+            assert_eq!(written, expected);
+        }
+    }
+
+    #[test]
+    fn precomputed_fill_is_much_cheaper_than_per_byte_fill() {
+        let pattern = [0x92, 0x49, 0x24];
+        let chunk = 1024 * 1024;
+        let chunks = 32;
+
+        let naive_started = Instant::now();
+        let mut buffer = vec![0u8; chunk];
+        let mut checksum = 0u64;
+        for _ in 0..chunks {
+            for (i, byte) in buffer.iter_mut().enumerate() {
+                *byte = pattern[i % pattern.len()];
+            }
+            checksum += buffer[chunk - 1] as u64;
+        }
+        let naive = naive_started.elapsed();
+
+        let precomputed_started = Instant::now();
+        let mut fill = PatternBuffer::new(&pattern, chunk);
+        for _ in 0..chunks {
+            checksum += fill.next_chunk(chunk)[chunk - 1] as u64;
+        }
+        let precomputed = precomputed_started.elapsed();
+
+        assert!(checksum > 0);
+        assert!(
+            precomputed * 4 < naive,
+            "precomputed fill took {:?}, per-byte fill {:?}",
+            precomputed,
+            naive
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+
+use crate::clock::ClockJump;
+use crate::fill::EstimateAdjustment;
+use crate::tree_scan::WalkPosition;
+
+/// Progress payload emitted to the UI during wipe operations.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WipeProgress {
+    pub current_pass: u32,
+    pub total_passes: u32,
+    pub bytes_processed: u64,
+    pub total_bytes: u64,
+    pub current_algorithm: String,
+    pub current_pattern: String,
+    pub percentage: f32,
+    pub estimated_total_bytes: Option<u64>,
+    /// Bytes written by phases already finished, e.g. a free-space fill and the overwrite passes
+    /// after it; `percentage` counts them along with `bytes_processed`.
+    #[serde(default)]
+    pub completed_phase_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes_per_second: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_seconds_remaining: Option<u64>,
+    /// Set on the one report after a free-space fill revised how much it expects to write.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate_adjusted: Option<EstimateAdjustment>,
+    /// Set on the one report after the clocks showed the system slept, or its clock was changed;
+    /// the rate behind `estimated_seconds_remaining` was measured afresh from then on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock_jump: Option<ClockJump>,
+    /// Which of the selected paths a file batch is working on; absent outside file batches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_index: Option<usize>,
+    /// Where a folder wipe is within the selected tree; absent for single files and free space.
+    #[serde(flatten, default)]
+    pub walk: Option<WalkPosition>,
+}
+
+impl WipeProgress {
+    pub fn new(total_passes: u32, total_bytes: u64, current_algorithm: &str) -> Self {
+        WipeProgress {
+            current_pass: 1,
+            total_passes,
+            bytes_processed: 0,
+            total_bytes,
+            current_algorithm: current_algorithm.to_string(),
+            current_pattern: String::new(),
+            percentage: 0.0,
+            estimated_total_bytes: None,
+            completed_phase_bytes: 0,
+            bytes_per_second: None,
+            estimated_seconds_remaining: None,
+            estimate_adjusted: None,
+            clock_jump: None,
+            root_index: None,
+            walk: None,
+        }
+    }
+
+    pub fn update(&mut self, bytes_processed: u64, pattern: &str) {
+        self.bytes_processed = bytes_processed;
+        self.current_pattern = pattern.to_string();
+        let written = (self.completed_phase_bytes + bytes_processed) as f32;
+        if let Some(est_total) = self.estimated_total_bytes {
+            self.percentage = (written / est_total as f32) * 100.0;
+        } else {
+            self.percentage = (written / self.total_bytes as f32) * 100.0;
+        }
+    }
+
+    /// Show `pass`, the progress of one overwrite pass over a `phase_bytes`-byte file, as part of
+    /// this run, which wrote `phase_bytes` in a phase of its own before the passes started.
+    pub fn follow_pass(&mut self, phase_bytes: u64, pass: &WipeProgress) {
+        self.current_pass = pass.current_pass + 1;
+        self.completed_phase_bytes = phase_bytes.saturating_mul(u64::from(pass.current_pass));
+        self.update(pass.bytes_processed.min(phase_bytes), &pass.current_pattern);
+    }
+}
+
+/// How far the engine got with one file: unlike `WipeProgress`, which is sampled for display,
+/// this counts every pass synced and every byte written, so it is exact wherever the wipe stopped.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct FileWipeProgressRecord {
+    pub total_passes: u32,
+    /// Passes written in full and synced.
+    pub passes_completed: u32,
+    /// What the last pass started wrote; `None` before the first pass.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_pass_pattern: Option<String>,
+    /// Bytes of `last_pass_pattern` written; the whole file once that pass completed.
+    pub bytes_of_last_pass: u64,
+    /// How long each pass this run completed took; passes an earlier run wrote are not listed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pass_timings: Vec<PassTiming>,
+    /// Whether the file's size differed from the one it was opened with at a pass boundary.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub size_changed_during_wipe: bool,
+    /// Largest size a completed pass covered; above the opened size when the file grew.
+    #[serde(default)]
+    pub max_bytes_overwritten: u64,
+    /// Chunks read back and found as written, for each pass that was verified.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verified_chunks: Vec<PassVerification>,
+    /// Seed this file's sampled chunks followed, when it resumed a checkpoint whose run drew a
+    /// different one from the report's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_seed: Option<u64>,
+    /// Problems that did not fail the wipe, e.g. a directory that could not be synced once the
+    /// file was deleted from it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Read-back of one completed pass.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PassVerification {
+    /// 1-based, as in `WipeProgress::current_pass`.
+    pub pass: u32,
+    pub chunks: u64,
+}
+
+/// Time and bytes of one completed pass.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PassTiming {
+    /// 1-based, as in `WipeProgress::current_pass`.
+    pub pass: u32,
+    pub millis: u64,
+    /// Written in this run; less than the file when the pass resumed part-way.
+    pub bytes: u64,
+}
+
+impl FileWipeProgressRecord {
+    pub fn new(total_passes: u32) -> Self {
+        FileWipeProgressRecord {
+            total_passes,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn start_pass(&mut self, pattern: &str) {
+        self.last_pass_pattern = Some(pattern.to_string());
+        self.bytes_of_last_pass = 0;
+    }
+
+    pub(crate) fn wrote(&mut self, bytes: u64) {
+        self.bytes_of_last_pass += bytes;
+    }
+
+    pub(crate) fn pass_completed(&mut self) {
+        self.passes_completed += 1;
+    }
+
+    pub(crate) fn pass_timed(&mut self, elapsed: std::time::Duration, bytes: u64) {
+        self.pass_timings.push(PassTiming { pass: self.passes_completed, millis: elapsed.as_millis() as u64, bytes });
+    }
+
+    pub(crate) fn pass_verified(&mut self, chunks: u64) {
+        self.verified_chunks.push(PassVerification { pass: self.passes_completed, chunks });
+    }
+
+    /// Average write rate over the timed passes; `None` until one completed.
+    pub fn bytes_per_second(&self) -> Option<u64> {
+        let bytes: u64 = self.pass_timings.iter().map(|timing| timing.bytes).sum();
+        let millis: u64 = self.pass_timings.iter().map(|timing| timing.millis).sum();
+        (!self.pass_timings.is_empty()).then(|| bytes.saturating_mul(1000) / millis.max(1))
+    }
+}
+
+/// Receives progress from the engine. Any `FnMut(WipeProgress)` closure is a sink.
+pub trait ProgressSink {
+    fn report(&mut self, progress: WipeProgress);
+}
+
+impl<F: FnMut(WipeProgress)> ProgressSink for F {
+    fn report(&mut self, progress: WipeProgress) {
+        self(progress)
+    }
+}
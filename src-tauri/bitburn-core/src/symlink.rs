@@ -0,0 +1,101 @@
+use rand::distributions::{Alphanumeric, DistString};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::WipeError;
+use crate::tree_removal::remove_entry;
+
+/// What is done with a symbolic link that was selected for wiping. Links found inside a selected
+/// folder are never followed, whatever the policy; they are removed with the folder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkPolicy {
+    /// Refuse the link.
+    #[default]
+    Reject,
+    /// Rename the link to a random name and delete it, leaving what it points at alone.
+    DeleteLinkOnly,
+    /// Wipe what the link points at, then delete the link. Only one level is resolved.
+    FollowTarget,
+}
+
+/// Whether `path` itself is a symbolic link (or, on Windows, a junction), dangling or not.
+pub fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+}
+
+/// What `link` points at, one level down: a relative target is taken from the link's folder.
+/// Fails with `LinkChain` when the target is itself a link and `PathNotFound` when it is gone.
+pub fn link_target(link: &Path) -> Result<PathBuf, WipeError> {
+    let target = fs::read_link(link).map_err(WipeError::Io)?;
+    let target = match link.parent() {
+        Some(parent) if target.is_relative() => parent.join(target),
+        _ => target,
+    };
+    match fs::symlink_metadata(&target) {
+        Ok(metadata) if metadata.file_type().is_symlink() => Err(WipeError::LinkChain),
+        Ok(_) => Ok(target),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Err(WipeError::PathNotFound),
+        Err(e) => Err(WipeError::Io(e)),
+    }
+}
+
+/// Rename `link` to a random name in its folder and delete it, so the folder no longer names it.
+/// What it points at is not touched.
+pub fn delete_link(link: &Path) -> Result<(), WipeError> {
+    if !is_symlink(link) {
+        return Err(WipeError::Io(io::Error::new(io::ErrorKind::InvalidInput, "Not a symbolic link")));
+    }
+    let scrambled = link.with_file_name(Alphanumeric.sample_string(&mut rand::thread_rng(), 16));
+    fs::rename(link, &scrambled).map_err(WipeError::Io)?;
+    remove_entry(&scrambled).map_err(WipeError::Io)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bitburn_core_symlink_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn targets_are_resolved_one_level_only() {
+        let dir = scratch("resolve");
+        fs::write(dir.join("data.bin"), b"data").unwrap();
+        symlink("data.bin", dir.join("relative")).unwrap();
+        symlink(dir.join("relative"), dir.join("chained")).unwrap();
+        symlink(dir.join("missing"), dir.join("dangling")).unwrap();
+
+        assert_eq!(link_target(&dir.join("relative")).unwrap(), dir.join("data.bin"));
+        assert!(matches!(link_target(&dir.join("chained")), Err(WipeError::LinkChain)));
+        assert!(matches!(link_target(&dir.join("dangling")), Err(WipeError::PathNotFound)));
+        assert!(is_symlink(&dir.join("dangling")) && !is_symlink(&dir.join("data.bin")));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn deleting_a_link_leaves_its_target_alone() {
+        let dir = scratch("delete");
+        let folder = dir.join("folder");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("kept.txt"), b"kept").unwrap();
+        symlink(&folder, dir.join("folder_link")).unwrap();
+        symlink(folder.join("kept.txt"), dir.join("file_link")).unwrap();
+
+        delete_link(&dir.join("folder_link")).unwrap();
+        delete_link(&dir.join("file_link")).unwrap();
+        let mut left: Vec<_> = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        left.sort();
+        assert_eq!(left, ["folder"]);
+        assert_eq!(fs::read(folder.join("kept.txt")).unwrap(), b"kept");
+        assert!(delete_link(&folder).is_err(), "a real folder is not a link");
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
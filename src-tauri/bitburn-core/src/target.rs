@@ -0,0 +1,257 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Destination the wipe engine overwrites pass by pass.
+/// Every pass starts with `rewind`, writes the full target length and ends with `sync`.
+pub trait WipeTarget {
+    fn size(&mut self) -> io::Result<u64>;
+    fn rewind(&mut self) -> io::Result<()>;
+    fn write_chunk(&mut self, buf: &[u8]) -> io::Result<()>;
+    fn sync(&mut self) -> io::Result<()>;
+    /// Move to `offset` bytes into the pass just rewound, to carry on a pass an earlier run
+    /// checkpointed part-way through. Targets that cannot seek refuse.
+    fn resume_at(&mut self, offset: u64) -> io::Result<()> {
+        let _ = offset;
+        Err(io::Error::new(io::ErrorKind::Unsupported, "this target cannot resume a pass part-way through"))
+    }
+    /// Drop the target's contents once every pass is done, before the file is removed.
+    fn truncate(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+    /// Fill `buf` with what the target holds at `offset`, to verify a pass. Targets that cannot
+    /// be read back refuse.
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let _ = (offset, buf);
+        Err(io::Error::new(io::ErrorKind::Unsupported, "this target cannot be read back"))
+    }
+}
+
+impl<T: WipeTarget + ?Sized> WipeTarget for Box<T> {
+    fn size(&mut self) -> io::Result<u64> {
+        (**self).size()
+    }
+
+    fn rewind(&mut self) -> io::Result<()> {
+        (**self).rewind()
+    }
+
+    fn write_chunk(&mut self, buf: &[u8]) -> io::Result<()> {
+        (**self).write_chunk(buf)
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        (**self).sync()
+    }
+
+    fn resume_at(&mut self, offset: u64) -> io::Result<()> {
+        (**self).resume_at(offset)
+    }
+
+    fn truncate(&mut self) -> io::Result<()> {
+        (**self).truncate()
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        (**self).read_at(offset, buf)
+    }
+}
+
+impl WipeTarget for File {
+    fn size(&mut self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    fn rewind(&mut self) -> io::Result<()> {
+        self.seek(SeekFrom::Start(0)).map(|_| ())
+    }
+
+    fn write_chunk(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.write_all(buf)
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.sync_all()
+    }
+
+    fn resume_at(&mut self, offset: u64) -> io::Result<()> {
+        self.seek(SeekFrom::Start(offset)).map(|_| ())
+    }
+
+    fn truncate(&mut self) -> io::Result<()> {
+        self.set_len(0)
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.seek(SeekFrom::Start(offset))?;
+        self.read_exact(buf)
+    }
+}
+
+/// A byte range of another target. Every pass seeks back to `offset` and writes `length` bytes,
+/// and the wrapped target is never truncated, so the data around the range survives.
+#[derive(Debug)]
+pub struct RangeTarget<T> {
+    inner: T,
+    offset: u64,
+    length: u64,
+}
+
+impl<T> RangeTarget<T> {
+    pub fn new(inner: T, offset: u64, length: u64) -> Self {
+        RangeTarget { inner, offset, length }
+    }
+
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: WipeTarget + Seek> WipeTarget for RangeTarget<T> {
+    fn size(&mut self) -> io::Result<u64> {
+        Ok(self.length)
+    }
+
+    fn rewind(&mut self) -> io::Result<()> {
+        self.inner.seek(SeekFrom::Start(self.offset)).map(|_| ())
+    }
+
+    fn write_chunk(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.inner.write_chunk(buf)
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.inner.sync()
+    }
+
+    fn resume_at(&mut self, offset: u64) -> io::Result<()> {
+        self.inner.seek(SeekFrom::Start(self.offset + offset.min(self.length))).map(|_| ())
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.inner.read_at(self.offset + offset, buf)
+    }
+}
+
+/// Bytes written during one pass over an in-memory target.
+#[derive(Debug, Default, Clone)]
+pub struct PassRecord {
+    pub bytes: Vec<u8>,
+    pub synced: bool,
+}
+
+/// In-memory target used by the simulation mode.
+/// Records the exact bytes of every pass so tests can assert patterns without touching disk.
+#[derive(Debug, Default)]
+pub struct MemoryTarget {
+    data: Vec<u8>,
+    position: usize,
+    passes: Vec<PassRecord>,
+    write_limit: Option<u64>,
+    /// OS error code writes past `write_limit` fail with, instead of `WriteZero`.
+    write_error: Option<i32>,
+    written: u64,
+    /// Offset whose byte reads back flipped, like a drive that did not store what it was sent.
+    corrupt_at: Option<u64>,
+}
+
+impl MemoryTarget {
+    pub fn new(contents: Vec<u8>) -> Self {
+        MemoryTarget {
+            data: contents,
+            ..Default::default()
+        }
+    }
+
+    /// Fail every write once `limit` bytes have been written in total, to exercise error paths.
+    pub fn with_write_limit(mut self, limit: u64) -> Self {
+        self.write_limit = Some(limit);
+        self
+    }
+
+    /// Like `with_write_limit`, but writes past `limit` fail with the OS error `code`, e.g. to
+    /// stand in for a drive that was pulled out.
+    pub fn with_write_error(mut self, limit: u64, code: i32) -> Self {
+        self.write_limit = Some(limit);
+        self.write_error = Some(code);
+        self
+    }
+
+    /// Read the byte at `offset` back inverted, to exercise verification.
+    pub fn with_corruption_at(mut self, offset: u64) -> Self {
+        self.corrupt_at = Some(offset);
+        self
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn passes(&self) -> &[PassRecord] {
+        &self.passes
+    }
+}
+
+impl WipeTarget for MemoryTarget {
+    fn size(&mut self) -> io::Result<u64> {
+        Ok(self.data.len() as u64)
+    }
+
+    fn rewind(&mut self) -> io::Result<()> {
+        self.position = 0;
+        self.passes.push(PassRecord::default());
+        Ok(())
+    }
+
+    fn write_chunk(&mut self, buf: &[u8]) -> io::Result<()> {
+        if let Some(limit) = self.write_limit {
+            if self.written + buf.len() as u64 > limit {
+                return Err(match self.write_error {
+                    Some(code) => io::Error::from_raw_os_error(code),
+                    None => io::Error::new(io::ErrorKind::WriteZero, "simulated write failure"),
+                });
+            }
+        }
+
+        let end = self.position + buf.len();
+        if end > self.data.len() {
+            self.data.resize(end, 0);
+        }
+        self.data[self.position..end].copy_from_slice(buf);
+        self.position = end;
+        self.written += buf.len() as u64;
+
+        if self.passes.is_empty() {
+            self.passes.push(PassRecord::default());
+        }
+        if let Some(pass) = self.passes.last_mut() {
+            pass.bytes.extend_from_slice(buf);
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        if let Some(pass) = self.passes.last_mut() {
+            pass.synced = true;
+        }
+        Ok(())
+    }
+
+    fn resume_at(&mut self, offset: u64) -> io::Result<()> {
+        self.position = offset as usize;
+        Ok(())
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let start = offset as usize;
+        let stored = self.data.get(start..start + buf.len()).ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        buf.copy_from_slice(stored);
+        if let Some(corrupt) = self.corrupt_at.and_then(|at| at.checked_sub(offset)).filter(|&at| at < buf.len() as u64) {
+            buf[corrupt as usize] ^= 0xFF;
+        }
+        Ok(())
+    }
+}
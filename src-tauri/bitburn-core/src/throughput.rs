@@ -0,0 +1,132 @@
+use std::time::{Duration, Instant};
+
+use crate::clock::{ClockJump, ResumeDetector};
+
+/// Minimum sampling window so a single fast write does not produce a wild rate.
+const MIN_SAMPLE_WINDOW: Duration = Duration::from_millis(500);
+/// A gap between writes longer than this is not disk time: the machine was suspended or the
+/// write stalled, so the window is started again rather than averaged over it.
+const MAX_RECORD_GAP: Duration = Duration::from_secs(30);
+
+/// Instantaneous throughput over the most recent sampling window.
+#[derive(Debug, Clone)]
+pub struct ThroughputMeter {
+    window_start: Instant,
+    window_bytes: u64,
+    bytes_per_second: Option<u64>,
+    last_record: Instant,
+    resume: ResumeDetector,
+}
+
+impl ThroughputMeter {
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+
+    fn starting_at(now: Instant) -> Self {
+        ThroughputMeter {
+            window_start: now,
+            window_bytes: 0,
+            bytes_per_second: None,
+            last_record: now,
+            resume: ResumeDetector::new(),
+        }
+    }
+
+    /// Account for `bytes` written and refresh the rate once the window has elapsed. Returns the
+    /// jump when the clocks show the system slept since the last write; the window then starts
+    /// again, keeping the rate from before.
+    pub fn record(&mut self, bytes: u64) -> Option<ClockJump> {
+        let now = Instant::now();
+        let jump = self.resume.observe();
+        match jump {
+            Some(_) => self.restart_window(now),
+            None => self.record_at(bytes, now),
+        }
+        jump
+    }
+
+    fn restart_window(&mut self, now: Instant) {
+        self.window_start = now;
+        self.window_bytes = 0;
+        self.last_record = now;
+    }
+
+    fn record_at(&mut self, bytes: u64, now: Instant) {
+        if now.saturating_duration_since(self.last_record) > MAX_RECORD_GAP {
+            self.restart_window(now);
+            return;
+        }
+        self.last_record = now;
+        self.window_bytes += bytes;
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed >= MIN_SAMPLE_WINDOW {
+            self.bytes_per_second = Some((self.window_bytes as f64 / elapsed.as_secs_f64()) as u64);
+            self.window_start = now;
+            self.window_bytes = 0;
+        }
+    }
+
+    pub fn bytes_per_second(&self) -> Option<u64> {
+        self.bytes_per_second
+    }
+
+    pub fn megabytes_per_second(&self) -> Option<f64> {
+        self.bytes_per_second.map(|rate| rate as f64 / 1024.0 / 1024.0)
+    }
+
+    /// Seconds needed to write `remaining_bytes` at the current rate.
+    pub fn estimated_seconds_remaining(&self, remaining_bytes: u64) -> Option<u64> {
+        match self.bytes_per_second {
+            Some(rate) if rate > 0 => Some(remaining_bytes.div_ceil(rate)),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ThroughputMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_is_unknown_until_window_elapses() {
+        let start = Instant::now();
+        let mut meter = ThroughputMeter::starting_at(start);
+        meter.record_at(1024, start + Duration::from_millis(100));
+        assert_eq!(meter.bytes_per_second(), None);
+        assert_eq!(meter.estimated_seconds_remaining(1024), None);
+    }
+
+    #[test]
+    fn rate_reflects_latest_window() {
+        let start = Instant::now();
+        let mut meter = ThroughputMeter::starting_at(start);
+        meter.record_at(50 * 1024 * 1024, start + Duration::from_secs(1));
+        assert_eq!(meter.megabytes_per_second(), Some(50.0));
+
+        meter.record_at(10 * 1024 * 1024, start + Duration::from_secs(2));
+        assert_eq!(meter.megabytes_per_second(), Some(10.0));
+        assert_eq!(meter.estimated_seconds_remaining(25 * 1024 * 1024), Some(3));
+    }
+
+    #[test]
+    fn a_long_gap_restarts_the_window_instead_of_diluting_the_rate() {
+        let start = Instant::now();
+        let mut meter = ThroughputMeter::starting_at(start);
+        meter.record_at(50 * 1024 * 1024, start + Duration::from_secs(1));
+        // Suspended mid-window: the chunk that spans the gap is not counted against it.
+        meter.record_at(1024 * 1024, start + Duration::from_millis(1_200));
+        let resumed = start + Duration::from_secs(3_600);
+        meter.record_at(1024 * 1024, resumed);
+        assert_eq!(meter.megabytes_per_second(), Some(50.0));
+
+        meter.record_at(20 * 1024 * 1024, resumed + Duration::from_secs(1));
+        assert_eq!(meter.megabytes_per_second(), Some(20.0));
+    }
+}
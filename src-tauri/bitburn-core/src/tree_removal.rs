@@ -0,0 +1,223 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cancel::CancellationToken;
+
+/// Entries `remove_tree` deletes between progress reports and cancellation checks.
+pub const REMOVAL_BATCH: usize = 1000;
+
+/// What `remove_tree` deleted and what it could not.
+#[derive(Debug, Default)]
+pub struct TreeRemoval {
+    pub removed: usize,
+    pub total: usize,
+    /// Directories among `removed`.
+    pub directories_removed: usize,
+    /// Entries whose removal failed. Directories holding one of them are left in place and not listed.
+    pub failed: Vec<(PathBuf, io::Error)>,
+}
+
+/// `remove_tree` was cancelled with `removed` of `total` entries deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemovalCancelled {
+    pub removed: usize,
+    pub total: usize,
+}
+
+/// Every entry under `dir`, and `dir` itself unless `keep_root`, files first and then directories
+/// deepest first, so each directory is empty by the time it is removed. Symlinks are listed,
+/// never followed.
+fn removal_order(dir: &Path, keep_root: bool) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut entries = Vec::new();
+    let mut directories = vec![dir.to_path_buf()];
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(listing) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in listing.flatten() {
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() => {
+                    directories.push(path.clone());
+                    pending.push(path);
+                }
+                _ => entries.push(path),
+            }
+        }
+    }
+    if keep_root {
+        directories.remove(0);
+    }
+    // Parents are listed before their children; removing in reverse empties each one first.
+    directories.reverse();
+    (entries, directories)
+}
+
+pub(crate) fn remove_entry(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        // A symlink to a directory is removed as a directory on Windows.
+        Err(e) if path.is_symlink() => fs::remove_dir(path).map_err(|_| e),
+        result => result,
+    }
+}
+
+/// Delete `dir` and everything in it, bottom-up in batches of `REMOVAL_BATCH`, so a tree of
+/// hundreds of thousands of wiped files reports progress and can be stopped instead of sitting
+/// in one long `remove_dir_all`. `on_progress(removed, total)` runs after each batch, and
+/// `cancel` is checked before the next one. Entries that cannot be removed are collected in
+/// `failed` and the rest of the tree is still removed.
+pub fn remove_tree<P>(dir: &Path, cancel: &CancellationToken, on_progress: P) -> Result<TreeRemoval, RemovalCancelled>
+where
+    P: FnMut(usize, usize),
+{
+    remove_entries(dir, false, cancel, on_progress)
+}
+
+/// Like `remove_tree`, but `dir` itself is left in place, untouched, once everything inside it
+/// is gone.
+pub fn empty_tree<P>(dir: &Path, cancel: &CancellationToken, on_progress: P) -> Result<TreeRemoval, RemovalCancelled>
+where
+    P: FnMut(usize, usize),
+{
+    remove_entries(dir, true, cancel, on_progress)
+}
+
+fn remove_entries<P>(
+    dir: &Path,
+    keep_root: bool,
+    cancel: &CancellationToken,
+    mut on_progress: P,
+) -> Result<TreeRemoval, RemovalCancelled>
+where
+    P: FnMut(usize, usize),
+{
+    let (entries, directories) = removal_order(dir, keep_root);
+    let total = entries.len() + directories.len();
+    let mut removal = TreeRemoval { total, ..Default::default() };
+    // Directories that still hold an entry that failed; removing them would fail as well.
+    let mut kept = HashSet::new();
+
+    for (index, path) in entries.iter().chain(directories.iter()).enumerate() {
+        if index > 0 && index % REMOVAL_BATCH == 0 {
+            on_progress(removal.removed, total);
+            if cancel.is_cancelled() {
+                return Err(RemovalCancelled { removed: removal.removed, total });
+            }
+        }
+        if kept.contains(path) {
+            continue;
+        }
+        let is_directory = index >= entries.len();
+        let outcome = if is_directory { fs::remove_dir(path) } else { remove_entry(path) };
+        match outcome {
+            Ok(()) => {
+                removal.removed += 1;
+                removal.directories_removed += usize::from(is_directory);
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => removal.removed += 1,
+            Err(e) => {
+                kept.extend(path.ancestors().skip(1).take_while(|ancestor| ancestor.starts_with(dir)).map(Path::to_path_buf));
+                removal.failed.push((path.clone(), e));
+            }
+        }
+    }
+    on_progress(removal.removed, total);
+    Ok(removal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn tree_of_tiny_files(name: &str, folders: usize, files_per_folder: usize) -> PathBuf {
+        let unique = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let root = std::env::temp_dir().join(format!("bitburn_core_remove_{}_{}", name, unique));
+        for folder in 0..folders {
+            let dir = root.join(format!("folder_{}", folder)).join("nested");
+            fs::create_dir_all(&dir).unwrap();
+            for file in 0..files_per_folder {
+                fs::write(dir.join(format!("{}.tmp", file)), b"").unwrap();
+            }
+        }
+        root
+    }
+
+    #[test]
+    fn large_trees_are_removed_with_progress_between_batches() {
+        let root = tree_of_tiny_files("progress", 20, 1000);
+        let mut reports = Vec::new();
+
+        let removal = remove_tree(&root, &CancellationToken::new(), |removed, total| reports.push((removed, total)))
+            .expect("nothing cancelled the removal");
+
+        // 20,000 files, 40 folders and the root itself.
+        assert_eq!(removal.total, 20_041);
+        assert_eq!(removal.removed, removal.total);
+        assert_eq!(removal.directories_removed, 41);
+        assert!(removal.failed.is_empty());
+        assert!(!root.exists());
+        assert_eq!(reports.len(), 21, "one report per batch plus the final one");
+        assert!(reports.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(reports.last(), Some(&(20_041, 20_041)));
+    }
+
+    #[test]
+    fn cancelling_midway_stops_between_batches() {
+        let root = tree_of_tiny_files("cancel", 20, 1000);
+        let cancel = CancellationToken::new();
+
+        let stopped = remove_tree(&root, &cancel, |removed, _| {
+            if removed >= 5 * REMOVAL_BATCH {
+                cancel.cancel();
+            }
+        })
+        .expect_err("the removal was cancelled");
+
+        assert_eq!(stopped, RemovalCancelled { removed: 5 * REMOVAL_BATCH, total: 20_041 });
+        let left = fs::read_dir(&root).unwrap().flatten().flat_map(|folder| fs::read_dir(folder.path().join("nested")).unwrap()).count();
+        assert_eq!(left, 15_000);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn emptying_a_tree_keeps_the_root_folder() {
+        let root = tree_of_tiny_files("empty", 3, 10);
+        fs::write(root.join("top.tmp"), b"").unwrap();
+
+        let removal = empty_tree(&root, &CancellationToken::new(), |_, _| {}).unwrap();
+
+        assert_eq!((removal.removed, removal.total, removal.directories_removed), (37, 37, 6));
+        assert!(root.is_dir());
+        assert_eq!(fs::read_dir(&root).unwrap().count(), 0);
+        fs::remove_dir(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn entries_that_cannot_be_removed_are_reported_and_their_parents_kept() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = tree_of_tiny_files("failures", 2, 3);
+        let locked = root.join("folder_0").join("nested");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o555)).unwrap();
+        // Root ignores directory permissions, so the failure can only be provoked for other users.
+        let enforced = fs::write(locked.join("probe"), b"").is_err();
+
+        let removal = remove_tree(&root, &CancellationToken::new(), |_, _| {}).unwrap();
+        if enforced {
+            assert_eq!(removal.failed.len(), 3);
+            assert!(removal.failed.iter().all(|(path, _)| path.starts_with(&locked)));
+            assert!(locked.exists());
+            assert!(!root.join("folder_1").exists());
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            fs::remove_dir_all(&root).unwrap();
+        } else {
+            assert!(removal.failed.is_empty());
+            assert!(!root.exists());
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Files directly inside one directory of a scanned tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedDirectory {
+    pub path: PathBuf,
+    pub files: Vec<PathBuf>,
+}
+
+/// One walk of a selected folder, grouped by directory so the wipe can say where it is.
+/// The pre-flight lock scan builds it and the wipe reuses it instead of walking the tree again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeScan {
+    /// Directories holding files, in the order they were first reached.
+    pub directories: Vec<ScannedDirectory>,
+    pub bytes: u64,
+}
+
+/// Files a chunked walk hands out at a time unless told otherwise.
+pub const DEFAULT_CHUNK_FILES: usize = 50_000;
+
+impl TreeScan {
+    pub fn walk(root: &Path) -> Self {
+        TreeScan::walk_excluding(root, &HashSet::new())
+    }
+
+    /// Walk `root`, leaving out files in `exclude` (ones an earlier pass already attempted).
+    pub fn walk_excluding(root: &Path, exclude: &HashSet<PathBuf>) -> Self {
+        TreeScan::chunks(root, exclude, usize::MAX).next().unwrap_or_default()
+    }
+
+    /// Walk `root` lazily, `chunk_files` files at a time, so a tree of millions of files never
+    /// sits in memory whole. Files of one directory can be split across chunks.
+    pub fn chunks<'a>(root: &Path, exclude: &'a HashSet<PathBuf>, chunk_files: usize) -> TreeChunks<'a> {
+        TreeChunks {
+            root: root.to_path_buf(),
+            walk: WalkDir::new(root).sort_by_file_name().into_iter(),
+            pending: None,
+            exclude,
+            chunk_files: chunk_files.max(1),
+        }
+    }
+
+    /// The files under `root` not in `exclude` and their size, without keeping their paths.
+    pub fn count_excluding(root: &Path, exclude: &HashSet<PathBuf>) -> TreeCount {
+        let mut count = TreeCount::default();
+        for entry in WalkDir::new(root).into_iter().filter_map(Result::ok) {
+            if entry.file_type().is_file() && !exclude.contains(entry.path()) {
+                count.files += 1;
+                count.bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+            }
+        }
+        count
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.directories.iter().map(|directory| directory.files.len()).sum()
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.directories.iter().flat_map(|directory| directory.files.iter())
+    }
+}
+
+/// How many files a tree holds and their combined size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TreeCount {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// A tree walked a chunk at a time; see `TreeScan::chunks`.
+pub struct TreeChunks<'a> {
+    root: PathBuf,
+    walk: walkdir::IntoIter,
+    /// A file read ahead by `finished`.
+    pending: Option<walkdir::DirEntry>,
+    exclude: &'a HashSet<PathBuf>,
+    chunk_files: usize,
+}
+
+impl TreeChunks<'_> {
+    fn next_file(&mut self) -> Option<walkdir::DirEntry> {
+        if let Some(entry) = self.pending.take() {
+            return Some(entry);
+        }
+        self.walk
+            .by_ref()
+            .filter_map(Result::ok)
+            .find(|entry| entry.file_type().is_file() && !self.exclude.contains(entry.path()))
+    }
+
+    /// Whether every file has been handed out, i.e. the chunks so far cover the whole tree.
+    pub fn finished(&mut self) -> bool {
+        if self.pending.is_none() {
+            self.pending = self.next_file();
+        }
+        self.pending.is_none()
+    }
+}
+
+impl Iterator for TreeChunks<'_> {
+    type Item = TreeScan;
+
+    fn next(&mut self) -> Option<TreeScan> {
+        let mut chunk = TreeScan::default();
+        let mut index_of: HashMap<PathBuf, usize> = HashMap::new();
+        let mut files = 0;
+        while files < self.chunk_files {
+            let Some(entry) = self.next_file() else {
+                break;
+            };
+            chunk.bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+            let parent = entry.path().parent().unwrap_or(&self.root).to_path_buf();
+            let index = *index_of.entry(parent.clone()).or_insert_with(|| {
+                chunk.directories.push(ScannedDirectory { path: parent, files: Vec::new() });
+                chunk.directories.len() - 1
+            });
+            chunk.directories[index].files.push(entry.into_path());
+            files += 1;
+        }
+        (files > 0).then_some(chunk)
+    }
+}
+
+/// Where a folder wipe has got to, sent with each progress update.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalkPosition {
+    pub current_directory: String,
+    /// Files finished in `current_directory`.
+    pub directory_files_done: usize,
+    pub directory_files_total: usize,
+    /// Files finished in the whole selected folder.
+    pub files_done: usize,
+    pub files_total: usize,
+    /// Files appeared or disappeared since the scan; the totals are best effort.
+    pub tree_changed: bool,
+}
+
+/// Advances a `WalkPosition` through a scanned tree. Counters never pass their totals, however
+/// the tree changes underneath.
+#[derive(Debug, Clone, Default)]
+pub struct WalkTracker {
+    position: WalkPosition,
+}
+
+impl WalkTracker {
+    pub fn new(files_total: usize) -> Self {
+        WalkTracker {
+            position: WalkPosition {
+                files_total,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn position(&self) -> &WalkPosition {
+        &self.position
+    }
+
+    /// Start on the `files` files of `directory`.
+    pub fn enter(&mut self, directory: &Path, files: usize) {
+        self.position.current_directory = directory.to_string_lossy().to_string();
+        self.position.directory_files_done = 0;
+        self.position.directory_files_total = files;
+    }
+
+    pub fn file_done(&mut self) {
+        let position = &mut self.position;
+        position.directory_files_done = (position.directory_files_done + 1).min(position.directory_files_total);
+        position.files_done = (position.files_done + 1).min(position.files_total);
+    }
+
+    /// Files the scan did not know about turned up.
+    pub fn files_added(&mut self, count: usize) {
+        self.position.files_total += count;
+        self.position.tree_changed = true;
+    }
+
+    /// A scanned file was gone by the time the wipe reached it.
+    pub fn file_vanished(&mut self) {
+        self.position.tree_changed = true;
+        self.file_done();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn nested_tree() -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "BitBurn_tree_{}_{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        for (dir, files) in [("", 2), ("2023", 1), ("2023/Q4", 3), ("empty", 0)] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            for i in 0..files {
+                fs::write(root.join(dir).join(format!("f{}.bin", i)), [0u8; 10]).unwrap();
+            }
+        }
+        root
+    }
+
+    #[test]
+    fn scan_groups_files_by_directory() {
+        let root = nested_tree();
+        let scan = TreeScan::walk(&root);
+        let shape: Vec<(PathBuf, usize)> =
+            scan.directories.iter().map(|d| (d.path.clone(), d.files.len())).collect();
+        assert_eq!(
+            shape,
+            vec![(root.join("2023").join("Q4"), 3), (root.join("2023"), 1), (root.clone(), 2)]
+        );
+        assert_eq!(scan.file_count(), 6);
+        assert_eq!(scan.bytes, 60);
+
+        let excluded: HashSet<PathBuf> = scan.directories[0].files.iter().cloned().collect();
+        assert_eq!(TreeScan::walk_excluding(&root, &excluded).file_count(), 3);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn chunks_hand_out_the_same_files_a_bounded_number_at_a_time() {
+        let root = nested_tree();
+        let whole: Vec<PathBuf> = TreeScan::walk(&root).files().cloned().collect();
+        let none = HashSet::new();
+
+        let chunks: Vec<TreeScan> = TreeScan::chunks(&root, &none, 4).collect();
+        assert_eq!(chunks.iter().map(TreeScan::file_count).collect::<Vec<_>>(), vec![4, 2]);
+        assert_eq!(chunks.iter().flat_map(TreeScan::files).cloned().collect::<Vec<_>>(), whole);
+        assert_eq!(chunks.iter().map(|chunk| chunk.bytes).sum::<u64>(), 60);
+        assert_eq!(TreeScan::count_excluding(&root, &none), TreeCount { files: 6, bytes: 60 });
+
+        let mut fits = TreeScan::chunks(&root, &none, 6);
+        assert!(!fits.finished());
+        assert_eq!(fits.next().map(|chunk| chunk.file_count()), Some(6));
+        assert!(fits.finished());
+        assert_eq!(fits.next(), None);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn directory_counters_reset_at_each_boundary() {
+        let root = nested_tree();
+        let scan = TreeScan::walk(&root);
+        let mut tracker = WalkTracker::new(scan.file_count());
+        let mut seen = Vec::new();
+        for directory in &scan.directories {
+            tracker.enter(&directory.path, directory.files.len());
+            for _ in &directory.files {
+                let p = tracker.position();
+                seen.push((p.directory_files_done, p.directory_files_total, p.files_done));
+                tracker.file_done();
+            }
+        }
+        assert_eq!(seen, vec![(0, 3, 0), (1, 3, 1), (2, 3, 2), (0, 1, 3), (0, 2, 4), (1, 2, 5)]);
+        assert_eq!(tracker.position().files_done, 6);
+        assert!(!tracker.position().tree_changed);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn changes_after_the_scan_are_flagged_and_clamped() {
+        let mut tracker = WalkTracker::new(2);
+        tracker.enter(Path::new("/data"), 1);
+        tracker.file_done();
+        tracker.file_done();
+        tracker.file_done();
+        assert_eq!((tracker.position().directory_files_done, tracker.position().files_done), (1, 2));
+        assert!(!tracker.position().tree_changed);
+
+        tracker.file_vanished();
+        assert!(tracker.position().tree_changed);
+        tracker.files_added(3);
+        assert_eq!(tracker.position().files_total, 5);
+    }
+}
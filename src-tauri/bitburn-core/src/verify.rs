@@ -0,0 +1,110 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+/// How much of each pass is read back once it is synced.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyMode {
+    /// Every chunk of every pass.
+    Full,
+    /// This percentage of each pass's chunks, at least one, chosen at random.
+    Sampled(f64),
+    /// Every chunk of the last pass only, which is what the drive is left holding.
+    FinalOnly,
+}
+
+/// Read-back verification for a run. The chunks a sampled pass re-reads follow from `seed`, the
+/// pass and the file's chunk count alone, so a report's seed reproduces the sample.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Verification {
+    pub mode: VerifyMode,
+    pub seed: u64,
+}
+
+impl VerifyMode {
+    /// Whether the mode is usable: a sampled percentage must be above 0 and at most 100.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            VerifyMode::Sampled(percent) => *percent > 0.0 && *percent <= 100.0,
+            _ => true,
+        }
+    }
+}
+
+/// The chunks of `chunk_count` a pass sampled at `percent` re-reads, in ascending order. `pass` is
+/// 1-based, as in `WipeProgress::current_pass`.
+pub fn sampled_chunks(seed: u64, pass: u32, chunk_count: u64, percent: f64) -> Vec<u64> {
+    if chunk_count == 0 {
+        return Vec::new();
+    }
+    let wanted = ((chunk_count as f64 * percent / 100.0).ceil() as u64).clamp(1, chunk_count);
+    let mut rng = StdRng::seed_from_u64(seed ^ u64::from(pass).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    let mut chunks: Vec<u64> =
+        rand::seq::index::sample(&mut rng, chunk_count as usize, wanted as usize).into_iter().map(|chunk| chunk as u64).collect();
+    chunks.sort_unstable();
+    chunks
+}
+
+/// Which chunks of one pass are read back.
+pub(crate) enum PassSample {
+    All,
+    Chunks(HashSet<u64>),
+}
+
+impl PassSample {
+    /// What pass `pass` (1-based) of `pass_count` re-reads out of `chunk_count`; `None` for none.
+    pub(crate) fn of(verification: &Verification, pass: u32, pass_count: u32, chunk_count: u64) -> Option<Self> {
+        match verification.mode {
+            VerifyMode::Full => Some(PassSample::All),
+            VerifyMode::FinalOnly => (pass == pass_count).then_some(PassSample::All),
+            VerifyMode::Sampled(percent) => {
+                Some(PassSample::Chunks(sampled_chunks(verification.seed, pass, chunk_count, percent).into_iter().collect()))
+            }
+        }
+    }
+
+    pub(crate) fn contains(&self, chunk: u64) -> bool {
+        match self {
+            PassSample::All => true,
+            PassSample::Chunks(chunks) => chunks.contains(&chunk),
+        }
+    }
+}
+
+/// Fingerprint of a chunk as written, compared with the same chunk read back.
+pub(crate) fn chunk_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_seed_reproduces_each_pass_sample() {
+        let first = sampled_chunks(42, 3, 1000, 1.0);
+        assert_eq!(first.len(), 10);
+        assert_eq!(first, sampled_chunks(42, 3, 1000, 1.0));
+        assert!(first.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_ne!(first, sampled_chunks(42, 4, 1000, 1.0), "each pass samples its own chunks");
+        assert_ne!(first, sampled_chunks(43, 3, 1000, 1.0));
+        // Small files still get a chunk checked, and 100% is every chunk.
+        assert_eq!(sampled_chunks(42, 1, 3, 1.0).len(), 1);
+        assert_eq!(sampled_chunks(42, 1, 3, 100.0), [0, 1, 2]);
+        assert!(sampled_chunks(42, 1, 0, 50.0).is_empty());
+    }
+
+    #[test]
+    fn sampled_percentages_must_be_in_range() {
+        assert!(VerifyMode::Sampled(0.5).is_valid());
+        assert!(VerifyMode::Sampled(100.0).is_valid());
+        assert!(!VerifyMode::Sampled(0.0).is_valid());
+        assert!(!VerifyMode::Sampled(120.0).is_valid());
+        assert!(!VerifyMode::Sampled(f64::NAN).is_valid());
+    }
+}
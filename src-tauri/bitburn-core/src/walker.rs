@@ -0,0 +1,141 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::cancel::CancellationToken;
+use crate::tree_scan::{TreeScan, WalkPosition, WalkTracker};
+
+/// How many times a directory is re-walked for files created while it was being wiped.
+pub const MAX_DIRECTORY_RESCANS: u32 = 3;
+
+/// How a directory root was wiped.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DirectoryOutcome {
+    pub path: String,
+    pub files_wiped: usize,
+    /// Files that appeared after the initial walk and were caught by a re-scan.
+    pub rescanned_files: usize,
+    /// Chunks of at most `chunk_files` files the walks were split into.
+    pub chunks: usize,
+}
+
+/// Why `wipe_directory_files` stopped before the tree was empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirectoryError {
+    Cancelled,
+    /// New files were still appearing after `rescans` re-walks; `new_files` turned up in the last one.
+    Active { rescans: u32, new_files: usize },
+}
+
+impl fmt::Display for DirectoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DirectoryError::Cancelled => write!(f, "Operation cancelled by user"),
+            DirectoryError::Active { rescans, .. } => {
+                write!(f, "Files were still being created after {} re-scans", rescans)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DirectoryError {}
+
+/// Wipe every file under `dir` with `wipe_file(file, position)`, then re-walk the tree and wipe
+/// files that appeared meanwhile, so `remove_dir_all` never plain-deletes unwiped data.
+/// The first pass works from `scan` when the caller already walked the tree. Otherwise each walk
+/// holds at most `chunk_files` paths at a time. `after_pass` runs after each walk has been wiped.
+/// Fails with `Active` if new files are still appearing after `MAX_DIRECTORY_RESCANS` re-scans;
+/// the directory should then be left in place.
+/// Links under `dir` are not followed, whatever the `SymlinkPolicy`, so nothing outside the tree
+/// is wiped; `remove_tree` deletes them as links. A link passed as `dir` itself is walked, so
+/// resolve it first under the policy.
+pub fn wipe_directory_files<W, H>(
+    dir: &Path,
+    scan: Option<TreeScan>,
+    chunk_files: usize,
+    cancel: &CancellationToken,
+    mut wipe_file: W,
+    mut after_pass: H,
+) -> Result<DirectoryOutcome, DirectoryError>
+where
+    W: FnMut(&Path, &WalkPosition) -> bool,
+    H: FnMut(u32),
+{
+    let mut outcome = DirectoryOutcome {
+        path: dir.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    // Files attempted but not wiped, so ones that failed or were skipped are not retried by a re-scan.
+    let mut attempted = HashSet::new();
+    let mut cached = scan;
+    let mut tracker: Option<WalkTracker> = None;
+
+    for scan in 0..=MAX_DIRECTORY_RESCANS + 1 {
+        let excluded = attempted.clone();
+        let mut chunks = TreeScan::chunks(dir, &excluded, chunk_files);
+        let from_cache = cached.is_some();
+        let (first, file_count) = match cached.take() {
+            Some(cached) => {
+                let file_count = cached.file_count();
+                (Some(cached), file_count)
+            }
+            None => {
+                let first = chunks.next();
+                let file_count = match &first {
+                    Some(first) if chunks.finished() => first.file_count(),
+                    Some(_) => TreeScan::count_excluding(dir, &excluded).files,
+                    None => 0,
+                };
+                (first, file_count)
+            }
+        };
+        let Some(first) = first.filter(|_| file_count > 0) else {
+            break;
+        };
+        if scan > MAX_DIRECTORY_RESCANS {
+            return Err(DirectoryError::Active {
+                rescans: MAX_DIRECTORY_RESCANS,
+                new_files: file_count,
+            });
+        }
+        let tracker = match tracker.as_mut() {
+            Some(tracker) => {
+                tracker.files_added(file_count);
+                tracker
+            }
+            None => tracker.insert(WalkTracker::new(file_count)),
+        };
+        if scan > 0 {
+            outcome.rescanned_files += file_count;
+        }
+
+        // A cached scan is the whole tree; the walk is only needed without one.
+        let rest = if from_cache { None } else { Some(chunks) };
+        for chunk in std::iter::once(first).chain(rest.into_iter().flatten()) {
+            outcome.chunks += 1;
+            for directory in chunk.directories {
+                tracker.enter(&directory.path, directory.files.len());
+                for file in directory.files {
+                    if cancel.is_cancelled() {
+                        return Err(DirectoryError::Cancelled);
+                    }
+                    if !file.exists() {
+                        // Removed since the scan; there is nothing left to wipe.
+                        tracker.file_vanished();
+                        continue;
+                    }
+                    if wipe_file(&file, tracker.position()) {
+                        outcome.files_wiped += 1;
+                    } else {
+                        attempted.insert(file);
+                    }
+                    tracker.file_done();
+                }
+            }
+        }
+        after_pass(scan);
+    }
+
+    Ok(outcome)
+}
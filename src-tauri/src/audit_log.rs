@@ -0,0 +1,475 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::signing::canonical_json;
+
+const AUDIT_DIR: &str = "audit";
+const FILE_PREFIX: &str = "audit-";
+const FILE_SUFFIX: &str = ".jsonl";
+/// Start a new file once the current one would grow past this size.
+pub const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Number of log files kept on disk; older files are deleted after rotation.
+pub const MAX_FILES: usize = 20;
+/// `prev_hash` of the very first genesis entry.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+const GENESIS_EVENT: &str = "audit_log_genesis";
+/// Raised when a record could not be appended. It is queued for the log like any other event.
+const WRITE_FAILED_EVENT: &str = "audit_log_write_failed";
+
+fn hash_entry(entry: &Map<String, Value>) -> String {
+    hex::encode(Sha256::digest(canonical_json(&Value::Object(entry.clone())).as_bytes()))
+}
+
+fn file_name(sequence: u64) -> String {
+    format!("{}{:06}{}", FILE_PREFIX, sequence, FILE_SUFFIX)
+}
+
+fn file_sequence(path: &Path) -> Option<u64> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix(FILE_PREFIX)?
+        .strip_suffix(FILE_SUFFIX)?
+        .parse()
+        .ok()
+}
+
+/// Audit log files in `dir`, oldest first.
+fn log_files(dir: &Path) -> Vec<(u64, PathBuf)> {
+    let mut files: Vec<(u64, PathBuf)> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter_map(|path| file_sequence(&path).map(|seq| (seq, path)))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Appends hash-chained JSONL entries, rotating files by size. Each entry's
+/// `entry_hash` is the SHA-256 of its canonical JSON without that field, and
+/// its `prev_hash` is the previous entry's `entry_hash`. Every file opens with a
+/// genesis entry whose `prev_hash` is the last hash of the file before it.
+pub struct AuditWriter {
+    dir: PathBuf,
+    max_file_bytes: u64,
+    max_files: usize,
+    file_sequence: u64,
+    file_bytes: u64,
+    last_hash: String,
+    file: Option<File>,
+}
+
+impl AuditWriter {
+    /// Resume the chain from the newest file in `dir`, if any.
+    pub fn open(dir: PathBuf, max_file_bytes: u64, max_files: usize) -> Self {
+        let mut writer = AuditWriter {
+            dir,
+            max_file_bytes,
+            max_files,
+            file_sequence: 0,
+            file_bytes: 0,
+            last_hash: GENESIS_HASH.to_string(),
+            file: None,
+        };
+
+        if let Some((sequence, path)) = log_files(&writer.dir).pop() {
+            writer.file_sequence = sequence;
+            writer.file_bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let last_hash = File::open(&path).ok().and_then(|file| {
+                BufReader::new(file)
+                    .lines()
+                    .map_while(Result::ok)
+                    .filter(|line| !line.trim().is_empty())
+                    .last()
+                    .and_then(|line| serde_json::from_str::<Value>(&line).ok())
+                    .and_then(|entry| entry.get("entry_hash")?.as_str().map(str::to_string))
+            });
+            match last_hash {
+                Some(hash) => {
+                    writer.last_hash = hash;
+                    writer.file = OpenOptions::new().append(true).open(&path).ok();
+                }
+                // Unreadable tail: start a fresh file rather than extend a chain we cannot link to.
+                None => writer.file_bytes = u64::MAX,
+            }
+        }
+        writer
+    }
+
+    fn write_entry(&mut self, event: &str, fields: Value, timestamp_ms: u64) -> std::io::Result<()> {
+        let mut entry = Map::new();
+        entry.insert("ts".to_string(), json!(timestamp_ms));
+        entry.insert("event".to_string(), json!(event));
+        entry.insert("fields".to_string(), fields);
+        entry.insert("prev_hash".to_string(), json!(self.last_hash));
+        let entry_hash = hash_entry(&entry);
+        entry.insert("entry_hash".to_string(), json!(entry_hash));
+
+        let mut line = canonical_json(&Value::Object(entry));
+        line.push('\n');
+        let file = match self.file.as_mut() {
+            Some(file) => file,
+            None => return Err(std::io::Error::other("audit log file is not open")),
+        };
+        file.write_all(line.as_bytes())?;
+        self.file_bytes += line.len() as u64;
+        self.last_hash = entry_hash;
+        Ok(())
+    }
+
+    fn rotate(&mut self, timestamp_ms: u64) -> std::io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let previous_file = (self.file_sequence > 0).then(|| file_name(self.file_sequence));
+        self.file_sequence += 1;
+        let path = self.dir.join(file_name(self.file_sequence));
+        self.file = Some(OpenOptions::new().create(true).append(true).open(&path)?);
+        self.file_bytes = 0;
+        self.write_entry(
+            GENESIS_EVENT,
+            json!({"file_sequence": self.file_sequence, "previous_file": previous_file}),
+            timestamp_ms,
+        )?;
+
+        let files = log_files(&self.dir);
+        if files.len() > self.max_files {
+            for (_, old) in &files[..files.len() - self.max_files] {
+                let _ = fs::remove_file(old);
+            }
+        }
+        Ok(())
+    }
+
+    /// Append one event, starting a new file first when the current one is full.
+    pub fn append(&mut self, event: &str, fields: Value, timestamp_ms: u64) -> std::io::Result<()> {
+        if self.file.is_none() || self.file_bytes >= self.max_file_bytes {
+            self.rotate(timestamp_ms)?;
+        }
+        self.write_entry(event, fields, timestamp_ms)
+    }
+}
+
+/// First link in the chain that failed to verify.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BrokenLink {
+    pub file: String,
+    /// 1-based line number within `file`.
+    pub line: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditVerification {
+    pub valid: bool,
+    pub files: usize,
+    pub entries: usize,
+    /// The oldest retained file links to one that was deleted by rotation.
+    pub truncated_history: bool,
+    /// Records this run failed to append. The chain links around them, so only this shows the gap.
+    pub dropped_records: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub broken: Option<BrokenLink>,
+}
+
+/// Walk every file in `dir` oldest first and check each link of the chain.
+pub fn verify_chain(dir: &Path) -> AuditVerification {
+    let files = log_files(dir);
+    let mut result = AuditVerification {
+        valid: true,
+        files: files.len(),
+        entries: 0,
+        truncated_history: false,
+        dropped_records: 0,
+        broken: None,
+    };
+    let mut expected_prev: Option<String> = None;
+
+    for (index, (sequence, path)) in files.iter().enumerate() {
+        let name = file_name(*sequence);
+        let broken = |line: usize, reason: String| BrokenLink {
+            file: name.clone(),
+            line,
+            reason,
+        };
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) => {
+                result.valid = false;
+                result.broken = Some(broken(0, format!("Cannot read file: {}", e)));
+                return result;
+            }
+        };
+
+        for (line_index, line) in BufReader::new(file).lines().enumerate() {
+            let line_number = line_index + 1;
+            let checked = line
+                .map_err(|e| format!("Cannot read line: {}", e))
+                .and_then(|line| verify_line(&line, line_number == 1, expected_prev.as_deref()));
+            match checked {
+                Ok(link) => {
+                    if index == 0 && line_number == 1 && link.prev_hash != GENESIS_HASH {
+                        result.truncated_history = true;
+                    }
+                    expected_prev = Some(link.entry_hash);
+                    result.entries += 1;
+                }
+                Err(reason) => {
+                    result.valid = false;
+                    result.broken = Some(broken(line_number, reason));
+                    return result;
+                }
+            }
+        }
+    }
+    result
+}
+
+struct VerifiedLink {
+    prev_hash: String,
+    entry_hash: String,
+}
+
+fn verify_line(line: &str, first_in_file: bool, expected_prev: Option<&str>) -> Result<VerifiedLink, String> {
+    let mut entry: Map<String, Value> =
+        serde_json::from_str(line).map_err(|e| format!("Entry is not valid JSON: {}", e))?;
+    let entry_hash = match entry.remove("entry_hash") {
+        Some(Value::String(hash)) => hash,
+        _ => return Err("Entry has no entry_hash".to_string()),
+    };
+    let prev_hash = entry
+        .get("prev_hash")
+        .and_then(Value::as_str)
+        .ok_or("Entry has no prev_hash")?
+        .to_string();
+
+    if hash_entry(&entry) != entry_hash {
+        return Err("Entry contents do not match entry_hash".to_string());
+    }
+    if first_in_file && entry.get("event").and_then(Value::as_str) != Some(GENESIS_EVENT) {
+        return Err("File does not start with a genesis entry".to_string());
+    }
+    // The oldest retained genesis entry may point at a deleted file; every other link must match.
+    if let Some(expected) = expected_prev {
+        if prev_hash != expected {
+            return Err("prev_hash does not match the previous entry".to_string());
+        }
+    }
+    Ok(VerifiedLink { prev_hash, entry_hash })
+}
+
+struct AuditRecord {
+    event: String,
+    fields: Value,
+    timestamp_ms: u64,
+}
+
+static AUDIT_SINK: OnceLock<Mutex<Sender<AuditRecord>>> = OnceLock::new();
+
+/// Queue an event for the logger thread. Hashing and file IO happen there, so
+/// callers only pay for a clone and a channel send.
+pub fn submit(event: &str, fields: &Value) {
+    let Some(sink) = AUDIT_SINK.get() else {
+        return;
+    };
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let record = AuditRecord {
+        event: event.to_string(),
+        fields: fields.clone(),
+        timestamp_ms,
+    };
+    if let Ok(sender) = sink.lock() {
+        let _ = sender.send(record);
+    }
+}
+
+/// Location of the audit log, shared through managed state.
+pub struct AuditLogState {
+    dir: Option<PathBuf>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Append `record`, counting it in `dropped` if it cannot be written. The failure is reported
+/// through `log_event`, except for a failure report that itself failed, which would only fail again.
+fn write_record(writer: &mut AuditWriter, record: AuditRecord, dropped: &AtomicU64) {
+    let failed_report = record.event == WRITE_FAILED_EVENT;
+    if let Err(e) = writer.append(&record.event, record.fields, record.timestamp_ms) {
+        let dropped_records = dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        if !failed_report {
+            log_event(
+                WRITE_FAILED_EVENT,
+                json!({"event": record.event, "message": e.to_string(), "dropped_records": dropped_records}),
+            );
+        }
+    }
+}
+
+/// Start the logger thread writing to the app log directory.
+pub fn init_audit_log<R: Runtime>(app: &AppHandle<R>) -> AuditLogState {
+    let dropped = Arc::new(AtomicU64::new(0));
+    let Ok(dir) = app.path().app_log_dir().map(|dir| dir.join(AUDIT_DIR)) else {
+        return AuditLogState { dir: None, dropped };
+    };
+
+    let (sender, receiver) = mpsc::channel::<AuditRecord>();
+    if AUDIT_SINK.set(Mutex::new(sender)).is_ok() {
+        let writer_dir = dir.clone();
+        let writer_dropped = Arc::clone(&dropped);
+        std::thread::spawn(move || {
+            let mut writer = AuditWriter::open(writer_dir, MAX_FILE_BYTES, MAX_FILES);
+            for record in receiver {
+                write_record(&mut writer, record, &writer_dropped);
+            }
+        });
+    }
+    AuditLogState { dir: Some(dir), dropped }
+}
+
+/// Verify the audit log hash chain across all retained files, with the records this run dropped.
+#[tauri::command]
+pub async fn verify_audit_log(state: State<'_, AuditLogState>) -> Result<AuditVerification, BitBurnError> {
+    let dir = state.dir.clone().ok_or_else(|| BitBurnError::internal("Audit log directory is unavailable"))?;
+    let mut verification = tauri::async_runtime::spawn_blocking(move || verify_chain(&dir))
+        .await
+        .map_err(|e| BitBurnError::internal(format!("verify_audit_log task join error: {}", e)))?;
+    verification.dropped_records = state.dropped.load(Ordering::Relaxed);
+    Ok(verification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    fn write_events(writer: &mut AuditWriter, count: u64) {
+        for i in 0..count {
+            writer
+                .append("wipe_files_end", json!({"status": "success", "count": i}), 1_000 + i)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn chain_verifies_and_counts_entries() {
+        let dir = create_test_dir().unwrap();
+        let mut writer = AuditWriter::open(dir.clone(), MAX_FILE_BYTES, MAX_FILES);
+        write_events(&mut writer, 5);
+
+        let verification = verify_chain(&dir);
+        assert!(verification.valid);
+        assert_eq!((verification.files, verification.entries), (1, 6));
+        assert!(!verification.truncated_history);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn rotation_links_new_file_to_the_previous_one() {
+        let dir = create_test_dir().unwrap();
+        let mut writer = AuditWriter::open(dir.clone(), 600, MAX_FILES);
+        write_events(&mut writer, 12);
+
+        let files = log_files(&dir);
+        assert!(files.len() > 1);
+        let last_of_first = fs::read_to_string(&files[0].1).unwrap().lines().last().unwrap().to_string();
+        let first_of_second = fs::read_to_string(&files[1].1).unwrap().lines().next().unwrap().to_string();
+        let last: Value = serde_json::from_str(&last_of_first).unwrap();
+        let genesis: Value = serde_json::from_str(&first_of_second).unwrap();
+        assert_eq!(genesis["event"], GENESIS_EVENT);
+        assert_eq!(genesis["prev_hash"], last["entry_hash"]);
+        assert!(verify_chain(&dir).valid);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn reopening_continues_the_chain() {
+        let dir = create_test_dir().unwrap();
+        write_events(&mut AuditWriter::open(dir.clone(), MAX_FILE_BYTES, MAX_FILES), 2);
+        write_events(&mut AuditWriter::open(dir.clone(), MAX_FILE_BYTES, MAX_FILES), 2);
+
+        let verification = verify_chain(&dir);
+        assert!(verification.valid);
+        assert_eq!((verification.files, verification.entries), (1, 5));
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn corrupted_middle_line_is_pinpointed() {
+        let dir = create_test_dir().unwrap();
+        let mut writer = AuditWriter::open(dir.clone(), MAX_FILE_BYTES, MAX_FILES);
+        write_events(&mut writer, 6);
+        let path = log_files(&dir)[0].1.clone();
+        let contents = fs::read_to_string(&path).unwrap();
+        let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+        lines[3] = lines[3].replace("\"count\":2", "\"count\":9");
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        let verification = verify_chain(&dir);
+        assert!(!verification.valid);
+        assert_eq!(verification.entries, 3);
+        let broken = verification.broken.unwrap();
+        assert_eq!(broken.file, file_name(1));
+        assert_eq!(broken.line, 4);
+        assert_eq!(broken.reason, "Entry contents do not match entry_hash");
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn deleted_line_breaks_the_following_link() {
+        let dir = create_test_dir().unwrap();
+        let mut writer = AuditWriter::open(dir.clone(), MAX_FILE_BYTES, MAX_FILES);
+        write_events(&mut writer, 4);
+        let path = log_files(&dir)[0].1.clone();
+        let mut lines: Vec<String> = fs::read_to_string(&path).unwrap().lines().map(str::to_string).collect();
+        lines.remove(2);
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        let broken = verify_chain(&dir).broken.unwrap();
+        assert_eq!(broken.line, 3);
+        assert_eq!(broken.reason, "prev_hash does not match the previous entry");
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn records_that_cannot_be_written_are_counted() {
+        let dir = create_test_dir().unwrap();
+        // A file where the log directory should be, so no log file can be created.
+        let blocked = dir.join("audit");
+        fs::write(&blocked, b"not a directory").unwrap();
+        let mut writer = AuditWriter::open(blocked, MAX_FILE_BYTES, MAX_FILES);
+        let dropped = AtomicU64::new(0);
+        let record = |event: &str| AuditRecord { event: event.to_string(), fields: json!({}), timestamp_ms: 1_000 };
+
+        write_record(&mut writer, record("wipe_files_end"), &dropped);
+        write_record(&mut writer, record(WRITE_FAILED_EVENT), &dropped);
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn pruned_history_is_reported_but_still_valid() {
+        let dir = create_test_dir().unwrap();
+        let mut writer = AuditWriter::open(dir.clone(), 400, 2);
+        write_events(&mut writer, 20);
+
+        let verification = verify_chain(&dir);
+        assert_eq!(verification.files, 2);
+        assert!(verification.valid);
+        assert!(verification.truncated_history);
+        cleanup_test_dir(dir);
+    }
+}
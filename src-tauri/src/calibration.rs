@@ -0,0 +1,384 @@
+//! Algorithm calibration: time one pass of each kind of data on the target volume with a small
+//! sample file, then project how long every algorithm would take for the selection, so the
+//! confirmation dialog can show "NIST 800-88 Purge: ~22 min, Gutmann: ~4.5 h" before anyone
+//! commits to 35 passes.
+
+use bitburn_core::{
+    secure_wipe_file, CancellationToken, PassKind, PatternBuffer, TreeScan, WipeAlgorithm, WipeProgress, BUFFER_SIZE,
+};
+use rand::RngCore;
+use serde::Serialize;
+use serde_json::json;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::async_runtime::spawn_blocking;
+use tauri::State;
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::scratch;
+
+pub const DEFAULT_SAMPLE_MB: u64 = 16;
+pub const MAX_SAMPLE_MB: u64 = 1024;
+const SAMPLE_FILE: &str = "calibration.bin";
+/// Fixed pattern timed for `PassKind::Pattern`; Gutmann's 0x92 0x49 0x24 does not divide the chunk size.
+const SAMPLE_PATTERN: [u8; 3] = [0x92, 0x49, 0x24];
+
+const ALGORITHMS: [WipeAlgorithm; 5] = [
+    WipeAlgorithm::NistClear,
+    WipeAlgorithm::NistPurge,
+    WipeAlgorithm::GutmannLite,
+    WipeAlgorithm::Gutmann,
+    WipeAlgorithm::Random,
+];
+
+/// Measured write rate of each pass kind, in bytes per second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PassRates {
+    pub zeros: u64,
+    pub pattern: u64,
+    pub random: u64,
+}
+
+impl PassRates {
+    /// Rates from the time one pass of `sample_bytes` took per kind. A pass too quick for the
+    /// clock counts as one millisecond so the rate stays finite.
+    pub fn from_timings(sample_bytes: u64, zeros: Duration, pattern: Duration, random: Duration) -> Self {
+        let rate = |elapsed: Duration| (sample_bytes as f64 / elapsed.max(Duration::from_millis(1)).as_secs_f64()) as u64;
+        PassRates {
+            zeros: rate(zeros),
+            pattern: rate(pattern),
+            random: rate(random),
+        }
+    }
+
+    fn rate(&self, kind: PassKind) -> u64 {
+        match kind {
+            PassKind::Zeros => self.zeros,
+            PassKind::Pattern => self.pattern,
+            PassKind::Random => self.random,
+        }
+    }
+}
+
+/// How long one algorithm would take for the selection at the measured rates.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlgorithmProjection {
+    pub algorithm: WipeAlgorithm,
+    pub passes: u32,
+    /// `None` when a pass kind it needs could not be measured.
+    pub seconds: Option<u64>,
+    /// e.g. `~22 min`.
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Calibration {
+    pub path: String,
+    pub sample_bytes: u64,
+    pub target_bytes: u64,
+    pub rates: PassRates,
+    pub projections: Vec<AlgorithmProjection>,
+    /// One line for the confirmation dialog: `NIST 800-88 Clear: ~8 min, ...`.
+    pub summary: String,
+}
+
+/// Project every algorithm at its default pass count (`random_passes` for Random) over `target_bytes`.
+pub fn project(rates: &PassRates, target_bytes: u64, random_passes: u32) -> Vec<AlgorithmProjection> {
+    ALGORITHMS
+        .iter()
+        .map(|algorithm| {
+            let passes = match algorithm {
+                WipeAlgorithm::Random => random_passes.max(1),
+                _ => algorithm.default_passes(),
+            };
+            let seconds = algorithm
+                .pass_kinds(passes)
+                .into_iter()
+                .map(|kind| match rates.rate(kind) {
+                    0 => None,
+                    rate => Some(target_bytes as f64 / rate as f64),
+                })
+                .sum::<Option<f64>>()
+                .map(|seconds| seconds.ceil() as u64);
+            AlgorithmProjection {
+                algorithm: algorithm.clone(),
+                passes,
+                seconds,
+                label: seconds.map(approximate_duration).unwrap_or_else(|| "unknown".to_string()),
+            }
+        })
+        .collect()
+}
+
+/// `~40 s`, `~22 min` or `~4.5 h`.
+pub fn approximate_duration(seconds: u64) -> String {
+    if seconds < 60 {
+        format!("~{} s", seconds.max(1))
+    } else if seconds < 60 * 60 {
+        format!("~{} min", (seconds as f64 / 60.0).round() as u64)
+    } else {
+        format!("~{:.1} h", seconds as f64 / 3600.0)
+    }
+}
+
+fn summary(projections: &[AlgorithmProjection]) -> String {
+    projections
+        .iter()
+        .map(|p| format!("{}: {}", p.algorithm.display_name(), p.label))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Bytes a wipe of `paths` would overwrite; folders are walked.
+fn selection_bytes(paths: &[PathBuf]) -> u64 {
+    paths
+        .iter()
+        .map(|path| match fs::metadata(path) {
+            Ok(metadata) if metadata.is_dir() => TreeScan::walk(path).bytes,
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Directory the sample goes in: beside `path`, on the same volume but outside any folder
+/// that is about to be wiped. A volume root has no parent and holds the sample itself.
+fn sample_parent(path: &Path) -> PathBuf {
+    path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(path).to_path_buf()
+}
+
+/// Overwrite the first `bytes` of `file` with `kind`, flush it, and return how long that took.
+fn time_pass(file: &mut File, kind: PassKind, bytes: u64, cancel: &CancellationToken) -> Result<Duration, BitBurnError> {
+    let zeros = vec![0u8; BUFFER_SIZE as usize];
+    let mut random = vec![0u8; BUFFER_SIZE as usize];
+    let mut pattern = PatternBuffer::new(&SAMPLE_PATTERN, BUFFER_SIZE as usize);
+    let mut rng = rand::thread_rng();
+    let io_error = |e: io::Error| BitBurnError::io(&e, None);
+
+    file.seek(SeekFrom::Start(0)).map_err(io_error)?;
+    let started = Instant::now();
+    let mut written = 0u64;
+    while written < bytes {
+        if cancel.is_cancelled() {
+            return Err(BitBurnError::Cancelled);
+        }
+        let len = BUFFER_SIZE.min(bytes - written) as usize;
+        let chunk = match kind {
+            PassKind::Zeros => &zeros[..len],
+            PassKind::Pattern => pattern.next_chunk(len),
+            PassKind::Random => {
+                rng.fill_bytes(&mut random[..len]);
+                &random[..len]
+            }
+        };
+        file.write_all(chunk).map_err(io_error)?;
+        written += len as u64;
+    }
+    file.sync_all().map_err(io_error)?;
+    Ok(started.elapsed())
+}
+
+/// Write a `sample_bytes` sample in scratch space beside `path` and time an overwrite of it with
+/// each pass kind. The sample is written once untimed first, so every timed pass overwrites
+/// allocated blocks the way a wipe does. It is securely deleted afterwards.
+pub fn measure(path: &Path, sample_bytes: u64, cancel: &CancellationToken) -> Result<PassRates, BitBurnError> {
+    let scratch = scratch::ensure_scratch_space_on(&sample_parent(path), sample_bytes)?;
+    let sample = scratch.path().join(SAMPLE_FILE);
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&sample)
+        .map_err(|e| BitBurnError::io(&e, Some(&sample)))?;
+
+    let timings = [PassKind::Random, PassKind::Zeros, PassKind::Pattern, PassKind::Random]
+        .into_iter()
+        .map(|kind| time_pass(&mut file, kind, sample_bytes, cancel))
+        .collect::<Result<Vec<_>, _>>();
+    drop(file);
+    // The scratch directory removes the sample whatever happens; wiping it first leaves nothing
+    // of the timing data in the freed blocks.
+    if let Err(e) = secure_wipe_file(&sample, 1, &WipeAlgorithm::NistClear, cancel, |_: WipeProgress| {}) {
+        log_event("calibration_sample_wipe_failed", json!({"path": sample.to_string_lossy(), "message": e.to_string()}));
+    }
+    let timings = timings?;
+    Ok(PassRates::from_timings(sample_bytes, timings[1], timings[2], timings[3]))
+}
+
+/// The calibration in progress, so `cancel_calibration` can stop it.
+#[derive(Default)]
+pub struct CalibrationState {
+    running: Mutex<Option<CancellationToken>>,
+}
+
+impl CalibrationState {
+    /// Token for a new calibration; one still running is cancelled, as its numbers are no longer wanted.
+    fn begin(&self) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Some(previous) = self.running.lock().unwrap_or_else(|p| p.into_inner()).replace(token.clone()) {
+            previous.cancel();
+        }
+        token
+    }
+
+    fn cancel(&self) -> bool {
+        match self.running.lock().unwrap_or_else(|p| p.into_inner()).take() {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Time each kind of pass on the volume holding `path` and project every algorithm's duration
+/// for `target_bytes`, or for the size of `paths` (the pending selection, `path` by default).
+#[tauri::command]
+pub async fn calibrate_algorithms(
+    state: State<'_, CalibrationState>,
+    path: String,
+    sample_mb: Option<u64>,
+    target_bytes: Option<u64>,
+    paths: Option<Vec<String>>,
+    passes: Option<u32>,
+) -> Result<Calibration, BitBurnError> {
+    let sample_bytes = sample_mb.unwrap_or(DEFAULT_SAMPLE_MB).clamp(1, MAX_SAMPLE_MB) * 1024 * 1024;
+    let cancel = state.begin();
+    let location = PathBuf::from(&path);
+    let selection: Vec<PathBuf> = match paths {
+        Some(paths) => paths.into_iter().map(PathBuf::from).collect(),
+        None => vec![location.clone()],
+    };
+
+    let task_cancel = cancel.clone();
+    let (rates, target_bytes) = spawn_blocking(move || -> Result<(PassRates, u64), BitBurnError> {
+        let target_bytes = target_bytes.unwrap_or_else(|| selection_bytes(&selection));
+        Ok((measure(&location, sample_bytes, &task_cancel)?, target_bytes))
+    })
+    .await
+    .map_err(|e| BitBurnError::internal(format!("calibrate_algorithms join error: {}", e)))??;
+    // Clear the slot unless a newer calibration has already replaced this one.
+    state.running.lock().unwrap_or_else(|p| p.into_inner()).take_if(|running| Arc::ptr_eq(running.flag(), cancel.flag()));
+
+    let projections = project(&rates, target_bytes, passes.unwrap_or(WipeAlgorithm::Random.default_passes()));
+    log_event(
+        "algorithms_calibrated",
+        json!({"path": path, "sample_bytes": sample_bytes, "target_bytes": target_bytes, "rates": rates}),
+    );
+    Ok(Calibration {
+        path,
+        sample_bytes,
+        target_bytes,
+        rates,
+        summary: summary(&projections),
+        projections,
+    })
+}
+
+/// Stop the running calibration. Returns false when none is running.
+#[tauri::command]
+pub async fn cancel_calibration(state: State<'_, CalibrationState>) -> Result<bool, BitBurnError> {
+    Ok(state.cancel())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    const MB: u64 = 1024 * 1024;
+
+    fn seconds(projections: &[AlgorithmProjection], algorithm: WipeAlgorithm) -> Option<u64> {
+        projections.iter().find(|p| p.algorithm == algorithm).unwrap().seconds
+    }
+
+    #[test]
+    fn gutmann_variants_split_their_passes_by_kind() {
+        let count = |kinds: &[PassKind], kind: PassKind| kinds.iter().filter(|&&k| k == kind).count();
+        let gutmann = WipeAlgorithm::Gutmann.pass_kinds(35);
+        assert_eq!(
+            (count(&gutmann, PassKind::Random), count(&gutmann, PassKind::Zeros), count(&gutmann, PassKind::Pattern)),
+            (8, 1, 26)
+        );
+        let lite = WipeAlgorithm::GutmannLite.pass_kinds(13);
+        assert_eq!((count(&lite, PassKind::Random), count(&lite, PassKind::Pattern)), (8, 5));
+        assert_eq!(WipeAlgorithm::Random.pass_kinds(7), vec![PassKind::Random; 7]);
+    }
+
+    #[test]
+    fn projections_sum_each_pass_at_its_measured_rate() {
+        // 16 MB took 0.1 s of zeros, 0.2 s of pattern and 0.4 s of random: 160, 80 and 40 MB/s.
+        let rates = PassRates::from_timings(
+            16 * MB,
+            Duration::from_millis(100),
+            Duration::from_millis(200),
+            Duration::from_millis(400),
+        );
+        assert_eq!(rates, PassRates { zeros: 160 * MB, pattern: 80 * MB, random: 40 * MB });
+
+        let projections = project(&rates, 8000 * MB, 5);
+        assert_eq!(seconds(&projections, WipeAlgorithm::NistClear), Some(50));
+        assert_eq!(seconds(&projections, WipeAlgorithm::NistPurge), Some(50 + 100 + 200));
+        assert_eq!(seconds(&projections, WipeAlgorithm::GutmannLite), Some(8 * 200 + 5 * 100));
+        assert_eq!(seconds(&projections, WipeAlgorithm::Gutmann), Some(8 * 200 + 50 + 26 * 100));
+        assert_eq!(seconds(&projections, WipeAlgorithm::Random), Some(5 * 200));
+
+        let random = projections.iter().find(|p| p.algorithm == WipeAlgorithm::Random).unwrap();
+        assert_eq!((random.passes, random.label.as_str()), (5, "~17 min"));
+        assert!(summary(&projections).starts_with("NIST 800-88 Clear: ~50 s, NIST 800-88 Purge: ~6 min"));
+    }
+
+    #[test]
+    fn unmeasured_pass_kinds_leave_the_projection_unknown() {
+        let rates = PassRates { zeros: 100 * MB, pattern: 0, random: 100 * MB };
+        let projections = project(&rates, 100 * MB, 3);
+        assert_eq!(seconds(&projections, WipeAlgorithm::NistClear), Some(1));
+        assert_eq!(seconds(&projections, WipeAlgorithm::Random), Some(3));
+        for algorithm in [WipeAlgorithm::NistPurge, WipeAlgorithm::Gutmann, WipeAlgorithm::GutmannLite] {
+            let projection = projections.iter().find(|p| p.algorithm == algorithm).unwrap();
+            assert_eq!((projection.seconds, projection.label.as_str()), (None, "unknown"));
+        }
+    }
+
+    #[test]
+    fn durations_are_approximated_in_the_largest_sensible_unit() {
+        assert_eq!(approximate_duration(0), "~1 s");
+        assert_eq!(approximate_duration(40), "~40 s");
+        assert_eq!(approximate_duration(89), "~1 min");
+        assert_eq!(approximate_duration(22 * 60 + 20), "~22 min");
+        assert_eq!(approximate_duration(4 * 3600 + 1800), "~4.5 h");
+    }
+
+    #[test]
+    fn measuring_times_every_kind_and_leaves_no_sample_behind() {
+        let dir = create_test_dir().unwrap();
+        let target = dir.join("selection.bin");
+
+        let rates = measure(&target, MB, &CancellationToken::new()).expect("calibration on a temp dir");
+        assert!(rates.zeros > 0 && rates.pattern > 0 && rates.random > 0);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert!(matches!(measure(&target, MB, &cancel), Err(BitBurnError::Cancelled)));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        cleanup_test_dir(&dir);
+    }
+
+    #[test]
+    fn a_new_calibration_cancels_the_one_still_running() {
+        let state = CalibrationState::default();
+        let first = state.begin();
+        let second = state.begin();
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+        assert!(state.cancel());
+        assert!(second.is_cancelled());
+        assert!(!state.cancel());
+    }
+}
@@ -0,0 +1,304 @@
+//! Free-space wipes cancelled during the fill. By then the temp file holds the volume's free space
+//! with one pass of fill data, short of the passes asked for, so it is kept rather than deleted:
+//! `finalize_cancelled_free_space` either releases the space as it is or first finishes a minimal
+//! wipe of one zero pass over what was written. Fills still waiting when the app exits are released
+//! as they are by `release_all`, from the app's `RunEvent::Exit`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use tauri::async_runtime::spawn_blocking;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::consent::Consent;
+use crate::error::BitBurnError;
+use crate::messages;
+use crate::operations::{OperationGuard, OperationKind, OperationRegistry};
+use crate::report::WipeReport;
+use crate::scratch::ScratchDir;
+use crate::{log_event, progress_coalescer_for, WipeResult};
+use bitburn_core::{
+    finish_wipe, open_wipe_target, overwrite_target_with, CancellationToken, FileWipeProgressRecord, OverwriteOptions,
+    ProgressSink, WipeAlgorithm, WipeError,
+};
+
+/// What to do with the space a cancelled fill still holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinalizeAction {
+    /// Delete the temp file now; the space keeps only the fill, and the wipe is incomplete.
+    ReleaseSpace,
+    /// Overwrite everything the fill wrote once with zeros, then delete it.
+    FinishMinimalWipe,
+}
+
+/// What a report says about a free-space wipe cancelled during its fill.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CancelledFill {
+    pub bytes_written: u64,
+    /// The fill and every overwrite pass the wipe was started with.
+    pub passes_requested: u32,
+    /// Passes the written space actually received, the fill included.
+    pub passes_received: u32,
+    /// `None` while the choice is still to be made.
+    pub action: Option<FinalizeAction>,
+}
+
+/// A cancelled fill waiting for `finalize_cancelled_free_space`.
+#[derive(Debug)]
+pub struct PendingFill {
+    pub volume: String,
+    pub temp_file: PathBuf,
+    /// Holds the temp file; dropping it releases the space.
+    pub scratch: ScratchDir,
+    pub summary: CancelledFill,
+    pub consent: Option<Consent>,
+}
+
+/// Cancelled fills by the id of the operation that was cancelled.
+#[derive(Debug, Default)]
+pub struct CancelledFills {
+    pending: Mutex<HashMap<String, PendingFill>>,
+}
+
+impl CancelledFills {
+    pub fn park(&self, operation_id: &str, fill: PendingFill) {
+        self.lock().insert(operation_id.to_string(), fill);
+    }
+
+    pub fn take(&self, operation_id: &str) -> Option<PendingFill> {
+        self.lock().remove(operation_id)
+    }
+
+    /// Release the space of every fill still waiting, as it is; called as the app exits, since
+    /// managed state is never dropped.
+    pub fn release_all(&self) {
+        for (operation_id, fill) in self.lock().drain() {
+            log_event(
+                "wipe_free_space_released_on_exit",
+                json!({"operation_id": operation_id, "path": fill.volume, "bytes_written": fill.summary.bytes_written}),
+            );
+            if let Err(e) = fs::remove_file(&fill.temp_file) {
+                log_event("cancelled_fill_cleanup_failed", json!({"path": fill.temp_file.to_string_lossy(), "message": e.to_string()}));
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, PendingFill>> {
+        self.pending.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The result of a fill cancelled after writing `fill.summary.bytes_written` bytes: cancelled, with
+/// a report offering the choice. The fill is kept for `finalize_cancelled_free_space`; with nothing
+/// written, or nowhere to keep it, its space is released at once.
+pub fn park<R: Runtime>(app: &AppHandle<R>, operation_id: &str, fill: PendingFill) -> WipeResult {
+    let summary = fill.summary.clone();
+    log_event(
+        "wipe_free_space_cancelled",
+        json!({"path": fill.volume, "bytes_written": summary.bytes_written, "passes_requested": summary.passes_requested}),
+    );
+    let mut report = WipeReport::new(operation_id, None);
+    report.consent = fill.consent.clone();
+    match app.try_state::<CancelledFills>() {
+        Some(fills) if summary.bytes_written > 0 => {
+            fills.park(operation_id, fill);
+            report.cancelled_fill = Some(summary);
+        }
+        _ => drop(fill),
+    }
+    WipeResult::failure(BitBurnError::Cancelled).with_report(report)
+}
+
+/// Carry out `action` on `fill` and release its space. A minimal wipe stopped by `cancel` still
+/// releases it, with the passes that were finished.
+pub fn finalize<S: ProgressSink>(
+    fill: PendingFill,
+    action: FinalizeAction,
+    cancel: &CancellationToken,
+    sink: S,
+) -> Result<CancelledFill, WipeError> {
+    let mut summary = CancelledFill { action: Some(action), ..fill.summary.clone() };
+    if action == FinalizeAction::FinishMinimalWipe {
+        let mut file = open_wipe_target(&fill.temp_file, 1)?;
+        let mut record = FileWipeProgressRecord::default();
+        let zeroed = overwrite_target_with(
+            &mut file,
+            1,
+            &WipeAlgorithm::NistClear,
+            cancel,
+            sink,
+            &mut record,
+            OverwriteOptions::default(),
+        );
+        match zeroed {
+            Ok(()) => {
+                summary.passes_received += 1;
+                return finish_wipe(file, &fill.temp_file, cancel).map(|()| summary);
+            }
+            Err(_) if cancel.is_cancelled() => {}
+            Err(e) => return Err(e),
+        }
+    }
+    fs::remove_file(&fill.temp_file).map_err(WipeError::Io)?;
+    drop(fill.scratch);
+    Ok(summary)
+}
+
+/// Release the space held by a free-space wipe cancelled during its fill, as it is or after one
+/// zero pass. The cancelled operation's report records the action and the passes the space got.
+#[tauri::command]
+pub async fn finalize_cancelled_free_space<R: Runtime>(
+    window: tauri::Window<R>,
+    operation_id: String,
+    action: FinalizeAction,
+) -> Result<WipeResult, BitBurnError> {
+    let app = window.app_handle().clone();
+    let fill = app
+        .state::<CancelledFills>()
+        .take(&operation_id)
+        .ok_or_else(|| BitBurnError::OperationNotFound { operation_id: operation_id.clone() })?;
+    let registry = app.state::<OperationRegistry>();
+    let operation = OperationGuard::begin(&app, &registry, OperationKind::FreeSpaceWipe, fill.volume.clone());
+    let cancel = CancellationToken::from(operation.cancel_flag());
+    let coalescer = progress_coalescer_for(&app, window.label(), operation.id(), operation.diagnostics());
+    let cancelled = operation.cancel_flag();
+    let volume = fill.volume.clone();
+    let temp_file = fill.temp_file.clone();
+    let consent = fill.consent.clone();
+
+    let finalized = spawn_blocking(move || {
+        let sink = |progress: bitburn_core::WipeProgress| {
+            if !cancelled.load(Ordering::SeqCst) {
+                coalescer.update(progress);
+            }
+        };
+        finalize(fill, action, &cancel, sink)
+    })
+    .await
+    .map_err(|e| BitBurnError::internal(format!("finalize_cancelled_free_space task join error: {}", e)))?;
+
+    let result = match finalized {
+        Ok(summary) => {
+            log_event(
+                "wipe_free_space_finalized",
+                json!({"path": volume, "action": action, "passes_received": summary.passes_received}),
+            );
+            let message = match summary.action {
+                Some(FinalizeAction::FinishMinimalWipe) if summary.passes_received > 1 => {
+                    messages::FREE_SPACE_MINIMAL_WIPE_FINISHED.render(json!({
+                        "passes_received": summary.passes_received,
+                        "passes_requested": summary.passes_requested,
+                    }))
+                }
+                _ => messages::FREE_SPACE_RELEASED_INCOMPLETE.render(json!({
+                    "passes_received": summary.passes_received,
+                    "passes_requested": summary.passes_requested,
+                })),
+            };
+            let mut report = WipeReport::new(operation_id.as_str(), None);
+            report.cancelled_fill = Some(summary);
+            report.consent = consent;
+            registry.attach_report(&operation_id, report.clone());
+            WipeResult::success(message).with_report(report)
+        }
+        Err(e) => {
+            let error = BitBurnError::from_wipe_error(e, &temp_file);
+            log_event("wipe_free_space_finalize_failed", json!({"path": volume, "code": error.code()}));
+            WipeResult::failure(error)
+        }
+    };
+    operation.record_result(&result);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scratch::ensure_scratch_space_on;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    fn pending(contents: &[u8]) -> PendingFill {
+        let volume = create_test_dir().unwrap();
+        let scratch = ensure_scratch_space_on(&volume, 0).unwrap();
+        let temp_file = scratch.path().join("BitBurn_temp_wipe_file");
+        fs::write(&temp_file, contents).unwrap();
+        PendingFill {
+            volume: volume.to_string_lossy().to_string(),
+            temp_file,
+            scratch,
+            summary: CancelledFill {
+                bytes_written: contents.len() as u64,
+                passes_requested: 4,
+                passes_received: 1,
+                action: None,
+            },
+            consent: None,
+        }
+    }
+
+    #[test]
+    fn releasing_the_space_deletes_the_fill_and_records_the_single_pass() {
+        let fill = pending(&[0x5Au8; 8192]);
+        let volume = PathBuf::from(&fill.volume);
+        let temp_file = fill.temp_file.clone();
+        let mut reports = 0;
+        let summary = finalize(fill, FinalizeAction::ReleaseSpace, &CancellationToken::new(), |_| reports += 1).unwrap();
+
+        assert!(!temp_file.exists());
+        assert_eq!(reports, 0, "nothing is written");
+        assert_eq!(summary.action, Some(FinalizeAction::ReleaseSpace));
+        assert_eq!((summary.passes_received, summary.passes_requested, summary.bytes_written), (1, 4, 8192));
+        cleanup_test_dir(volume);
+    }
+
+    #[test]
+    fn a_minimal_wipe_zeroes_what_was_written_before_deleting_it() {
+        let fill = pending(&[0x5Au8; 8192]);
+        let volume = PathBuf::from(&fill.volume);
+        let temp_file = fill.temp_file.clone();
+        let mut last = None;
+        let summary = finalize(fill, FinalizeAction::FinishMinimalWipe, &CancellationToken::new(), |progress| {
+            last = Some(progress)
+        })
+        .unwrap();
+
+        assert!(!temp_file.exists());
+        assert_eq!(last.map(|p| p.bytes_processed), Some(8192), "the zero pass covered the fill");
+        assert_eq!(summary.action, Some(FinalizeAction::FinishMinimalWipe));
+        assert_eq!(summary.passes_received, 2);
+
+        // Stopped before its zero pass, it still releases the space and says the pass is missing.
+        let fill = pending(&[0x5Au8; 8192]);
+        let cancelled_volume = PathBuf::from(&fill.volume);
+        let temp_file = fill.temp_file.clone();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let summary = finalize(fill, FinalizeAction::FinishMinimalWipe, &cancel, |_| {}).unwrap();
+        assert!(!temp_file.exists());
+        assert_eq!(summary.passes_received, 1);
+        cleanup_test_dir(volume);
+        cleanup_test_dir(cancelled_volume);
+    }
+
+    #[test]
+    fn fills_still_waiting_at_exit_are_released() {
+        let fills = CancelledFills::default();
+        let (first, second) = (pending(b"fill"), pending(b"fill"));
+        let volumes = [PathBuf::from(&first.volume), PathBuf::from(&second.volume)];
+        let scratch_dirs = [first.scratch.path().to_path_buf(), second.scratch.path().to_path_buf()];
+        fills.park("op-a", first);
+        fills.park("op-b", second);
+
+        fills.release_all();
+        assert!(fills.take("op-a").is_none() && fills.take("op-b").is_none());
+        assert!(scratch_dirs.iter().all(|dir| !dir.exists()), "the temp files and their folders are gone");
+        volumes.iter().for_each(|volume| {
+            cleanup_test_dir(volume);
+        });
+    }
+}
@@ -0,0 +1,275 @@
+//! One-shot capability probe for the first-run experience. Every probe runs on its own, so
+//! a failing probe reports an error in its own field instead of failing the whole command.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use sysinfo::{DiskExt, DiskKind, System, SystemExt};
+use tauri::async_runtime::spawn_blocking;
+use tauri::State;
+
+use crate::error::BitBurnError;
+use crate::messages::Message;
+use crate::platform::{autostart, context_menu, elevation};
+use crate::scratch;
+use crate::volume_history::{FreeSpaceWipeRecord, HistoryStore, WipeHistory};
+use crate::{AutostartStatus, PlatformInfo, FREE_SPACE_TEMP_FILE};
+
+/// Result of a single probe: `value` when it succeeded, `error` when it did not.
+#[derive(Serialize)]
+pub struct Probe<T> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<BitBurnError>,
+}
+
+impl<T> From<Result<T, BitBurnError>> for Probe<T> {
+    fn from(result: Result<T, BitBurnError>) -> Self {
+        match result {
+            Ok(value) => Probe { value: Some(value), error: None },
+            Err(error) => Probe { value: None, error: Some(error) },
+        }
+    }
+}
+
+/// Shell integration as the first-run wizard needs it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextMenuCapability {
+    /// This platform has a context-menu integration at all.
+    pub available: bool,
+    pub registered: bool,
+    /// Entries exist but launch a different (moved or deleted) copy of BitBurn.
+    pub stale: bool,
+    #[serde(flatten)]
+    pub message: Message,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriveMedia {
+    Ssd,
+    Hdd,
+    Removable,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DriveCapability {
+    pub mount_point: String,
+    pub name: String,
+    pub file_system: String,
+    pub media: DriveMedia,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+    /// Filled in by `probe_capabilities` from the volume's wipe history.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_free_space_wipe: Option<FreeSpaceWipeRecord>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DriveSummary {
+    pub drives: Vec<DriveCapability>,
+    /// Every fixed drive is an SSD, where overwriting cannot guarantee erasure.
+    pub ssd_only: bool,
+}
+
+/// BitBurn does not ship a notification plugin, so permission is never requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationPermission {
+    Unavailable,
+}
+
+/// Everything `probe_capabilities` reports, one probe per field.
+#[derive(Serialize)]
+pub struct Capabilities {
+    pub platform: Probe<PlatformInfo>,
+    pub elevated: Probe<bool>,
+    pub context_menu: Probe<ContextMenuCapability>,
+    pub autostart: Probe<AutostartStatus>,
+    pub drives: Probe<DriveSummary>,
+    pub notifications: Probe<NotificationPermission>,
+    /// Free-space temp files left at drive roots by an interrupted wipe.
+    pub orphaned_temp_files: Probe<Vec<String>>,
+}
+
+fn media_of(kind: DiskKind, removable: bool) -> DriveMedia {
+    if removable {
+        return DriveMedia::Removable;
+    }
+    match kind {
+        DiskKind::SSD => DriveMedia::Ssd,
+        DiskKind::HDD => DriveMedia::Hdd,
+        DiskKind::Unknown(_) => DriveMedia::Unknown,
+    }
+}
+
+fn summarize_drives(drives: Vec<DriveCapability>) -> DriveSummary {
+    let mut fixed = drives.iter().filter(|drive| drive.media != DriveMedia::Removable).peekable();
+    let ssd_only = fixed.peek().is_some() && fixed.all(|drive| drive.media == DriveMedia::Ssd);
+    DriveSummary { drives, ssd_only }
+}
+
+pub(crate) fn list_drives() -> Result<Vec<DriveCapability>, BitBurnError> {
+    let mut sys = System::new();
+    sys.refresh_disks_list();
+    let drives: Vec<DriveCapability> = sys
+        .disks()
+        .iter()
+        .map(|disk| DriveCapability {
+            mount_point: disk.mount_point().to_string_lossy().to_string(),
+            name: disk.name().to_string_lossy().to_string(),
+            file_system: String::from_utf8_lossy(disk.file_system()).to_string(),
+            media: media_of(disk.kind(), disk.is_removable()),
+            total_bytes: disk.total_space(),
+            available_bytes: disk.available_space(),
+            last_free_space_wipe: None,
+        })
+        .collect();
+    if drives.is_empty() {
+        return Err(BitBurnError::DiskInfoUnavailable { path: "any drive".to_string() });
+    }
+    Ok(drives)
+}
+
+/// Drive holding `path`: the one with the longest mount point it starts with.
+pub(crate) fn drive_holding<'a>(drives: &'a [DriveCapability], path: &str) -> Option<&'a DriveCapability> {
+    drives
+        .iter()
+        .filter(|drive| Path::new(path).starts_with(&drive.mount_point))
+        .max_by_key(|drive| drive.mount_point.len())
+}
+
+/// Free-space temp files left at `roots` by older versions, and scratch directories there that
+/// no running wipe owns.
+fn find_orphaned_temp_files<'a>(roots: impl IntoIterator<Item = &'a Path>) -> Vec<String> {
+    roots
+        .into_iter()
+        .flat_map(|root| {
+            let legacy = Some(root.join(FREE_SPACE_TEMP_FILE)).filter(|temp_file| temp_file.is_file());
+            legacy.into_iter().chain(scratch::orphans_in(root))
+        })
+        .map(|temp_file| temp_file.to_string_lossy().to_string())
+        .collect()
+}
+
+pub(crate) async fn run_blocking<T, F>(probe: F) -> Result<T, BitBurnError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, BitBurnError> + Send + 'static,
+{
+    spawn_blocking(probe)
+        .await
+        .map_err(|e| BitBurnError::internal(e.to_string()))?
+}
+
+/// Probe everything the first-run wizard adapts to in one call.
+#[tauri::command]
+pub async fn probe_capabilities(history: State<'_, HistoryStore>) -> Result<Capabilities, BitBurnError> {
+    Ok(probe_all(history.history()).await)
+}
+
+async fn probe_all(history: WipeHistory) -> Capabilities {
+    let drives = run_blocking(move || list_drives().map(|drives| history.annotate(drives))).await;
+    let orphaned_temp_files = match &drives {
+        Ok(drives) => {
+            let roots: Vec<PathBuf> = drives.iter().map(|drive| PathBuf::from(&drive.mount_point)).collect();
+            run_blocking(move || Ok(find_orphaned_temp_files(roots.iter().map(PathBuf::as_path)))).await
+        }
+        Err(error) => Err(error.clone()),
+    };
+
+    let context_menu = context_menu::get_context_menu_status().await.map(|status| ContextMenuCapability {
+        available: cfg!(any(windows, target_os = "macos", target_os = "linux")),
+        registered: status.enabled,
+        stale: status.stale,
+        message: status.message,
+    });
+
+    Capabilities {
+        platform: crate::platform_info().await.into(),
+        elevated: Ok(elevation::is_elevated()).into(),
+        context_menu: context_menu.into(),
+        autostart: autostart::get_autostart_status().await.into(),
+        drives: drives.map(summarize_drives).into(),
+        notifications: Ok(NotificationPermission::Unavailable).into(),
+        orphaned_temp_files: orphaned_temp_files.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+    use serde_json::json;
+
+    fn drive(mount_point: &str, media: DriveMedia) -> DriveCapability {
+        DriveCapability {
+            mount_point: mount_point.to_string(),
+            name: String::new(),
+            file_system: "ntfs".to_string(),
+            media,
+            total_bytes: 100,
+            available_bytes: 40,
+            last_free_space_wipe: None,
+        }
+    }
+
+    #[test]
+    fn probes_serialize_as_value_or_error() {
+        let ok: Probe<bool> = Ok(true).into();
+        assert_eq!(serde_json::to_value(&ok).unwrap(), json!({"value": true}));
+
+        let failed: Probe<bool> = Err(BitBurnError::MissingExecutablePath).into();
+        assert_eq!(
+            serde_json::to_value(&failed).unwrap(),
+            json!({"error": {"code": "missing_executable_path"}})
+        );
+    }
+
+    #[test]
+    fn drive_summary_serializes_media_and_flags_ssd_only_machines() {
+        let summary = summarize_drives(vec![drive("C:\\", DriveMedia::Ssd), drive("E:\\", DriveMedia::Removable)]);
+        assert!(summary.ssd_only, "removable drives do not count against ssd_only");
+        assert_eq!(serde_json::to_value(&summary.drives[0]).unwrap()["media"], json!("ssd"));
+
+        assert!(!summarize_drives(vec![drive("C:\\", DriveMedia::Ssd), drive("D:\\", DriveMedia::Hdd)]).ssd_only);
+        assert!(!summarize_drives(vec![drive("E:\\", DriveMedia::Removable)]).ssd_only);
+    }
+
+    #[test]
+    fn orphaned_temp_files_are_found_at_drive_roots() {
+        let base = create_test_dir().unwrap();
+        let (dirty, clean) = (base.join("dirty"), base.join("clean"));
+        std::fs::create_dir_all(&dirty).unwrap();
+        std::fs::create_dir_all(&clean).unwrap();
+        std::fs::write(dirty.join(FREE_SPACE_TEMP_FILE), b"left over").unwrap();
+
+        let found = find_orphaned_temp_files([dirty.as_path(), clean.as_path()]);
+        assert_eq!(found, vec![dirty.join(FREE_SPACE_TEMP_FILE).to_string_lossy().to_string()]);
+
+        let live = scratch::ensure_scratch_space_on(&clean, 0).unwrap();
+        let crashed = clean.join(format!("{}crashed", scratch::SCRATCH_PREFIX));
+        std::fs::create_dir(&crashed).unwrap();
+        let found = find_orphaned_temp_files([clean.as_path()]);
+        assert_eq!(found, vec![crashed.to_string_lossy().to_string()], "{:?} is still in use", live);
+
+        cleanup_test_dir(&base);
+    }
+
+    #[test]
+    fn every_probe_reports_a_value_or_an_error_on_this_platform() {
+        let capabilities = tauri::async_runtime::block_on(probe_all(WipeHistory::default()));
+        let serialized = serde_json::to_value(&capabilities).unwrap();
+        let fields = serialized.as_object().unwrap();
+        assert_eq!(fields.len(), 7);
+        for (name, probe) in fields {
+            let has_value = probe.get("value").is_some();
+            let has_error = probe.get("error").is_some();
+            assert!(has_value != has_error, "{} should carry exactly one of value or error: {}", name, probe);
+        }
+        assert!(capabilities.platform.value.is_some());
+        assert!(capabilities.elevated.value.is_some());
+        assert_eq!(capabilities.notifications.value, Some(NotificationPermission::Unavailable));
+    }
+}
@@ -0,0 +1,169 @@
+//! Companion files: the owner, lock, backup and swap files editors leave beside a document,
+//! which can hold some or all of its contents. With `companion_files` on, a batch wipes the
+//! ones it finds beside each selected file along with it and reports them under that file.
+//! The names looked for are the `COMPANION_PATTERNS` table, so more can be added there.
+
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::settings::SettingsStore;
+
+/// How a companion is named after the file it belongs to.
+#[derive(Debug, Clone, Copy)]
+pub enum CompanionName {
+    /// `prefix`, the file's name, then `suffix`.
+    Affixed { prefix: &'static str, suffix: &'static str },
+    /// The owner file Office keeps beside an open document, see `office_owner_name`.
+    OfficeOwner,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CompanionPattern {
+    /// What leaves the companion behind, for the log.
+    pub source: &'static str,
+    pub name: CompanionName,
+}
+
+impl CompanionPattern {
+    const fn new(source: &'static str, prefix: &'static str, suffix: &'static str) -> Self {
+        CompanionPattern { source, name: CompanionName::Affixed { prefix, suffix } }
+    }
+
+    /// The companion's name for a file named `name`, if the pattern applies to it.
+    pub fn name_for(&self, name: &str) -> Option<String> {
+        match self.name {
+            CompanionName::Affixed { prefix, suffix } => Some(format!("{}{}{}", prefix, name, suffix)),
+            CompanionName::OfficeOwner => office_owner_name(name),
+        }
+    }
+}
+
+/// Documents Office keeps an owner file beside while they are open.
+const OFFICE_EXTENSIONS: &[&str] = &[
+    "doc", "docx", "docm", "dot", "dotx", "dotm", "rtf", "xls", "xlsx", "xlsm", "xlsb", "xlt", "xltx", "xltm", "ppt",
+    "pptx", "pptm", "pot", "potx", "potm", "pps", "ppsx", "ppsm", "vsd", "vsdx", "mpp", "pub",
+];
+
+/// The owner file Office names after `name`: `~$` and the name, less the first character of a
+/// seven-character stem or the first two of a longer one. Only one name fits each document, so a
+/// `~$` file beside anything else is never taken for its companion.
+fn office_owner_name(name: &str) -> Option<String> {
+    let (stem, extension) = name.rsplit_once('.')?;
+    if !OFFICE_EXTENSIONS.iter().any(|known| extension.eq_ignore_ascii_case(known)) {
+        return None;
+    }
+    let skip = match stem.chars().count() {
+        0 => return None,
+        1..=6 => 0,
+        7 => 1,
+        _ => 2,
+    };
+    Some(format!("~${}", name.chars().skip(skip).collect::<String>()))
+}
+
+/// The companions looked for beside every selected file.
+pub const COMPANION_PATTERNS: &[CompanionPattern] = &[
+    CompanionPattern { source: "Microsoft Office owner file", name: CompanionName::OfficeOwner },
+    CompanionPattern::new("LibreOffice lock file", ".~lock.", "#"),
+    CompanionPattern::new("temporary copy", "", ".tmp"),
+    CompanionPattern::new("backup copy", "", ".bak"),
+    CompanionPattern::new("Vim swap file", ".", ".swp"),
+    CompanionPattern::new("Vim swap file", ".", ".swo"),
+    CompanionPattern::new("Emacs backup", "", "~"),
+    CompanionPattern::new("Emacs auto-save file", "#", "#"),
+];
+
+/// The companions of `path` that exist in its directory, in table order. Only regular files
+/// count; links and folders with a matching name are left alone.
+pub fn find(path: &Path) -> Vec<PathBuf> {
+    let (Some(directory), Some(name)) = (path.parent(), path.file_name().and_then(|name| name.to_str())) else {
+        return Vec::new();
+    };
+    let mut found: Vec<PathBuf> = Vec::new();
+    for pattern in COMPANION_PATTERNS {
+        let Some(companion) = pattern.name_for(name).map(|companion| directory.join(companion)) else {
+            continue;
+        };
+        let is_file = fs::symlink_metadata(&companion).is_ok_and(|metadata| metadata.file_type().is_file());
+        if is_file && companion != path && !found.contains(&companion) {
+            log_event(
+                "companion_file_found",
+                json!({"path": path.to_string_lossy(), "companion": companion.to_string_lossy(), "source": pattern.source}),
+            );
+            found.push(companion);
+        }
+    }
+    found
+}
+
+/// Wipe the companion files found beside each selected file along with it.
+#[tauri::command]
+pub async fn set_companion_files(settings: State<'_, SettingsStore>, enabled: bool) -> Result<bool, BitBurnError> {
+    let updated = settings.update(|s| s.companion_files = enabled)?;
+    log_event("companion_files_configured", json!({"enabled": updated.companion_files}));
+    Ok(updated.companion_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    #[test]
+    fn companions_are_named_after_the_file() {
+        let names: Vec<String> = COMPANION_PATTERNS.iter().filter_map(|p| p.name_for("quarterly.docx")).collect();
+        assert_eq!(
+            names,
+            [
+                "~$arterly.docx",
+                ".~lock.quarterly.docx#",
+                "quarterly.docx.tmp",
+                "quarterly.docx.bak",
+                ".quarterly.docx.swp",
+                ".quarterly.docx.swo",
+                "quarterly.docx~",
+                "#quarterly.docx#",
+            ]
+        );
+    }
+
+    #[test]
+    fn office_owner_files_follow_the_stem_length_and_only_for_office_documents() {
+        let owner = |name| office_owner_name(name);
+        assert_eq!(owner("report.docx").as_deref(), Some("~$report.docx"));
+        assert_eq!(owner("budget1.XLSX").as_deref(), Some("~$udget1.XLSX"));
+        assert_eq!(owner("quarterly.pptx").as_deref(), Some("~$arterly.pptx"));
+        assert_eq!(owner("notes.txt"), None, "not an Office document");
+        assert_eq!(owner("docx"), None);
+        assert_eq!(owner(".docx"), None);
+    }
+
+    #[test]
+    fn only_existing_regular_files_are_found() {
+        let dir = create_test_dir().unwrap();
+        let doc = dir.join("report.docx");
+        fs::write(&doc, b"contents").unwrap();
+        fs::write(dir.join("~$report.docx"), b"owner").unwrap();
+        fs::write(dir.join("report.docx.bak"), b"backup").unwrap();
+        fs::create_dir(dir.join(".report.docx.swp")).unwrap();
+        fs::write(dir.join("other.docx.bak"), b"unrelated").unwrap();
+        // Office would have dropped the first two characters of a longer name, not none.
+        let long = dir.join("quarterly.docx");
+        fs::write(&long, b"contents").unwrap();
+        fs::write(dir.join("~$quarterly.docx"), b"unrelated").unwrap();
+        // A `~$` file beside a document Office does not open is no owner file.
+        let text = dir.join("notes.txt");
+        fs::write(&text, b"contents").unwrap();
+        fs::write(dir.join("~$notes.txt"), b"unrelated").unwrap();
+
+        assert_eq!(find(&doc), vec![dir.join("~$report.docx"), dir.join("report.docx.bak")]);
+        assert!(find(&long).is_empty());
+        assert!(find(&text).is_empty());
+        assert!(find(&dir.join("missing.txt")).is_empty());
+        cleanup_test_dir(&dir);
+    }
+}
@@ -0,0 +1,207 @@
+//! Taskbar flash and system sound when an operation finishes while the window is in the
+//! background, for users who miss notifications under focus assist. Each is its own setting and
+//! independent of `reopen_on_completion`. What to do is decided by `decide`; doing it goes
+//! through `AlertOutput`, which is `FlashWindowEx` and `MessageBeep` on Windows and the
+//! window's attention request elsewhere, where there is no sound.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Listener, Manager, State, WebviewWindow};
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::operations::{OperationRegistry, OutcomeStatus};
+use crate::settings::SettingsStore;
+
+/// Times the taskbar button flashes; it then stays highlighted until the window is focused.
+pub const FLASH_COUNT: u32 = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompletionAlertSettings {
+    pub flash_taskbar: bool,
+    pub play_sound: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSound {
+    /// The system asterisk sound, for an operation that completed.
+    Asterisk,
+    /// The system exclamation sound, for one that failed.
+    Exclamation,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CompletionAlerts {
+    pub flash: bool,
+    pub sound: Option<AlertSound>,
+}
+
+/// What to do when an operation ends with `outcome`. Nothing while the window has focus, since
+/// the user is looking at it, and nothing for a cancellation, which the user asked for.
+pub fn decide(settings: CompletionAlertSettings, window_focused: bool, outcome: Option<OutcomeStatus>) -> CompletionAlerts {
+    let sound = match outcome {
+        _ if window_focused => return CompletionAlerts::default(),
+        Some(OutcomeStatus::Cancelled) => return CompletionAlerts::default(),
+        Some(OutcomeStatus::Completed) => AlertSound::Asterisk,
+        // An operation that ended without recording an outcome did not complete either.
+        Some(OutcomeStatus::Failed) | None => AlertSound::Exclamation,
+    };
+    CompletionAlerts {
+        flash: settings.flash_taskbar,
+        sound: settings.play_sound.then_some(sound),
+    }
+}
+
+/// Where alerts go; the platform calls sit behind it so `deliver` can be checked without them.
+pub trait AlertOutput {
+    fn flash(&self, count: u32);
+    fn play(&self, sound: AlertSound);
+}
+
+pub fn deliver(output: &impl AlertOutput, alerts: CompletionAlerts) {
+    if alerts.flash {
+        output.flash(FLASH_COUNT);
+    }
+    if let Some(sound) = alerts.sound {
+        output.play(sound);
+    }
+}
+
+/// Alerts for the main window.
+struct WindowAlerts(WebviewWindow);
+
+impl AlertOutput for WindowAlerts {
+    #[cfg(windows)]
+    fn flash(&self, count: u32) {
+        use windows_sys::Win32::UI::WindowsAndMessaging::{FlashWindowEx, FLASHWINFO, FLASHW_ALL};
+
+        let Ok(hwnd) = self.0.hwnd() else {
+            return;
+        };
+        let info = FLASHWINFO {
+            cbSize: std::mem::size_of::<FLASHWINFO>() as u32,
+            hwnd: hwnd.0 as _,
+            dwFlags: FLASHW_ALL,
+            uCount: count,
+            dwTimeout: 0,
+        };
+        unsafe { FlashWindowEx(&info) };
+    }
+
+    #[cfg(not(windows))]
+    fn flash(&self, _count: u32) {
+        let _ = self.0.request_user_attention(Some(tauri::UserAttentionType::Informational));
+    }
+
+    #[cfg(windows)]
+    fn play(&self, sound: AlertSound) {
+        use windows_sys::Win32::System::Diagnostics::Debug::MessageBeep;
+        use windows_sys::Win32::UI::WindowsAndMessaging::{MB_ICONASTERISK, MB_ICONEXCLAMATION};
+
+        let kind = match sound {
+            AlertSound::Asterisk => MB_ICONASTERISK,
+            AlertSound::Exclamation => MB_ICONEXCLAMATION,
+        };
+        unsafe { MessageBeep(kind) };
+    }
+
+    #[cfg(not(windows))]
+    fn play(&self, _sound: AlertSound) {}
+}
+
+/// Alert on every `operation_finished` as the settings ask.
+pub fn listen(app: &AppHandle) {
+    let app_handle = app.clone();
+    app.listen_any("operation_finished", move |event| {
+        let Some(settings) = app_handle.try_state::<SettingsStore>().map(|s| s.get().completion_alert) else {
+            return;
+        };
+        if !settings.flash_taskbar && !settings.play_sound {
+            return;
+        }
+        let Some(window) = app_handle.get_webview_window("main") else {
+            return;
+        };
+        let operation_id = serde_json::from_str::<serde_json::Value>(event.payload())
+            .ok()
+            .and_then(|snapshot| snapshot["operation_id"].as_str().map(str::to_string));
+        let outcome = operation_id.as_deref().and_then(|id| {
+            app_handle.try_state::<OperationRegistry>().and_then(|registry| registry.outcome(id)).map(|outcome| outcome.status)
+        });
+        let focused = window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false);
+        let alerts = decide(settings, focused, outcome);
+        if alerts != CompletionAlerts::default() {
+            log_event("completion_alert", json!({"operation_id": operation_id, "alerts": alerts}));
+            deliver(&WindowAlerts(window), alerts);
+        }
+    });
+}
+
+/// Choose whether a finished operation flashes the taskbar button and plays a sound while the
+/// window is in the background.
+#[tauri::command]
+pub async fn set_completion_alert(
+    settings: State<'_, SettingsStore>,
+    flash_taskbar: bool,
+    play_sound: bool,
+) -> Result<CompletionAlertSettings, BitBurnError> {
+    let updated = settings.update(|s| s.completion_alert = CompletionAlertSettings { flash_taskbar, play_sound })?;
+    log_event("completion_alert_configured", json!({"flash_taskbar": flash_taskbar, "play_sound": play_sound}));
+    Ok(updated.completion_alert)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    const BOTH: CompletionAlertSettings = CompletionAlertSettings { flash_taskbar: true, play_sound: true };
+
+    #[derive(Default)]
+    struct Recorded(RefCell<Vec<String>>);
+
+    impl AlertOutput for Recorded {
+        fn flash(&self, count: u32) {
+            self.0.borrow_mut().push(format!("flash {}", count));
+        }
+
+        fn play(&self, sound: AlertSound) {
+            self.0.borrow_mut().push(format!("{:?}", sound));
+        }
+    }
+
+    #[test]
+    fn the_sound_follows_the_outcome_and_a_focused_window_gets_nothing() {
+        let completed = decide(BOTH, false, Some(OutcomeStatus::Completed));
+        assert_eq!(completed, CompletionAlerts { flash: true, sound: Some(AlertSound::Asterisk) });
+        assert_eq!(decide(BOTH, false, Some(OutcomeStatus::Failed)).sound, Some(AlertSound::Exclamation));
+        assert_eq!(decide(BOTH, false, None).sound, Some(AlertSound::Exclamation));
+        for outcome in [Some(OutcomeStatus::Completed), Some(OutcomeStatus::Failed), None] {
+            assert_eq!(decide(BOTH, true, outcome), CompletionAlerts::default());
+        }
+        assert_eq!(decide(BOTH, false, Some(OutcomeStatus::Cancelled)), CompletionAlerts::default());
+    }
+
+    #[test]
+    fn each_setting_works_alone() {
+        let flash_only = CompletionAlertSettings { flash_taskbar: true, play_sound: false };
+        let sound_only = CompletionAlertSettings { flash_taskbar: false, play_sound: true };
+        let completed = Some(OutcomeStatus::Completed);
+        assert_eq!(decide(flash_only, false, completed), CompletionAlerts { flash: true, sound: None });
+        assert_eq!(decide(sound_only, false, completed), CompletionAlerts { flash: false, sound: Some(AlertSound::Asterisk) });
+        assert_eq!(decide(CompletionAlertSettings::default(), false, completed), CompletionAlerts::default());
+    }
+
+    #[test]
+    fn delivery_flashes_a_bounded_number_of_times_then_plays() {
+        let output = Recorded::default();
+        deliver(&output, decide(BOTH, false, Some(OutcomeStatus::Failed)));
+        assert_eq!(*output.0.borrow(), ["flash 5", "Exclamation"]);
+
+        let quiet = Recorded::default();
+        deliver(&quiet, CompletionAlerts::default());
+        assert!(quiet.0.borrow().is_empty());
+    }
+}
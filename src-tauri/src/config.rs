@@ -0,0 +1,295 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, State};
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::messages;
+use crate::settings::{write_file_atomic, Settings, SettingsStore};
+use crate::WipeResult;
+
+/// Version of the exported configuration document understood by this build.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Settings keys that describe this machine rather than the deployment and are never exported.
+const HOST_SPECIFIC_KEYS: &[&str] = &["window_layout"];
+
+/// Nested `(section, field)` secrets that are never exported and are kept from the local settings on import.
+const SECRET_FIELDS: &[(&str, &str)] = &[("webhook", "bearer_token")];
+
+/// Portable configuration document used to ship a standard setup across machines.
+/// Unknown fields are ignored so newer builds of the same version can add sections.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigDocument {
+    pub version: u32,
+    #[serde(default)]
+    pub exported_at: u64,
+    #[serde(default)]
+    pub settings: Map<String, Value>,
+}
+
+/// Build the export document from the current settings, stripping host-specific values.
+pub fn build_document(settings: &Settings) -> Result<ConfigDocument, BitBurnError> {
+    let exported_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok(ConfigDocument {
+        version: CONFIG_VERSION,
+        exported_at,
+        settings: portable_settings(settings)?,
+    })
+}
+
+fn settings_to_map(settings: &Settings) -> Result<Map<String, Value>, BitBurnError> {
+    match serde_json::to_value(settings) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(BitBurnError::internal("Settings did not serialize to an object")),
+        Err(e) => Err(BitBurnError::internal(format!("Failed to serialize settings: {}", e))),
+    }
+}
+
+fn portable_settings(settings: &Settings) -> Result<Map<String, Value>, BitBurnError> {
+    let mut map = settings_to_map(settings)?;
+    for key in HOST_SPECIFIC_KEYS {
+        map.remove(*key);
+    }
+    for (section, field) in SECRET_FIELDS {
+        if let Some(Value::Object(section)) = map.get_mut(*section) {
+            section.remove(*field);
+        }
+    }
+    Ok(map)
+}
+
+/// Parse and validate a configuration document.
+/// The version is checked before the body so unknown versions get a precise error.
+pub fn parse_document(contents: &str) -> Result<ConfigDocument, BitBurnError> {
+    let value: Value = serde_json::from_str(contents).map_err(|e| BitBurnError::InvalidConfiguration {
+        message: format!("not valid JSON: {}", e),
+    })?;
+
+    let version = value
+        .get("version")
+        .ok_or_else(|| BitBurnError::InvalidConfiguration {
+            message: "missing \"version\" field".to_string(),
+        })?
+        .as_u64()
+        .ok_or_else(|| BitBurnError::InvalidConfiguration {
+            message: "\"version\" must be a positive integer".to_string(),
+        })?;
+
+    if version != u64::from(CONFIG_VERSION) {
+        return Err(BitBurnError::UnsupportedConfigVersion {
+            found: version,
+            supported: CONFIG_VERSION,
+        });
+    }
+
+    let document: ConfigDocument = serde_json::from_value(value).map_err(|e| BitBurnError::InvalidConfiguration {
+        message: e.to_string(),
+    })?;
+
+    // Reject sections whose values do not fit the settings schema instead of silently dropping them.
+    serde_json::from_value::<Settings>(Value::Object(document.settings.clone())).map_err(|e| {
+        BitBurnError::InvalidConfiguration {
+            message: format!("settings: {}", e),
+        }
+    })?;
+
+    Ok(document)
+}
+
+/// Compute the settings that result from importing `document` over `current`.
+/// Replace mode starts from defaults; merge mode only overlays values that differ from the defaults.
+/// Host-specific values always come from `current`.
+pub fn apply_document(current: &Settings, document: &ConfigDocument, merge: bool) -> Result<Settings, BitBurnError> {
+    // Compare against the exported form of the defaults so stripped secrets do not count as changes.
+    let portable_defaults = portable_settings(&Settings::default())?;
+    let current_map = settings_to_map(current)?;
+    let mut result = if merge {
+        current_map.clone()
+    } else {
+        settings_to_map(&Settings::default())?
+    };
+
+    for (key, value) in &document.settings {
+        if HOST_SPECIFIC_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        if merge && portable_defaults.get(key) == Some(value) {
+            continue;
+        }
+        result.insert(key.clone(), value.clone());
+    }
+
+    for key in HOST_SPECIFIC_KEYS {
+        match current_map.get(*key) {
+            Some(value) => result.insert(key.to_string(), value.clone()),
+            None => result.remove(*key),
+        };
+    }
+
+    for (section, field) in SECRET_FIELDS {
+        let local = current_map
+            .get(*section)
+            .and_then(|s| s.get(*field))
+            .cloned()
+            .unwrap_or(Value::Null);
+        if let Some(Value::Object(section)) = result.get_mut(*section) {
+            section.insert(field.to_string(), local);
+        }
+    }
+
+    serde_json::from_value(Value::Object(result)).map_err(|e| BitBurnError::InvalidConfiguration {
+        message: format!("settings: {}", e),
+    })
+}
+
+/// Write the portable configuration (settings without host-specific values) to `dest_path`.
+#[tauri::command]
+pub async fn export_configuration(
+    settings: State<'_, SettingsStore>,
+    dest_path: String,
+) -> Result<WipeResult, BitBurnError> {
+    let document = build_document(&settings.get())?;
+    let serialized = serde_json::to_string_pretty(&document)
+        .map_err(|e| BitBurnError::internal(format!("Failed to serialize configuration: {}", e)))?;
+    write_file_atomic(Path::new(&dest_path), serialized.as_bytes())?;
+
+    log_event("configuration_exported", json!({"path": dest_path, "version": CONFIG_VERSION}));
+    Ok(WipeResult::success(messages::CONFIGURATION_EXPORTED.render(json!({"path": dest_path}))))
+}
+
+/// Import a configuration document from `src_path`.
+/// `merge = false` replaces all portable settings in one atomic write; `merge = true` overlays non-default values.
+#[tauri::command]
+pub async fn import_configuration(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    src_path: String,
+    merge: bool,
+) -> Result<WipeResult, BitBurnError> {
+    let path = Path::new(&src_path);
+    let contents = fs::read_to_string(path).map_err(|e| BitBurnError::io(&e, Some(path)))?;
+    let document = parse_document(&contents)?;
+
+    let imported = apply_document(&settings.get(), &document, merge)?;
+    settings.update(|s| *s = imported)?;
+    crate::emergency_stop::register_from_settings(&app);
+
+    log_event(
+        "configuration_imported",
+        json!({"path": src_path, "version": document.version, "merge": merge}),
+    );
+    Ok(WipeResult::success(messages::CONFIGURATION_IMPORTED.render(json!({"path": src_path}))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::WindowLayout;
+
+    fn sample_layout() -> WindowLayout {
+        WindowLayout {
+            x: 10,
+            y: 20,
+            width: 800,
+            height: 1000,
+            monitor: Some("DISPLAY1".to_string()),
+        }
+    }
+
+    #[test]
+    fn export_excludes_host_specific_values() {
+        let mut settings = Settings {
+            window_layout: Some(sample_layout()),
+            ..Default::default()
+        };
+        settings.webhook.bearer_token = Some("s3cret".to_string());
+        let document = build_document(&settings).unwrap();
+        assert_eq!(document.version, CONFIG_VERSION);
+        assert!(!document.settings.contains_key("window_layout"));
+        assert!(document.settings["webhook"].get("bearer_token").is_none());
+    }
+
+    #[test]
+    fn merge_overlays_only_non_default_values_and_keeps_local_secrets() {
+        let mut exported = Settings::default();
+        exported.webhook.enabled = true;
+        exported.webhook.webhook_url = "https://collector.example/hook".to_string();
+        let document = parse_document(&serde_json::to_string(&build_document(&exported).unwrap()).unwrap()).unwrap();
+
+        let mut current = Settings::default();
+        current.webhook.bearer_token = Some("local-token".to_string());
+        for merge in [false, true] {
+            let imported = apply_document(&current, &document, merge).unwrap();
+            assert!(imported.webhook.enabled);
+            assert_eq!(imported.webhook.webhook_url, "https://collector.example/hook");
+            assert_eq!(imported.webhook.bearer_token.as_deref(), Some("local-token"));
+        }
+
+        // A default-valued section in the document does not clobber local changes when merging.
+        let defaults = parse_document(&serde_json::to_string(&build_document(&Settings::default()).unwrap()).unwrap()).unwrap();
+        let mut configured = Settings::default();
+        configured.webhook.enabled = true;
+        assert!(apply_document(&configured, &defaults, true).unwrap().webhook.enabled);
+        assert!(!apply_document(&configured, &defaults, false).unwrap().webhook.enabled);
+    }
+
+    #[test]
+    fn round_trip_preserves_local_host_values() {
+        let exported = build_document(&Settings::default()).unwrap();
+        let serialized = serde_json::to_string(&exported).unwrap();
+        let parsed = parse_document(&serialized).unwrap();
+
+        let current = Settings {
+            window_layout: Some(sample_layout()),
+            ..Default::default()
+        };
+        for merge in [false, true] {
+            let imported = apply_document(&current, &parsed, merge).unwrap();
+            assert_eq!(imported.window_layout, Some(sample_layout()));
+        }
+    }
+
+    #[test]
+    fn imported_window_layout_is_ignored() {
+        let contents = r#"{"version": 1, "settings": {"window_layout": {"x": 1, "y": 2, "width": 3, "height": 4}}}"#;
+        let document = parse_document(contents).unwrap();
+        let imported = apply_document(&Settings::default(), &document, false).unwrap();
+        assert_eq!(imported.window_layout, None);
+    }
+
+    #[test]
+    fn unknown_fields_within_version_are_ignored() {
+        let contents = r#"{"version": 1, "future_section": [1, 2], "settings": {"future_setting": true}}"#;
+        let document = parse_document(contents).unwrap();
+        let imported = apply_document(&Settings::default(), &document, true).unwrap();
+        assert_eq!(imported, Settings::default());
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let err = parse_document(r#"{"version": 2, "settings": {}}"#).unwrap_err();
+        assert_eq!(err, BitBurnError::UnsupportedConfigVersion { found: 2, supported: CONFIG_VERSION });
+    }
+
+    #[test]
+    fn malformed_documents_are_rejected() {
+        for contents in [
+            "not json",
+            r#"{"settings": {}}"#,
+            r#"{"version": "1"}"#,
+            r#"{"version": -1}"#,
+            r#"{"version": 1, "settings": []}"#,
+            r#"{"version": 1, "settings": {"window_layout": "left"}}"#,
+        ] {
+            let err = parse_document(contents).unwrap_err();
+            assert_eq!(err.code(), "invalid_configuration", "accepted {}", contents);
+        }
+    }
+}
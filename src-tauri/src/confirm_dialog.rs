@@ -0,0 +1,163 @@
+//! Native confirmation dialogs that cannot hang a wipe. On Windows Server Core, in session 0 and
+//! in some RDP sessions the dialog plugin never returns, so a dialog is only shown where there is
+//! an interactive desktop, and is given up on after `DIALOG_TIMEOUT`. Either way the caller gets
+//! `dialog_unavailable` carrying the prompt and the nonce it is kept under, which the window can
+//! show itself and answer with `record_webview_confirmation`, or which `--yes` answers on a
+//! `--wipe` command line.
+
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::consent::ConsentStore;
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::messages;
+
+/// Long enough to read the prompt and decide; an answer given after it is ignored.
+pub const DIALOG_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Dialogs given up on that are still open. While one is, another would most likely hang the
+/// same way, so the window asks at once; when it finally closes, dialogs are tried again.
+static UNANSWERED: AtomicUsize = AtomicUsize::new(0);
+
+/// Ask `prompt`, about a wipe of `selection`, in a native Yes/No warning dialog.
+pub fn confirm<R: Runtime>(app: &AppHandle<R>, prompt: &str, selection: &[String]) -> Result<bool, BitBurnError> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let unavailable = |reason: String| {
+        log_event("confirmation_dialog_unavailable", json!({"reason": reason}));
+        let now = SystemTime::now();
+        let nonce = match app.try_state::<ConsentStore>() {
+            Some(store) => store.ask_in_window(prompt, selection, now),
+            None => ConsentStore::default().ask_in_window(prompt, selection, now),
+        };
+        BitBurnError::DialogUnavailable { reason, prompt: prompt.to_string(), nonce }
+    };
+    if let Some(reason) = missing_desktop() {
+        return Err(unavailable(reason.to_string()));
+    }
+    if dialog_stuck() {
+        return Err(unavailable("an earlier dialog is still open unanswered".to_string()));
+    }
+    let dialog = app
+        .dialog()
+        .message(prompt)
+        .kind(tauri_plugin_dialog::MessageDialogKind::Warning)
+        .title(messages::CONFIRM_DIALOG_TITLE.text().text)
+        .buttons(tauri_plugin_dialog::MessageDialogButtons::YesNo);
+    show_with_timeout(move || dialog.blocking_show(), DIALOG_TIMEOUT).map_err(unavailable)
+}
+
+/// Whether a dialog given up on is still open.
+fn dialog_stuck() -> bool {
+    UNANSWERED.load(Ordering::SeqCst) > 0
+}
+
+/// Run `show` on a thread of its own and wait at most `timeout` for its answer. A dialog that
+/// never returns keeps its thread, but not the caller, and counts as unanswered until it returns.
+pub fn show_with_timeout<F>(show: F, timeout: Duration) -> Result<bool, String>
+where
+    F: FnOnce() -> bool + Send + 'static,
+{
+    let (sender, answer) = mpsc::channel();
+    // Whichever of the dialog and the timeout comes second sees it set.
+    let settled = Arc::new(AtomicBool::new(false));
+    let dialog_settled = settled.clone();
+    std::thread::spawn(move || {
+        // Dropped however `show` ends, a panic included.
+        struct Settle(Arc<AtomicBool>);
+        impl Drop for Settle {
+            fn drop(&mut self) {
+                if self.0.swap(true, Ordering::SeqCst) {
+                    UNANSWERED.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+        }
+        let settle = Settle(dialog_settled);
+        let answered = show();
+        drop(settle);
+        let _ = sender.send(answered);
+    });
+    answer.recv_timeout(timeout).map_err(|e| match e {
+        RecvTimeoutError::Timeout => {
+            if !settled.swap(true, Ordering::SeqCst) {
+                UNANSWERED.fetch_add(1, Ordering::SeqCst);
+            }
+            format!("no answer within {} seconds", timeout.as_secs())
+        }
+        RecvTimeoutError::Disconnected => "the dialog closed without an answer".to_string(),
+    })
+}
+
+/// Why no one could see a dialog here, if they could not.
+#[cfg(windows)]
+fn missing_desktop() -> Option<&'static str> {
+    use windows_sys::Win32::System::RemoteDesktop::ProcessIdToSessionId;
+    use windows_sys::Win32::System::StationsAndDesktops::{CloseDesktop, OpenInputDesktop, DESKTOP_READOBJECTS};
+    use windows_sys::Win32::System::Threading::GetCurrentProcessId;
+
+    let mut session = 0u32;
+    if unsafe { ProcessIdToSessionId(GetCurrentProcessId(), &mut session) } != 0 && session == 0 {
+        return Some("BitBurn is running in session 0, which has no interactive desktop");
+    }
+    // Fails on a locked or disconnected session and on a service's window station.
+    let desktop = unsafe { OpenInputDesktop(0, 0, DESKTOP_READOBJECTS) };
+    if desktop.is_null() {
+        return Some("there is no interactive desktop to show it on");
+    }
+    unsafe { CloseDesktop(desktop) };
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn missing_desktop() -> Option<&'static str> {
+    let unset = |name: &str| std::env::var_os(name).is_none_or(|value| value.is_empty());
+    (unset("DISPLAY") && unset("WAYLAND_DISPLAY")).then_some("there is no graphical session to show it on")
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn missing_desktop() -> Option<&'static str> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn a_dialog_that_never_returns_is_given_up_on_within_the_timeout() {
+        let (close, closed) = mpsc::channel::<()>();
+        let started = Instant::now();
+        let answer = show_with_timeout(
+            move || {
+                let _ = closed.recv();
+                true
+            },
+            Duration::from_millis(100),
+        );
+        assert!(answer.is_err_and(|reason| reason.contains("no answer")));
+        assert!(started.elapsed() < Duration::from_secs(2), "returned after {:?}", started.elapsed());
+
+        // Later prompts skip the dialog only while that one is still open.
+        assert!(dialog_stuck());
+        close.send(()).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while dialog_stuck() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!dialog_stuck());
+    }
+
+    #[test]
+    fn answers_come_back_and_a_dialog_that_dies_is_unavailable() {
+        assert_eq!(show_with_timeout(|| true, Duration::from_secs(5)), Ok(true));
+        assert_eq!(show_with_timeout(|| false, Duration::from_secs(5)), Ok(false));
+        let crashed = show_with_timeout(|| panic!("dialog plugin crashed"), Duration::from_secs(5));
+        assert_eq!(crashed, Err("the dialog closed without an answer".to_string()));
+    }
+}
@@ -0,0 +1,391 @@
+//! Consent to a wipe, recorded by the backend when the confirmation dialog is answered.
+//! `show_confirmation_dialog` hands out a token naming the text shown, the selection it was
+//! shown for and the choice made; destructive commands redeem it and copy it into their report.
+//! With `confirm_before_wipe` on they refuse to start without one. Where the native dialog cannot
+//! be shown, the backend keeps the prompt and selection it meant to show under a nonce, the window
+//! asks that prompt instead, and `record_webview_confirmation` issues the same token for the nonce.
+//! The window only supplies the answer, so it cannot obtain a token for anything it was not asked.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::confirm_dialog;
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::settings::SettingsStore;
+
+/// How many paths a prompt for targets the backend worked out names before summarizing the rest.
+const LISTED_TARGETS: usize = 10;
+
+/// How long after the dialog was answered its token can start a wipe.
+pub const CONSENT_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Where the user answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentOrigin {
+    Dialog,
+    /// The app window, standing in for a native dialog that could not be shown.
+    Webview,
+    /// `--yes` on the command line that asked for the wipe.
+    CommandLine,
+}
+
+/// What the user was asked before a wipe and what they answered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Consent {
+    pub token: String,
+    /// The dialog text, and its SHA-256.
+    pub message: String,
+    pub message_sha256: String,
+    /// SHA-256 of the paths the dialog was shown for, one per line.
+    pub selection_sha256: String,
+    /// Unix seconds when the dialog was answered.
+    pub answered_at: u64,
+    pub confirmed: bool,
+    pub origin: ConsentOrigin,
+}
+
+/// What the user confirmed before a file wipe, passed back by the frontend.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WipeConfirmation {
+    /// From `show_confirmation_dialog`; required while `confirm_before_wipe` is on.
+    #[serde(default)]
+    pub consent_token: Option<String>,
+    /// Folders that are volume roots the user typed the path of to confirm; only these are walked.
+    #[serde(default)]
+    pub volume_roots: Vec<String>,
+}
+
+/// SHA-256 of a selection as the confirmation dialog shows it, one path per line.
+pub fn selection_sha256(paths: &[String]) -> String {
+    hex::encode(Sha256::digest(paths.join("\n").as_bytes()))
+}
+
+/// A prompt the backend could not show in a dialog, waiting for the window's answer.
+#[derive(Debug, Clone)]
+struct PendingPrompt {
+    prompt: String,
+    selection: Vec<String>,
+    asked_at: SystemTime,
+}
+
+/// Consents handed out and not yet expired, and prompts waiting for the window's answer.
+#[derive(Debug, Default)]
+pub struct ConsentStore {
+    issued: Mutex<HashMap<String, Consent>>,
+    pending: Mutex<HashMap<String, PendingPrompt>>,
+}
+
+impl ConsentStore {
+    /// Record the answer, given at `origin`, to `message` shown for `selection`.
+    pub fn record(
+        &self,
+        message: &str,
+        selection: &[String],
+        confirmed: bool,
+        origin: ConsentOrigin,
+        now: SystemTime,
+    ) -> Consent {
+        let consent = Consent {
+            token: format!("consent-{:016x}{:016x}", rand::random::<u64>(), rand::random::<u64>()),
+            message: message.to_string(),
+            message_sha256: hex::encode(Sha256::digest(message.as_bytes())),
+            selection_sha256: selection_sha256(selection),
+            answered_at: now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            confirmed,
+            origin,
+        };
+        let mut issued = self.lock();
+        issued.retain(|_, earlier| !expired(earlier, now));
+        issued.insert(consent.token.clone(), consent.clone());
+        consent
+    }
+
+    /// The consent `token` was issued for, if it confirmed exactly `selection` and has not expired.
+    /// A token can start more than one attempt at the same wipe, e.g. after a pre-flight refusal.
+    pub fn redeem(&self, token: &str, selection: &[String], now: SystemTime) -> Result<Consent, BitBurnError> {
+        let refused = |reason: &str| BitBurnError::ConfirmationRequired { reason: reason.to_string() };
+        let consent = self.lock().get(token).cloned().ok_or_else(|| refused("the confirmation was not found"))?;
+        if expired(&consent, now) {
+            return Err(refused("the confirmation has expired"));
+        }
+        if !consent.confirmed {
+            return Err(refused("the wipe was declined"));
+        }
+        if consent.selection_sha256 != selection_sha256(selection) {
+            return Err(refused("the selection differs from the one confirmed"));
+        }
+        Ok(consent)
+    }
+
+    /// The consent a command started with `token` for `selection` goes ahead with. Without a
+    /// token, or with one that does not hold, it goes ahead with none unless `required`.
+    pub fn check(
+        &self,
+        token: Option<&str>,
+        selection: &[String],
+        required: bool,
+        now: SystemTime,
+    ) -> Result<Option<Consent>, BitBurnError> {
+        let missing = || BitBurnError::ConfirmationRequired { reason: "no confirmation was given".to_string() };
+        match token.map(|token| self.redeem(token, selection, now)) {
+            Some(Ok(consent)) => Ok(Some(consent)),
+            Some(Err(e)) if required => Err(e),
+            None if required => Err(missing()),
+            _ => Ok(None),
+        }
+    }
+
+    /// Keep `prompt`, meant for `selection`, for the window to ask; the nonce names it in the answer.
+    pub fn ask_in_window(&self, prompt: &str, selection: &[String], now: SystemTime) -> String {
+        let nonce = format!("prompt-{:016x}{:016x}", rand::random::<u64>(), rand::random::<u64>());
+        let mut pending = self.pending.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        pending.retain(|_, earlier| !too_old(earlier.asked_at, now));
+        pending.insert(
+            nonce.clone(),
+            PendingPrompt { prompt: prompt.to_string(), selection: selection.to_vec(), asked_at: now },
+        );
+        nonce
+    }
+
+    /// Record the window's answer to the prompt kept under `nonce`, for the selection it was kept
+    /// with. Each prompt is answered once.
+    pub fn answer_in_window(&self, nonce: &str, confirmed: bool, now: SystemTime) -> Result<Consent, BitBurnError> {
+        let refused = |reason: &str| BitBurnError::ConfirmationRequired { reason: reason.to_string() };
+        let pending = self
+            .pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(nonce)
+            .ok_or_else(|| refused("the prompt was not found"))?;
+        if too_old(pending.asked_at, now) {
+            return Err(refused("the prompt has expired"));
+        }
+        Ok(self.record(&pending.prompt, &pending.selection, confirmed, ConsentOrigin::Webview, now))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Consent>> {
+        self.issued.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn expired(consent: &Consent, now: SystemTime) -> bool {
+    too_old(UNIX_EPOCH + Duration::from_secs(consent.answered_at), now)
+}
+
+fn too_old(at: SystemTime, now: SystemTime) -> bool {
+    now.duration_since(at).is_ok_and(|age| age > CONSENT_TTL)
+}
+
+/// `ConsentStore::check` against the app's store and the `confirm_before_wipe` setting, logging refusals.
+pub fn check<R: Runtime>(
+    app: &AppHandle<R>,
+    token: Option<&str>,
+    selection: &[String],
+) -> Result<Option<Consent>, BitBurnError> {
+    let required = app.try_state::<SettingsStore>().is_some_and(|settings| settings.get().confirm_before_wipe);
+    let checked = match app.try_state::<ConsentStore>() {
+        Some(store) => store.check(token, selection, required, SystemTime::now()),
+        None => ConsentStore::default().check(None, selection, required, SystemTime::now()),
+    };
+    if let Err(e) = &checked {
+        log_event("consent_refused", json!({"code": e.code(), "message": e.to_string(), "items": selection.len()}));
+    }
+    checked
+}
+
+/// Consent to wipe `targets`, which the backend worked out itself from a stored report, a journal
+/// or checkpoints, so the window never confirmed them path by path. With `token` it must be a
+/// consent to exactly `targets`; without one `prompt` is asked now, in the native dialog or, by way
+/// of `dialog_unavailable`, in the window, and the command is called again with that answer's
+/// token. Blocks while the dialog is open. A declined prompt is refused like a declined token.
+pub fn confirm_targets<R: Runtime>(
+    app: &AppHandle<R>,
+    token: Option<&str>,
+    prompt: &str,
+    targets: &[String],
+) -> Result<Consent, BitBurnError> {
+    let fallback = ConsentStore::default();
+    let managed = app.try_state::<ConsentStore>();
+    let store = managed.as_deref().unwrap_or(&fallback);
+    let confirmed = match token {
+        Some(token) => store.redeem(token, targets, SystemTime::now()),
+        None => confirm_dialog::confirm(app, prompt, targets).and_then(|confirmed| {
+            let consent = store.record(prompt, targets, confirmed, ConsentOrigin::Dialog, SystemTime::now());
+            log_event(
+                "consent_recorded",
+                json!({"confirmed": confirmed, "items": targets.len(), "message_sha256": consent.message_sha256}),
+            );
+            match confirmed {
+                true => Ok(consent),
+                false => Err(BitBurnError::ConfirmationRequired { reason: "the wipe was declined".to_string() }),
+            }
+        }),
+    };
+    if let Err(e) = &confirmed {
+        log_event("consent_refused", json!({"code": e.code(), "message": e.to_string(), "items": targets.len()}));
+    }
+    confirmed
+}
+
+/// `confirm_targets` on a blocking thread, for commands, since the dialog may be open for minutes.
+pub async fn confirm_targets_async<R: Runtime>(
+    app: AppHandle<R>,
+    token: Option<String>,
+    prompt: String,
+    targets: Vec<String>,
+) -> Result<Consent, BitBurnError> {
+    tauri::async_runtime::spawn_blocking(move || confirm_targets(&app, token.as_deref(), &prompt, &targets))
+        .await
+        .map_err(|e| BitBurnError::internal(format!("confirmation join error: {}", e)))?
+}
+
+/// `targets` one per line for a prompt, the first few of a long list and a count of the rest.
+pub fn list_targets(targets: &[String]) -> String {
+    let mut listed = targets.iter().take(LISTED_TARGETS).cloned().collect::<Vec<_>>().join("\n");
+    if targets.len() > LISTED_TARGETS {
+        listed.push_str(&format!("\n… and {} more", targets.len() - LISTED_TARGETS));
+    }
+    listed
+}
+
+/// Record the window's answer to the prompt a `dialog_unavailable` refusal carried, named by the
+/// refusal's `nonce`. The consent covers the prompt and selection the backend kept, not anything
+/// the window sends.
+#[tauri::command]
+pub async fn record_webview_confirmation(
+    consents: State<'_, ConsentStore>,
+    nonce: String,
+    confirmed: bool,
+) -> Result<Consent, BitBurnError> {
+    let consent = consents.answer_in_window(&nonce, confirmed, SystemTime::now()).inspect_err(|e| {
+        log_event("consent_refused", json!({"code": e.code(), "message": e.to_string()}));
+    })?;
+    log_event(
+        "consent_recorded",
+        json!({"confirmed": confirmed, "message_sha256": consent.message_sha256, "origin": consent.origin}),
+    );
+    Ok(consent)
+}
+
+/// Turn the requirement for a consent token on or off.
+#[tauri::command]
+pub async fn set_confirm_before_wipe(settings: State<'_, SettingsStore>, enabled: bool) -> Result<bool, BitBurnError> {
+    let updated = settings.update(|s| s.confirm_before_wipe = enabled)?;
+    log_event("confirm_before_wipe_configured", json!({"enabled": updated.confirm_before_wipe}));
+    Ok(updated.confirm_before_wipe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection() -> Vec<String> {
+        vec!["C:\\Users\\me\\tax-2023.pdf".to_string(), "C:\\Users\\me\\old-scans".to_string()]
+    }
+
+    #[test]
+    fn consents_expire_and_only_cover_what_was_confirmed() {
+        let store = ConsentStore::default();
+        let shown = SystemTime::now();
+        let consent = store.record("Wipe 2 items?", &selection(), true, ConsentOrigin::Dialog, shown);
+        assert_eq!(consent.message_sha256, hex::encode(Sha256::digest(b"Wipe 2 items?")));
+
+        let later = shown + Duration::from_secs(60);
+        assert_eq!(store.redeem(&consent.token, &selection(), later).unwrap(), consent);
+        assert_eq!(store.redeem(&consent.token, &selection(), later).unwrap(), consent, "retries reuse it");
+
+        let mut changed = selection();
+        changed.push("C:\\Windows\\System32".to_string());
+        let mismatch = store.redeem(&consent.token, &changed, later);
+        assert!(matches!(mismatch, Err(BitBurnError::ConfirmationRequired { reason }) if reason.contains("selection")));
+
+        let expiry = shown + CONSENT_TTL + Duration::from_secs(1);
+        let expired = store.redeem(&consent.token, &selection(), expiry);
+        assert!(matches!(expired, Err(BitBurnError::ConfirmationRequired { reason }) if reason.contains("expired")));
+
+        let declined = store.record("Wipe 2 items?", &selection(), false, ConsentOrigin::Dialog, shown);
+        assert!(store.redeem(&declined.token, &selection(), later).is_err());
+        assert!(store.redeem("consent-forged", &selection(), later).is_err());
+    }
+
+    #[test]
+    fn tokens_are_only_required_while_the_setting_is_on() {
+        let store = ConsentStore::default();
+        let now = SystemTime::now();
+        let consent = store.record("Wipe 2 items?", &selection(), true, ConsentOrigin::Dialog, now);
+
+        assert_eq!(store.check(None, &selection(), false, now).unwrap(), None);
+        assert_eq!(store.check(Some("consent-forged"), &selection(), false, now).unwrap(), None);
+        assert_eq!(store.check(Some(&consent.token), &selection(), false, now).unwrap(), Some(consent.clone()));
+
+        assert!(matches!(store.check(None, &selection(), true, now), Err(BitBurnError::ConfirmationRequired { .. })));
+        assert!(store.check(Some("consent-forged"), &selection(), true, now).is_err());
+        assert_eq!(store.check(Some(&consent.token), &selection(), true, now).unwrap(), Some(consent));
+    }
+
+    #[test]
+    fn a_confirmation_given_in_the_window_is_redeemed_like_the_dialog() {
+        let store = ConsentStore::default();
+        let now = SystemTime::now();
+        let webview = store.record("Wipe 2 items?", &selection(), true, ConsentOrigin::Webview, now);
+        assert_eq!(store.check(Some(&webview.token), &selection(), true, now).unwrap(), Some(webview.clone()));
+        assert_eq!(serde_json::to_value(&webview).unwrap()["origin"], "webview");
+
+        let declined = store.record("Wipe 2 items?", &selection(), false, ConsentOrigin::Webview, now);
+        assert!(store.check(Some(&declined.token), &selection(), true, now).is_err());
+    }
+
+    #[test]
+    fn the_window_can_only_answer_a_prompt_the_backend_kept() {
+        let store = ConsentStore::default();
+        let now = SystemTime::now();
+        assert!(matches!(
+            store.answer_in_window("prompt-forged", true, now),
+            Err(BitBurnError::ConfirmationRequired { reason }) if reason.contains("not found")
+        ));
+
+        let nonce = store.ask_in_window("Wipe 2 items?", &selection(), now);
+        let consent = store.answer_in_window(&nonce, true, now).unwrap();
+        assert_eq!(consent.selection_sha256, selection_sha256(&selection()));
+        assert_eq!(consent.message, "Wipe 2 items?");
+        assert_eq!(store.redeem(&consent.token, &selection(), now).unwrap(), consent);
+        assert!(store.answer_in_window(&nonce, true, now).is_err(), "a prompt is answered once");
+
+        let stale = store.ask_in_window("Wipe 2 items?", &selection(), now);
+        let expired = store.answer_in_window(&stale, true, now + CONSENT_TTL + Duration::from_secs(1));
+        assert!(matches!(expired, Err(BitBurnError::ConfirmationRequired { reason }) if reason.contains("expired")));
+    }
+
+    #[test]
+    fn targets_the_backend_chose_need_a_consent_to_exactly_them() {
+        let app = tauri::test::mock_app();
+        app.manage(ConsentStore::default());
+        let store = app.state::<ConsentStore>();
+        let consent = store.record("Resume 2 items?", &selection(), true, ConsentOrigin::Dialog, SystemTime::now());
+
+        let confirmed = confirm_targets(app.handle(), Some(&consent.token), "Resume 2 items?", &selection());
+        assert_eq!(confirmed.unwrap(), consent);
+        let fewer = &selection()[..1];
+        assert!(matches!(
+            confirm_targets(app.handle(), Some(&consent.token), "Resume 1 item?", fewer),
+            Err(BitBurnError::ConfirmationRequired { reason }) if reason.contains("selection")
+        ));
+        assert!(confirm_targets(app.handle(), Some("consent-forged"), "Resume 2 items?", &selection()).is_err());
+    }
+
+    #[test]
+    fn long_target_lists_are_cut_short() {
+        let targets: Vec<String> = (0..12).map(|i| format!("/data/{}.bin", i)).collect();
+        assert_eq!(list_targets(&targets[..2]), "/data/0.bin\n/data/1.bin");
+        let listed = list_targets(&targets);
+        assert_eq!(listed.lines().count(), LISTED_TARGETS + 1);
+        assert!(listed.ends_with("… and 2 more"));
+    }
+}
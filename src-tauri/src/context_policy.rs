@@ -0,0 +1,917 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{AppHandle, Manager, State};
+use walkdir::WalkDir;
+
+use crate::confirm_dialog;
+use crate::consent::{Consent, ConsentOrigin, ConsentStore};
+use crate::diagnostics;
+use crate::error::BitBurnError;
+use crate::messages;
+use crate::operations::OperationRegistry;
+use crate::platform::context_menu::{dispatch_context_wipe, ContextWipePayload};
+use crate::policy;
+use crate::protected::ProtectedPaths;
+use crate::remote_tier;
+use crate::settings::SettingsStore;
+use crate::{log_event, start_file_wipe, BatchOptions, WipeAlgorithm, WipeResult};
+
+/// Selections sent to the frontend that are kept for `run_context_wipe`; older ones are dropped.
+const MAX_BUFFERED_PAYLOADS: usize = 16;
+
+/// How a context-menu invocation is handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextMenuBehavior {
+    /// Show the selection in the main window and let the user start the wipe.
+    #[default]
+    Ask,
+    /// Confirm with a native dialog and wipe with the default algorithm.
+    Immediate,
+    /// Add the selection to the context queue without interrupting the user.
+    Queue,
+}
+
+/// Rough size of a selection, shown in the native confirmation dialog.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TargetEstimate {
+    pub files: u64,
+    pub bytes: u64,
+    /// Files left out of `files` and `bytes` because their data is in remote or archive storage
+    /// and the wipe will refuse them.
+    pub remote_tiered: u64,
+}
+
+impl TargetEstimate {
+    /// Count a file of `len` bytes with `attributes`, unless the wipe will refuse it.
+    pub fn add(&mut self, attributes: u32, len: u64, allow_recall: bool) {
+        if remote_tier::is_remote(attributes) && !allow_recall {
+            self.remote_tiered += 1;
+        } else {
+            self.files += 1;
+            self.bytes += len;
+        }
+    }
+}
+
+/// Count files and bytes under `paths` without following symlinks. Files stored in a remote or
+/// archive tier are only counted with `allow_recall`, since the wipe refuses them otherwise.
+pub fn estimate_targets(paths: &[String], allow_recall: bool) -> TargetEstimate {
+    let mut estimate = TargetEstimate::default();
+    for path in paths {
+        for entry in WalkDir::new(path).follow_links(false).into_iter().flatten() {
+            if entry.file_type().is_file() {
+                let metadata = entry.metadata().ok();
+                let attributes = metadata.as_ref().map(remote_tier::attributes).unwrap_or(0);
+                estimate.add(attributes, metadata.map(|m| m.len()).unwrap_or(0), allow_recall);
+            }
+        }
+    }
+    estimate
+}
+
+/// Side effects of each policy branch, injected so the branching can be tested without a running app.
+pub(crate) trait ContextWipeExecutor {
+    /// Hand the selection to the frontend, as before this setting existed.
+    fn ask(&self, payload: ContextWipePayload);
+    /// The user's answer, or `dialog_unavailable` when no dialog could ask it. `assume_yes` stands
+    /// in for the answer only then.
+    fn confirm(
+        &self,
+        paths: &[String],
+        estimate: TargetEstimate,
+        algorithm: &WipeAlgorithm,
+        assume_yes: bool,
+    ) -> Result<bool, BitBurnError>;
+    fn start(&self, paths: Vec<String>, algorithm: WipeAlgorithm);
+    /// Queue the selection and return the number of queued jobs.
+    fn enqueue(&self, paths: Vec<String>) -> usize;
+    fn report_invalid(&self, invalid: Vec<String>, source: String);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ContextPolicyOutcome {
+    Asked,
+    Started,
+    Declined,
+    Queued { queued: usize },
+    /// Nothing valid was left to wipe.
+    NothingToWipe,
+}
+
+/// Route a context-menu payload according to `behavior`.
+/// Invalid entries always reach the user; `Immediate` and `Queue` also drop protected paths and
+/// volume roots first, since only the UI can take the typed confirmation a volume root needs.
+/// `Immediate` falls back to asking in the window when the native dialog cannot be shown.
+pub(crate) fn apply_context_policy(
+    behavior: ContextMenuBehavior,
+    algorithm: WipeAlgorithm,
+    protected: &ProtectedPaths,
+    payload: ContextWipePayload,
+    executor: &dyn ContextWipeExecutor,
+) -> ContextPolicyOutcome {
+    if behavior == ContextMenuBehavior::Ask {
+        executor.ask(payload);
+        return ContextPolicyOutcome::Asked;
+    }
+
+    let ContextWipePayload { paths, mut invalid, source, volume_roots, assume_yes, .. } = payload;
+    let allowed = screen_paths(paths, &volume_roots, protected, &mut invalid);
+    if !invalid.is_empty() {
+        executor.report_invalid(invalid, source.clone());
+    }
+    if allowed.is_empty() {
+        return ContextPolicyOutcome::NothingToWipe;
+    }
+
+    if behavior == ContextMenuBehavior::Queue {
+        let queued = executor.enqueue(allowed);
+        return ContextPolicyOutcome::Queued { queued };
+    }
+
+    let estimate = estimate_targets(&allowed, false);
+    match executor.confirm(&allowed, estimate, &algorithm, assume_yes) {
+        Ok(true) => {}
+        Ok(false) => return ContextPolicyOutcome::Declined,
+        Err(_) => {
+            executor.ask(ContextWipePayload {
+                paths: allowed,
+                invalid: Vec::new(),
+                source,
+                volume_roots: Vec::new(),
+                elevation_mismatch: None,
+                token: None,
+                assume_yes,
+            });
+            return ContextPolicyOutcome::Asked;
+        }
+    }
+    executor.start(allowed, algorithm);
+    ContextPolicyOutcome::Started
+}
+
+/// Keep the entries of `paths` that may be wiped without the UI's typed confirmation, adding a
+/// message to `invalid` for each protected path and volume root left out.
+fn screen_paths(
+    paths: Vec<String>,
+    volume_roots: &[String],
+    protected: &ProtectedPaths,
+    invalid: &mut Vec<String>,
+) -> Vec<String> {
+    let mut allowed = Vec::with_capacity(paths.len());
+    for path in paths {
+        if volume_roots.contains(&path) {
+            invalid.push(BitBurnError::VolumeRoot { path }.to_string());
+            continue;
+        }
+        match protected.check(Path::new(&path)) {
+            Ok(()) => allowed.push(path),
+            Err(err) => invalid.push(err.to_string()),
+        }
+    }
+    allowed
+}
+
+/// What `run_context_wipe` should do with a buffered selection once it has been screened and confirmed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ContextRunPlan {
+    Run(Vec<String>),
+    Declined,
+    NothingToWipe,
+}
+
+/// Screen a buffered selection like `Immediate` does, drop entries deleted since it was made,
+/// then confirm the rest with its estimate. Returns the plan and a message per entry left out, or
+/// `dialog_unavailable` when the confirmation could not be asked.
+pub(crate) fn plan_context_run(
+    payload: ContextWipePayload,
+    algorithm: &WipeAlgorithm,
+    protected: &ProtectedPaths,
+    executor: &dyn ContextWipeExecutor,
+) -> Result<(ContextRunPlan, Vec<String>), BitBurnError> {
+    let ContextWipePayload { paths, mut invalid, volume_roots, assume_yes, .. } = payload;
+    let mut present = Vec::with_capacity(paths.len());
+    for path in paths {
+        if Path::new(&path).symlink_metadata().is_ok() {
+            present.push(path);
+        } else {
+            invalid.push(BitBurnError::PathNotFound { path }.to_string());
+        }
+    }
+    let allowed = screen_paths(present, &volume_roots, protected, &mut invalid);
+    if allowed.is_empty() {
+        return Ok((ContextRunPlan::NothingToWipe, invalid));
+    }
+    if !executor.confirm(&allowed, estimate_targets(&allowed, false), algorithm, assume_yes)? {
+        return Ok((ContextRunPlan::Declined, invalid));
+    }
+    Ok((ContextRunPlan::Run(allowed), invalid))
+}
+
+/// Context-menu selections handed to the frontend, kept until `run_context_wipe` claims them by token.
+#[derive(Debug, Default)]
+pub struct ContextPayloads {
+    buffered: Mutex<VecDeque<(String, ContextWipePayload)>>,
+}
+
+impl ContextPayloads {
+    /// Keep `payload` and return the token that claims it.
+    pub fn buffer(&self, payload: ContextWipePayload) -> String {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        let token = format!("ctx-{:x}-{:08x}", nanos, rand::random::<u32>());
+        let mut buffered = self.buffered.lock().unwrap_or_else(|p| p.into_inner());
+        if buffered.len() == MAX_BUFFERED_PAYLOADS {
+            buffered.pop_front();
+        }
+        buffered.push_back((token.clone(), payload));
+        token
+    }
+
+    /// Remove and return the selection `token` was issued for; each token works once.
+    pub fn take(&self, token: &str) -> Option<ContextWipePayload> {
+        let mut buffered = self.buffered.lock().unwrap_or_else(|p| p.into_inner());
+        let index = buffered.iter().position(|(issued, _)| issued == token)?;
+        buffered.remove(index).map(|(_, payload)| payload)
+    }
+
+    /// Put back a selection taken with `token` that could not be confirmed, so the same token
+    /// claims it again once the window has asked instead.
+    pub fn restore(&self, token: String, payload: ContextWipePayload) {
+        let mut buffered = self.buffered.lock().unwrap_or_else(|p| p.into_inner());
+        if buffered.len() == MAX_BUFFERED_PAYLOADS {
+            buffered.pop_front();
+        }
+        buffered.push_back((token, payload));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextWipeStatus {
+    Completed,
+    Declined,
+    /// Every entry was invalid, protected or deleted since the selection was made.
+    NothingToWipe,
+}
+
+/// Outcome of `run_context_wipe`: the batch result when it ran, and why entries were left out.
+#[derive(Serialize)]
+pub struct ContextWipeRun {
+    pub status: ContextWipeStatus,
+    pub invalid: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<WipeResult>,
+}
+
+/// A context-menu selection waiting for the user to start it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueuedContextWipe {
+    pub job_id: u64,
+    pub paths: Vec<String>,
+    pub queued_at: u64,
+}
+
+/// Selections queued by the `queue` behavior, in arrival order.
+#[derive(Debug, Default)]
+pub struct ContextWipeQueue {
+    jobs: Mutex<Vec<QueuedContextWipe>>,
+    next_id: Mutex<u64>,
+}
+
+impl ContextWipeQueue {
+    pub fn push(&self, paths: Vec<String>) -> QueuedContextWipe {
+        let job_id = {
+            let mut next = self.next_id.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            *next += 1;
+            *next
+        };
+        let job = QueuedContextWipe {
+            job_id,
+            paths,
+            queued_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        };
+        self.lock().push(job.clone());
+        job
+    }
+
+    pub fn jobs(&self) -> Vec<QueuedContextWipe> {
+        self.lock().clone()
+    }
+
+    pub fn take(&self) -> Vec<QueuedContextWipe> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<QueuedContextWipe>> {
+        self.jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Executor backed by the running app: native dialogs, the operation registry and the tray.
+pub(crate) struct AppContextExecutor {
+    pub app: AppHandle,
+    /// The answer `confirm` recorded, copied into the report of the wipe that follows.
+    consent: Mutex<Option<Consent>>,
+}
+
+impl AppContextExecutor {
+    pub fn new(app: AppHandle) -> Self {
+        AppContextExecutor { app, consent: Mutex::new(None) }
+    }
+
+    /// Executor for a selection the user already confirmed in the window: `confirm` answers with
+    /// `consent` instead of a dialog.
+    fn confirmed(app: AppHandle, consent: Consent) -> Self {
+        AppContextExecutor { app, consent: Mutex::new(Some(consent)) }
+    }
+
+    fn take_consent(&self) -> Option<Consent> {
+        self.consent.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take()
+    }
+}
+
+impl ContextWipeExecutor for AppContextExecutor {
+    fn ask(&self, payload: ContextWipePayload) {
+        dispatch_context_wipe(&self.app, payload);
+    }
+
+    fn confirm(
+        &self,
+        paths: &[String],
+        estimate: TargetEstimate,
+        algorithm: &WipeAlgorithm,
+        assume_yes: bool,
+    ) -> Result<bool, BitBurnError> {
+        let mut consent = self.consent.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if consent.as_ref().is_some_and(|consent| consent.confirmed) {
+            return Ok(true);
+        }
+        let message = messages::CONFIRM_CONTEXT_WIPE.render(json!({
+            "files": estimate.files,
+            "size_mb": format!("{:.1}", estimate.bytes as f64 / 1024.0 / 1024.0),
+            "bytes": estimate.bytes,
+            "items": paths.len(),
+            "algorithm": format!("{:?}", algorithm),
+        }));
+        let (confirmed, origin) = match confirm_dialog::confirm(&self.app, &message.text, paths) {
+            Ok(confirmed) => (confirmed, ConsentOrigin::Dialog),
+            Err(_) if assume_yes => (true, ConsentOrigin::CommandLine),
+            Err(e) => return Err(e),
+        };
+        let now = SystemTime::now();
+        *consent = Some(match self.app.try_state::<ConsentStore>() {
+            Some(store) => store.record(&message.text, paths, confirmed, origin, now),
+            None => ConsentStore::default().record(&message.text, paths, confirmed, origin, now),
+        });
+        Ok(confirmed)
+    }
+
+    fn start(&self, paths: Vec<String>, algorithm: WipeAlgorithm) {
+        let app = self.app.clone();
+        let consent = self.take_consent();
+        tauri::async_runtime::spawn(async move {
+            let registry = app.state::<OperationRegistry>().inner().clone();
+            let passes = algorithm.default_passes();
+            let options = crate::BatchOptions {
+                source: Some("context-menu".to_string()),
+                consent,
+                ..Default::default()
+            };
+            let result = start_file_wipe(app.clone(), "main".to_string(), &registry, paths, passes, algorithm, options)
+                .await
+                .unwrap_or_else(crate::WipeResult::failure);
+            diagnostics::emit(&app, "context_wipe_finished", &result);
+        });
+    }
+
+    fn enqueue(&self, paths: Vec<String>) -> usize {
+        let Some(queue) = self.app.try_state::<ContextWipeQueue>() else {
+            return 0;
+        };
+        let job = queue.push(paths);
+        let queued = queue.jobs().len();
+        crate::ui::set_tray_tooltip(&self.app, &queued_tooltip(queued));
+        diagnostics::emit(&self.app, "context_wipe_queued", &job);
+        queued
+    }
+
+    fn report_invalid(&self, invalid: Vec<String>, source: String) {
+        dispatch_context_wipe(
+            &self.app,
+            ContextWipePayload {
+                paths: Vec::new(),
+                invalid,
+                source,
+                volume_roots: Vec::new(),
+                elevation_mismatch: None,
+                token: None,
+                assume_yes: false,
+            },
+        );
+    }
+}
+
+pub(crate) fn queued_tooltip(queued: usize) -> String {
+    match queued {
+        0 => "BitBurn".to_string(),
+        1 => "BitBurn - 1 queued wipe".to_string(),
+        n => format!("BitBurn - {} queued wipes", n),
+    }
+}
+
+/// Apply the configured behavior to a context-menu selection. Dialogs block, so this runs off the main thread.
+pub fn handle_context_payload(app: &AppHandle, payload: ContextWipePayload) {
+    if payload.paths.is_empty() && payload.invalid.is_empty() {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let settings = app.try_state::<SettingsStore>().map(|store| store.get()).unwrap_or_default();
+        let behavior = settings.context_menu_behavior;
+        let algorithm = policy::current(&app).map_or(settings.default_algorithm(), |policy| {
+            policy.default_algorithm(settings.default_algorithm())
+        });
+        let outcome = apply_context_policy(
+            behavior,
+            algorithm,
+            &ProtectedPaths::current(),
+            payload,
+            &AppContextExecutor::new(app.clone()),
+        );
+        log_event("context_policy_applied", json!({"behavior": behavior, "outcome": format!("{:?}", outcome)}));
+    });
+}
+
+/// Estimate, confirm and wipe a selection buffered by `dispatch_context_wipe` in one call, so the
+/// files confirmed are the files wiped however many selections arrive meanwhile. The native dialog
+/// shows the estimate; the batch emits its usual events. When the dialog cannot be shown the
+/// selection stays buffered under the same token and the call fails with `dialog_unavailable`;
+/// calling again with the `consent_token` of the window's own confirmation of the selection's
+/// paths runs it without a dialog.
+#[tauri::command]
+pub async fn run_context_wipe(
+    window: tauri::Window,
+    payloads: State<'_, ContextPayloads>,
+    registry: State<'_, OperationRegistry>,
+    payload_token: String,
+    algorithm: WipeAlgorithm,
+    passes: Option<u32>,
+    consent_token: Option<String>,
+) -> Result<ContextWipeRun, BitBurnError> {
+    let payload = payloads
+        .take(&payload_token)
+        .ok_or_else(|| BitBurnError::ContextPayloadNotFound { token: payload_token.clone() })?;
+    let source = payload.source.clone();
+    let app = window.app_handle().clone();
+    let executor = match consent_token {
+        Some(token) => match app.state::<ConsentStore>().redeem(&token, &payload.paths, SystemTime::now()) {
+            Ok(consent) => AppContextExecutor::confirmed(app.clone(), consent),
+            Err(e) => {
+                payloads.restore(payload_token, payload);
+                return Err(e);
+            }
+        },
+        None => AppContextExecutor::new(app.clone()),
+    };
+    let plan_algorithm = algorithm.clone();
+    let retained = payload.clone();
+    let planned = spawn_blocking(move || {
+        plan_context_run(payload, &plan_algorithm, &ProtectedPaths::current(), &executor)
+            .map(|(plan, invalid)| (plan, invalid, executor.take_consent()))
+    })
+    .await
+    .map_err(|e| BitBurnError::internal(format!("run_context_wipe task join error: {}", e)))?;
+    let (plan, invalid, consent) = match planned {
+        Ok(planned) => planned,
+        Err(e) => {
+            log_event("context_wipe_unconfirmed", json!({"token": payload_token, "code": e.code()}));
+            payloads.restore(payload_token, retained);
+            return Err(e);
+        }
+    };
+    log_event(
+        "context_wipe_run",
+        json!({"token": payload_token, "plan": format!("{:?}", plan), "invalid": invalid.len()}),
+    );
+
+    let (status, result) = match plan {
+        ContextRunPlan::Declined => (ContextWipeStatus::Declined, None),
+        ContextRunPlan::NothingToWipe => (ContextWipeStatus::NothingToWipe, None),
+        ContextRunPlan::Run(paths) => {
+            let passes = passes.unwrap_or_else(|| algorithm.default_passes());
+            let options = BatchOptions {
+                source: Some(source),
+                consent,
+                ..Default::default()
+            };
+            let window_label = window.label().to_string();
+            let result = start_file_wipe(app, window_label, &registry, paths, passes, algorithm, options).await?;
+            (ContextWipeStatus::Completed, Some(result))
+        }
+    };
+    Ok(ContextWipeRun { status, invalid, result })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextMenuPolicy {
+    pub behavior: ContextMenuBehavior,
+    pub default_algorithm: WipeAlgorithm,
+}
+
+#[tauri::command]
+pub async fn get_context_menu_behavior(settings: State<'_, SettingsStore>) -> Result<ContextMenuPolicy, BitBurnError> {
+    let current = settings.get();
+    Ok(ContextMenuPolicy {
+        behavior: current.context_menu_behavior,
+        default_algorithm: current.default_algorithm(),
+    })
+}
+
+#[tauri::command]
+pub async fn set_context_menu_behavior(
+    settings: State<'_, SettingsStore>,
+    behavior: ContextMenuBehavior,
+    default_algorithm: Option<WipeAlgorithm>,
+) -> Result<ContextMenuPolicy, BitBurnError> {
+    let updated = settings.update(|s| {
+        s.context_menu_behavior = behavior;
+        if default_algorithm.is_some() {
+            s.default_algorithm = default_algorithm;
+        }
+    })?;
+    log_event("context_menu_behavior_configured", json!({"behavior": behavior}));
+    Ok(ContextMenuPolicy {
+        behavior: updated.context_menu_behavior,
+        default_algorithm: updated.default_algorithm(),
+    })
+}
+
+/// Selections queued by context-menu invocations.
+#[tauri::command]
+pub async fn get_context_queue(queue: State<'_, ContextWipeQueue>) -> Result<Vec<QueuedContextWipe>, BitBurnError> {
+    Ok(queue.jobs())
+}
+
+/// Remove and return every queued selection so the UI can start them.
+#[tauri::command]
+pub async fn take_context_queue(
+    app: AppHandle,
+    queue: State<'_, ContextWipeQueue>,
+) -> Result<Vec<QueuedContextWipe>, BitBurnError> {
+    let jobs = queue.take();
+    crate::ui::set_tray_tooltip(&app, &queued_tooltip(0));
+    Ok(jobs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::fs;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    #[derive(Default)]
+    struct MockExecutor {
+        confirm_answer: bool,
+        /// Why the dialog cannot be shown, if it cannot.
+        dialog_unavailable: Option<&'static str>,
+        calls: RefCell<Vec<String>>,
+        started: RefCell<Vec<(Vec<String>, WipeAlgorithm)>>,
+        estimates: RefCell<Vec<TargetEstimate>>,
+        invalid: RefCell<Vec<String>>,
+    }
+
+    impl ContextWipeExecutor for MockExecutor {
+        fn ask(&self, payload: ContextWipePayload) {
+            self.calls.borrow_mut().push(format!("ask:{}:{}", payload.paths.len(), payload.invalid.len()));
+        }
+
+        fn confirm(
+            &self,
+            _paths: &[String],
+            estimate: TargetEstimate,
+            _algorithm: &WipeAlgorithm,
+            assume_yes: bool,
+        ) -> Result<bool, BitBurnError> {
+            self.calls.borrow_mut().push("confirm".to_string());
+            self.estimates.borrow_mut().push(estimate);
+            match self.dialog_unavailable {
+                Some(reason) if !assume_yes => {
+                    Err(BitBurnError::DialogUnavailable {
+                        reason: reason.to_string(),
+                        prompt: "Wipe?".to_string(),
+                        nonce: "prompt-test".to_string(),
+                    })
+                }
+                Some(_) => Ok(true),
+                None => Ok(self.confirm_answer),
+            }
+        }
+
+        fn start(&self, paths: Vec<String>, algorithm: WipeAlgorithm) {
+            self.calls.borrow_mut().push("start".to_string());
+            self.started.borrow_mut().push((paths, algorithm));
+        }
+
+        fn enqueue(&self, _paths: Vec<String>) -> usize {
+            self.calls.borrow_mut().push("enqueue".to_string());
+            1
+        }
+
+        fn report_invalid(&self, invalid: Vec<String>, _source: String) {
+            self.calls.borrow_mut().push("report_invalid".to_string());
+            self.invalid.borrow_mut().extend(invalid);
+        }
+    }
+
+    fn payload(paths: Vec<String>, invalid: Vec<String>) -> ContextWipePayload {
+        ContextWipePayload {
+            paths,
+            invalid,
+            source: "context-menu".to_string(),
+            volume_roots: Vec::new(),
+            elevation_mismatch: None,
+            token: None,
+            assume_yes: false,
+        }
+    }
+
+    fn protected_dir(dir: &Path) -> ProtectedPaths {
+        ProtectedPaths::new(vec![dir.join("system")], Vec::new())
+    }
+
+    #[test]
+    fn ask_hands_the_whole_payload_to_the_frontend() {
+        let executor = MockExecutor::default();
+        let outcome = apply_context_policy(
+            ContextMenuBehavior::Ask,
+            WipeAlgorithm::NistPurge,
+            &ProtectedPaths::default(),
+            payload(vec!["/data/a.txt".into()], vec!["Path not found: /x".into()]),
+            &executor,
+        );
+        assert_eq!(outcome, ContextPolicyOutcome::Asked);
+        assert_eq!(*executor.calls.borrow(), vec!["ask:1:1"]);
+    }
+
+    #[test]
+    fn immediate_confirms_with_estimate_then_starts_with_default_algorithm() {
+        let dir = create_test_dir().unwrap();
+        let file = dir.join("a.bin");
+        fs::write(&file, vec![0u8; 2048]).unwrap();
+        let executor = MockExecutor {
+            confirm_answer: true,
+            ..Default::default()
+        };
+
+        let outcome = apply_context_policy(
+            ContextMenuBehavior::Immediate,
+            WipeAlgorithm::Gutmann,
+            &protected_dir(&dir),
+            payload(vec![file.to_string_lossy().to_string()], Vec::new()),
+            &executor,
+        );
+
+        assert_eq!(outcome, ContextPolicyOutcome::Started);
+        assert_eq!(*executor.calls.borrow(), vec!["confirm", "start"]);
+        assert_eq!(executor.estimates.borrow()[0], TargetEstimate { files: 1, bytes: 2048, remote_tiered: 0 });
+        assert_eq!(executor.started.borrow()[0].1, WipeAlgorithm::Gutmann);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn immediate_never_starts_volume_roots() {
+        let dir = create_test_dir().unwrap();
+        let file = dir.join("a.bin");
+        fs::write(&file, b"data").unwrap();
+        let (file, root) = (file.to_string_lossy().to_string(), "D:\\".to_string());
+        let executor = MockExecutor {
+            confirm_answer: true,
+            ..Default::default()
+        };
+        let mut tagged = payload(vec![root.clone(), file.clone()], Vec::new());
+        tagged.volume_roots = vec![root];
+
+        let outcome = apply_context_policy(
+            ContextMenuBehavior::Immediate,
+            WipeAlgorithm::NistPurge,
+            &protected_dir(&dir),
+            tagged,
+            &executor,
+        );
+
+        assert_eq!(outcome, ContextPolicyOutcome::Started);
+        assert_eq!(executor.started.borrow()[0].0, vec![file]);
+        assert!(executor.invalid.borrow()[0].contains("root of a volume"));
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn declined_confirmation_starts_nothing() {
+        let dir = create_test_dir().unwrap();
+        let executor = MockExecutor::default();
+        let outcome = apply_context_policy(
+            ContextMenuBehavior::Immediate,
+            WipeAlgorithm::NistPurge,
+            &protected_dir(&dir),
+            payload(vec![dir.join("a.txt").to_string_lossy().to_string()], Vec::new()),
+            &executor,
+        );
+        assert_eq!(outcome, ContextPolicyOutcome::Declined);
+        assert!(executor.started.borrow().is_empty());
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn queue_enqueues_silently() {
+        let dir = create_test_dir().unwrap();
+        let executor = MockExecutor::default();
+        let outcome = apply_context_policy(
+            ContextMenuBehavior::Queue,
+            WipeAlgorithm::NistPurge,
+            &protected_dir(&dir),
+            payload(vec![dir.join("a.txt").to_string_lossy().to_string()], Vec::new()),
+            &executor,
+        );
+        assert_eq!(outcome, ContextPolicyOutcome::Queued { queued: 1 });
+        assert_eq!(*executor.calls.borrow(), vec!["enqueue"]);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn invalid_and_protected_paths_surface_in_every_silent_mode() {
+        let dir = create_test_dir().unwrap();
+        let protected = protected_dir(&dir);
+        let system_file = dir.join("system").join("kernel.bin").to_string_lossy().to_string();
+        let user_file = dir.join("notes.txt").to_string_lossy().to_string();
+
+        for behavior in [ContextMenuBehavior::Immediate, ContextMenuBehavior::Queue] {
+            let executor = MockExecutor::default();
+            apply_context_policy(
+                behavior,
+                WipeAlgorithm::NistPurge,
+                &protected,
+                payload(vec![system_file.clone(), user_file.clone()], vec!["Path not found: /gone".into()]),
+                &executor,
+            );
+            assert_eq!(executor.calls.borrow()[0], "report_invalid");
+            let invalid = executor.invalid.borrow();
+            assert_eq!(invalid.len(), 2);
+            assert!(invalid.iter().any(|msg| msg.contains("protected")));
+        }
+
+        let executor = MockExecutor::default();
+        let outcome = apply_context_policy(
+            ContextMenuBehavior::Queue,
+            WipeAlgorithm::NistPurge,
+            &protected,
+            payload(vec![system_file], Vec::new()),
+            &executor,
+        );
+        assert_eq!(outcome, ContextPolicyOutcome::NothingToWipe);
+        assert_eq!(*executor.calls.borrow(), vec!["report_invalid"]);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn declined_buffered_run_wipes_nothing_and_spends_the_token() {
+        let dir = create_test_dir().unwrap();
+        let file = dir.join("a.bin");
+        fs::write(&file, vec![0u8; 512]).unwrap();
+        let payloads = ContextPayloads::default();
+        let token = payloads.buffer(payload(vec![file.to_string_lossy().to_string()], Vec::new()));
+
+        let executor = MockExecutor::default();
+        let buffered = payloads.take(&token).expect("buffered payload");
+        let (plan, invalid) = plan_context_run(buffered, &WipeAlgorithm::NistPurge, &protected_dir(&dir), &executor).unwrap();
+
+        assert_eq!(plan, ContextRunPlan::Declined);
+        assert!(invalid.is_empty());
+        assert_eq!(executor.estimates.borrow()[0], TargetEstimate { files: 1, bytes: 512, remote_tiered: 0 });
+        assert!(file.exists());
+        assert!(payloads.take(&token).is_none(), "a token is only good once");
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn files_deleted_after_buffering_are_left_out_of_the_run() {
+        let dir = create_test_dir().unwrap();
+        let (kept, gone) = (dir.join("kept.bin"), dir.join("gone.bin"));
+        fs::write(&kept, vec![0u8; 256]).unwrap();
+        fs::write(&gone, vec![0u8; 256]).unwrap();
+        let (kept, gone) = (kept.to_string_lossy().to_string(), gone.to_string_lossy().to_string());
+        let payloads = ContextPayloads::default();
+        let token = payloads.buffer(payload(vec![kept.clone(), gone.clone()], Vec::new()));
+        fs::remove_file(&gone).unwrap();
+
+        let executor = MockExecutor {
+            confirm_answer: true,
+            ..Default::default()
+        };
+        let buffered = payloads.take(&token).unwrap();
+        let (plan, invalid) = plan_context_run(buffered, &WipeAlgorithm::NistPurge, &protected_dir(&dir), &executor).unwrap();
+        assert_eq!(plan, ContextRunPlan::Run(vec![kept.clone()]));
+        assert_eq!(invalid, vec![BitBurnError::PathNotFound { path: gone }.to_string()]);
+        assert_eq!(executor.estimates.borrow()[0], TargetEstimate { files: 1, bytes: 256, remote_tiered: 0 });
+
+        // Once everything is gone there is nothing to confirm.
+        fs::remove_file(&kept).unwrap();
+        let executor = MockExecutor::default();
+        let buffered = payload(vec![kept], Vec::new());
+        let (plan, invalid) = plan_context_run(buffered, &WipeAlgorithm::NistPurge, &protected_dir(&dir), &executor).unwrap();
+        assert_eq!(plan, ContextRunPlan::NothingToWipe);
+        assert_eq!(invalid.len(), 1);
+        assert!(executor.calls.borrow().is_empty());
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn immediate_hands_the_selection_to_the_window_when_no_dialog_can_ask() {
+        let dir = create_test_dir().unwrap();
+        let file = dir.join("a.bin");
+        fs::write(&file, b"data").unwrap();
+        let executor = MockExecutor {
+            dialog_unavailable: Some("session 0"),
+            ..Default::default()
+        };
+
+        let outcome = apply_context_policy(
+            ContextMenuBehavior::Immediate,
+            WipeAlgorithm::NistPurge,
+            &protected_dir(&dir),
+            payload(vec![file.to_string_lossy().to_string()], Vec::new()),
+            &executor,
+        );
+
+        assert_eq!(outcome, ContextPolicyOutcome::Asked);
+        assert_eq!(*executor.calls.borrow(), vec!["confirm", "ask:1:0"]);
+        assert!(executor.started.borrow().is_empty());
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn a_buffered_run_without_a_dialog_needs_yes_on_the_command_line() {
+        let dir = create_test_dir().unwrap();
+        let file = dir.join("a.bin");
+        fs::write(&file, b"data").unwrap();
+        let file = file.to_string_lossy().to_string();
+        let executor = MockExecutor {
+            dialog_unavailable: Some("no answer within 120 seconds"),
+            ..Default::default()
+        };
+
+        let unconfirmed = plan_context_run(
+            payload(vec![file.clone()], Vec::new()),
+            &WipeAlgorithm::NistPurge,
+            &protected_dir(&dir),
+            &executor,
+        );
+        assert!(matches!(unconfirmed, Err(BitBurnError::DialogUnavailable { .. })));
+
+        let mut with_yes = payload(vec![file.clone()], Vec::new());
+        with_yes.assume_yes = true;
+        let (plan, _) = plan_context_run(with_yes, &WipeAlgorithm::NistPurge, &protected_dir(&dir), &executor).unwrap();
+        assert_eq!(plan, ContextRunPlan::Run(vec![file]));
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn a_restored_selection_is_claimed_again_by_its_token() {
+        let payloads = ContextPayloads::default();
+        let token = payloads.buffer(payload(vec!["/a".into()], Vec::new()));
+        let taken = payloads.take(&token).unwrap();
+        payloads.restore(token.clone(), taken);
+        assert_eq!(payloads.take(&token).map(|payload| payload.paths), Some(vec!["/a".to_string()]));
+    }
+
+    #[test]
+    fn buffer_drops_the_oldest_selection_when_full() {
+        let payloads = ContextPayloads::default();
+        let first = payloads.buffer(payload(vec!["/a".into()], Vec::new()));
+        let tokens: Vec<String> = (0..MAX_BUFFERED_PAYLOADS)
+            .map(|_| payloads.buffer(payload(vec!["/b".into()], Vec::new())))
+            .collect();
+        assert!(payloads.take(&first).is_none());
+        assert!(tokens.iter().all(|token| payloads.take(token).is_some()));
+    }
+
+    #[test]
+    fn queue_hands_out_jobs_once() {
+        let queue = ContextWipeQueue::default();
+        let first = queue.push(vec!["/a".into()]);
+        let second = queue.push(vec!["/b".into()]);
+        assert_eq!((first.job_id, second.job_id), (1, 2));
+        assert_eq!(queue.jobs().len(), 2);
+        assert_eq!(queue.take().len(), 2);
+        assert!(queue.take().is_empty());
+    }
+}
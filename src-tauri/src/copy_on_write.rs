@@ -0,0 +1,219 @@
+//! Copy-on-write file systems. Btrfs, ZFS, APFS, ReFS and bcachefs write changed blocks to a new
+//! place, so overwriting a file there leaves its old blocks, and snapshots of them, on disk until
+//! the file system reuses them. Each batch file's file system is looked up before it is wiped and
+//! recorded in its outcome, and the batch's message and report say the overwrite may not have
+//! reached the old data. On Btrfs the file is first marked NOCOW, which the kernel only honours
+//! for files without data yet; the flag is read back so the report says whether it took.
+
+use serde::Serialize;
+use std::path::Path;
+
+/// `f_type` of `statfs` for the copy-on-write file systems Linux mounts.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const BTRFS_SUPER_MAGIC: u32 = 0x9123_683E;
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const ZFS_SUPER_MAGIC: u32 = 0x2FC1_2FC1;
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const BCACHEFS_SUPER_MAGIC: u32 = 0xCA45_1A4E;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CowFileSystem {
+    Btrfs,
+    Zfs,
+    Apfs,
+    Refs,
+    Bcachefs,
+}
+
+impl CowFileSystem {
+    /// The copy-on-write file system a name such as `GetVolumeInformationW` or `sysinfo` reports.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "btrfs" => Some(CowFileSystem::Btrfs),
+            "zfs" => Some(CowFileSystem::Zfs),
+            "apfs" => Some(CowFileSystem::Apfs),
+            "refs" => Some(CowFileSystem::Refs),
+            "bcachefs" => Some(CowFileSystem::Bcachefs),
+            _ => None,
+        }
+    }
+
+    /// The copy-on-write file system a Linux `statfs` `f_type` stands for.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub fn from_statfs_magic(magic: u32) -> Option<Self> {
+        match magic {
+            BTRFS_SUPER_MAGIC => Some(CowFileSystem::Btrfs),
+            ZFS_SUPER_MAGIC => Some(CowFileSystem::Zfs),
+            BCACHEFS_SUPER_MAGIC => Some(CowFileSystem::Bcachefs),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CowFileSystem::Btrfs => "Btrfs",
+            CowFileSystem::Zfs => "ZFS",
+            CowFileSystem::Apfs => "APFS",
+            CowFileSystem::Refs => "ReFS",
+            CowFileSystem::Bcachefs => "bcachefs",
+        }
+    }
+}
+
+/// The copy-on-write file system `path` is stored on; `None` for any other or when it cannot be told.
+#[cfg(target_os = "linux")]
+pub fn detect(path: &Path) -> Option<CowFileSystem> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    // The magic numbers are 32-bit; `f_type` is wider or signed on some targets.
+    CowFileSystem::from_statfs_magic(stat.f_type as u32)
+}
+
+#[cfg(target_os = "macos")]
+pub fn detect(path: &Path) -> Option<CowFileSystem> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let name = unsafe { std::ffi::CStr::from_ptr(stat.f_fstypename.as_ptr()) };
+    CowFileSystem::from_name(&name.to_string_lossy())
+}
+
+#[cfg(windows)]
+pub fn detect(path: &Path) -> Option<CowFileSystem> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{GetVolumeInformationW, GetVolumePathNameW};
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut root = [0u16; 261];
+    let mut name = [0u16; 261];
+    let found = unsafe {
+        GetVolumePathNameW(wide.as_ptr(), root.as_mut_ptr(), root.len() as u32) != 0
+            && GetVolumeInformationW(
+                root.as_ptr(),
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                name.as_mut_ptr(),
+                name.len() as u32,
+            ) != 0
+    };
+    if !found {
+        return None;
+    }
+    let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+    CowFileSystem::from_name(&String::from_utf16_lossy(&name[..len]))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn detect(_path: &Path) -> Option<CowFileSystem> {
+    None
+}
+
+/// Set the Btrfs NOCOW attribute on `path` and report whether it is set afterwards. Btrfs accepts
+/// the flag on a file that already has data but keeps copying its blocks, so only the read-back
+/// counts.
+#[cfg(target_os = "linux")]
+fn disable_cow(path: &Path) -> bool {
+    use std::os::fd::AsRawFd;
+
+    /// `FS_NOCOW_FL` in the inode flags.
+    const NOCOW: libc::c_long = 0x0080_0000;
+
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let fd = file.as_raw_fd();
+    let mut flags: libc::c_long = 0;
+    if unsafe { libc::ioctl(fd, libc::FS_IOC_GETFLAGS, &mut flags) } != 0 {
+        return false;
+    }
+    if flags & NOCOW != 0 {
+        return true;
+    }
+    flags |= NOCOW;
+    if unsafe { libc::ioctl(fd, libc::FS_IOC_SETFLAGS, &flags) } != 0 {
+        return false;
+    }
+    let mut read_back: libc::c_long = 0;
+    unsafe { libc::ioctl(fd, libc::FS_IOC_GETFLAGS, &mut read_back) == 0 && read_back & NOCOW != 0 }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn disable_cow(_path: &Path) -> bool {
+    false
+}
+
+/// What a file's outcome records about copy-on-write.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CopyOnWrite {
+    pub file_system: Option<CowFileSystem>,
+    /// Whether NOCOW was in effect before the first pass; only tried on Btrfs.
+    pub nocow_applied: Option<bool>,
+}
+
+/// Look up the file system `path` is on and, on Btrfs, try to stop it copying the file's blocks
+/// before they are overwritten.
+pub fn prepare(path: &Path) -> CopyOnWrite {
+    let file_system = detect(path);
+    let nocow_applied = (file_system == Some(CowFileSystem::Btrfs)).then(|| disable_cow(path));
+    CopyOnWrite { file_system, nocow_applied }
+}
+
+/// The copy-on-write file systems among `file_systems`, by name, each once, in the order first seen.
+pub fn names(file_systems: impl IntoIterator<Item = CowFileSystem>) -> Vec<&'static str> {
+    let mut names = Vec::new();
+    for file_system in file_systems {
+        if !names.contains(&file_system.name()) {
+            names.push(file_system.name());
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_system_names_and_magic_numbers_are_mapped() {
+        for (name, expected) in [
+            ("btrfs", CowFileSystem::Btrfs),
+            ("ZFS", CowFileSystem::Zfs),
+            ("apfs", CowFileSystem::Apfs),
+            ("ReFS", CowFileSystem::Refs),
+            ("bcachefs", CowFileSystem::Bcachefs),
+        ] {
+            assert_eq!(CowFileSystem::from_name(name), Some(expected), "{}", name);
+        }
+        for name in ["NTFS", "ext4", "exfat", "hfs", "tmpfs", ""] {
+            assert_eq!(CowFileSystem::from_name(name), None, "{}", name);
+        }
+        assert_eq!(CowFileSystem::from_statfs_magic(0x9123_683E), Some(CowFileSystem::Btrfs));
+        assert_eq!(CowFileSystem::from_statfs_magic(0x2FC1_2FC1), Some(CowFileSystem::Zfs));
+        assert_eq!(CowFileSystem::from_statfs_magic(0xCA45_1A4E), Some(CowFileSystem::Bcachefs));
+        // ext4 and tmpfs.
+        assert_eq!(CowFileSystem::from_statfs_magic(0xEF53), None);
+        assert_eq!(CowFileSystem::from_statfs_magic(0x0102_1994), None);
+        assert_eq!(serde_json::to_value(CowFileSystem::Refs).unwrap(), "refs");
+    }
+
+    #[test]
+    fn nocow_is_only_tried_on_btrfs() {
+        let dir = std::env::temp_dir();
+        let prepared = prepare(&dir);
+        assert_eq!(prepared.nocow_applied.is_some(), prepared.file_system == Some(CowFileSystem::Btrfs));
+        assert_eq!(names([CowFileSystem::Zfs, CowFileSystem::Btrfs, CowFileSystem::Zfs]), ["ZFS", "Btrfs"]);
+    }
+}
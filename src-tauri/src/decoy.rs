@@ -0,0 +1,88 @@
+//! Decoy data: an optional final pass, and the free-space fill, written with innocuous-looking
+//! bytes from a user-supplied file or text instead of zeros or noise, so the drive does not
+//! read as wiped. The source is chosen in settings and loaded once per operation; reports name
+//! its SHA-256, never its contents.
+
+use serde::Serialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::settings::SettingsStore;
+use bitburn_core::{DecoyData, WipeError, WipePattern};
+
+/// What a report says about the decoy a wipe wrote.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecoySummary {
+    /// `file` or `text`.
+    pub source: &'static str,
+    pub sha256: String,
+    pub bytes: u64,
+}
+
+/// A loaded decoy source, shared with the writer threads.
+#[derive(Debug, Clone)]
+pub struct Decoy {
+    pub data: Arc<DecoyData>,
+    pub summary: DecoySummary,
+}
+
+impl Decoy {
+    pub fn load(pattern: &WipePattern) -> Result<Self, BitBurnError> {
+        let data = pattern.load().map_err(|e| match e {
+            WipeError::InvalidDecoy(reason) => BitBurnError::InvalidDecoy { reason },
+            other => BitBurnError::internal(other.to_string()),
+        })?;
+        let summary = DecoySummary {
+            source: match pattern {
+                WipePattern::FromFile(_) => "file",
+                WipePattern::FromText(_) => "text",
+            },
+            sha256: hex::encode(Sha256::digest(data.bytes())),
+            bytes: data.bytes().len() as u64,
+        };
+        Ok(Decoy { data: Arc::new(data), summary })
+    }
+}
+
+/// The decoy chosen in settings, loaded; `None` when there is none.
+pub fn from_settings<R: Runtime>(app: &AppHandle<R>) -> Result<Option<Decoy>, BitBurnError> {
+    match app.try_state::<SettingsStore>().and_then(|settings| settings.get().decoy_pattern) {
+        Some(pattern) => Decoy::load(&pattern).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Choose the decoy data written last by every wipe; `None` turns decoys off. The source is
+/// checked before it is saved.
+#[tauri::command]
+pub async fn set_decoy_pattern(
+    settings: State<'_, SettingsStore>,
+    pattern: Option<WipePattern>,
+) -> Result<Option<DecoySummary>, BitBurnError> {
+    let summary = pattern.as_ref().map(Decoy::load).transpose()?.map(|decoy| decoy.summary);
+    settings.update(|s| s.decoy_pattern = pattern)?;
+    log_event("decoy_pattern_configured", json!({"decoy": summary}));
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summaries_carry_the_hash_of_the_source_not_the_source() {
+        let decoy = Decoy::load(&WipePattern::FromText("Quarterly figures attached.".to_string())).unwrap();
+        assert_eq!(decoy.summary.source, "text");
+        assert_eq!(decoy.summary.bytes, 27);
+        assert_eq!(decoy.summary.sha256, hex::encode(Sha256::digest(b"Quarterly figures attached.")));
+        let serialized = serde_json::to_string(&decoy.summary).unwrap();
+        assert!(!serialized.contains("Quarterly"), "{}", serialized);
+
+        let empty = Decoy::load(&WipePattern::FromText(String::new()));
+        assert!(matches!(empty, Err(BitBurnError::InvalidDecoy { .. })));
+    }
+}
@@ -0,0 +1,218 @@
+//! Counters for events the app sends and loses: progress events emitted and coalesced away,
+//! operation log entries evicted and IPC emits that failed. They are plain atomics, bumped on
+//! hot paths, and read by `get_diagnostics` and by exported reports of operations that lost any.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use tauri::{AppHandle, Emitter, EventTarget, Manager, Runtime, State};
+
+use crate::error::BitBurnError;
+
+const WARNING_EVENT: &str = "diagnostics_warning";
+
+#[derive(Debug, Default)]
+struct Counters {
+    events_emitted: AtomicU64,
+    events_coalesced: AtomicU64,
+    log_entries_dropped: AtomicU64,
+    emit_failures: AtomicU64,
+}
+
+impl Counters {
+    fn snapshot(&self) -> DiagnosticsSnapshot {
+        DiagnosticsSnapshot {
+            events_emitted: self.events_emitted.load(Ordering::Relaxed),
+            events_coalesced: self.events_coalesced.load(Ordering::Relaxed),
+            log_entries_dropped: self.log_entries_dropped.load(Ordering::Relaxed),
+            emit_failures: self.emit_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counter values at one moment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DiagnosticsSnapshot {
+    /// Events handed to the IPC bridge, including those that then failed.
+    pub events_emitted: u64,
+    /// Progress snapshots replaced by a newer one before they were sent.
+    pub events_coalesced: u64,
+    /// Operation log entries evicted to keep the log within its capacity.
+    pub log_entries_dropped: u64,
+    /// Emits the IPC bridge rejected, e.g. because the window had closed.
+    pub emit_failures: u64,
+}
+
+impl DiagnosticsSnapshot {
+    /// Whether anything was coalesced away, evicted or failed to send.
+    pub fn has_drops(&self) -> bool {
+        self.events_coalesced > 0 || self.log_entries_dropped > 0 || self.emit_failures > 0
+    }
+}
+
+/// App-wide counters, in managed state.
+#[derive(Debug, Default, Clone)]
+pub struct Diagnostics {
+    totals: Arc<Counters>,
+}
+
+impl Diagnostics {
+    pub fn snapshot(&self) -> DiagnosticsSnapshot {
+        self.totals.snapshot()
+    }
+
+    fn record_emit<T>(&self, result: &tauri::Result<T>) {
+        self.totals.events_emitted.fetch_add(1, Ordering::Relaxed);
+        if result.is_err() {
+            self.totals.emit_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+type Warn = Box<dyn Fn(DiagnosticsSnapshot) + Send + Sync>;
+
+struct Connection {
+    totals: Diagnostics,
+    warn: Warn,
+}
+
+#[derive(Default)]
+struct OperationCounters {
+    counters: Counters,
+    warned: AtomicBool,
+    connection: OnceLock<Connection>,
+}
+
+/// One operation's counters. Each count also goes to the app-wide totals once `connect`ed, and
+/// the first loss calls the warning hook. Cheap to clone; clones share the counters.
+#[derive(Clone, Default)]
+pub struct OperationDiagnostics {
+    inner: Arc<OperationCounters>,
+}
+
+impl std::fmt::Debug for OperationDiagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OperationDiagnostics").field(&self.snapshot()).finish()
+    }
+}
+
+impl OperationDiagnostics {
+    /// Feed the app-wide `totals` too, and call `warn` the first time the operation loses an event.
+    /// Only the first connection counts.
+    pub fn connect(&self, totals: Diagnostics, warn: impl Fn(DiagnosticsSnapshot) + Send + Sync + 'static) {
+        let _ = self.inner.connection.set(Connection { totals, warn: Box::new(warn) });
+    }
+
+    pub fn snapshot(&self) -> DiagnosticsSnapshot {
+        self.inner.counters.snapshot()
+    }
+
+    pub fn event_emitted(&self) {
+        self.add(|c| &c.events_emitted, 1);
+    }
+
+    /// `count` progress snapshots were replaced before they were sent.
+    pub fn events_coalesced(&self, count: u64) {
+        if count > 0 {
+            self.add(|c| &c.events_coalesced, count);
+            self.dropped();
+        }
+    }
+
+    pub fn log_entry_dropped(&self) {
+        self.add(|c| &c.log_entries_dropped, 1);
+        self.dropped();
+    }
+
+    pub fn emit_failed(&self) {
+        self.add(|c| &c.emit_failures, 1);
+        self.dropped();
+    }
+
+    fn add(&self, counter: impl Fn(&Counters) -> &AtomicU64, count: u64) {
+        counter(&self.inner.counters).fetch_add(count, Ordering::Relaxed);
+        if let Some(connection) = self.inner.connection.get() {
+            counter(&connection.totals.totals).fetch_add(count, Ordering::Relaxed);
+        }
+    }
+
+    fn dropped(&self) {
+        if self.inner.warned.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        if let Some(connection) = self.inner.connection.get() {
+            (connection.warn)(self.snapshot());
+        }
+    }
+}
+
+/// Payload of `diagnostics_warning`.
+#[derive(Debug, Clone, Serialize)]
+struct DiagnosticsWarning {
+    operation_id: String,
+    #[serde(flatten)]
+    counters: DiagnosticsSnapshot,
+}
+
+/// Connect `diagnostics` to the app's totals and have its first loss emit `diagnostics_warning`.
+pub fn connect_operation<R: Runtime>(app: &AppHandle<R>, operation_id: &str, diagnostics: &OperationDiagnostics) {
+    let totals = app.try_state::<Diagnostics>().map(|state| state.inner().clone()).unwrap_or_default();
+    let app = app.clone();
+    let operation_id = operation_id.to_string();
+    diagnostics.connect(totals, move |counters| {
+        // Sent outside the coalescer, whose lock the caller may hold; a failure here is not
+        // counted again, the operation is already flagged.
+        let _ = app.emit(WARNING_EVENT, DiagnosticsWarning { operation_id: operation_id.clone(), counters });
+    });
+}
+
+/// Emit `event` to every window, counting it and any failure in the app's totals.
+pub fn emit<R: Runtime, S: Serialize + Clone>(app: &AppHandle<R>, event: &str, payload: S) {
+    let result = app.emit(event, payload);
+    if let Some(diagnostics) = app.try_state::<Diagnostics>() {
+        diagnostics.record_emit(&result);
+    }
+}
+
+/// Emit `event` to `target`, counting it and any failure in the app's totals.
+pub fn emit_to<R: Runtime, I: Into<EventTarget>, S: Serialize + Clone>(app: &AppHandle<R>, target: I, event: &str, payload: S) {
+    let result = app.emit_to(target, event, payload);
+    if let Some(diagnostics) = app.try_state::<Diagnostics>() {
+        diagnostics.record_emit(&result);
+    }
+}
+
+/// App-wide event and log counters since startup.
+#[tauri::command]
+pub async fn get_diagnostics(diagnostics: State<'_, Diagnostics>) -> Result<DiagnosticsSnapshot, BitBurnError> {
+    Ok(diagnostics.snapshot())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn operation_counts_reach_the_totals_and_warn_once() {
+        let totals = Diagnostics::default();
+        let warnings: Arc<Mutex<Vec<DiagnosticsSnapshot>>> = Arc::default();
+        let operation = OperationDiagnostics::default();
+        let seen = warnings.clone();
+        operation.connect(totals.clone(), move |snapshot| seen.lock().unwrap().push(snapshot));
+
+        operation.event_emitted();
+        operation.events_coalesced(0);
+        assert!(warnings.lock().unwrap().is_empty(), "nothing lost yet");
+        operation.log_entry_dropped();
+        operation.emit_failed();
+        operation.events_coalesced(3);
+
+        let expected = DiagnosticsSnapshot { events_emitted: 1, events_coalesced: 3, log_entries_dropped: 1, emit_failures: 1 };
+        assert_eq!(operation.snapshot(), expected);
+        assert_eq!(totals.snapshot(), expected);
+        let warnings = warnings.lock().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].log_entries_dropped, 1);
+    }
+}
@@ -0,0 +1,411 @@
+//! Bytes BitBurn has written to each volume, kept in daily buckets in the app data directory.
+//! Multi-pass wipes spend SSD endurance without making the erase more thorough, so a free-space
+//! estimate that would push the last 30 days past `wear_warning_bytes` carries a warning.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::capabilities::{self, DriveCapability, DriveMedia};
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::messages::{self, Message};
+use crate::report::{FileWipeOutcome, WipeReport};
+use crate::settings::{write_file_atomic, SettingsStore};
+
+const WEAR_FILE_NAME: &str = "drive_wear.json";
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Daily buckets older than this are folded away; the lifetime total keeps counting them.
+const KEPT_DAYS: u64 = 365;
+
+/// Writes to an SSD over 30 days, including a planned wipe, above which the estimate warns.
+pub const DEFAULT_WEAR_WARNING_BYTES: u64 = 500 * 1024 * 1024 * 1024;
+
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+fn day_of(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs() / SECONDS_PER_DAY).unwrap_or(0)
+}
+
+/// Everything recorded for one volume.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolumeWrites {
+    /// Device the volume is on, as the drive list names it.
+    pub disk: Option<String>,
+    pub media: Option<DriveMedia>,
+    pub lifetime_bytes: u64,
+    /// Bytes written per day, keyed by days since the Unix epoch.
+    pub daily_bytes: BTreeMap<u64, u64>,
+}
+
+/// The persisted record, keyed by volume mount point.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WearLedger {
+    pub volumes: BTreeMap<String, VolumeWrites>,
+}
+
+/// Bytes written to one volume over the reported time windows.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DriveWear {
+    pub volume: String,
+    pub disk: Option<String>,
+    pub media: Option<DriveMedia>,
+    pub last_7_days: u64,
+    pub last_30_days: u64,
+    pub last_365_days: u64,
+    pub lifetime_bytes: u64,
+}
+
+/// A planned wipe would take an SSD past the 30-day warning level.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WearWarning {
+    pub volume: String,
+    pub written_last_30_days: u64,
+    pub planned_bytes: u64,
+    pub threshold_bytes: u64,
+    #[serde(flatten)]
+    pub message: Message,
+}
+
+impl WearLedger {
+    pub fn record(&mut self, drive: &DriveCapability, bytes: u64, now: SystemTime) {
+        if bytes == 0 {
+            return;
+        }
+        let today = day_of(now);
+        let writes = self.volumes.entry(drive.mount_point.clone()).or_default();
+        writes.disk = Some(drive.name.clone()).filter(|name| !name.is_empty());
+        writes.media = Some(drive.media);
+        writes.lifetime_bytes = writes.lifetime_bytes.saturating_add(bytes);
+        let bucket = writes.daily_bytes.entry(today).or_default();
+        *bucket = bucket.saturating_add(bytes);
+        writes.daily_bytes.retain(|day, _| day + KEPT_DAYS > today);
+    }
+
+    /// Bytes written to `volume` today and on the `days - 1` days before.
+    pub fn written_within(&self, volume: &str, days: u64, now: SystemTime) -> u64 {
+        let today = day_of(now);
+        self.volumes.get(volume).map_or(0, |writes| {
+            writes.daily_bytes.range(today.saturating_sub(days - 1)..=today).map(|(_, bytes)| bytes).sum()
+        })
+    }
+
+    pub fn contribution(&self, now: SystemTime) -> Vec<DriveWear> {
+        self.volumes
+            .iter()
+            .map(|(volume, writes)| DriveWear {
+                volume: volume.clone(),
+                disk: writes.disk.clone(),
+                media: writes.media,
+                last_7_days: self.written_within(volume, 7, now),
+                last_30_days: self.written_within(volume, 30, now),
+                last_365_days: self.written_within(volume, 365, now),
+                lifetime_bytes: writes.lifetime_bytes,
+            })
+            .collect()
+    }
+
+    /// Warn when writing `planned_bytes` to `drive` would take it past `threshold_bytes` over
+    /// 30 days. Only SSDs are checked; overwriting wears nothing else measurably.
+    pub fn wear_warning(
+        &self,
+        drive: &DriveCapability,
+        planned_bytes: u64,
+        threshold_bytes: u64,
+        now: SystemTime,
+    ) -> Option<WearWarning> {
+        if drive.media != DriveMedia::Ssd {
+            return None;
+        }
+        let written = self.written_within(&drive.mount_point, 30, now);
+        let total = written.saturating_add(planned_bytes);
+        if total <= threshold_bytes {
+            return None;
+        }
+        Some(WearWarning {
+            volume: drive.mount_point.clone(),
+            written_last_30_days: written,
+            planned_bytes,
+            threshold_bytes,
+            message: messages::WEAR_WARNING.render(json!({
+                "volume": drive.mount_point,
+                "total_gb": format!("{:.1}", total as f64 / BYTES_PER_GB),
+                "threshold_gb": format!("{:.1}", threshold_bytes as f64 / BYTES_PER_GB),
+            })),
+        })
+    }
+}
+
+/// Bytes a file's wipe wrote: every pass in full once they all completed, otherwise at least the
+/// part of the last pass that was written.
+fn bytes_written(outcome: &FileWipeOutcome) -> u64 {
+    let passes = &outcome.passes;
+    if passes.total_passes > 0 && passes.passes_completed == passes.total_passes {
+        passes.bytes_of_last_pass.saturating_mul(u64::from(passes.total_passes))
+    } else {
+        passes.bytes_of_last_pass
+    }
+}
+
+/// The ledger shared through managed state, written back after every change.
+pub struct WearStore {
+    path: Option<PathBuf>,
+    ledger: Mutex<WearLedger>,
+}
+
+impl WearStore {
+    /// Load the ledger from `path`, starting empty when the file is missing or unreadable.
+    pub fn load(path: PathBuf) -> Self {
+        let ledger = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                log_event("drive_wear_load_error", json!({"path": path.to_string_lossy(), "message": e.to_string()}));
+                WearLedger::default()
+            }),
+            Err(_) => WearLedger::default(),
+        };
+        WearStore { path: Some(path), ledger: Mutex::new(ledger) }
+    }
+
+    /// Store that never touches disk; used when the data directory cannot be resolved.
+    pub fn in_memory() -> Self {
+        WearStore { path: None, ledger: Mutex::new(WearLedger::default()) }
+    }
+
+    pub fn ledger(&self) -> WearLedger {
+        self.lock().clone()
+    }
+
+    /// Add writes to the volumes they went to and persist the ledger.
+    pub fn record<'a>(&self, writes: impl IntoIterator<Item = (&'a DriveCapability, u64)>, now: SystemTime) {
+        let mut ledger = self.lock();
+        for (drive, bytes) in writes {
+            ledger.record(drive, bytes, now);
+        }
+        if let Some(path) = &self.path {
+            let written = serde_json::to_vec_pretty(&*ledger)
+                .map_err(|e| BitBurnError::internal(e.to_string()))
+                .and_then(|contents| write_file_atomic(path, &contents));
+            if let Err(e) = written {
+                log_event("drive_wear_save_error", json!({"message": e.to_string()}));
+            }
+        }
+    }
+
+    /// Record what a finished file batch or free-space wipe wrote, resolving each path's volume
+    /// against `drives`. Paths on no known volume are not counted.
+    pub fn record_report(
+        &self,
+        report: &WipeReport,
+        free_space_path: Option<&str>,
+        drives: &[DriveCapability],
+        now: SystemTime,
+    ) {
+        let mut per_volume: BTreeMap<&str, (&DriveCapability, u64)> = BTreeMap::new();
+        let mut add = |path: &str, bytes: u64| {
+            if let Some(drive) = capabilities::drive_holding(drives, path) {
+                let entry = per_volume.entry(drive.mount_point.as_str()).or_insert((drive, 0));
+                entry.1 = entry.1.saturating_add(bytes);
+            }
+        };
+        for outcome in report.files.iter().flat_map(FileWipeOutcome::with_companions) {
+            add(&outcome.path, bytes_written(outcome));
+        }
+        if let (Some(path), Some(coverage)) = (free_space_path, &report.free_space) {
+            add(path, coverage.total_bytes_written);
+        }
+        if !per_volume.is_empty() {
+            self.record(per_volume.into_values(), now);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, WearLedger> {
+        self.ledger.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Build the managed wear store rooted in the app data directory.
+pub fn init_wear_store<R: Runtime>(app: &AppHandle<R>) -> WearStore {
+    match app.path().app_data_dir() {
+        Ok(dir) => WearStore::load(dir.join(WEAR_FILE_NAME)),
+        Err(_) => {
+            log_event("drive_wear_path_unavailable", json!({"action": "bytes written are not persisted"}));
+            WearStore::in_memory()
+        }
+    }
+}
+
+/// Record what `report` wrote, if the app tracks wear.
+pub fn record_finished<R: Runtime>(app: &AppHandle<R>, report: &WipeReport, free_space_path: Option<&str>) {
+    if let Some(store) = app.try_state::<WearStore>() {
+        let drives = capabilities::list_drives().unwrap_or_default();
+        store.record_report(report, free_space_path, &drives, SystemTime::now());
+    }
+}
+
+/// The warning level configured in settings.
+pub fn threshold_bytes<R: Runtime>(app: &AppHandle<R>) -> u64 {
+    app.try_state::<SettingsStore>()
+        .and_then(|settings| settings.get().wear_warning_bytes)
+        .unwrap_or(DEFAULT_WEAR_WARNING_BYTES)
+}
+
+/// Bytes BitBurn wrote to each volume over the last 7, 30 and 365 days and overall.
+#[tauri::command]
+pub async fn get_drive_wear_contribution(store: State<'_, WearStore>) -> Result<Vec<DriveWear>, BitBurnError> {
+    Ok(store.ledger().contribution(SystemTime::now()))
+}
+
+/// Set the 30-day write volume above which SSD free-space estimates warn; `None` restores the default.
+#[tauri::command]
+pub async fn set_wear_warning_threshold(
+    settings: State<'_, SettingsStore>,
+    threshold_bytes: Option<u64>,
+) -> Result<u64, BitBurnError> {
+    let updated = settings.update(|s| s.wear_warning_bytes = threshold_bytes)?;
+    let threshold = updated.wear_warning_bytes.unwrap_or(DEFAULT_WEAR_WARNING_BYTES);
+    log_event("wear_warning_threshold_configured", json!({"threshold_bytes": threshold}));
+    Ok(threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitburn_core::FileWipeProgressRecord;
+    use std::time::Duration;
+
+    use crate::report::FileWipeStatus;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn drive(mount_point: &str, media: DriveMedia) -> DriveCapability {
+        DriveCapability {
+            mount_point: mount_point.to_string(),
+            name: "nvme0n1".to_string(),
+            file_system: "ext4".to_string(),
+            media,
+            total_bytes: 0,
+            available_bytes: 0,
+            last_free_space_wipe: None,
+        }
+    }
+
+    fn days_after(start: SystemTime, days: u64) -> SystemTime {
+        start + Duration::from_secs(days * SECONDS_PER_DAY)
+    }
+
+    #[test]
+    fn writes_accumulate_into_daily_buckets() {
+        let ssd = drive("/", DriveMedia::Ssd);
+        let start = UNIX_EPOCH + Duration::from_secs(20_000 * SECONDS_PER_DAY + 3600);
+        let mut ledger = WearLedger::default();
+
+        ledger.record(&ssd, 10 * GB, start);
+        ledger.record(&ssd, 5 * GB, start + Duration::from_secs(60));
+        ledger.record(&ssd, 0, start);
+
+        let writes = &ledger.volumes["/"];
+        assert_eq!(writes.lifetime_bytes, 15 * GB);
+        assert_eq!(writes.daily_bytes.len(), 1);
+        assert_eq!(writes.disk.as_deref(), Some("nvme0n1"));
+        assert_eq!(writes.media, Some(DriveMedia::Ssd));
+    }
+
+    #[test]
+    fn contribution_is_bucketed_by_the_clock() {
+        let ssd = drive("C:\\", DriveMedia::Ssd);
+        let hdd = drive("D:\\", DriveMedia::Hdd);
+        let start = UNIX_EPOCH + Duration::from_secs(20_000 * SECONDS_PER_DAY);
+        let mut ledger = WearLedger::default();
+        for (days, bytes) in [(0, GB), (300, 2 * GB), (340, 4 * GB), (362, 8 * GB), (366, 16 * GB)] {
+            ledger.record(&ssd, bytes, days_after(start, days));
+        }
+        ledger.record(&hdd, 7 * GB, days_after(start, 366));
+
+        let now = days_after(start, 366) + Duration::from_secs(12 * 3600);
+        let wear = ledger.contribution(now);
+        assert_eq!(
+            wear[0],
+            DriveWear {
+                volume: "C:\\".to_string(),
+                disk: Some("nvme0n1".to_string()),
+                media: Some(DriveMedia::Ssd),
+                last_7_days: 24 * GB,
+                last_30_days: 28 * GB,
+                last_365_days: 30 * GB,
+                lifetime_bytes: 31 * GB,
+            }
+        );
+        assert_eq!((wear[1].volume.as_str(), wear[1].last_7_days), ("D:\\", 7 * GB));
+        // Day 0 fell out of the kept buckets but still counts towards the lifetime total.
+        assert!(!ledger.volumes["C:\\"].daily_bytes.contains_key(&day_of(start)));
+
+        let a_month_later = days_after(now, 30);
+        assert_eq!(ledger.written_within("C:\\", 30, a_month_later), 0);
+        assert_eq!(ledger.written_within("C:\\", 365, a_month_later), 30 * GB);
+        assert_eq!(ledger.written_within("E:\\", 30, now), 0);
+    }
+
+    #[test]
+    fn planned_wipes_past_the_threshold_warn_on_ssds_only() {
+        let ssd = drive("/", DriveMedia::Ssd);
+        let hdd = drive("/data", DriveMedia::Hdd);
+        let now = UNIX_EPOCH + Duration::from_secs(20_000 * SECONDS_PER_DAY);
+        let mut ledger = WearLedger::default();
+        ledger.record(&ssd, 300 * GB, now - Duration::from_secs(20 * SECONDS_PER_DAY));
+        ledger.record(&ssd, 900 * GB, now - Duration::from_secs(45 * SECONDS_PER_DAY));
+        ledger.record(&hdd, 900 * GB, now);
+
+        assert_eq!(ledger.wear_warning(&ssd, 200 * GB, 500 * GB, now), None, "exactly at the threshold");
+        let warning = ledger.wear_warning(&ssd, 200 * GB + 1, 500 * GB, now).expect("one byte over");
+        assert_eq!(warning.written_last_30_days, 300 * GB);
+        assert_eq!(warning.message.code, "wear_warning");
+        assert!(warning.message.text.contains("500.0 GB"));
+        assert_eq!(ledger.wear_warning(&hdd, 900 * GB, 500 * GB, now), None);
+    }
+
+    #[test]
+    fn finished_reports_are_recorded_per_volume_and_persisted() {
+        let path = create_test_dir().unwrap().join(WEAR_FILE_NAME);
+        let drives = [drive("/", DriveMedia::Ssd), drive("/mnt/usb", DriveMedia::Removable)];
+        let file = |path: &str, passes_completed, bytes_of_last_pass| FileWipeOutcome {
+            path: path.to_string(),
+            status: FileWipeStatus::Success,
+            algorithm: None,
+            flash_media: false,
+            recalled_from_remote: false,
+            cow_filesystem: None,
+            nocow_applied: None,
+            ownership_taken: false,
+            original_owner: None,
+            link: None,
+            exclusive_access: true,
+            duration_ms: 0,
+            bytes_per_second: None,
+            passes: FileWipeProgressRecord { passes_completed, bytes_of_last_pass, ..FileWipeProgressRecord::new(3) },
+            companions: Vec::new(),
+        };
+        let report = WipeReport {
+            files: vec![file("/home/a", 3, 100), file("/mnt/usb/b", 1, 40), file("/home/c", 3, 10)],
+            ..Default::default()
+        };
+        let now = SystemTime::now();
+
+        let store = WearStore::load(path.clone());
+        store.record_report(&report, None, &drives, now);
+
+        let reloaded = WearStore::load(path.clone()).ledger();
+        assert_eq!(reloaded.volumes["/"].lifetime_bytes, 330);
+        assert_eq!(reloaded.volumes["/mnt/usb"].lifetime_bytes, 40, "an unfinished wipe counts its last pass");
+        cleanup_test_dir(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,108 @@
+//! Ed25519 (RFC 8032) signing and verification over `ed25519-dalek`, kept behind the same
+//! three byte-array functions the signer has always called.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+pub const PUBLIC_KEY_LEN: usize = ed25519_dalek::PUBLIC_KEY_LENGTH;
+pub const SEED_LEN: usize = ed25519_dalek::SECRET_KEY_LENGTH;
+pub const SIGNATURE_LEN: usize = ed25519_dalek::SIGNATURE_LENGTH;
+
+/// Public key for a 32-byte secret seed. The expanded key is zeroed when dropped.
+pub fn public_key(seed: &[u8; SEED_LEN]) -> [u8; PUBLIC_KEY_LEN] {
+    SigningKey::from_bytes(seed).verifying_key().to_bytes()
+}
+
+pub fn sign(seed: &[u8; SEED_LEN], message: &[u8]) -> [u8; SIGNATURE_LEN] {
+    SigningKey::from_bytes(seed).sign(message).to_bytes()
+}
+
+/// Strict verification: non-canonical scalars and small-order keys are refused.
+pub fn verify(public_key: &[u8; PUBLIC_KEY_LEN], message: &[u8], signature: &[u8; SIGNATURE_LEN]) -> bool {
+    let Ok(key) = VerifyingKey::from_bytes(public_key) else {
+        return false;
+    };
+    key.verify_strict(message, &Signature::from_bytes(signature)).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Order of the base point, little endian.
+    const L: [u8; 32] = [
+        0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10,
+    ];
+
+    fn decode<const N: usize>(hex_str: &str) -> [u8; N] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    /// RFC 8032 section 7.1, tests 1-3.
+    const VECTORS: [(&str, &str, &str, &str); 3] = [
+        (
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        ),
+        (
+            "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "72",
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+        ),
+        (
+            "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+            "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+            "af82",
+            "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+        ),
+    ];
+
+    #[test]
+    fn matches_rfc8032_vectors() {
+        for (seed, public, message, signature) in VECTORS {
+            let seed: [u8; 32] = decode(seed);
+            let public: [u8; 32] = decode(public);
+            let message = hex::decode(message).unwrap();
+            let expected: [u8; 64] = decode(signature);
+
+            assert_eq!(public_key(&seed), public);
+            assert_eq!(sign(&seed, &message), expected);
+            assert!(verify(&public, &message, &expected));
+        }
+    }
+
+    #[test]
+    fn rejects_modified_message_signature_and_key() {
+        let (seed, public, _, _) = VECTORS[1];
+        let seed: [u8; 32] = decode(seed);
+        let public: [u8; 32] = decode(public);
+        let signature = sign(&seed, b"report body");
+        assert!(verify(&public, b"report body", &signature));
+        assert!(!verify(&public, b"report bodY", &signature));
+
+        let mut tampered = signature;
+        tampered[10] ^= 1;
+        assert!(!verify(&public, b"report body", &tampered));
+
+        let other = public_key(&[7u8; 32]);
+        assert!(!verify(&other, b"report body", &signature));
+    }
+
+    #[test]
+    fn rejects_non_canonical_scalar() {
+        let seed = [3u8; 32];
+        let public = public_key(&seed);
+        let mut signature = sign(&seed, b"x");
+        // s + L verifies under the group law but must be refused.
+        let mut carry = 0u16;
+        for i in 0..32 {
+            let sum = u16::from(signature[32 + i]) + L[i] as u16 + carry;
+            signature[32 + i] = sum as u8;
+            carry = sum >> 8;
+        }
+        assert!(!verify(&public, b"x", &signature));
+    }
+}
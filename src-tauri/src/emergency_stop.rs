@@ -0,0 +1,234 @@
+//! The panic button: a global shortcut that cancels every running operation and brings the
+//! window forward, for when a wipe is heading somewhere it should not.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::diagnostics;
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::operations::{OperationRegistry, OperationSnapshot};
+use crate::platform::hotkey::Hotkey;
+use crate::report::{InterruptedFile, PartialWipe};
+use crate::settings::SettingsStore;
+
+pub const DEFAULT_ACCELERATOR: &str = "Ctrl+Alt+Shift+B";
+
+/// How long to wait for cancelled operations to stop before reporting what they left behind.
+const STOP_WAIT: Duration = Duration::from_secs(10);
+const STOP_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmergencyShortcutSettings {
+    pub enabled: bool,
+    pub accelerator: String,
+}
+
+impl Default for EmergencyShortcutSettings {
+    fn default() -> Self {
+        EmergencyShortcutSettings {
+            enabled: false,
+            accelerator: DEFAULT_ACCELERATOR.to_string(),
+        }
+    }
+}
+
+/// An operation the panic button cancelled and what it left behind, sent in `emergency_stop`.
+#[derive(Debug, Clone, Serialize)]
+pub struct StoppedOperation {
+    #[serde(flatten)]
+    pub snapshot: OperationSnapshot,
+    /// False when the operation was still winding down after `STOP_WAIT`; its files may still change.
+    pub stopped: bool,
+    pub partially_wiped: Vec<PartialWipe>,
+    pub interrupted: Vec<InterruptedFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EmergencyStop {
+    pub operations: Vec<StoppedOperation>,
+}
+
+/// Managed state: the shortcut registered with the global-shortcut plugin and why the configured
+/// one is not live.
+#[derive(Default)]
+pub struct EmergencyShortcut {
+    registered: Mutex<Option<Shortcut>>,
+    problem: Mutex<Option<BitBurnError>>,
+}
+
+impl EmergencyShortcut {
+    /// Why the configured shortcut is not registered, for settings validation.
+    pub fn problem(&self) -> Option<BitBurnError> {
+        self.problem.lock().unwrap_or_else(|p| p.into_inner()).clone()
+    }
+
+    /// Release any current shortcut and register the configured one.
+    fn apply(&self, app: &AppHandle, config: &EmergencyShortcutSettings) {
+        let mut registered = self.registered.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(shortcut) = registered.take() {
+            if let Err(e) = app.global_shortcut().unregister(shortcut) {
+                log_event("emergency_shortcut_release_failed", json!({"message": e.to_string()}));
+            }
+        }
+        let result = if config.enabled {
+            Hotkey::parse(&config.accelerator)
+                .and_then(|hotkey| register(app, &config.accelerator, hotkey.shortcut()))
+                .map(Some)
+        } else {
+            Ok(None)
+        };
+        let problem = match result {
+            Ok(live) => {
+                *registered = live;
+                None
+            }
+            Err(e) => {
+                log_event(
+                    "emergency_shortcut_unavailable",
+                    json!({"accelerator": config.accelerator, "code": e.code(), "message": e.to_string()}),
+                );
+                Some(e)
+            }
+        };
+        *self.problem.lock().unwrap_or_else(|p| p.into_inner()) = problem;
+    }
+}
+
+/// Have the global-shortcut plugin trigger the panic button when `shortcut` is pressed.
+fn register(app: &AppHandle, accelerator: &str, shortcut: Shortcut) -> Result<Shortcut, BitBurnError> {
+    app.global_shortcut()
+        .on_shortcut(shortcut, |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                trigger(app);
+            }
+        })
+        .map(|()| shortcut)
+        .map_err(|e| match e {
+            // The plugin passes on the message of global-hotkey's `AlreadyRegistered`.
+            tauri_plugin_global_shortcut::Error::GlobalHotkey(message) if message.starts_with("HotKey already registered") => {
+                BitBurnError::ShortcutConflict { accelerator: accelerator.to_string() }
+            }
+            e => BitBurnError::internal(format!("Failed to register shortcut {}: {}", accelerator, e)),
+        })
+}
+
+/// Register the shortcut saved in settings, at startup and after a configuration import.
+pub fn register_from_settings(app: &AppHandle) {
+    let config = app.state::<SettingsStore>().get().emergency_shortcut;
+    app.state::<EmergencyShortcut>().apply(app, &config);
+}
+
+/// Cancel everything, raise the window and, once the operations have wound down, emit `emergency_stop`.
+pub fn trigger(app: &AppHandle) {
+    let registry = app.state::<OperationRegistry>().inner().clone();
+    let cancelled = registry.cancel_all();
+    log_event(
+        "emergency_stop",
+        json!({"operation_ids": cancelled.iter().map(|s| s.operation_id.clone()).collect::<Vec<_>>()}),
+    );
+
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let operations = wait_for_stop(&registry, cancelled, STOP_WAIT);
+        diagnostics::emit(&app, "emergency_stop", EmergencyStop { operations });
+    });
+}
+
+/// Wait up to `limit` for `cancelled` operations to unregister, then collect what their reports
+/// say was left partly wiped.
+pub fn wait_for_stop(registry: &OperationRegistry, cancelled: Vec<OperationSnapshot>, limit: Duration) -> Vec<StoppedOperation> {
+    let deadline = Instant::now() + limit;
+    while cancelled.iter().any(|s| registry.get(&s.operation_id).is_some()) && Instant::now() < deadline {
+        std::thread::sleep(STOP_POLL);
+    }
+    cancelled
+        .into_iter()
+        .map(|snapshot| {
+            let report = registry.report(&snapshot.operation_id).unwrap_or_default();
+            StoppedOperation {
+                stopped: registry.get(&snapshot.operation_id).is_none(),
+                snapshot,
+                partially_wiped: report.partially_wiped,
+                interrupted: report.interrupted,
+            }
+        })
+        .collect()
+}
+
+/// Enable, disable or rebind the panic-button shortcut. The setting is saved even when the
+/// shortcut cannot be registered; `get_settings` reports why.
+#[tauri::command]
+pub async fn configure_emergency_shortcut(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    shortcut: State<'_, EmergencyShortcut>,
+    enabled: bool,
+    accelerator: Option<String>,
+) -> Result<EmergencyShortcutSettings, BitBurnError> {
+    let accelerator = accelerator.unwrap_or_else(|| settings.get().emergency_shortcut.accelerator);
+    Hotkey::parse(&accelerator)?;
+    let updated = settings.update(|s| s.emergency_shortcut = EmergencyShortcutSettings { enabled, accelerator })?;
+    shortcut.apply(&app, &updated.emergency_shortcut);
+    log_event("emergency_shortcut_configured", json!({"enabled": enabled}));
+    Ok(updated.emergency_shortcut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::OperationKind;
+    use crate::report::WipeReport;
+
+    #[test]
+    fn stopped_operations_carry_their_partial_wipes() {
+        let registry = OperationRegistry::default();
+        let done = registry.register(OperationKind::WipeFiles, "C:/data").operation_id;
+        let stuck = registry.register(OperationKind::FreeSpaceWipe, "D:\\").operation_id;
+        let cancelled = registry.cancel_all();
+        assert_eq!(cancelled.len(), 2);
+
+        // The worker notices its flag, reports the file it was in the middle of and unregisters.
+        let worker = registry.clone();
+        let worker_id = done.clone();
+        let handle = std::thread::spawn(move || {
+            assert!(worker.cancel_flag(&worker_id).unwrap().load(std::sync::atomic::Ordering::SeqCst));
+            worker.attach_report(
+                &worker_id,
+                WipeReport {
+                    partially_wiped: vec![PartialWipe { path: "C:/data/big.iso".into(), bytes_overwritten: 4096, last_pass: 2 }],
+                    ..Default::default()
+                },
+            );
+            worker.unregister(&worker_id);
+        });
+        handle.join().unwrap();
+
+        let stopped = wait_for_stop(&registry, cancelled, Duration::from_millis(250));
+        let by_id = |id: &str| stopped.iter().find(|s| s.snapshot.operation_id == id).unwrap();
+        assert!(by_id(&done).stopped);
+        assert_eq!(by_id(&done).partially_wiped[0].path, "C:/data/big.iso");
+        assert!(!by_id(&stuck).stopped, "operations still winding down are reported as such");
+        assert!(by_id(&stuck).partially_wiped.is_empty());
+    }
+
+    #[test]
+    fn the_shortcut_is_off_until_enabled() {
+        let defaults = EmergencyShortcutSettings::default();
+        assert!(!defaults.enabled);
+        assert!(Hotkey::parse(&defaults.accelerator).is_ok());
+        let restored: EmergencyShortcutSettings = serde_json::from_str(r#"{"enabled": true}"#).unwrap();
+        assert_eq!(restored.accelerator, DEFAULT_ACCELERATOR);
+    }
+}
@@ -0,0 +1,615 @@
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+
+use crate::platform::autostart::AutostartError;
+use crate::platform::context_menu::ContextMenuError;
+use crate::platform::send_to::SendToError;
+use crate::{DriveValidationError, WipeError};
+
+/// Structured error shared between the backend and the frontend.
+/// Serialized with a stable `code` tag so the UI can branch on and localize errors;
+/// `to_string()` keeps a human-readable English message for logging.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Error)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum BitBurnError {
+    #[error("Path not found: {path}")]
+    PathNotFound { path: String },
+    #[error("Invalid number of passes: {passes}")]
+    InvalidPasses { passes: u32 },
+    #[error("Cannot wipe symbolic links: {path}")]
+    SymlinkNotSupported { path: String },
+    #[error("Access denied: {path}. The file might be in use or require administrator privileges.")]
+    AccessDenied { path: String },
+    #[error("IO error: {message}")]
+    Io {
+        kind: String,
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        offset: Option<u64>,
+    },
+    #[error("Selected path is not a drive root: {path}")]
+    NotDriveRoot { path: String },
+    #[error("Could not find disk information for {path}")]
+    DiskInfoUnavailable { path: String },
+    #[error("{feature} is not supported on this platform")]
+    UnsupportedPlatform { feature: String },
+    #[error("Missing executable path")]
+    MissingExecutablePath,
+    #[error("Registry error: {message}")]
+    Registry { message: String },
+    #[error("Path is protected and cannot be wiped: {path}")]
+    ProtectedPath { path: String },
+    #[error("Verification failed for {path} at offset {offset}")]
+    VerificationFailed {
+        path: String,
+        offset: u64,
+        /// Every chunk that read back differently, when a pass was verified chunk by chunk.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        offsets: Vec<u64>,
+    },
+    #[error("Operation cancelled by user")]
+    Cancelled,
+    #[error("Administrator privileges are required: {reason}")]
+    ElevationRequired { reason: String },
+    #[error("File is in use by another process: {path}")]
+    FileInUse { path: String, pids: Vec<u32> },
+    #[error("Unsupported filesystem {filesystem} for {path}")]
+    UnsupportedFilesystem { path: String, filesystem: String },
+    #[error("Operation not found: {operation_id}")]
+    OperationNotFound { operation_id: String },
+    #[error("Network paths are not supported: {path}")]
+    NetworkPathNotSupported { path: String },
+    #[error("Unsupported path encoding: {path}")]
+    InvalidPathEncoding { path: String },
+    #[error("Invalid manifest {path}: {message}")]
+    InvalidManifest { path: String, message: String },
+    #[error("Invalid configuration file: {message}")]
+    InvalidConfiguration { message: String },
+    #[error("Unsupported configuration version {found} (this build supports version {supported})")]
+    UnsupportedConfigVersion { found: u64, supported: u32 },
+    #[error("Unknown cleanup preset: {preset_id}")]
+    UnknownPreset { preset_id: String },
+    #[error("Invalid retention rule for {folder}: {message}")]
+    InvalidRetentionRule { folder: String, message: String },
+    #[error("Report signing error: {message}")]
+    Signing { message: String },
+    #[error("Shortcut error: {message}")]
+    Shortcut { message: String },
+    #[error("Wipe of {path} timed out after {seconds} seconds without write progress")]
+    Timeout { path: String, seconds: u64 },
+    #[error("Shadow copy error: {message}")]
+    ShadowCopy { message: String },
+    #[error("Directory is actively being written to: {path} (new files kept appearing after {rescans} re-scans)")]
+    DirectoryActive { path: String, rescans: u32 },
+    #[error("Refusing to wipe BitBurn's own program or data files: {path}")]
+    SelfProtection { path: String },
+    #[error("Free space on {path} changed from {estimated_bytes} to {available_bytes} bytes since the estimate was made")]
+    StaleEstimate { path: String, estimated_bytes: u64, available_bytes: u64 },
+    #[error("Free-space estimate not found or expired: {token}")]
+    EstimateNotFound { token: String },
+    #[error("{} files are locked by other programs", paths.len())]
+    FilesLocked { paths: Vec<String> },
+    #[error("Not enough space for temporary files in {path}: {required} bytes needed, {available} available")]
+    InsufficientScratchSpace { path: String, required: u64, available: u64 },
+    #[error("{path} is the root of a volume; use the free-space or whole-drive wipe instead of wiping it as a folder")]
+    VolumeRoot { path: String },
+    #[error("Global shortcut {accelerator} is already registered by another program")]
+    ShortcutConflict { accelerator: String },
+    #[error("{path} is not accessible from this BitBurn instance, which runs with different privileges from the one that sent it; mapped drives and user folders differ between them")]
+    ElevationContextMismatch { path: String },
+    #[error("{volume} is read-only or write-protected; {} selected items on it were left untouched", paths.len())]
+    VolumeReadOnly { volume: String, paths: Vec<String> },
+    #[error("Range of {length} bytes at offset {offset} is outside {path}, which is {size} bytes long")]
+    InvalidRange { path: String, offset: u64, length: u64, size: u64 },
+    #[error("A range of {path} overlapping {length} bytes at offset {offset} is already being wiped")]
+    RangeInUse { path: String, offset: u64, length: u64 },
+    #[error("Context-menu selection not found or already used: {token}")]
+    ContextPayloadNotFound { token: String },
+    #[error("{} selected items are on removable flash media, where overwriting files does not reliably destroy their data", paths.len())]
+    FlashMediaRefused { paths: Vec<String> },
+    #[error("Skipped by the user after {passes_completed} completed passes and deleted without finishing its wipe: {path}")]
+    SkippedByUser { path: String, passes_completed: u32 },
+    #[error("Blocked by the machine policy ({rule}): {detail}")]
+    BlockedByPolicy { rule: String, detail: String },
+    #[error("{volume} was disconnected during the wipe; {} items on it were not wiped", paths.len())]
+    VolumeDisconnected { volume: String, paths: Vec<String> },
+    #[error("Invalid decoy data: {reason}")]
+    InvalidDecoy { reason: String },
+    #[error("The wipe was not confirmed: {reason}")]
+    ConfirmationRequired { reason: String },
+    #[error("{path} is stored in a remote or archive tier; wiping it would download its data and destroy only the local copy")]
+    RemoteTieredFile { path: String },
+    #[error("Invalid command line: {message}")]
+    InvalidArguments { message: String },
+    #[error("Undefined environment variable {variable} in {path}")]
+    UndefinedVariable { path: String, variable: String },
+    #[error("Relative paths are not accepted here: {path}")]
+    RelativePathNotAllowed { path: String },
+    #[error("The confirmation dialog could not be shown: {reason}")]
+    DialogUnavailable { reason: String, prompt: String, nonce: String },
+    #[error("{path} grew from {initial_size} to {size} bytes while it was wiped; another program is still writing it")]
+    InActiveUse { path: String, initial_size: u64, size: u64 },
+    #[error("{path} points at another link; only one level of links is followed")]
+    SymlinkChain { path: String },
+    #[error("Internal error: {message}")]
+    Internal { message: String },
+}
+
+impl BitBurnError {
+    /// Stable machine-readable code, identical to the serialized `code` tag.
+    pub fn code(&self) -> &'static str {
+        match self {
+            BitBurnError::PathNotFound { .. } => "path_not_found",
+            BitBurnError::InvalidPasses { .. } => "invalid_passes",
+            BitBurnError::SymlinkNotSupported { .. } => "symlink_not_supported",
+            BitBurnError::AccessDenied { .. } => "access_denied",
+            BitBurnError::Io { .. } => "io",
+            BitBurnError::NotDriveRoot { .. } => "not_drive_root",
+            BitBurnError::DiskInfoUnavailable { .. } => "disk_info_unavailable",
+            BitBurnError::UnsupportedPlatform { .. } => "unsupported_platform",
+            BitBurnError::MissingExecutablePath => "missing_executable_path",
+            BitBurnError::Registry { .. } => "registry",
+            BitBurnError::ProtectedPath { .. } => "protected_path",
+            BitBurnError::VerificationFailed { .. } => "verification_failed",
+            BitBurnError::Cancelled => "cancelled",
+            BitBurnError::ElevationRequired { .. } => "elevation_required",
+            BitBurnError::FileInUse { .. } => "file_in_use",
+            BitBurnError::UnsupportedFilesystem { .. } => "unsupported_filesystem",
+            BitBurnError::OperationNotFound { .. } => "operation_not_found",
+            BitBurnError::NetworkPathNotSupported { .. } => "network_path_not_supported",
+            BitBurnError::InvalidPathEncoding { .. } => "invalid_path_encoding",
+            BitBurnError::InvalidManifest { .. } => "invalid_manifest",
+            BitBurnError::InvalidConfiguration { .. } => "invalid_configuration",
+            BitBurnError::UnsupportedConfigVersion { .. } => "unsupported_config_version",
+            BitBurnError::UnknownPreset { .. } => "unknown_preset",
+            BitBurnError::InvalidRetentionRule { .. } => "invalid_retention_rule",
+            BitBurnError::Signing { .. } => "signing",
+            BitBurnError::Shortcut { .. } => "shortcut",
+            BitBurnError::Timeout { .. } => "timeout",
+            BitBurnError::ShadowCopy { .. } => "shadow_copy",
+            BitBurnError::DirectoryActive { .. } => "directory_active",
+            BitBurnError::SelfProtection { .. } => "self_protection",
+            BitBurnError::StaleEstimate { .. } => "stale_estimate",
+            BitBurnError::EstimateNotFound { .. } => "estimate_not_found",
+            BitBurnError::FilesLocked { .. } => "files_locked",
+            BitBurnError::InsufficientScratchSpace { .. } => "insufficient_scratch_space",
+            BitBurnError::VolumeRoot { .. } => "volume_root",
+            BitBurnError::ShortcutConflict { .. } => "shortcut_conflict",
+            BitBurnError::ElevationContextMismatch { .. } => "elevation_context_mismatch",
+            BitBurnError::VolumeReadOnly { .. } => "volume_read_only",
+            BitBurnError::InvalidRange { .. } => "invalid_range",
+            BitBurnError::RangeInUse { .. } => "range_in_use",
+            BitBurnError::ContextPayloadNotFound { .. } => "context_payload_not_found",
+            BitBurnError::FlashMediaRefused { .. } => "flash_media_refused",
+            BitBurnError::SkippedByUser { .. } => "skipped_by_user",
+            BitBurnError::BlockedByPolicy { .. } => "blocked_by_policy",
+            BitBurnError::VolumeDisconnected { .. } => "volume_disconnected",
+            BitBurnError::InvalidDecoy { .. } => "invalid_decoy",
+            BitBurnError::ConfirmationRequired { .. } => "confirmation_required",
+            BitBurnError::RemoteTieredFile { .. } => "remote_tiered_file",
+            BitBurnError::InvalidArguments { .. } => "invalid_arguments",
+            BitBurnError::UndefinedVariable { .. } => "undefined_variable",
+            BitBurnError::RelativePathNotAllowed { .. } => "relative_path_not_allowed",
+            BitBurnError::DialogUnavailable { .. } => "dialog_unavailable",
+            BitBurnError::InActiveUse { .. } => "in_active_use",
+            BitBurnError::SymlinkChain { .. } => "symlink_chain",
+            BitBurnError::Internal { .. } => "internal",
+        }
+    }
+
+    /// Map an IO error to the most specific variant, attaching the affected path when known.
+    pub fn io(err: &io::Error, path: Option<&Path>) -> Self {
+        let path_str = path.map(|p| p.to_string_lossy().to_string());
+        match (err.kind(), path_str) {
+            (io::ErrorKind::NotFound, Some(path)) => BitBurnError::PathNotFound { path },
+            (io::ErrorKind::PermissionDenied, Some(path)) => BitBurnError::AccessDenied { path },
+            (kind, path) => BitBurnError::Io {
+                kind: format!("{:?}", kind),
+                message: err.to_string(),
+                path,
+                offset: None,
+            },
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        BitBurnError::Internal { message: message.into() }
+    }
+
+    /// Convert a wipe engine error raised while processing `path`.
+    pub fn from_wipe_error(err: WipeError, path: &Path) -> Self {
+        let path_str = path.to_string_lossy().to_string();
+        match err {
+            WipeError::PathNotFound => BitBurnError::PathNotFound { path: path_str },
+            WipeError::InvalidPasses { passes } => BitBurnError::InvalidPasses { passes },
+            WipeError::SymbolicLink => BitBurnError::SymlinkNotSupported { path: path_str },
+            WipeError::LinkChain => BitBurnError::SymlinkChain { path: path_str },
+            WipeError::Timeout { seconds } => BitBurnError::Timeout { path: path_str, seconds },
+            WipeError::InvalidRange { offset, length, size } => BitBurnError::InvalidRange { path: path_str, offset, length, size },
+            WipeError::InvalidDecoy(reason) => BitBurnError::InvalidDecoy { reason },
+            WipeError::VerificationFailed { offsets, .. } => BitBurnError::VerificationFailed {
+                path: path_str,
+                offset: offsets.first().copied().unwrap_or(0),
+                offsets,
+            },
+            WipeError::InActiveUse { initial_size, size } => BitBurnError::InActiveUse { path: path_str, initial_size, size },
+            WipeError::Io(e) if e.kind() == io::ErrorKind::Interrupted => BitBurnError::Cancelled,
+            WipeError::Io(e) => BitBurnError::io(&e, Some(path)),
+        }
+    }
+
+    /// Convert a drive validation failure for `path`.
+    pub fn from_drive_validation(err: DriveValidationError, path: &Path) -> Self {
+        let path = path.to_string_lossy().to_string();
+        match err {
+            DriveValidationError::PathNotFound => BitBurnError::PathNotFound { path },
+            DriveValidationError::NotDriveRoot => BitBurnError::NotDriveRoot { path },
+            DriveValidationError::NotWritable => BitBurnError::AccessDenied { path },
+        }
+    }
+}
+
+impl From<ContextMenuError> for BitBurnError {
+    fn from(err: ContextMenuError) -> Self {
+        match err {
+            #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+            ContextMenuError::UnsupportedPlatform => BitBurnError::UnsupportedPlatform {
+                feature: "Context menu".to_string(),
+            },
+            ContextMenuError::MissingExecutablePath => BitBurnError::MissingExecutablePath,
+            #[cfg(windows)]
+            ContextMenuError::Registry(message) => BitBurnError::Registry { message },
+            #[cfg(any(target_os = "macos", target_os = "linux"))]
+            ContextMenuError::Service(err) => BitBurnError::io(&err, None),
+            #[cfg(target_os = "macos")]
+            ContextMenuError::MissingHomeDir => BitBurnError::UnsupportedPlatform {
+                feature: "Finder Quick Action (HOME is not set)".to_string(),
+            },
+            #[cfg(target_os = "linux")]
+            ContextMenuError::MissingHomeDir => BitBurnError::UnsupportedPlatform {
+                feature: "File manager integration (HOME is not set)".to_string(),
+            },
+        }
+    }
+}
+
+impl From<SendToError> for BitBurnError {
+    fn from(err: SendToError) -> Self {
+        match err {
+            #[cfg(not(windows))]
+            SendToError::UnsupportedPlatform => BitBurnError::UnsupportedPlatform {
+                feature: "Send To shortcut".to_string(),
+            },
+            #[cfg(windows)]
+            SendToError::MissingExecutablePath => BitBurnError::MissingExecutablePath,
+            #[cfg(windows)]
+            SendToError::MissingAppData => BitBurnError::UnsupportedPlatform {
+                feature: "Send To shortcut (APPDATA is not set)".to_string(),
+            },
+            #[cfg(windows)]
+            SendToError::Shortcut(message) => BitBurnError::Shortcut { message },
+        }
+    }
+}
+
+impl From<AutostartError> for BitBurnError {
+    fn from(err: AutostartError) -> Self {
+        match err {
+            #[cfg(not(windows))]
+            AutostartError::UnsupportedPlatform => BitBurnError::UnsupportedPlatform {
+                feature: "Autostart".to_string(),
+            },
+            AutostartError::MissingExecutablePath => BitBurnError::MissingExecutablePath,
+            #[cfg(windows)]
+            AutostartError::Registry(message) => BitBurnError::Registry { message },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot(err: BitBurnError, expected: serde_json::Value) {
+        let value = serde_json::to_value(&err).expect("error should serialize");
+        assert_eq!(value, expected, "serialized form changed for {:?}", err);
+        assert_eq!(value["code"], err.code(), "code() disagrees with serde tag");
+        let round_trip: BitBurnError = serde_json::from_value(value).expect("error should deserialize");
+        assert_eq!(round_trip, err);
+    }
+
+    #[test]
+    fn path_variants_serialize_with_stable_codes() {
+        snapshot(
+            BitBurnError::PathNotFound { path: "C:/a.txt".into() },
+            json!({"code": "path_not_found", "path": "C:/a.txt"}),
+        );
+        snapshot(
+            BitBurnError::SymlinkNotSupported { path: "/tmp/link".into() },
+            json!({"code": "symlink_not_supported", "path": "/tmp/link"}),
+        );
+        snapshot(
+            BitBurnError::AccessDenied { path: "C:/locked.pst".into() },
+            json!({"code": "access_denied", "path": "C:/locked.pst"}),
+        );
+        snapshot(
+            BitBurnError::NotDriveRoot { path: "C:/Users".into() },
+            json!({"code": "not_drive_root", "path": "C:/Users"}),
+        );
+        snapshot(
+            BitBurnError::DiskInfoUnavailable { path: "Z:/".into() },
+            json!({"code": "disk_info_unavailable", "path": "Z:/"}),
+        );
+        snapshot(
+            BitBurnError::ProtectedPath { path: "C:/Windows".into() },
+            json!({"code": "protected_path", "path": "C:/Windows"}),
+        );
+        snapshot(
+            BitBurnError::NetworkPathNotSupported { path: "\\\\server\\share".into() },
+            json!({"code": "network_path_not_supported", "path": "\\\\server\\share"}),
+        );
+        snapshot(
+            BitBurnError::InvalidPathEncoding { path: "C:/bad".into() },
+            json!({"code": "invalid_path_encoding", "path": "C:/bad"}),
+        );
+        snapshot(
+            BitBurnError::InvalidManifest { path: "flagged.csv".into(), message: "missing path column".into() },
+            json!({"code": "invalid_manifest", "path": "flagged.csv", "message": "missing path column"}),
+        );
+        snapshot(
+            BitBurnError::DirectoryActive { path: "C:/spool".into(), rescans: 3 },
+            json!({"code": "directory_active", "path": "C:/spool", "rescans": 3}),
+        );
+        snapshot(
+            BitBurnError::SelfProtection { path: "C:/Program Files/BitBurn".into() },
+            json!({"code": "self_protection", "path": "C:/Program Files/BitBurn"}),
+        );
+        snapshot(
+            BitBurnError::StaleEstimate { path: "D:\\".into(), estimated_bytes: 100, available_bytes: 50 },
+            json!({"code": "stale_estimate", "path": "D:\\", "estimated_bytes": 100, "available_bytes": 50}),
+        );
+        snapshot(
+            BitBurnError::EstimateNotFound { token: "est-1".into() },
+            json!({"code": "estimate_not_found", "token": "est-1"}),
+        );
+        snapshot(
+            BitBurnError::ContextPayloadNotFound { token: "ctx-1".into() },
+            json!({"code": "context_payload_not_found", "token": "ctx-1"}),
+        );
+        snapshot(
+            BitBurnError::FlashMediaRefused { paths: vec!["E:/DCIM/IMG_0001.jpg".into()] },
+            json!({"code": "flash_media_refused", "paths": ["E:/DCIM/IMG_0001.jpg"]}),
+        );
+        snapshot(
+            BitBurnError::SkippedByUser { path: "D:/images/win.iso".into(), passes_completed: 1 },
+            json!({"code": "skipped_by_user", "path": "D:/images/win.iso", "passes_completed": 1}),
+        );
+        snapshot(
+            BitBurnError::BlockedByPolicy { rule: "disabled_commands".into(), detail: "wipe_files is disabled".into() },
+            json!({"code": "blocked_by_policy", "rule": "disabled_commands", "detail": "wipe_files is disabled"}),
+        );
+        snapshot(
+            BitBurnError::VolumeDisconnected { volume: "E:\\".into(), paths: vec!["E:\\backup\\a.zip".into()] },
+            json!({"code": "volume_disconnected", "volume": "E:\\", "paths": ["E:\\backup\\a.zip"]}),
+        );
+        snapshot(
+            BitBurnError::InvalidDecoy { reason: "the decoy source is empty".into() },
+            json!({"code": "invalid_decoy", "reason": "the decoy source is empty"}),
+        );
+        snapshot(
+            BitBurnError::ConfirmationRequired { reason: "the confirmation has expired".into() },
+            json!({"code": "confirmation_required", "reason": "the confirmation has expired"}),
+        );
+        snapshot(
+            BitBurnError::RemoteTieredFile { path: "C:/OneDrive/tax.pdf".into() },
+            json!({"code": "remote_tiered_file", "path": "C:/OneDrive/tax.pdf"}),
+        );
+        snapshot(
+            BitBurnError::InvalidArguments { message: "--passes must be a number, not three".into() },
+            json!({"code": "invalid_arguments", "message": "--passes must be a number, not three"}),
+        );
+        snapshot(
+            BitBurnError::UndefinedVariable { path: "%NOPE%\\a.txt".into(), variable: "NOPE".into() },
+            json!({"code": "undefined_variable", "path": "%NOPE%\\a.txt", "variable": "NOPE"}),
+        );
+        snapshot(
+            BitBurnError::RelativePathNotAllowed { path: "reports/q1.xlsx".into() },
+            json!({"code": "relative_path_not_allowed", "path": "reports/q1.xlsx"}),
+        );
+        snapshot(
+            BitBurnError::DialogUnavailable {
+                reason: "no answer within 120 seconds".into(),
+                prompt: "Wipe 2 items?".into(),
+                nonce: "prompt-0f".into(),
+            },
+            json!({"code": "dialog_unavailable", "reason": "no answer within 120 seconds", "prompt": "Wipe 2 items?", "nonce": "prompt-0f"}),
+        );
+        snapshot(
+            BitBurnError::FilesLocked { paths: vec!["C:/mail/outlook.pst".into()] },
+            json!({"code": "files_locked", "paths": ["C:/mail/outlook.pst"]}),
+        );
+        snapshot(
+            BitBurnError::InsufficientScratchSpace { path: "C:/cache".into(), required: 4096, available: 512 },
+            json!({"code": "insufficient_scratch_space", "path": "C:/cache", "required": 4096, "available": 512}),
+        );
+        snapshot(
+            BitBurnError::VolumeRoot { path: "D:\\".into() },
+            json!({"code": "volume_root", "path": "D:\\"}),
+        );
+        snapshot(
+            BitBurnError::ShortcutConflict { accelerator: "Ctrl+Alt+Shift+B".into() },
+            json!({"code": "shortcut_conflict", "accelerator": "Ctrl+Alt+Shift+B"}),
+        );
+        snapshot(
+            BitBurnError::ElevationContextMismatch { path: "Z:\\share\\a.txt".into() },
+            json!({"code": "elevation_context_mismatch", "path": "Z:\\share\\a.txt"}),
+        );
+        snapshot(
+            BitBurnError::VolumeReadOnly { volume: "E:\\".into(), paths: vec!["E:\\DCIM".into()] },
+            json!({"code": "volume_read_only", "volume": "E:\\", "paths": ["E:\\DCIM"]}),
+        );
+        snapshot(
+            BitBurnError::InvalidRange { path: "C:/vault.kdbx".into(), offset: 4096, length: 512, size: 2048 },
+            json!({"code": "invalid_range", "path": "C:/vault.kdbx", "offset": 4096, "length": 512, "size": 2048}),
+        );
+        snapshot(
+            BitBurnError::RangeInUse { path: "C:/vault.kdbx".into(), offset: 0, length: 512 },
+            json!({"code": "range_in_use", "path": "C:/vault.kdbx", "offset": 0, "length": 512}),
+        );
+    }
+
+    #[test]
+    fn structured_variants_serialize_with_stable_codes() {
+        snapshot(BitBurnError::InvalidPasses { passes: 0 }, json!({"code": "invalid_passes", "passes": 0}));
+        snapshot(
+            BitBurnError::Io {
+                kind: "Other".into(),
+                message: "boom".into(),
+                path: Some("C:/f".into()),
+                offset: Some(4096),
+            },
+            json!({"code": "io", "kind": "Other", "message": "boom", "path": "C:/f", "offset": 4096}),
+        );
+        snapshot(
+            BitBurnError::Io {
+                kind: "Other".into(),
+                message: "boom".into(),
+                path: None,
+                offset: None,
+            },
+            json!({"code": "io", "kind": "Other", "message": "boom"}),
+        );
+        snapshot(
+            BitBurnError::VerificationFailed { path: "C:/f".into(), offset: 1024, offsets: Vec::new() },
+            json!({"code": "verification_failed", "path": "C:/f", "offset": 1024}),
+        );
+        snapshot(
+            BitBurnError::FileInUse { path: "C:/f".into(), pids: vec![4, 1234] },
+            json!({"code": "file_in_use", "path": "C:/f", "pids": [4, 1234]}),
+        );
+        snapshot(
+            BitBurnError::UnsupportedFilesystem { path: "/mnt/x".into(), filesystem: "btrfs".into() },
+            json!({"code": "unsupported_filesystem", "path": "/mnt/x", "filesystem": "btrfs"}),
+        );
+        snapshot(
+            BitBurnError::Timeout { path: "E:/backup.vhdx".into(), seconds: 600 },
+            json!({"code": "timeout", "path": "E:/backup.vhdx", "seconds": 600}),
+        );
+        snapshot(
+            BitBurnError::ElevationRequired { reason: "HKLM write".into() },
+            json!({"code": "elevation_required", "reason": "HKLM write"}),
+        );
+    }
+
+    #[test]
+    fn platform_variants_serialize_with_stable_codes() {
+        snapshot(
+            BitBurnError::UnsupportedPlatform { feature: "Autostart".into() },
+            json!({"code": "unsupported_platform", "feature": "Autostart"}),
+        );
+        snapshot(BitBurnError::MissingExecutablePath, json!({"code": "missing_executable_path"}));
+        snapshot(
+            BitBurnError::Registry { message: "denied".into() },
+            json!({"code": "registry", "message": "denied"}),
+        );
+        snapshot(BitBurnError::Cancelled, json!({"code": "cancelled"}));
+        snapshot(
+            BitBurnError::OperationNotFound { operation_id: "op-1".into() },
+            json!({"code": "operation_not_found", "operation_id": "op-1"}),
+        );
+        snapshot(
+            BitBurnError::InvalidConfiguration { message: "missing version".into() },
+            json!({"code": "invalid_configuration", "message": "missing version"}),
+        );
+        snapshot(
+            BitBurnError::UnsupportedConfigVersion { found: 9, supported: 1 },
+            json!({"code": "unsupported_config_version", "found": 9, "supported": 1}),
+        );
+        snapshot(
+            BitBurnError::UnknownPreset { preset_id: "recycle_bin".into() },
+            json!({"code": "unknown_preset", "preset_id": "recycle_bin"}),
+        );
+        snapshot(
+            BitBurnError::InvalidRetentionRule { folder: "/data".into(), message: "max_age_days must be at least 1".into() },
+            json!({"code": "invalid_retention_rule", "folder": "/data", "message": "max_age_days must be at least 1"}),
+        );
+        snapshot(
+            BitBurnError::Signing { message: "corrupt keyring".into() },
+            json!({"code": "signing", "message": "corrupt keyring"}),
+        );
+        snapshot(
+            BitBurnError::Shortcut { message: "Save failed".into() },
+            json!({"code": "shortcut", "message": "Save failed"}),
+        );
+        snapshot(
+            BitBurnError::ShadowCopy { message: "Error: access denied".into() },
+            json!({"code": "shadow_copy", "message": "Error: access denied"}),
+        );
+        snapshot(
+            BitBurnError::InActiveUse { path: "C:\\logs\\app.log".into(), initial_size: 4096, size: 20_000_000 },
+            json!({"code": "in_active_use", "path": "C:\\logs\\app.log", "initial_size": 4096, "size": 20_000_000}),
+        );
+        snapshot(
+            BitBurnError::SymlinkChain { path: "/home/me/latest".into() },
+            json!({"code": "symlink_chain", "path": "/home/me/latest"}),
+        );
+        snapshot(
+            BitBurnError::Internal { message: "join error".into() },
+            json!({"code": "internal", "message": "join error"}),
+        );
+    }
+
+    #[test]
+    fn io_errors_map_to_specific_variants() {
+        let path = Path::new("/tmp/example");
+        let denied = io::Error::new(io::ErrorKind::PermissionDenied, "nope");
+        assert_eq!(BitBurnError::io(&denied, Some(path)).code(), "access_denied");
+
+        let missing = io::Error::new(io::ErrorKind::NotFound, "gone");
+        assert_eq!(BitBurnError::io(&missing, Some(path)).code(), "path_not_found");
+
+        let other = io::Error::other("disk on fire");
+        let mapped = BitBurnError::io(&other, None);
+        assert_eq!(mapped.code(), "io");
+        assert_eq!(mapped.to_string(), "IO error: disk on fire");
+    }
+
+    #[test]
+    fn wipe_errors_convert_with_path_context() {
+        let path = Path::new("/tmp/example");
+        assert_eq!(
+            BitBurnError::from_wipe_error(WipeError::PathNotFound, path),
+            BitBurnError::PathNotFound { path: "/tmp/example".into() }
+        );
+        assert_eq!(
+            BitBurnError::from_wipe_error(WipeError::InvalidPasses { passes: 0 }, path),
+            BitBurnError::InvalidPasses { passes: 0 }
+        );
+        assert_eq!(
+            BitBurnError::from_wipe_error(WipeError::SymbolicLink, path).code(),
+            "symlink_not_supported"
+        );
+        let interrupted = io::Error::new(io::ErrorKind::Interrupted, "Operation cancelled by user");
+        assert_eq!(
+            BitBurnError::from_wipe_error(WipeError::Io(interrupted), path),
+            BitBurnError::Cancelled
+        );
+        assert_eq!(
+            BitBurnError::from_wipe_error(WipeError::VerificationFailed { pass: 2, offsets: vec![4096, 8192] }, path),
+            BitBurnError::VerificationFailed { path: "/tmp/example".into(), offset: 4096, offsets: vec![4096, 8192] }
+        );
+        assert_eq!(
+            BitBurnError::from_drive_validation(DriveValidationError::NotDriveRoot, path).code(),
+            "not_drive_root"
+        );
+        assert_eq!(
+            BitBurnError::from_drive_validation(DriveValidationError::NotWritable, path).code(),
+            "access_denied"
+        );
+    }
+}
@@ -0,0 +1,337 @@
+//! Checkpoints for very large files. A many-pass wipe of a 500 GB disk image can run for a day;
+//! without checkpoints a crash or a cancelled batch near the end throws all of it away. Files at
+//! least `min_file_bytes` long record how far their passes got in the operation journal every
+//! `interval_bytes`, and a resumed or retried batch carries on from there, as long as the file is
+//! still the one the checkpoint was taken of.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::State;
+
+use crate::error::BitBurnError;
+use crate::journal::OperationJournal;
+use crate::settings::{Settings, SettingsStore};
+use crate::{log_event, WipeAlgorithm, WipeError};
+use bitburn_core::{
+    overwrite_target_with, CancellationToken, Checkpointing, FileWipeProgressRecord, OverwriteOptions, PassCheckpoint,
+    ProgressSink, WipeTarget,
+};
+
+const GIB: u64 = 1024 * 1024 * 1024;
+/// Files smaller than this are quick enough to wipe again from the start.
+pub const DEFAULT_CHECKPOINT_MIN_FILE_BYTES: u64 = 4 * GIB;
+pub const DEFAULT_CHECKPOINT_INTERVAL_BYTES: u64 = GIB;
+
+/// Which files are checkpointed, and how often.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CheckpointPolicy {
+    /// `None` checkpoints nothing.
+    pub min_file_bytes: Option<u64>,
+    pub interval_bytes: u64,
+}
+
+impl Default for CheckpointPolicy {
+    fn default() -> Self {
+        CheckpointPolicy {
+            min_file_bytes: Some(DEFAULT_CHECKPOINT_MIN_FILE_BYTES),
+            interval_bytes: DEFAULT_CHECKPOINT_INTERVAL_BYTES,
+        }
+    }
+}
+
+impl CheckpointPolicy {
+    pub fn from_settings(settings: &Settings) -> Self {
+        let defaults = CheckpointPolicy::default();
+        CheckpointPolicy {
+            min_file_bytes: match settings.checkpoint_min_file_bytes {
+                Some(0) => None,
+                Some(bytes) => Some(bytes),
+                None => defaults.min_file_bytes,
+            },
+            interval_bytes: settings.checkpoint_interval_bytes.unwrap_or(defaults.interval_bytes),
+        }
+    }
+}
+
+/// Which file a checkpoint was taken of: its size, when it was last written, and the volume and
+/// file id the OS knows it by, so a file replaced under the same name does not match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileIdentity {
+    pub size: u64,
+    /// Milliseconds since the Unix epoch.
+    pub modified: u64,
+    /// Volume serial number on Windows, device number elsewhere.
+    pub volume: u64,
+    /// File index on Windows, inode number elsewhere.
+    pub file_id: u64,
+}
+
+impl FileIdentity {
+    pub fn of(path: &Path) -> io::Result<Self> {
+        let file = fs::File::open(path)?;
+        let metadata = file.metadata()?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_millis() as u64)
+            .unwrap_or(0);
+        let (volume, file_id) = volume_and_file_id(&file, &metadata)?;
+        Ok(FileIdentity { size: metadata.len(), modified, volume, file_id })
+    }
+
+    /// Whether `current` is the file this identity was taken of. The wipe keeps writing after its
+    /// last checkpoint, so the file may have been modified since, but not before.
+    pub fn matches(&self, current: &FileIdentity) -> bool {
+        self.size == current.size
+            && self.volume == current.volume
+            && self.file_id == current.file_id
+            && current.modified >= self.modified
+    }
+}
+
+#[cfg(windows)]
+fn volume_and_file_id(file: &fs::File, _metadata: &fs::Metadata) -> io::Result<(u64, u64)> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION};
+
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let file_id = (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow);
+    Ok((u64::from(info.dwVolumeSerialNumber), file_id))
+}
+
+#[cfg(unix)]
+fn volume_and_file_id(_file: &fs::File, metadata: &fs::Metadata) -> io::Result<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Ok((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(any(windows, unix)))]
+fn volume_and_file_id(_file: &fs::File, _metadata: &fs::Metadata) -> io::Result<(u64, u64)> {
+    Ok((0, 0))
+}
+
+/// How far the passes over one file got, as kept in the operation journal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileCheckpoint {
+    pub identity: FileIdentity,
+    pub algorithm: WipeAlgorithm,
+    pub passes: u32,
+    pub progress: PassCheckpoint,
+}
+
+impl FileCheckpoint {
+    /// Whether a wipe of `identity` with `algorithm` and `passes` can carry on from here.
+    pub fn resumes(&self, identity: &FileIdentity, algorithm: &WipeAlgorithm, passes: u32) -> bool {
+        self.identity.matches(identity) && &self.algorithm == algorithm && self.passes == passes
+    }
+}
+
+/// Checkpointing for one file of a batch, moved onto its writer thread.
+pub struct FileCheckpointer {
+    journal: OperationJournal,
+    path: PathBuf,
+    identity: FileIdentity,
+    algorithm: WipeAlgorithm,
+    passes: u32,
+    interval: u64,
+    resume: Option<PassCheckpoint>,
+}
+
+impl FileCheckpointer {
+    /// Checkpointing for `path`, if it is big enough to need it. A checkpoint the journal holds
+    /// for a different file, or for a different algorithm, is dropped and the wipe starts over.
+    pub fn prepare(
+        journal: Option<&OperationJournal>,
+        policy: CheckpointPolicy,
+        path: &Path,
+        algorithm: &WipeAlgorithm,
+        passes: u32,
+    ) -> Option<Self> {
+        let journal = journal?;
+        let identity = FileIdentity::of(path).ok()?;
+        if identity.size < policy.min_file_bytes? {
+            return None;
+        }
+        let key = path.to_string_lossy();
+        let resume = match journal.checkpoint(&key) {
+            Some(checkpoint) if checkpoint.resumes(&identity, algorithm, passes) => Some(checkpoint.progress),
+            Some(_) => {
+                log_event("checkpoint_discarded", json!({"path": key, "reason": "file or wipe settings changed"}));
+                journal.clear_checkpoint(&key);
+                None
+            }
+            None => None,
+        };
+        Some(FileCheckpointer {
+            journal: journal.clone(),
+            path: path.to_path_buf(),
+            identity,
+            algorithm: algorithm.clone(),
+            passes,
+            interval: policy.interval_bytes,
+            resume,
+        })
+    }
+
+    /// `overwrite_target_with` `options`, adding checkpoints. The file's checkpoint goes once every
+    /// pass is written.
+    pub fn overwrite<T, S>(
+        self,
+        target: &mut T,
+        cancel: &CancellationToken,
+        sink: S,
+        record: &mut FileWipeProgressRecord,
+        options: OverwriteOptions<'_>,
+    ) -> Result<(), WipeError>
+    where
+        T: WipeTarget,
+        S: ProgressSink,
+    {
+        let key = self.path.to_string_lossy().to_string();
+        if let Some(resume) = &self.resume {
+            log_event(
+                "wipe_resumed_from_checkpoint",
+                json!({"path": key, "completed_passes": resume.completed_passes, "offset": resume.offset}),
+            );
+        }
+        let mut on_checkpoint = |progress: &PassCheckpoint| {
+            // Later writes move the modification time on, so take it again with each checkpoint.
+            let identity = FileIdentity::of(&self.path).unwrap_or(self.identity);
+            let checkpoint =
+                FileCheckpoint { identity, algorithm: self.algorithm.clone(), passes: self.passes, progress: progress.clone() };
+            self.journal.record_checkpoint(&key, checkpoint);
+        };
+        let checkpointing = Checkpointing { interval: self.interval, resume: self.resume.clone(), on_checkpoint: &mut on_checkpoint };
+        let options = OverwriteOptions { checkpointing: Some(checkpointing), ..options };
+        overwrite_target_with(target, self.passes, &self.algorithm, cancel, sink, record, options)?;
+        self.journal.clear_checkpoint(&key);
+        Ok(())
+    }
+}
+
+/// Set which files are checkpointed and how often; `None` restores each default, and a minimum
+/// size of 0 turns checkpoints off.
+#[tauri::command]
+pub async fn set_checkpointing(
+    settings: State<'_, SettingsStore>,
+    min_file_bytes: Option<u64>,
+    interval_bytes: Option<u64>,
+) -> Result<CheckpointPolicy, BitBurnError> {
+    if interval_bytes == Some(0) {
+        return Err(BitBurnError::InvalidConfiguration { message: "The checkpoint interval must be above zero".to_string() });
+    }
+    let updated = settings.update(|s| {
+        s.checkpoint_min_file_bytes = min_file_bytes;
+        s.checkpoint_interval_bytes = interval_bytes;
+    })?;
+    let policy = CheckpointPolicy::from_settings(&updated);
+    log_event("checkpointing_configured", json!(policy));
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::{BatchSpec, JournalStore};
+    use crate::operations::OperationRegistry;
+    use crate::test_support::{batch_context, cleanup_test_dir, create_test_dir};
+    use crate::{run_wipe_batch, BatchOptions};
+    use bitburn_core::{MemoryTarget, SymlinkPolicy, BUFFER_SIZE};
+    use std::collections::BTreeMap;
+    use tauri::Manager;
+
+    const FILE_BYTES: u64 = 3 * BUFFER_SIZE;
+
+    #[test]
+    fn a_file_matches_its_identity_until_it_is_replaced() {
+        let dir = create_test_dir().unwrap();
+        let path = dir.join("disk.img");
+        fs::write(&path, vec![1u8; 4096]).unwrap();
+        let identity = FileIdentity::of(&path).unwrap();
+
+        assert!(identity.matches(&FileIdentity::of(&path).unwrap()));
+        assert!(identity.matches(&FileIdentity { modified: identity.modified + 5_000, ..identity }), "written since");
+        assert!(!identity.matches(&FileIdentity { modified: identity.modified - 5_000, ..identity }), "older contents");
+        assert!(!identity.matches(&FileIdentity { size: 4097, ..identity }));
+
+        // Another file put in its place under the same name is not the one checkpointed.
+        let other = dir.join("other.img");
+        fs::write(&other, vec![2u8; 4096]).unwrap();
+        let kept = dir.join("kept.img");
+        fs::rename(&path, &kept).unwrap();
+        fs::rename(&other, &path).unwrap();
+        assert!(!identity.matches(&FileIdentity::of(&path).unwrap()));
+
+        let checkpoint = FileCheckpoint { identity, algorithm: WipeAlgorithm::Random, passes: 3, progress: PassCheckpoint::default() };
+        let moved = FileIdentity::of(&kept).unwrap();
+        assert!(checkpoint.resumes(&moved, &WipeAlgorithm::Random, 3));
+        assert!(!checkpoint.resumes(&moved, &WipeAlgorithm::Random, 2));
+        assert!(!checkpoint.resumes(&moved, &WipeAlgorithm::NistPurge, 3));
+        cleanup_test_dir(dir);
+    }
+
+    // The first attempt dies on the third chunk; the retry can only write one more chunk, so it
+    // succeeds only if it starts from the checkpoint after the second.
+    fn dies_on_third_chunk(_: &Path) -> Option<Box<dyn WipeTarget + Send>> {
+        Some(Box::new(MemoryTarget::new(vec![0; FILE_BYTES as usize]).with_write_limit(2 * BUFFER_SIZE + 10)))
+    }
+
+    fn writes_one_chunk(_: &Path) -> Option<Box<dyn WipeTarget + Send>> {
+        Some(Box::new(MemoryTarget::new(vec![0; FILE_BYTES as usize]).with_write_limit(BUFFER_SIZE + 10)))
+    }
+
+    #[test]
+    fn a_retried_file_carries_on_from_its_checkpoint() {
+        let dir = create_test_dir().unwrap();
+        let path = dir.join("disk.img");
+        fs::write(&path, vec![7u8; FILE_BYTES as usize]).unwrap();
+        let key = path.to_string_lossy().to_string();
+
+        let app = tauri::test::mock_app();
+        app.manage(JournalStore::new(dir.join("journals")));
+        let registry = OperationRegistry::default();
+        let policy = CheckpointPolicy { min_file_bytes: Some(FILE_BYTES), interval_bytes: BUFFER_SIZE };
+        let run = |simulate: crate::SimulatedTarget, checkpoints: BTreeMap<String, FileCheckpoint>| {
+            let ctx = batch_context(&app, &registry, "checkpoint test");
+            let spec = BatchSpec {
+                paths: vec![key.clone()],
+                passes: 1,
+                algorithm: WipeAlgorithm::NistClear,
+                source: None,
+                preserve_roots: false,
+                symlink_policy: SymlinkPolicy::Reject,
+                tasks: Vec::new(),
+            };
+            let options = BatchOptions {
+                journal: app.state::<JournalStore>().begin(ctx.operation.id(), spec, checkpoints),
+                checkpoint_policy: Some(policy),
+                simulate_target: Some(simulate),
+                ..Default::default()
+            };
+            run_wipe_batch(&ctx, [Ok(key.clone())], 1, 1, &WipeAlgorithm::NistClear, options)
+        };
+
+        assert!(!run(dies_on_third_chunk, BTreeMap::new()).success);
+        assert!(path.exists());
+        let retained = app.state::<JournalStore>().take_retained(std::slice::from_ref(&key));
+        let checkpoint = retained.get(&key).expect("the interrupted file kept its checkpoint");
+        assert_eq!((checkpoint.progress.completed_passes, checkpoint.progress.offset), (0, 2 * BUFFER_SIZE));
+        assert_eq!(checkpoint.identity.size, FILE_BYTES);
+
+        // Without the checkpoint the retry would need all three chunks.
+        assert!(!run(writes_one_chunk, BTreeMap::new()).success);
+        app.state::<JournalStore>().take_retained(std::slice::from_ref(&key));
+        let result = run(writes_one_chunk, retained);
+        assert!(result.success, "{}", result.message.text);
+        assert!(!path.exists());
+        assert!(app.state::<JournalStore>().take_retained(&[key]).is_empty(), "a finished file leaves no checkpoint");
+        cleanup_test_dir(dir);
+    }
+}
@@ -0,0 +1,330 @@
+//! Pre-flight check for removable flash media. USB sticks and memory cards remap writes inside
+//! the device to spread wear, so overwriting a file there may leave its old blocks untouched.
+//! Files on such media are flagged, and with `block_file_wipe_on_flash` the batch is refused.
+
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{Manager, Runtime};
+
+use crate::error::BitBurnError;
+use crate::messages;
+use crate::read_only::write_location;
+use crate::settings::SettingsStore;
+use crate::{log_event, BatchContext, BatchOptions, WipeResult};
+
+/// Bus a storage device is attached through, as far as flash detection cares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusType {
+    Usb,
+    /// SD or MMC card reader.
+    Sd,
+    Other,
+}
+
+/// What the operating system reports about the device a path is stored on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceDescriptor {
+    pub bus: BusType,
+    pub removable: bool,
+}
+
+/// Whether a device is wear-levelled flash that file overwrites cannot be trusted on. Memory
+/// cards always are; USB devices only when removable, since USB enclosures holding an ordinary
+/// disk report themselves as fixed.
+pub fn is_flash_media(device: &DeviceDescriptor) -> bool {
+    match device.bus {
+        BusType::Sd => true,
+        BusType::Usb => device.removable,
+        BusType::Other => false,
+    }
+}
+
+/// Ask the storage stack which device holds `path`.
+#[cfg(windows)]
+pub fn device_descriptor(path: &Path) -> Option<DeviceDescriptor> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        CreateFileW, GetVolumePathNameW, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+    };
+    use windows_sys::Win32::System::IO::DeviceIoControl;
+
+    const IOCTL_STORAGE_QUERY_PROPERTY: u32 = 0x002D_1400;
+    const BUS_TYPE_USB: i32 = 7;
+    const BUS_TYPE_SD: i32 = 12;
+    const BUS_TYPE_MMC: i32 = 13;
+
+    /// `STORAGE_PROPERTY_QUERY` for `StorageDeviceProperty` / `PropertyStandardQuery`.
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct PropertyQuery {
+        property_id: i32,
+        query_type: i32,
+        additional: [u8; 1],
+    }
+
+    /// Fixed-size head of `STORAGE_DEVICE_DESCRIPTOR`; only the bus and removable flag are read.
+    #[repr(C)]
+    #[allow(dead_code)]
+    #[derive(Default)]
+    struct DeviceDescriptorHead {
+        version: u32,
+        size: u32,
+        device_type: u8,
+        device_type_modifier: u8,
+        removable_media: u8,
+        command_queueing: u8,
+        vendor_id_offset: u32,
+        product_id_offset: u32,
+        product_revision_offset: u32,
+        serial_number_offset: u32,
+        bus_type: i32,
+        raw_properties_length: u32,
+        raw_device_properties: [u8; 1],
+    }
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut root = [0u16; 261];
+    if unsafe { GetVolumePathNameW(wide.as_ptr(), root.as_mut_ptr(), root.len() as u32) } == 0 {
+        return None;
+    }
+    // `E:\` becomes the volume device `\\.\E:`.
+    let root = String::from_utf16_lossy(&root[..root.iter().position(|&c| c == 0).unwrap_or(root.len())]);
+    let device: Vec<u16> = format!("\\\\.\\{}", root.trim_end_matches('\\'))
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+
+    unsafe {
+        let handle = CreateFileW(
+            device.as_ptr(),
+            0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE,
+            std::ptr::null(),
+            OPEN_EXISTING,
+            0,
+            std::ptr::null_mut(),
+        );
+        if handle == INVALID_HANDLE_VALUE {
+            return None;
+        }
+        let query = PropertyQuery { property_id: 0, query_type: 0, additional: [0] };
+        let mut descriptor = DeviceDescriptorHead::default();
+        let mut returned = 0u32;
+        let ok = DeviceIoControl(
+            handle,
+            IOCTL_STORAGE_QUERY_PROPERTY,
+            &query as *const PropertyQuery as *const _,
+            std::mem::size_of::<PropertyQuery>() as u32,
+            &mut descriptor as *mut DeviceDescriptorHead as *mut _,
+            std::mem::size_of::<DeviceDescriptorHead>() as u32,
+            &mut returned,
+            std::ptr::null_mut(),
+        );
+        CloseHandle(handle);
+        if ok == 0 {
+            return None;
+        }
+        let bus = match descriptor.bus_type {
+            BUS_TYPE_USB => BusType::Usb,
+            BUS_TYPE_SD | BUS_TYPE_MMC => BusType::Sd,
+            _ => BusType::Other,
+        };
+        Some(DeviceDescriptor { bus, removable: descriptor.removable_media != 0 })
+    }
+}
+
+/// Ask the storage stack which device holds `path`, through the block device's sysfs entry.
+#[cfg(target_os = "linux")]
+pub fn device_descriptor(path: &Path) -> Option<DeviceDescriptor> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dev = std::fs::metadata(path).ok()?.dev();
+    let sys = std::fs::canonicalize(format!("/sys/dev/block/{}:{}", libc::major(dev), libc::minor(dev))).ok()?;
+    Some(describe_sysfs_device(&sys))
+}
+
+/// Classify a block device from its canonical sysfs path. A partition keeps its `removable`
+/// flag on the parent disk.
+#[cfg(any(target_os = "linux", test))]
+fn describe_sysfs_device(sys: &Path) -> DeviceDescriptor {
+    let sys_path = sys.to_string_lossy();
+    let bus = if sys_path.contains("/usb") {
+        BusType::Usb
+    } else if sys_path.contains("/mmc") {
+        BusType::Sd
+    } else {
+        BusType::Other
+    };
+    let removable = [Some(sys), sys.parent()]
+        .into_iter()
+        .flatten()
+        .find_map(|dir| std::fs::read_to_string(dir.join("removable")).ok())
+        .is_some_and(|flag| flag.trim() == "1");
+    DeviceDescriptor { bus, removable }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn device_descriptor(_path: &Path) -> Option<DeviceDescriptor> {
+    None
+}
+
+/// Roots stored on flash media, asking `describe` once per directory the roots live in.
+/// Devices that cannot be described are assumed not to be flash.
+pub fn flash_roots<F>(roots: &[String], mut describe: F) -> Vec<String>
+where
+    F: FnMut(&Path) -> Option<DeviceDescriptor>,
+{
+    let mut known: HashMap<PathBuf, bool> = HashMap::new();
+    roots
+        .iter()
+        .filter(|root| {
+            let location = write_location(Path::new(root));
+            *known
+                .entry(location)
+                .or_insert_with_key(|location| describe(location).as_ref().is_some_and(is_flash_media))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Check the batch for roots on flash media before anything is overwritten. Returns a result to
+/// end the batch with when `block_file_wipe_on_flash` is set; otherwise emits
+/// `flash_media_warning` and records the roots in `options.flash_media` for the report.
+pub(crate) fn preflight<R: Runtime>(ctx: &BatchContext<R>, roots: &[String], options: &mut BatchOptions) -> Option<WipeResult> {
+    let block = ctx
+        .app_handle
+        .try_state::<SettingsStore>()
+        .is_some_and(|settings| settings.get().block_file_wipe_on_flash);
+    preflight_with(ctx, roots, options, block, device_descriptor)
+}
+
+fn preflight_with<R, F>(
+    ctx: &BatchContext<R>,
+    roots: &[String],
+    options: &mut BatchOptions,
+    block: bool,
+    describe: F,
+) -> Option<WipeResult>
+where
+    R: Runtime,
+    F: FnMut(&Path) -> Option<DeviceDescriptor>,
+{
+    let flash = flash_roots(roots, describe);
+    if flash.is_empty() {
+        return None;
+    }
+    log_event(
+        "flash_media_detected",
+        json!({"operation_id": ctx.operation.id(), "count": flash.len(), "blocked": block}),
+    );
+    if block {
+        return Some(WipeResult::failure(BitBurnError::FlashMediaRefused { paths: flash }));
+    }
+
+    let recommendation = messages::FLASH_MEDIA_WARNING.render(json!({"count": flash.len()}));
+    ctx.emit_critical(
+        "flash_media_warning",
+        json!({"operation_id": ctx.operation.id(), "paths": flash, "recommendation": recommendation}),
+    );
+    options.flash_media.extend(flash.into_iter().map(PathBuf::from));
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::OperationRegistry;
+    use crate::report::FileWipeStatus;
+    use crate::test_support::{batch_context, cleanup_test_dir, create_test_dir};
+    use crate::{run_wipe_batch, WipeAlgorithm};
+    use std::fs;
+
+    const USB_STICK: DeviceDescriptor = DeviceDescriptor { bus: BusType::Usb, removable: true };
+    const INTERNAL_DISK: DeviceDescriptor = DeviceDescriptor { bus: BusType::Other, removable: false };
+
+    /// `stick` stands in for a USB flash drive mounted inside the temp directory.
+    fn describe_stick(stick: &Path) -> impl FnMut(&Path) -> Option<DeviceDescriptor> + '_ {
+        move |dir| Some(if dir.starts_with(stick) { USB_STICK } else { INTERNAL_DISK })
+    }
+
+    #[test]
+    fn removable_usb_and_memory_cards_count_as_flash() {
+        assert!(is_flash_media(&USB_STICK));
+        assert!(is_flash_media(&DeviceDescriptor { bus: BusType::Sd, removable: false }));
+        // A USB enclosure around an ordinary disk reports itself as fixed.
+        assert!(!is_flash_media(&DeviceDescriptor { bus: BusType::Usb, removable: false }));
+        assert!(!is_flash_media(&INTERNAL_DISK));
+        assert!(!is_flash_media(&DeviceDescriptor { bus: BusType::Other, removable: true }));
+    }
+
+    #[test]
+    fn sysfs_paths_reveal_usb_and_card_readers() {
+        let dir = create_test_dir().unwrap();
+        let usb_disk = dir.join("devices/pci0000:00/usb1/1-1/host6/block/sdb");
+        let usb_partition = usb_disk.join("sdb1");
+        let card = dir.join("devices/platform/mmc0/mmc0:0001/block/mmcblk0");
+        let nvme = dir.join("devices/pci0000:00/nvme/nvme0/nvme0n1");
+        for device in [&usb_partition, &card, &nvme] {
+            fs::create_dir_all(device).unwrap();
+        }
+        fs::write(usb_disk.join("removable"), "1\n").unwrap();
+        fs::write(card.join("removable"), "0\n").unwrap();
+        fs::write(nvme.join("removable"), "0\n").unwrap();
+
+        assert_eq!(describe_sysfs_device(&usb_partition), USB_STICK);
+        assert!(is_flash_media(&describe_sysfs_device(&card)));
+        assert_eq!(describe_sysfs_device(&nvme), INTERNAL_DISK);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn files_on_a_flash_drive_are_wiped_with_a_warning_and_tagged() {
+        let dir = create_test_dir().unwrap();
+        let (stick, disk) = (dir.join("stick"), dir.join("disk"));
+        fs::create_dir_all(&stick).unwrap();
+        fs::create_dir_all(&disk).unwrap();
+        let (photo, notes) = (stick.join("photo.jpg"), disk.join("notes.txt"));
+        fs::write(&photo, b"photo").unwrap();
+        fs::write(&notes, b"notes").unwrap();
+        let roots = vec![photo.to_string_lossy().to_string(), notes.to_string_lossy().to_string()];
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "flash warn test");
+        let mut options = BatchOptions::default();
+        assert!(preflight_with(&ctx, &roots, &mut options, false, describe_stick(&stick)).is_none());
+        assert_eq!(options.flash_media, vec![photo.clone()]);
+
+        let result = run_wipe_batch(&ctx, roots.into_iter().map(Ok), 2, 1, &WipeAlgorithm::NistClear, options);
+        assert!(result.success, "{}", result.message.text);
+        let files = result.report.unwrap().files;
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|file| file.status == FileWipeStatus::Success));
+        assert!(files[0].flash_media && !files[1].flash_media);
+        assert!(!photo.exists() && !notes.exists());
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn blocking_refuses_the_batch_before_anything_is_overwritten() {
+        let dir = create_test_dir().unwrap();
+        let stick = dir.join("stick");
+        fs::create_dir_all(&stick).unwrap();
+        let photo = stick.join("photo.jpg");
+        fs::write(&photo, b"photo").unwrap();
+        let roots = vec![photo.to_string_lossy().to_string()];
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "flash block test");
+        let result = preflight_with(&ctx, &roots, &mut BatchOptions::default(), true, describe_stick(&stick))
+            .expect("the batch must be refused");
+
+        assert_eq!(result.error, Some(BitBurnError::FlashMediaRefused { paths: roots }));
+        assert_eq!(result.error.unwrap().code(), "flash_media_refused");
+        assert_eq!(fs::read(&photo).unwrap(), b"photo");
+        cleanup_test_dir(dir);
+    }
+}
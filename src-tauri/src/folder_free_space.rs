@@ -0,0 +1,150 @@
+//! Free-space wipes asked for from a folder. Wiping free space needs the root of a volume, which
+//! left anyone who had just cleaned a folder to work out which drive it was on.
+//! `free_space_wipe_for_path` takes the folder's volume, the deepest mount point it lies under,
+//! checks it like any drive picked for a free-space wipe and wipes its free space, noting the
+//! folder in the report. Explorer's folder menu offers it through `--free-space-for`.
+
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::error::BitBurnError;
+use crate::path_expansion::{self, RelativeBase};
+use crate::{diagnostics, log_event, read_only, validate_drive_path_internal, WipeAlgorithm, WipeResult};
+
+pub const FREE_SPACE_FOR_FLAG: &str = "--free-space-for";
+
+/// The volume `folder` is on: the deepest of `mounts` it lies under.
+pub(crate) fn resolve_volume(folder: &Path, mounts: &[PathBuf]) -> Result<PathBuf, BitBurnError> {
+    if !folder.exists() {
+        return Err(BitBurnError::PathNotFound { path: folder.to_string_lossy().to_string() });
+    }
+    Ok(read_only::volume_of(folder, mounts))
+}
+
+/// `resolve_volume` against this machine's mounts, checked as a free-space target.
+fn validated_volume(folder: &Path) -> Result<PathBuf, BitBurnError> {
+    let volume = resolve_volume(folder, &read_only::mount_points())?;
+    validate_drive_path_internal(&volume).map_err(|e| BitBurnError::from_drive_validation(e, &volume))?;
+    Ok(volume)
+}
+
+/// Note on the report of a wipe of `folder`'s volume which folder it was asked for from.
+fn annotate(mut result: WipeResult, folder: &str) -> WipeResult {
+    if let Some(report) = result.report.as_mut() {
+        report.free_space_origin = Some(folder.to_string());
+    }
+    result
+}
+
+/// Wipe the free space of the volume `path` is on.
+#[tauri::command]
+pub async fn free_space_wipe_for_path<R: Runtime>(
+    window: tauri::Window<R>,
+    path: String,
+    algorithm: WipeAlgorithm,
+    passes: u32,
+    delete_shadow_copies: Option<bool>,
+    consent_token: Option<String>,
+) -> Result<WipeResult, BitBurnError> {
+    let folder = PathBuf::from(&path);
+    let resolved = tauri::async_runtime::spawn_blocking(move || validated_volume(&folder))
+        .await
+        .map_err(|e| BitBurnError::internal(format!("free_space_wipe_for_path task join error: {}", e)))?;
+    let volume = match resolved {
+        Ok(volume) => volume.to_string_lossy().to_string(),
+        Err(error) => {
+            log_event("free_space_for_path_rejected", json!({"path": path, "code": error.code()}));
+            return Ok(WipeResult::failure(error));
+        }
+    };
+    log_event("free_space_for_path", json!({"path": path, "volume": volume}));
+    let result =
+        crate::execute_free_space_wipe(window, volume, algorithm, passes, delete_shadow_copies, None, consent_token).await?;
+    Ok(annotate(result, &path))
+}
+
+/// The folder after `--free-space-for`.
+pub fn free_space_arg(argv: &[String]) -> Option<String> {
+    let index = argv.iter().position(|arg| arg == FREE_SPACE_FOR_FLAG)?;
+    argv.get(index + 1).filter(|value| !value.starts_with("--")).cloned()
+}
+
+/// Payload of `free_space_for_path_request`: the frontend confirms the wipe and calls
+/// `free_space_wipe_for_path` with `path`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FreeSpaceRequest {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume: Option<String>,
+    /// Why the folder's volume cannot have its free space wiped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<BitBurnError>,
+}
+
+fn request_for(raw: &str, cwd: &Path) -> FreeSpaceRequest {
+    let resolved = path_expansion::expand(raw, RelativeBase::Dir(cwd))
+        .map_err(|error| (raw.to_string(), error))
+        .and_then(|path| validated_volume(Path::new(&path)).map(|volume| (path.clone(), volume)).map_err(|error| (path, error)));
+    match resolved {
+        Ok((path, volume)) => FreeSpaceRequest { path, volume: Some(volume.to_string_lossy().to_string()), error: None },
+        Err((path, error)) => FreeSpaceRequest { path, volume: None, error: Some(error) },
+    }
+}
+
+/// Hand a `--free-space-for` folder from the file manager to the frontend, with its volume.
+pub fn dispatch(app: &AppHandle, raw: &str, cwd: &Path) {
+    let (app, raw, cwd) = (app.clone(), raw.to_string(), cwd.to_path_buf());
+    tauri::async_runtime::spawn_blocking(move || {
+        let request = request_for(&raw, &cwd);
+        log_event("free_space_for_path_requested", json!({"path": request.path, "volume": request.volume}));
+        diagnostics::emit(&app, "free_space_for_path_request", &request);
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::WipeReport;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+    use crate::volume_root::comparable;
+    use std::fs;
+
+    #[test]
+    fn a_folder_resolves_to_the_deepest_mount_above_it() {
+        let dir = create_test_dir().unwrap();
+        let folder = dir.join("usb").join("photos").join("2024");
+        fs::create_dir_all(&folder).unwrap();
+        fs::create_dir_all(dir.join("usb-backup")).unwrap();
+        let root = comparable(Path::new("/"));
+        let mounts = vec![root.clone(), comparable(&dir), comparable(&dir.join("usb"))];
+
+        assert_eq!(resolve_volume(&folder, &mounts).unwrap(), comparable(&dir.join("usb")));
+        // A sibling whose name starts with a mount's is not under it.
+        assert_eq!(resolve_volume(&dir.join("usb-backup"), &mounts).unwrap(), comparable(&dir));
+        assert_eq!(resolve_volume(&dir.join("usb"), &mounts).unwrap(), comparable(&dir.join("usb")));
+        assert_eq!(resolve_volume(&folder, &[root.clone(), comparable(&dir)]).unwrap(), comparable(&dir));
+        assert!(matches!(resolve_volume(&dir.join("gone"), &mounts), Err(BitBurnError::PathNotFound { .. })));
+        cleanup_test_dir(&dir);
+    }
+
+    #[test]
+    fn the_report_names_the_folder_the_wipe_came_from() {
+        let result = WipeResult::success(crate::messages::PATH_VALIDATED.text()).with_report(WipeReport::new("op-1", None));
+        let annotated = annotate(result, "/data/reports");
+        let json = serde_json::to_value(&annotated).unwrap();
+        assert_eq!(json["report"]["free_space_origin"], "/data/reports");
+
+        let refused = annotate(WipeResult::failure(BitBurnError::Cancelled), "/data/reports");
+        assert!(serde_json::to_value(&refused).unwrap().get("report").is_none());
+
+        let argv: Vec<String> = ["BitBurn", "--sender-context=standard", FREE_SPACE_FOR_FLAG, "/data/reports"].map(String::from).to_vec();
+        assert_eq!(free_space_arg(&argv).as_deref(), Some("/data/reports"));
+        assert_eq!(free_space_arg(&argv[..3]), None);
+    }
+}
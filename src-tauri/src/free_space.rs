@@ -0,0 +1,124 @@
+use serde::Serialize;
+use serde_json::json;
+use std::path::Path;
+
+use crate::messages::{self, Message};
+
+/// Free space on a volume as seen by this process. With NTFS quotas (or reserved blocks
+/// on Unix) the caller may only write `caller_available` of the volume's `total_free` bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreeSpace {
+    pub caller_available: u64,
+    pub total_free: u64,
+}
+
+impl FreeSpace {
+    /// Bytes the fill can be expected to write, used as the progress total.
+    pub fn fill_estimate(&self) -> u64 {
+        self.caller_available.min(self.total_free)
+    }
+
+    /// Whether a quota keeps this user from filling the whole volume.
+    pub fn is_quota_limited(&self) -> bool {
+        self.caller_available < self.total_free
+    }
+}
+
+/// Query free space for the volume containing `path`; `None` when the platform call fails.
+#[cfg(windows)]
+pub fn query_free_space(path: &Path) -> Option<FreeSpace> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut caller_available = 0u64;
+    let mut total_bytes = 0u64;
+    let mut total_free = 0u64;
+    let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut caller_available, &mut total_bytes, &mut total_free) };
+    (ok != 0).then_some(FreeSpace { caller_available, total_free })
+}
+
+#[cfg(unix)]
+pub fn query_free_space(path: &Path) -> Option<FreeSpace> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let block = stat.f_frsize as u64;
+    Some(FreeSpace {
+        caller_available: stat.f_bavail as u64 * block,
+        total_free: stat.f_bfree as u64 * block,
+    })
+}
+
+#[cfg(not(any(windows, unix)))]
+pub fn query_free_space(_path: &Path) -> Option<FreeSpace> {
+    None
+}
+
+/// How much of the volume's free space a fill actually covered.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FreeSpaceCoverage {
+    pub bytes_written: u64,
+    /// Bytes written by the fill and every overwrite pass of the filled space.
+    pub total_bytes_written: u64,
+    /// Free space the volume reported before the fill, including space outside the user's quota.
+    pub volume_free_bytes: u64,
+    /// The fill stopped at the user's quota instead of a full disk.
+    pub quota_limited: bool,
+}
+
+impl FreeSpaceCoverage {
+    /// Result message; quota-limited fills spell out how much free space they left untouched.
+    pub fn message(&self) -> Message {
+        if self.quota_limited {
+            messages::FREE_SPACE_WIPED_TO_QUOTA.render(json!({
+                "written_mb": format!("{:.1}", self.bytes_written as f64 / 1024.0 / 1024.0),
+                "free_mb": format!("{:.1}", self.volume_free_bytes as f64 / 1024.0 / 1024.0),
+                "bytes_written": self.bytes_written,
+                "volume_free_bytes": self.volume_free_bytes,
+            }))
+        } else {
+            messages::FREE_SPACE_WIPED.text()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn progress_estimate_uses_the_callers_quota() {
+        let quota = FreeSpace { caller_available: 400 * MB, total_free: 1000 * MB };
+        assert_eq!(quota.fill_estimate(), 400 * MB);
+        assert!(quota.is_quota_limited());
+
+        let unrestricted = FreeSpace { caller_available: 1000 * MB, total_free: 1000 * MB };
+        assert_eq!(unrestricted.fill_estimate(), 1000 * MB);
+        assert!(!unrestricted.is_quota_limited());
+    }
+
+    #[test]
+    fn quota_limited_coverage_reports_written_versus_free() {
+        let coverage = FreeSpaceCoverage {
+            bytes_written: 400 * MB,
+            total_bytes_written: 1600 * MB,
+            volume_free_bytes: 1000 * MB,
+            quota_limited: true,
+        };
+        let message = coverage.message();
+        assert_eq!(message.code, "free_space_wiped_to_quota");
+        assert_eq!(message.params["bytes_written"], 400 * MB);
+        assert!(message.text.contains("quota"));
+        assert!(message.text.contains("400.0 MB written of 1000.0 MB"));
+
+        let full = FreeSpaceCoverage { quota_limited: false, ..coverage };
+        assert_eq!(full.message().text, "Successfully wiped free space");
+    }
+}
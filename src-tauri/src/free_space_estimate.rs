@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::drive_wear::WearWarning;
+use crate::error::BitBurnError;
+use crate::volume_history::FreeSpaceWipeRecord;
+
+/// Write rate assumed when no free-space fill has been measured yet: a slow spinning disk,
+/// so the estimate errs towards taking longer.
+pub const DEFAULT_BYTES_PER_SECOND: u64 = 80 * 1024 * 1024;
+
+/// A wipe refuses to start when available space moved by more than this share of the estimate.
+pub const STALE_ESTIMATE_TOLERANCE: f64 = 0.20;
+
+/// Estimates are forgotten after this long; the user has to look at fresh numbers.
+const ESTIMATE_LIFETIME: Duration = Duration::from_secs(60 * 60);
+
+/// Relative spread of the ETA range around the point estimate.
+const MEASURED_SPREAD: f64 = 0.25;
+const DEFAULT_SPREAD: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThroughputSource {
+    /// Average rate of the last free-space fill in this session.
+    Measured,
+    /// `DEFAULT_BYTES_PER_SECOND`.
+    Default,
+}
+
+/// What a free-space wipe of a volume will write and roughly how long it will take.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FreeSpaceEstimate {
+    /// Pass to `execute_free_space_wipe` so it can check the numbers are still current.
+    pub token: String,
+    pub path: String,
+    pub available_bytes: u64,
+    pub passes: u32,
+    /// The fill plus every overwrite pass of the filled space.
+    pub total_bytes: u64,
+    pub bytes_per_second: u64,
+    pub throughput_source: ThroughputSource,
+    pub eta_min_seconds: u64,
+    pub eta_max_seconds: u64,
+    /// The volume is an SSD this wipe would take past the 30-day write warning level.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wear_warning: Option<WearWarning>,
+    /// The volume's free space was wiped to completion within `volume_history::RECENT_WIPE_WINDOW`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recently_wiped: Option<FreeSpaceWipeRecord>,
+}
+
+impl FreeSpaceEstimate {
+    pub fn compute(path: &str, available_bytes: u64, passes: u32, measured_bytes_per_second: Option<u64>) -> Self {
+        let (bytes_per_second, throughput_source, spread) = match measured_bytes_per_second {
+            Some(rate) if rate > 0 => (rate, ThroughputSource::Measured, MEASURED_SPREAD),
+            _ => (DEFAULT_BYTES_PER_SECOND, ThroughputSource::Default, DEFAULT_SPREAD),
+        };
+        // The fill writes the free space once before the passes overwrite it.
+        let total_bytes = available_bytes.saturating_mul(u64::from(passes) + 1);
+        let seconds_at = |rate: f64| (total_bytes as f64 / rate).ceil() as u64;
+        FreeSpaceEstimate {
+            token: String::new(),
+            path: path.to_string(),
+            available_bytes,
+            passes,
+            total_bytes,
+            bytes_per_second,
+            throughput_source,
+            eta_min_seconds: seconds_at(bytes_per_second as f64 * (1.0 + spread)),
+            eta_max_seconds: seconds_at(bytes_per_second as f64 * (1.0 - spread)),
+            wear_warning: None,
+            recently_wiped: None,
+        }
+    }
+}
+
+/// Estimate a free-space wipe was confirmed against, passed back by the frontend.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EstimateCheck {
+    pub token: String,
+    /// Start even if free space changed by more than `STALE_ESTIMATE_TOLERANCE`.
+    #[serde(default)]
+    pub ignore_stale: bool,
+}
+
+/// Whether available space moved too far from what the user was shown.
+pub fn is_stale(estimated_bytes: u64, available_bytes: u64) -> bool {
+    let drift = estimated_bytes.abs_diff(available_bytes) as f64;
+    drift > estimated_bytes.max(1) as f64 * STALE_ESTIMATE_TOLERANCE
+}
+
+struct IssuedEstimate {
+    path: String,
+    available_bytes: u64,
+    issued_at: SystemTime,
+}
+
+/// Managed state: estimates handed out to the frontend and the last measured fill rate.
+#[derive(Default)]
+pub struct FreeSpaceEstimates {
+    issued: Mutex<HashMap<String, IssuedEstimate>>,
+    measured_bytes_per_second: Mutex<Option<u64>>,
+}
+
+impl FreeSpaceEstimates {
+    pub fn measured_bytes_per_second(&self) -> Option<u64> {
+        *self.measured_bytes_per_second.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Remember the average rate of a completed fill for later estimates.
+    pub fn record_throughput(&self, bytes: u64, elapsed: Duration) {
+        if bytes > 0 && elapsed.as_secs_f64() > 0.0 {
+            let rate = (bytes as f64 / elapsed.as_secs_f64()) as u64;
+            *self.measured_bytes_per_second.lock().unwrap_or_else(|p| p.into_inner()) = Some(rate);
+        }
+    }
+
+    /// Store `estimate` and fill in its token.
+    pub fn issue(&self, mut estimate: FreeSpaceEstimate, now: SystemTime) -> FreeSpaceEstimate {
+        let mut issued = self.issued.lock().unwrap_or_else(|p| p.into_inner());
+        issued.retain(|_, entry| !expired(entry, now));
+        let nanos = now.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        estimate.token = format!("est-{:x}-{:08x}", nanos, rand::random::<u32>());
+        issued.insert(
+            estimate.token.clone(),
+            IssuedEstimate {
+                path: estimate.path.clone(),
+                available_bytes: estimate.available_bytes,
+                issued_at: now,
+            },
+        );
+        estimate
+    }
+
+    /// Check a wipe of `path` against the estimate behind `token` before it starts.
+    /// `override_stale` lets the user go ahead after being told the numbers changed.
+    pub fn check(
+        &self,
+        token: &str,
+        path: &str,
+        available_bytes: u64,
+        override_stale: bool,
+        now: SystemTime,
+    ) -> Result<(), BitBurnError> {
+        let issued = self.issued.lock().unwrap_or_else(|p| p.into_inner());
+        let Some(entry) = issued.get(token).filter(|entry| entry.path == path && !expired(entry, now)) else {
+            return Err(BitBurnError::EstimateNotFound { token: token.to_string() });
+        };
+        if !override_stale && is_stale(entry.available_bytes, available_bytes) {
+            return Err(BitBurnError::StaleEstimate {
+                path: path.to_string(),
+                estimated_bytes: entry.available_bytes,
+                available_bytes,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn expired(entry: &IssuedEstimate, now: SystemTime) -> bool {
+    now.duration_since(entry.issued_at).map(|age| age > ESTIMATE_LIFETIME).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+    const TB: u64 = 1024 * GB;
+
+    #[test]
+    fn estimate_counts_the_fill_and_every_pass() {
+        let estimate = FreeSpaceEstimate::compute("/mnt/nas", 14 * TB, 2, Some(1024 * 1024 * 1024));
+        assert_eq!(estimate.total_bytes, 42 * TB);
+        assert_eq!(estimate.throughput_source, ThroughputSource::Measured);
+        // 42 TiB at 1 GiB/s is 43008 s; the range is ±25% of the rate.
+        assert_eq!(estimate.eta_min_seconds, (43008.0f64 / 1.25).ceil() as u64);
+        assert_eq!(estimate.eta_max_seconds, (43008.0f64 / 0.75).ceil() as u64);
+        assert!(estimate.eta_min_seconds < estimate.eta_max_seconds);
+    }
+
+    #[test]
+    fn unmeasured_throughput_falls_back_to_the_conservative_default() {
+        let estimate = FreeSpaceEstimate::compute("C:\\", 100 * GB, 1, None);
+        assert_eq!(estimate.throughput_source, ThroughputSource::Default);
+        assert_eq!(estimate.bytes_per_second, DEFAULT_BYTES_PER_SECOND);
+        assert_eq!(estimate.total_bytes, 200 * GB);
+        assert_eq!(estimate.eta_max_seconds, (200.0 * 1024.0 / 80.0 / 0.5f64).ceil() as u64);
+
+        let zero_rate = FreeSpaceEstimate::compute("C:\\", 100 * GB, 1, Some(0));
+        assert_eq!(zero_rate.throughput_source, ThroughputSource::Default);
+    }
+
+    #[test]
+    fn more_than_twenty_percent_drift_is_stale() {
+        assert!(!is_stale(100 * GB, 100 * GB));
+        assert!(!is_stale(100 * GB, 80 * GB));
+        assert!(!is_stale(100 * GB, 120 * GB));
+        assert!(is_stale(100 * GB, 79 * GB));
+        assert!(is_stale(100 * GB, 121 * GB));
+        assert!(is_stale(0, 1));
+    }
+
+    #[test]
+    fn wipes_are_checked_against_the_issued_estimate() {
+        let estimates = FreeSpaceEstimates::default();
+        let now = SystemTime::now();
+        let issued = estimates.issue(FreeSpaceEstimate::compute("D:\\", 500 * GB, 3, None), now);
+        assert!(issued.token.starts_with("est-"));
+
+        assert_eq!(estimates.check(&issued.token, "D:\\", 450 * GB, false, now), Ok(()));
+        assert_eq!(
+            estimates.check(&issued.token, "D:\\", 300 * GB, false, now),
+            Err(BitBurnError::StaleEstimate { path: "D:\\".into(), estimated_bytes: 500 * GB, available_bytes: 300 * GB })
+        );
+        assert_eq!(estimates.check(&issued.token, "D:\\", 300 * GB, true, now), Ok(()), "the override skips the guard");
+
+        let wrong_volume = estimates.check(&issued.token, "E:\\", 500 * GB, false, now);
+        assert!(matches!(wrong_volume, Err(BitBurnError::EstimateNotFound { .. })));
+        let later = now + ESTIMATE_LIFETIME + Duration::from_secs(1);
+        let expired = estimates.check(&issued.token, "D:\\", 500 * GB, true, later);
+        assert!(matches!(expired, Err(BitBurnError::EstimateNotFound { .. })));
+    }
+
+    #[test]
+    fn completed_fills_become_the_measured_rate() {
+        let estimates = FreeSpaceEstimates::default();
+        assert_eq!(estimates.measured_bytes_per_second(), None);
+        estimates.record_throughput(0, Duration::from_secs(1));
+        assert_eq!(estimates.measured_bytes_per_second(), None);
+        estimates.record_throughput(10 * GB, Duration::from_secs(100));
+        assert_eq!(estimates.measured_bytes_per_second(), Some(10 * GB / 100));
+    }
+}
@@ -0,0 +1,335 @@
+//! Read-only look at what a volume's free space still holds, for a before/after comparison around
+//! a free-space wipe. A scratch file is allocated on the volume without being written, and random
+//! regions of it are read back: whatever deleted data those clusters held is what shows. Only
+//! statistics leave this module, never the bytes read. Windows lets an elevated process do this
+//! with `SetFileValidData`; other file systems zero fresh blocks before they can be read, so
+//! there the sample is reported as unsupported.
+
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::async_runtime::spawn_blocking;
+use tauri::State;
+
+use crate::error::BitBurnError;
+use crate::free_space;
+use crate::log_event;
+use crate::scratch;
+
+/// Size of each region read back.
+pub const SAMPLE_BYTES: u64 = 64 * 1024;
+pub const DEFAULT_SAMPLES: usize = 32;
+pub const MAX_SAMPLES: usize = 1024;
+/// The scratch file is this many times the bytes sampled, so the regions come from a wider
+/// stretch of free space than they cover.
+const ALLOCATION_SPREAD: u64 = 16;
+const SAMPLE_FILE: &str = "free-space-sample.bin";
+/// A sample at least this printable reads as plain text.
+const PLAINTEXT_RATIO: f64 = 0.85;
+
+/// Statistics of one region read back.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SampleStats {
+    pub offset: u64,
+    /// Shannon entropy in bits per byte, 0 to 8. Wiped random data is close to 8.
+    pub entropy: f64,
+    /// Share of bytes that are printable ASCII, tabs or line breaks.
+    pub printable_ratio: f64,
+    pub zeroed: bool,
+}
+
+impl SampleStats {
+    pub fn measure(offset: u64, data: &[u8]) -> Self {
+        SampleStats {
+            offset,
+            entropy: entropy(data),
+            printable_ratio: printable_ratio(data),
+            zeroed: data.iter().all(|&byte| byte == 0),
+        }
+    }
+
+    pub fn is_plaintext(&self) -> bool {
+        !self.zeroed && self.printable_ratio >= PLAINTEXT_RATIO
+    }
+}
+
+/// Totals over the samples of one run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SampleSummary {
+    pub sampled_at: u64,
+    pub samples: usize,
+    pub sample_bytes: u64,
+    pub mean_entropy: f64,
+    pub mean_printable_ratio: f64,
+    /// Samples that read as plain text: the obviously recoverable kind.
+    pub plaintext_samples: usize,
+    /// Samples of nothing but zeros, never written or already wiped with zeros.
+    pub zeroed_samples: usize,
+}
+
+impl SampleSummary {
+    pub fn of(samples: &[SampleStats], sample_bytes: u64, sampled_at: u64) -> Self {
+        let mean = |value: fn(&SampleStats) -> f64| match samples.len() {
+            0 => 0.0,
+            count => samples.iter().map(value).sum::<f64>() / count as f64,
+        };
+        SampleSummary {
+            sampled_at,
+            samples: samples.len(),
+            sample_bytes,
+            mean_entropy: mean(|sample| sample.entropy),
+            mean_printable_ratio: mean(|sample| sample.printable_ratio),
+            plaintext_samples: samples.iter().filter(|sample| sample.is_plaintext()).count(),
+            zeroed_samples: samples.iter().filter(|sample| sample.zeroed).count(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FreeSpaceSample {
+    pub path: String,
+    /// Why free space could not be read here; there are no samples then.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unsupported: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<SampleSummary>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<SampleStats>,
+    /// The last sample of the same path in this session, e.g. the one taken before a wipe.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<SampleSummary>,
+}
+
+impl FreeSpaceSample {
+    fn unsupported(path: &str, reason: impl Into<String>) -> Self {
+        FreeSpaceSample {
+            path: path.to_string(),
+            unsupported: Some(reason.into()),
+            summary: None,
+            samples: Vec::new(),
+            previous: None,
+        }
+    }
+}
+
+/// Managed state: the last summary sampled for each path, so the next sample and a free-space
+/// wipe's report can be compared with it.
+#[derive(Default)]
+pub struct FreeSpaceSamples {
+    last: Mutex<HashMap<String, SampleSummary>>,
+}
+
+impl FreeSpaceSamples {
+    pub fn last(&self, path: &str) -> Option<SampleSummary> {
+        self.last.lock().unwrap_or_else(|p| p.into_inner()).get(path).cloned()
+    }
+
+    /// Keep `summary` as the latest for `path` and return the one it replaces.
+    fn record(&self, path: &str, summary: SampleSummary) -> Option<SampleSummary> {
+        self.last.lock().unwrap_or_else(|p| p.into_inner()).insert(path.to_string(), summary)
+    }
+}
+
+pub fn entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0usize; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+    let len = data.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+pub fn printable_ratio(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let printable = data.iter().filter(|&&byte| matches!(byte, 0x20..=0x7e | b'\t' | b'\n' | b'\r')).count();
+    printable as f64 / data.len() as f64
+}
+
+/// Read `count` distinct regions of `sample_bytes` at random, region-aligned offsets below `len`.
+pub fn read_samples<F: Read + Seek>(file: &mut F, len: u64, count: usize, sample_bytes: u64) -> io::Result<Vec<SampleStats>> {
+    let regions = len / sample_bytes.max(1);
+    let count = (count as u64).min(regions);
+    let mut picked = BTreeSet::new();
+    while (picked.len() as u64) < count {
+        picked.insert(rand::random::<u64>() % regions);
+    }
+    let mut buffer = vec![0u8; sample_bytes as usize];
+    let mut samples = Vec::with_capacity(picked.len());
+    for region in picked {
+        let offset = region * sample_bytes;
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut buffer)?;
+        samples.push(SampleStats::measure(offset, &buffer));
+    }
+    Ok(samples)
+}
+
+/// Sample the free space of the volume holding `path`.
+pub fn sample(path: &Path, count: usize) -> Result<FreeSpaceSample, BitBurnError> {
+    let display = path.to_string_lossy().to_string();
+    fs::metadata(path).map_err(|e| BitBurnError::io(&e, Some(path)))?;
+    if let Some(reason) = unsupported_here() {
+        return Ok(FreeSpaceSample::unsupported(&display, reason));
+    }
+    let count = count.clamp(1, MAX_SAMPLES);
+    let wanted = count as u64 * SAMPLE_BYTES;
+    let free = free_space::query_free_space(path).map(|space| space.fill_estimate()).unwrap_or(0);
+    // Leave at least half of the free space alone; this is only a look.
+    let len = (wanted * ALLOCATION_SPREAD).min(free / 2 / SAMPLE_BYTES * SAMPLE_BYTES);
+    if len < SAMPLE_BYTES {
+        return Ok(FreeSpaceSample::unsupported(&display, "there is too little free space to sample"));
+    }
+
+    let scratch = scratch::ensure_scratch_space_on(path, len)?;
+    let file_path = scratch.path().join(SAMPLE_FILE);
+    // Its clusters hold other files' old data once marked valid, so nobody else may open it: no
+    // sharing, and a DACL for its owner alone, set before any cluster is exposed.
+    let mut file =
+        scratch::create_temp_file(&file_path).map_err(|e| BitBurnError::io(&e, Some(&file_path)))?;
+    if let Err(reason) = allocate_unwritten(&file, len) {
+        return Ok(FreeSpaceSample::unsupported(&display, reason));
+    }
+    let samples = read_samples(&mut file, len, count, SAMPLE_BYTES).map_err(|e| BitBurnError::io(&e, Some(&file_path)))?;
+    // Nothing was written, so removing the file leaves the clusters as they were.
+    drop(file);
+    drop(scratch);
+
+    let sampled_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    Ok(FreeSpaceSample {
+        path: display,
+        unsupported: None,
+        summary: Some(SampleSummary::of(&samples, SAMPLE_BYTES, sampled_at)),
+        samples,
+        previous: None,
+    })
+}
+
+#[cfg(windows)]
+fn unsupported_here() -> Option<&'static str> {
+    (!crate::platform::elevation::is_elevated()).then_some("reading unwritten clusters needs BitBurn to run as administrator")
+}
+
+#[cfg(not(windows))]
+fn unsupported_here() -> Option<&'static str> {
+    Some("this platform's file systems zero newly allocated space before it can be read")
+}
+
+/// Give `file` `len` bytes of allocated clusters and mark them valid without writing them.
+#[cfg(windows)]
+fn allocate_unwritten(file: &File, len: u64) -> Result<(), String> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::SetFileValidData;
+
+    enable_manage_volume_privilege()?;
+    file.set_len(len).map_err(|e| format!("could not allocate the sample file: {}", e))?;
+    if unsafe { SetFileValidData(file.as_raw_handle() as _, len as i64) } == 0 {
+        return Err(format!("the file system would not expose unwritten clusters: {}", io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn allocate_unwritten(_file: &File, _len: u64) -> Result<(), String> {
+    Err("newly allocated space cannot be read before it is written".to_string())
+}
+
+/// `SetFileValidData` needs SeManageVolumePrivilege, which administrators hold but do not enable.
+#[cfg(windows)]
+fn enable_manage_volume_privilege() -> Result<(), String> {
+    crate::platform::elevation::enable_privilege("SeManageVolumePrivilege")
+        .map_err(|e| format!("the volume maintenance privilege is not available: {}", e))
+}
+
+/// Sample the free space of the volume holding `path` in `samples` regions (`DEFAULT_SAMPLES`
+/// when not given), comparing with the last sample of the same path.
+#[tauri::command]
+pub async fn sample_free_space(
+    state: State<'_, FreeSpaceSamples>,
+    path: String,
+    samples: Option<usize>,
+) -> Result<FreeSpaceSample, BitBurnError> {
+    let location = PathBuf::from(&path);
+    let count = samples.unwrap_or(DEFAULT_SAMPLES);
+    let mut result = spawn_blocking(move || sample(&location, count))
+        .await
+        .map_err(|e| BitBurnError::internal(format!("sample_free_space join error: {}", e)))??;
+    match &result.summary {
+        Some(summary) => result.previous = state.record(&path, summary.clone()),
+        None => result.previous = state.last(&path),
+    }
+    log_event(
+        "free_space_sampled",
+        json!({"path": path, "unsupported": result.unsupported, "summary": result.summary}),
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn entropy_and_printable_ratio_of_synthetic_buffers() {
+        assert_eq!(entropy(&[]), 0.0);
+        assert_eq!(entropy(&[0u8; 4096]), 0.0);
+        // Every byte value equally often is the maximum of 8 bits per byte.
+        let uniform: Vec<u8> = (0..=255u8).cycle().take(256 * 16).collect();
+        assert!((entropy(&uniform) - 8.0).abs() < 1e-9);
+        // Two values half and half is one bit.
+        assert!((entropy(&[b'a', b'b'].repeat(100)) - 1.0).abs() < 1e-9);
+
+        let text = b"Quarterly figures\r\n\tRevenue: 1,204,000\n".repeat(50);
+        assert_eq!(printable_ratio(&text), 1.0);
+        assert!(SampleStats::measure(0, &text).is_plaintext());
+        assert!((printable_ratio(&uniform) - 98.0 / 256.0).abs() < 1e-9);
+        assert!(!SampleStats::measure(0, &uniform).is_plaintext());
+
+        let zeros = SampleStats::measure(0, &[0u8; 512]);
+        assert!(zeros.zeroed && !zeros.is_plaintext());
+        assert_eq!(printable_ratio(&[]), 0.0);
+    }
+
+    #[test]
+    fn samples_are_distinct_regions_and_summarised() {
+        let mut data = vec![0u8; 8 * 16];
+        data[..16].copy_from_slice(b"plain text here.");
+        let regions: Vec<u8> = (128..=255u8).cycle().take(16 * 6).collect();
+        data[32..128].copy_from_slice(&regions);
+        let samples = read_samples(&mut Cursor::new(&data), data.len() as u64, 20, 16).unwrap();
+        // Only eight regions exist, so asking for more reads each once.
+        assert_eq!(samples.iter().map(|sample| sample.offset).collect::<Vec<_>>(), (0..8).map(|i| i * 16).collect::<Vec<_>>());
+
+        let summary = SampleSummary::of(&samples, 16, 7);
+        assert_eq!((summary.samples, summary.plaintext_samples, summary.zeroed_samples), (8, 1, 1));
+        assert_eq!(summary.mean_entropy, samples.iter().map(|s| s.entropy).sum::<f64>() / 8.0);
+        assert_eq!(SampleSummary::of(&[], 16, 7).mean_entropy, 0.0);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn sampling_is_reported_unsupported_where_fresh_space_cannot_be_read() {
+        let dir = std::env::temp_dir();
+        let result = sample(&dir, 4).unwrap();
+        assert!(result.unsupported.is_some());
+        assert!(result.summary.is_none() && result.samples.is_empty());
+        assert!(sample(&dir.join("BitBurn_no_such_sample_dir"), 4).is_err());
+    }
+}
@@ -0,0 +1,391 @@
+use glob::{MatchOptions, Pattern};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use tauri::async_runtime::spawn_blocking;
+use walkdir::WalkDir;
+
+use crate::error::BitBurnError;
+use crate::path_expansion::{self, RelativeBase};
+use crate::platform::context_menu::{sanitize_target_path, ContextWipePayload};
+
+/// CLI flag followed by one or more paths or glob patterns to wipe.
+pub const WIPE_FLAG: &str = "--wipe";
+/// Confirms a `--wipe` or `--paths-from` wipe where no dialog can be shown to ask.
+pub const YES_FLAG: &str = "--yes";
+
+/// Outcome of expanding one pattern.
+#[derive(Debug, Clone, Serialize)]
+pub struct PatternExpansion {
+    pub pattern: String,
+    pub matches: usize,
+    /// Pattern is anchored at a drive/filesystem root and was not expanded without explicit confirmation.
+    pub requires_confirmation: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// Matched targets for a set of patterns, ready for preview or for `wipe_files`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GlobExpansion {
+    pub paths: Vec<String>,
+    pub patterns: Vec<PatternExpansion>,
+    /// Matches rejected by the standard sanitization (symlinks, network paths, ...).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<BitBurnError>,
+}
+
+fn match_options() -> MatchOptions {
+    MatchOptions {
+        case_sensitive: !cfg!(windows),
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    }
+}
+
+pub(crate) fn has_wildcards(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Directory part of a pattern before its first wildcard component.
+fn literal_prefix(pattern: &str) -> PathBuf {
+    let mut prefix = PathBuf::new();
+    for component in Path::new(pattern).components() {
+        if has_wildcards(&component.as_os_str().to_string_lossy()) {
+            break;
+        }
+        prefix.push(component.as_os_str());
+    }
+    prefix
+}
+
+/// Entries under the literal prefix of `pattern` that match it, in name order. The walk never
+/// follows a symbolic link or junction, so `**` stays inside the folder it starts from; a link
+/// the pattern itself matches is still returned, for sanitization to refuse.
+fn matching_entries(pattern: &str) -> Result<Vec<Result<PathBuf, BitBurnError>>, glob::PatternError> {
+    let compiled = Pattern::new(pattern)?;
+    let prefix = literal_prefix(pattern);
+    let relative = prefix.as_os_str().is_empty();
+    let root = if relative { PathBuf::from(".") } else { prefix.clone() };
+    let below: Vec<_> = Path::new(pattern).components().skip(prefix.components().count()).collect();
+    let recursive = below.iter().any(|component| component.as_os_str().to_string_lossy().contains("**"));
+
+    let mut walk = WalkDir::new(&root).min_depth(1).sort_by_file_name();
+    if !recursive {
+        walk = walk.max_depth(below.len());
+    }
+    let options = match_options();
+    let mut entries = Vec::new();
+    for entry in walk {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                let path = err.path().map(Path::to_path_buf);
+                entries.push(Err(BitBurnError::io(&io::Error::from(err), path.as_deref())));
+                continue;
+            }
+        };
+        let path = if relative {
+            entry.path().strip_prefix(".").unwrap_or(entry.path()).to_path_buf()
+        } else {
+            entry.into_path()
+        };
+        if compiled.matches_path_with(&path, options) {
+            entries.push(Ok(path));
+        }
+    }
+    Ok(entries)
+}
+
+/// True when a wildcard pattern would walk an entire drive or filesystem root, e.g. `C:\**\*.tmp`.
+pub fn is_root_anchored(pattern: &str) -> bool {
+    if !has_wildcards(pattern) {
+        return false;
+    }
+    let prefix = literal_prefix(pattern);
+    let mut components = prefix.components();
+    match components.next() {
+        Some(Component::RootDir) => components.next().is_none(),
+        Some(Component::Prefix(_)) => match components.next() {
+            None => true,
+            Some(Component::RootDir) => components.next().is_none(),
+            Some(_) => false,
+        },
+        _ => false,
+    }
+}
+
+/// Expand paths and glob patterns (`**`, `?`, `[...]`) into sanitized, de-duplicated targets.
+/// Wildcard patterns only select files; literal paths may name directories.
+/// Root-anchored patterns are left unexpanded unless `allow_root_patterns` is set.
+pub fn expand_patterns(patterns: &[String], allow_root_patterns: bool) -> GlobExpansion {
+    let mut expansion = GlobExpansion::default();
+    let mut seen = HashSet::new();
+
+    for raw in patterns {
+        let pattern = raw.trim().to_string();
+        if pattern.is_empty() {
+            continue;
+        }
+
+        let mut outcome = PatternExpansion {
+            pattern: pattern.clone(),
+            matches: 0,
+            requires_confirmation: false,
+            warning: None,
+        };
+
+        if !has_wildcards(&pattern) {
+            match sanitize_target_path(&pattern) {
+                Ok(path) => {
+                    outcome.matches = 1;
+                    if seen.insert(path.clone()) {
+                        expansion.paths.push(path);
+                    }
+                }
+                Err(err) => outcome.warning = Some(err.to_string()),
+            }
+            expansion.patterns.push(outcome);
+            continue;
+        }
+
+        if is_root_anchored(&pattern) && !allow_root_patterns {
+            outcome.requires_confirmation = true;
+            outcome.warning = Some(format!(
+                "Pattern {} scans an entire drive and must be confirmed before it is expanded",
+                pattern
+            ));
+            expansion.patterns.push(outcome);
+            continue;
+        }
+
+        let entries = match matching_entries(&pattern) {
+            Ok(entries) => entries,
+            Err(err) => {
+                outcome.warning = Some(format!("Invalid pattern {}: {}", pattern, err));
+                expansion.patterns.push(outcome);
+                continue;
+            }
+        };
+
+        for entry in entries {
+            let path = match entry {
+                Ok(path) => path,
+                Err(err) => {
+                    expansion.skipped.push(err);
+                    continue;
+                }
+            };
+
+            // Symlinks are reported by sanitization; other non-files (directories) are not targets.
+            if !path.is_symlink() && !path.is_file() {
+                continue;
+            }
+
+            match sanitize_target_path(&path.to_string_lossy()) {
+                Ok(path) => {
+                    outcome.matches += 1;
+                    if seen.insert(path.clone()) {
+                        expansion.paths.push(path);
+                    }
+                }
+                Err(err) => expansion.skipped.push(err),
+            }
+        }
+
+        if outcome.matches == 0 {
+            outcome.warning = Some(format!("Pattern {} did not match any files", pattern));
+        }
+        expansion.patterns.push(outcome);
+    }
+
+    expansion
+}
+
+/// Arguments following `--wipe` up to the next flag.
+pub fn wipe_arg_patterns(argv: &[String]) -> Vec<String> {
+    argv.iter()
+        .position(|arg| arg == WIPE_FLAG)
+        .map(|index| {
+            argv.iter()
+                .skip(index + 1)
+                .take_while(|arg| !arg.starts_with("--"))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Build the context-wipe payload for `--wipe` so matches go through the usual confirmation flow.
+/// Environment variables are expanded and relative patterns resolved against `cwd`, the working
+/// directory of the command line. Root-anchored patterns are never expanded from the CLI; they
+/// appear as warnings instead.
+pub fn glob_context_payload(patterns: &[String], cwd: &Path) -> ContextWipePayload {
+    let mut invalid = Vec::new();
+    let patterns: Vec<String> = patterns
+        .iter()
+        .filter_map(|pattern| match path_expansion::expand(pattern, RelativeBase::Dir(cwd)) {
+            Ok(pattern) => Some(pattern),
+            Err(err) => {
+                invalid.push(err.to_string());
+                None
+            }
+        })
+        .collect();
+    let expansion = expand_patterns(&patterns, false);
+    invalid.extend(expansion.patterns.iter().filter_map(|p| p.warning.clone()));
+    invalid.extend(expansion.skipped.iter().map(|err| err.to_string()));
+
+    ContextWipePayload {
+        paths: expansion.paths,
+        invalid,
+        source: "cli".to_string(),
+        volume_roots: Vec::new(),
+        elevation_mismatch: None,
+        token: None,
+        assume_yes: false,
+    }
+}
+
+/// Resolve paths and glob patterns for preview; pass the returned `paths` to `wipe_files` to wipe them.
+/// Patterns anchored at a drive root are only expanded when `allow_root_patterns` is true.
+#[tauri::command]
+pub async fn expand_globs(
+    patterns: Vec<String>,
+    allow_root_patterns: Option<bool>,
+) -> Result<GlobExpansion, BitBurnError> {
+    let allow_root_patterns = allow_root_patterns.unwrap_or(false);
+    spawn_blocking(move || expand_patterns(&patterns, allow_root_patterns))
+        .await
+        .map_err(|e| BitBurnError::internal(format!("expand_globs task join error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+    use std::fs;
+
+    fn build_tree() -> PathBuf {
+        let root = create_test_dir().unwrap();
+        fs::create_dir_all(root.join("sub").join("deep")).unwrap();
+        fs::write(root.join("a.tmp"), b"a").unwrap();
+        fs::write(root.join("sub").join("b.tmp"), b"b").unwrap();
+        fs::write(root.join("sub").join("deep").join("c.tmp"), b"c").unwrap();
+        fs::write(root.join("sub").join("keep.txt"), b"k").unwrap();
+        root
+    }
+
+    fn pattern_in(root: &Path, suffix: &str) -> String {
+        format!("{}/{}", Pattern::escape(&root.to_string_lossy()), suffix)
+    }
+
+    fn file_names(expansion: &GlobExpansion) -> Vec<String> {
+        let mut names: Vec<String> = expansion
+            .paths
+            .iter()
+            .map(|p| Path::new(p).file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn recursive_pattern_matches_nested_files() {
+        let root = build_tree();
+        let expansion = expand_patterns(&[pattern_in(&root, "**/*.tmp")], false);
+        assert_eq!(file_names(&expansion), vec!["a.tmp", "b.tmp", "c.tmp"]);
+        assert_eq!(expansion.patterns[0].matches, 3);
+        assert!(expansion.patterns[0].warning.is_none());
+        cleanup_test_dir(root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn recursive_patterns_do_not_follow_linked_folders() {
+        let root = build_tree();
+        let outside = root.with_extension("outside");
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("elsewhere.tmp"), b"e").unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("sub").join("linked")).unwrap();
+        // A loop would never end if links were followed.
+        std::os::unix::fs::symlink(&root, root.join("sub").join("loop")).unwrap();
+
+        let expansion = expand_patterns(&[pattern_in(&root, "**/*.tmp")], false);
+        assert_eq!(file_names(&expansion), vec!["a.tmp", "b.tmp", "c.tmp"]);
+        let expansion = expand_patterns(&[pattern_in(&root, "sub/*/*.tmp")], false);
+        assert_eq!(file_names(&expansion), vec!["c.tmp"]);
+        assert!(outside.join("elsewhere.tmp").exists());
+        cleanup_test_dir(root);
+        cleanup_test_dir(outside);
+    }
+
+    #[test]
+    fn single_star_does_not_cross_directories() {
+        let root = build_tree();
+        let expansion = expand_patterns(&[pattern_in(&root, "*.tmp")], false);
+        assert_eq!(file_names(&expansion), vec!["a.tmp"]);
+        cleanup_test_dir(root);
+    }
+
+    #[test]
+    fn extension_filters_and_character_classes_select_files() {
+        let root = build_tree();
+        let expansion = expand_patterns(
+            &[pattern_in(&root, "sub/*.txt"), pattern_in(&root, "sub/[bc].tm?")],
+            false,
+        );
+        assert_eq!(file_names(&expansion), vec!["b.tmp", "keep.txt"]);
+        cleanup_test_dir(root);
+    }
+
+    #[test]
+    fn overlapping_patterns_are_deduplicated() {
+        let root = build_tree();
+        let expansion = expand_patterns(&[pattern_in(&root, "**/*.tmp"), pattern_in(&root, "a.*")], false);
+        assert_eq!(expansion.paths.len(), 3);
+        assert_eq!(expansion.patterns[1].matches, 1);
+        cleanup_test_dir(root);
+    }
+
+    #[test]
+    fn zero_match_pattern_produces_warning() {
+        let root = build_tree();
+        let expansion = expand_patterns(&[pattern_in(&root, "**/*.docx")], false);
+        assert!(expansion.paths.is_empty());
+        let warning = expansion.patterns[0].warning.as_deref().expect("warning expected");
+        assert!(warning.contains("did not match any files"));
+
+        let payload = glob_context_payload(&[pattern_in(&root, "**/*.docx")], &root);
+        assert!(payload.paths.is_empty());
+        assert_eq!(payload.invalid.len(), 1);
+        cleanup_test_dir(root);
+    }
+
+    #[test]
+    fn root_anchored_patterns_require_confirmation() {
+        assert!(is_root_anchored("/**/*.tmp"));
+        assert!(!is_root_anchored("/tmp/**/*.tmp"));
+        assert!(!is_root_anchored("/tmp/file.txt"));
+        #[cfg(windows)]
+        {
+            assert!(is_root_anchored("C:\\**\\*.tmp"));
+            assert!(is_root_anchored("C:\\*"));
+            assert!(!is_root_anchored("C:\\Temp\\**\\*.tmp"));
+        }
+
+        let expansion = expand_patterns(&["/*.definitely-not-here".to_string()], false);
+        assert!(expansion.patterns[0].requires_confirmation);
+        assert!(expansion.paths.is_empty());
+    }
+
+    #[test]
+    fn wipe_flag_collects_patterns_until_next_flag() {
+        let args: Vec<String> = ["BitBurn.exe", "--wipe", "C:/Temp/**/*.tmp", "D:/x.bin", "--other"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(wipe_arg_patterns(&args), vec!["C:/Temp/**/*.tmp", "D:/x.bin"]);
+    }
+}
@@ -0,0 +1,462 @@
+//! Headless wipes for scripts and orchestration tools:
+//! `BitBurn --headless --wipe <path or pattern>... [--passes N] [--algorithm NAME] [--output json|text|quiet]`
+//! wipes without opening a window and exits with 0 once everything was wiped, 1 if anything was
+//! not and 2 on bad arguments. The paths go through the same pre-flight checks and batch as
+//! `wipe_files`: journal and checkpoints, lock scan, companions, read-only volumes, flash media,
+//! remote recall and parent timestamps all follow the user's settings. Nobody is there to answer a
+//! prompt, so volume roots are refused and files the lock scan finds open stop the run unless the
+//! settings say what to do with them.
+//!
+//! `--output json` turns stdout into a JSON-lines stream of `StreamLine`s: one `start` line, then
+//! `progress` lines at most `progress_events_per_second` times a second and a `file` line per file,
+//! then one `result` line holding the same result and report `wipe_files` returns to the UI.
+//! `quiet` prints the result line alone and `text`, the default, prints the same events for people.
+//! Log events go to stderr in every mode, so stdout only ever carries the stream.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{App, AppHandle, Context, Manager, Runtime, Wry};
+
+use crate::error::BitBurnError;
+use crate::glob_targets::{self, expand_patterns, has_wildcards};
+use crate::operations::{OperationGuard, OperationKind, OperationRegistry};
+use crate::path_expansion::{self, RelativeBase};
+use crate::platform::context_menu::sanitize_target_path;
+use crate::policy;
+use crate::progress_coalescer::{CoalescedProgress, ProgressCoalescer, DEFAULT_EVENTS_PER_SECOND, PROGRESS_EVENT};
+use crate::report::{FileWipeOutcome, FileWipeStatus};
+use crate::settings::SettingsStore;
+use crate::{log_event, BatchContext, BatchOptions, WipeResult};
+use bitburn_core::WipeAlgorithm;
+
+/// Run a wipe without the desktop app; everything else on the command line works as for `--wipe`.
+pub const HEADLESS_FLAG: &str = "--headless";
+pub const OUTPUT_FLAG: &str = "--output";
+pub const PASSES_FLAG: &str = "--passes";
+pub const ALGORITHM_FLAG: &str = "--algorithm";
+/// Recorded as the report's `source`.
+const SOURCE: &str = "headless";
+
+pub const EXIT_SUCCESS: i32 = 0;
+/// Something was not wiped; the result line says what.
+pub const EXIT_INCOMPLETE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether this process is a headless wipe, whose stdout belongs to the stream.
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputMode {
+    Json,
+    #[default]
+    Text,
+    Quiet,
+}
+
+impl OutputMode {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "json" => Some(OutputMode::Json),
+            "text" => Some(OutputMode::Text),
+            "quiet" => Some(OutputMode::Quiet),
+            _ => None,
+        }
+    }
+}
+
+/// What a headless command line asks for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadlessSpec {
+    pub patterns: Vec<String>,
+    pub passes: u32,
+    pub algorithm: WipeAlgorithm,
+    pub output: OutputMode,
+}
+
+/// The `type: "start"` line: what is about to be wiped, after patterns were expanded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartLine {
+    pub operation_id: String,
+    pub paths: Vec<String>,
+    pub passes: u32,
+    pub algorithm: WipeAlgorithm,
+}
+
+/// The `type: "progress"` line, made from the coalesced `wipe_progress` event the UI receives.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressLine {
+    pub operation_id: String,
+    pub phase: String,
+    pub overall_percentage: f32,
+    pub bytes_per_second: Option<u64>,
+    /// Files finished since the previous progress line.
+    pub files_completed: u64,
+}
+
+impl ProgressLine {
+    /// `event`'s phase and rate, with the whole batch's percentage; the event carries the file's own.
+    fn new(event: CoalescedProgress, overall_percentage: f32) -> Self {
+        ProgressLine {
+            operation_id: event.operation_id,
+            phase: event.progress.current_pattern,
+            overall_percentage,
+            bytes_per_second: event.progress.bytes_per_second,
+            files_completed: event.files_completed,
+        }
+    }
+}
+
+/// The `type: "file"` line, written once each file's wipe has ended.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileLine {
+    pub operation_id: String,
+    pub path: String,
+    pub status: FileWipeStatus,
+}
+
+/// The `type: "result"` line: the result `wipe_files` returns, report included.
+#[derive(Serialize)]
+pub struct ResultLine {
+    pub operation_id: String,
+    #[serde(flatten)]
+    pub result: WipeResult,
+}
+
+/// One line of the stream. Lines only ever gain fields, so parsers should ignore ones they do not know.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamLine {
+    Start(StartLine),
+    Progress(ProgressLine),
+    File(FileLine),
+    Result(Box<ResultLine>),
+}
+
+/// Value following `flag`, if the flag is present.
+fn flag_value<'a>(argv: &'a [String], flag: &str) -> Option<Result<&'a str, BitBurnError>> {
+    let index = argv.iter().position(|arg| arg == flag)?;
+    Some(
+        argv.get(index + 1)
+            .filter(|value| !value.starts_with("--"))
+            .map(String::as_str)
+            .ok_or_else(|| BitBurnError::InvalidArguments { message: format!("{} needs a value", flag) }),
+    )
+}
+
+/// Read a headless command line; `None` when `--headless` is absent.
+pub fn parse_args(argv: &[String]) -> Option<Result<HeadlessSpec, BitBurnError>> {
+    if !is_requested(argv) {
+        return None;
+    }
+    Some((|| {
+        let invalid = |message: String| BitBurnError::InvalidArguments { message };
+        let output = match flag_value(argv, OUTPUT_FLAG).transpose()? {
+            Some(value) => OutputMode::parse(value)
+                .ok_or_else(|| invalid(format!("{} must be json, text or quiet, not {}", OUTPUT_FLAG, value)))?,
+            None => OutputMode::default(),
+        };
+        let algorithm = match flag_value(argv, ALGORITHM_FLAG).transpose()? {
+            Some(value) => serde_json::from_value(json!(value))
+                .map_err(|_| invalid(format!("Unknown algorithm {}", value)))?,
+            None => WipeAlgorithm::NistPurge,
+        };
+        let passes = match flag_value(argv, PASSES_FLAG).transpose()? {
+            Some(value) => value.parse().map_err(|_| invalid(format!("{} must be a number, not {}", PASSES_FLAG, value)))?,
+            None => algorithm.default_passes(),
+        };
+        if passes == 0 {
+            return Err(BitBurnError::InvalidPasses { passes });
+        }
+        let patterns = glob_targets::wipe_arg_patterns(argv);
+        if patterns.is_empty() {
+            return Err(invalid(format!("{} needs at least one path", glob_targets::WIPE_FLAG)));
+        }
+        Ok(HeadlessSpec { patterns, passes, algorithm, output })
+    })())
+}
+
+/// Writes stream lines to stdout in the chosen form.
+#[derive(Clone, Copy)]
+struct Output(OutputMode);
+
+impl Output {
+    fn write(&self, line: &StreamLine) {
+        match (self.0, line) {
+            (OutputMode::Json, _) | (OutputMode::Quiet, StreamLine::Result(_)) => {
+                if let Ok(serialized) = serde_json::to_string(line) {
+                    println!("{}", serialized);
+                }
+            }
+            (OutputMode::Quiet, _) => {}
+            (OutputMode::Text, line) => println!("{}", text(line)),
+        }
+    }
+}
+
+/// A stream line as people read it.
+fn text(line: &StreamLine) -> String {
+    match line {
+        StreamLine::Start(start) => format!(
+            "Wiping {} paths with {:?}, {} passes ({})",
+            start.paths.len(),
+            start.algorithm,
+            start.passes,
+            start.operation_id
+        ),
+        StreamLine::Progress(progress) => {
+            let rate = progress
+                .bytes_per_second
+                .map(|rate| format!("  {:.1} MB/s", rate as f64 / 1024.0 / 1024.0))
+                .unwrap_or_default();
+            format!("{:5.1}%  {}{}", progress.overall_percentage, progress.phase, rate)
+        }
+        StreamLine::File(file) => {
+            let status = serde_json::to_value(file.status).ok().and_then(|v| v.as_str().map(str::to_string));
+            format!("{:<9} {}", status.unwrap_or_default(), file.path)
+        }
+        StreamLine::Result(result) => {
+            let mut lines = vec![result.result.message.text.clone()];
+            lines.extend(result.result.failures.iter().map(|failure| format!("  {}", failure.message.text)));
+            lines.join("\n")
+        }
+    }
+}
+
+/// Whether the command line asks for a headless run.
+pub fn is_requested(argv: &[String]) -> bool {
+    argv.iter().any(|arg| arg == HEADLESS_FLAG)
+}
+
+/// Run the headless wipe a command line with `--headless` asks for and return the exit code.
+pub fn run_from_args(argv: &[String], context: Context<Wry>) -> i32 {
+    ACTIVE.store(true, Ordering::Relaxed);
+    let spec = parse_args(argv).unwrap_or_else(|| Err(BitBurnError::InvalidArguments { message: format!("{} is missing", HEADLESS_FLAG) }));
+    let spec = match spec {
+        Ok(spec) => spec,
+        Err(e) => {
+            log_event("headless_arguments_invalid", json!({"message": e.to_string()}));
+            // Even a usage error ends the stream with a result line parsers can rely on.
+            let requested = flag_value(argv, OUTPUT_FLAG).and_then(Result::ok).and_then(OutputMode::parse);
+            let output = Output(requested.unwrap_or(OutputMode::Json));
+            let operation_id = format!("headless-{:x}", std::process::id());
+            output.write(&StreamLine::Result(Box::new(ResultLine { operation_id, result: WipeResult::failure(e) })));
+            return EXIT_USAGE;
+        }
+    };
+    let output = Output(spec.output);
+    let line = match build_app(context) {
+        Ok(app) => run(app.handle(), &spec, output),
+        Err(e) => {
+            let operation_id = format!("headless-{:x}", std::process::id());
+            ResultLine { operation_id, result: WipeResult::failure(BitBurnError::internal(format!("headless start failed: {}", e))) }
+        }
+    };
+    let code = if line.result.success { EXIT_SUCCESS } else { EXIT_INCOMPLETE };
+    output.write(&StreamLine::Result(Box::new(line)));
+    code
+}
+
+/// The app without its window, holding the state a batch reads; its event loop never runs.
+fn build_app(mut context: Context<Wry>) -> tauri::Result<App<Wry>> {
+    context.config_mut().app.windows.clear();
+    let app = tauri::Builder::default().build(context)?;
+    crate::init_state(&app);
+    Ok(app)
+}
+
+/// Expand the spec's paths and patterns the way `--wipe` does, after their environment variables;
+/// relative ones are taken from the working directory. Literal paths that cannot be wiped are
+/// refused, and pattern matches the sanitization turns away are skipped.
+fn expand(spec: &HeadlessSpec, options: &mut BatchOptions) -> Vec<String> {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let mut paths: Vec<String> = Vec::new();
+    for pattern in &spec.patterns {
+        let pattern = match path_expansion::expand(pattern, RelativeBase::Dir(&cwd)) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                options.carried_failures.push(e);
+                continue;
+            }
+        };
+        let pattern = &pattern;
+        if !has_wildcards(pattern) {
+            match sanitize_target_path(pattern) {
+                Ok(path) if !paths.contains(&path) => paths.push(path),
+                Ok(_) => {}
+                Err(e) => options.carried_failures.push(e),
+            }
+            continue;
+        }
+        let expansion = expand_patterns(std::slice::from_ref(pattern), false);
+        options.carried_warnings.extend(expansion.patterns.into_iter().filter_map(|outcome| outcome.warning));
+        options.carried_skips.extend(expansion.skipped);
+        for path in expansion.paths {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+/// Drive the batch `wipe_files` runs, with its pre-flight checks, over the spec's paths; its
+/// progress and file outcomes become stream lines.
+fn run<R: Runtime>(app: &AppHandle<R>, spec: &HeadlessSpec, output: Output) -> ResultLine {
+    let mut options = BatchOptions { source: Some(SOURCE.to_string()), ..Default::default() };
+    let paths = expand(spec, &mut options);
+    let registry = app.state::<OperationRegistry>();
+    let operation = OperationGuard::begin(app, &registry, OperationKind::WipeFiles, crate::summarize_targets(&paths));
+    let operation_id = operation.id().to_string();
+    if let Err(e) = policy::check_wipe(app, policy::WIPE_FILES, &spec.algorithm, spec.passes, &paths) {
+        let result = WipeResult::failure(e);
+        operation.record_result(&result);
+        return ResultLine { operation_id, result };
+    }
+
+    let events_per_second = app
+        .try_state::<SettingsStore>()
+        .and_then(|settings| settings.get().progress_events_per_second)
+        .unwrap_or(DEFAULT_EVENTS_PER_SECOND);
+    let overall = app.clone();
+    let progress = ProgressCoalescer::new(&operation_id, events_per_second, move |event: &str, payload: Value| {
+        if event != PROGRESS_EVENT {
+            return;
+        }
+        if let Ok(event) = serde_json::from_value::<CoalescedProgress>(payload) {
+            // The batch's own account of how far it is, as the operation list shows it.
+            let percentage = overall
+                .state::<OperationRegistry>()
+                .get(&event.operation_id)
+                .map_or(0.0, |snapshot| snapshot.overall_percentage);
+            output.write(&StreamLine::Progress(ProgressLine::new(event, percentage)));
+        }
+    });
+    let mut ctx = BatchContext::with_progress(app.clone(), operation, progress);
+    let file_operation = operation_id.clone();
+    ctx.on_file = Some(Box::new(move |outcome: &FileWipeOutcome| {
+        let line = FileLine { operation_id: file_operation.clone(), path: outcome.path.clone(), status: outcome.status };
+        output.write(&StreamLine::File(line));
+    }));
+
+    output.write(&StreamLine::Start(StartLine {
+        operation_id: operation_id.clone(),
+        paths: paths.clone(),
+        passes: spec.passes,
+        algorithm: spec.algorithm.clone(),
+    }));
+    let _log_scope = ctx.operation.enter_log_scope();
+    log_event(
+        "wipe_files_start",
+        json!({"operation_id": operation_id, "count": paths.len(), "algorithm": format!("{:?}", spec.algorithm), "passes": spec.passes, "source": SOURCE}),
+    );
+    let result = crate::wipe_selection(&ctx, paths, spec.passes, &spec.algorithm, options);
+    ResultLine { operation_id, result }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+    use bitburn_core::WipeProgress;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        std::iter::once("BitBurn").chain(args.iter().copied()).map(String::from).collect()
+    }
+
+    #[test]
+    fn command_lines_are_read_with_defaults_and_rejected_when_malformed() {
+        assert!(parse_args(&argv(&["--wipe", "/tmp/a"])).is_none(), "only --headless runs without the app");
+        let spec = parse_args(&argv(&["--headless", "--wipe", "/tmp/a", "/tmp/*.log"])).unwrap().unwrap();
+        assert_eq!(spec.patterns, ["/tmp/a", "/tmp/*.log"]);
+        assert_eq!((spec.passes, spec.algorithm, spec.output), (3, WipeAlgorithm::NistPurge, OutputMode::Text));
+
+        let spec = parse_args(&argv(&["--headless", "--output", "quiet", "--algorithm", "NistClear", "--wipe", "/a"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!((spec.passes, spec.algorithm, spec.output), (1, WipeAlgorithm::NistClear, OutputMode::Quiet));
+
+        for bad in [
+            &["--headless", "--wipe"][..],
+            &["--headless", "--wipe", "/a", "--output", "xml"],
+            &["--headless", "--wipe", "/a", "--passes", "three"],
+            &["--headless", "--wipe", "/a", "--passes", "0"],
+            &["--headless", "--wipe", "/a", "--algorithm", "Shred"],
+            &["--headless", "--wipe", "/a", "--output"],
+        ] {
+            assert!(parse_args(&argv(bad)).unwrap().is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn stream_lines_are_tagged_with_their_type() {
+        let progress = CoalescedProgress {
+            operation_id: "headless-1".into(),
+            progress: WipeProgress {
+                percentage: 90.0,
+                current_pattern: "Pass 2/3".into(),
+                bytes_per_second: Some(1024),
+                ..WipeProgress::new(3, 100, "NistPurge")
+            },
+            files_completed: 2,
+            coalesced_updates: 7,
+        };
+        assert_eq!(
+            serde_json::to_value(StreamLine::Progress(ProgressLine::new(progress, 42.5))).unwrap(),
+            json!({
+                "type": "progress",
+                "operation_id": "headless-1",
+                "phase": "Pass 2/3",
+                "overall_percentage": 42.5,
+                "bytes_per_second": 1024,
+                "files_completed": 2,
+            })
+        );
+        let file = FileLine { operation_id: "headless-1".into(), path: "/a".into(), status: FileWipeStatus::Error };
+        assert_eq!(
+            serde_json::to_value(StreamLine::File(file)).unwrap(),
+            json!({"type": "file", "operation_id": "headless-1", "path": "/a", "status": "error"})
+        );
+        let result = ResultLine { operation_id: "headless-1".into(), result: WipeResult::failure(BitBurnError::Cancelled) };
+        let result = serde_json::to_value(StreamLine::Result(Box::new(result))).unwrap();
+        assert_eq!((result["type"].as_str(), result["success"].as_bool()), (Some("result"), Some(false)));
+        assert_eq!(result["error"]["code"], "cancelled");
+    }
+
+    #[test]
+    fn a_headless_run_goes_through_the_batch_and_reports_each_file() {
+        let dir = create_test_dir().unwrap();
+        let (file, companion) = (dir.join("notes.txt"), dir.join("notes.txt.bak"));
+        std::fs::write(&file, b"secret").unwrap();
+        std::fs::write(&companion, b"secret").unwrap();
+
+        let app = tauri::test::mock_app();
+        app.manage(OperationRegistry::default());
+        let spec = HeadlessSpec {
+            patterns: [file.clone(), dir.join("gone.txt"), dir.join("*.missing")]
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect(),
+            passes: 1,
+            algorithm: WipeAlgorithm::NistClear,
+            output: OutputMode::Quiet,
+        };
+        let line = run(app.handle(), &spec, Output(OutputMode::Quiet));
+
+        // The missing file fails the run; the other is wiped all the same.
+        assert!(!line.result.success);
+        assert_eq!(line.result.failures.len(), 1);
+        assert!(!file.exists());
+        let report = line.result.report.expect("a report");
+        assert_eq!((report.operation_id.as_str(), report.source.as_deref()), (line.operation_id.as_str(), Some(SOURCE)));
+        assert_eq!((report.files.len(), report.files_wiped), (1, 1));
+        assert!(!report.warnings.is_empty(), "a pattern that matched nothing is reported");
+        // Companions follow the settings, which default to leaving them alone.
+        assert!(companion.exists());
+        cleanup_test_dir(dir);
+    }
+}
@@ -0,0 +1,664 @@
+//! Crash journal for file batches. While a batch runs, its spec and the indices of the roots it
+//! has finished are kept in a small JSON file in the app data directory. A clean exit removes it,
+//! so a journal found at startup belongs to a batch the process never finished, which the UI can
+//! offer to resume or discard. Journals hold paths and progress only, never file contents.
+//! Very large files also keep the checkpoints of their passes here (see `file_checkpoint`).
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::consent;
+use crate::error::BitBurnError;
+use crate::file_checkpoint::FileCheckpoint;
+use crate::messages;
+use crate::operations::OperationRegistry;
+use crate::protected::{self, ProtectedPaths};
+use crate::report::{FileDisposition, InterruptedFile};
+use crate::wipe_finish::RenameLog;
+use crate::wipe_tasks::WipeTask;
+use crate::{log_event, scratch, start_file_wipe, BatchOptions, WipeAlgorithm, WipeResult};
+use bitburn_core::{secure_wipe_file, CancellationToken, SymlinkPolicy, WipeProgress};
+
+pub const JOURNAL_DIR: &str = "journals";
+const JOURNAL_VERSION: u32 = 1;
+const JOURNAL_EXTENSION: &str = "json";
+/// How long completed roots may go unrecorded; a crash re-wipes at most this much work.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What a batch was asked to do, enough to start the rest of it again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchSpec {
+    pub paths: Vec<String>,
+    pub passes: u32,
+    pub algorithm: WipeAlgorithm,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Selected folders are emptied but kept; a resumed batch must not remove them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub preserve_roots: bool,
+    /// How selected symbolic links were to be handled; a resumed batch handles them the same way.
+    #[serde(default)]
+    pub symlink_policy: SymlinkPolicy,
+    /// Roots of a mixed batch with their own algorithm and passes; a resumed batch keeps them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tasks: Vec<WipeTask>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalFile {
+    version: u32,
+    operation_id: String,
+    started_at: u64,
+    updated_at: u64,
+    spec: BatchSpec,
+    /// Indices into `spec.paths` of roots that were wiped, as merged half-open ranges.
+    completed: Vec<(usize, usize)>,
+    /// Pass checkpoints of large files still being wiped, by path.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    checkpoints: BTreeMap<String, FileCheckpoint>,
+    /// Files given a random name before deletion, by original path, until they are gone.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    renamed: BTreeMap<String, String>,
+}
+
+impl JournalFile {
+    fn mark_completed(&mut self, index: usize) {
+        if self.is_completed(index) {
+            return;
+        }
+        self.completed.push((index, index + 1));
+        self.completed.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(self.completed.len());
+        for &(start, end) in &self.completed {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        self.completed = merged;
+    }
+
+    fn is_completed(&self, index: usize) -> bool {
+        self.completed.iter().any(|&(start, end)| (start..end).contains(&index))
+    }
+
+    /// Renamed files still on disk under their random name.
+    fn left_renamed(&self) -> Vec<InterruptedFile> {
+        self.renamed
+            .iter()
+            .filter(|(_, on_disk)| Path::new(on_disk).exists())
+            .map(|(original, on_disk)| InterruptedFile {
+                path: original.clone(),
+                disposition: FileDisposition::LeftOnDisk { on_disk_path: on_disk.clone() },
+            })
+            .collect()
+    }
+
+    fn completed_count(&self) -> usize {
+        self.completed.iter().map(|(start, end)| end - start).sum()
+    }
+
+    fn remaining(&self) -> Vec<String> {
+        self.spec
+            .paths
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !self.is_completed(*index))
+            .map(|(_, path)| path.clone())
+            .collect()
+    }
+}
+
+/// A journal left behind by a batch that did not finish, as shown to the user.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InterruptedOperation {
+    pub operation_id: String,
+    pub started_at: u64,
+    pub updated_at: u64,
+    pub algorithm: WipeAlgorithm,
+    pub passes: u32,
+    pub total: usize,
+    pub completed: usize,
+    /// Roots not recorded as wiped; a root finished in the last moments before the crash may be
+    /// among them and is simply gone by the time the batch resumes.
+    pub remaining: Vec<String>,
+    /// Files the crash left under the random name they were given to be deleted.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub left_renamed: Vec<InterruptedFile>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+/// Overwrite and delete a journal; a plain delete if that fails, since the paths must go either way.
+fn destroy(path: &Path) {
+    if secure_wipe_file(path, 1, &WipeAlgorithm::NistClear, &CancellationToken::new(), |_: WipeProgress| {}).is_err() {
+        if let Err(e) = fs::remove_file(path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log_event("journal_remove_failed", json!({"path": path.to_string_lossy(), "message": e.to_string()}));
+            }
+        }
+    }
+}
+
+/// Where journals live. Without an app data directory batches simply run unjournaled.
+pub struct JournalStore {
+    dir: Option<PathBuf>,
+    flush_interval: Duration,
+    /// Checkpoints of files batches that ended in this session left part-way, for a retry.
+    retained: Mutex<BTreeMap<String, FileCheckpoint>>,
+}
+
+impl JournalStore {
+    pub fn new(dir: PathBuf) -> Self {
+        JournalStore {
+            dir: Some(dir),
+            flush_interval: FLUSH_INTERVAL,
+            retained: Mutex::default(),
+        }
+    }
+
+    pub fn unavailable() -> Self {
+        JournalStore {
+            dir: None,
+            flush_interval: FLUSH_INTERVAL,
+            retained: Mutex::default(),
+        }
+    }
+
+    fn path_for(&self, operation_id: &str) -> Result<PathBuf, BitBurnError> {
+        let dir = self.dir.as_ref().ok_or_else(|| BitBurnError::internal("Operation journal directory is unavailable"))?;
+        // Ids come back from the frontend; anything but a registry id must not name a file.
+        if operation_id.is_empty() || !operation_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(BitBurnError::OperationNotFound { operation_id: operation_id.to_string() });
+        }
+        Ok(dir.join(format!("{}.{}", operation_id, JOURNAL_EXTENSION)))
+    }
+
+    /// Start journaling a batch, carrying over `checkpoints` from the run it resumes. Returns
+    /// `None`, after logging why, if the journal cannot be written.
+    pub fn begin(
+        &self,
+        operation_id: &str,
+        spec: BatchSpec,
+        checkpoints: BTreeMap<String, FileCheckpoint>,
+    ) -> Option<OperationJournal> {
+        let path = self.path_for(operation_id).ok()?;
+        let started_at = now_ms();
+        let mut writer = JournalWriter {
+            path,
+            file: JournalFile {
+                version: JOURNAL_VERSION,
+                operation_id: operation_id.to_string(),
+                started_at,
+                updated_at: started_at,
+                spec,
+                completed: Vec::new(),
+                checkpoints,
+                renamed: BTreeMap::new(),
+            },
+            flush_interval: self.flush_interval,
+            last_flush: Instant::now(),
+        };
+        if let Err(e) = writer.flush() {
+            log_event("journal_unavailable", json!({"operation_id": operation_id, "message": e.to_string()}));
+            return None;
+        }
+        Some(OperationJournal {
+            writer: Arc::new(Mutex::new(writer)),
+        })
+    }
+
+    fn load(&self, operation_id: &str) -> Result<JournalFile, BitBurnError> {
+        let path = self.path_for(operation_id)?;
+        let not_found = || BitBurnError::OperationNotFound { operation_id: operation_id.to_string() };
+        let text = fs::read_to_string(&path).map_err(|_| not_found())?;
+        serde_json::from_str(&text).map_err(|_| not_found())
+    }
+
+    /// Journals of batches that are not running, oldest first. `active` are the ids of batches
+    /// running in this process, whose journals are still being written.
+    pub fn interrupted(&self, active: &[String]) -> Vec<InterruptedOperation> {
+        let Some(entries) = self.dir.as_ref().and_then(|dir| fs::read_dir(dir).ok()) else {
+            return Vec::new();
+        };
+        let mut found: Vec<InterruptedOperation> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == JOURNAL_EXTENSION))
+            .filter_map(|path| serde_json::from_str::<JournalFile>(&fs::read_to_string(&path).ok()?).ok())
+            .filter(|journal| !active.contains(&journal.operation_id))
+            .map(|journal| InterruptedOperation {
+                operation_id: journal.operation_id.clone(),
+                started_at: journal.started_at,
+                updated_at: journal.updated_at,
+                algorithm: journal.spec.algorithm.clone(),
+                passes: journal.spec.passes,
+                total: journal.spec.paths.len(),
+                completed: journal.completed_count(),
+                remaining: journal.remaining(),
+                left_renamed: journal.left_renamed(),
+            })
+            .collect();
+        found.sort_by_key(|operation| operation.started_at);
+        found
+    }
+
+    /// Keep the checkpoints a finished batch left, for a retry of its files.
+    pub fn retain(&self, checkpoints: BTreeMap<String, FileCheckpoint>) {
+        self.lock_retained().extend(checkpoints);
+    }
+
+    /// Hand over the retained checkpoints of `paths`.
+    pub fn take_retained(&self, paths: &[String]) -> BTreeMap<String, FileCheckpoint> {
+        let mut retained = self.lock_retained();
+        paths.iter().filter_map(|path| Some((path.clone(), retained.remove(path)?))).collect()
+    }
+
+    fn lock_retained(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, FileCheckpoint>> {
+        self.retained.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Securely delete a journal without resuming it.
+    pub fn discard(&self, operation_id: &str) -> Result<(), BitBurnError> {
+        let path = self.path_for(operation_id)?;
+        if !path.exists() {
+            return Err(BitBurnError::OperationNotFound { operation_id: operation_id.to_string() });
+        }
+        destroy(&path);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct JournalWriter {
+    path: PathBuf,
+    file: JournalFile,
+    flush_interval: Duration,
+    last_flush: Instant,
+}
+
+impl JournalWriter {
+    fn flush(&mut self) -> Result<(), BitBurnError> {
+        self.write(false)
+    }
+
+    /// Write the journal and wait until it is on disk.
+    fn sync(&mut self) -> Result<(), BitBurnError> {
+        self.write(true)
+    }
+
+    fn write(&mut self, durable: bool) -> Result<(), BitBurnError> {
+        self.file.updated_at = now_ms();
+        let contents = serde_json::to_vec(&self.file).map_err(|e| BitBurnError::internal(e.to_string()))?;
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| BitBurnError::io(&e, Some(dir)))?;
+        }
+        if durable {
+            scratch::write_durably_via_scratch(&self.path, &contents)?;
+        } else {
+            scratch::write_via_scratch(&self.path, &contents)?;
+        }
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+}
+
+/// The journal of one running batch. Clones share it.
+#[derive(Debug, Clone)]
+pub struct OperationJournal {
+    writer: Arc<Mutex<JournalWriter>>,
+}
+
+impl OperationJournal {
+    fn lock(&self) -> std::sync::MutexGuard<'_, JournalWriter> {
+        self.writer.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Record root `index` as wiped, writing the journal if it has not been written for a while.
+    pub fn completed(&self, index: usize) {
+        let mut writer = self.lock();
+        writer.file.mark_completed(index);
+        if writer.last_flush.elapsed() >= writer.flush_interval {
+            if let Err(e) = writer.flush() {
+                log_event("journal_write_failed", json!({"operation_id": writer.file.operation_id, "message": e.to_string()}));
+            }
+        }
+    }
+
+    pub fn checkpoint(&self, path: &str) -> Option<FileCheckpoint> {
+        self.lock().file.checkpoints.get(path).cloned()
+    }
+
+    /// Record how far `path` got and write the journal at once: a checkpoint only helps if it
+    /// survives the crash it is for.
+    pub fn record_checkpoint(&self, path: &str, checkpoint: FileCheckpoint) {
+        let mut writer = self.lock();
+        writer.file.checkpoints.insert(path.to_string(), checkpoint);
+        if let Err(e) = writer.flush() {
+            log_event("journal_write_failed", json!({"operation_id": writer.file.operation_id, "message": e.to_string()}));
+        }
+    }
+
+    /// Forget `path`'s checkpoint, once its passes are done or no longer fit it.
+    pub fn clear_checkpoint(&self, path: &str) {
+        self.lock().file.checkpoints.remove(path);
+    }
+
+    /// The batch ended without crashing, however it went: the journal is no longer needed.
+    /// Returns the checkpoints of files it left part-way.
+    pub fn finish(&self) -> BTreeMap<String, FileCheckpoint> {
+        let mut writer = self.lock();
+        destroy(&writer.path);
+        std::mem::take(&mut writer.file.checkpoints)
+    }
+}
+
+impl RenameLog for OperationJournal {
+    /// An unwritten record is taken back, so the journal never names a file that kept its name.
+    fn renaming(&self, original: &Path, on_disk: &Path) -> Result<(), BitBurnError> {
+        let mut writer = self.lock();
+        let original = original.to_string_lossy().to_string();
+        writer.file.renamed.insert(original.clone(), on_disk.to_string_lossy().to_string());
+        let written = writer.sync();
+        if written.is_err() {
+            writer.file.renamed.remove(&original);
+        }
+        written
+    }
+
+    fn settled(&self, original: &Path) {
+        self.lock().file.renamed.remove(original.to_string_lossy().as_ref());
+    }
+}
+
+/// Build the managed journal store rooted in the app data directory.
+pub fn init_journal_store<R: Runtime>(app: &AppHandle<R>) -> JournalStore {
+    match app.path().app_data_dir() {
+        Ok(dir) => JournalStore::new(dir.join(JOURNAL_DIR)),
+        Err(_) => {
+            log_event("journal_dir_unavailable", json!({"action": "batches run without a crash journal"}));
+            JournalStore::unavailable()
+        }
+    }
+}
+
+/// Batches a previous run of BitBurn left unfinished.
+#[tauri::command]
+pub async fn get_interrupted_operations(
+    journals: State<'_, JournalStore>,
+    registry: State<'_, OperationRegistry>,
+) -> Result<Vec<InterruptedOperation>, BitBurnError> {
+    let active: Vec<String> = registry.active().into_iter().map(|snapshot| snapshot.operation_id).collect();
+    Ok(journals.interrupted(&active))
+}
+
+/// Wipe the roots an interrupted batch had not finished, through the normal batch pipeline.
+/// Roots that no longer exist are dropped first; they were wiped just before the crash. The journal
+/// is only a file on disk, so the rest are sanitized as a selection is, and those now protected or
+/// BitBurn's own are reported as failures. What is left is confirmed through `consent_token` or a
+/// prompt shown now, before the journal is discarded.
+#[tauri::command]
+pub async fn resume_interrupted_operation<R: Runtime>(
+    window: tauri::Window<R>,
+    registry: State<'_, OperationRegistry>,
+    journals: State<'_, JournalStore>,
+    operation_id: String,
+    consent_token: Option<String>,
+) -> Result<WipeResult, BitBurnError> {
+    let mut journal = journals.load(&operation_id)?;
+    let remaining = journal.remaining();
+    let (remaining, gone): (Vec<String>, Vec<String>) = remaining.into_iter().partition(|path| Path::new(path).exists());
+    let (present, refused) = sanitize_roots(&remaining, &mut journal.spec);
+    let prompt = messages::CONFIRM_RESUME_WIPE.render(json!({
+        "count": present.len(),
+        "algorithm": format!("{:?}", journal.spec.algorithm),
+        "paths": consent::list_targets(&present),
+    }));
+    let consent =
+        consent::confirm_targets_async(window.app_handle().clone(), consent_token, prompt.text, present.clone()).await?;
+    log_event(
+        "journal_resume",
+        json!({"operation_id": operation_id, "remaining": present.len(), "already_gone": gone.len(), "refused": refused.len()}),
+    );
+    journals.discard(&operation_id)?;
+
+    let options = BatchOptions {
+        source: Some("resume".to_string()),
+        preserve_roots: journal.spec.preserve_roots,
+        symlink_policy: journal.spec.symlink_policy,
+        tasks: journal.spec.tasks,
+        checkpoints: journal.checkpoints,
+        carried_failures: refused,
+        consent: Some(consent),
+        ..Default::default()
+    };
+    start_file_wipe(
+        window.app_handle().clone(),
+        window.label().to_string(),
+        &registry,
+        present,
+        journal.spec.passes,
+        journal.spec.algorithm,
+        options,
+    )
+    .await
+}
+
+/// Sanitize the journal's remaining `roots` with the batch's link policy: the roots to wipe, and
+/// why each of the others was refused. Tasks follow their root to its sanitized form.
+fn sanitize_roots(roots: &[String], spec: &mut BatchSpec) -> (Vec<String>, Vec<BitBurnError>) {
+    let protected = ProtectedPaths::current();
+    let mut targets = Vec::new();
+    let mut refused = Vec::new();
+    for root in roots {
+        match protected::sanitize_stored_target(root, spec.symlink_policy, &protected) {
+            Ok(target) => {
+                for task in spec.tasks.iter_mut().filter(|task| &task.path == root) {
+                    task.path = target.clone();
+                }
+                targets.push(target);
+            }
+            Err(e) => {
+                log_event("journal_root_refused", json!({"code": e.code(), "message": e.to_string()}));
+                refused.push(e);
+            }
+        }
+    }
+    (targets, refused)
+}
+
+/// Securely delete an interrupted batch's journal without wiping anything.
+#[tauri::command]
+pub async fn discard_interrupted_operation(
+    journals: State<'_, JournalStore>,
+    operation_id: String,
+) -> Result<(), BitBurnError> {
+    journals.discard(&operation_id)?;
+    log_event("journal_discarded", json!({"operation_id": operation_id}));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run_wipe_batch;
+    use crate::test_support::{batch_context, cleanup_test_dir, create_test_dir};
+
+    fn store(dir: &Path) -> JournalStore {
+        JournalStore {
+            dir: Some(dir.join(JOURNAL_DIR)),
+            flush_interval: Duration::ZERO,
+            retained: Mutex::default(),
+        }
+    }
+
+    fn spec(paths: &[PathBuf]) -> BatchSpec {
+        BatchSpec {
+            paths: paths.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+            passes: 1,
+            algorithm: WipeAlgorithm::NistClear,
+            source: None,
+            preserve_roots: false,
+            symlink_policy: SymlinkPolicy::Reject,
+            tasks: Vec::new(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn journal_roots_are_sanitized_and_protected_ones_refused() {
+        let dir = create_test_dir().unwrap();
+        let file = dir.join("ledger.xlsx");
+        fs::write(&file, b"secret").unwrap();
+        let dotted = dir.join("..").join(dir.file_name().unwrap()).join("ledger.xlsx");
+        let mut batch = spec(&[dotted.clone(), PathBuf::from("/etc/passwd")]);
+        batch.tasks = vec![WipeTask {
+            path: dotted.to_string_lossy().to_string(),
+            algorithm: WipeAlgorithm::NistPurge,
+            passes: 3,
+        }];
+
+        let roots = batch.paths.clone();
+        let (targets, refused) = sanitize_roots(&roots, &mut batch);
+        let canonical = file.canonicalize().unwrap().to_string_lossy().to_string();
+        assert_eq!(targets, std::slice::from_ref(&canonical));
+        assert_eq!(refused, [BitBurnError::ProtectedPath { path: "/etc/passwd".to_string() }]);
+        assert_eq!(batch.tasks[0].path, canonical, "the task follows its root");
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn completed_indices_are_kept_as_merged_ranges() {
+        let mut journal = JournalFile {
+            version: JOURNAL_VERSION,
+            operation_id: "op-1".into(),
+            started_at: 0,
+            updated_at: 0,
+            spec: BatchSpec {
+                paths: (0..10).map(|i| i.to_string()).collect(),
+                passes: 1,
+                algorithm: WipeAlgorithm::NistClear,
+                source: None,
+                preserve_roots: false,
+                symlink_policy: SymlinkPolicy::Reject,
+                tasks: Vec::new(),
+            },
+            completed: Vec::new(),
+            checkpoints: BTreeMap::new(),
+            renamed: BTreeMap::new(),
+        };
+        for index in [0, 1, 2, 5, 4, 9, 2] {
+            journal.mark_completed(index);
+        }
+        assert_eq!(journal.completed, vec![(0, 3), (4, 6), (9, 10)]);
+        assert_eq!(journal.completed_count(), 6);
+        assert_eq!(journal.remaining(), vec!["3", "6", "7", "8"]);
+    }
+
+    #[test]
+    fn an_abandoned_journal_resumes_without_the_completed_entries() {
+        let dir = create_test_dir().unwrap();
+        let files: Vec<PathBuf> = (0..4).map(|i| dir.join(format!("file{}.txt", i))).collect();
+        for file in &files {
+            fs::write(file, b"secret").unwrap();
+        }
+        let journals = store(&dir);
+
+        // The batch records two roots as done, then the process dies without `finish`.
+        let journal = journals.begin("op-crashed-1", spec(&files), BTreeMap::new()).expect("journal written");
+        journal.completed(0);
+        journal.completed(2);
+        drop(journal);
+
+        let interrupted = journals.interrupted(&[]);
+        assert_eq!(interrupted.len(), 1);
+        assert_eq!((interrupted[0].total, interrupted[0].completed), (4, 2));
+        let remaining = interrupted[0].remaining.clone();
+        assert_eq!(remaining, vec![files[1].to_string_lossy().to_string(), files[3].to_string_lossy().to_string()]);
+        assert!(journals.interrupted(&["op-crashed-1".to_string()]).is_empty(), "a running batch is not interrupted");
+        let text = fs::read_to_string(journals.path_for("op-crashed-1").unwrap()).unwrap();
+        assert!(!text.contains("secret"), "journals never hold file contents");
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "resume test");
+        let result = run_wipe_batch(&ctx, remaining.into_iter().map(Ok), 2, 1, &WipeAlgorithm::NistClear, BatchOptions::default());
+
+        assert!(result.success, "{}", result.message.text);
+        assert!(!files[1].exists() && !files[3].exists());
+        // Files 0 and 2 stand in for roots wiped before the crash: resuming must not touch them.
+        assert_eq!(fs::read(&files[0]).unwrap(), b"secret");
+        assert_eq!(fs::read(&files[2]).unwrap(), b"secret");
+
+        journals.discard("op-crashed-1").unwrap();
+        assert!(journals.interrupted(&[]).is_empty());
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn a_file_renamed_before_the_crash_is_reported_under_its_random_name() {
+        let dir = create_test_dir().unwrap();
+        let (original, random, deleted) = (dir.join("payroll.xlsx"), dir.join("Xq3LrT9a"), dir.join("gone.txt"));
+        fs::write(&original, b"").unwrap();
+        let journals = store(&dir);
+        let journal = journals.begin("op-renamed-1", spec(std::slice::from_ref(&original)), BTreeMap::new()).unwrap();
+
+        journal.renaming(&original, &random).unwrap();
+        fs::rename(&original, &random).unwrap();
+        journal.renaming(&deleted, &dir.join("Zk81mQpw")).unwrap();
+        // The second file was deleted but the crash came before the journal heard.
+        let interrupted = journals.interrupted(&[]);
+        assert_eq!(
+            interrupted[0].left_renamed,
+            vec![InterruptedFile {
+                path: original.to_string_lossy().to_string(),
+                disposition: FileDisposition::LeftOnDisk { on_disk_path: random.to_string_lossy().to_string() },
+            }]
+        );
+
+        // Settling is written with the next flush.
+        journal.settled(&original);
+        journal.completed(0);
+        assert!(journals.interrupted(&[])[0].left_renamed.is_empty());
+        journal.finish();
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn a_finished_batch_leaves_no_journal() {
+        let dir = create_test_dir().unwrap();
+        let file = dir.join("a.txt");
+        fs::write(&file, b"a").unwrap();
+        let journals = store(&dir);
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "finish test");
+        let options = BatchOptions {
+            journal: journals.begin(ctx.operation.id(), spec(std::slice::from_ref(&file)), BTreeMap::new()),
+            ..Default::default()
+        };
+        assert_eq!(journals.interrupted(&[]).len(), 1);
+        let result = run_wipe_batch(&ctx, [Ok(file.to_string_lossy().to_string())], 1, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(result.success);
+        assert!(journals.interrupted(&[]).is_empty());
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn journal_ids_cannot_name_other_files() {
+        let journals = store(&std::env::temp_dir());
+        for id in ["../settings", "op/1", "", "op-1.json"] {
+            assert!(matches!(journals.discard(id), Err(BitBurnError::OperationNotFound { .. })), "{:?}", id);
+        }
+    }
+}
@@ -0,0 +1,528 @@
+use bitburn_core::{TreeScan, DEFAULT_CHUNK_FILES};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{Manager, Runtime, State};
+
+use crate::companions;
+use crate::error::BitBurnError;
+use crate::settings::SettingsStore;
+use crate::{cancelled_wipe_result, log_event, BatchContext, BatchOptions, WipeResult};
+
+/// In `Auto` mode, batches with at least this many files are scanned.
+pub const AUTO_SCAN_MIN_FILES: usize = 200;
+/// In `Auto` mode, batches with at least this many bytes are scanned.
+pub const AUTO_SCAN_MIN_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// A probe that has not answered in this long ends the scan; the rest of the batch is not checked.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_SCAN_WORKERS: usize = 8;
+
+/// How long `WaitAndRetry` waits for locked files to be released, and how often it looks.
+const RETRY_WAIT: Duration = Duration::from_secs(60);
+const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// When a file batch is checked for locked files before the first overwrite.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockScanMode {
+    /// Only batches of at least `AUTO_SCAN_MIN_FILES` files or `AUTO_SCAN_MIN_BYTES` bytes.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// What the user chose to do about locked files found by the pre-flight scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockPolicy {
+    /// Leave locked files untouched and wipe the rest.
+    SkipLocked,
+    /// Wait for the locked files to be released, then wipe everything.
+    WaitAndRetry,
+    /// Wipe nothing.
+    Abort,
+}
+
+/// Result of a pre-flight lock scan.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LockScan {
+    pub scanned: usize,
+    pub locked: Vec<String>,
+    /// False if a probe hung and the remaining files were not checked.
+    pub complete: bool,
+}
+
+/// Whether another process holds `path` open in a way that would make the wipe fail or be unsafe.
+/// Never writes to the file.
+#[cfg(windows)]
+pub fn is_locked(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+    // No sharing: fails with a sharing violation if any other handle is open.
+    match std::fs::OpenOptions::new().read(true).share_mode(0).open(path) {
+        Ok(_) => false,
+        Err(e) => matches!(e.raw_os_error(), Some(32) | Some(33)),
+    }
+}
+
+#[cfg(unix)]
+pub fn is_locked(path: &Path) -> bool {
+    use std::os::unix::io::AsRawFd;
+    // Unix has no mandatory sharing modes; an exclusive advisory lock is the closest signal.
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let fd = file.as_raw_fd();
+    if unsafe { libc::flock(fd, libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        unsafe { libc::flock(fd, libc::LOCK_UN) };
+        false
+    } else {
+        std::io::Error::last_os_error().raw_os_error() == Some(libc::EWOULDBLOCK)
+    }
+}
+
+#[cfg(not(any(windows, unix)))]
+pub fn is_locked(_path: &Path) -> bool {
+    false
+}
+
+/// Probe `files` in parallel. Returns `Cancelled` as soon as `cancelled` is set.
+pub fn scan(files: Vec<PathBuf>, cancelled: &AtomicBool) -> Result<LockScan, BitBurnError> {
+    scan_with(files, cancelled, PROBE_TIMEOUT, is_locked)
+}
+
+fn scan_with(
+    files: Vec<PathBuf>,
+    cancelled: &AtomicBool,
+    probe_timeout: Duration,
+    probe: fn(&Path) -> bool,
+) -> Result<LockScan, BitBurnError> {
+    let total = files.len();
+    let files = Arc::new(files);
+    let next = Arc::new(AtomicUsize::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+    let (sender, results) = mpsc::channel();
+    let workers = thread::available_parallelism().map_or(2, |n| n.get()).clamp(1, MAX_SCAN_WORKERS).min(total.max(1));
+    // Workers are detached: one stuck in an open() on a dead share must not hold up the batch.
+    for _ in 0..workers {
+        let (files, next, stop, sender) = (files.clone(), next.clone(), stop.clone(), sender.clone());
+        thread::spawn(move || loop {
+            let index = next.fetch_add(1, Ordering::SeqCst);
+            if stop.load(Ordering::SeqCst) || index >= files.len() {
+                return;
+            }
+            if sender.send((index, probe(&files[index]))).is_err() {
+                return;
+            }
+        });
+    }
+    drop(sender);
+
+    let mut scan = LockScan::default();
+    let mut locked = Vec::new();
+    let poll = probe_timeout.min(Duration::from_millis(100));
+    let mut last_answer = Instant::now();
+    while scan.scanned < total {
+        if cancelled.load(Ordering::SeqCst) {
+            stop.store(true, Ordering::SeqCst);
+            return Err(BitBurnError::Cancelled);
+        }
+        match results.recv_timeout(poll) {
+            Ok((index, is_locked)) => {
+                scan.scanned += 1;
+                last_answer = Instant::now();
+                if is_locked {
+                    locked.push(index);
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) if last_answer.elapsed() < probe_timeout => {}
+            Err(_) => break,
+        }
+    }
+    stop.store(true, Ordering::SeqCst);
+    locked.sort_unstable();
+    scan.locked = locked.into_iter().map(|i| files[i].to_string_lossy().to_string()).collect();
+    scan.complete = scan.scanned == total;
+    Ok(scan)
+}
+
+/// Re-probe `locked` until every file is released, `wait` runs out or the batch is cancelled.
+/// Returns the files still locked.
+fn wait_for_release(
+    mut locked: Vec<String>,
+    cancelled: &AtomicBool,
+    wait: Duration,
+    interval: Duration,
+    probe: fn(&Path) -> bool,
+) -> Result<Vec<String>, BitBurnError> {
+    let deadline = Instant::now() + wait;
+    while !locked.is_empty() && Instant::now() < deadline {
+        let pause_until = (Instant::now() + interval).min(deadline);
+        while Instant::now() < pause_until {
+            if cancelled.load(Ordering::SeqCst) {
+                return Err(BitBurnError::Cancelled);
+            }
+            thread::sleep(Duration::from_millis(50).min(interval));
+        }
+        let paths = locked.iter().map(PathBuf::from).collect();
+        locked = scan_with(paths, cancelled, PROBE_TIMEOUT, probe)?.locked;
+    }
+    Ok(locked)
+}
+
+/// What the lock scan checks: the files it holds, and folders of more than one chunk, which are
+/// walked again a chunk at a time.
+#[derive(Debug, Default)]
+struct ScanTargets {
+    files: Vec<PathBuf>,
+    large_roots: Vec<PathBuf>,
+    /// Files and bytes of everything above, as the batch will meet them.
+    count: usize,
+    bytes: u64,
+}
+
+/// Files under `roots`, with the companions of selected files when `companions` is set. Walks of
+/// folders that fit in one chunk of `chunk_files` are added to `scans` for the wipe to reuse;
+/// larger ones are only counted.
+fn collect_files(
+    roots: &[String],
+    companions: bool,
+    chunk_files: usize,
+    scans: &mut HashMap<PathBuf, TreeScan>,
+) -> ScanTargets {
+    let mut targets = ScanTargets::default();
+    let none = HashSet::new();
+    for root in roots {
+        let root = Path::new(root);
+        if root.is_dir() {
+            let mut chunks = TreeScan::chunks(root, &none, chunk_files);
+            let first = chunks.next().unwrap_or_default();
+            if chunks.finished() {
+                targets.count += first.file_count();
+                targets.bytes += first.bytes;
+                targets.files.extend(first.files().cloned());
+                scans.insert(root.to_path_buf(), first);
+            } else {
+                let tree = TreeScan::count_excluding(root, &none);
+                targets.count += tree.files;
+                targets.bytes += tree.bytes;
+                targets.large_roots.push(root.to_path_buf());
+            }
+        } else if let Ok(metadata) = fs::metadata(root) {
+            let mut add = |file: PathBuf, bytes: u64| {
+                targets.count += 1;
+                targets.bytes += bytes;
+                targets.files.push(file);
+            };
+            add(root.to_path_buf(), metadata.len());
+            if companions {
+                for companion in companions::find(root) {
+                    let bytes = fs::metadata(&companion).map_or(0, |metadata| metadata.len());
+                    add(companion, bytes);
+                }
+            }
+        }
+    }
+    targets
+}
+
+/// Probe `targets` a chunk at a time, stopping at the first chunk a hung probe left incomplete.
+fn scan_targets(targets: ScanTargets, chunk_files: usize, cancelled: &AtomicBool) -> Result<LockScan, BitBurnError> {
+    let mut found = scan(targets.files, cancelled)?;
+    let none = HashSet::new();
+    let chunks = targets.large_roots.iter().flat_map(|root| TreeScan::chunks(root, &none, chunk_files));
+    for chunk in chunks {
+        if !found.complete {
+            break;
+        }
+        let part = scan(chunk.files().cloned().collect(), cancelled)?;
+        found.scanned += part.scanned;
+        found.locked.extend(part.locked);
+        found.complete = part.complete;
+    }
+    Ok(found)
+}
+
+fn should_scan(mode: LockScanMode, policy: Option<LockPolicy>, files: usize, bytes: u64) -> bool {
+    match mode {
+        // A policy means the user already saw locked files; check again since they may have changed.
+        _ if policy.is_some() => true,
+        LockScanMode::Always => true,
+        LockScanMode::Never => false,
+        LockScanMode::Auto => files >= AUTO_SCAN_MIN_FILES || bytes >= AUTO_SCAN_MIN_BYTES,
+    }
+}
+
+/// Check the batch for locked files before anything is overwritten, then apply `options.lock_policy`.
+/// Returns a result to end the batch with, or `None` to go ahead; skipped files are added to
+/// `options.locked_skips`. Without a policy, locked files end the batch with `FilesLocked` so the
+/// frontend can ask the user and start again with one.
+pub(crate) fn preflight<R: Runtime>(ctx: &BatchContext<R>, roots: &[String], options: &mut BatchOptions) -> Option<WipeResult> {
+    let settings = ctx.app_handle.try_state::<SettingsStore>().map(|settings| settings.get());
+    let mode = settings.as_ref().map(|settings| settings.lock_scan).unwrap_or_default();
+    // Not even the file list is gathered when scanning is off.
+    if mode == LockScanMode::Never && options.lock_policy.is_none() {
+        return None;
+    }
+    let companions = options
+        .companion_files
+        .unwrap_or_else(|| settings.as_ref().is_some_and(|settings| settings.companion_files));
+    let chunk_files = options
+        .chunk_files
+        .unwrap_or_else(|| settings.as_ref().map_or(DEFAULT_CHUNK_FILES, |settings| settings.chunk_files()));
+    let targets = collect_files(roots, companions, chunk_files, &mut options.tree_scans);
+    if !should_scan(mode, options.lock_policy, targets.count, targets.bytes) {
+        return None;
+    }
+
+    let cancelled = ctx.operation.cancel_flag();
+    ctx.operation.update("Checking for locked files", 0.0);
+    let started = Instant::now();
+    let scan = match scan_targets(targets, chunk_files, &cancelled) {
+        Ok(scan) => scan,
+        Err(_) => return Some(cancelled_wipe_result()),
+    };
+    log_event(
+        "lock_scan",
+        json!({
+            "operation_id": ctx.operation.id(),
+            "scanned": scan.scanned,
+            "locked": scan.locked.len(),
+            "complete": scan.complete,
+            "elapsed_ms": started.elapsed().as_millis() as u64,
+        }),
+    );
+    if scan.locked.is_empty() {
+        return None;
+    }
+
+    let still_locked = match options.lock_policy {
+        Some(LockPolicy::SkipLocked) => {
+            options.locked_skips.extend(scan.locked.iter().map(PathBuf::from));
+            return None;
+        }
+        Some(LockPolicy::WaitAndRetry) => {
+            match wait_for_release(scan.locked, &cancelled, RETRY_WAIT, RETRY_INTERVAL, is_locked) {
+                Ok(still_locked) if still_locked.is_empty() => return None,
+                Ok(still_locked) => still_locked,
+                Err(_) => return Some(cancelled_wipe_result()),
+            }
+        }
+        Some(LockPolicy::Abort) | None => scan.locked,
+    };
+    Some(WipeResult::failure(BitBurnError::FilesLocked { paths: still_locked }))
+}
+
+/// Choose whether files are held exclusively while they are wiped. With it off, other programs
+/// can still read a file, or open it again, until its wipe finishes.
+#[tauri::command]
+pub async fn set_exclusive_lock(settings: State<'_, SettingsStore>, enabled: bool) -> Result<bool, BitBurnError> {
+    let updated = settings.update(|s| s.exclusive_lock = Some(enabled))?;
+    log_event("exclusive_lock_configured", json!({"enabled": updated.exclusive_lock()}));
+    Ok(updated.exclusive_lock())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::OperationRegistry;
+    use crate::settings::Settings;
+    use crate::test_support::{batch_context, cleanup_test_dir, create_test_dir};
+    use crate::{run_wipe_batch, WipeAlgorithm};
+    use std::fs;
+
+    /// Hold `path` the way another program would, so `is_locked` sees it.
+    fn hold_lock(path: &Path) -> fs::File {
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::OpenOptionsExt;
+            fs::OpenOptions::new().read(true).write(true).share_mode(0).open(path).unwrap()
+        }
+        #[cfg(not(windows))]
+        {
+            use std::os::unix::io::AsRawFd;
+            let file = fs::File::open(path).unwrap();
+            assert_eq!(unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) }, 0);
+            file
+        }
+    }
+
+    #[test]
+    fn a_file_locked_by_this_process_shows_up_in_the_scan() {
+        let dir = create_test_dir().unwrap();
+        let files: Vec<PathBuf> = (0..20).map(|i| dir.join(format!("file{}.txt", i))).collect();
+        for file in &files {
+            fs::write(file, b"data").unwrap();
+        }
+        let held = hold_lock(&files[7]);
+
+        let scan = scan(files.clone(), &AtomicBool::new(false)).unwrap();
+        assert_eq!(scan.scanned, 20);
+        assert!(scan.complete);
+        assert_eq!(scan.locked, vec![files[7].to_string_lossy().to_string()]);
+
+        drop(held);
+        assert!(super::scan(files, &AtomicBool::new(false)).unwrap().locked.is_empty());
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn folders_larger_than_a_chunk_are_scanned_a_chunk_at_a_time_and_not_cached() {
+        let small = create_test_dir().unwrap();
+        let large = create_test_dir().unwrap();
+        for i in 0..3 {
+            fs::write(small.join(format!("file{}.txt", i)), b"data").unwrap();
+        }
+        let files: Vec<PathBuf> = (0..25).map(|i| large.join(format!("file{:02}.txt", i))).collect();
+        for file in &files {
+            fs::write(file, b"data").unwrap();
+        }
+        let held = hold_lock(&files[23]);
+        let roots = [small.to_string_lossy().to_string(), large.to_string_lossy().to_string()];
+
+        let mut scans = HashMap::new();
+        let targets = collect_files(&roots, false, 10, &mut scans);
+        assert_eq!((targets.count, targets.bytes, targets.files.len()), (28, 112, 3));
+        assert_eq!(targets.large_roots, vec![large.clone()]);
+        assert!(scans.contains_key(&small) && !scans.contains_key(&large));
+
+        let scan = scan_targets(targets, 10, &AtomicBool::new(false)).unwrap();
+        assert_eq!(scan.scanned, 28);
+        assert!(scan.complete);
+        assert_eq!(scan.locked, vec![files[23].to_string_lossy().to_string()]);
+
+        drop(held);
+        cleanup_test_dir(small);
+        cleanup_test_dir(large);
+    }
+
+    #[test]
+    fn a_hung_probe_ends_the_scan_incomplete_and_cancel_stops_it() {
+        fn hangs_on_slow(path: &Path) -> bool {
+            if path.ends_with("slow") {
+                thread::sleep(Duration::from_secs(5));
+            }
+            false
+        }
+        let files = vec![PathBuf::from("slow")];
+        let started = Instant::now();
+        let scan = scan_with(files.clone(), &AtomicBool::new(false), Duration::from_millis(200), hangs_on_slow).unwrap();
+        assert!(!scan.complete);
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let cancelled = AtomicBool::new(true);
+        let result = scan_with(files, &cancelled, Duration::from_secs(10), hangs_on_slow);
+        assert_eq!(result, Err(BitBurnError::Cancelled));
+    }
+
+    #[test]
+    fn waiting_returns_once_files_are_released() {
+        fn released(_: &Path) -> bool {
+            false
+        }
+        fn never_released(_: &Path) -> bool {
+            true
+        }
+        let locked = vec!["a.pst".to_string()];
+        let cancelled = AtomicBool::new(false);
+        let short = Duration::from_millis(50);
+        assert_eq!(wait_for_release(locked.clone(), &cancelled, short * 4, short, released), Ok(Vec::new()));
+        assert_eq!(wait_for_release(locked.clone(), &cancelled, short * 4, short, never_released), Ok(locked));
+    }
+
+    #[test]
+    fn skip_locked_wipes_the_rest_and_leaves_the_locked_file_untouched() {
+        let dir = create_test_dir().unwrap();
+        let mailbox = dir.join("outlook.pst");
+        let notes = dir.join("notes.txt");
+        fs::write(&mailbox, b"mailbox contents").unwrap();
+        fs::write(&notes, b"notes").unwrap();
+        let held = hold_lock(&mailbox);
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "lock test");
+        let roots = vec![dir.to_string_lossy().to_string()];
+        let mut options = BatchOptions {
+            lock_policy: Some(LockPolicy::SkipLocked),
+            ..Default::default()
+        };
+
+        assert!(preflight(&ctx, &roots, &mut options).is_none());
+        let result = run_wipe_batch(&ctx, roots.clone().into_iter().map(Ok), 1, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(result.success, "{}", result.message.text);
+        assert!(!notes.exists());
+        assert_eq!(fs::read(&mailbox).unwrap(), b"mailbox contents");
+        let report = result.report.unwrap();
+        assert_eq!(
+            report.skipped,
+            vec![BitBurnError::FileInUse { path: mailbox.to_string_lossy().to_string(), pids: Vec::new() }]
+        );
+        drop(held);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn without_a_policy_locked_files_stop_the_batch_before_any_overwrite() {
+        let dir = create_test_dir().unwrap();
+        let mailbox = dir.join("outlook.pst");
+        fs::write(&mailbox, b"mailbox contents").unwrap();
+        let held = hold_lock(&mailbox);
+
+        let app = tauri::test::mock_app();
+        app.manage(SettingsStore::in_memory(Settings {
+            lock_scan: LockScanMode::Always,
+            ..Default::default()
+        }));
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "lock test");
+        let roots = vec![mailbox.to_string_lossy().to_string()];
+
+        let result = preflight(&ctx, &roots, &mut BatchOptions::default()).expect("the batch must stop");
+        assert_eq!(result.error, Some(BitBurnError::FilesLocked { paths: roots.clone() }));
+        assert_eq!(fs::read(&mailbox).unwrap(), b"mailbox contents");
+        drop(held);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn never_mode_does_not_even_walk_the_selection() {
+        let dir = create_test_dir().unwrap();
+        let mailbox = dir.join("outlook.pst");
+        fs::write(&mailbox, b"mailbox contents").unwrap();
+        let held = hold_lock(&mailbox);
+
+        let app = tauri::test::mock_app();
+        app.manage(SettingsStore::in_memory(Settings {
+            lock_scan: LockScanMode::Never,
+            ..Default::default()
+        }));
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "lock test");
+        let roots = vec![dir.to_string_lossy().to_string()];
+        let mut options = BatchOptions::default();
+
+        assert!(preflight(&ctx, &roots, &mut options).is_none());
+        assert!(options.tree_scans.is_empty(), "the folder was walked");
+        drop(held);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn auto_mode_scans_only_large_batches() {
+        assert!(!should_scan(LockScanMode::Auto, None, 10, 1024));
+        assert!(should_scan(LockScanMode::Auto, None, AUTO_SCAN_MIN_FILES, 0));
+        assert!(should_scan(LockScanMode::Auto, None, 1, AUTO_SCAN_MIN_BYTES));
+        assert!(!should_scan(LockScanMode::Never, None, 10_000, 0));
+        assert!(should_scan(LockScanMode::Never, Some(LockPolicy::SkipLocked), 1, 0));
+        assert!(should_scan(LockScanMode::Always, None, 1, 0));
+    }
+}
@@ -1,126 +1,216 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::fs::{self, OpenOptions};
-use std::io::{self, Seek, SeekFrom, Write};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
-use tauri::{Emitter, Listener, Manager, Runtime};
+use tauri::{Emitter, Manager, Runtime};
 use tauri::async_runtime::spawn_blocking;
-use walkdir::WalkDir;
 use std::fmt;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use sysinfo::{DiskExt, System, SystemExt};
+mod audit_log;
+mod calibration;
+mod cancelled_fill;
+mod companions;
+mod completion_alert;
+mod capabilities;
+mod config;
+mod confirm_dialog;
+mod consent;
+mod context_policy;
+mod copy_on_write;
+mod decoy;
+mod diagnostics;
+mod drive_wear;
+mod ed25519;
+mod emergency_stop;
+mod error;
+mod file_checkpoint;
+mod flash_media;
+mod folder_free_space;
+mod free_space;
+mod free_space_estimate;
+mod free_space_sample;
+mod glob_targets;
+mod headless;
+mod journal;
+mod lock_scan;
+mod operation_log;
+mod operation_summary;
+mod ownership;
+mod operations;
+mod outcomes;
+mod parent_dirs;
+mod path_expansion;
+mod performance;
+mod manifest;
+mod memory_budget;
+mod messages;
+mod named_streams;
 mod platform;
+mod policy;
+mod progress_coalescer;
+mod presets;
+mod protected;
+mod progress_window;
+mod range_wipe;
+mod read_only;
+mod recommend;
+mod remote_tier;
+mod report;
+mod retention;
+mod retry;
+mod scheduler;
+mod scratch;
+mod search_index;
+mod selection;
+mod self_protection;
+mod self_test;
+mod settings;
+mod signing;
+mod stall_watch;
+mod status_file;
+mod symlinks;
+#[cfg(test)]
+mod test_support;
+mod theme;
 mod ui;
+mod uninstall;
+mod verification;
+mod volume_history;
+mod volume_lost;
+mod volume_root;
+mod webhook;
+mod wipe_finish;
+mod wipe_tasks;
+
+use cancelled_fill::{CancelledFill, CancelledFills, PendingFill};
+use consent::{Consent, ConsentOrigin, ConsentStore, WipeConfirmation};
+use diagnostics::OperationDiagnostics;
+use error::BitBurnError;
+use file_checkpoint::{CheckpointPolicy, FileCheckpoint, FileCheckpointer};
+use free_space::{FreeSpace, FreeSpaceCoverage};
+use lock_scan::LockPolicy;
+use journal::{BatchSpec, JournalStore};
+use drive_wear::WearStore;
+use free_space_estimate::{EstimateCheck, FreeSpaceEstimate, FreeSpaceEstimates};
+use free_space_sample::FreeSpaceSamples;
+use messages::Message;
+use outcomes::OutcomeSpill;
+use parent_dirs::ParentDirectories;
+use report::{FileWipeOutcome, FileWipeStatus, OperationTimings, PartialWipe, PreservedRoot, RootGrouping, WipeReport};
+use wipe_finish::FinishOptions;
+use wipe_tasks::WipeTask;
+use settings::SettingsStore;
+use stall_watch::StallPolicy;
+use symlinks::SelectedLink;
+use volume_lost::LostVolumes;
+use volume_root::VolumeRoots;
+use progress_coalescer::ProgressCoalescer;
+use operations::{
+    batch_percentage, get_active_operations, get_operation, summarize_targets, OperationGuard,
+    OperationKind, OperationRegistry,
+};
 
 use platform::context_menu::{
     get_context_menu_status,
     handle_context_invocation,
     process_cli_side_effects,
     register_context_menu,
+    repair_context_menu,
     unregister_context_menu,
 };
+use bitburn_core::{
+    fill_free_space_with, finish_wipe, open_wipe_target_with, overwrite_target_with, CancellationToken, DirectoryError,
+    DirectoryOutcome, FileWipeProgressRecord, FillEnd, FillError, FillPlan, OverwriteOptions, RemovalCancelled,
+    SymlinkPolicy, TreeRemoval, TreeScan, Verification, WalkPosition, WipeTarget, DEFAULT_CHUNK_FILES,
+};
+pub use bitburn_core::{WipeAlgorithm, WipeError, WipeProgress};
+
+use platform::shadow_copies::{self, ShadowCopyAction, ShadowCopyReport};
 use platform::autostart::{get_autostart_status, register_autostart, unregister_autostart, AUTOSTART_FLAG};
 
-/// Errors that can occur while securely wiping files.
-#[derive(Debug)]
-pub enum WipeError {
-    PathNotFound,
-    Io(std::io::Error),
-    InvalidPasses,
+/// User-facing result payload returned by wipe commands.
+/// Carries a success flag and a catalog message (code, params and English text) for UI display,
+/// plus structured errors the frontend can branch on.
+#[derive(Serialize)]
+pub struct WipeResult {
+    success: bool,
+    #[serde(flatten)]
+    message: Message,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<BitBurnError>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    failures: Vec<FileFailure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    report: Option<WipeReport>,
 }
 
-impl fmt::Display for WipeError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            WipeError::PathNotFound => write!(f, "Path not found"),
-            WipeError::Io(err) => write!(f, "IO error: {}", err),
-            WipeError::InvalidPasses => write!(f, "Invalid number of passes"),
+impl WipeResult {
+    pub(crate) fn success(message: Message) -> Self {
+        WipeResult {
+            success: true,
+            message,
+            error: None,
+            failures: Vec::new(),
+            report: None,
         }
     }
-}
 
-impl std::error::Error for WipeError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            WipeError::Io(err) => Some(err),
-            _ => None,
+    pub(crate) fn failure(error: BitBurnError) -> Self {
+        let message = Message::from_error(&error);
+        WipeResult::failure_with_message(error, message)
+    }
+
+    pub(crate) fn failure_with_message(error: BitBurnError, message: Message) -> Self {
+        WipeResult {
+            success: false,
+            message,
+            error: Some(error),
+            failures: Vec::new(),
+            report: None,
         }
     }
-}
 
-/// Supported wipe algorithms exposed to the frontend.
-/// Each variant maps to a specific pass count and pattern strategy enforced in the backend.
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub enum WipeAlgorithm {
-    NistClear,      // NIST 800-88 Clear: 1 pass zeros (replaces Basic)
-    NistPurge,      // NIST 800-88 Purge: 3 pass overwrite (replaces DOD)
-    Gutmann,        // 35 pass: Gutmann pattern (kept for legacy/specific needs)
-    Random,         // N passes of random data (replaces DOD_E and custom needs)
+    pub(crate) fn with_report(mut self, report: WipeReport) -> Self {
+        self.report = Some(report);
+        self
+    }
 }
 
-/// Progress payload emitted to the UI during wipe operations.
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct WipeProgress {
-    current_pass: u32,
-    total_passes: u32,
-    bytes_processed: u64,
-    total_bytes: u64,
-    current_algorithm: String,
-    current_pattern: String,
-    percentage: f32,
-    estimated_total_bytes: Option<u64>,
+/// A file or folder a batch could not wipe: the structured error plus its catalog message.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileFailure {
+    error: BitBurnError,
+    #[serde(flatten)]
+    message: Message,
 }
 
-impl WipeProgress {
-    fn new(total_passes: u32, total_bytes: u64, current_algorithm: &str) -> Self {
-        WipeProgress {
-            current_pass: 1,
-            total_passes,
-            bytes_processed: 0,
-            total_bytes,
-            current_algorithm: current_algorithm.to_string(),
-            current_pattern: String::new(),
-            percentage: 0.0,
-            estimated_total_bytes: None,
-        }
-    }
-
-    fn update(&mut self, bytes_processed: u64, pattern: &str) {
-        self.bytes_processed = bytes_processed;
-        self.current_pattern = pattern.to_string();
-        if let Some(est_total) = self.estimated_total_bytes {
-            self.percentage = (bytes_processed as f32 / est_total as f32) * 100.0;
-        } else {
-            self.percentage = (bytes_processed as f32 / self.total_bytes as f32) * 100.0;
-        }
+impl FileFailure {
+    fn new(error: BitBurnError, message: Message) -> Self {
+        FileFailure { error, message }
     }
 }
 
-/// User-facing result payload returned by wipe commands.
-/// Carries a success flag and human-readable status message for UI display.
-#[derive(Serialize)]
-pub struct WipeResult {
-    success: bool,
-    message: String,
+impl From<BitBurnError> for FileFailure {
+    fn from(error: BitBurnError) -> Self {
+        let message = Message::from_error(&error);
+        FileFailure { error, message }
+    }
 }
 
 fn cancelled_wipe_result() -> WipeResult {
-    WipeResult {
-        success: false,
-        message: "Operation cancelled by user".to_string(),
-    }
+    WipeResult::failure(BitBurnError::Cancelled)
 }
 
-fn free_space_error_result(message: impl Into<String>) -> WipeResult {
-    WipeResult {
-        success: false,
-        message: message.into(),
-    }
+fn free_space_error_result(error: BitBurnError, message: Message) -> WipeResult {
+    WipeResult::failure_with_message(error, message)
 }
 
 /// Context menu registration status returned to the frontend.
@@ -128,19 +218,40 @@ fn free_space_error_result(message: impl Into<String>) -> WipeResult {
 #[derive(Serialize)]
 pub struct ContextMenuStatus {
     enabled: bool,
-    message: String,
+    /// Installed, but for a different (moved or deleted) copy of BitBurn.
+    stale: bool,
+    #[serde(flatten)]
+    message: Message,
+    /// Each Explorer verb as registered; empty where the menu is not a set of registry verbs.
+    verbs: Vec<platform::context_menu::VerbStatus>,
 }
 
 /// Autostart registration status returned to the frontend.
 #[derive(Serialize)]
 pub struct AutostartStatus {
     enabled: bool,
-    message: String,
+    #[serde(flatten)]
+    message: Message,
 }
 
-pub(crate) fn log_event(event: &str, fields: serde_json::Value) {
+/// Print a structured event line and queue it for the hash-chained audit log. Events raised
+/// inside an operation's log scope are tagged with its `operation_id` and also captured into
+/// that operation's log.
+pub(crate) fn log_event(event: &str, mut fields: serde_json::Value) {
+    if let Some(operation_id) = operation_log::current_operation_id() {
+        if let Some(map) = fields.as_object_mut() {
+            map.entry("operation_id").or_insert_with(|| json!(operation_id));
+        }
+        operation_log::capture(event, &fields);
+    }
+    audit_log::submit(event, &fields);
     if let Ok(serialized) = serde_json::to_string(&json!({ "event": event, "fields": fields })) {
-        println!("{}", serialized);
+        // A headless run's stdout is its progress stream.
+        if headless::is_active() {
+            eprintln!("{}", serialized);
+        } else {
+            println!("{}", serialized);
+        }
     }
 }
 
@@ -152,312 +263,47 @@ pub struct PlatformInfo {
 }
 
 
-fn secure_wipe_file<F>(path: &Path, passes: u32, algorithm: &WipeAlgorithm, mut progress_callback: F) -> Result<(), WipeError>
-where
-    F: FnMut(WipeProgress),
-{
-    let cancelled = Arc::new(AtomicBool::new(false));
-
-    let check_cancelled = || {
-        if cancelled.load(Ordering::SeqCst) {
-            return Err(WipeError::Io(std::io::Error::new(
-                std::io::ErrorKind::Interrupted,
-                "Operation cancelled by user"
-            )));
-        }
-        Ok(())
-    };
-
-    if path.is_symlink() {
-        return Err(WipeError::Io(std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            "Cannot wipe symbolic links"
-        )));
-    }
-
-    if !path.exists() {
-        return Err(WipeError::PathNotFound);
-    }
-
-    if passes == 0 {
-        return Err(WipeError::InvalidPasses);
-    }
-
-    // Try to open file with minimal permissions first to check access
-    match OpenOptions::new().write(true).open(path) {
-        Ok(_) => {},
-        Err(e) => {
-            if e.kind() == std::io::ErrorKind::PermissionDenied {
-                return Err(WipeError::Io(std::io::Error::new(
-                    std::io::ErrorKind::PermissionDenied,
-                    "Access denied. The file might be in use or require administrator privileges."
-                )));
-            }
-            return Err(WipeError::Io(e));
-        }
+/// Free space on the volume holding `path`, falling back to sysinfo where the platform query fails.
+fn volume_free_space(sys: &mut System, path: &Path) -> Result<FreeSpace, BitBurnError> {
+    if let Some(space) = free_space::query_free_space(path) {
+        return Ok(space);
     }
-
-    let mut file = OpenOptions::new()
-        .write(true)
-        .read(true)
-        .open(path)
-        .map_err(|e| {
-            if e.kind() == std::io::ErrorKind::PermissionDenied {
-                WipeError::Io(std::io::Error::new(
-                    std::io::ErrorKind::PermissionDenied,
-                    "Access denied. The file might be in use or require administrator privileges."
-                ))
-            } else {
-                WipeError::Io(e)
-            }
+    sys.refresh_disks_list();
+    let available = sys
+        .disks()
+        .iter()
+        .find(|disk| path.starts_with(disk.mount_point()))
+        .map(|disk| disk.available_space())
+        .ok_or_else(|| BitBurnError::DiskInfoUnavailable {
+            path: path.to_string_lossy().to_string(),
         })?;
+    Ok(FreeSpace {
+        caller_available: available,
+        total_free: available,
+    })
+}
 
-    let file_size = file.metadata().map_err(WipeError::Io)?.len();
-    let mut rng = rand::thread_rng();
-    let mut progress = WipeProgress::new(
-        passes,
-        file_size,
-        match algorithm {
-            WipeAlgorithm::NistClear => "NIST 800-88 Clear",
-            WipeAlgorithm::NistPurge => "NIST 800-88 Purge",
-            WipeAlgorithm::Gutmann => "Gutmann",
-            WipeAlgorithm::Random => "Random",
-        }
-    );
-
-    // Increase buffer size to 1MB for better performance and smooth updates
-    const BUFFER_SIZE: u64 = 1024 * 1024; // 1MB
-    let mut last_progress_update = std::time::Instant::now();
-    let progress_update_interval = std::time::Duration::from_millis(16); // ~60 fps
-
-    match algorithm {
-        WipeAlgorithm::NistClear => {
-            // NIST 800-88 Clear: Single pass with zeros
-            progress.update(0, "NIST 800-88 Clear - Writing zeros");
-            progress_callback(progress.clone());
-
-            let buffer = vec![0u8; BUFFER_SIZE as usize];
-            for chunk_start in (0..file_size).step_by(BUFFER_SIZE as usize) {
-                check_cancelled()?;
-                let chunk_size = std::cmp::min(BUFFER_SIZE, file_size - chunk_start);
-                file.write_all(&buffer[..chunk_size as usize]).map_err(WipeError::Io)?;
-
-                // Update progress at most every 16ms for smooth animation
-                if last_progress_update.elapsed() >= progress_update_interval {
-                    progress.update(
-                        chunk_start + chunk_size,
-                        &format!("NIST 800-88 Clear - Writing zeros ({:.2} MB / {:.2} MB)",
-                            (chunk_start + chunk_size) as f64 / 1024.0 / 1024.0,
-                            file_size as f64 / 1024.0 / 1024.0
-                        )
-                    );
-                    progress_callback(progress.clone());
-                    last_progress_update = std::time::Instant::now();
-                }
-            }
-            file.sync_all().map_err(WipeError::Io)?;
-            
-            // Final cleanup
-            check_cancelled()?;
-            progress.update(file_size, "Finalizing NIST 800-88 Clear wipe");
-            progress_callback(progress);
-        },
-        WipeAlgorithm::NistPurge => {
-            // NIST 800-88 Purge: Three-pass overwrite
-            let patterns = [
-                (0x00, false, "zeros"),
-                (0xFF, false, "ones"),
-                (0x00, true, "random data")
-            ];
-
-            for (pass, &(pattern, is_random, pattern_type)) in patterns.iter().enumerate() {
-                check_cancelled()?;
-                progress.current_pass = (pass + 1) as u32;
-                let desc = format!("NIST 800-88 Purge - Writing {} (Pass {}/3)", pattern_type, pass + 1);
-                progress.update(0, &desc);
-                progress_callback(progress.clone());
-
-                file.seek(SeekFrom::Start(0)).map_err(WipeError::Io)?;
-                let mut buffer = vec![pattern; BUFFER_SIZE as usize];
-
-                for chunk_start in (0..file_size).step_by(BUFFER_SIZE as usize) {
-                    check_cancelled()?;
-                    let chunk_size = std::cmp::min(BUFFER_SIZE, file_size - chunk_start);
-                    if is_random {
-                        rng.fill_bytes(&mut buffer[..chunk_size as usize]);
-                    }
-                    file.write_all(&buffer[..chunk_size as usize]).map_err(WipeError::Io)?;
-
-                    // Update progress at most every 16ms for smooth animation
-                    if last_progress_update.elapsed() >= progress_update_interval {
-                        progress.update(
-                            chunk_start + chunk_size,
-                            &format!("NIST 800-88 Purge - Writing {} (Pass {}/3) - {:.2} MB / {:.2} MB",
-                                pattern_type,
-                                pass + 1,
-                                (chunk_start + chunk_size) as f64 / 1024.0 / 1024.0,
-                                file_size as f64 / 1024.0 / 1024.0
-                            )
-                        );
-                        progress_callback(progress.clone());
-                        last_progress_update = std::time::Instant::now();
-                    }
-                }
-                file.sync_all().map_err(WipeError::Io)?;
-            }
-            
-            // Final cleanup
-            check_cancelled()?;
-            progress.update(file_size, "Finalizing NIST 800-88 Purge wipe");
-            progress_callback(progress);
-        },
-        WipeAlgorithm::Gutmann => {
-            // Gutmann 35-pass pattern
-            // Reference: https://en.wikipedia.org/wiki/Gutmann_method
-            let patterns: &[(Vec<u8>, bool, &str)] = &[
-                // Passes 1-4: Random
-                (vec![0x00], true, "Random data (Pass 1/35)"),
-                (vec![0x00], true, "Random data (Pass 2/35)"),
-                (vec![0x00], true, "Random data (Pass 3/35)"),
-                (vec![0x00], true, "Random data (Pass 4/35)"),
-                
-                // Passes 5-31: Fixed patterns
-                (vec![0x55, 0xAA, 0x55, 0xAA], false, "Pattern 5/35: 0x55 0xAA"),
-                (vec![0xAA, 0x55, 0xAA, 0x55], false, "Pattern 6/35: 0xAA 0x55"),
-                (vec![0x92, 0x49, 0x24], false, "Pattern 7/35: 0x92 0x49 0x24"),
-                (vec![0x49, 0x24, 0x92], false, "Pattern 8/35: 0x49 0x24 0x92"),
-                (vec![0x24, 0x92, 0x49], false, "Pattern 9/35: 0x24 0x92 0x49"),
-                (vec![0x00], false, "Pattern 10/35: 0x00"),
-                (vec![0x11], false, "Pattern 11/35: 0x11"),
-                (vec![0x22], false, "Pattern 12/35: 0x22"),
-                (vec![0x33], false, "Pattern 13/35: 0x33"),
-                (vec![0x44], false, "Pattern 14/35: 0x44"),
-                (vec![0x55], false, "Pattern 15/35: 0x55"),
-                (vec![0x66], false, "Pattern 16/35: 0x66"),
-                (vec![0x77], false, "Pattern 17/35: 0x77"),
-                (vec![0x88], false, "Pattern 18/35: 0x88"),
-                (vec![0x99], false, "Pattern 19/35: 0x99"),
-                (vec![0xAA], false, "Pattern 20/35: 0xAA"),
-                (vec![0xBB], false, "Pattern 21/35: 0xBB"),
-                (vec![0xCC], false, "Pattern 22/35: 0xCC"),
-                (vec![0xDD], false, "Pattern 23/35: 0xDD"),
-                (vec![0xEE], false, "Pattern 24/35: 0xEE"),
-                (vec![0xFF], false, "Pattern 25/35: 0xFF"),
-                (vec![0x92, 0x49, 0x24], false, "Pattern 26/35: 0x92 0x49 0x24"),
-                (vec![0x49, 0x24, 0x92], false, "Pattern 27/35: 0x49 0x24 0x92"),
-                (vec![0x24, 0x92, 0x49], false, "Pattern 28/35: 0x24 0x92 0x49"),
-                (vec![0x6D, 0xB6, 0xDB], false, "Pattern 29/35: 0x6D 0xB6 0xDB"),
-                (vec![0xB6, 0xDB, 0x6D], false, "Pattern 30/35: 0xB6 0xDB 0x6D"),
-                (vec![0xDB, 0x6D, 0xB6], false, "Pattern 31/35: 0xDB 0x6D 0xB6"),
-                
-                // Passes 32-35: Random
-                (vec![0x00], true, "Random data (Pass 32/35)"),
-                (vec![0x00], true, "Random data (Pass 33/35)"),
-                (vec![0x00], true, "Random data (Pass 34/35)"),
-                (vec![0x00], true, "Random data (Pass 35/35)")
-            ];
-
-            for (pass, &(ref pattern, is_random, desc)) in patterns.iter().enumerate() {
-                check_cancelled()?;
-                progress.current_pass = (pass + 1) as u32;
-                progress.update(0, desc);
-                progress_callback(progress.clone());
-
-                file.seek(SeekFrom::Start(0)).map_err(WipeError::Io)?;
-                let mut buffer = vec![0u8; BUFFER_SIZE as usize];
-
-                for chunk_start in (0..file_size).step_by(BUFFER_SIZE as usize) {
-                    check_cancelled()?;
-                    let chunk_size = std::cmp::min(BUFFER_SIZE, file_size - chunk_start) as usize;
-                    
-                    if is_random {
-                        rng.fill_bytes(&mut buffer[..chunk_size]);
-                    } else {
-                        // Fill the buffer with the repeating pattern
-                        for i in 0..chunk_size {
-                            buffer[i] = pattern[i % pattern.len()];
-                        }
-                    }
-                    
-                    file.write_all(&buffer[..chunk_size]).map_err(WipeError::Io)?;
-
-                    // Update progress at most every 16ms for smooth animation
-                    if last_progress_update.elapsed() >= progress_update_interval {
-                        progress.update(
-                            chunk_start + chunk_size as u64,
-                            &format!("{} - {:.2} MB / {:.2} MB",
-                                desc,
-                                (chunk_start + chunk_size as u64) as f64 / 1024.0 / 1024.0,
-                                file_size as f64 / 1024.0 / 1024.0
-                            )
-                        );
-                        progress_callback(progress.clone());
-                        last_progress_update = std::time::Instant::now();
-                    }
-                }
-                file.sync_all().map_err(WipeError::Io)?;
-            }
-            
-            // Final cleanup
-            check_cancelled()?;
-            progress.update(file_size, "Finalizing Gutmann wipe");
-            progress_callback(progress);
-        },
-        WipeAlgorithm::Random => {
-            for pass in 1..=passes {
-                check_cancelled()?;
-                progress.current_pass = pass;
-                let desc = format!("Writing random data (Pass {}/{})", pass, passes);
-                progress.update(0, &desc);
-                progress_callback(progress.clone());
-
-                file.seek(SeekFrom::Start(0)).map_err(WipeError::Io)?;
-                let mut buffer = vec![0u8; BUFFER_SIZE as usize];
-                for chunk_start in (0..file_size).step_by(BUFFER_SIZE as usize) {
-                    check_cancelled()?;
-                    let chunk_size = std::cmp::min(BUFFER_SIZE, file_size - chunk_start);
-                    rng.fill_bytes(&mut buffer[..chunk_size as usize]);
-                    file.write_all(&buffer[..chunk_size as usize]).map_err(WipeError::Io)?;
-
-                    // Update progress at most every 16ms for smooth animation
-                    if last_progress_update.elapsed() >= progress_update_interval {
-                        progress.update(
-                            chunk_start + chunk_size,
-                            &format!("Writing random data (Pass {}/{}) - {:.2} MB / {:.2} MB",
-                                pass,
-                                passes,
-                                (chunk_start + chunk_size) as f64 / 1024.0 / 1024.0,
-                                file_size as f64 / 1024.0 / 1024.0
-                            )
-                        );
-                        progress_callback(progress.clone());
-                        last_progress_update = std::time::Instant::now();
-                    }
-                }
-                file.sync_all().map_err(WipeError::Io)?;
-            }
-            
-            // Final cleanup
-            check_cancelled()?;
-            progress.update(file_size, "Finalizing random wipe");
-            progress_callback(progress);
-        },
+fn current_free_space(sys: &mut System, path: &Path) -> Option<u64> {
+    if let Some(space) = free_space::query_free_space(path) {
+        return Some(space.total_free);
     }
-
-    // Final cleanup
-    check_cancelled()?;
-    file.set_len(0).map_err(WipeError::Io)?;
-    drop(file);
-    fs::remove_file(path).map_err(WipeError::Io)?;
-
-    Ok(())
+    sys.refresh_disks_list();
+    sys.disks()
+        .iter()
+        .find(|disk| path.starts_with(disk.mount_point()))
+        .map(|disk| disk.available_space())
 }
 
+/// File a free-space wipe fills at the drive root; one left behind means a fill was interrupted.
+pub(crate) const FREE_SPACE_TEMP_FILE: &str = ".temp_wipe_file";
+
 /// Validation errors for drive-root selection when wiping free space.
 #[derive(Debug)]
 pub enum DriveValidationError {
     PathNotFound,
     NotDriveRoot,
+    /// The volume's root does not let this user create the temp file, e.g. a root-owned mount.
+    NotWritable,
 }
 
 impl fmt::Display for DriveValidationError {
@@ -465,6 +311,7 @@ impl fmt::Display for DriveValidationError {
         match self {
             DriveValidationError::PathNotFound => write!(f, "Path not found"),
             DriveValidationError::NotDriveRoot => write!(f, "Selected path is not a drive root"),
+            DriveValidationError::NotWritable => write!(f, "Selected drive is not writable by this user"),
         }
     }
 }
@@ -474,79 +321,96 @@ fn validate_drive_path_internal(path: &Path) -> Result<(), DriveValidationError>
         return Err(DriveValidationError::PathNotFound);
     }
 
-    // Check if it's a drive root (e.g., "C:\")
-    if path.to_string_lossy().matches('\\').count() != 1 {
+    // A drive root (e.g., "C:\") or a mount point such as "/" or "/media/usb"
+    if !VolumeRoots::from_system().is_volume_root(path) {
         return Err(DriveValidationError::NotDriveRoot);
     }
 
+    // The temp file goes at the root; say so now rather than once the fill fails.
+    if !is_writable_dir(path) {
+        return Err(DriveValidationError::NotWritable);
+    }
+
     Ok(())
 }
 
+#[cfg(unix)]
+fn is_writable_dir(path: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(c_path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    unsafe { libc::access(c_path.as_ptr(), libc::W_OK | libc::X_OK) == 0 }
+}
+
+#[cfg(not(unix))]
+fn is_writable_dir(_path: &Path) -> bool {
+    true
+}
+
 /// Validate that the provided path is an existing drive root (e.g., "C:\").
 /// Returns a user-friendly `WipeResult` describing success or the validation failure.
 #[tauri::command]
-async fn validate_drive_path(path: String) -> Result<WipeResult, String> {
+async fn validate_drive_path(path: String) -> Result<WipeResult, BitBurnError> {
     let path = Path::new(&path);
     
     match validate_drive_path_internal(path) {
         Ok(_) => {
             log_event("validate_drive_path", json!({"status": "success", "path": path.to_string_lossy()}));
-            Ok(WipeResult {
-                success: true,
-                message: "Path validation successful".to_string(),
-            })
+            Ok(WipeResult::success(messages::PATH_VALIDATED.text()))
         }
         Err(e) => {
             log_event("validate_drive_path", json!({"status": "error", "path": path.to_string_lossy(), "message": e.to_string()}));
-            Ok(WipeResult {
-                success: false,
-                message: e.to_string(),
-            })
+            Ok(WipeResult::failure(BitBurnError::from_drive_validation(e, path)))
         }
     }
 }
 
 /// Show a blocking warning dialog summarizing the wipe request.
-/// The dialog warns the user about the impending wipe and returns their confirmation choice.
+/// The dialog warns the user about the impending wipe and returns their choice as a consent,
+/// whose token the wipe commands take as proof it was confirmed. Where the dialog cannot be
+/// shown the answer is `dialog_unavailable` with the prompt, for the window to ask instead.
 #[tauri::command]
 async fn show_confirmation_dialog<R: Runtime>(
     window: tauri::Window<R>,
+    consents: tauri::State<'_, ConsentStore>,
     path: String,
     algorithm: String,
     description: String,
-) -> Result<bool, String> {
-    use tauri_plugin_dialog::DialogExt;
-
+) -> Result<Consent, BitBurnError> {
     let message = if path.contains('\n') {
         // File wiping confirmation
-        let file_count = path.lines().count();
-        format!(
-            "You are about to permanently erase {} file(s) using:\n\nAlgorithm: {}\nDescription: {}\n\nTHIS ACTION CANNOT BE UNDONE!\n\nAre you absolutely sure you want to continue?",
-            file_count, algorithm, description
-        )
+        messages::CONFIRM_FILE_WIPE.render(json!({
+            "count": path.lines().count(),
+            "algorithm": algorithm,
+            "description": description,
+        }))
     } else {
         // Drive wiping confirmation
-        format!(
-            "You are about to wipe all free space on the selected drive using:\n\nAlgorithm: {}\nDescription: {}\n\nTHIS ACTION CANNOT BE UNDONE!\n\nAre you absolutely sure you want to continue?",
-            algorithm, description
-        )
+        messages::CONFIRM_FREE_SPACE_WIPE.render(json!({"algorithm": algorithm, "description": description}))
     };
 
-    let confirmed = window
-        .dialog()
-        .message(&message)
-        .kind(tauri_plugin_dialog::MessageDialogKind::Warning)
-        .title("⚠️ WARNING ⚠️")
-        .buttons(tauri_plugin_dialog::MessageDialogButtons::YesNo)
-        .blocking_show();
-
-    Ok(confirmed)
+    // File selections arrive one path per line.
+    let selection: Vec<String> = path.lines().map(str::to_string).collect();
+    let app = window.app_handle().clone();
+    let (prompt, asked) = (message.text.clone(), selection.clone());
+    let confirmed = spawn_blocking(move || confirm_dialog::confirm(&app, &prompt, &asked))
+        .await
+        .map_err(|e| BitBurnError::internal(format!("confirmation dialog join error: {}", e)))??;
+
+    let consent = consents.record(&message.text, &selection, confirmed, ConsentOrigin::Dialog, std::time::SystemTime::now());
+    log_event(
+        "consent_recorded",
+        json!({"confirmed": confirmed, "items": selection.len(), "message_sha256": consent.message_sha256}),
+    );
+    Ok(consent)
 }
 
 /// Report platform information to the frontend for capability gating.
 /// Used by the UI to toggle platform-specific controls without leaking OS concerns into core logic.
 #[tauri::command]
-async fn platform_info() -> Result<PlatformInfo, String> {
+async fn platform_info() -> Result<PlatformInfo, BitBurnError> {
     #[cfg(windows)]
     {
         Ok(PlatformInfo {
@@ -580,6 +444,53 @@ async fn platform_info() -> Result<PlatformInfo, String> {
     }
 }
 
+/// Preview a free-space wipe of `path`: bytes it will write and an ETA range. The returned token
+/// lets `execute_free_space_wipe` refuse to start if free space has changed much since.
+#[tauri::command]
+async fn estimate_free_space_wipe(
+    app: tauri::AppHandle,
+    estimates: tauri::State<'_, FreeSpaceEstimates>,
+    path: String,
+    algorithm: WipeAlgorithm,
+    passes: Option<u32>,
+) -> Result<FreeSpaceEstimate, BitBurnError> {
+    let volume = PathBuf::from(&path);
+    validate_drive_path_internal(&volume).map_err(|e| BitBurnError::from_drive_validation(e, &volume))?;
+    let (space, drives) = spawn_blocking(move || {
+        let space = volume_free_space(&mut System::new(), &volume)?;
+        Ok::<_, BitBurnError>((space, capabilities::list_drives().unwrap_or_default()))
+    })
+    .await
+    .map_err(|e| BitBurnError::internal(format!("estimate_free_space_wipe join error: {}", e)))??;
+
+    let passes = passes.unwrap_or_else(|| algorithm.default_passes());
+    let mut estimate = FreeSpaceEstimate::compute(&path, space.fill_estimate(), passes, estimates.measured_bytes_per_second());
+    let now = std::time::SystemTime::now();
+    let drive = capabilities::drive_holding(&drives, &path);
+    if let (Some(drive), Some(wear)) = (drive, app.try_state::<WearStore>()) {
+        let threshold = drive_wear::threshold_bytes(&app);
+        estimate.wear_warning = wear.ledger().wear_warning(drive, estimate.total_bytes, threshold, now);
+    }
+    if let (Some(drive), Some(store)) = (drive, app.try_state::<volume_history::HistoryStore>()) {
+        let (history, identity) = (store.history(), volume_history::VolumeIdentity::of(drive));
+        if history.wiped_within(&identity, volume_history::RECENT_WIPE_WINDOW, now) {
+            estimate.recently_wiped = history.last_wipe(&identity).cloned();
+        }
+    }
+    let estimate = estimates.issue(estimate, now);
+    log_event(
+        "free_space_estimate",
+        json!({"path": path, "available": estimate.available_bytes, "total_bytes": estimate.total_bytes, "throughput_source": estimate.throughput_source}),
+    );
+    if let Some(warning) = &estimate.wear_warning {
+        log_event(
+            "wear_warning",
+            json!({"path": path, "written_last_30_days": warning.written_last_30_days, "threshold_bytes": warning.threshold_bytes}),
+        );
+    }
+    Ok(estimate)
+}
+
 /// Wipe free space by filling a temp file and securely deleting it.
 /// Blocks heavy I/O on a worker thread while emitting progress events to the main window.
 #[tauri::command]
@@ -587,21 +498,59 @@ async fn execute_free_space_wipe<R: Runtime>(
     window: tauri::Window<R>,
     path: String,
     algorithm: WipeAlgorithm,
-    passes: u32
-) -> Result<WipeResult, String> {
+    passes: u32,
+    delete_shadow_copies: Option<bool>,
+    estimate: Option<EstimateCheck>,
+    consent_token: Option<String>,
+) -> Result<WipeResult, BitBurnError> {
     let window_label = window.label().to_string();
     let app_handle = window.app_handle().clone();
-    let cancelled = Arc::new(AtomicBool::new(false));
-    let cancel_for_listener = cancelled.clone();
-
-    let _cancel_listener = app_handle.listen("cancel_operation", move |_| {
-        cancel_for_listener.store(true, Ordering::SeqCst);
+    policy::check_wipe(&app_handle, policy::WIPE_FREE_SPACE, &algorithm, passes, std::slice::from_ref(&path))?;
+    let consent = consent::check(&app_handle, consent_token.as_deref(), std::slice::from_ref(&path))?;
+    let operation = Arc::new(OperationGuard::begin(
+        &app_handle,
+        &window.state::<OperationRegistry>(),
+        OperationKind::FreeSpaceWipe,
+        path.clone(),
+    ));
+    operation.record_algorithm(&algorithm, passes);
+
+    // Shadow copies survive a free-space fill, so report them first and delete them only on request.
+    let preflight_path = PathBuf::from(&path);
+    let delete_requested = delete_shadow_copies.unwrap_or(false);
+    let preflight = spawn_blocking(move || -> Result<Option<ShadowCopyReport>, BitBurnError> {
+        if validate_drive_path_internal(&preflight_path).is_err() {
+            return Ok(None);
+        }
+        let info = shadow_copies::inspect_volume(&preflight_path);
+        let action = shadow_copies::plan_shadow_copies(&info, delete_requested, platform::elevation::is_elevated())?;
+        if action == ShadowCopyAction::Deleted {
+            shadow_copies::delete_shadow_copies(&info.volume)?;
+        }
+        Ok(Some(ShadowCopyReport::new(&info, action)))
     });
-
-    let path_buf = PathBuf::from(path);
+    let shadow_report = match preflight
+        .await
+        .map_err(|e| BitBurnError::internal(format!("shadow copy preflight join error: {}", e)))?
+    {
+        Ok(report) => report,
+        Err(error) => {
+            let result = WipeResult::failure(error);
+            operation.record_result(&result);
+            return Ok(result);
+        }
+    };
+    if let Some(report) = &shadow_report {
+        diagnostics::emit_to(&app_handle, &window_label, "free_space_preflight", json!({"path": path, "shadow_copies": report}));
+    }
+    let cancelled = operation.cancel_flag();
+    let path_buf = PathBuf::from(&path);
     let algo_for_task = algorithm.clone();
+    let operation_for_task = operation.clone();
 
     let join_result = spawn_blocking(move || {
+        let operation = operation_for_task;
+        let _log_scope = operation.enter_log_scope();
         let path = path_buf;
 
         log_event(
@@ -611,289 +560,1547 @@ async fn execute_free_space_wipe<R: Runtime>(
 
         // Validate again just to be safe
         if let Err(e) = validate_drive_path_internal(&path) {
-            return Ok(free_space_error_result(e.to_string()));
+            return Ok(WipeResult::failure(BitBurnError::from_drive_validation(e, &path)));
         }
+        let Some(_slot) = scheduler::acquire(&app_handle, operation.id(), scheduler::volumes_of([&path]), &cancelled) else {
+            return Ok(cancelled_wipe_result());
+        };
+        let memory = memory_budget::check(&app_handle, operation.id());
+        let buffer_size = memory.map(|memory| memory.buffer_bytes);
+        let decoy = match decoy::from_settings(&app_handle) {
+            Ok(decoy) => decoy,
+            Err(e) => {
+                log_event("wipe_decoy_invalid", json!({"path": path.to_string_lossy(), "message": e.to_string()}));
+                return Ok(WipeResult::failure(e));
+            }
+        };
+        let decoy_data = decoy.as_ref().map(|decoy| &*decoy.data);
 
         let mut sys = System::new_all();
-        sys.refresh_disks_list();
-
-        let disk_info = sys
-            .disks()
-            .iter()
-            .find(|disk| path.starts_with(disk.mount_point()))
-            .ok_or_else(|| "Could not find disk information".to_string())?;
-
-        let available_space = disk_info.available_space();
+        // With a disk quota only part of the free space is writable, so progress runs against that part.
+        let volume_space = volume_free_space(&mut sys, &path)?;
+        if let Some(estimate) = &estimate {
+            let estimates = app_handle.state::<FreeSpaceEstimates>();
+            let checked = estimates.check(
+                &estimate.token,
+                &path.to_string_lossy(),
+                volume_space.fill_estimate(),
+                estimate.ignore_stale,
+                std::time::SystemTime::now(),
+            );
+            if let Err(error) = checked {
+                log_event(
+                    "wipe_free_space_estimate_rejected",
+                    json!({"path": path.to_string_lossy(), "code": error.code(), "available": volume_space.fill_estimate()}),
+                );
+                return Ok(WipeResult::failure(error));
+            }
+        }
+        if volume_space.is_quota_limited() {
+            log_event(
+                "wipe_free_space_quota_limit",
+                json!({"path": path.to_string_lossy(), "caller_available": volume_space.caller_available, "volume_free": volume_space.total_free}),
+            );
+        }
+        let available_space = volume_space.fill_estimate();
+        let initial_free = volume_space.total_free;
+        let operation_id = operation.id().to_string();
+        let mut quota_limited = false;
         let cancelled_clone = cancelled.clone();
-        let app_handle = app_handle.clone();
-        let window_label = window_label.clone();
+        let operation = operation.clone();
+        // Dropped with this task, which flushes the final snapshot before the command resolves.
+        let coalescer = progress_coalescer_for(&app_handle, &window_label, &operation_id, operation.diagnostics());
+        let last_pattern = RefCell::new(String::new());
+        let volume = path.to_string_lossy().into_owned();
         let progress_callback = move |progress: WipeProgress| {
             if !cancelled_clone.load(Ordering::SeqCst) {
-                let _ = app_handle.emit_to(&window_label, "wipe_progress", progress);
+                operation.update(&progress.current_pattern, progress.percentage);
+                if let Some(adjustment) = &progress.estimate_adjusted {
+                    log_event(
+                        "wipe_free_space_estimate_adjusted",
+                        json!({"path": volume, "previous_bytes": adjustment.previous_bytes, "estimate_bytes": adjustment.estimate_bytes}),
+                    );
+                }
+                if let Some(jump) = &progress.clock_jump {
+                    log_event(
+                        "system_resumed",
+                        json!({"path": volume, "wall_ms": jump.wall_ms, "monotonic_ms": jump.monotonic_ms}),
+                    );
+                }
+                // Filling, cleanup and each overwrite pass have their own pattern, and a revised estimate
+                // or a resume comes with a note: send those at once.
+                let noted = progress.estimate_adjusted.is_some() || progress.clock_jump.is_some();
+                if *last_pattern.borrow() != progress.current_pattern || noted {
+                    last_pattern.replace(progress.current_pattern.clone());
+                    coalescer.phase_change(progress);
+                } else {
+                    coalescer.update(progress);
+                }
             }
         };
 
-        let mut progress = WipeProgress::new(
-            passes,
-            0,
-            match algo_for_task {
-                WipeAlgorithm::NistClear => "NIST 800-88 Clear",
-                WipeAlgorithm::NistPurge => "NIST 800-88 Purge",
-                WipeAlgorithm::Gutmann => "Gutmann",
-                WipeAlgorithm::Random => "Random",
-            },
-        );
+        // The fill is pass 1; each overwrite pass then rewrites everything it wrote, the decoy pass included.
+        let overwrite_passes = (algo_for_task.pass_kinds(passes).len() + usize::from(decoy.is_some())) as u64;
+        let mut progress = WipeProgress::new(overwrite_passes as u32 + 1, 0, algo_for_task.display_name());
 
-        progress.estimated_total_bytes = Some(available_space);
+        progress.estimated_total_bytes = Some(available_space.saturating_mul(overwrite_passes + 1));
 
         progress.update(0, "Filling drive space");
         progress_callback(progress.clone());
 
-        let temp_file_path = path.join(".temp_wipe_file");
-
-        if temp_file_path.exists() {
+        let legacy_temp_file = path.join(FREE_SPACE_TEMP_FILE);
+        if legacy_temp_file.exists() {
             progress.update(0, "Cleaning up previous temporary file");
             progress_callback(progress.clone());
-            if let Err(e) = fs::remove_file(&temp_file_path) {
-                return Ok(free_space_error_result(format!(
-                    "Failed to remove existing temporary file: {}",
-                    e
-                )));
+            if let Err(e) = fs::remove_file(&legacy_temp_file) {
+                return Ok(free_space_error_result(
+                    BitBurnError::io(&e, Some(&legacy_temp_file)),
+                    messages::FREE_SPACE_TEMP_REMOVE_FAILED.render(json!({"error": e.to_string()})),
+                ));
             }
         }
 
-        let mut file = match OpenOptions::new().write(true).create(true).open(&temp_file_path) {
-            Ok(f) => f,
+        // The fill is meant to use up whatever is free, so no minimum is asked for; the scratch
+        // directory removes the temp file on every early return and is swept after a crash.
+        let scratch = match scratch::ensure_scratch_space_on(&path, 0) {
+            Ok(scratch) => scratch,
             Err(e) => {
-                return Ok(free_space_error_result(format!(
-                    "Failed to create temporary file: {}",
-                    e
-                )));
+                let message = messages::FREE_SPACE_TEMP_CREATE_FAILED.render(json!({"error": e.to_string()}));
+                return Ok(free_space_error_result(e, message));
             }
         };
+        let temp_file_path = scratch.path().join(FREE_SPACE_TEMP_FILE);
 
-        let chunk_size = 1024 * 1024; // 1MB chunks
-        let mut buffer = vec![0u8; chunk_size];
-        let mut rng = rand::thread_rng();
-        let mut total_written = 0u64;
-        let mut last_refresh = std::time::Instant::now();
-        let mut last_space_used = 0u64;
-
-        loop {
-            if cancelled.load(Ordering::SeqCst) {
-                let _ = file.sync_all();
-                let _ = fs::remove_file(&temp_file_path);
-                return Ok(cancelled_wipe_result());
-            }
-
-            if last_refresh.elapsed() >= std::time::Duration::from_millis(100) {
-                sys.refresh_disks_list();
-                if let Some(disk) = sys.disks().iter().find(|disk| path.starts_with(disk.mount_point())) {
-                    let current_available = disk.available_space();
-                    last_space_used = available_space - current_available;
-                }
-                last_refresh = std::time::Instant::now();
+        let mut file = match scratch::create_temp_file(&temp_file_path) {
+            Ok(f) => f,
+            Err(e) => {
+                return Ok(free_space_error_result(
+                    BitBurnError::io(&e, Some(&temp_file_path)),
+                    messages::FREE_SPACE_TEMP_CREATE_FAILED.render(json!({"error": e.to_string()})),
+                ));
             }
+        };
 
-            rng.fill_bytes(&mut buffer);
-            match file.write_all(&buffer) {
-                Ok(_) => {
-                    total_written += chunk_size as u64;
-                    progress.update(last_space_used, &format!("Filling drive space ({} MB written)", total_written / 1024 / 1024));
-                    progress_callback(progress.clone());
-
-                    if total_written % (10 * chunk_size as u64) == 0 {
-                        if let Err(_) = file.sync_all() {
-                            break;
-                        }
-                    }
-                }
-                Err(e) => {
-                    if e.kind() == io::ErrorKind::StorageFull
-                        || e.kind() == io::ErrorKind::OutOfMemory
-                        || e.kind() == io::ErrorKind::WriteZero
-                    {
-                        sys.refresh_disks_list();
-                        if let Some(disk) = sys.disks().iter().find(|disk| path.starts_with(disk.mount_point())) {
-                            let current_available = disk.available_space();
-                            let space_used = available_space - current_available;
-                            progress.update(space_used, "Drive space filled");
-                            progress_callback(progress.clone());
-                        }
-                        break;
+        let cancel = CancellationToken::from(cancelled.clone());
+        let fill_started = std::time::Instant::now();
+        let fill = fill_free_space_with(
+            &mut file,
+            FillPlan { available_bytes: available_space, initial_free, decoy: decoy_data, buffer_size },
+            || current_free_space(&mut sys, &path),
+            &mut progress,
+            &progress_callback,
+            &cancel,
+        );
+        let total_written = match fill {
+            Ok(outcome) => {
+                match outcome.end {
+                    FillEnd::QuotaReached => {
+                        log_event(
+                            "wipe_free_space_quota_reached",
+                            json!({"path": path.to_string_lossy(), "written": outcome.bytes_written, "volume_free": initial_free}),
+                        );
+                        quota_limited = true;
                     }
-                    let _ = fs::remove_file(&temp_file_path);
-                    return Ok(free_space_error_result(format!(
-                        "Failed to write to temporary file: {}",
-                        e
-                    )));
+                    FillEnd::DiskFilled { error_kind, available } => log_event(
+                        "wipe_free_space_filled",
+                        json!({"path": path.to_string_lossy(), "error_kind": format!("{:?}", error_kind), "available": available}),
+                    ),
+                    FillEnd::SyncFailed => {}
                 }
+                outcome.bytes_written
             }
-        }
+            Err(FillError::Cancelled) => {
+                let bytes_written = file.metadata().map(|m| m.len()).unwrap_or(0);
+                drop(file);
+                let fill = PendingFill {
+                    volume: path.to_string_lossy().to_string(),
+                    temp_file: temp_file_path,
+                    scratch,
+                    summary: CancelledFill {
+                        bytes_written,
+                        passes_requested: overwrite_passes as u32 + 1,
+                        passes_received: 1,
+                        action: None,
+                    },
+                    consent,
+                };
+                return Ok(cancelled_fill::park(&app_handle, &operation_id, fill));
+            }
+            Err(FillError::DeviceGone(e)) => {
+                // The temp file went with the volume; removing it would only fail again.
+                scratch.abandon();
+                return Ok(volume_lost::free_space_lost(&app_handle, &window_label, &operation_id, &path, &e));
+            }
+            Err(FillError::Write(e)) if !path.exists() => {
+                scratch.abandon();
+                return Ok(volume_lost::free_space_lost(&app_handle, &window_label, &operation_id, &path, &e));
+            }
+            Err(FillError::Write(e)) => {
+                let _ = fs::remove_file(&temp_file_path);
+                return Ok(free_space_error_result(
+                    BitBurnError::io(&e, Some(&temp_file_path)),
+                    messages::FREE_SPACE_TEMP_WRITE_FAILED.render(json!({"error": e.to_string()})),
+                ));
+            }
+        };
 
+        if let Some(estimates) = app_handle.try_state::<FreeSpaceEstimates>() {
+            estimates.record_throughput(total_written, fill_started.elapsed());
+        }
         progress.total_bytes = total_written;
+        progress.estimated_total_bytes = Some(total_written.saturating_mul(overwrite_passes + 1));
+        // Cancelling during the overwrite passes is acted on once they finish.
         let cancelled_clone = cancelled.clone();
-        match secure_wipe_file(&temp_file_path, passes, &algo_for_task, move |p| {
+        let follow_passes = move |p: WipeProgress| {
             if !cancelled_clone.load(Ordering::SeqCst) {
-                progress_callback(p);
+                progress.follow_pass(total_written, &p);
+                progress_callback(progress.clone());
             }
-        }) {
+        };
+        // The fill's handle is kept for the passes so the file is never open to anyone else.
+        let options = OverwriteOptions { decoy: decoy_data, buffer_size, ..Default::default() };
+        let mut record = FileWipeProgressRecord::default();
+        let cancel = CancellationToken::new();
+        let overwritten = overwrite_target_with(&mut file, passes, &algo_for_task, &cancel, follow_passes, &mut record, options)
+            .and_then(|_| finish_wipe(file, &temp_file_path, &cancel));
+        match overwritten {
             Ok(_) => {
                 if cancelled.load(Ordering::SeqCst) {
                     log_event("wipe_free_space_cancelled", json!({"path": path.to_string_lossy()}));
                     Ok(cancelled_wipe_result())
                 } else {
-                    log_event("wipe_free_space_complete", json!({"path": path.to_string_lossy(), "status": "success"}));
-                    Ok(WipeResult {
-                        success: true,
-                        message: "Successfully wiped free space".to_string(),
-                    })
+                    let coverage = FreeSpaceCoverage {
+                        bytes_written: total_written,
+                        total_bytes_written: total_written.saturating_mul(overwrite_passes + 1),
+                        volume_free_bytes: initial_free,
+                        quota_limited,
+                    };
+                    log_event(
+                        "wipe_free_space_complete",
+                        json!({"path": path.to_string_lossy(), "status": "success", "quota_limited": quota_limited}),
+                    );
+                    let mut report = WipeReport::new(operation_id, None);
+                    report.free_space = Some(coverage.clone());
+                    report.free_space_sample = app_handle
+                        .try_state::<FreeSpaceSamples>()
+                        .and_then(|samples| samples.last(&path.to_string_lossy()));
+                    report.decoy = decoy.map(|decoy| decoy.summary);
+                    report.consent = consent;
+                    report.memory_constrained = memory;
+                    drive_wear::record_finished(&app_handle, &report, Some(&path.to_string_lossy()));
+                    Ok(WipeResult::success(coverage.message()).with_report(report))
                 }
             }
+            Err(e) if e.is_device_gone() || !path.exists() => {
+                scratch.abandon();
+                Ok(volume_lost::free_space_lost(&app_handle, &window_label, &operation_id, &path, &e))
+            }
             Err(e) => {
                 let _ = fs::remove_file(&temp_file_path);
+                let message = messages::FREE_SPACE_WIPE_FAILED.render(json!({"error": e.to_string()}));
+                let error = BitBurnError::from_wipe_error(e, &temp_file_path);
                 log_event(
                     "wipe_free_space_error",
-                    json!({"path": path.to_string_lossy(), "message": format!("{}", e)}),
+                    json!({"path": path.to_string_lossy(), "message": message.text, "code": error.code()}),
                 );
-                Ok(free_space_error_result(format!("Failed to wipe free space: {}", e)))
+                Ok(free_space_error_result(error, message))
             }
         }
     })
     .await
-    .map_err(|e| format!("wipe_free_space task join error: {}", e))?;
-
+    .map_err(|e| BitBurnError::internal(format!("wipe_free_space task join error: {}", e)))?;
+
+    let join_result = join_result.map(|mut result| {
+        let report = match (result.report.take(), shadow_report) {
+            (report, Some(shadow_copies)) => {
+                let mut report = report.unwrap_or_else(|| WipeReport::new(operation.id(), None));
+                report.shadow_copies = Some(shadow_copies);
+                Some(report)
+            }
+            (report, None) => report,
+        };
+        match report {
+            Some(report) => {
+                operation.attach_report(report.clone());
+                result.with_report(report)
+            }
+            None => result,
+        }
+    });
+    if let Ok(result) = &join_result {
+        volume_history::record_finished(window.app_handle(), &path, &algorithm, passes, result);
+        operation.record_result(result);
+    }
     join_result
 }
 
-/// Securely wipe files or folders using the selected algorithm.
-/// Runs in a blocking task to avoid UI stalls and streams progress to the main window.
-#[tauri::command]
-async fn wipe_files<R: Runtime>(
-    window: tauri::Window<R>,
-    paths: Vec<String>,
-    passes: u32,
-    algorithm: WipeAlgorithm
-) -> Result<WipeResult, String> {
-    let window_label = window.label().to_string();
-    let app_handle = window.app_handle().clone();
-    let cancelled = Arc::new(AtomicBool::new(false));
-    let cancel_for_listener = cancelled.clone();
-
-    let _cancel_listener = app_handle.listen("cancel_operation", move |_| {
-        cancel_for_listener.store(true, Ordering::SeqCst);
-    });
+/// Receives each file's outcome as soon as it is recorded.
+type FileSink = Box<dyn Fn(&FileWipeOutcome) + Send + Sync>;
 
-    let paths_for_task = paths.clone();
-    let algo_for_task = algorithm.clone();
+/// Shared state for a running file-wipe batch: where progress goes and how cancellation is observed.
+struct BatchContext<R: Runtime> {
+    app_handle: tauri::AppHandle<R>,
+    operation: Arc<OperationGuard<R>>,
+    progress: ProgressCoalescer,
+    /// Set by runs without a window, which report every file rather than only failed ones.
+    on_file: Option<FileSink>,
+}
 
-    let join_result = spawn_blocking(move || {
-        log_event(
-            "wipe_files_start",
-            json!({"count": paths_for_task.len(), "algorithm": format!("{:?}", algo_for_task), "passes": passes}),
-        );
+impl<R: Runtime> BatchContext<R> {
+    fn new(app_handle: tauri::AppHandle<R>, window_label: String, operation: OperationGuard<R>) -> Self {
+        let progress = progress_coalescer_for(&app_handle, &window_label, operation.id(), operation.diagnostics());
+        Self::with_progress(app_handle, operation, progress)
+    }
 
-        let mut total_files = 0;
-        let mut failed_files = Vec::new();
+    /// A batch whose progress goes to `progress` instead of the operation's windows.
+    fn with_progress(app_handle: tauri::AppHandle<R>, operation: OperationGuard<R>, progress: ProgressCoalescer) -> Self {
+        BatchContext {
+            app_handle,
+            operation: Arc::new(operation),
+            progress,
+            on_file: None,
+        }
+    }
 
-        for path_str in paths_for_task {
-            if cancelled.load(Ordering::SeqCst) {
-                return Ok(cancelled_wipe_result());
+    fn progress_emitter<'a, P>(&'a self, root_index: Option<usize>, overall: P) -> impl FnMut(WipeProgress) + 'a
+    where
+        P: Fn(f32) -> f32 + 'a,
+    {
+        let cancelled = self.operation.cancel_flag();
+        move |mut progress: WipeProgress| {
+            if !cancelled.load(Ordering::SeqCst) {
+                progress.root_index = root_index;
+                self.operation.update(&progress.current_pattern, overall(progress.percentage / 100.0));
+                self.progress.update(progress);
             }
+        }
+    }
 
-            let path = Path::new(&path_str);
+    /// Emit an event the UI must not miss, ahead of any coalesced progress still pending.
+    fn emit_critical(&self, event: &str, payload: serde_json::Value) {
+        self.progress.emit_now(event, payload);
+    }
 
-            if !path.exists() {
-                failed_files.push(format!("Path not found: {}", path_str));
-                continue;
-            }
+    /// Pass the outcome just recorded in `report` to the file sink, if there is one.
+    fn file_ended(&self, report: &WipeReport) {
+        if let (Some(on_file), Some(outcome)) = (&self.on_file, report.files.last()) {
+            on_file(outcome);
+        }
+    }
+}
 
-            let emit_progress = {
-                let app_handle = app_handle.clone();
-                let window_label = window_label.clone();
-                let cancelled_clone = cancelled.clone();
-                move |progress| {
-                    if !cancelled_clone.load(Ordering::SeqCst) {
-                        let _ = app_handle.emit_to(&window_label, "wipe_progress", progress);
-                    }
-                }
-            };
+/// Coalesce an operation's `wipe_progress` events to the rate configured in settings.
+fn progress_coalescer_for<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    window_label: &str,
+    operation_id: &str,
+    diagnostics: OperationDiagnostics,
+) -> ProgressCoalescer {
+    let events_per_second = app_handle
+        .try_state::<SettingsStore>()
+        .and_then(|settings| settings.get().progress_events_per_second)
+        .unwrap_or(progress_coalescer::DEFAULT_EVENTS_PER_SECOND);
+    let app_handle = app_handle.clone();
+    let window_label = window_label.to_string();
+    let id = operation_id.to_string();
+    let failures = diagnostics.clone();
+    ProgressCoalescer::with_diagnostics(operation_id, events_per_second, diagnostics, move |event: &str, payload| {
+        for target in progress_window::targets_for(&app_handle, &window_label, &id) {
+            if app_handle.emit_to(&target, event, payload.clone()).is_err() {
+                failures.emit_failed();
+            }
+        }
+    })
+}
 
-            if path.is_file() {
-                match secure_wipe_file(path, passes, &algo_for_task, emit_progress) {
-                    Ok(_) => total_files += 1,
-                    Err(e) => failed_files.push(format!("Failed to wipe {}: {}", path_str, e)),
-                }
-            } else if path.is_dir() {
-                let files: Vec<_> = WalkDir::new(path)
-                    .into_iter()
-                    .filter_map(|e| e.ok())
-                    .filter(|e| e.file_type().is_file())
-                    .collect();
-
-                for entry in files {
-                    if cancelled.load(Ordering::SeqCst) {
-                        return Ok(WipeResult {
-                            success: false,
-                            message: "Operation cancelled by user".to_string(),
-                        });
-                    }
+/// Per-batch choices made by the entry point that started the wipe.
+#[derive(Debug, Clone, Default)]
+struct BatchOptions {
+    /// Origin label recorded in the report, e.g. `manifest:paths.txt`.
+    source: Option<String>,
+    /// Record files locked by another process as skips instead of failures.
+    skip_in_use: bool,
+    /// Stall and timeout limits for each file; `None` reads them from the user's settings.
+    stall_policy: Option<StallPolicy>,
+    /// Renaming and cancellation cleanup once a file is overwritten; `None` reads them from the user's settings.
+    finish: Option<FinishOptions>,
+    /// What to do about files the pre-flight lock scan finds; `None` stops the batch so the user can choose.
+    lock_policy: Option<LockPolicy>,
+    /// Files the pre-flight scan found locked under `LockPolicy::SkipLocked`; left untouched.
+    locked_skips: HashSet<PathBuf>,
+    /// Volume roots and mount points the user confirmed by typing their path; the batch walks
+    /// these and refuses any other.
+    confirmed_volume_roots: Vec<String>,
+    /// Empty selected folders but leave the folders themselves in place.
+    preserve_roots: bool,
+    /// Also wipe the companion files found beside each selected file; `None` reads it from the user's settings.
+    companion_files: Option<bool>,
+    /// Files walked, scanned and reported in memory at a time; `None` reads it from the user's settings.
+    chunk_files: Option<usize>,
+    /// Overwrite the named data streams of folders before removing them; `None` reads it from the user's settings.
+    named_streams: Option<bool>,
+    /// Wipe files whose data is in remote or archive storage, recalling it first; they are refused otherwise.
+    allow_recall: bool,
+    /// Take over files that refuse access, see `ownership`.
+    take_ownership: bool,
+    /// What to do with selected symbolic links, see `symlinks`.
+    symlink_policy: SymlinkPolicy,
+    /// Crash journal recording which roots are done; removed once the batch returns.
+    journal: Option<journal::OperationJournal>,
+    /// Which large files record pass checkpoints in the journal; `None` reads it from the user's settings.
+    checkpoint_policy: Option<CheckpointPolicy>,
+    /// The operation whose failed files this batch retries.
+    retry_of: Option<String>,
+    /// Failures of that operation that are not retried, reported again as still failed.
+    carried_failures: Vec<BitBurnError>,
+    /// Entries dropped before the batch, e.g. pattern matches the sanitization refused; reported as skipped.
+    carried_skips: Vec<BitBurnError>,
+    /// Warnings raised before the batch, e.g. a pattern that matched nothing; copied into the report.
+    carried_warnings: Vec<String>,
+    /// Checkpoints of files the run being resumed or retried left part-way, handed to the new journal.
+    checkpoints: BTreeMap<String, FileCheckpoint>,
+    /// One `VolumeReadOnly` per volume the pre-flight check found read-only, and an `AccessDenied` per root
+    /// in a folder this user may not write in; their roots are not in the batch.
+    read_only_failures: Vec<BitBurnError>,
+    /// Folder walks made by the pre-flight lock scan, keyed by root, reused by the wipe.
+    tree_scans: HashMap<PathBuf, TreeScan>,
+    /// Roots the pre-flight found on removable flash media; their files are tagged in the report.
+    flash_media: Vec<PathBuf>,
+    /// Put back the timestamps of directories files were deleted from; `None` reads it from the user's settings.
+    preserve_parent_mtime: Option<bool>,
+    /// Decoy data written as a final pass over every file; `None` reads it from the user's settings.
+    decoy: Option<decoy::Decoy>,
+    /// How each file's passes are read back; `None` reads it from the user's settings.
+    verification: Option<Verification>,
+    /// Time the pre-flight checks took, recorded in the report's `timings`.
+    scan_time: std::time::Duration,
+    /// The user's answer to the confirmation dialog, copied into the report.
+    consent: Option<Consent>,
+    /// Open each file exclusively, see `bitburn_core::open_exclusive`: unshared on Windows, under an
+    /// advisory `flock` elsewhere; a file that cannot be had exclusively is treated as in use.
+    /// `None` reads it from the user's settings.
+    exclusive_lock: Option<bool>,
+    /// Set when memory was short as the batch started; its files are written through the smaller buffer.
+    memory: Option<memory_budget::MemoryConstraint>,
+    /// Roots of a mixed batch with their own algorithm and passes; the others use the batch's.
+    tasks: Vec<WipeTask>,
+    /// Replaces the file opened for chosen paths with a simulated target.
+    #[cfg(test)]
+    simulate_target: Option<SimulatedTarget>,
+}
 
-                    let emit_progress = {
-                        let app_handle = app_handle.clone();
-                        let window_label = window_label.clone();
-                        let cancelled_clone = cancelled.clone();
-                        move |progress| {
-                            if !cancelled_clone.load(Ordering::SeqCst) {
-                                let _ = app_handle.emit_to(&window_label, "wipe_progress", progress);
-                            }
-                        }
-                    };
+#[cfg(test)]
+type SimulatedTarget = fn(&Path) -> Option<Box<dyn WipeTarget + Send>>;
 
-                    match secure_wipe_file(entry.path(), passes, &algo_for_task, emit_progress) {
-                        Ok(_) => total_files += 1,
-                        Err(e) => failed_files.push(format!("Failed to wipe {}: {}", entry.path().display(), e)),
-                    }
-                }
+impl BatchOptions {
+    fn volume_root_confirmed(&self, path: &Path) -> bool {
+        let path = volume_root::comparable(path);
+        self.confirmed_volume_roots.iter().any(|root| volume_root::comparable(Path::new(root)) == path)
+    }
 
-                if let Err(e) = fs::remove_dir_all(path) {
-                    failed_files.push(format!("Failed to remove directory {}: {}", path_str, e));
-                }
-            }
+    fn open_target(&self, path: &Path, passes: u32) -> Result<Box<dyn WipeTarget + Send>, WipeError> {
+        let file = open_wipe_target_with(path, passes, self.exclusive_lock.unwrap_or(true))?;
+        #[cfg(test)]
+        if let Some(target) = self.simulate_target.and_then(|simulate| simulate(path)) {
+            return Ok(target);
         }
+        Ok(Box::new(file))
+    }
+}
 
-            if cancelled.load(Ordering::SeqCst) {
-                let result = cancelled_wipe_result();
-                log_event("wipe_files_end", json!({"status": "cancelled", "count": total_files, "errors": failed_files.len()}));
-                Ok(result)
-            } else if failed_files.is_empty() {
-            let result = WipeResult {
-                success: true,
-                message: format!("Successfully wiped {} files", total_files),
-            };
-            log_event("wipe_files_end", json!({"status": "success", "count": total_files}));
-            Ok(result)
-        } else {
-            let result = WipeResult {
-                success: false,
-                message: format!(
-                    "Wiped {} files with {} errors:\n{}",
-                    total_files,
-                    failed_files.len(),
-                    failed_files.join("\n")
-                ),
-            };
-            log_event(
-                "wipe_files_end",
-                json!({"status": "partial", "count": total_files, "errors": failed_files.len()}),
-            );
-            Ok(result)
+/// How a batch file that did not fail ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BatchFileEnd {
+    Wiped,
+    /// Stopped by `skip_current_file`; the file was deleted and recorded as skipped.
+    Skipped,
+}
+
+/// Whether a wipe failed because another process holds the file open.
+fn is_in_use_error(err: &WipeError) -> bool {
+    match err {
+        WipeError::Io(e) => {
+            // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION
+            (cfg!(windows) && matches!(e.raw_os_error(), Some(32) | Some(33)))
+                || e.kind() == io::ErrorKind::ResourceBusy
+                || e.kind() == io::ErrorKind::ExecutableFileBusy
         }
-    })
+        _ => false,
+    }
+}
+
+/// Wipe one file of a batch on a watched writer thread, so a write that hangs on failing hardware
+/// raises `wipe_stalled` and eventually times out instead of stalling the whole batch.
+/// If the wipe fails or is cancelled after it started overwriting, the file is left half-destroyed,
+/// so record how far it got in `partially_wiped`. Interruptions after the last pass are recorded
+/// in `interrupted` with where the file ended up instead. Every outcome is added to `files` with
+/// the passes the file received. A file the user skipped is deleted as it is and not treated as a
+/// partial wipe, since the user chose to let it go.
+fn wipe_batch_file<R, F>(
+    ctx: &BatchContext<R>,
+    path: &Path,
+    passes: u32,
+    algorithm: &WipeAlgorithm,
+    options: &BatchOptions,
+    mut emit_progress: F,
+    report: &mut WipeReport,
+) -> Result<BatchFileEnd, WipeError>
+where
+    R: Runtime,
+    F: FnMut(WipeProgress),
+{
+    let started = std::time::Instant::now();
+    let cancelled = ctx.operation.cancel_flag();
+    let skip = ctx.operation.skip_flag();
+    // A skip that arrived while the previous file was finishing must not cut this one short.
+    skip.store(false, Ordering::SeqCst);
+    let stop_requested = || cancelled.load(Ordering::SeqCst) || skip.load(Ordering::SeqCst);
+    let last_progress = Cell::new(None);
+    let interrupted_before = report.interrupted.len();
+    let on_stall = |seconds: u64| {
+        let payload = json!({"operation_id": ctx.operation.id(), "path": path.to_string_lossy(), "seconds": seconds});
+        log_event("wipe_stalled", payload.clone());
+        ctx.emit_critical("wipe_stalled", payload);
+    };
+
+    // Written by the writer thread when it finishes; a writer abandoned by a timeout never does.
+    let recorded = Arc::new(Mutex::new(None));
+    let writer_record = recorded.clone();
+    let checkpointer = FileCheckpointer::prepare(
+        options.journal.as_ref(),
+        options.checkpoint_policy.unwrap_or_default(),
+        path,
+        algorithm,
+        passes,
+    );
+    let decoy = options.decoy.as_ref().map(|decoy| decoy.data.clone());
+    let verification = options.verification;
+    let buffer_size = options.memory.map(|memory| memory.buffer_bytes);
+    // Looked up before the first pass, which NOCOW has to precede to have any effect.
+    let copy_on_write = copy_on_write::prepare(path);
+    // Wiping the file deletes its named streams with it, without overwriting them.
+    let streams = match options.named_streams {
+        Some(true) => {
+            let cancel = CancellationToken::from(cancelled.clone());
+            named_streams::wipe_recorded(path, passes, algorithm, &cancel, &mut report.named_streams)
+        }
+        _ => Ok(()),
+    };
+    let (opened, takeover) = match streams {
+        Ok(()) => {
+            ownership::with_takeover(&ownership::SystemAcl, path, options.take_ownership, || options.open_target(path, passes))
+        }
+        Err(e) => (Err(e), None),
+    };
+    let original_owner = takeover.as_ref().and_then(|takeover| takeover.original_owner.clone());
+    let exclusive_access = opened.is_ok() && options.exclusive_lock.unwrap_or(true);
+    let result = opened.and_then(|mut target| {
+        let algorithm = algorithm.clone();
+        let target = stall_watch::run_watched(
+            options.stall_policy.unwrap_or_default(),
+            &stop_requested,
+            move |stop, progress| {
+                let mut record = FileWipeProgressRecord::default();
+                let decoy = decoy.as_deref();
+                let options = OverwriteOptions { decoy, verification, buffer_size, ..Default::default() };
+                let overwritten = match checkpointer {
+                    Some(checkpointer) => checkpointer.overwrite(&mut target, stop, progress, &mut record, options),
+                    None => overwrite_target_with(&mut target, passes, &algorithm, stop, progress, &mut record, options),
+                };
+                *writer_record.lock().unwrap_or_else(|p| p.into_inner()) = Some(record);
+                overwritten.map(|_| target)
+            },
+            |progress: WipeProgress| {
+                last_progress.set(Some((progress.current_pass, progress.bytes_processed)));
+                emit_progress(progress);
+            },
+            on_stall,
+        )?;
+        wipe_finish::finish_overwritten(
+            target,
+            path,
+            &cancelled,
+            options.finish.unwrap_or_default(),
+            options.journal.as_ref().map(|journal| journal as &dyn wipe_finish::RenameLog),
+            report,
+            &mut |_| {},
+        )
+    });
+
+    if let Err(WipeError::Timeout { seconds }) = &result {
+        log_event("wipe_file_timeout", json!({"path": path.to_string_lossy(), "seconds": seconds}));
+    }
+    let record = recorded.lock().unwrap_or_else(|p| p.into_inner()).take().unwrap_or_else(|| {
+        // Abandoned or never started: the last progress report is the best account left.
+        let pass_count = algorithm.pass_kinds(passes).len() + usize::from(options.decoy.is_some());
+        let mut record = FileWipeProgressRecord::new(pass_count as u32);
+        if let Some((current_pass, bytes_processed)) = last_progress.get() {
+            record.passes_completed = current_pass.saturating_sub(1);
+            record.bytes_of_last_pass = bytes_processed;
+        }
+        record
+    });
+    let flash_media = options.flash_media.iter().any(|root| path.starts_with(root));
+    let skipped = result.is_err() && !cancelled.load(Ordering::SeqCst) && skip.swap(false, Ordering::SeqCst);
+    if skipped {
+        let passes_completed = record.passes_completed;
+        report.files.push(FileWipeOutcome {
+            path: path.to_string_lossy().to_string(),
+            status: FileWipeStatus::SkippedByUser,
+            algorithm: Some(algorithm.clone()),
+            flash_media,
+            recalled_from_remote: false,
+            cow_filesystem: copy_on_write.file_system,
+            nocow_applied: copy_on_write.nocow_applied,
+            ownership_taken: takeover.is_some(),
+            original_owner: original_owner.clone(),
+            link: None,
+            exclusive_access,
+            duration_ms: started.elapsed().as_millis() as u64,
+            bytes_per_second: record.bytes_per_second(),
+            passes: record,
+            companions: Vec::new(),
+        });
+        fs::remove_file(path).map_err(WipeError::Io)?;
+        log_event(
+            "wipe_file_skipped",
+            json!({"operation_id": ctx.operation.id(), "path": path.to_string_lossy(), "passes_completed": passes_completed}),
+        );
+        report.skipped.push(BitBurnError::SkippedByUser { path: path.to_string_lossy().to_string(), passes_completed });
+        ctx.file_ended(report);
+        return Ok(BatchFileEnd::Skipped);
+    }
+    let status = match &result {
+        Ok(()) => FileWipeStatus::Success,
+        Err(WipeError::Timeout { .. }) => FileWipeStatus::Timeout,
+        Err(_) if cancelled.load(Ordering::SeqCst) => FileWipeStatus::Cancelled,
+        Err(_) => FileWipeStatus::Error,
+    };
+    report.files.push(FileWipeOutcome {
+        path: path.to_string_lossy().to_string(),
+        status,
+        algorithm: Some(algorithm.clone()),
+        flash_media,
+        recalled_from_remote: false,
+        cow_filesystem: copy_on_write.file_system,
+        nocow_applied: copy_on_write.nocow_applied,
+        ownership_taken: takeover.is_some(),
+        original_owner,
+        link: None,
+        exclusive_access,
+        duration_ms: started.elapsed().as_millis() as u64,
+        bytes_per_second: record.bytes_per_second(),
+        passes: record,
+        companions: Vec::new(),
+    });
+    ctx.file_ended(report);
+    if let (Err(_), Some((last_pass, bytes_overwritten))) = (&result, last_progress.get()) {
+        if path.exists() && report.interrupted.len() == interrupted_before {
+            report.partially_wiped.push(PartialWipe {
+                path: path.to_string_lossy().to_string(),
+                bytes_overwritten,
+                last_pass,
+            });
+        }
+    }
+    result.map(|()| BatchFileEnd::Wiped)
+}
+
+/// The path a root was turned away for, read from its error; empty when the error names none.
+fn rejected_path(error: &BitBurnError) -> String {
+    match serde_json::to_value(error) {
+        Ok(serde_json::Value::Object(fields)) => {
+            fields.get("path").and_then(|path| path.as_str()).unwrap_or_default().to_string()
+        }
+        _ => String::new(),
+    }
+}
+
+/// Tell the UI straight away that a file failed, bypassing progress coalescing.
+fn report_file_failure<R: Runtime>(ctx: &BatchContext<R>, failure: FileFailure) -> FileFailure {
+    let mut payload = json!({"operation_id": ctx.operation.id()});
+    if let (Some(map), Ok(serde_json::Value::Object(fields))) = (payload.as_object_mut(), serde_json::to_value(&failure)) {
+        map.extend(fields);
+    }
+    ctx.emit_critical("wipe_file_failed", payload);
+    failure
+}
+
+/// Wipe the files under `dir` through `bitburn_core::wipe_directory_files`, logging re-scans and
+/// turning its errors into the ones the UI understands.
+fn wipe_directory_files<W, H>(
+    dir: &Path,
+    scan: Option<TreeScan>,
+    chunk_files: usize,
+    cancel: &CancellationToken,
+    wipe_file: W,
+    after_pass: H,
+) -> Result<DirectoryOutcome, BitBurnError>
+where
+    W: FnMut(&Path, &WalkPosition) -> bool,
+    H: FnMut(u32),
+{
+    match bitburn_core::wipe_directory_files(dir, scan, chunk_files, cancel, wipe_file, after_pass) {
+        Ok(outcome) => {
+            if outcome.rescanned_files > 0 {
+                log_event(
+                    "directory_rescan",
+                    json!({"path": outcome.path, "rescanned_files": outcome.rescanned_files}),
+                );
+            }
+            Ok(outcome)
+        }
+        Err(DirectoryError::Cancelled) => Err(BitBurnError::Cancelled),
+        Err(DirectoryError::Active { rescans, new_files }) => {
+            let path = dir.to_string_lossy().to_string();
+            log_event("directory_active", json!({"path": path, "rescans": rescans, "new_files": new_files}));
+            Err(BitBurnError::DirectoryActive { path, rescans })
+        }
+    }
+}
+
+/// `n` with thousands separators, e.g. `120,000`.
+fn group_digits(n: usize) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Remove a folder whose files have all been wiped, or only its contents when `keep_root` is set,
+/// reporting "Removing directory entries (120,000 / 480,000)" as the phase so a tree of many
+/// small files does not look hung. The batch's overall percentage stays at `overall` meanwhile.
+fn remove_wiped_directory<R: Runtime>(
+    ctx: &BatchContext<R>,
+    path: &Path,
+    root_index: usize,
+    cancel: &CancellationToken,
+    algorithm: &WipeAlgorithm,
+    keep_root: bool,
+    overall: f32,
+) -> Result<TreeRemoval, RemovalCancelled> {
+    let mut emit_progress = ctx.progress_emitter(Some(root_index), move |_| overall);
+    let remove = if keep_root { bitburn_core::empty_tree } else { bitburn_core::remove_tree };
+    remove(path, cancel, |removed, total| {
+        let mut progress = WipeProgress::new(1, total as u64, algorithm.display_name());
+        let phase = format!("Removing directory entries ({} / {})", group_digits(removed), group_digits(total));
+        progress.update(removed as u64, &phase);
+        emit_progress(progress);
+    })
+}
+
+/// Overwrite the named data streams of the folders, and any files left, under `path` before the batch removes them.
+fn wipe_directory_streams(
+    path: &Path,
+    options: &BatchOptions,
+    passes: u32,
+    algorithm: &WipeAlgorithm,
+    cancelled: &Arc<AtomicBool>,
+    report: &mut WipeReport,
+) -> Result<(), BitBurnError> {
+    if options.named_streams != Some(true) {
+        return Ok(());
+    }
+    let cancel = CancellationToken::from(cancelled.clone());
+    named_streams::wipe_tree(path, options.preserve_roots, passes, algorithm, &cancel, &mut report.named_streams)
+}
+
+/// Wipe every root produced by `roots`, streaming so huge selections never sit in memory twice.
+/// `Err` entries were rejected by sanitization upstream and are reported as skips, not failures.
+fn run_wipe_batch<R, I>(
+    ctx: &BatchContext<R>,
+    roots: I,
+    root_count: usize,
+    passes: u32,
+    algorithm: &WipeAlgorithm,
+    options: BatchOptions,
+) -> WipeResult
+where
+    R: Runtime,
+    I: IntoIterator<Item = Result<String, BitBurnError>>,
+{
+    ctx.operation.record_algorithm(algorithm, passes);
+    let journal = options.journal.clone();
+    let mut options = options;
+    let chunk_files = *options.chunk_files.get_or_insert_with(|| {
+        ctx.app_handle.try_state::<SettingsStore>().map_or(DEFAULT_CHUNK_FILES, |settings| settings.get().chunk_files())
+    });
+    let mut spill = OutcomeSpill::new(&ctx.app_handle, ctx.operation.id(), chunk_files);
+    let scan_time = options.scan_time;
+    let wipe_started = std::time::Instant::now();
+    let mut result = wipe_batch_roots(ctx, roots, root_count, passes, algorithm, options, &mut spill);
+    let wipe_time = wipe_started.elapsed();
+    if let Some(journal) = journal {
+        let checkpoints = journal.finish();
+        if let Some(journals) = ctx.app_handle.try_state::<JournalStore>() {
+            journals.retain(checkpoints);
+        }
+    }
+    if let Some(report) = &mut result.report {
+        if !report.interrupted.is_empty() {
+            log_event(
+                "wipe_interrupted_files",
+                json!({"operation_id": ctx.operation.id(), "files": report.interrupted}),
+            );
+        }
+        if !report.partially_wiped.is_empty() {
+            log_event(
+                "partial_wipe_detected",
+                json!({"operation_id": ctx.operation.id(), "count": report.partially_wiped.len()}),
+            );
+            ctx.emit_critical(
+                "partial_wipe_detected",
+                json!({"operation_id": ctx.operation.id(), "partially_wiped": report.partially_wiped}),
+            );
+        }
+        drive_wear::record_finished(&ctx.app_handle, report, None);
+        search_index::purge_finished(&ctx.app_handle, report);
+        spill.finish(report);
+        report.timings = Some(OperationTimings {
+            scan_ms: scan_time.as_millis() as u64,
+            wipe_ms: wipe_time.as_millis() as u64,
+            cleanup_ms: (wipe_started.elapsed() - wipe_time).as_millis() as u64,
+        });
+        ctx.operation.attach_report(report.clone());
+    }
+    // The final snapshot must reach the UI before the command resolves.
+    ctx.progress.finish();
+    ctx.operation.record_result(&result);
+    result
+}
+
+fn wipe_batch_roots<R, I>(
+    ctx: &BatchContext<R>,
+    roots: I,
+    root_count: usize,
+    passes: u32,
+    algorithm: &WipeAlgorithm,
+    options: BatchOptions,
+    spill: &mut OutcomeSpill,
+) -> WipeResult
+where
+    R: Runtime,
+    I: IntoIterator<Item = Result<String, BitBurnError>>,
+{
+    // Retention rules reach the batch without `start_file_wipe`.
+    let policy = match policy::current(&ctx.app_handle).and_then(|policy| {
+        policy.check_command(policy::WIPE_FILES)?;
+        policy.check_algorithm(algorithm, passes)?;
+        for task in &options.tasks {
+            policy.check_algorithm(&task.algorithm, task.passes)?;
+        }
+        Ok(policy)
+    }) {
+        Ok(policy) => policy,
+        Err(e) => {
+            log_event("policy_blocked", json!({"operation_id": ctx.operation.id(), "message": e.to_string()}));
+            return WipeResult::failure(e);
+        }
+    };
+    let cancelled = ctx.operation.cancel_flag();
+    let mut options = options;
+    if let Some(settings) = ctx.app_handle.try_state::<SettingsStore>().map(|store| store.get()) {
+        options.stall_policy.get_or_insert_with(|| StallPolicy::from_settings(&settings));
+        options.checkpoint_policy.get_or_insert_with(|| CheckpointPolicy::from_settings(&settings));
+        options.finish.get_or_insert_with(|| FinishOptions::from_settings(&settings));
+        options.preserve_parent_mtime.get_or_insert(settings.preserve_parent_mtime);
+        options.companion_files.get_or_insert(settings.companion_files);
+        options.exclusive_lock.get_or_insert(settings.exclusive_lock());
+        if options.verification.is_none() {
+            options.verification = verification::from_settings(&settings);
+        }
+        options.named_streams.get_or_insert(settings.wipe_named_streams || settings.obfuscate_file_names);
+    }
+    if options.decoy.is_none() {
+        match decoy::from_settings(&ctx.app_handle) {
+            Ok(decoy) => options.decoy = decoy,
+            Err(e) => {
+                log_event("wipe_decoy_invalid", json!({"operation_id": ctx.operation.id(), "message": e.to_string()}));
+                return WipeResult::failure(e);
+            }
+        }
+    }
+    let mut report = WipeReport::new(ctx.operation.id(), options.source.clone());
+    report.decoy = options.decoy.as_ref().map(|decoy| decoy.summary.clone());
+    report.verification = options.verification;
+    report.consent = options.consent.clone();
+    report.memory_constrained = options.memory;
+    let mut parents = ParentDirectories::new(options.preserve_parent_mtime.unwrap_or(false));
+    let mut total_files = 0;
+    if let Some(original) = &options.retry_of {
+        ctx.operation.link_retry_of(original, &mut report);
+    }
+    let mut failures: Vec<FileFailure> = options
+        .carried_failures
+        .drain(..)
+        .chain(options.read_only_failures.drain(..))
+        .map(|error| report_file_failure(ctx, error.into()))
+        .collect();
+    report.skipped.append(&mut options.carried_skips);
+    report.warnings.append(&mut options.carried_warnings);
+    let mut lost = LostVolumes::from_system();
+    let mut grouping = RootGrouping::default();
+    // Selected files that were already wiped as another selected file's companion.
+    let mut wiped_companions: HashSet<PathBuf> = HashSet::new();
+
+    for (root_index, entry) in roots.into_iter().enumerate() {
+        if cancelled.load(Ordering::SeqCst) {
+            report.warnings.extend(parents.finish());
+            report.roots = grouping.group(&report, failures.len());
+            return cancelled_wipe_result().with_report(report);
+        }
+        spill.spill_if_full(ctx, &mut report, &mut grouping);
+
+        let path_str = match entry {
+            Ok(path_str) => path_str,
+            Err(skip) => {
+                grouping.begin(root_index, &rejected_path(&skip), &report, failures.len());
+                grouping.reject(&skip);
+                report.skipped.push(skip);
+                continue;
+            }
+        };
+        grouping.begin(root_index, &path_str, &report, failures.len());
+        let (algorithm, passes) = wipe_tasks::for_root(&options.tasks, &path_str).unwrap_or((algorithm, passes));
+        // A followed link is wiped as its target, and the link deleted after it.
+        let (path_str, followed_link) = if bitburn_core::is_symlink(Path::new(&path_str)) {
+            match symlinks::resolve(Path::new(&path_str), options.symlink_policy) {
+                Ok(SelectedLink::Follow(target)) => (target.to_string_lossy().to_string(), Some(PathBuf::from(path_str))),
+                Ok(SelectedLink::Delete) => {
+                    match symlinks::delete_selected(Path::new(&path_str)) {
+                        Ok(outcome) => {
+                            total_files += 1;
+                            report.wiped_files.push(outcome.path.clone());
+                            report.files.push(outcome);
+                            ctx.progress.file_completed();
+                        }
+                        Err(e) => {
+                            grouping.reject(&e);
+                            failures.push(report_file_failure(ctx, e.into()));
+                        }
+                    }
+                    continue;
+                }
+                Err(e @ BitBurnError::SymlinkNotSupported { .. }) => {
+                    grouping.reject(&e);
+                    report.skipped.push(e);
+                    continue;
+                }
+                Err(e) => {
+                    log_event("symlink_target_refused", json!({"path": path_str, "message": e.to_string()}));
+                    grouping.reject(&e);
+                    failures.push(report_file_failure(ctx, e.into()));
+                    continue;
+                }
+            }
+        } else {
+            (path_str, None)
+        };
+        let path = Path::new(&path_str);
+
+        if lost.set_aside(path) {
+            continue;
+        }
+
+        if wiped_companions.contains(path) {
+            continue;
+        }
+
+        if !path.exists() {
+            // The whole volume may have gone, not just this root.
+            let missing = WipeError::Io(io::ErrorKind::NotFound.into());
+            if !volume_lost::note_failure(ctx, &mut lost, path, &missing) {
+                let error = BitBurnError::PathNotFound { path: path_str.clone() };
+                grouping.reject(&error);
+                failures.push(error.into());
+            }
+            continue;
+        }
+
+        if let Err(e) = self_protection::check(path) {
+            log_event("self_protection_rejected", json!({"path": path_str}));
+            grouping.reject(&e);
+            failures.push(report_file_failure(ctx, e.into()));
+            continue;
+        }
+
+        if let Err(e) = policy.check_targets(std::slice::from_ref(&path_str), policy::is_removable) {
+            log_event("policy_blocked", json!({"path": path_str}));
+            grouping.reject(&e);
+            failures.push(report_file_failure(ctx, e.into()));
+            continue;
+        }
+
+        if path.is_dir() && !options.volume_root_confirmed(path) {
+            if let Err(e) = volume_root::check(path) {
+                log_event("volume_root_rejected", json!({"path": path_str}));
+                grouping.reject(&e);
+                failures.push(report_file_failure(ctx, e.into()));
+                continue;
+            }
+        }
+
+        if options.locked_skips.contains(path) {
+            report.skipped.push(BitBurnError::FileInUse { path: path_str.clone(), pids: Vec::new() });
+        } else if path.is_file() {
+            let companions = match options.companion_files {
+                Some(true) => companions::find(path),
+                _ => Vec::new(),
+            };
+            for file in std::iter::once(path).chain(companions.iter().map(PathBuf::as_path)) {
+                let is_root = file == path;
+                let file_str = file.to_string_lossy().to_string();
+                if !is_root {
+                    if cancelled.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if options.locked_skips.contains(file) {
+                        report.skipped.push(BitBurnError::FileInUse { path: file_str, pids: Vec::new() });
+                        continue;
+                    }
+                    if lost.set_aside(file) {
+                        continue;
+                    }
+                }
+                let recalled = match remote_tier::check(file, options.allow_recall) {
+                    Ok(recalled) => recalled,
+                    Err(e) => {
+                        if is_root {
+                            grouping.reject(&e);
+                        }
+                        failures.push(report_file_failure(ctx, e.into()));
+                        // A refused selection keeps its companions as they are.
+                        if is_root {
+                            break;
+                        }
+                        continue;
+                    }
+                };
+                let emit_progress = ctx.progress_emitter(Some(root_index), move |fraction| {
+                    batch_percentage(root_index, root_count, fraction)
+                });
+                parents.before_delete(file);
+                spill.spill_if_full(ctx, &mut report, &mut grouping);
+                match wipe_batch_file(ctx, file, passes, algorithm, &options, emit_progress, &mut report) {
+                    Ok(end) => {
+                        if end == BatchFileEnd::Wiped {
+                            total_files += 1;
+                            report.wiped_files.push(file_str);
+                            ctx.progress.file_completed();
+                        }
+                        if !is_root {
+                            wiped_companions.insert(file.to_path_buf());
+                        } else if let Some(journal) = &options.journal {
+                            journal.completed(root_index);
+                        }
+                    }
+                    Err(e) if options.skip_in_use && is_in_use_error(&e) => {
+                        report.skipped.push(BitBurnError::FileInUse { path: file_str, pids: Vec::new() });
+                    }
+                    Err(e) if volume_lost::note_failure(ctx, &mut lost, file, &e) => {}
+                    Err(e) => {
+                        let message = messages::FILE_WIPE_FAILED.render(json!({"path": file_str, "error": e.to_string()}));
+                        let failure = FileFailure::new(BitBurnError::from_wipe_error(e, file), message);
+                        failures.push(report_file_failure(ctx, failure));
+                    }
+                }
+                if recalled {
+                    remote_tier::mark_recalled(&mut report, file);
+                }
+            }
+            report.nest_companions(&path_str, &companions);
+        } else if path.is_dir() {
+            let mut kept_locked = false;
+            let mut kept_remote = false;
+            let cached_scan = options.tree_scans.remove(path);
+            let wiped = wipe_directory_files(
+                path,
+                cached_scan,
+                options.chunk_files.unwrap_or(DEFAULT_CHUNK_FILES),
+                &CancellationToken::from(cancelled.clone()),
+                |file, position| {
+                    if lost.set_aside(file) {
+                        return false;
+                    }
+                    if options.locked_skips.contains(file) {
+                        report.skipped.push(BitBurnError::FileInUse {
+                            path: file.to_string_lossy().to_string(),
+                            pids: Vec::new(),
+                        });
+                        kept_locked = true;
+                        return false;
+                    }
+                    let recalled = match remote_tier::check(file, options.allow_recall) {
+                        Ok(recalled) => recalled,
+                        Err(e) => {
+                            failures.push(report_file_failure(ctx, e.into()));
+                            kept_remote = true;
+                            return false;
+                        }
+                    };
+                    let (files_done, files_total) = (position.files_done, position.files_total.max(1));
+                    let mut emit_directory_progress = ctx.progress_emitter(Some(root_index), move |fraction| {
+                        let directory_fraction = (files_done as f32 + fraction) / files_total as f32;
+                        batch_percentage(root_index, root_count, directory_fraction)
+                    });
+                    let position = position.clone();
+                    let emit_progress = move |mut progress: WipeProgress| {
+                        progress.walk = Some(position.clone());
+                        emit_directory_progress(progress)
+                    };
+                    parents.before_delete(file);
+                    spill.spill_if_full(ctx, &mut report, &mut grouping);
+
+                    let wiped = match wipe_batch_file(ctx, file, passes, algorithm, &options, emit_progress, &mut report) {
+                        Ok(BatchFileEnd::Skipped) => false,
+                        Ok(BatchFileEnd::Wiped) => {
+                            total_files += 1;
+                            report.wiped_files.push(file.to_string_lossy().to_string());
+                            ctx.progress.file_completed();
+                            true
+                        }
+                        Err(e) if options.skip_in_use && is_in_use_error(&e) => {
+                            report.skipped.push(BitBurnError::FileInUse {
+                                path: file.to_string_lossy().to_string(),
+                                pids: Vec::new(),
+                            });
+                            false
+                        }
+                        Err(e) if volume_lost::note_failure(ctx, &mut lost, file, &e) => false,
+                        Err(e) => {
+                            let message = messages::FILE_WIPE_FAILED
+                                .render(json!({"path": file.to_string_lossy(), "error": e.to_string()}));
+                            let failure = FileFailure::new(BitBurnError::from_wipe_error(e, file), message);
+                            failures.push(report_file_failure(ctx, failure));
+                            false
+                        }
+                    };
+                    if recalled {
+                        remote_tier::mark_recalled(&mut report, file);
+                    }
+                    wiped
+                },
+                |_| {},
+            );
+
+            match wiped {
+                Ok(outcome) => {
+                    report.directories.push(outcome);
+                    if lost.is_lost(path) {
+                        // Removing entries from a volume that is gone can only fail, once per entry.
+                        log_event("directory_removal_skipped", json!({"path": path_str, "reason": "volume_lost"}));
+                    } else if kept_locked {
+                        // Removing the tree would plain-delete the locked files the user chose to keep.
+                        log_event("directory_kept_for_locked_files", json!({"path": path_str}));
+                    } else if kept_remote {
+                        // Deleting a placeholder can delete the remote copy the user chose not to recall.
+                        log_event("directory_kept_for_remote_files", json!({"path": path_str}));
+                    } else if let Err(e) = wipe_directory_streams(path, &options, passes, algorithm, &cancelled, &mut report) {
+                        // Removing the tree would delete the streams that could not be overwritten.
+                        log_event("directory_kept_for_named_streams", json!({"path": path_str}));
+                        failures.push(report_file_failure(ctx, e.into()));
+                    } else {
+                        if options.preserve_roots {
+                            parents.before_emptying(path);
+                        } else {
+                            parents.before_delete(path);
+                        }
+                        let cancel = CancellationToken::from(cancelled.clone());
+                        let overall = batch_percentage(root_index, root_count, 1.0);
+                        let keep_root = options.preserve_roots;
+                        let removed =
+                            remove_wiped_directory(ctx, path, root_index, &cancel, algorithm, keep_root, overall);
+                        if let (true, Ok(removal)) = (options.preserve_roots, &removed) {
+                            report.preserved_roots.push(PreservedRoot {
+                                path: path_str.clone(),
+                                subdirectories_removed: removal.directories_removed,
+                                entries_removed: removal.removed,
+                            });
+                        }
+                        match removed {
+                            Ok(removal) if removal.failed.is_empty() => {
+                                if let Some(journal) = &options.journal {
+                                    journal.completed(root_index);
+                                }
+                            }
+                            Ok(removal) => {
+                                let (first, e) = &removal.failed[0];
+                                let error = format!(
+                                    "{} of {} entries could not be removed, including {}: {}",
+                                    removal.failed.len(),
+                                    removal.total,
+                                    first.display(),
+                                    e
+                                );
+                                let message = messages::DIRECTORY_REMOVE_FAILED.render(json!({"path": path_str, "error": error}));
+                                failures.push(FileFailure::new(BitBurnError::io(e, Some(first)), message));
+                            }
+                            Err(stopped) => {
+                                log_event(
+                                    "directory_remove_cancelled",
+                                    json!({"path": path_str, "removed": stopped.removed, "total": stopped.total}),
+                                );
+                                report.warnings.extend(parents.finish());
+                                report.roots = grouping.group(&report, failures.len());
+                                return cancelled_wipe_result().with_report(report);
+                            }
+                        }
+                    }
+                }
+                Err(BitBurnError::Cancelled) => {
+                    report.warnings.extend(parents.finish());
+                    report.roots = grouping.group(&report, failures.len());
+                    return cancelled_wipe_result().with_report(report);
+                }
+                Err(e) => failures.push(e.into()),
+            }
+        }
+
+        if let Some(link) = followed_link {
+            if let Err(e) = symlinks::finish_followed(&link, path, &mut report.files) {
+                failures.push(report_file_failure(ctx, e.into()));
+            }
+        }
+    }
+
+    report.roots = grouping.group(&report, failures.len());
+    failures.extend(lost.into_errors().into_iter().map(FileFailure::from));
+    report.files_wiped = total_files;
+    // Deletions are durable before the result is reported.
+    report.warnings.extend(parents.finish());
+    batch_result(report, failures, total_files, cancelled.load(Ordering::SeqCst))
+}
+
+/// The result of a file batch that wiped `total_files` files and ran into `failures`.
+fn batch_result(mut report: WipeReport, failures: Vec<FileFailure>, total_files: usize, cancelled: bool) -> WipeResult {
+    report.failures = failures.iter().map(|failure| failure.error.clone()).collect();
+    let skipped = report.skipped.len();
+    let copy_on_write: Vec<_> = report
+        .files
+        .iter()
+        .flat_map(FileWipeOutcome::with_companions)
+        .filter_map(|file| file.cow_filesystem)
+        .collect();
+    let cow_file_systems = copy_on_write::names(copy_on_write.iter().copied()).join(", ");
+    if !copy_on_write.is_empty() {
+        report.warnings.push(
+            messages::COPY_ON_WRITE_WARNING
+                .render(json!({"count": copy_on_write.len(), "file_systems": cow_file_systems}))
+                .text,
+        );
+    }
+    if cancelled {
+        let result = cancelled_wipe_result().with_report(report);
+        log_event("wipe_files_end", json!({"status": "cancelled", "count": total_files, "errors": failures.len()}));
+        result
+    } else if failures.is_empty() {
+        let message = if !copy_on_write.is_empty() {
+            messages::FILES_WIPED_ON_COPY_ON_WRITE.render(json!({
+                "count": total_files,
+                "skipped": skipped,
+                "cow_count": copy_on_write.len(),
+                "file_systems": cow_file_systems,
+            }))
+        } else if skipped > 0 {
+            messages::FILES_WIPED_WITH_SKIPS.render(json!({"count": total_files, "skipped": skipped}))
+        } else {
+            messages::FILES_WIPED.render(json!({"count": total_files}))
+        };
+        let result = WipeResult::success(message).with_report(report);
+        log_event("wipe_files_end", json!({"status": "success", "count": total_files, "skipped": skipped}));
+        result
+    } else {
+        let details = failures.iter().map(|f| f.message.text.as_str()).collect::<Vec<_>>().join("\n");
+        let message = if skipped > 0 {
+            messages::FILES_WIPED_WITH_SKIPS_AND_ERRORS.render(json!({
+                "count": total_files,
+                "skipped": skipped,
+                "errors": failures.len(),
+                "details": details,
+            }))
+        } else {
+            messages::FILES_WIPED_WITH_ERRORS.render(json!({
+                "count": total_files,
+                "errors": failures.len(),
+                "details": details,
+            }))
+        };
+        log_event(
+            "wipe_files_end",
+            json!({"status": "partial", "count": total_files, "errors": failures.len(), "skipped": skipped}),
+        );
+        WipeResult {
+            success: false,
+            message,
+            error: None,
+            failures,
+            report: Some(report),
+        }
+    }
+}
+
+/// Securely wipe files or folders using the selected algorithm.
+/// Runs in a blocking task to avoid UI stalls and streams progress to the main window.
+/// Folders that are volume roots are refused unless listed in `confirmation.volume_roots`, which
+/// the UI only does for each one after the user has typed its path to confirm.
+/// With `choices.preserve_roots` selected folders are emptied but kept, e.g. for shared drop folders
+/// cleaned out every night, and `choices.companion_files` overrides the setting of that name.
+/// Files stored in a remote or archive tier are refused unless `choices.allow_recall` is set.
+/// `choices.take_ownership` takes over files whose ACL denies access, and is refused unless elevated.
+/// `choices.symlink_policy` says what becomes of selected symbolic links; they are refused by default.
+/// While `confirm_before_wipe` is on, `confirmation.consent_token` must cover `paths`.
+/// Instead of `paths`, `passes` and `algorithm`, `tasks` gives each path its own algorithm and
+/// passes, see `wipe_tasks`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn wipe_files<R: Runtime>(
+    window: tauri::Window<R>,
+    paths: Option<Vec<String>>,
+    passes: Option<u32>,
+    algorithm: Option<WipeAlgorithm>,
+    lock_policy: Option<LockPolicy>,
+    choices: Option<FileWipeChoices>,
+    confirmation: Option<WipeConfirmation>,
+    tasks: Option<Vec<WipeTask>>,
+) -> Result<WipeResult, BitBurnError> {
+    let request = wipe_tasks::request(paths, passes, algorithm, tasks)?;
+    let registry = window.state::<OperationRegistry>();
+    let choices = choices.unwrap_or_default();
+    if choices.take_ownership && !platform::elevation::is_elevated() {
+        return Err(BitBurnError::ElevationRequired {
+            reason: "Taking ownership of files requires administrator rights".to_string(),
+        });
+    }
+    let confirmation = confirmation.unwrap_or_default();
+    let consent = consent::check(window.app_handle(), confirmation.consent_token.as_deref(), &request.paths)?;
+    let options = BatchOptions {
+        lock_policy,
+        confirmed_volume_roots: confirmation.volume_roots,
+        preserve_roots: choices.preserve_roots,
+        companion_files: choices.companion_files,
+        allow_recall: choices.allow_recall,
+        take_ownership: choices.take_ownership,
+        symlink_policy: choices.symlink_policy,
+        consent,
+        tasks: request.tasks,
+        ..Default::default()
+    };
+    start_file_wipe(
+        window.app_handle().clone(),
+        window.label().to_string(),
+        &registry,
+        request.paths,
+        request.passes,
+        request.algorithm,
+        options,
+    )
     .await
-    .map_err(|e| format!("wipe_files task join error: {}", e))?;
+}
 
-    join_result
+/// How a file wipe treats what surrounds the selection, chosen per operation in the UI.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct FileWipeChoices {
+    /// Empty selected folders but leave the folders themselves in place.
+    #[serde(default)]
+    preserve_roots: bool,
+    /// Also wipe the companion files beside each selected file; `None` follows the setting.
+    #[serde(default)]
+    companion_files: Option<bool>,
+    /// Recall and wipe files whose data is in remote or archive storage instead of refusing them.
+    #[serde(default)]
+    allow_recall: bool,
+    /// Take ownership of files whose ACL denies access and try again; needs an elevated instance.
+    #[serde(default)]
+    take_ownership: bool,
+    /// Refuse selected symbolic links, delete only the link, or wipe what it points at.
+    #[serde(default)]
+    symlink_policy: SymlinkPolicy,
+}
+
+/// Wipe files left partially overwritten by a cancelled or failed batch.
+/// Each file restarts from pass 1, since a pass that was interrupted cannot be trusted, unless it
+/// recorded a checkpoint: then it carries on from there with the checkpoint's algorithm and
+/// passes, whatever `algorithm` and `passes` say, which only apply to files without one. The paths
+/// are sanitized as a selection is, those refused reported as failures, and the rest confirmed
+/// through `consent_token` or a prompt shown now.
+#[tauri::command]
+async fn resume_partial_wipes<R: Runtime>(
+    window: tauri::Window<R>,
+    registry: tauri::State<'_, OperationRegistry>,
+    paths: Vec<String>,
+    passes: u32,
+    algorithm: WipeAlgorithm,
+    consent_token: Option<String>,
+) -> Result<WipeResult, BitBurnError> {
+    let (paths, refused) = protected::sanitize_stored_targets(&paths, SymlinkPolicy::Reject);
+    for e in &refused {
+        log_event("resume_target_refused", json!({"code": e.code(), "message": e.to_string()}));
+    }
+    let prompt = messages::CONFIRM_RESUME_WIPE.render(json!({
+        "count": paths.len(),
+        "algorithm": format!("{:?}", algorithm),
+        "paths": consent::list_targets(&paths),
+    }));
+    let consent =
+        consent::confirm_targets_async(window.app_handle().clone(), consent_token, prompt.text, paths.clone()).await?;
+    let checkpoints = window.state::<JournalStore>().take_retained(&paths);
+    let options = BatchOptions {
+        source: Some("resume".to_string()),
+        carried_failures: refused,
+        tasks: checkpoint_tasks(&checkpoints),
+        checkpoints,
+        consent: Some(consent),
+        ..Default::default()
+    };
+    start_file_wipe(window.app_handle().clone(), window.label().to_string(), &registry, paths, passes, algorithm, options)
+        .await
+}
+
+/// A task per checkpointed file, so it carries on with the algorithm and passes it was started with.
+fn checkpoint_tasks(checkpoints: &BTreeMap<String, FileCheckpoint>) -> Vec<WipeTask> {
+    checkpoints
+        .iter()
+        .map(|(path, checkpoint)| WipeTask {
+            path: path.clone(),
+            algorithm: checkpoint.algorithm.clone(),
+            passes: checkpoint.passes,
+        })
+        .collect()
+}
+
+/// Stop a batch before anything is touched when a requested folder is a whole volume.
+fn refuse_volume_roots(paths: &[String], options: &BatchOptions) -> Option<WipeResult> {
+    let roots = VolumeRoots::from_system();
+    let error = paths
+        .iter()
+        .map(Path::new)
+        .filter(|path| path.is_dir() && !options.volume_root_confirmed(path))
+        .find_map(|path| roots.check(path).err())?;
+    log_event("volume_root_rejected", json!({"message": error.to_string()}));
+    Some(WipeResult::failure(error))
+}
+
+/// Shared body of `wipe_files`, also used when a context-menu invocation starts a wipe without the UI.
+async fn start_file_wipe<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    window_label: String,
+    registry: &OperationRegistry,
+    paths: Vec<String>,
+    passes: u32,
+    algorithm: WipeAlgorithm,
+    options: BatchOptions,
+) -> Result<WipeResult, BitBurnError> {
+    policy::check_wipe(&app_handle, policy::WIPE_FILES, &algorithm, passes, &paths)?;
+    for task in &options.tasks {
+        policy::check_wipe(&app_handle, policy::WIPE_FILES, &task.algorithm, task.passes, std::slice::from_ref(&task.path))?;
+    }
+    let operation = OperationGuard::begin(&app_handle, registry, OperationKind::WipeFiles, summarize_targets(&paths));
+    let ctx = BatchContext::new(app_handle, window_label, operation);
+
+    let join_result = spawn_blocking(move || {
+        let _log_scope = ctx.operation.enter_log_scope();
+        log_event(
+            "wipe_files_start",
+            json!({"operation_id": ctx.operation.id(), "count": paths.len(), "algorithm": format!("{:?}", algorithm), "passes": passes}),
+        );
+        wipe_selection(&ctx, paths, passes, &algorithm, options)
+    })
+    .await
+    .map_err(|e| BitBurnError::internal(format!("wipe_files task join error: {}", e)))?;
+
+    Ok(join_result)
+}
+
+/// The pre-flight checks and the batch for a selection, on the thread that runs the wipe: volume
+/// roots, the volume slot, read-only volumes, flash media and the lock scan, then the journal.
+fn wipe_selection<R: Runtime>(
+    ctx: &BatchContext<R>,
+    paths: Vec<String>,
+    passes: u32,
+    algorithm: &WipeAlgorithm,
+    options: BatchOptions,
+) -> WipeResult {
+    let paths = selection::normalize_logged(paths, ctx.operation.id());
+    if let Some(result) = refuse_volume_roots(&paths, &options) {
+        ctx.progress.finish();
+        ctx.operation.record_result(&result);
+        return result;
+    }
+    let volumes = scheduler::volumes_of(&paths);
+    let Some(_slot) = scheduler::acquire(&ctx.app_handle, ctx.operation.id(), volumes, &ctx.operation.cancel_flag()) else {
+        let result = cancelled_wipe_result();
+        ctx.progress.finish();
+        ctx.operation.record_result(&result);
+        return result;
+    };
+    let mut options = options;
+    options.memory = memory_budget::check(&ctx.app_handle, ctx.operation.id());
+    let scan_started = std::time::Instant::now();
+    let (paths, read_only_failures) = read_only::preflight(ctx, paths);
+    options.read_only_failures = read_only_failures;
+    if let Some(result) = flash_media::preflight(ctx, &paths, &mut options) {
+        ctx.progress.finish();
+        ctx.operation.record_result(&result);
+        return result;
+    }
+    if let Some(result) = lock_scan::preflight(ctx, &paths, &mut options) {
+        ctx.progress.finish();
+        ctx.operation.record_result(&result);
+        return result;
+    }
+    options.scan_time = scan_started.elapsed();
+    let root_count = paths.len();
+    options.journal = ctx.app_handle.try_state::<JournalStore>().and_then(|journals| {
+        let spec = BatchSpec {
+            paths: paths.clone(),
+            passes,
+            algorithm: algorithm.clone(),
+            source: options.source.clone(),
+            preserve_roots: options.preserve_roots,
+            symlink_policy: options.symlink_policy,
+            tasks: options.tasks.clone(),
+        };
+        journals.begin(ctx.operation.id(), spec, std::mem::take(&mut options.checkpoints))
+    });
+    run_wipe_batch(ctx, paths.into_iter().map(Ok), root_count, passes, algorithm, options)
+}
+
+/// The state every command and batch reads, and the protections set up before anything is wiped;
+/// shared by the desktop app and headless runs.
+fn init_state<R: Runtime>(app: &tauri::App<R>) {
+    app.manage(audit_log::init_audit_log(app.handle()));
+    app.manage(OperationRegistry::default());
+    app.manage(diagnostics::Diagnostics::default());
+    app.manage(settings::init_settings(app.handle()));
+    app.manage(policy::init_policy(app.handle()));
+    app.manage(webhook::WebhookNotifier::default());
+    app.manage(signing::init_signer(app.handle()));
+    app.manage(retention::RetentionState::default());
+    app.manage(calibration::CalibrationState::default());
+    app.manage(self_test::SelfTestState::default());
+    app.manage(range_wipe::RangeLocks::default());
+    app.manage(progress_window::ProgressWindow::default());
+    app.manage(journal::init_journal_store(app.handle()));
+    app.manage(outcomes::init_outcome_store(app.handle()));
+    app.manage(context_policy::ContextWipeQueue::default());
+    app.manage(context_policy::ContextPayloads::default());
+    app.manage(FreeSpaceEstimates::default());
+    app.manage(FreeSpaceSamples::default());
+    app.manage(drive_wear::init_wear_store(app.handle()));
+    app.manage(volume_history::init_history_store(app.handle()));
+    app.manage(performance::init_timing_history(app.handle()));
+    app.manage(emergency_stop::EmergencyShortcut::default());
+    app.manage(ConsentStore::default());
+    app.manage(CancelledFills::default());
+    app.manage(scheduler::JobScheduler::default());
+    app.manage(theme::ThemeState::default());
+    self_protection::init(app.handle());
+    scratch::init(app.handle());
 }
 
 fn main() {
@@ -901,35 +2108,130 @@ fn main() {
     if let Some(code) = process_cli_side_effects(&args, log_event) {
         std::process::exit(code);
     }
+    let context = tauri::generate_context!();
+    if headless::is_requested(&args) {
+        std::process::exit(headless::run_from_args(&args, context));
+    }
 
     let launch_hidden = args.iter().any(|arg| arg == AUTOSTART_FLAG);
     let initial_args = args.clone();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_single_instance::init(|app, argv, _| {
-            handle_context_invocation(&app.app_handle(), &argv);
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            handle_context_invocation(&app.app_handle(), &argv, Path::new(&cwd));
         }))
         .invoke_handler(tauri::generate_handler![
             validate_drive_path,
             show_confirmation_dialog,
+            consent::record_webview_confirmation,
             execute_free_space_wipe,
+            folder_free_space::free_space_wipe_for_path,
             wipe_files,
+            resume_partial_wipes,
+            retry::retry_failed,
+            range_wipe::wipe_file_range,
+            manifest::wipe_from_manifest,
+            glob_targets::expand_globs,
+            symlinks::sanitize_paths,
+            presets::list_cleanup_presets,
+            presets::wipe_preset,
+            retention::list_retention_rules,
+            retention::set_retention_rules,
+            retention::cancel_retention_run,
+            retention::get_last_retention_run,
+            journal::get_interrupted_operations,
+            journal::resume_interrupted_operation,
+            journal::discard_interrupted_operation,
+            calibration::calibrate_algorithms,
+            calibration::cancel_calibration,
+            self_test::run_self_test,
+            self_test::cancel_self_test,
             register_context_menu,
             unregister_context_menu,
+            repair_context_menu,
             get_context_menu_status,
             register_autostart,
             unregister_autostart,
             get_autostart_status,
-            platform_info
+            platform::send_to::register_sendto_shortcut,
+            platform::send_to::unregister_sendto_shortcut,
+            platform::send_to::get_sendto_status,
+            context_policy::get_context_menu_behavior,
+            context_policy::set_context_menu_behavior,
+            context_policy::run_context_wipe,
+            progress_window::show_progress_window,
+            progress_window::hide_progress_window,
+            context_policy::get_context_queue,
+            context_policy::take_context_queue,
+            platform_info,
+            capabilities::probe_capabilities,
+            recommend::recommend_algorithm,
+            recommend::recommend_tasks,
+            drive_wear::get_drive_wear_contribution,
+            drive_wear::set_wear_warning_threshold,
+            scheduler::set_max_parallel_jobs,
+            volume_history::get_volume_wipe_history,
+            file_checkpoint::set_checkpointing,
+            decoy::set_decoy_pattern,
+            consent::set_confirm_before_wipe,
+            companions::set_companion_files,
+            named_streams::set_wipe_named_streams,
+            lock_scan::set_exclusive_lock,
+            verification::set_verify_mode,
+            search_index::get_search_index_status,
+            search_index::set_purge_search_index,
+            cancelled_fill::finalize_cancelled_free_space,
+            messages::get_message_catalog,
+            estimate_free_space_wipe,
+            free_space_sample::sample_free_space,
+            get_active_operations,
+            get_operation,
+            operations::cancel_operation,
+            operations::skip_current_file,
+            operations::get_operation_log,
+            diagnostics::get_diagnostics,
+            operations::export_wipe_report,
+            outcomes::get_operation_outcomes,
+            signing::get_signing_public_key,
+            signing::set_report_signing,
+            signing::rotate_signing_key,
+            signing::verify_wipe_report,
+            audit_log::verify_audit_log,
+            webhook::get_webhook_status,
+            webhook::configure_webhook,
+            ui::reset_window_layout,
+            ui::set_reopen_on_completion,
+            completion_alert::set_completion_alert,
+            theme::get_system_theme,
+            performance::get_performance_summary,
+            config::export_configuration,
+            config::import_configuration,
+            settings::get_settings,
+            emergency_stop::configure_emergency_shortcut,
+            status_file::get_status_file_path,
+            status_file::set_status_file,
+            policy::get_effective_policy
         ])
         .setup(move |app| {
-            handle_context_invocation(&app.app_handle(), &initial_args);
+            init_state(app);
+            retention::spawn_scheduler(app.handle());
+            status_file::spawn_writer(app.handle());
+            policy::spawn_revalidation(app.handle());
+            emergency_stop::register_from_settings(app.handle());
+            handle_context_invocation(&app.app_handle(), &initial_args, &std::env::current_dir().unwrap_or_default());
+            completion_alert::listen(app.handle());
             ui::init_ui(&app.app_handle(), launch_hidden)?;
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(context)
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                app.state::<CancelledFills>().release_all();
+            }
+        });
 }
 
 #[cfg(test)]
@@ -941,13 +2243,18 @@ mod tests {
     use std::time::{SystemTime, UNIX_EPOCH};
     use std::thread;
     use std::time::Duration;
+    use bitburn_core::{secure_wipe_file, MAX_DIRECTORY_RESCANS};
+    use crate::test_support::{batch_context, cleanup_test_dir, create_test_dir};
     use crate::platform::context_menu::{
         collect_context_paths,
         sanitize_context_paths,
+        sanitize_forwarded_paths,
         enable_context_menu,
         disable_context_menu,
         is_context_menu_enabled,
     };
+    #[cfg(windows)]
+    use crate::platform::context_menu::{enable_context_menu_with, registered_verbs, RegistryWrite, VerbProblem};
 
     fn get_unique_id() -> u128 {
         thread::sleep(Duration::from_millis(10)); // Ensure unique timestamps
@@ -957,16 +2264,6 @@ mod tests {
             .as_nanos()
     }
 
-    fn create_test_dir() -> io::Result<PathBuf> {
-        let unique_id = get_unique_id();
-        let test_dir = std::env::temp_dir().join(format!("BitBurn_test_{}", unique_id));
-        fs::create_dir_all(&test_dir)?;
-        println!("Created test directory: {:?}", test_dir);
-        // Add a small delay to ensure directory is fully created
-        thread::sleep(Duration::from_millis(50));
-        Ok(test_dir)
-    }
-
     fn create_test_file(dir: &Path, content: &[u8]) -> io::Result<PathBuf> {
         let unique_id = get_unique_id();
         let file_path = dir.join(format!("test_file_{}", unique_id));
@@ -1025,15 +2322,47 @@ mod tests {
     }
 
     #[test]
-    fn collect_context_paths_splits_multi_value_argument() {
+    fn collect_context_paths_accepts_many_send_to_arguments() {
+        let mut args = vec!["BitBurn.exe".to_string(), "--context-wipe".to_string()];
+        let expected: Vec<String> = (1..=12)
+            .map(|i| format!("C:/Users/alex/Documents/report {:02}.docx", i))
+            .collect();
+        args.extend(expected.iter().cloned());
+
+        assert_eq!(collect_context_paths(&args), expected);
+    }
+
+    #[test]
+    fn collect_context_paths_keeps_existing_paths_with_separators() {
+        let dir = create_test_dir().unwrap();
+        let odd = dir.join("budget;final v2.txt");
+        File::create(&odd).unwrap();
+        let odd = odd.to_string_lossy().to_string();
+
         let args = vec![
             "BitBurn.exe".to_string(),
             "--context-wipe".to_string(),
-            "C:/one.txt|D:/two.txt;E:/three.txt\nF:/four.txt".to_string(),
+            odd.clone(),
+            "C:/one.txt;D:/two.txt".to_string(),
         ];
-
-        let collected = collect_context_paths(&args);
-        assert_eq!(collected, vec![
+        assert_eq!(collect_context_paths(&args), vec![
+            odd,
+            "C:/one.txt".to_string(),
+            "D:/two.txt".to_string(),
+        ]);
+        cleanup_test_dir(&dir);
+    }
+
+    #[test]
+    fn collect_context_paths_splits_multi_value_argument() {
+        let args = vec![
+            "BitBurn.exe".to_string(),
+            "--context-wipe".to_string(),
+            "C:/one.txt|D:/two.txt;E:/three.txt\nF:/four.txt".to_string(),
+        ];
+
+        let collected = collect_context_paths(&args);
+        assert_eq!(collected, vec![
             "C:/one.txt".to_string(),
             "D:/two.txt".to_string(),
             "E:/three.txt".to_string(),
@@ -1051,11 +2380,74 @@ mod tests {
             valid_file.to_string_lossy().to_string(),
             "\\\\server\\share\\file.txt".to_string(),
             missing.to_string_lossy().to_string(),
-        ]);
+            dir.to_string_lossy().to_string(),
+        ], None);
 
-        assert_eq!(payload.paths.len(), 1);
+        assert_eq!(payload.paths.len(), 2);
         assert_eq!(payload.invalid.len(), 2);
         assert!(payload.paths[0].contains("test_file_"));
+        assert!(payload.volume_roots.is_empty(), "an ordinary folder is not tagged");
+        cleanup_test_dir(&dir);
+    }
+
+    #[test]
+    fn forwarded_paths_unreachable_across_elevation_are_reported_as_a_mismatch() {
+        use crate::platform::elevation::LaunchContext;
+
+        let standard = LaunchContext { elevated: false, uid: None };
+        let elevated = LaunchContext { elevated: true, uid: None };
+        // The elevated instance cannot see the user's mapped Z: drive.
+        let probe = |raw: &str| {
+            if raw.starts_with("Z:") {
+                Err(BitBurnError::PathNotFound { path: raw.to_string() })
+            } else if raw.starts_with("\\\\") {
+                Err(BitBurnError::NetworkPathNotSupported { path: raw.to_string() })
+            } else {
+                Ok(raw.replace('/', "\\"))
+            }
+        };
+        let raw = vec![
+            "C:/Users/me/a.txt".to_string(),
+            "Z:\\share\\b.txt".to_string(),
+            "\\\\server\\c.txt".to_string(),
+        ];
+
+        let payload = sanitize_forwarded_paths(raw.clone(), Some(standard), elevated, probe, |_| {});
+        assert_eq!(payload.paths, vec!["C:\\Users\\me\\a.txt".to_string()]);
+        assert_eq!(
+            payload.invalid,
+            vec![
+                BitBurnError::ElevationContextMismatch { path: "Z:\\share\\b.txt".into() }.to_string(),
+                BitBurnError::NetworkPathNotSupported { path: "\\\\server\\c.txt".into() }.to_string(),
+            ]
+        );
+        let mismatch = payload.elevation_mismatch.expect("the mismatch is included in the payload");
+        assert_eq!((mismatch.sender, mismatch.receiver), (standard, elevated));
+
+        let same = sanitize_forwarded_paths(raw.clone(), Some(elevated), elevated, probe, |_| {});
+        assert!(same.elevation_mismatch.is_none());
+        assert!(same.invalid[0].starts_with("Path not found"), "{}", same.invalid[0]);
+        let unrecorded = sanitize_forwarded_paths(raw, None, elevated, probe, |_| {});
+        assert!(unrecorded.elevation_mismatch.is_none(), "entries registered before the flag carry no sender");
+    }
+
+    #[test]
+    fn sanitize_context_paths_rejects_bitburns_own_executable() {
+        let dir = create_test_dir().expect("should create temp dir");
+        let valid_file = create_test_file(&dir, b"test").expect("should create file");
+        let exe = std::env::current_exe().unwrap();
+        let exe_dir = exe.parent().unwrap().to_path_buf();
+        let sneaky = exe_dir.join("..").join(exe_dir.file_name().unwrap()).join(exe.file_name().unwrap());
+
+        let payload = sanitize_context_paths(vec![
+            valid_file.to_string_lossy().to_string(),
+            sneaky.to_string_lossy().to_string(),
+        ], None);
+
+        assert_eq!(payload.paths.len(), 1);
+        assert_eq!(payload.invalid.len(), 1);
+        assert!(payload.invalid[0].contains("BitBurn's own"));
+        cleanup_test_dir(&dir);
     }
 
     #[cfg(windows)]
@@ -1071,238 +2463,1238 @@ mod tests {
 
         enable_context_menu(&dummy_exe).expect("should write context menu keys");
         assert!(is_context_menu_enabled().unwrap());
+        let verbs = registered_verbs();
+        assert_eq!(verbs.len(), 3);
+        assert!(verbs.iter().all(|verb| verb.has_placeholder && verb.command.is_some()), "{:?}", verbs);
+
+        // A folder command without `%V` is pinpointed, and registering again from scratch repairs it.
+        let hkcu = winreg::RegKey::predef(winreg::enums::HKEY_CURRENT_USER);
+        let folder_command = format!("{}\\Directory\\shell\\BitBurn\\command", temp_root);
+        hkcu.create_subkey(&folder_command).unwrap().0.set_value("", &"\"C:/BitBurn/BitBurn.exe\" --context-wipe".to_string()).unwrap();
+        let verbs = registered_verbs();
+        assert_eq!(verbs[1].verb, "folders");
+        assert!(!verbs[1].has_placeholder);
+        assert_ne!(verbs[1].problem, None);
+        assert!(verbs[0].has_placeholder, "the file verb is untouched");
+        enable_context_menu(&dummy_exe).expect("should rewrite context menu keys");
+        assert!(registered_verbs().iter().all(|verb| verb.has_placeholder));
+        assert!(registered_verbs().iter().all(|verb| verb.problem != Some(VerbProblem::NoPlaceholder)));
 
         disable_context_menu().expect("should remove context menu keys");
         assert!(!is_context_menu_enabled().unwrap());
 
+        // The Directory key write fails after the `*` entry was written: nothing may be left behind.
+        let err = enable_context_menu_with(&dummy_exe, |write| match write {
+            RegistryWrite::CreateKey { key } if key.contains("\\Directory\\") => {
+                Err(std::io::Error::other("injected failure"))
+            }
+            RegistryWrite::CreateKey { key } => hkcu.create_subkey(key).map(|_| ()),
+            RegistryWrite::SetValue { key, name, value } => hkcu.create_subkey(key)?.0.set_value(*name, value),
+        })
+        .expect_err("registration should fail");
+        assert!(err.to_string().contains("Directory"), "{}", err);
+        assert!(!is_context_menu_enabled().unwrap());
+        for leftover in ["*\\shell\\BitBurn", "Directory\\shell\\BitBurn"] {
+            let key = format!("{}\\{}", temp_root, leftover);
+            assert!(hkcu.open_subkey(&key).is_err(), "stray key {}", key);
+        }
+        let _ = hkcu.delete_subkey_all(&temp_root);
+
         // Cleanup env override
         std::env::remove_var("BITBURN_CONTEXT_ROOT");
     }
 
     #[test]
     fn validate_drive_path_rejects_non_root() {
-        let temp_dir = std::env::temp_dir();
+        // A folder below the temp directory, which may itself be a mount point (tmpfs on Linux).
+        let temp_dir = create_test_dir().unwrap();
         let result = validate_drive_path_internal(temp_dir.as_path());
         assert!(matches!(result, Err(DriveValidationError::NotDriveRoot)));
+        cleanup_test_dir(&temp_dir);
+    }
+
+    /// A size-capped tmpfs, mounted directly or with `sudo -n`, and unmounted when dropped.
+    #[cfg(target_os = "linux")]
+    struct Tmpfs {
+        path: PathBuf,
+    }
+
+    #[cfg(target_os = "linux")]
+    impl Tmpfs {
+        /// `None`, after saying why, when this process cannot mount one.
+        fn mount(options: &str) -> Option<Self> {
+            let path = create_test_dir().ok()?;
+            let mount = ["mount", "-t", "tmpfs", "-o", options, "tmpfs", path.to_str()?];
+            if privileged(&mount) {
+                return Some(Tmpfs { path });
+            }
+            let _ = fs::remove_dir(&path);
+            eprintln!("skipped: cannot mount a tmpfs here (needs root or passwordless sudo)");
+            None
+        }
+
+        fn path_string(&self) -> String {
+            self.path.to_string_lossy().to_string()
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    impl Drop for Tmpfs {
+        fn drop(&mut self) {
+            if let Some(path) = self.path.to_str() {
+                privileged(&["umount", path]);
+            }
+            let _ = fs::remove_dir(&self.path);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn privileged(command: &[&str]) -> bool {
+        let run = |program: &str, args: &[&str]| {
+            std::process::Command::new(program)
+                .args(args)
+                .stderr(std::process::Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+        };
+        run(command[0], &command[1..]) || run("sudo", &[&["-n"], command].concat())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn run_free_space_wipe(path: String) -> WipeResult {
+        let app = tauri::test::mock_app();
+        app.manage(OperationRegistry::default());
+        let window = tauri::WebviewWindowBuilder::new(&app, "main", Default::default()).build().unwrap();
+        let wipe = execute_free_space_wipe(window.as_ref().window(), path, WipeAlgorithm::NistClear, 1, None, None, None);
+        tauri::async_runtime::block_on(wipe).expect("the command should resolve")
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn free_space_wipe_fills_and_wipes_a_tmpfs_mount() {
+        const MB: u64 = 1024 * 1024;
+        let (uid, gid) = unsafe { (libc::geteuid(), libc::getegid()) };
+        let Some(tmpfs) = Tmpfs::mount(&format!("size=8m,uid={},gid={},mode=0755", uid, gid)) else {
+            return;
+        };
+        let deleted = tmpfs.path.join("deleted.txt");
+        fs::write(&deleted, vec![0x5A; 2 * MB as usize]).unwrap();
+        fs::remove_file(&deleted).unwrap();
+        assert!(validate_drive_path_internal(&tmpfs.path).is_ok(), "a mount point is a drive");
+
+        let result = run_free_space_wipe(tmpfs.path_string());
+        assert!(result.success, "{:?}", result.message);
+        let coverage = result.report.and_then(|report| report.free_space).expect("coverage is reported");
+        // The fill ran until the volume answered ENOSPC, short of it by at most a chunk.
+        assert!(coverage.bytes_written + MB >= 8 * MB, "{:?}", coverage);
+        assert_eq!(coverage.total_bytes_written, coverage.bytes_written * 2, "one overwrite pass after the fill");
+        assert_eq!(fs::read_dir(&tmpfs.path).unwrap().count(), 0, "the temp file and its folder are gone");
+        let free = free_space::query_free_space(&tmpfs.path).unwrap();
+        assert_eq!(free.total_free, 8 * MB, "the space is released");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn a_root_owned_tmpfs_mount_is_refused_before_the_fill() {
+        if unsafe { libc::geteuid() } == 0 {
+            eprintln!("skipped: root can write to any mount");
+            return;
+        }
+        let Some(tmpfs) = Tmpfs::mount("size=1m,mode=0755") else {
+            return;
+        };
+        assert!(matches!(validate_drive_path_internal(&tmpfs.path), Err(DriveValidationError::NotWritable)));
+        let result = run_free_space_wipe(tmpfs.path_string());
+        assert_eq!(result.error.as_ref().map(|e| e.code()), Some("access_denied"));
     }
 
     #[test]
     fn cancelled_wipe_result_has_expected_message() {
         let result = cancelled_wipe_result();
         assert!(!result.success);
-        assert_eq!(result.message, "Operation cancelled by user");
+        assert_eq!(result.message.code, "cancelled");
+        assert_eq!(result.message.text, "Operation cancelled by user");
+    }
+
+    #[test]
+    fn cancelled_wipe_result_carries_cancelled_code() {
+        let result = cancelled_wipe_result();
+        assert_eq!(result.error.as_ref().map(|e| e.code()), Some("cancelled"));
     }
 
     #[test]
     fn free_space_error_result_formats_message() {
-        let result = free_space_error_result("sample error".to_string());
+        let message = messages::FREE_SPACE_WIPE_FAILED.render(json!({"error": "sample error"}));
+        let result = free_space_error_result(BitBurnError::internal("sample"), message);
         assert!(!result.success);
-        assert_eq!(result.message, "sample error");
+        assert_eq!(result.message.code, "free_space_wipe_failed");
+        assert_eq!(result.message.text, "Failed to wipe free space: sample error");
+        assert_eq!(result.error.as_ref().map(|e| e.code()), Some("internal"));
     }
 
-    fn cleanup_test_dir(dir: &Path) {
-        println!("Cleaning up test directory: {:?}", dir);
-        // Sleep briefly to ensure file handles are released
-        thread::sleep(Duration::from_millis(50));
-        if let Err(e) = fs::remove_dir_all(dir) {
-            println!("Warning: Failed to clean up test directory: {:?} - {}", dir, e);
+    #[test]
+    fn batch_keeps_progressing_and_cancels_by_id_after_listeners_drop() -> io::Result<()> {
+        use std::sync::atomic::AtomicUsize;
+        use tauri::Listener;
+
+        let test_dir = create_test_dir()?;
+        let file_path = create_test_file(&test_dir, &vec![0x5A; 4 * 1024 * 1024])?;
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "reload test");
+        let operation_id = ctx.operation.id().to_string();
+
+        let seen = Arc::new(AtomicUsize::new(0));
+        let seen_by_listener = seen.clone();
+        let listener = app.listen_any("wipe_progress", move |_| {
+            seen_by_listener.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let target = file_path.to_string_lossy().to_string();
+        let worker = thread::spawn(move || {
+            run_wipe_batch(&ctx, vec![Ok(target)], 1, 35, &WipeAlgorithm::Gutmann, BatchOptions::default())
+        });
+
+        let percentage = |registry: &OperationRegistry| registry.get(&operation_id).map(|s| s.overall_percentage);
+        while seen.load(Ordering::SeqCst) == 0 {
+            thread::sleep(Duration::from_millis(5));
+        }
+        // The webview reloads: every listener from the old page is gone.
+        app.unlisten(listener);
+        let at_reload = percentage(&registry).expect("operation should still be registered");
+
+        while percentage(&registry).is_some_and(|p| p <= at_reload) {
+            thread::sleep(Duration::from_millis(5));
         }
+        assert!(registry.active().iter().any(|s| s.operation_id == operation_id));
+        assert!(registry.cancel(&operation_id), "fresh frontend should cancel by id");
+
+        let result = worker.join().expect("wipe thread should not panic");
+        assert_eq!(result.error, Some(BitBurnError::Cancelled));
+        assert!(registry.get(&operation_id).is_none());
+        assert!(!registry.cancel(&operation_id));
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
     }
 
     #[test]
-    fn test_nonexistent_file() {
-        let dir = std::env::temp_dir();
-        let file_path = dir.join("nonexistent_test_file");
-        
-        let result = secure_wipe_file(&file_path, 1, &WipeAlgorithm::NistClear, |_| {});
-        assert!(matches!(result, Err(WipeError::PathNotFound)));
+    fn cancelling_mid_file_reports_it_as_partially_wiped() -> io::Result<()> {
+        use std::sync::atomic::AtomicUsize;
+        use tauri::Listener;
+
+        let test_dir = create_test_dir()?;
+        let file_len = 4 * 1024 * 1024;
+        let file_path = create_test_file(&test_dir, &vec![0x5A; file_len])?;
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "partial test");
+        let operation_id = ctx.operation.id().to_string();
+        let partial_events = Arc::new(AtomicUsize::new(0));
+        let partial_events_seen = partial_events.clone();
+        app.listen_any("partial_wipe_detected", move |_| {
+            partial_events_seen.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let target = file_path.to_string_lossy().to_string();
+        let worker = thread::spawn(move || {
+            run_wipe_batch(&ctx, vec![Ok(target)], 1, 35, &WipeAlgorithm::Gutmann, BatchOptions::default())
+        });
+        while registry.get(&operation_id).is_some_and(|s| s.overall_percentage <= 0.0) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(registry.cancel(&operation_id));
+
+        let result = worker.join().expect("wipe thread should not panic");
+        assert_eq!(result.error, Some(BitBurnError::Cancelled));
+        let report = result.report.expect("cancelled batch should carry a report");
+        assert_eq!(report.files[0].status, FileWipeStatus::Cancelled);
+        assert!(report.files[0].passes.passes_completed < 35);
+        let partial = &report.partially_wiped;
+        assert_eq!(partial.len(), 1);
+        assert_eq!(partial[0].path, file_path.to_string_lossy());
+        assert!((1..35).contains(&partial[0].last_pass), "stopped in pass {}", partial[0].last_pass);
+        assert!(partial[0].bytes_overwritten <= file_len as u64);
+        assert_eq!(fs::metadata(&file_path)?.len(), file_len as u64, "interrupted file is left in place");
+        assert_eq!(partial_events.load(Ordering::SeqCst), 1);
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
     }
 
     #[test]
-    fn test_invalid_passes() -> io::Result<()> {
+    fn stalled_file_times_out_and_the_batch_continues() -> io::Result<()> {
+        use crate::stall_watch::StallingTarget;
+        use bitburn_core::MemoryTarget;
+        use std::sync::atomic::AtomicUsize;
+        use tauri::Listener;
+
+        fn stall_dying_disk(path: &Path) -> Option<Box<dyn WipeTarget + Send>> {
+            let name = path.file_name()?.to_string_lossy();
+            name.starts_with("dying").then(|| {
+                Box::new(StallingTarget::new(
+                    MemoryTarget::new(vec![0x5A; 2 * 1024 * 1024]),
+                    1024 * 1024,
+                    Duration::from_secs(3),
+                )) as Box<dyn WipeTarget + Send>
+            })
+        }
+
         let test_dir = create_test_dir()?;
-        let test_data = [0xAA; 1024];
-        let file_path = create_test_file(&test_dir, &test_data)?;
-        
-        let result = secure_wipe_file(&file_path, 0, &WipeAlgorithm::Random, |_| {});
-        assert!(matches!(result, Err(WipeError::InvalidPasses)));
-        
+        let stuck = test_dir.join("dying_usb.bin");
+        let healthy = test_dir.join("healthy.bin");
+        fs::write(&stuck, vec![0x5A; 2 * 1024 * 1024])?;
+        fs::write(&healthy, vec![0x5A; 1024 * 1024])?;
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "stall test");
+        let stalls = Arc::new(AtomicUsize::new(0));
+        let stalls_seen = stalls.clone();
+        app.listen_any("wipe_stalled", move |_| {
+            stalls_seen.fetch_add(1, Ordering::SeqCst);
+        });
+        let options = BatchOptions {
+            stall_policy: Some(StallPolicy {
+                stall_after: Duration::from_millis(100),
+                timeout: Some(Duration::from_millis(400)),
+                poll_interval: Duration::from_millis(10),
+            }),
+            simulate_target: Some(stall_dying_disk),
+            ..Default::default()
+        };
+
+        let started = std::time::Instant::now();
+        let roots = vec![Ok(stuck.to_string_lossy().to_string()), Ok(healthy.to_string_lossy().to_string())];
+        let result = run_wipe_batch(&ctx, roots, 2, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(started.elapsed() < Duration::from_secs(3), "batch should not wait for the stuck write");
+        assert!(!result.success);
+        assert_eq!(result.failures.len(), 1);
+        assert!(matches!(&result.failures[0].error, BitBurnError::Timeout { path, .. } if path == &stuck.to_string_lossy()));
+        assert!(!healthy.exists(), "the next file should still be wiped");
+        assert!(stuck.exists(), "the timed-out file is left in place");
+        assert_eq!(stalls.load(Ordering::SeqCst), 1);
+        let files = result.report.expect("batch should carry a report").files;
+        assert_eq!(files[0].status, FileWipeStatus::Timeout);
+        assert_eq!(files[0].passes.passes_completed, 0);
+
         cleanup_test_dir(&test_dir);
         Ok(())
     }
 
     #[test]
-    fn test_nist_clear_wipe() -> io::Result<()> {
+    fn report_records_the_passes_each_file_received() -> io::Result<()> {
+        use bitburn_core::{MemoryTarget, BUFFER_SIZE};
+
+        // Pass 1 of NIST Purge covers the 2MB file; pass 2 fails after its first chunk.
+        fn fail_in_pass_two(path: &Path) -> Option<Box<dyn WipeTarget + Send>> {
+            let name = path.file_name()?.to_string_lossy();
+            name.starts_with("failing").then(|| {
+                let target = MemoryTarget::new(vec![0x5A; 2 * BUFFER_SIZE as usize]).with_write_limit(3 * BUFFER_SIZE);
+                Box::new(target) as Box<dyn WipeTarget + Send>
+            })
+        }
+
         let test_dir = create_test_dir()?;
-        let test_data = [0xAA; 1024];
-        let file_path = create_test_file(&test_dir, &test_data)?;
-        
-        // Verify file exists and has correct size
-        let metadata = fs::metadata(&file_path)?;
-        assert!(metadata.is_file(), "Created path should be a file");
-        assert_eq!(metadata.len(), 1024, "File should be 1024 bytes");
-        
-        let mut progress_patterns_seen = Vec::new();
-        let result = secure_wipe_file(&file_path, 1, &WipeAlgorithm::NistClear, |progress| {
-            progress_patterns_seen.push(progress.current_pattern.clone());
-        });
-        
-        // Verify the operation succeeded
-        assert!(result.is_ok(), "Wipe operation should succeed: {:?}", result);
-        
-        // Verify progress messages contain "NIST Clear"
-        for pattern in &progress_patterns_seen {
-            assert!(pattern.contains("NIST 800-88 Clear"), 
-                "Progress pattern should mention NIST Clear: {}", pattern);
+        let failing = test_dir.join("failing_disk.bin");
+        let healthy = test_dir.join("healthy.bin");
+        fs::write(&failing, vec![0x5A; 2 * BUFFER_SIZE as usize])?;
+        fs::write(&healthy, vec![0x5A; 4096])?;
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "pass record test");
+        let options = BatchOptions {
+            simulate_target: Some(fail_in_pass_two),
+            ..Default::default()
+        };
+        let roots = vec![Ok(failing.to_string_lossy().to_string()), Ok(healthy.to_string_lossy().to_string())];
+        let result = run_wipe_batch(&ctx, roots, 2, 3, &WipeAlgorithm::NistPurge, options);
+
+        assert!(!result.success);
+        let files = result.report.expect("batch should carry a report").files;
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, failing.to_string_lossy());
+        assert_eq!(files[0].status, FileWipeStatus::Error);
+        assert_eq!(files[0].passes.total_passes, 3);
+        assert_eq!(files[0].passes.passes_completed, 1);
+        assert_eq!(files[0].passes.last_pass_pattern.as_deref(), Some("ones"));
+        assert_eq!(files[0].passes.bytes_of_last_pass, BUFFER_SIZE);
+        assert_eq!(files[1].status, FileWipeStatus::Success);
+        assert_eq!((files[1].passes.passes_completed, files[1].passes.bytes_of_last_pass), (3, 4096));
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn a_small_wipe_reports_its_file_pass_and_phase_timings() -> io::Result<()> {
+        let test_dir = create_test_dir()?;
+        let file = test_dir.join("timed.bin");
+        fs::write(&file, vec![0x5A; 64 * 1024])?;
+
+        let app = tauri::test::mock_app();
+        app.manage(performance::TimingHistory::in_memory());
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "timing test");
+        let roots = vec![Ok(file.to_string_lossy().to_string())];
+        let result = run_wipe_batch(&ctx, roots, 1, 3, &WipeAlgorithm::NistPurge, BatchOptions::default());
+        drop(ctx);
+
+        assert!(result.success, "{}", result.message.text);
+        let report = result.report.expect("batch should carry a report");
+        assert!(report.timings.is_some());
+        let timed = &report.files[0];
+        let passes: Vec<_> = timed.passes.pass_timings.iter().map(|pass| (pass.pass, pass.bytes)).collect();
+        assert_eq!(passes, [(1, 65536), (2, 65536), (3, 65536)]);
+        assert!(timed.bytes_per_second.is_some_and(|rate| rate > 0));
+        assert!(timed.duration_ms >= timed.passes.pass_timings.iter().map(|pass| pass.millis).sum::<u64>());
+
+        let summary = performance::summarize(&app.state::<performance::TimingHistory>().recent(10));
+        assert_eq!((summary.operations, summary.files), (1, 1));
+        assert_eq!(summary.pass_ms.map(|pass| pass.samples), Some(3));
+        assert_eq!(summary.wipe_ms.map(|wipe| wipe.samples), Some(1));
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn a_mixed_batch_wipes_each_task_with_its_own_algorithm() -> io::Result<()> {
+        let test_dir = create_test_dir()?;
+        let ssd_file = test_dir.join("notes.txt");
+        let archive = test_dir.join("archive");
+        fs::create_dir_all(&archive)?;
+        fs::write(&ssd_file, b"draft")?;
+        fs::write(archive.join("2019.tar"), vec![0x5A; 4096])?;
+        let tasks = vec![
+            WipeTask { path: ssd_file.to_string_lossy().to_string(), algorithm: WipeAlgorithm::NistClear, passes: 1 },
+            WipeTask { path: archive.to_string_lossy().to_string(), algorithm: WipeAlgorithm::NistPurge, passes: 3 },
+        ];
+        let request = wipe_tasks::request(None, None, None, Some(tasks)).expect("the tasks are compatible");
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "mixed test");
+        let options = BatchOptions { tasks: request.tasks, ..Default::default() };
+        let roots = request.paths.into_iter().map(Ok);
+        let result = run_wipe_batch(&ctx, roots, 2, request.passes, &request.algorithm, options);
+        drop(ctx);
+
+        assert!(result.success, "{}", result.message.text);
+        let report = result.report.expect("batch should carry a report");
+        let applied: Vec<_> = report
+            .files
+            .iter()
+            .map(|file| (Path::new(&file.path).file_name().unwrap().to_owned(), file.algorithm.clone(), file.passes.total_passes))
+            .collect();
+        assert_eq!(
+            applied,
+            [
+                ("notes.txt".into(), Some(WipeAlgorithm::NistClear), 1),
+                ("2019.tar".into(), Some(WipeAlgorithm::NistPurge), 3),
+            ]
+        );
+        let json = serde_json::to_value(&report.files[1]).unwrap();
+        assert_eq!(json["algorithm"], "NistPurge");
+        assert!(!ssd_file.exists() && !archive.exists());
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn resumed_files_keep_the_algorithm_and_passes_of_their_checkpoint() -> io::Result<()> {
+        let test_dir = create_test_dir()?;
+        let image = test_dir.join("disk.img");
+        fs::write(&image, vec![1u8; 4096])?;
+        let identity = file_checkpoint::FileIdentity::of(&image)?;
+        let checkpoint = FileCheckpoint {
+            identity,
+            algorithm: WipeAlgorithm::NistPurge,
+            passes: 3,
+            progress: bitburn_core::PassCheckpoint::default(),
+        };
+        let key = image.to_string_lossy().to_string();
+        let tasks = checkpoint_tasks(&BTreeMap::from([(key.clone(), checkpoint)]));
+        assert_eq!(tasks, [WipeTask { path: key.clone(), algorithm: WipeAlgorithm::NistPurge, passes: 3 }]);
+        // Whatever the window asks for, the checkpointed file is wiped as it was started.
+        assert_eq!(wipe_tasks::for_root(&tasks, &key), Some((&WipeAlgorithm::NistPurge, 3)));
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn a_file_another_process_holds_exclusively_is_skipped_as_in_use() -> io::Result<()> {
+        let test_dir = create_test_dir()?;
+        let held = test_dir.join("held.db");
+        let free = test_dir.join("free.txt");
+        fs::write(&held, b"still readable")?;
+        fs::write(&free, b"secret")?;
+        let holder = bitburn_core::open_exclusive(fs::OpenOptions::new().read(true), &held)?;
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let wipe = |roots: Vec<&Path>, exclusive_lock: bool| {
+            let ctx = batch_context(&app, &registry, "exclusive test");
+            let roots = roots.into_iter().map(|root| Ok(root.to_string_lossy().to_string()));
+            let options = BatchOptions { skip_in_use: true, exclusive_lock: Some(exclusive_lock), ..Default::default() };
+            run_wipe_batch(&ctx, roots, 2, 1, &WipeAlgorithm::NistClear, options)
+        };
+
+        let report = wipe(vec![&held, &free], true).report.expect("batch should carry a report");
+        assert_eq!(report.skipped.iter().map(BitBurnError::code).collect::<Vec<_>>(), ["file_in_use"]);
+        assert_eq!(fs::read(&held)?, b"still readable");
+        assert!(!free.exists());
+        let exclusive: Vec<_> = report.files.iter().map(|file| (file.status, file.exclusive_access)).collect();
+        assert_eq!(exclusive, [(FileWipeStatus::Error, false), (FileWipeStatus::Success, true)]);
+
+        fs::write(&free, b"secret")?;
+        let shared = wipe(vec![&free], false).report.expect("batch should carry a report");
+        assert!(!shared.files[0].exclusive_access);
+
+        drop(holder);
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn files_on_copy_on_write_file_systems_qualify_the_success_message() {
+        use copy_on_write::CowFileSystem;
+
+        let file = |path: &str, cow_filesystem| FileWipeOutcome {
+            path: path.to_string(),
+            status: FileWipeStatus::Success,
+            algorithm: None,
+            flash_media: false,
+            recalled_from_remote: false,
+            cow_filesystem,
+            nocow_applied: (cow_filesystem == Some(CowFileSystem::Btrfs)).then_some(false),
+            ownership_taken: false,
+            original_owner: None,
+            link: None,
+            exclusive_access: true,
+            passes: FileWipeProgressRecord::new(1),
+            duration_ms: 1,
+            bytes_per_second: None,
+            companions: Vec::new(),
+        };
+        let report = |files| WipeReport { files, ..Default::default() };
+
+        let plain = batch_result(report(vec![file("/home/a.txt", None)]), Vec::new(), 1, false);
+        assert_eq!(plain.message.code, "files_wiped");
+        assert!(plain.report.unwrap().warnings.is_empty());
+
+        let files = vec![
+            file("/home/a.txt", Some(CowFileSystem::Btrfs)),
+            file("/home/b.txt", None),
+            file("/tank/c.txt", Some(CowFileSystem::Zfs)),
+        ];
+        let result = batch_result(report(files), Vec::new(), 3, false);
+        assert!(result.success);
+        assert_eq!(result.message.code, "files_wiped_on_copy_on_write");
+        assert_eq!(result.message.params["cow_count"], 2);
+        assert_eq!(result.message.params["file_systems"], "Btrfs, ZFS");
+        let report = result.report.unwrap();
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("free space"));
+        let first = serde_json::to_value(&report.files[0]).unwrap();
+        assert_eq!((&first["cow_filesystem"], &first["nocow_applied"]), (&json!("btrfs"), &json!(false)));
+        let second = serde_json::to_value(&report.files[1]).unwrap();
+        assert!(second.get("cow_filesystem").is_none() && second.get("nocow_applied").is_none());
+    }
+
+    #[test]
+    fn sampled_verification_fails_a_file_whose_sampled_chunk_reads_back_wrong() -> io::Result<()> {
+        use bitburn_core::{MemoryTarget, VerifyMode, BUFFER_SIZE};
+
+        fn corrupt_damaged_file(path: &Path) -> Option<Box<dyn WipeTarget + Send>> {
+            let name = path.file_name()?.to_string_lossy();
+            // A single chunk, so it is always in the sample.
+            name.starts_with("damaged").then(|| {
+                Box::new(MemoryTarget::new(vec![0x5A; 4096]).with_corruption_at(100)) as Box<dyn WipeTarget + Send>
+            })
         }
-        
-        // Verify file is deleted
-        assert!(!file_path.exists(), "File should be deleted after wiping");
-        
+
+        let test_dir = create_test_dir()?;
+        let sound = test_dir.join("sound.bin");
+        let damaged = test_dir.join("damaged.bin");
+        fs::write(&sound, vec![0x5A; 3 * BUFFER_SIZE as usize])?;
+        fs::write(&damaged, vec![0x5A; 4096])?;
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "verification test");
+        let verification = Verification { mode: VerifyMode::Sampled(1.0), seed: 7 };
+        let options =
+            BatchOptions { verification: Some(verification), simulate_target: Some(corrupt_damaged_file), ..Default::default() };
+        let roots = vec![Ok(sound.to_string_lossy().to_string()), Ok(damaged.to_string_lossy().to_string())];
+        let result = run_wipe_batch(&ctx, roots, 2, 3, &WipeAlgorithm::NistPurge, options);
+        drop(ctx);
+
+        let report = result.report.expect("batch should carry a report");
+        assert_eq!(report.verification, Some(verification));
+        assert!(!sound.exists());
+        let checked: Vec<_> = report.files[0].passes.verified_chunks.iter().map(|pass| (pass.pass, pass.chunks)).collect();
+        assert_eq!(checked, [(1, 1), (2, 1), (3, 1)]);
+        assert_eq!(report.files[1].status, FileWipeStatus::Error);
+        assert!(damaged.exists(), "a file that fails verification is not deleted");
+        assert_eq!(
+            report.failures,
+            [BitBurnError::VerificationFailed { path: damaged.to_string_lossy().to_string(), offset: 0, offsets: vec![0] }]
+        );
+
         cleanup_test_dir(&test_dir);
         Ok(())
     }
 
     #[test]
-    fn test_nist_purge_wipe() -> io::Result<()> {
+    fn a_mixed_selection_groups_its_outcomes_under_each_selected_root() -> io::Result<()> {
+        use bitburn_core::MemoryTarget;
+        use tauri::Listener;
+
+        fn fail_loose_file(path: &Path) -> Option<Box<dyn WipeTarget + Send>> {
+            let name = path.file_name()?.to_string_lossy();
+            name.starts_with("failing").then(|| {
+                Box::new(MemoryTarget::new(vec![0x5A; 8192]).with_write_limit(4096)) as Box<dyn WipeTarget + Send>
+            })
+        }
+
         let test_dir = create_test_dir()?;
-        let test_data = [0xAA; 1024];
-        let file_path = create_test_file(&test_dir, &test_data)?;
-        
-        // Verify file exists and has correct size
-        let metadata = fs::metadata(&file_path)?;
-        assert!(metadata.is_file(), "Created path should be a file");
-        assert_eq!(metadata.len(), 1024, "File should be 1024 bytes");
-        
-        let mut progress_patterns_seen = Vec::new();
-        let result = secure_wipe_file(&file_path, 3, &WipeAlgorithm::NistPurge, |progress| {
-            progress_patterns_seen.push(progress.current_pattern.clone());
+        let photos = test_dir.join("photos");
+        fs::create_dir_all(photos.join("2024"))?;
+        fs::write(photos.join("a.jpg"), vec![1; 1000])?;
+        fs::write(photos.join("b.jpg"), vec![2; 2000])?;
+        fs::write(photos.join("2024").join("c.jpg"), vec![3; 3000])?;
+        let loose = test_dir.join("failing_notes.txt");
+        fs::write(&loose, vec![4; 8192])?;
+        let missing = test_dir.join("gone");
+        let link = test_dir.join("shortcut");
+        let letters = test_dir.join("letters");
+        fs::create_dir_all(&letters)?;
+        fs::write(letters.join("d.txt"), vec![5; 500])?;
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "grouping test");
+        let root_indices = Arc::new(Mutex::new(HashSet::new()));
+        let seen = root_indices.clone();
+        app.listen_any("wipe_progress", move |event| {
+            let payload: serde_json::Value = serde_json::from_str(event.payload()).unwrap();
+            seen.lock().unwrap().insert(payload["root_index"].as_u64());
         });
-        
-        // Verify the operation succeeded
-        assert!(result.is_ok(), "Wipe operation should succeed: {:?}", result);
-        
-        // Verify progress messages contain "NIST Purge"
-        for pattern in &progress_patterns_seen {
-            assert!(pattern.contains("NIST 800-88 Purge"), 
-                "Progress pattern should mention NIST Purge: {}", pattern);
+        let options = BatchOptions { simulate_target: Some(fail_loose_file), ..Default::default() };
+        let roots = vec![
+            Ok(photos.to_string_lossy().to_string()),
+            Ok(loose.to_string_lossy().to_string()),
+            Ok(missing.to_string_lossy().to_string()),
+            Err(BitBurnError::SymlinkNotSupported { path: link.to_string_lossy().to_string() }),
+            Ok(letters.to_string_lossy().to_string()),
+        ];
+        let result = run_wipe_batch(&ctx, roots, 5, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(!result.success);
+        let failures = result.failures.len();
+        let report = result.report.expect("batch should carry a report");
+        let summary = |root: &report::RootReport| {
+            (root.root_index, root.files_attempted, root.succeeded, root.failed, root.skipped, root.bytes_wiped)
+        };
+        let roots: Vec<_> = report.roots.iter().map(summary).collect();
+        // (root, attempted, succeeded, failed, skipped, bytes)
+        assert_eq!(
+            roots,
+            [(0, 3, 3, 0, 0, 6000), (1, 1, 0, 1, 0, 0), (2, 0, 0, 1, 0, 0), (3, 0, 0, 0, 1, 0), (4, 1, 1, 0, 0, 500)]
+        );
+        assert_eq!(report.roots[0].path, photos.to_string_lossy());
+        assert_eq!(report.roots[0].files.len(), 3);
+        assert!(report.roots[0].files.iter().all(|file| file.path.starts_with(&*photos.to_string_lossy())));
+        assert_eq!(report.roots[1].error, None, "the loose file was attempted, not turned away");
+        let missing_path = missing.to_string_lossy();
+        assert!(matches!(&report.roots[2].error, Some(BitBurnError::PathNotFound { path }) if path == &missing_path));
+        assert_eq!(report.roots[3].path, link.to_string_lossy());
+        assert!(matches!(report.roots[3].error, Some(BitBurnError::SymlinkNotSupported { .. })));
+
+        // The groups add up to the flat totals.
+        assert_eq!(report.roots.iter().map(|root| root.files_attempted).sum::<usize>(), report.files.len());
+        assert_eq!(report.roots.iter().map(|root| root.succeeded).sum::<usize>(), report.files_wiped);
+        assert_eq!(report.roots.iter().map(|root| root.failed).sum::<usize>(), failures);
+        assert_eq!(report.roots.iter().map(|root| root.skipped).sum::<usize>(), report.skipped.len());
+
+        let root_indices = root_indices.lock().unwrap();
+        assert!(root_indices.contains(&Some(4)), "{:?}", root_indices);
+        assert!(root_indices.iter().all(|index| matches!(index, Some(0 | 1 | 4))), "{:?}", root_indices);
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn selected_symlinks_are_handled_as_the_policy_says() -> io::Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let test_dir = create_test_dir()?.canonicalize()?;
+        let run = |links: &[&PathBuf], symlink_policy| {
+            let app = tauri::test::mock_app();
+            let registry = OperationRegistry::default();
+            let ctx = batch_context(&app, &registry, "symlink test");
+            let roots = links.iter().map(|link| Ok(link.to_string_lossy().to_string()));
+            let options = BatchOptions { symlink_policy, ..Default::default() };
+            run_wipe_batch(&ctx, roots, links.len(), 1, &WipeAlgorithm::NistClear, options)
+        };
+        let target_file = test_dir.join("secret.txt");
+        let target_folder = test_dir.join("folder");
+        let file_link = test_dir.join("file_link");
+        let folder_link = test_dir.join("folder_link");
+        let make = || -> io::Result<()> {
+            fs::create_dir_all(&target_folder)?;
+            fs::write(&target_file, vec![7; 2048])?;
+            fs::write(target_folder.join("inner.txt"), vec![8; 1024])?;
+            for (target, link) in [(&target_file, &file_link), (&target_folder, &folder_link)] {
+                if fs::symlink_metadata(link).is_err() {
+                    symlink(target, link)?;
+                }
+            }
+            Ok(())
+        };
+
+        make()?;
+        let report = run(&[&file_link, &folder_link], SymlinkPolicy::Reject).report.expect("report");
+        let skipped: Vec<_> = report.skipped.iter().map(BitBurnError::code).collect();
+        assert_eq!(skipped, ["symlink_not_supported", "symlink_not_supported"]);
+        assert!(file_link.is_symlink() && folder_link.is_symlink());
+        assert!(target_file.exists() && target_folder.join("inner.txt").exists());
+
+        let result = run(&[&file_link, &folder_link], SymlinkPolicy::DeleteLinkOnly);
+        assert!(result.success, "{:?}", result.message);
+        let report = result.report.expect("report");
+        assert!(fs::symlink_metadata(&file_link).is_err() && fs::symlink_metadata(&folder_link).is_err());
+        assert_eq!(fs::read(&target_file)?, vec![7; 2048]);
+        assert_eq!(fs::read(target_folder.join("inner.txt"))?, vec![8; 1024]);
+        let links: Vec<_> = report.files.iter().map(|file| file.link.as_ref().map(|link| link.policy)).collect();
+        assert_eq!(links, [Some(SymlinkPolicy::DeleteLinkOnly), Some(SymlinkPolicy::DeleteLinkOnly)]);
+        assert!(report.files.iter().all(|file| file.passes.passes_completed == 0));
+
+        make()?;
+        let result = run(&[&file_link, &folder_link], SymlinkPolicy::FollowTarget);
+        assert!(result.success, "{:?}", result.message);
+        let report = result.report.expect("report");
+        assert!(!target_file.exists() && !target_folder.exists());
+        assert!(fs::symlink_metadata(&file_link).is_err() && fs::symlink_metadata(&folder_link).is_err());
+        let followed = report.files.iter().find(|file| file.path == target_file.to_string_lossy()).expect("target outcome");
+        let record = followed.link.as_ref().expect("link recorded");
+        assert_eq!(record.link, file_link.to_string_lossy());
+        assert_eq!(record.target, target_file.to_string_lossy());
+        assert_eq!(report.files_wiped, 2);
+
+        // A link into a protected folder is refused and left as it was, with its target.
+        let protected = test_dir.join("etc_link");
+        symlink("/etc", &protected)?;
+        let result = run(&[&protected], SymlinkPolicy::FollowTarget);
+        assert!(!result.success);
+        assert!(matches!(&result.failures[..], [failure] if failure.error.code() == "protected_path"));
+        assert!(protected.is_symlink() && Path::new("/etc").is_dir());
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn a_decoy_batch_ends_each_file_with_a_decoy_pass_and_reports_its_hash() -> io::Result<()> {
+        let test_dir = create_test_dir()?;
+        let target = test_dir.join("ledger.xlsx");
+        fs::write(&target, vec![0x5A; 4096])?;
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "decoy test");
+        let decoy = decoy::Decoy::load(&bitburn_core::WipePattern::FromText("Minutes of the meeting.".to_string()))
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        let options = BatchOptions { decoy: Some(decoy.clone()), ..Default::default() };
+        let roots = vec![Ok(target.to_string_lossy().to_string())];
+        let result = run_wipe_batch(&ctx, roots, 1, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(result.success, "{:?}", result.message);
+        assert!(!target.exists());
+        let report = result.report.expect("batch should carry a report");
+        assert_eq!(report.decoy, Some(decoy.summary));
+        assert_eq!(report.files[0].passes.total_passes, 2);
+        assert_eq!(report.files[0].passes.passes_completed, 2);
+        assert_eq!(report.files[0].passes.last_pass_pattern.as_deref(), Some("decoy data"));
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn companions_are_wiped_with_their_document_and_reported_under_it() -> io::Result<()> {
+        let test_dir = create_test_dir()?;
+        let doc = test_dir.join("doc.docx");
+        let companions = ["~$doc.docx", ".~lock.doc.docx#", "doc.docx.tmp", "doc.docx.bak", ".doc.docx.swp"];
+        fs::write(&doc, vec![0x5A; 4096])?;
+        for companion in companions {
+            fs::write(test_dir.join(companion), vec![0x5A; 512])?;
         }
-        
-        // Verify we saw all 3 passes
-        assert!(progress_patterns_seen.iter().any(|p| p.contains("Pass 1/3")), 
-            "Missing first pass");
-        assert!(progress_patterns_seen.iter().any(|p| p.contains("Pass 2/3")), 
-            "Missing second pass");
-        assert!(progress_patterns_seen.iter().any(|p| p.contains("Pass 3/3")), 
-            "Missing third pass");
-        
-        // Verify file is deleted
-        assert!(!file_path.exists(), "File should be deleted after wiping");
-        
+        let unrelated = test_dir.join("notes.txt.bak");
+        fs::write(&unrelated, b"kept")?;
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "companion test");
+        let options = BatchOptions { companion_files: Some(true), ..Default::default() };
+        // The owner file is selected too; it is wiped as a companion and not missed later.
+        let roots = vec![
+            Ok(doc.to_string_lossy().to_string()),
+            Ok(test_dir.join("~$doc.docx").to_string_lossy().to_string()),
+        ];
+        let result = run_wipe_batch(&ctx, roots, 2, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(result.success, "{:?}", result.message);
+        assert!(!doc.exists());
+        assert!(companions.iter().all(|companion| !test_dir.join(companion).exists()));
+        assert!(unrelated.exists());
+        let report = result.report.expect("batch should carry a report");
+        assert_eq!(report.files_wiped, 6);
+        assert_eq!(report.files.len(), 1);
+        assert_eq!(report.files[0].path, doc.to_string_lossy());
+        let mut nested: Vec<_> = report.files[0].companions.iter().map(|file| file.path.clone()).collect();
+        let mut expected: Vec<_> =
+            companions.iter().map(|companion| test_dir.join(companion).to_string_lossy().to_string()).collect();
+        nested.sort();
+        expected.sort();
+        assert_eq!(nested, expected);
+        assert!(report.files[0].companions.iter().all(|file| file.status == FileWipeStatus::Success));
+        assert_eq!((report.roots[0].files_attempted, report.roots[0].succeeded), (6, 6));
+
+        // Off, the companions are left alone.
+        let doc = test_dir.join("plan.odt");
+        let lock = test_dir.join(".~lock.plan.odt#");
+        fs::write(&doc, b"plan")?;
+        fs::write(&lock, b"lock")?;
+        let roots = vec![Ok(doc.to_string_lossy().to_string())];
+        let result = run_wipe_batch(&ctx, roots, 1, 1, &WipeAlgorithm::NistClear, BatchOptions::default());
+        assert!(result.success, "{:?}", result.message);
+        assert!(!doc.exists());
+        assert!(lock.exists());
+
         cleanup_test_dir(&test_dir);
         Ok(())
     }
 
     #[test]
-    fn test_gutmann_wipe() -> io::Result<()> {
+    fn huge_folders_are_walked_a_bounded_chunk_at_a_time() -> io::Result<()> {
         let test_dir = create_test_dir()?;
-        let test_data = [0xAA; 4096];  // Larger file for pattern testing
-        let file_path = create_test_file(&test_dir, &test_data)?;
-        
-        let mut progress_patterns_seen = Vec::new();
-        let result = secure_wipe_file(&file_path, 35, &WipeAlgorithm::Gutmann, |progress| {
-            // Only store the base pattern without MB information
-            let base_pattern = progress.current_pattern
-                .split(" - ")
-                .next()
-                .unwrap_or(&progress.current_pattern)
-                .to_string();
-            if !progress_patterns_seen.contains(&base_pattern) {
-                progress_patterns_seen.push(base_pattern);
+        for shard in 0..20 {
+            let shard_dir = test_dir.join(format!("shard_{:02}", shard));
+            fs::create_dir_all(&shard_dir)?;
+            for i in 0..1_000 {
+                fs::File::create(shard_dir.join(format!("{:04}.bin", i)))?;
             }
-        });
-        
-        // Verify the operation succeeded
-        assert!(result.is_ok(), "Wipe operation failed: {:?}", result);
-        
-        // Verify we saw all 35 passes
-        let unique_passes = progress_patterns_seen.iter()
-            .filter(|p| p.contains("Pass") || p.contains("Pattern"))
-            .filter(|p| !p.contains("Finalizing"))
-            .count();
-        assert_eq!(unique_passes, 35, "Did not see all 35 passes");
-            
-        // Verify the sequence of passes
-        let pass_sequence = progress_patterns_seen.iter()
-            .map(|p| p.as_str())
-            .collect::<Vec<_>>();
-            
-        // Verify first 4 passes are random
-        for i in 0..4 {
-            assert!(pass_sequence.iter().any(|&p| p.contains(&format!("Random data (Pass {}/35)", i + 1))),
-                "Missing random pass {}", i + 1);
         }
-        
-        // Verify some key fixed patterns are present
-        assert!(pass_sequence.iter().any(|&p| p.contains("Pattern 5/35: 0x55 0xAA")),
-            "Missing alternating pattern 0x55 0xAA");
-        assert!(pass_sequence.iter().any(|&p| p.contains("Pattern 7/35: 0x92 0x49 0x24")),
-            "Missing pattern 0x92 0x49 0x24");
-            
-        // Verify last 4 passes are random
-        for i in 32..=35 {
-            assert!(pass_sequence.iter().any(|&p| p.contains(&format!("Random data (Pass {}/35)", i))),
-                "Missing random pass {}", i);
+        let mut totals = HashSet::new();
+        let mut visited = 0;
+
+        let outcome = wipe_directory_files(
+            &test_dir,
+            None,
+            5_000,
+            &CancellationToken::new(),
+            |file, position| {
+                totals.insert(position.files_total);
+                visited += 1;
+                fs::remove_file(file).is_ok()
+            },
+            |_| {},
+        )
+        .expect("directory should be wiped");
+
+        assert_eq!((outcome.files_wiped, outcome.chunks, outcome.rescanned_files), (20_000, 4, 0));
+        assert_eq!(visited, 20_000);
+        assert_eq!(totals, HashSet::from([20_000]), "the total comes from a count, not the first chunk");
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn outcomes_of_large_batches_are_spilled_to_disk_and_paged() -> io::Result<()> {
+        let test_dir = create_test_dir()?;
+        let tree = test_dir.join("tree");
+        for shard in 0..3 {
+            fs::create_dir_all(tree.join(shard.to_string()))?;
+            for i in 0..80 {
+                fs::write(tree.join(shard.to_string()).join(format!("{:02}.bin", i)), [0x5A; 16])?;
+            }
         }
-        
-        // Verify file is deleted
-        assert!(!file_path.exists(), "File should be deleted after wiping");
-        
+        let loose = test_dir.join("loose.bin");
+        fs::write(&loose, [0x5A; 16])?;
+
+        let app = tauri::test::mock_app();
+        app.manage(outcomes::OutcomeStore::new(test_dir.join("outcomes")));
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "spill test");
+        let options = BatchOptions { chunk_files: Some(100), ..Default::default() };
+        let roots = vec![Ok(tree.to_string_lossy().to_string()), Ok(loose.to_string_lossy().to_string())];
+        let result = run_wipe_batch(&ctx, roots, 2, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(result.success, "{:?}", result.message);
+        let report = result.report.expect("batch should carry a report");
+        let spilled = report.outcomes.clone().expect("outcomes should have been spilled");
+        assert_eq!((report.files_wiped, spilled.total), (241, 241));
+        assert!(report.files.is_empty() && report.wiped_files.is_empty());
+        let summary: Vec<_> =
+            report.roots.iter().map(|root| (root.files_attempted, root.succeeded, root.bytes_wiped)).collect();
+        assert_eq!(summary, [(240, 240, 3840), (1, 1, 16)]);
+        assert!(report.roots.iter().all(|root| root.files.is_empty()));
+
+        let store = app.state::<outcomes::OutcomeStore>();
+        let id = ctx.operation.id();
+        let mut paths = HashSet::new();
+        for offset in (0..300).step_by(100) {
+            let page = store.page(id, offset, 100).map_err(|e| io::Error::other(e.to_string()))?;
+            assert_eq!(page.len(), 100.min(241 - offset));
+            paths.extend(page.iter().map(|outcome| outcome["path"].as_str().unwrap_or_default().to_string()));
+        }
+        assert_eq!(paths.len(), 241);
+        assert!(paths.contains(&*loose.to_string_lossy()));
+        let contents = fs::read(store.path_for(id).map_err(|e| io::Error::other(e.to_string()))?)?;
+        assert_eq!(spilled.sha256, hex::encode(<sha2::Sha256 as sha2::Digest>::digest(&contents)));
+
         cleanup_test_dir(&test_dir);
         Ok(())
     }
 
+    #[cfg(any(windows, unix))]
     #[test]
-    fn test_random_wipe() -> io::Result<()> {
+    fn a_pulled_drive_fails_its_remaining_items_once_without_touching_them() -> io::Result<()> {
+        use bitburn_core::{MemoryTarget, BUFFER_SIZE};
+
+        fn unplugged_mid_file(path: &Path) -> Option<Box<dyn WipeTarget + Send>> {
+            #[cfg(windows)]
+            let code = 1167;
+            #[cfg(unix)]
+            let code = libc::ENODEV;
+            let name = path.file_name()?.to_string_lossy();
+            name.starts_with("1_").then(|| {
+                let target = MemoryTarget::new(vec![0x5A; 2 * BUFFER_SIZE as usize]).with_write_error(BUFFER_SIZE, code);
+                Box::new(target) as Box<dyn WipeTarget + Send>
+            })
+        }
+
         let test_dir = create_test_dir()?;
-        let test_data = [0xAA; 1024];
-        let file_path = create_test_file(&test_dir, &test_data)?;
-        
-        // Test with 5 passes
-        let passes = 5;
-        let mut progress_patterns_seen = Vec::new();
-        let result = secure_wipe_file(&file_path, passes, &WipeAlgorithm::Random, |progress| {
-            // Only store the base pattern without MB information
-            let base_pattern = progress.current_pattern
-                .split(" - ")
-                .next()
-                .unwrap_or(&progress.current_pattern)
-                .to_string();
-            if !progress_patterns_seen.contains(&base_pattern) {
-                progress_patterns_seen.push(base_pattern);
+        let pulled = test_dir.join("1_pulled.bin");
+        let later = test_dir.join("2_later.bin");
+        let folder = test_dir.join("3_folder");
+        fs::create_dir_all(&folder)?;
+        fs::write(&pulled, vec![0x5A; 2 * BUFFER_SIZE as usize])?;
+        fs::write(&later, b"later")?;
+        fs::write(folder.join("inside.txt"), b"inside")?;
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "volume lost test");
+        let options = BatchOptions {
+            simulate_target: Some(unplugged_mid_file),
+            ..Default::default()
+        };
+        let roots: Vec<_> = [&pulled, &later, &folder].iter().map(|p| Ok(p.to_string_lossy().to_string())).collect();
+        let result = run_wipe_batch(&ctx, roots, 3, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(!result.success);
+        assert_eq!(result.failures.len(), 1, "one failure for the volume: {:?}", result.failures);
+        match &result.failures[0].error {
+            BitBurnError::VolumeDisconnected { paths, .. } => {
+                let expected: Vec<String> = [&pulled, &later, &folder].iter().map(|p| p.to_string_lossy().to_string()).collect();
+                assert_eq!(paths, &expected);
             }
-        });
-        
-        // Verify the operation succeeded
-        assert!(result.is_ok(), "Wipe operation should succeed: {:?}", result);
-        
-        // Verify we saw all passes
-        let unique_passes = progress_patterns_seen.iter()
-            .filter(|p| p.contains("Pass"))
-            .filter(|p| !p.contains("Finalizing"))
-            .count();
-        assert_eq!(unique_passes, passes as usize, "Did not see all passes");
-        
-        // Verify pass numbering
-        for i in 1..=passes {
-            let pass_pattern = format!("Writing random data (Pass {}/{})", i, passes);
-            assert!(progress_patterns_seen.iter().any(|p| p == &pass_pattern),
-                "Missing pass {}", i);
+            other => panic!("expected VolumeDisconnected, got {:?}", other),
         }
-        
-        // Verify file is deleted
-        assert!(!file_path.exists(), "File should be deleted after wiping");
-        
+        // Only the file that hit the error was attempted.
+        assert_eq!(result.report.expect("batch should carry a report").files.len(), 1);
+        assert_eq!(fs::read(&later)?, b"later");
+        assert_eq!(fs::read(folder.join("inside.txt"))?, b"inside");
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn removal_phase_counts_are_grouped_by_thousands() {
+        assert_eq!(group_digits(0), "0");
+        assert_eq!(group_digits(999), "999");
+        assert_eq!(group_digits(120_000), "120,000");
+        assert_eq!(group_digits(1_480_000), "1,480,000");
+    }
+
+    #[test]
+    fn skipping_the_current_file_moves_on_to_the_next() -> io::Result<()> {
+        use bitburn_core::{MemoryTarget, BUFFER_SIZE};
+
+        // The second file's first pass writes one chunk, then hangs long enough to be skipped.
+        fn slow_second_file(path: &Path) -> Option<Box<dyn WipeTarget + Send>> {
+            let name = path.file_name()?.to_string_lossy();
+            name.starts_with("2_").then(|| {
+                let target = stall_watch::StallingTarget::new(
+                    MemoryTarget::new(vec![0x5A; 8 * BUFFER_SIZE as usize]),
+                    BUFFER_SIZE,
+                    Duration::from_secs(1),
+                );
+                Box::new(target) as Box<dyn WipeTarget + Send>
+            })
+        }
+
+        let test_dir = create_test_dir()?;
+        let files: Vec<PathBuf> = ["1_small.bin", "2_huge.iso", "3_small.bin"].iter().map(|n| test_dir.join(n)).collect();
+        for file in &files {
+            fs::write(file, vec![0x5A; 4096])?;
+        }
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "skip test");
+        let options = BatchOptions {
+            simulate_target: Some(slow_second_file),
+            ..Default::default()
+        };
+        let skipper = {
+            let (registry, operation_id, first) = (registry.clone(), ctx.operation.id().to_string(), files[0].clone());
+            thread::spawn(move || {
+                while first.exists() {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                // The second file is now in its stalled write.
+                thread::sleep(Duration::from_millis(300));
+                registry.skip_current_file(&operation_id)
+            })
+        };
+        let roots = files.iter().map(|f| Ok(f.to_string_lossy().to_string())).collect::<Vec<_>>();
+        let result = run_wipe_batch(&ctx, roots, 3, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(skipper.join().unwrap(), "the batch should still be registered");
+        assert!(result.success, "a skip is not a failure: {:?}", result.failures);
+        let report = result.report.expect("batch should carry a report");
+        assert_eq!(report.files.len(), 3);
+        assert_eq!(report.files[1].status, FileWipeStatus::SkippedByUser);
+        assert_eq!(report.files[1].passes.passes_completed, 0);
+        assert!(report.files[1].passes.bytes_of_last_pass < 8 * BUFFER_SIZE);
+        assert_eq!(report.files[2].status, FileWipeStatus::Success);
+        assert_eq!(
+            report.skipped,
+            vec![BitBurnError::SkippedByUser { path: files[1].to_string_lossy().to_string(), passes_completed: 0 }]
+        );
+        assert_eq!(report.wiped_files.len(), 2);
+        assert!(report.partially_wiped.is_empty(), "a skipped file is not a partial wipe");
+        assert!(files.iter().all(|f| !f.exists()));
+        assert!(!ctx.operation.cancel_flag().load(Ordering::SeqCst));
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn directory_rescan_wipes_files_added_between_passes() -> io::Result<()> {
+        let test_dir = create_test_dir()?;
+        create_test_file(&test_dir, &[0xAA; 1024])?;
+        create_test_file(&test_dir, &[0xBB; 1024])?;
+        let cancelled = CancellationToken::new();
+        let mut wiped = Vec::new();
+
+        let outcome = wipe_directory_files(
+            &test_dir,
+            None,
+            DEFAULT_CHUNK_FILES,
+            &cancelled,
+            |file, _| {
+                wiped.push(file.to_path_buf());
+                secure_wipe_file(file, 1, &WipeAlgorithm::NistClear, &CancellationToken::new(), |_: WipeProgress| {}).is_ok()
+            },
+            |scan| {
+                // Another process drops one more file into the folder after each of the first two passes.
+                if scan < 2 {
+                    let dir = test_dir.clone();
+                    thread::spawn(move || fs::write(dir.join(format!("late_{}.tmp", scan)), [0xCC; 512]))
+                        .join()
+                        .unwrap()
+                        .unwrap();
+                }
+            },
+        )
+        .expect("directory should settle within the re-scan limit");
+
+        assert_eq!(outcome.files_wiped, 4);
+        assert_eq!(outcome.rescanned_files, 2);
+        assert!(wiped.contains(&test_dir.join("late_0.tmp")));
+        assert!(wiped.contains(&test_dir.join("late_1.tmp")));
+        assert_eq!(fs::read_dir(&test_dir)?.count(), 0, "every file should have been wiped and removed");
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn directory_positions_follow_the_cached_scan_and_flag_changes() -> io::Result<()> {
+        let test_dir = create_test_dir()?;
+        fs::create_dir_all(test_dir.join("2023").join("Q4"))?;
+        for file in ["2023/Q4/a.bin", "2023/Q4/b.bin", "2023/c.bin", "d.bin"] {
+            fs::write(test_dir.join(file), [0xAA; 64])?;
+        }
+        let scan = TreeScan::walk(&test_dir);
+        // Removed after the pre-scan, before the wipe reaches it.
+        fs::remove_file(test_dir.join("2023").join("c.bin"))?;
+        let mut positions = Vec::new();
+
+        let outcome = wipe_directory_files(
+            &test_dir,
+            Some(scan),
+            DEFAULT_CHUNK_FILES,
+            &CancellationToken::new(),
+            |file, position| {
+                positions.push(position.clone());
+                secure_wipe_file(file, 1, &WipeAlgorithm::NistClear, &CancellationToken::new(), |_: WipeProgress| {}).is_ok()
+            },
+            |_| {},
+        )
+        .expect("directory should be wiped");
+
+        assert_eq!(outcome.files_wiped, 3);
+        let counters: Vec<(usize, usize, usize, usize, bool)> = positions
+            .iter()
+            .map(|p| (p.directory_files_done, p.directory_files_total, p.files_done, p.files_total, p.tree_changed))
+            .collect();
+        assert_eq!(counters, vec![(0, 2, 0, 4, false), (1, 2, 1, 4, false), (0, 1, 3, 4, true)]);
+        assert_eq!(positions[0].current_directory, test_dir.join("2023").join("Q4").to_string_lossy());
+        assert_eq!(positions[2].current_directory, test_dir.to_string_lossy());
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn folder_wipe_can_leave_the_parent_mtime_untouched() -> io::Result<()> {
+        use parent_dirs::{DirectoryFs, DirectoryTimes, RealDirectoryFs};
+
+        let test_dir = create_test_dir()?;
+        let folder = test_dir.join("project");
+        fs::create_dir_all(folder.join("src"))?;
+        fs::write(folder.join("src").join("main.rs"), [0xAA; 64])?;
+        let loose = create_test_file(&test_dir, &[0xBB; 64])?;
+        let old = std::time::SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+        RealDirectoryFs.set_times(&test_dir, DirectoryTimes { accessed: old, modified: old })?;
+        let before = fs::metadata(&test_dir)?.modified()?;
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "mtime test");
+        let options = BatchOptions { preserve_parent_mtime: Some(true), ..Default::default() };
+        let roots = vec![Ok(folder.to_string_lossy().to_string()), Ok(loose.to_string_lossy().to_string())];
+        let result = run_wipe_batch(&ctx, roots, 2, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(result.success, "{:?}", result.message);
+        assert!(!folder.exists() && !loose.exists());
+        assert!(result.report.unwrap().warnings.is_empty());
+        assert_eq!(fs::metadata(&test_dir)?.modified()?, before);
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn only_the_volume_roots_the_user_confirmed_are_let_through() -> io::Result<()> {
+        let test_dir = create_test_dir()?;
+        let root = if cfg!(windows) { "C:\\" } else { "/" };
+        let paths = vec![root.to_string(), test_dir.to_string_lossy().to_string()];
+        let refused = |confirmed: Vec<String>| {
+            let options = BatchOptions { confirmed_volume_roots: confirmed, ..Default::default() };
+            refuse_volume_roots(&paths, &options).map(|result| result.error)
+        };
+
+        let root_error = Some(Some(BitBurnError::VolumeRoot { path: root.to_string() }));
+        assert_eq!(refused(Vec::new()), root_error);
+        // Confirming the ordinary folder beside it says nothing about the volume.
+        assert_eq!(refused(vec![test_dir.to_string_lossy().to_string()]), root_error);
+        assert_eq!(refused(vec![root.to_string()]), None);
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn preserving_roots_empties_a_folder_but_keeps_it_and_its_permissions() -> io::Result<()> {
+        let test_dir = create_test_dir()?;
+        let drop_folder = test_dir.join("drop");
+        fs::create_dir_all(drop_folder.join("incoming").join("today"))?;
+        fs::write(drop_folder.join("incoming").join("today").join("batch.csv"), [0xAA; 256])?;
+        fs::write(drop_folder.join("incoming").join("readme.txt"), [0xBB; 64])?;
+        fs::write(drop_folder.join("top.bin"), [0xCC; 128])?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&drop_folder, fs::Permissions::from_mode(0o750))?;
+        }
+        let permissions = fs::metadata(&drop_folder)?.permissions();
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "preserve test");
+        let options = BatchOptions { preserve_roots: true, ..Default::default() };
+        let roots = vec![Ok(drop_folder.to_string_lossy().to_string())];
+        let result = run_wipe_batch(&ctx, roots, 1, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(result.success, "{:?}", result.message);
+        assert!(drop_folder.is_dir());
+        assert_eq!(fs::read_dir(&drop_folder)?.count(), 0);
+        assert_eq!(fs::metadata(&drop_folder)?.permissions(), permissions);
+        let report = result.report.unwrap();
+        assert_eq!(
+            report.preserved_roots,
+            vec![PreservedRoot {
+                path: drop_folder.to_string_lossy().to_string(),
+                subdirectories_removed: 2,
+                // The three files were deleted as they were wiped; only the emptied subfolders remained.
+                entries_removed: 2,
+            }]
+        );
+        assert_eq!(report.directories[0].files_wiped, 3);
+
+        cleanup_test_dir(&test_dir);
+        Ok(())
+    }
+
+    #[test]
+    fn directory_still_being_written_fails_instead_of_plain_delete() -> io::Result<()> {
+        let test_dir = create_test_dir()?;
+        create_test_file(&test_dir, &[0xAA; 1024])?;
+        let cancelled = CancellationToken::new();
+
+        let result = wipe_directory_files(
+            &test_dir,
+            None,
+            DEFAULT_CHUNK_FILES,
+            &cancelled,
+            |file, _| secure_wipe_file(file, 1, &WipeAlgorithm::NistClear, &CancellationToken::new(), |_: WipeProgress| {}).is_ok(),
+            |scan| {
+                let dir = test_dir.clone();
+                thread::spawn(move || fs::write(dir.join(format!("spool_{}.tmp", scan)), [0xCC; 512]))
+                    .join()
+                    .unwrap()
+                    .unwrap();
+            },
+        );
+
+        assert_eq!(
+            result,
+            Err(BitBurnError::DirectoryActive {
+                path: test_dir.to_string_lossy().to_string(),
+                rescans: MAX_DIRECTORY_RESCANS,
+            })
+        );
+        // The newest file was neither wiped nor deleted, and the directory is still there.
+        let spool = format!("spool_{}.tmp", MAX_DIRECTORY_RESCANS);
+        assert_eq!(fs::read(test_dir.join(spool))?, vec![0xCC; 512]);
+
         cleanup_test_dir(&test_dir);
         Ok(())
     }
@@ -0,0 +1,418 @@
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use std::path::{Path, PathBuf};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{Manager, Runtime};
+
+use crate::consent;
+use crate::error::BitBurnError;
+use crate::lock_scan::LockPolicy;
+use crate::messages;
+use crate::operations::OperationRegistry;
+use crate::path_expansion::{self, RelativeBase};
+use crate::platform::context_menu::{sanitize_target_path, ContextWipePayload};
+use crate::{log_event, start_file_wipe, BatchOptions, WipeAlgorithm, WipeResult};
+
+/// CLI flag naming a manifest file whose entries should be wiped.
+pub const PATHS_FROM_FLAG: &str = "--paths-from";
+
+/// Manifest layout, chosen from the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    /// One path per line.
+    PlainText,
+    /// Comma-separated with a header row containing a `path` column.
+    Csv,
+}
+
+impl ManifestFormat {
+    pub fn detect(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => ManifestFormat::Csv,
+            _ => ManifestFormat::PlainText,
+        }
+    }
+}
+
+/// Streaming reader over manifest entries.
+/// Yields one cleaned path per entry; unreadable lines are yielded as errors so they can be reported as skips.
+pub struct ManifestReader {
+    lines: Lines<BufReader<File>>,
+    format: ManifestFormat,
+    path_column: usize,
+    at_start: bool,
+    /// The folder holding the manifest, which relative entries are resolved against.
+    base: PathBuf,
+}
+
+impl ManifestReader {
+    pub fn open(path: &Path) -> Result<Self, BitBurnError> {
+        let file = File::open(path).map_err(|e| BitBurnError::io(&e, Some(path)))?;
+        let mut reader = ManifestReader {
+            lines: BufReader::new(file).lines(),
+            format: ManifestFormat::detect(path),
+            path_column: 0,
+            at_start: true,
+            base: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        };
+
+        if reader.format == ManifestFormat::Csv {
+            reader.path_column = reader.read_csv_header(path)?;
+        }
+
+        Ok(reader)
+    }
+
+    fn next_line(&mut self) -> Option<std::io::Result<String>> {
+        let line = self.lines.next()?;
+        let at_start = std::mem::replace(&mut self.at_start, false);
+        Some(line.map(|line| {
+            if at_start {
+                line.trim_start_matches('\u{feff}').to_string()
+            } else {
+                line
+            }
+        }))
+    }
+
+    fn read_csv_header(&mut self, path: &Path) -> Result<usize, BitBurnError> {
+        let invalid = |message: &str| BitBurnError::InvalidManifest {
+            path: path.to_string_lossy().to_string(),
+            message: message.to_string(),
+        };
+
+        while let Some(line) = self.next_line() {
+            let line = line.map_err(|e| BitBurnError::io(&e, Some(path)))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            return split_csv_line(&line)
+                .iter()
+                .position(|column| clean_entry(column).eq_ignore_ascii_case("path"))
+                .ok_or_else(|| invalid("CSV header has no \"path\" column"));
+        }
+
+        Err(invalid("CSV manifest is empty"))
+    }
+}
+
+impl Iterator for ManifestReader {
+    type Item = Result<String, BitBurnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.next_line()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(BitBurnError::io(&e, None))),
+            };
+
+            let entry = match self.format {
+                ManifestFormat::PlainText => clean_entry(&line),
+                ManifestFormat::Csv => split_csv_line(&line)
+                    .get(self.path_column)
+                    .map(|field| clean_entry(field))
+                    .unwrap_or_default(),
+            };
+
+            if !entry.is_empty() {
+                return Some(Ok(entry));
+            }
+        }
+    }
+}
+
+/// Strip whitespace, a leading BOM and one layer of surrounding quotes.
+fn clean_entry(raw: &str) -> String {
+    let trimmed = raw.trim().trim_start_matches('\u{feff}').trim();
+    let unquoted = trimmed
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .or_else(|| trimmed.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')))
+        .unwrap_or(trimmed);
+    unquoted.trim().to_string()
+}
+
+/// Split one CSV record, honoring quoted fields and doubled quotes.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+/// Read manifest entries, expand their environment variables, and run the standard sanitization
+/// on each one lazily. Relative entries are taken from the folder holding the manifest.
+/// Rejected entries come through as `Err` skips; duplicates of an already accepted path are dropped.
+pub fn sanitized_entries(reader: ManifestReader) -> impl Iterator<Item = Result<String, BitBurnError>> {
+    let mut seen = HashSet::new();
+    let base = reader.base.clone();
+    let sanitize = move |raw: String| {
+        path_expansion::expand(&raw, RelativeBase::Dir(&base)).and_then(|path| sanitize_target_path(&path))
+    };
+    reader.filter_map(move |entry| match entry.and_then(&sanitize) {
+        Ok(path) if !seen.insert(path.clone()) => None,
+        other => Some(other),
+    })
+}
+
+/// Label recorded as the batch source, e.g. `manifest:flagged.csv`.
+pub fn manifest_source(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+    format!("manifest:{}", name)
+}
+
+/// Value of `--paths-from <file>` when present on the command line.
+pub fn manifest_path_arg(argv: &[String]) -> Option<String> {
+    let index = argv.iter().position(|arg| arg == PATHS_FROM_FLAG)?;
+    argv.get(index + 1).filter(|value| !value.starts_with("--")).cloned()
+}
+
+/// Every path a manifest lists that passed sanitization, and the entries turned away, as skips.
+fn read_targets(manifest_path: &Path) -> Result<(Vec<String>, Vec<BitBurnError>), BitBurnError> {
+    let mut paths = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in sanitized_entries(ManifestReader::open(manifest_path)?) {
+        match entry {
+            Ok(path) => paths.push(path),
+            Err(err) => skipped.push(err),
+        }
+    }
+    Ok((paths, skipped))
+}
+
+/// Build the context-wipe payload for a CLI manifest so it goes through the usual confirmation flow.
+pub fn manifest_context_payload(manifest_path: &Path) -> ContextWipePayload {
+    let (paths, invalid) = match read_targets(manifest_path) {
+        Ok((paths, skipped)) => (paths, skipped.iter().map(ToString::to_string).collect()),
+        Err(err) => (Vec::new(), vec![err.to_string()]),
+    };
+
+    ContextWipePayload {
+        paths,
+        invalid,
+        source: manifest_source(manifest_path),
+        volume_roots: Vec::new(),
+        elevation_mismatch: None,
+        token: None,
+        assume_yes: false,
+    }
+}
+
+/// Wipe every path listed in a text or CSV manifest, through the same pre-flight checks, volume
+/// scheduling and crash journal as a selection. Entries that are missing, symlinks or otherwise
+/// refused are reported as skips; the manifest name is recorded in the report. The paths it lists
+/// are confirmed through `consent_token` or a prompt shown now.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn wipe_from_manifest<R: Runtime>(
+    window: tauri::Window<R>,
+    registry: tauri::State<'_, OperationRegistry>,
+    manifest_path: String,
+    algorithm: WipeAlgorithm,
+    passes: u32,
+    lock_policy: Option<LockPolicy>,
+    consent_token: Option<String>,
+) -> Result<WipeResult, BitBurnError> {
+    let manifest = PathBuf::from(&manifest_path);
+    let source = manifest_source(&manifest);
+    let (paths, skipped) = spawn_blocking(move || read_targets(&manifest))
+        .await
+        .map_err(|e| BitBurnError::internal(format!("wipe_from_manifest task join error: {}", e)))??;
+    log_event(
+        "wipe_manifest_start",
+        json!({"manifest": manifest_path, "entries": paths.len(), "skipped": skipped.len(), "algorithm": format!("{:?}", algorithm), "passes": passes}),
+    );
+    let prompt = messages::CONFIRM_MANIFEST_WIPE.render(json!({
+        "count": paths.len(),
+        "manifest": source,
+        "algorithm": format!("{:?}", algorithm),
+        "paths": consent::list_targets(&paths),
+    }));
+    let consent =
+        consent::confirm_targets_async(window.app_handle().clone(), consent_token, prompt.text, paths.clone()).await?;
+
+    let options = BatchOptions {
+        source: Some(source),
+        lock_policy,
+        carried_skips: skipped,
+        consent: Some(consent),
+        ..Default::default()
+    };
+    start_file_wipe(window.app_handle().clone(), window.label().to_string(), &registry, paths, passes, algorithm, options)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::SystemTime;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    fn read_entries(path: &Path) -> Vec<String> {
+        ManifestReader::open(path)
+            .expect("manifest should open")
+            .map(|entry| entry.expect("entry should read"))
+            .collect()
+    }
+
+    #[test]
+    fn plain_text_manifest_trims_whitespace_quotes_and_blank_lines() {
+        let dir = create_test_dir().unwrap();
+        let manifest = dir.join("flagged.txt");
+        fs::write(&manifest, "  C:/one.txt  \n\n\"C:/two words.txt\"\n'D:/three.bin'\n").unwrap();
+
+        assert_eq!(read_entries(&manifest), vec!["C:/one.txt", "C:/two words.txt", "D:/three.bin"]);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn crlf_and_bom_are_stripped() {
+        let dir = create_test_dir().unwrap();
+        let manifest = dir.join("flagged.txt");
+        fs::write(&manifest, "\u{feff}C:/one.txt\r\nC:/two.txt\r\n").unwrap();
+
+        assert_eq!(read_entries(&manifest), vec!["C:/one.txt", "C:/two.txt"]);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn csv_manifest_reads_path_column() {
+        let dir = create_test_dir().unwrap();
+        let manifest = dir.join("dlp.CSV");
+        fs::write(
+            &manifest,
+            "\u{feff}severity,\"Path\",rule\r\nhigh,\"C:/a, b.txt\",PII\r\nlow,C:/c.txt,\"say \"\"hi\"\"\"\r\nmedium\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(ManifestFormat::detect(&manifest), ManifestFormat::Csv);
+        assert_eq!(read_entries(&manifest), vec!["C:/a, b.txt", "C:/c.txt"]);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn csv_without_path_column_is_rejected() {
+        let dir = create_test_dir().unwrap();
+        let manifest = dir.join("dlp.csv");
+        fs::write(&manifest, "file,rule\nC:/a.txt,PII\n").unwrap();
+
+        let err = ManifestReader::open(&manifest).err().expect("header should be rejected");
+        assert_eq!(err.code(), "invalid_manifest");
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn missing_paths_become_skips_and_duplicates_collapse() {
+        let dir = create_test_dir().unwrap();
+        let present = dir.join("present.txt");
+        fs::write(&present, b"secret").unwrap();
+        let missing = dir.join("missing.txt");
+        let manifest = dir.join("flagged.txt");
+        fs::write(
+            &manifest,
+            format!("{}\n{}\n{}\n", present.display(), missing.display(), present.display()),
+        )
+        .unwrap();
+
+        let entries: Vec<_> = sanitized_entries(ManifestReader::open(&manifest).unwrap()).collect();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].as_ref().unwrap().ends_with("present.txt"));
+        assert_eq!(entries[1].as_ref().unwrap_err().code(), "path_not_found");
+
+        let payload = manifest_context_payload(&manifest);
+        assert_eq!(payload.paths.len(), 1);
+        assert_eq!(payload.invalid.len(), 1);
+        assert_eq!(payload.source, "manifest:flagged.txt");
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn relative_entries_are_taken_from_the_manifest_folder_and_variables_must_be_defined() {
+        let dir = create_test_dir().unwrap();
+        fs::create_dir_all(dir.join("exports")).unwrap();
+        fs::write(dir.join("exports").join("a.csv"), b"rows").unwrap();
+        let manifest = dir.join("flagged.txt");
+        let undefined = if cfg!(windows) { "%BITBURN_UNDEFINED_VAR%\\b.csv" } else { "$BITBURN_UNDEFINED_VAR/b.csv" };
+        fs::write(&manifest, format!("exports/a.csv\n{}\n", undefined)).unwrap();
+
+        let entries: Vec<_> = sanitized_entries(ManifestReader::open(&manifest).unwrap()).collect();
+        let expected = dir.join("exports").join("a.csv").canonicalize().unwrap();
+        assert_eq!(entries[0].as_deref(), Ok(expected.to_str().unwrap()));
+        assert_eq!(
+            entries[1],
+            Err(BitBurnError::UndefinedVariable { path: undefined.to_string(), variable: "BITBURN_UNDEFINED_VAR".into() })
+        );
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn a_manifest_wipe_needs_a_consent_to_exactly_the_paths_it_lists() {
+        use crate::consent::{ConsentOrigin, ConsentStore};
+        use crate::settings::{Settings, SettingsStore};
+
+        let dir = create_test_dir().unwrap();
+        let listed = dir.join("listed.txt");
+        fs::write(&listed, b"secret").unwrap();
+        let manifest = dir.join("flagged.txt");
+        fs::write(&manifest, format!("{}\n", listed.display())).unwrap();
+        let (paths, _) = read_targets(&manifest).unwrap();
+
+        let app = tauri::test::mock_app();
+        app.manage(OperationRegistry::default());
+        app.manage(ConsentStore::default());
+        app.manage(SettingsStore::in_memory(Settings { confirm_before_wipe: true, ..Default::default() }));
+        let window = tauri::WebviewWindowBuilder::new(&app, "main", Default::default()).build().unwrap();
+        let wipe = |token: &str| {
+            tauri::async_runtime::block_on(wipe_from_manifest(
+                window.as_ref().window(),
+                app.state::<OperationRegistry>(),
+                manifest.to_string_lossy().to_string(),
+                WipeAlgorithm::NistClear,
+                1,
+                None,
+                Some(token.to_string()),
+            ))
+        };
+        let store = app.state::<ConsentStore>();
+
+        let other = vec![dir.join("other.txt").to_string_lossy().to_string()];
+        let elsewhere = store.record("Wipe 1 item?", &other, true, ConsentOrigin::Dialog, SystemTime::now());
+        let refused = wipe(&elsewhere.token);
+        assert!(matches!(refused, Err(BitBurnError::ConfirmationRequired { reason }) if reason.contains("selection")));
+        assert!(listed.exists(), "nothing is wiped without consent");
+
+        let consent = store.record("Wipe 1 item?", &paths, true, ConsentOrigin::Dialog, SystemTime::now());
+        let result = wipe(&consent.token).unwrap();
+        assert!(result.success, "{:?}", result.message);
+        assert_eq!(result.report.and_then(|report| report.consent), Some(consent));
+        assert!(!listed.exists());
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn paths_from_flag_is_parsed() {
+        let args = vec!["BitBurn.exe".to_string(), PATHS_FROM_FLAG.to_string(), "C:/list.txt".to_string()];
+        assert_eq!(manifest_path_arg(&args), Some("C:/list.txt".to_string()));
+        assert_eq!(manifest_path_arg(&args[..2]), None);
+    }
+}
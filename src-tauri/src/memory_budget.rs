@@ -0,0 +1,75 @@
+//! Wipe buffers sized to the memory the machine has left. Every wipe wrote through 1 MB buffers
+//! whatever the machine, and on thin clients with 2 GB of RAM a few wipes at once pushed it into
+//! swapping, which made them slower than smaller buffers would have. When an operation starts,
+//! the memory available is read and `plan_buffer` shares it among the running jobs; a wipe that
+//! gets a smaller buffer than the full one logs `memory_constrained` and says so in its report.
+
+use bitburn_core::{plan_buffer, BUFFER_SIZE};
+use serde::Serialize;
+use serde_json::json;
+use sysinfo::{System, SystemExt};
+use tauri::{AppHandle, Runtime};
+
+use crate::{log_event, scheduler};
+
+/// Why an operation wrote through a smaller buffer than usual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MemoryConstraint {
+    /// Memory available when the operation started.
+    pub available_mb: u64,
+    /// Jobs running at the time, the operation included, which share that memory.
+    pub concurrent_jobs: usize,
+    pub requested_bytes: u64,
+    pub buffer_bytes: u64,
+}
+
+/// The constraint `plan_buffer` puts on an operation starting with `available_mb` free and
+/// `concurrent_jobs` running, or `None` when it keeps the full buffer.
+pub fn constraint(available_mb: u64, concurrent_jobs: usize) -> Option<MemoryConstraint> {
+    let plan = plan_buffer(available_mb, BUFFER_SIZE, concurrent_jobs);
+    plan.constrained.then_some(MemoryConstraint {
+        available_mb,
+        concurrent_jobs,
+        requested_bytes: BUFFER_SIZE,
+        buffer_bytes: plan.buffer_size,
+    })
+}
+
+fn available_mb() -> u64 {
+    let mut sys = System::new();
+    sys.refresh_memory();
+    sys.available_memory() / (1024 * 1024)
+}
+
+/// The constraint on `operation_id`, starting now alongside the jobs already running.
+pub fn check<R: Runtime>(app: &AppHandle<R>, operation_id: &str) -> Option<MemoryConstraint> {
+    let constraint = constraint(available_mb(), scheduler::running_jobs(app))?;
+    log_event(
+        "memory_constrained",
+        json!({
+            "operation_id": operation_id,
+            "available_mb": constraint.available_mb,
+            "concurrent_jobs": constraint.concurrent_jobs,
+            "requested_bytes": constraint.requested_bytes,
+            "buffer_bytes": constraint.buffer_bytes,
+        }),
+    );
+    Some(constraint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_a_smaller_buffer_is_a_constraint() {
+        assert_eq!(constraint(16_384, 2), None);
+        assert_eq!(constraint(700, 1), None);
+        let constrained = constraint(700, 2).expect("two wipes in 700 MB");
+        assert_eq!(
+            constrained,
+            MemoryConstraint { available_mb: 700, concurrent_jobs: 2, requested_bytes: BUFFER_SIZE, buffer_bytes: BUFFER_SIZE / 2 }
+        );
+        assert_eq!(serde_json::to_value(constrained).unwrap()["buffer_bytes"], BUFFER_SIZE / 2);
+    }
+}
@@ -0,0 +1,304 @@
+//! Catalog of every user-facing message the backend produces. Each message has a stable
+//! `message_code` and named `params` so the frontend can translate it, plus an English
+//! template rendered into `message` for logs and untranslated UIs.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::BitBurnError;
+
+/// One catalog entry. `{name}` in the template is replaced with the `name` parameter.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MessageDef {
+    pub code: &'static str,
+    pub template: &'static str,
+    pub params: &'static [&'static str],
+}
+
+/// A rendered message as sent to the frontend.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Message {
+    #[serde(rename = "message_code")]
+    pub code: String,
+    pub params: Map<String, Value>,
+    /// English rendering of the template.
+    #[serde(rename = "message")]
+    pub text: String,
+}
+
+impl MessageDef {
+    /// Render with `params`, a JSON object holding exactly the parameters this entry declares.
+    pub fn render(&self, params: Value) -> Message {
+        let params = match params {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        };
+        debug_assert!(
+            params.len() == self.params.len() && self.params.iter().all(|name| params.contains_key(*name)),
+            "{} expects parameters {:?}",
+            self.code,
+            self.params
+        );
+        let mut text = self.template.to_string();
+        for (name, value) in &params {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            text = text.replace(&format!("{{{}}}", name), &value);
+        }
+        Message {
+            code: self.code.to_string(),
+            params,
+            text,
+        }
+    }
+
+    /// Render an entry that takes no parameters.
+    pub fn text(&self) -> Message {
+        self.render(Value::Object(Map::new()))
+    }
+}
+
+impl Message {
+    /// Errors already carry a stable code and structured fields; reuse them as the message.
+    pub fn from_error(error: &BitBurnError) -> Self {
+        let mut params = match serde_json::to_value(error) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        params.remove("code");
+        Message {
+            code: error.code().to_string(),
+            params,
+            text: error.to_string(),
+        }
+    }
+}
+
+macro_rules! catalog {
+    ($($name:ident = $code:literal, $template:literal, [$($param:literal),*];)*) => {
+        $(pub const $name: MessageDef = MessageDef { code: $code, template: $template, params: &[$($param),*] };)*
+
+        /// Every entry, in declaration order.
+        pub const CATALOG: &[MessageDef] = &[$($name),*];
+    };
+}
+
+catalog! {
+    PATH_VALIDATED = "path_validated", "Path validation successful", [];
+    FILES_WIPED = "files_wiped", "Successfully wiped {count} files", ["count"];
+    FILES_WIPED_WITH_SKIPS = "files_wiped_with_skips", "Successfully wiped {count} files ({skipped} skipped)", ["count", "skipped"];
+    FILES_WIPED_ON_COPY_ON_WRITE = "files_wiped_on_copy_on_write",
+        "Overwrote {count} files ({skipped} skipped), but {cow_count} are on a copy-on-write file system \
+         ({file_systems}), which may keep earlier copies of their data on disk. Wipe the volume's free space, \
+         or rely on full-disk encryption.",
+        ["count", "skipped", "cow_count", "file_systems"];
+    FILE_NAMES_KEPT = "file_names_kept",
+        "File names were not replaced with random ones before deletion ({reason}); those files were deleted under \
+         their own names.",
+        ["reason"];
+    COPY_ON_WRITE_WARNING = "copy_on_write_warning",
+        "{count} files were on a copy-on-write file system ({file_systems}). Overwriting them writes new blocks, \
+         so their old contents and any snapshots of them may persist until the space is reused. Wipe the \
+         volume's free space, or rely on full-disk encryption.",
+        ["count", "file_systems"];
+    FILES_WIPED_WITH_ERRORS = "files_wiped_with_errors",
+        "Wiped {count} files with {errors} errors:\n{details}", ["count", "errors", "details"];
+    FILES_WIPED_WITH_SKIPS_AND_ERRORS = "files_wiped_with_skips_and_errors",
+        "Wiped {count} files ({skipped} skipped) with {errors} errors:\n{details}", ["count", "skipped", "errors", "details"];
+    FILE_WIPE_FAILED = "file_wipe_failed", "Failed to wipe {path}: {error}", ["path", "error"];
+    RANGE_WIPED = "range_wiped", "Wiped {length} bytes of {path} at offset {offset}", ["path", "offset", "length"];
+    FLASH_MEDIA_WARNING = "flash_media_warning",
+        "{count} selected items are on a USB flash drive or memory card. These devices move data around internally, \
+         so overwriting a file may leave old copies behind. Wipe the whole device, or encrypt it and wipe its free space.",
+        ["count"];
+    WEAR_WARNING = "wear_warning",
+        "This wipe would bring BitBurn's writes to the SSD at {volume} to {total_gb} GB in 30 days, past the \
+         {threshold_gb} GB warning level. Extra passes wear an SSD without erasing more; consider NIST 800-88 Clear.",
+        ["volume", "total_gb", "threshold_gb"];
+    REMOTE_COPY_MAY_PERSIST = "remote_copy_may_persist",
+        "{path} was recalled from remote storage before it was wiped. Only the local copy was destroyed; the copy \
+         in the cloud or archive tier may persist and must be deleted there.",
+        ["path"];
+    SELF_TEST_PASSED = "self_test_passed", "All {count} self-test checks passed on {volume}", ["count", "volume"];
+    SELF_TEST_FAILED = "self_test_failed", "{failed} of {count} self-test checks failed on {volume}", ["failed", "count", "volume"];
+    SELF_TEST_WIPE_FAILED = "self_test_wipe_failed",
+        "The wipe engine could not wipe the test file: {error}. Check that BitBurn may write to this volume and that \
+         no antivirus or backup program is holding its scratch folder open.",
+        ["error"];
+    SELF_TEST_VERIFICATION_UNSUPPORTED = "self_test_verification_unsupported",
+        "Verification unsupported: the file system on {volume} compresses data transparently, so the bytes on disk \
+         are not the bytes written. Turn off compression for the folders you wipe, or wipe the volume's free space.",
+        ["volume"];
+    SELF_TEST_VERIFICATION_MISMATCH = "self_test_verification_mismatch",
+        "Verification failed: the test file on {volume} did not read back as written. A driver, filter or the device \
+         itself is changing data on its way to disk; do not rely on file wiping on this volume.",
+        ["volume"];
+    SELF_TEST_STREAMS_UNSUPPORTED = "self_test_streams_unsupported",
+        "{volume} does not support named data streams, so there are none to check", ["volume"];
+    SELF_TEST_STREAM_REMAINS = "self_test_stream_remains",
+        "A named data stream on the test file survived the wipe. Turn on wipe_named_streams and check that no \
+         security software is blocking access to streams.",
+        [];
+    SELF_TEST_FILE_REMAINS = "self_test_file_remains",
+        "The test file {path} still exists after the wipe. Another program may be holding it open; exclude BitBurn's \
+         scratch folders from antivirus, backup and sync software.",
+        ["path"];
+    SELF_TEST_ENTRY_REMAINS = "self_test_entry_remains",
+        "The test file's name is still listed in {path} after the wipe. The file system or a sync client is keeping \
+         deleted entries; check its recycle or versioning settings.",
+        ["path"];
+    SELF_TEST_SCRATCH_REMAINS = "self_test_scratch_remains",
+        "The self-test could not remove its scratch folder {path}; delete it by hand.", ["path"];
+    DIRECTORY_REMOVE_FAILED = "directory_remove_failed", "Failed to remove directory {path}: {error}", ["path", "error"];
+    FREE_SPACE_WIPED = "free_space_wiped", "Successfully wiped free space", [];
+    FREE_SPACE_WIPED_TO_QUOTA = "free_space_wiped_to_quota",
+        "Wiped free space up to your disk quota only: {written_mb} MB written of {free_mb} MB free on the volume. \
+         Free space outside the quota was not overwritten.",
+        ["written_mb", "free_mb", "bytes_written", "volume_free_bytes"];
+    FREE_SPACE_TEMP_REMOVE_FAILED = "free_space_temp_remove_failed", "Failed to remove existing temporary file: {error}", ["error"];
+    FREE_SPACE_TEMP_CREATE_FAILED = "free_space_temp_create_failed", "Failed to create temporary file: {error}", ["error"];
+    FREE_SPACE_TEMP_WRITE_FAILED = "free_space_temp_write_failed", "Failed to write to temporary file: {error}", ["error"];
+    FREE_SPACE_WIPE_FAILED = "free_space_wipe_failed", "Failed to wipe free space: {error}", ["error"];
+    FREE_SPACE_RELEASED_INCOMPLETE = "free_space_released_incomplete",
+        "Released the space held by the cancelled free-space wipe. The wipe is incomplete: the space received \
+         {passes_received} of {passes_requested} passes.",
+        ["passes_received", "passes_requested"];
+    FREE_SPACE_MINIMAL_WIPE_FINISHED = "free_space_minimal_wipe_finished",
+        "Finished a minimal wipe of the cancelled free-space wipe with one zero pass: the space received \
+         {passes_received} of {passes_requested} passes.",
+        ["passes_received", "passes_requested"];
+    CONFIRM_FILE_WIPE = "confirm_file_wipe",
+        "You are about to permanently erase {count} file(s) using:\n\nAlgorithm: {algorithm}\nDescription: {description}\n\n\
+         THIS ACTION CANNOT BE UNDONE!\n\nAre you absolutely sure you want to continue?",
+        ["count", "algorithm", "description"];
+    CONFIRM_FREE_SPACE_WIPE = "confirm_free_space_wipe",
+        "You are about to wipe all free space on the selected drive using:\n\nAlgorithm: {algorithm}\nDescription: {description}\n\n\
+         THIS ACTION CANNOT BE UNDONE!\n\nAre you absolutely sure you want to continue?",
+        ["algorithm", "description"];
+    CONFIRM_CONTEXT_WIPE = "confirm_context_wipe",
+        "You are about to permanently erase {files} file(s) ({size_mb} MB) from {items} selected item(s) using:\n\n\
+         Algorithm: {algorithm}\n\nTHIS ACTION CANNOT BE UNDONE!\n\nAre you absolutely sure you want to continue?",
+        ["files", "size_mb", "bytes", "items", "algorithm"];
+    CONFIRM_RESUME_WIPE = "confirm_resume_wipe",
+        "You are about to permanently erase {count} item(s) an earlier wipe did not finish, using:\n\n\
+         Algorithm: {algorithm}\n\n{paths}\n\nTHIS ACTION CANNOT BE UNDONE!\n\nAre you absolutely sure you want to continue?",
+        ["count", "algorithm", "paths"];
+    CONFIRM_RETRY_WIPE = "confirm_retry_wipe",
+        "You are about to try again to permanently erase {count} item(s) an earlier wipe could not, using:\n\n\
+         Algorithm: {algorithm}\n\n{paths}\n\nTHIS ACTION CANNOT BE UNDONE!\n\nAre you absolutely sure you want to continue?",
+        ["count", "algorithm", "paths"];
+    CONFIRM_MANIFEST_WIPE = "confirm_manifest_wipe",
+        "You are about to permanently erase {count} item(s) listed in {manifest}, using:\n\n\
+         Algorithm: {algorithm}\n\n{paths}\n\nTHIS ACTION CANNOT BE UNDONE!\n\nAre you absolutely sure you want to continue?",
+        ["count", "manifest", "algorithm", "paths"];
+    CONFIRM_PRESET_WIPE = "confirm_preset_wipe",
+        "You are about to permanently erase {files} file(s) in {preset}, using:\n\n\
+         Algorithm: {algorithm}\n\n{paths}\n\nTHIS ACTION CANNOT BE UNDONE!\n\nAre you absolutely sure you want to continue?",
+        ["files", "preset", "algorithm", "paths"];
+    CONFIRM_RANGE_WIPE = "confirm_range_wipe",
+        "You are about to permanently overwrite {length} bytes of {path} from offset {offset} using:\n\n\
+         Algorithm: {algorithm}\n\nTHIS ACTION CANNOT BE UNDONE!\n\nAre you absolutely sure you want to continue?",
+        ["length", "path", "offset", "algorithm"];
+    CONFIRM_DIALOG_TITLE = "confirm_dialog_title", "⚠️ WARNING ⚠️", [];
+    CONTEXT_MENU_REGISTERED = "context_menu_registered", "Context menu registered for files and folders", [];
+    CONTEXT_MENU_REMOVED = "context_menu_removed", "Context menu removed", [];
+    CONTEXT_MENU_ENABLED = "context_menu_enabled", "Context menu is registered", [];
+    CONTEXT_MENU_DISABLED = "context_menu_disabled", "Context menu is not registered", [];
+    CONTEXT_MENU_UNAVAILABLE = "context_menu_unavailable", "Context menu not available on this platform", [];
+    QUICK_ACTION_INSTALLED = "quick_action_installed", "Finder Quick Action \"Shred with BitBurn\" installed", [];
+    QUICK_ACTION_REMOVED = "quick_action_removed", "Finder Quick Action removed", [];
+    QUICK_ACTION_CURRENT = "quick_action_current", "Finder Quick Action is installed", [];
+    QUICK_ACTION_MISSING = "quick_action_missing", "Finder Quick Action is not installed", [];
+    QUICK_ACTION_STALE = "quick_action_stale",
+        "Finder Quick Action points to {location}, not this copy of BitBurn; register it again", ["location"];
+    FILE_MANAGER_INSTALLED = "file_manager_installed", "\"Shred with BitBurn\" added to Nautilus and Dolphin", [];
+    FILE_MANAGER_REMOVED = "file_manager_removed", "File manager entries removed", [];
+    FILE_MANAGER_CURRENT = "file_manager_current", "File manager entries are installed", [];
+    FILE_MANAGER_MISSING = "file_manager_missing", "File manager entries are not installed", [];
+    FILE_MANAGER_INCOMPLETE = "file_manager_incomplete", "File manager entries are incomplete; register them again", [];
+    FILE_MANAGER_STALE = "file_manager_stale",
+        "File manager entries point to {locations}, not this copy of BitBurn; register them again", ["locations"];
+    SENDTO_CREATED = "sendto_created", "Send To shortcut created", [];
+    SENDTO_REMOVED = "sendto_removed", "Send To shortcut removed", [];
+    SENDTO_CURRENT = "sendto_current", "Send To shortcut is installed", [];
+    SENDTO_MISSING = "sendto_missing", "Send To shortcut is not installed", [];
+    SENDTO_STALE = "sendto_stale", "Send To shortcut points to {target}, not this copy of BitBurn; register it again", ["target"];
+    SENDTO_UNAVAILABLE = "sendto_unavailable", "Send To shortcut not available on this platform", [];
+    AUTOSTART_ENABLED = "autostart_enabled", "Autostart enabled", [];
+    AUTOSTART_DISABLED = "autostart_disabled", "Autostart disabled", [];
+    AUTOSTART_IS_ENABLED = "autostart_is_enabled", "Autostart is enabled", [];
+    AUTOSTART_IS_DISABLED = "autostart_is_disabled", "Autostart is disabled", [];
+    AUTOSTART_UNSUPPORTED = "autostart_unsupported", "Autostart not supported on this platform", [];
+    WINDOW_LAYOUT_RESET = "window_layout_reset", "Window layout reset", [];
+    REPORT_EXPORTED = "report_exported", "Report exported to {path}", ["path"];
+    CONFIGURATION_EXPORTED = "configuration_exported", "Configuration exported to {path}", ["path"];
+    CONFIGURATION_IMPORTED = "configuration_imported", "Configuration imported from {path}", ["path"];
+    WEBHOOK_SAVED = "webhook_saved", "Webhook settings saved", [];
+    SIGNATURE_VALID = "signature_valid", "Signature is valid", [];
+    REPORT_NOT_SIGNED = "report_not_signed", "Report is not signed", [];
+    SIGNATURE_ALGORITHM_UNSUPPORTED = "signature_algorithm_unsupported", "Unsupported signature algorithm", [];
+    SIGNATURE_MALFORMED = "signature_malformed", "Malformed signature", [];
+    SIGNING_KEY_UNKNOWN = "signing_key_unknown", "Signed by a key not present in the local keyring", [];
+    SIGNATURE_MISMATCH = "signature_mismatch", "Signature does not match the report contents", [];
+    RETENTION_IDLE = "retention_idle", "No files are past their retention age", [];
+    RETENTION_PENDING = "retention_pending",
+        "{files} files in {folders} folders will be wiped in {minutes} minutes", ["files", "folders", "minutes"];
+    RETENTION_CANCELLED_BEFORE_START = "retention_cancelled_before_start", "Retention run cancelled", [];
+    RETENTION_CANCELLED = "retention_cancelled", "Retention run cancelled after wiping {wiped} files", ["wiped"];
+    RETENTION_FAILED = "retention_failed", "Retention run wiped {wiped} files with errors", ["wiped"];
+    RETENTION_COMPLETED = "retention_completed", "Retention run wiped {wiped} files", ["wiped"];
+}
+
+/// Every catalog entry, so the frontend can check its translations cover each code.
+#[tauri::command]
+pub fn get_message_catalog() -> Vec<MessageDef> {
+    CATALOG.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    fn placeholders(template: &str) -> HashSet<&str> {
+        template
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn codes_are_unique_and_templates_only_use_declared_params() {
+        let mut codes = HashSet::new();
+        for def in CATALOG {
+            assert!(codes.insert(def.code), "duplicate message code {}", def.code);
+            let declared: HashSet<&str> = def.params.iter().copied().collect();
+            assert_eq!(declared.len(), def.params.len(), "{} declares a parameter twice", def.code);
+            for name in placeholders(def.template) {
+                assert!(declared.contains(name), "{} uses undeclared parameter {}", def.code, name);
+            }
+        }
+    }
+
+    #[test]
+    fn render_fills_the_template_and_keeps_params() {
+        let message = FILES_WIPED_WITH_SKIPS.render(json!({"count": 3, "skipped": 1}));
+        assert_eq!(message.text, "Successfully wiped 3 files (1 skipped)");
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({"message_code": "files_wiped_with_skips", "params": {"count": 3, "skipped": 1}, "message": message.text})
+        );
+    }
+
+    #[test]
+    fn errors_become_messages_with_their_fields_as_params() {
+        let message = Message::from_error(&BitBurnError::PathNotFound { path: "C:/gone".into() });
+        assert_eq!(message.code, "path_not_found");
+        assert_eq!(message.params, json!({"path": "C:/gone"}).as_object().unwrap().clone());
+        assert_eq!(message.text, "Path not found: C:/gone");
+    }
+}
@@ -0,0 +1,199 @@
+//! Named (alternate) data streams on NTFS. Deleting a file or removing a folder deletes its
+//! streams without overwriting them, and neither the overwrite of a file's main stream nor the
+//! file walker ever sees them, which makes a stream a classic place for data to hide. With
+//! `wipe_named_streams` or file name obfuscation on, a batch overwrites and deletes the streams of
+//! every file before wiping it and of every folder it is about to remove. Other file systems have
+//! no named streams and are skipped silently.
+
+use serde::Serialize;
+use serde_json::json;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use tauri::State;
+use walkdir::WalkDir;
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::settings::SettingsStore;
+use bitburn_core::{
+    overwrite_target, CancellationToken, FileWipeProgressRecord, WipeAlgorithm, WipeError, WipeProgress, WipeTarget,
+};
+
+/// The named streams wiped from one file or folder.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NamedStreams {
+    pub path: String,
+    pub streams_wiped: usize,
+}
+
+/// The named data streams of `path`, as `:name:$DATA`. The unnamed main stream is left out, and
+/// a file system without streams has none.
+#[cfg(windows)]
+pub fn list(path: &Path) -> Vec<OsString> {
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+    use windows_sys::Win32::Storage::FileSystem::{
+        FindClose, FindFirstStreamW, FindNextStreamW, FindStreamInfoStandard, WIN32_FIND_STREAM_DATA,
+    };
+
+    const MAIN_STREAM: &str = "::$DATA";
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut data: WIN32_FIND_STREAM_DATA = unsafe { std::mem::zeroed() };
+    let data_ptr = &mut data as *mut WIN32_FIND_STREAM_DATA as *mut std::ffi::c_void;
+    let handle = unsafe { FindFirstStreamW(wide.as_ptr(), FindStreamInfoStandard, data_ptr, 0) };
+    if handle == INVALID_HANDLE_VALUE {
+        return Vec::new();
+    }
+    let mut streams = Vec::new();
+    loop {
+        let name = &data.cStreamName;
+        let name = OsString::from_wide(&name[..name.iter().position(|&c| c == 0).unwrap_or(name.len())]);
+        if name != MAIN_STREAM {
+            streams.push(name);
+        }
+        if unsafe { FindNextStreamW(handle, data_ptr) } == 0 {
+            break;
+        }
+    }
+    unsafe { FindClose(handle) };
+    streams
+}
+
+#[cfg(not(windows))]
+pub fn list(_path: &Path) -> Vec<OsString> {
+    Vec::new()
+}
+
+/// Overwrite, truncate and delete every named stream of `path`, returning how many there were.
+pub fn wipe(path: &Path, passes: u32, algorithm: &WipeAlgorithm, cancel: &CancellationToken) -> Result<usize, WipeError> {
+    let streams = list(path);
+    for stream in &streams {
+        let mut stream_path = path.as_os_str().to_os_string();
+        stream_path.push(stream);
+        let stream_path = PathBuf::from(stream_path);
+        let mut file = OpenOptions::new().read(true).write(true).open(&stream_path).map_err(WipeError::Io)?;
+        let mut record = FileWipeProgressRecord::new(passes);
+        let rng = &mut rand::thread_rng();
+        overwrite_target(&mut file, passes, algorithm, rng, cancel, |_: WipeProgress| {}, &mut record)?;
+        file.truncate().map_err(WipeError::Io)?;
+        drop(file);
+        fs::remove_file(&stream_path).map_err(WipeError::Io)?;
+    }
+    Ok(streams.len())
+}
+
+/// Wipe the named streams of `path`, recording it in `wiped` if it had any.
+pub fn wipe_recorded(
+    path: &Path,
+    passes: u32,
+    algorithm: &WipeAlgorithm,
+    cancel: &CancellationToken,
+    wiped: &mut Vec<NamedStreams>,
+) -> Result<(), WipeError> {
+    if !cfg!(windows) {
+        return Ok(());
+    }
+    let streams_wiped = wipe(path, passes, algorithm, cancel)?;
+    if streams_wiped > 0 {
+        log_event("named_streams_wiped", json!({"path": path.to_string_lossy(), "streams": streams_wiped}));
+        wiped.push(NamedStreams { path: path.to_string_lossy().to_string(), streams_wiped });
+    }
+    Ok(())
+}
+
+/// Wipe the named streams of `root` and every folder and file under it, recording each one that
+/// had any in `wiped`. The batch wipes a file's streams along with the file, so this finds those of
+/// files it left behind. `root` itself is skipped when it stays in place; links are not followed.
+pub fn wipe_tree(
+    root: &Path,
+    keep_root: bool,
+    passes: u32,
+    algorithm: &WipeAlgorithm,
+    cancel: &CancellationToken,
+    wiped: &mut Vec<NamedStreams>,
+) -> Result<(), BitBurnError> {
+    if !cfg!(windows) {
+        return Ok(());
+    }
+    let walk = WalkDir::new(root).follow_links(false).min_depth(usize::from(keep_root));
+    for entry in walk.into_iter().flatten().filter(|entry| !entry.path_is_symlink()) {
+        let path = entry.path();
+        wipe_recorded(path, passes, algorithm, cancel, wiped).map_err(|e| BitBurnError::from_wipe_error(e, path))?;
+    }
+    Ok(())
+}
+
+/// Overwrite the named data streams of files and folders before removing them.
+#[tauri::command]
+pub async fn set_wipe_named_streams(settings: State<'_, SettingsStore>, enabled: bool) -> Result<bool, BitBurnError> {
+    let updated = settings.update(|s| s.wipe_named_streams = enabled)?;
+    log_event("wipe_named_streams_configured", json!({"enabled": updated.wipe_named_streams}));
+    Ok(updated.wipe_named_streams)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    /// A fresh test directory holding one empty folder, `inner`.
+    fn scratch_dir() -> PathBuf {
+        let dir = create_test_dir().unwrap();
+        fs::create_dir_all(dir.join("inner")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn folders_without_streams_are_left_alone() {
+        let dir = scratch_dir();
+        let mut wiped = Vec::new();
+        wipe_tree(&dir, false, 1, &WipeAlgorithm::NistClear, &CancellationToken::new(), &mut wiped).unwrap();
+        assert!(wiped.is_empty());
+        assert!(dir.join("inner").is_dir());
+        cleanup_test_dir(&dir);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn folder_streams_are_overwritten_and_deleted() {
+        let dir = scratch_dir();
+        let stream = PathBuf::from(format!("{}:hidden", dir.join("inner").display()));
+        // Not every temp directory is on NTFS; there is nothing to test without stream support.
+        if fs::write(&stream, b"the secret kept beside the folder").is_err() {
+            return;
+        }
+        assert_eq!(list(&dir.join("inner")), [OsString::from(":hidden:$DATA")]);
+
+        let mut wiped = Vec::new();
+        wipe_tree(&dir, false, 1, &WipeAlgorithm::NistClear, &CancellationToken::new(), &mut wiped).unwrap();
+        assert_eq!(
+            wiped,
+            [NamedStreams { path: dir.join("inner").to_string_lossy().to_string(), streams_wiped: 1 }]
+        );
+        assert!(list(&dir.join("inner")).is_empty());
+        assert!(fs::read(&stream).is_err(), "the stream can no longer be read");
+        bitburn_core::remove_tree(&dir, &CancellationToken::new(), |_, _| {}).unwrap();
+        assert!(!dir.exists());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn file_streams_are_overwritten_and_deleted_with_the_folders() {
+        let dir = scratch_dir();
+        let file = dir.join("inner").join("notes.txt");
+        fs::write(&file, b"visible").unwrap();
+        let stream = PathBuf::from(format!("{}:hidden", file.display()));
+        if fs::write(&stream, b"the secret kept beside the file").is_err() {
+            return;
+        }
+
+        let mut wiped = Vec::new();
+        wipe_tree(&dir, false, 1, &WipeAlgorithm::NistClear, &CancellationToken::new(), &mut wiped).unwrap();
+        assert_eq!(wiped, [NamedStreams { path: file.to_string_lossy().to_string(), streams_wiped: 1 }]);
+        assert!(list(&file).is_empty());
+        assert_eq!(fs::read(&file).unwrap(), b"visible", "the main stream is the batch's to wipe");
+        cleanup_test_dir(&dir);
+    }
+}
@@ -0,0 +1,198 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::diagnostics::OperationDiagnostics;
+
+/// Maximum number of events kept per operation; older events are dropped first.
+pub const OPERATION_LOG_CAPACITY: usize = 2_000;
+
+#[derive(Debug, Default)]
+struct LogBuffer {
+    events: VecDeque<Value>,
+    dropped: u64,
+}
+
+/// Bounded, shareable event buffer for a single operation.
+#[derive(Debug, Clone)]
+pub struct OperationLogHandle {
+    operation_id: String,
+    capacity: usize,
+    buffer: Arc<Mutex<LogBuffer>>,
+    diagnostics: OperationDiagnostics,
+}
+
+/// Captured events for an operation, as returned to the UI and written into exported reports.
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationLogView {
+    pub operation_id: String,
+    pub events: Vec<Value>,
+    pub dropped: u64,
+}
+
+thread_local! {
+    static CURRENT_LOG: RefCell<Option<OperationLogHandle>> = const { RefCell::new(None) };
+}
+
+impl OperationLogHandle {
+    pub fn new(operation_id: impl Into<String>) -> Self {
+        Self::with_capacity(operation_id, OPERATION_LOG_CAPACITY)
+    }
+
+    pub fn with_capacity(operation_id: impl Into<String>, capacity: usize) -> Self {
+        OperationLogHandle {
+            operation_id: operation_id.into(),
+            capacity: capacity.max(1),
+            buffer: Arc::new(Mutex::new(LogBuffer::default())),
+            diagnostics: OperationDiagnostics::default(),
+        }
+    }
+
+    /// Count evicted events in `diagnostics` as well as in the view's `dropped`.
+    pub fn with_diagnostics(mut self, diagnostics: OperationDiagnostics) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+
+    pub fn operation_id(&self) -> &str {
+        &self.operation_id
+    }
+
+    /// Append an event explicitly, independent of the current thread's scope.
+    pub fn record(&self, event: &str, fields: &Value) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let mut buffer = self.lock();
+        if buffer.events.len() >= self.capacity {
+            buffer.events.pop_front();
+            buffer.dropped += 1;
+            self.diagnostics.log_entry_dropped();
+        }
+        buffer
+            .events
+            .push_back(json!({"timestamp_ms": timestamp_ms, "event": event, "fields": fields}));
+    }
+
+    pub fn view(&self) -> OperationLogView {
+        let buffer = self.lock();
+        OperationLogView {
+            operation_id: self.operation_id.clone(),
+            events: buffer.events.iter().cloned().collect(),
+            dropped: buffer.dropped,
+        }
+    }
+
+    /// Route `log_event` calls on the current thread into this log until the scope is dropped.
+    pub fn enter(&self) -> OperationLogScope {
+        let previous = CURRENT_LOG.with(|current| current.replace(Some(self.clone())));
+        OperationLogScope { previous }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LogBuffer> {
+        self.buffer.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Restores the previously active operation log (if any) when dropped.
+pub struct OperationLogScope {
+    previous: Option<OperationLogHandle>,
+}
+
+impl Drop for OperationLogScope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_LOG.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// Operation id of the log active on the current thread.
+pub fn current_operation_id() -> Option<String> {
+    CURRENT_LOG.with(|current| current.borrow().as_ref().map(|log| log.operation_id().to_string()))
+}
+
+/// Record an event into the current thread's operation log, if one is active.
+pub fn capture(event: &str, fields: &Value) {
+    CURRENT_LOG.with(|current| {
+        if let Some(log) = current.borrow().as_ref() {
+            log.record(event, fields);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn buffer_is_bounded_and_counts_dropped_events() {
+        let diagnostics = OperationDiagnostics::default();
+        let log = OperationLogHandle::with_capacity("op-1", 3).with_diagnostics(diagnostics.clone());
+        for i in 0..5 {
+            log.record("tick", &json!({"i": i}));
+        }
+        let view = log.view();
+        assert_eq!(view.events.len(), 3);
+        assert_eq!(view.dropped, 2);
+        assert_eq!(view.events[0]["fields"]["i"], 2);
+        assert_eq!(diagnostics.snapshot().log_entries_dropped, 2);
+    }
+
+    #[test]
+    fn scope_routes_capture_and_restores_previous() {
+        let outer = OperationLogHandle::new("outer");
+        let inner = OperationLogHandle::new("inner");
+
+        capture("ignored", &json!({}));
+        {
+            let _outer_scope = outer.enter();
+            capture("first", &json!({}));
+            {
+                let _inner_scope = inner.enter();
+                assert_eq!(current_operation_id().as_deref(), Some("inner"));
+                capture("nested", &json!({}));
+            }
+            capture("second", &json!({}));
+        }
+        assert_eq!(current_operation_id(), None);
+
+        let outer_events: Vec<_> = outer.view().events.iter().map(|e| e["event"].clone()).collect();
+        assert_eq!(outer_events, vec![json!("first"), json!("second")]);
+        assert_eq!(inner.view().events.len(), 1);
+    }
+
+    #[test]
+    fn concurrent_operations_do_not_cross_contaminate() {
+        let logs: Vec<_> = (0..2).map(|i| OperationLogHandle::new(format!("op-{}", i))).collect();
+        let handles: Vec<_> = logs
+            .iter()
+            .cloned()
+            .map(|log| {
+                thread::spawn(move || {
+                    let _scope = log.enter();
+                    for step in 0..500 {
+                        crate::log_event("wipe_step", json!({"step": step}));
+                        assert_eq!(current_operation_id().as_deref(), Some(log.operation_id()));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().expect("worker panicked");
+        }
+
+        for log in &logs {
+            let view = log.view();
+            assert_eq!(view.events.len(), 500);
+            assert!(view
+                .events
+                .iter()
+                .all(|event| event["fields"]["operation_id"] == json!(log.operation_id())));
+        }
+    }
+}
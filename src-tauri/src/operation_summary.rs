@@ -0,0 +1,236 @@
+//! The results card shown when an operation ends. The frontend used to piece it together from the
+//! result message, its own timers and the last progress it saw; `operation_summary` now carries
+//! the backend's figures instead, and `get_operation` returns them once the operation is gone.
+//! The summary is built from the report's counters rather than its file list, so it stays the
+//! same size for a million files as for one.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::operations::{OperationKind, OperationOutcome, OperationSnapshot, OutcomeStatus};
+use crate::report::WipeReport;
+use crate::WipeAlgorithm;
+
+/// Error codes listed in `top_errors`.
+const TOP_ERRORS: usize = 5;
+/// Warnings carried in full; the rest are only counted.
+const SUMMARY_WARNINGS: usize = 5;
+
+/// How many of an operation's failures had one error code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorCount {
+    pub code: String,
+    pub count: usize,
+}
+
+/// Payload of `operation_summary`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OperationSummary {
+    pub operation_id: String,
+    pub kind: OperationKind,
+    /// `None` until the operation has a recorded result, e.g. when it was dropped without one.
+    pub status: Option<OutcomeStatus>,
+    /// Seconds since the Unix epoch.
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub duration_secs: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<WipeAlgorithm>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passes: Option<u32>,
+    pub files_ok: usize,
+    pub files_failed: usize,
+    pub files_skipped: usize,
+    /// Size of the files wiped in full plus the free space filled.
+    pub bytes_written: u64,
+    /// The most frequent error codes, most frequent first.
+    pub top_errors: Vec<ErrorCount>,
+    pub warnings: Vec<String>,
+    /// Warnings the report holds beyond those in `warnings`.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub more_warnings: usize,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+/// What is known about an operation when it ends.
+pub struct FinishedOperation<'a> {
+    pub snapshot: &'a OperationSnapshot,
+    /// Milliseconds since the Unix epoch, in UTC.
+    pub started_at_ms: u64,
+    pub finished_at_ms: u64,
+    /// Measured on the monotonic clock, so a clock change while the operation ran does not skew it.
+    pub duration_ms: u64,
+    pub algorithm: Option<&'a (WipeAlgorithm, u32)>,
+    pub outcome: Option<&'a OperationOutcome>,
+    pub report: Option<&'a WipeReport>,
+}
+
+/// The `TOP_ERRORS` most frequent codes, ties in code order.
+pub fn top_errors<'a>(codes: impl IntoIterator<Item = &'a str>) -> Vec<ErrorCount> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for code in codes {
+        *counts.entry(code).or_default() += 1;
+    }
+    let mut counts: Vec<ErrorCount> =
+        counts.into_iter().map(|(code, count)| ErrorCount { code: code.to_string(), count }).collect();
+    // Stable, so equal counts stay in code order.
+    counts.sort_by_key(|error| std::cmp::Reverse(error.count));
+    counts.truncate(TOP_ERRORS);
+    counts
+}
+
+pub fn summarize(finished: &FinishedOperation<'_>) -> OperationSummary {
+    let report = finished.report;
+    let failures = report.map(|report| report.failures.iter().map(|error| error.code()).collect::<Vec<_>>());
+    let top = match (&failures, finished.outcome.and_then(|outcome| outcome.error_code.as_deref())) {
+        (Some(codes), _) if !codes.is_empty() => top_errors(codes.iter().copied()),
+        // Refused or failed before there was a report: the error that stopped it.
+        (_, Some(code)) if code != "cancelled" => top_errors([code]),
+        _ => Vec::new(),
+    };
+    let warnings = report.map(|report| report.warnings.as_slice()).unwrap_or_default();
+    let bytes_written = report.map_or(0, |report| {
+        let files: u64 = report.roots.iter().map(|root| root.bytes_wiped).sum();
+        files + report.free_space.as_ref().map_or(0, |coverage| coverage.total_bytes_written)
+    });
+    OperationSummary {
+        operation_id: finished.snapshot.operation_id.clone(),
+        kind: finished.snapshot.kind,
+        status: finished.outcome.map(|outcome| outcome.status),
+        started_at: finished.started_at_ms / 1000,
+        finished_at: finished.finished_at_ms / 1000,
+        duration_secs: finished.duration_ms as f64 / 1000.0,
+        algorithm: finished.algorithm.map(|(algorithm, _)| algorithm.clone()),
+        passes: finished.algorithm.map(|(_, passes)| *passes),
+        files_ok: report.map_or(0, |report| report.files_wiped),
+        files_failed: failures.map_or(0, |codes| codes.len()),
+        files_skipped: report.map_or(0, |report| report.skipped.len()),
+        bytes_written,
+        top_errors: top,
+        warnings: warnings.iter().take(SUMMARY_WARNINGS).cloned().collect(),
+        more_warnings: warnings.len().saturating_sub(SUMMARY_WARNINGS),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::BitBurnError;
+    use crate::messages;
+    use crate::report::RootReport;
+
+    fn snapshot() -> OperationSnapshot {
+        OperationSnapshot {
+            operation_id: "op-1".to_string(),
+            kind: OperationKind::WipeFiles,
+            started_at: 1_700_000_000,
+            current_phase: "Finished".to_string(),
+            overall_percentage: 100.0,
+            target_summary: "C:/data".to_string(),
+        }
+    }
+
+    fn in_use(path: &str) -> BitBurnError {
+        BitBurnError::FileInUse { path: path.to_string(), pids: Vec::new() }
+    }
+
+    fn not_found(path: &str) -> BitBurnError {
+        BitBurnError::PathNotFound { path: path.to_string() }
+    }
+
+    #[test]
+    fn failures_are_grouped_by_code_and_capped() {
+        let codes = ["e", "a", "b", "a", "c", "d", "f", "b", "a", "g"];
+        let top = top_errors(codes);
+        let summary: Vec<_> = top.iter().map(|error| (error.code.as_str(), error.count)).collect();
+        assert_eq!(summary, [("a", 3), ("b", 2), ("c", 1), ("d", 1), ("e", 1)]);
+        assert!(top_errors([]).is_empty());
+    }
+
+    #[test]
+    fn a_large_batch_is_summarized_from_its_counters() {
+        let mut report = WipeReport::new("op-1", None);
+        report.files_wiped = 1_000_000;
+        report.failures = (0..30).map(|i| if i % 3 == 0 { not_found("x") } else { in_use("y") }).collect();
+        report.skipped = vec![BitBurnError::SkippedByUser { path: "z".to_string(), passes_completed: 1 }];
+        report.roots = [4096, 1 << 30]
+            .into_iter()
+            .enumerate()
+            .map(|(root_index, bytes_wiped)| RootReport {
+                root_index,
+                path: format!("root{}", root_index),
+                files_attempted: 0,
+                succeeded: 0,
+                failed: 0,
+                skipped: 0,
+                bytes_wiped,
+                error: None,
+                files: Vec::new(),
+            })
+            .collect();
+        report.warnings = (0..8).map(|i| format!("warning {}", i)).collect();
+        let outcome = OperationOutcome {
+            status: OutcomeStatus::Failed,
+            message: messages::FILES_WIPED.render(serde_json::json!({"count": 1})),
+            error_code: None,
+            failure_count: 30,
+        };
+        let algorithm = (WipeAlgorithm::NistPurge, 3);
+
+        let summary = summarize(&FinishedOperation {
+            snapshot: &snapshot(),
+            started_at_ms: 1_700_000_000_250,
+            finished_at_ms: 1_700_000_090_750,
+            duration_ms: 90_500,
+            algorithm: Some(&algorithm),
+            outcome: Some(&outcome),
+            report: Some(&report),
+        });
+        assert_eq!((summary.started_at, summary.finished_at), (1_700_000_000, 1_700_000_090));
+        assert_eq!(summary.duration_secs, 90.5);
+        assert_eq!((summary.algorithm, summary.passes), (Some(WipeAlgorithm::NistPurge), Some(3)));
+        assert_eq!((summary.files_ok, summary.files_failed, summary.files_skipped), (1_000_000, 30, 1));
+        assert_eq!(summary.bytes_written, 4096 + (1 << 30));
+        let top: Vec<_> = summary.top_errors.iter().map(|error| (error.code.as_str(), error.count)).collect();
+        assert_eq!(top, [("file_in_use", 20), ("path_not_found", 10)]);
+        assert_eq!((summary.warnings.len(), summary.more_warnings), (5, 3));
+        assert_eq!(summary.status, Some(OutcomeStatus::Failed));
+    }
+
+    #[test]
+    fn an_operation_refused_before_it_had_a_report_shows_its_error() {
+        let outcome = OperationOutcome::from_result(&crate::WipeResult::failure(BitBurnError::ProtectedPath {
+            path: "C:/Windows".to_string(),
+        }));
+        // A clock stepped back while it ran leaves the duration as measured.
+        let summary = summarize(&FinishedOperation {
+            snapshot: &snapshot(),
+            started_at_ms: 5_000,
+            finished_at_ms: 4_000,
+            duration_ms: 1_500,
+            algorithm: None,
+            outcome: Some(&outcome),
+            report: None,
+        });
+        assert_eq!((summary.started_at, summary.finished_at, summary.duration_secs), (5, 4, 1.5));
+        assert_eq!(summary.top_errors, [ErrorCount { code: "protected_path".to_string(), count: 1 }]);
+        assert_eq!((summary.files_ok, summary.files_failed, summary.bytes_written), (0, 0, 0));
+        let json = serde_json::to_value(&summary).unwrap();
+        assert!(json.get("algorithm").is_none() && json.get("more_warnings").is_none());
+
+        let cancelled = OperationOutcome::from_result(&crate::WipeResult::failure(BitBurnError::Cancelled));
+        let summary = summarize(&FinishedOperation {
+            snapshot: &snapshot(),
+            started_at_ms: 4_000,
+            finished_at_ms: 5_000,
+            duration_ms: 1_000,
+            algorithm: None,
+            outcome: Some(&cancelled),
+            report: None,
+        });
+        assert!(summary.top_errors.is_empty());
+    }
+}
@@ -0,0 +1,858 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::diagnostics::{self, OperationDiagnostics};
+use crate::error::BitBurnError;
+use crate::messages::{self, Message};
+use crate::operation_log::{OperationLogHandle, OperationLogScope, OperationLogView};
+use crate::operation_summary::{self, FinishedOperation, OperationSummary};
+use crate::outcomes::OutcomeStore;
+use crate::report::WipeReport;
+use crate::scratch;
+use crate::settings::SettingsStore;
+use crate::signing::ReportSigner;
+use crate::{log_event, WipeAlgorithm, WipeResult};
+
+/// Number of finished operations whose logs and reports stay available for the details pane.
+const FINISHED_RECORD_RETENTION: usize = 32;
+
+/// Kinds of destructive operations tracked by the registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    WipeFiles,
+    FreeSpaceWipe,
+}
+
+/// Point-in-time view of a running operation, shared with the frontend and tray.
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationSnapshot {
+    pub operation_id: String,
+    pub kind: OperationKind,
+    pub started_at: u64,
+    pub current_phase: String,
+    pub overall_percentage: f32,
+    pub target_summary: String,
+}
+
+/// Final status of a finished operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeStatus {
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// How an operation ended, derived from the `WipeResult` its command returned.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OperationOutcome {
+    pub status: OutcomeStatus,
+    #[serde(flatten)]
+    pub message: Message,
+    pub error_code: Option<String>,
+    pub failure_count: usize,
+}
+
+impl OperationOutcome {
+    pub fn from_result(result: &WipeResult) -> Self {
+        let status = if result.success {
+            OutcomeStatus::Completed
+        } else if result.error == Some(BitBurnError::Cancelled) {
+            OutcomeStatus::Cancelled
+        } else {
+            OutcomeStatus::Failed
+        };
+        OperationOutcome {
+            status,
+            message: result.message.clone(),
+            error_code: result.error.as_ref().map(|e| e.code().to_string()),
+            failure_count: result.failures.len(),
+        }
+    }
+}
+
+/// Log, report and outcome kept for an operation while it runs and for a while after it finishes.
+#[derive(Debug, Clone)]
+struct OperationRecord {
+    log: OperationLogHandle,
+    diagnostics: OperationDiagnostics,
+    report: Option<WipeReport>,
+    outcome: Option<OperationOutcome>,
+    /// Milliseconds since the Unix epoch, for the summary's start time.
+    started_at_ms: u64,
+    /// For the summary's duration, which the wall clock would get wrong across a clock change.
+    started: Instant,
+    algorithm: Option<(WipeAlgorithm, u32)>,
+    /// Set when the operation finishes.
+    summary: Option<OperationSummary>,
+}
+
+#[derive(Debug, Default)]
+struct RecordTable {
+    records: HashMap<String, OperationRecord>,
+    finished: VecDeque<String>,
+}
+
+/// Central registry of in-flight operations.
+/// Cheap to clone; all clones share the same underlying table so worker threads can update snapshots.
+#[derive(Clone, Default)]
+pub struct OperationRegistry {
+    operations: Arc<Mutex<HashMap<String, OperationSnapshot>>>,
+    records: Arc<Mutex<RecordTable>>,
+    cancel_flags: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    skip_flags: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    next_sequence: Arc<AtomicU64>,
+}
+
+impl OperationRegistry {
+    /// Register a new operation and return its generated id.
+    pub fn register(&self, kind: OperationKind, target_summary: impl Into<String>) -> OperationSnapshot {
+        self.register_with_cancel(kind, target_summary, Arc::new(AtomicBool::new(false)))
+    }
+
+    /// Register an operation whose cancellation flag is shared with the caller,
+    /// so `cancel` reaches work that also watches another source of cancellation.
+    pub fn register_with_cancel(
+        &self,
+        kind: OperationKind,
+        target_summary: impl Into<String>,
+        cancelled: Arc<AtomicBool>,
+    ) -> OperationSnapshot {
+        let started_at_ms = now_ms();
+        let started_at = started_at_ms / 1000;
+        let sequence = self.next_sequence.fetch_add(1, Ordering::SeqCst) + 1;
+        let snapshot = OperationSnapshot {
+            operation_id: format!("op-{:x}-{}", started_at, sequence),
+            kind,
+            started_at,
+            current_phase: "Starting".to_string(),
+            overall_percentage: 0.0,
+            target_summary: target_summary.into(),
+        };
+
+        let diagnostics = OperationDiagnostics::default();
+        self.lock_records().records.insert(
+            snapshot.operation_id.clone(),
+            OperationRecord {
+                log: OperationLogHandle::new(snapshot.operation_id.clone()).with_diagnostics(diagnostics.clone()),
+                diagnostics,
+                report: None,
+                outcome: None,
+                started_at_ms,
+                started: Instant::now(),
+                algorithm: None,
+                summary: None,
+            },
+        );
+        self.lock_cancel_flags()
+            .insert(snapshot.operation_id.clone(), cancelled);
+        self.lock_skip_flags()
+            .insert(snapshot.operation_id.clone(), Arc::new(AtomicBool::new(false)));
+        self.lock()
+            .insert(snapshot.operation_id.clone(), snapshot.clone());
+        snapshot
+    }
+
+    /// Update the phase and overall percentage of a registered operation.
+    /// Unknown ids are ignored so late progress ticks after unregistering are harmless.
+    pub fn update(&self, operation_id: &str, current_phase: &str, overall_percentage: f32) {
+        if let Some(snapshot) = self.lock().get_mut(operation_id) {
+            snapshot.current_phase = current_phase.to_string();
+            snapshot.overall_percentage = overall_percentage.clamp(0.0, 100.0);
+        }
+    }
+
+    /// Remove an operation from the active table; its log and report are retained for later inspection.
+    pub fn unregister(&self, operation_id: &str) -> Option<OperationSnapshot> {
+        self.lock_cancel_flags().remove(operation_id);
+        self.lock_skip_flags().remove(operation_id);
+        let snapshot = self.lock().remove(operation_id)?;
+
+        let mut table = self.lock_records();
+        if table.records.contains_key(operation_id) {
+            table.finished.push_back(operation_id.to_string());
+            while table.finished.len() > FINISHED_RECORD_RETENTION {
+                if let Some(expired) = table.finished.pop_front() {
+                    table.records.remove(&expired);
+                }
+            }
+        }
+        Some(snapshot)
+    }
+
+    /// Ask a running operation to stop. Returns false when no such operation is active.
+    pub fn cancel(&self, operation_id: &str) -> bool {
+        match self.lock_cancel_flags().get(operation_id) {
+            Some(flag) => {
+                flag.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Ask every running operation to stop at once, returning the ones that were signalled.
+    pub fn cancel_all(&self) -> Vec<OperationSnapshot> {
+        let signalled: Vec<String> = {
+            let flags = self.lock_cancel_flags();
+            for flag in flags.values() {
+                flag.store(true, Ordering::SeqCst);
+            }
+            flags.keys().cloned().collect()
+        };
+        self.active()
+            .into_iter()
+            .filter(|snapshot| signalled.contains(&snapshot.operation_id))
+            .collect()
+    }
+
+    pub fn cancel_flag(&self, operation_id: &str) -> Option<Arc<AtomicBool>> {
+        self.lock_cancel_flags().get(operation_id).cloned()
+    }
+
+    /// Ask a running batch to give up on the file it is wiping and move on to the next one.
+    /// The operation itself keeps running. Returns false when no such operation is active.
+    pub fn skip_current_file(&self, operation_id: &str) -> bool {
+        match self.lock_skip_flags().get(operation_id) {
+            Some(flag) => {
+                flag.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn skip_flag(&self, operation_id: &str) -> Option<Arc<AtomicBool>> {
+        self.lock_skip_flags().get(operation_id).cloned()
+    }
+
+    pub fn log(&self, operation_id: &str) -> Option<OperationLogHandle> {
+        self.lock_records()
+            .records
+            .get(operation_id)
+            .map(|record| record.log.clone())
+    }
+
+    /// Event and log counters of a running or recently finished operation.
+    pub fn diagnostics(&self, operation_id: &str) -> Option<OperationDiagnostics> {
+        self.lock_records()
+            .records
+            .get(operation_id)
+            .map(|record| record.diagnostics.clone())
+    }
+
+    pub fn attach_report(&self, operation_id: &str, report: WipeReport) {
+        if let Some(record) = self.lock_records().records.get_mut(operation_id) {
+            record.report = Some(report);
+        }
+    }
+
+    pub fn report(&self, operation_id: &str) -> Option<WipeReport> {
+        self.lock_records()
+            .records
+            .get(operation_id)
+            .and_then(|record| record.report.clone())
+    }
+
+    /// Note on `original`'s report that `retry` retried its failed files.
+    pub fn link_retry(&self, original: &str, retry: &str) {
+        if let Some(report) = self.lock_records().records.get_mut(original).and_then(|record| record.report.as_mut()) {
+            report.retried_by.push(retry.to_string());
+        }
+    }
+
+    pub fn record_outcome(&self, operation_id: &str, outcome: OperationOutcome) {
+        if let Some(record) = self.lock_records().records.get_mut(operation_id) {
+            record.outcome = Some(outcome);
+        }
+    }
+
+    pub fn record_algorithm(&self, operation_id: &str, algorithm: &WipeAlgorithm, passes: u32) {
+        if let Some(record) = self.lock_records().records.get_mut(operation_id) {
+            record.algorithm = Some((algorithm.clone(), passes));
+        }
+    }
+
+    /// Summarize an operation `unregister` has just returned `snapshot` for, and keep the summary.
+    fn finish_summary(&self, snapshot: &OperationSnapshot) -> Option<OperationSummary> {
+        let mut table = self.lock_records();
+        let record = table.records.get_mut(&snapshot.operation_id)?;
+        let summary = operation_summary::summarize(&FinishedOperation {
+            snapshot,
+            started_at_ms: record.started_at_ms,
+            finished_at_ms: now_ms(),
+            duration_ms: record.started.elapsed().as_millis() as u64,
+            algorithm: record.algorithm.as_ref(),
+            outcome: record.outcome.as_ref(),
+            report: record.report.as_ref(),
+        });
+        record.summary = Some(summary.clone());
+        Some(summary)
+    }
+
+    /// The summary of a recently finished operation.
+    pub fn summary(&self, operation_id: &str) -> Option<OperationSummary> {
+        self.lock_records()
+            .records
+            .get(operation_id)
+            .and_then(|record| record.summary.clone())
+    }
+
+    pub fn outcome(&self, operation_id: &str) -> Option<OperationOutcome> {
+        self.lock_records()
+            .records
+            .get(operation_id)
+            .and_then(|record| record.outcome.clone())
+    }
+
+    pub fn get(&self, operation_id: &str) -> Option<OperationSnapshot> {
+        self.lock().get(operation_id).cloned()
+    }
+
+    /// All active operations, oldest first.
+    pub fn active(&self) -> Vec<OperationSnapshot> {
+        let mut snapshots: Vec<_> = self.lock().values().cloned().collect();
+        snapshots.sort_by(|a, b| {
+            a.started_at
+                .cmp(&b.started_at)
+                .then_with(|| a.operation_id.cmp(&b.operation_id))
+        });
+        snapshots
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.lock().is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, OperationSnapshot>> {
+        // A panic while holding the lock only affects snapshot bookkeeping; keep serving.
+        self.operations.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn lock_records(&self) -> std::sync::MutexGuard<'_, RecordTable> {
+        self.records.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn lock_cancel_flags(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<AtomicBool>>> {
+        self.cancel_flags.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn lock_skip_flags(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<AtomicBool>>> {
+        self.skip_flags.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Registers an operation for its lifetime and emits lifecycle events.
+/// Dropping the guard unregisters the operation and emits `operation_finished`,
+/// so every early return in a command is covered.
+pub struct OperationGuard<R: Runtime> {
+    registry: OperationRegistry,
+    app: AppHandle<R>,
+    operation_id: String,
+    cancelled: Arc<AtomicBool>,
+    skip: Arc<AtomicBool>,
+    diagnostics: OperationDiagnostics,
+}
+
+impl<R: Runtime> OperationGuard<R> {
+    pub fn begin(
+        app: &AppHandle<R>,
+        registry: &OperationRegistry,
+        kind: OperationKind,
+        target_summary: impl Into<String>,
+    ) -> Self {
+        let snapshot = registry.register(kind, target_summary);
+        Self::track(app, registry, snapshot)
+    }
+
+    /// Like `begin`, but `cancel_operation` sets the caller's existing flag.
+    pub fn begin_with_cancel(
+        app: &AppHandle<R>,
+        registry: &OperationRegistry,
+        kind: OperationKind,
+        target_summary: impl Into<String>,
+        cancelled: Arc<AtomicBool>,
+    ) -> Self {
+        let snapshot = registry.register_with_cancel(kind, target_summary, cancelled);
+        Self::track(app, registry, snapshot)
+    }
+
+    fn track(app: &AppHandle<R>, registry: &OperationRegistry, snapshot: OperationSnapshot) -> Self {
+        let operation_id = snapshot.operation_id.clone();
+        let cancelled = registry.cancel_flag(&operation_id).unwrap_or_default();
+        let skip = registry.skip_flag(&operation_id).unwrap_or_default();
+        let diagnostics = registry.diagnostics(&operation_id).unwrap_or_default();
+        diagnostics::connect_operation(app, &operation_id, &diagnostics);
+        diagnostics::emit(app, "operation_registered", snapshot);
+        OperationGuard {
+            registry: registry.clone(),
+            app: app.clone(),
+            operation_id,
+            cancelled,
+            skip,
+            diagnostics,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.operation_id
+    }
+
+    /// Flag set by `cancel_operation`; workers poll it between chunks.
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        self.cancelled.clone()
+    }
+
+    /// Flag set by `skip_current_file`; batch workers clear it as each file starts.
+    pub fn skip_flag(&self) -> Arc<AtomicBool> {
+        self.skip.clone()
+    }
+
+    /// Counters of the events this operation sends and loses.
+    pub fn diagnostics(&self) -> OperationDiagnostics {
+        self.diagnostics.clone()
+    }
+
+    pub fn update(&self, current_phase: &str, overall_percentage: f32) {
+        self.registry
+            .update(&self.operation_id, current_phase, overall_percentage);
+    }
+
+    /// Tag `log_event` calls on the current (worker) thread with this operation until the scope drops.
+    pub fn enter_log_scope(&self) -> Option<OperationLogScope> {
+        self.registry.log(&self.operation_id).map(|log| log.enter())
+    }
+
+    pub fn attach_report(&self, report: WipeReport) {
+        self.registry.attach_report(&self.operation_id, report);
+    }
+
+    /// Link this operation and `original`, whose failed files it retries, in both their reports.
+    pub fn link_retry_of(&self, original: &str, report: &mut WipeReport) {
+        report.retry_of = Some(original.to_string());
+        self.registry.link_retry(original, &self.operation_id);
+    }
+
+    /// Remember the algorithm and passes for the operation's summary.
+    pub fn record_algorithm(&self, algorithm: &WipeAlgorithm, passes: u32) {
+        self.registry.record_algorithm(&self.operation_id, algorithm, passes);
+    }
+
+    /// Remember how the operation ended so completion hooks can report it.
+    pub fn record_result(&self, result: &WipeResult) {
+        self.registry
+            .record_outcome(&self.operation_id, OperationOutcome::from_result(result));
+    }
+}
+
+impl<R: Runtime> Drop for OperationGuard<R> {
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.registry.unregister(&self.operation_id) {
+            let report = self.registry.report(&self.operation_id);
+            if let Some(report) = &report {
+                crate::performance::record_finished(&self.app, report);
+            }
+            crate::webhook::notify_operation_finished(&self.app, &snapshot, self.registry.outcome(&self.operation_id), report);
+            // Ahead of `operation_finished`, so the card is ready when the progress view closes.
+            if let Some(summary) = self.registry.finish_summary(&snapshot) {
+                diagnostics::emit(&self.app, "operation_summary", summary);
+            }
+            diagnostics::emit(&self.app, "operation_finished", snapshot);
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+/// Summarize a selection of paths for display in operation snapshots.
+pub fn summarize_targets(paths: &[String]) -> String {
+    match paths {
+        [] => "No targets".to_string(),
+        [single] => single.clone(),
+        [first, rest @ ..] => format!("{} and {} more", first, rest.len()),
+    }
+}
+
+/// Overall batch percentage given the current root's position and its own completion fraction.
+pub fn batch_percentage(root_index: usize, root_count: usize, root_fraction: f32) -> f32 {
+    if root_count == 0 {
+        return 100.0;
+    }
+    let fraction = root_fraction.clamp(0.0, 1.0);
+    ((root_index as f32 + fraction) / root_count as f32 * 100.0).clamp(0.0, 100.0)
+}
+
+/// List every destructive operation currently running.
+#[tauri::command]
+pub async fn get_active_operations(
+    registry: State<'_, OperationRegistry>,
+) -> Result<Vec<OperationSnapshot>, BitBurnError> {
+    Ok(registry.active())
+}
+
+/// A running operation's snapshot, or a finished one's summary.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum OperationView {
+    Running(OperationSnapshot),
+    Finished(OperationSummary),
+}
+
+/// Fetch a running operation's snapshot, or the summary of one that finished recently.
+#[tauri::command]
+pub async fn get_operation(
+    registry: State<'_, OperationRegistry>,
+    operation_id: String,
+) -> Result<OperationView, BitBurnError> {
+    if let Some(snapshot) = registry.get(&operation_id) {
+        return Ok(OperationView::Running(snapshot));
+    }
+    registry
+        .summary(&operation_id)
+        .map(OperationView::Finished)
+        .ok_or(BitBurnError::OperationNotFound { operation_id })
+}
+
+/// Cancel a running operation by id. Unlike a window event this does not depend on
+/// the webview that started the operation, so a reloaded frontend can still stop it.
+#[tauri::command]
+pub async fn cancel_operation(
+    registry: State<'_, OperationRegistry>,
+    operation_id: String,
+) -> Result<(), BitBurnError> {
+    if !registry.cancel(&operation_id) {
+        return Err(BitBurnError::OperationNotFound { operation_id });
+    }
+    log_event("operation_cancel_requested", json!({"operation_id": operation_id}));
+    Ok(())
+}
+
+/// Stop wiping the file a batch is on and continue with the next one. The skipped file is
+/// deleted without finishing its passes and reported as skipped; the rest of the batch runs.
+#[tauri::command]
+pub async fn skip_current_file(
+    registry: State<'_, OperationRegistry>,
+    operation_id: String,
+) -> Result<(), BitBurnError> {
+    if !registry.skip_current_file(&operation_id) {
+        return Err(BitBurnError::OperationNotFound { operation_id });
+    }
+    log_event("operation_skip_file_requested", json!({"operation_id": operation_id}));
+    Ok(())
+}
+
+/// Events captured for a running or recently finished operation.
+#[tauri::command]
+pub async fn get_operation_log(
+    registry: State<'_, OperationRegistry>,
+    operation_id: String,
+) -> Result<OperationLogView, BitBurnError> {
+    registry
+        .log(&operation_id)
+        .map(|log| log.view())
+        .ok_or(BitBurnError::OperationNotFound { operation_id })
+}
+
+/// Write an operation's report and captured events to `dest_path` as JSON. Signing, the copy and
+/// the write run on a blocking thread: spilled outcomes of a huge selection can take a while to
+/// copy, and signing may first have to read or generate the key.
+#[tauri::command]
+pub async fn export_wipe_report<R: Runtime>(
+    app: AppHandle<R>,
+    registry: State<'_, OperationRegistry>,
+    settings: State<'_, SettingsStore>,
+    outcomes: State<'_, OutcomeStore>,
+    operation_id: String,
+    dest_path: String,
+) -> Result<WipeResult, BitBurnError> {
+    let log = registry
+        .log(&operation_id)
+        .ok_or_else(|| BitBurnError::OperationNotFound { operation_id: operation_id.clone() })?;
+    let report = registry.report(&operation_id);
+    let outcomes_source = match report.as_ref().and_then(|report| report.outcomes.as_ref()) {
+        Some(_) => Some(outcomes.path_for(&operation_id)?),
+        None => None,
+    };
+    let mut document = Map::new();
+    document.insert("operation_id".to_string(), json!(operation_id));
+    document.insert("report".to_string(), json!(report));
+    document.insert("events".to_string(), json!(log.view().events));
+    if let Some(counters) = registry.diagnostics(&operation_id).map(|d| d.snapshot()).filter(|c| c.has_drops()) {
+        document.insert("diagnostics".to_string(), json!(counters));
+    }
+    let signed = settings.get().sign_reports;
+
+    let dest = dest_path.clone();
+    let outcomes_path = tauri::async_runtime::spawn_blocking(move || -> Result<Option<String>, BitBurnError> {
+        if signed {
+            app.state::<ReportSigner>().sign_document(&mut document)?;
+        }
+        // Spilled outcomes are copied beside the report, which names their hash, rather than read back in.
+        let outcomes_path = match outcomes_source {
+            Some(source) => {
+                let copy = format!("{}.outcomes.jsonl", dest);
+                fs::copy(&source, &copy).map_err(|e| BitBurnError::io(&e, Some(Path::new(&copy))))?;
+                Some(copy)
+            }
+            None => None,
+        };
+        let serialized = serde_json::to_string_pretty(&document)
+            .map_err(|e| BitBurnError::internal(format!("Failed to serialize report: {}", e)))?;
+        scratch::write_via_scratch(Path::new(&dest), serialized.as_bytes())?;
+        Ok(outcomes_path)
+    })
+    .await
+    .map_err(|e| BitBurnError::internal(format!("export_wipe_report task join error: {}", e)))??;
+
+    log_event(
+        "wipe_report_exported",
+        json!({"operation_id": operation_id, "path": dest_path, "signed": signed, "outcomes_path": outcomes_path}),
+    );
+    Ok(WipeResult::success(messages::REPORT_EXPORTED.render(json!({"path": dest_path}))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn register_update_and_unregister_round_trip() {
+        let registry = OperationRegistry::default();
+        let snapshot = registry.register(OperationKind::WipeFiles, "C:/data");
+        assert!(registry.is_active());
+
+        registry.update(&snapshot.operation_id, "Pass 1/3", 42.0);
+        let current = registry.get(&snapshot.operation_id).expect("operation should exist");
+        assert_eq!(current.current_phase, "Pass 1/3");
+        assert_eq!(current.overall_percentage, 42.0);
+
+        let finished = registry.unregister(&snapshot.operation_id).expect("operation should unregister");
+        assert_eq!(finished.operation_id, snapshot.operation_id);
+        assert!(!registry.is_active());
+        assert!(registry.get(&snapshot.operation_id).is_none());
+    }
+
+    #[test]
+    fn update_clamps_percentage_and_ignores_unknown_ids() {
+        let registry = OperationRegistry::default();
+        let snapshot = registry.register(OperationKind::FreeSpaceWipe, "D:\\");
+        registry.update(&snapshot.operation_id, "Filling", 140.0);
+        assert_eq!(registry.get(&snapshot.operation_id).unwrap().overall_percentage, 100.0);
+
+        registry.update("missing", "Filling", 10.0);
+        assert_eq!(registry.active().len(), 1);
+    }
+
+    #[test]
+    fn concurrent_updates_keep_snapshots_isolated() {
+        let registry = OperationRegistry::default();
+        let handles: Vec<_> = (0..8)
+            .map(|worker| {
+                let registry = registry.clone();
+                thread::spawn(move || {
+                    let snapshot = registry.register(OperationKind::WipeFiles, format!("worker-{}", worker));
+                    for step in 0..=100 {
+                        registry.update(&snapshot.operation_id, &format!("worker-{} step", worker), step as f32);
+                    }
+                    snapshot.operation_id
+                })
+            })
+            .collect();
+
+        let ids: Vec<String> = handles.into_iter().map(|h| h.join().expect("worker panicked")).collect();
+        let active = registry.active();
+        assert_eq!(active.len(), 8);
+
+        let mut unique = ids.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), 8, "operation ids must be unique");
+
+        for snapshot in &active {
+            assert_eq!(snapshot.overall_percentage, 100.0);
+            let worker = snapshot.target_summary.trim_start_matches("worker-");
+            assert_eq!(snapshot.current_phase, format!("worker-{} step", worker));
+        }
+
+        let removers: Vec<_> = ids
+            .into_iter()
+            .map(|id| {
+                let registry = registry.clone();
+                thread::spawn(move || registry.unregister(&id).is_some())
+            })
+            .collect();
+        assert!(removers.into_iter().all(|h| h.join().unwrap()));
+        assert!(!registry.is_active());
+    }
+
+    #[test]
+    fn logs_and_reports_outlive_the_active_operation() {
+        let registry = OperationRegistry::default();
+        let snapshot = registry.register(OperationKind::WipeFiles, "C:/data");
+        let id = snapshot.operation_id.clone();
+
+        registry.log(&id).unwrap().record("wipe_files_start", &json!({}));
+        registry.attach_report(&id, WipeReport::new(id.clone(), None));
+        registry.unregister(&id);
+
+        assert_eq!(registry.log(&id).unwrap().view().events.len(), 1);
+        assert_eq!(registry.report(&id).unwrap().operation_id, id);
+    }
+
+    #[test]
+    fn cancel_sets_the_shared_flag_only_while_active() {
+        let registry = OperationRegistry::default();
+        let external = Arc::new(AtomicBool::new(false));
+        let id = registry
+            .register_with_cancel(OperationKind::WipeFiles, "C:/data", external.clone())
+            .operation_id;
+
+        assert!(registry.cancel(&id));
+        assert!(external.load(Ordering::SeqCst));
+
+        registry.unregister(&id);
+        assert!(!registry.cancel(&id));
+        assert!(registry.cancel_flag(&id).is_none());
+    }
+
+    #[test]
+    fn cancel_all_signals_every_active_operation() {
+        let registry = OperationRegistry::default();
+        assert!(registry.cancel_all().is_empty());
+
+        let flags: Vec<Arc<AtomicBool>> = (0..3).map(|_| Arc::new(AtomicBool::new(false))).collect();
+        let ids: Vec<String> = flags
+            .iter()
+            .map(|flag| {
+                registry
+                    .register_with_cancel(OperationKind::WipeFiles, "C:/data", flag.clone())
+                    .operation_id
+            })
+            .collect();
+        let finished = registry.register(OperationKind::FreeSpaceWipe, "D:\\");
+        let finished_flag = registry.cancel_flag(&finished.operation_id).unwrap();
+        registry.unregister(&finished.operation_id);
+
+        let cancelled = registry.cancel_all();
+        let cancelled_ids: Vec<&str> = cancelled.iter().map(|s| s.operation_id.as_str()).collect();
+        assert_eq!(cancelled_ids, ids.iter().map(String::as_str).collect::<Vec<_>>());
+        assert!(flags.iter().all(|flag| flag.load(Ordering::SeqCst)));
+        assert!(!finished_flag.load(Ordering::SeqCst), "finished operations are left alone");
+        assert_eq!(registry.active().len(), 3, "operations stay registered until their workers stop");
+    }
+
+    #[test]
+    fn outcome_is_derived_from_result() {
+        let done = OperationOutcome::from_result(&WipeResult::success(messages::PATH_VALIDATED.text()));
+        assert_eq!(done.status, OutcomeStatus::Completed);
+
+        let cancelled = OperationOutcome::from_result(&WipeResult::failure(BitBurnError::Cancelled));
+        assert_eq!(cancelled.status, OutcomeStatus::Cancelled);
+        assert_eq!(cancelled.error_code.as_deref(), Some("cancelled"));
+
+        let failed = OperationOutcome::from_result(&WipeResult::failure(BitBurnError::internal("boom")));
+        assert_eq!(failed.status, OutcomeStatus::Failed);
+    }
+
+    #[test]
+    fn finished_records_are_evicted_beyond_retention() {
+        let registry = OperationRegistry::default();
+        let ids: Vec<String> = (0..FINISHED_RECORD_RETENTION + 1)
+            .map(|_| {
+                let id = registry.register(OperationKind::WipeFiles, "x").operation_id;
+                registry.unregister(&id);
+                id
+            })
+            .collect();
+
+        assert!(registry.log(&ids[0]).is_none());
+        assert!(registry.log(ids.last().unwrap()).is_some());
+    }
+
+    #[test]
+    fn summarize_targets_describes_selection() {
+        assert_eq!(summarize_targets(&[]), "No targets");
+        assert_eq!(summarize_targets(&["a".to_string()]), "a");
+        assert_eq!(
+            summarize_targets(&["a".to_string(), "b".to_string(), "c".to_string()]),
+            "a and 2 more"
+        );
+    }
+
+    #[test]
+    fn batch_percentage_spans_roots() {
+        assert_eq!(batch_percentage(0, 4, 0.0), 0.0);
+        assert_eq!(batch_percentage(1, 4, 0.5), 37.5);
+        assert_eq!(batch_percentage(3, 4, 1.0), 100.0);
+        assert_eq!(batch_percentage(0, 0, 0.0), 100.0);
+    }
+
+    #[test]
+    fn an_overflowing_operation_log_warns_once_and_reaches_the_app_totals() {
+        use crate::diagnostics::Diagnostics;
+        use crate::operation_log::OPERATION_LOG_CAPACITY;
+        use tauri::{Listener, Manager};
+
+        let app = tauri::test::mock_app();
+        app.manage(Diagnostics::default());
+        let warnings: Arc<Mutex<Vec<serde_json::Value>>> = Arc::default();
+        let seen = warnings.clone();
+        app.listen_any("diagnostics_warning", move |event| {
+            seen.lock().unwrap().push(serde_json::from_str(event.payload()).unwrap());
+        });
+        let registry = OperationRegistry::default();
+        let guard = OperationGuard::begin(app.handle(), &registry, OperationKind::WipeFiles, "x");
+        let log = registry.log(guard.id()).unwrap();
+
+        for i in 0..OPERATION_LOG_CAPACITY + 5 {
+            log.record("tick", &json!({"i": i}));
+        }
+
+        assert_eq!(guard.diagnostics().snapshot().log_entries_dropped, 5);
+        assert_eq!(app.state::<Diagnostics>().snapshot().log_entries_dropped, 5);
+        let warnings = warnings.lock().unwrap();
+        assert_eq!(warnings.len(), 1, "only the first drop warns");
+        assert_eq!(warnings[0]["operation_id"], json!(guard.id()));
+        assert_eq!(warnings[0]["log_entries_dropped"], json!(1));
+    }
+
+    #[test]
+    fn a_finished_operation_emits_and_keeps_its_summary() {
+        use tauri::Listener;
+
+        let app = tauri::test::mock_app();
+        let events: Arc<Mutex<Vec<String>>> = Arc::default();
+        for name in ["operation_summary", "operation_finished"] {
+            let seen = events.clone();
+            app.listen_any(name, move |event| {
+                let payload: serde_json::Value = serde_json::from_str(event.payload()).unwrap();
+                seen.lock().unwrap().push(format!("{}:{}", name, payload["files_ok"]));
+            });
+        }
+        let registry = OperationRegistry::default();
+        let guard = OperationGuard::begin(app.handle(), &registry, OperationKind::WipeFiles, "x");
+        let id = guard.id().to_string();
+        guard.record_algorithm(&WipeAlgorithm::NistClear, 1);
+        let mut report = WipeReport::new(id.as_str(), None);
+        report.files_wiped = 2;
+        guard.attach_report(report.clone());
+        guard.record_result(&WipeResult::success(messages::FILES_WIPED.render(json!({"count": 2}))).with_report(report));
+        assert!(registry.summary(&id).is_none(), "not summarized while running");
+        drop(guard);
+
+        assert_eq!(*events.lock().unwrap(), ["operation_summary:2", "operation_finished:null"]);
+        let summary = registry.summary(&id).unwrap();
+        assert_eq!((summary.files_ok, summary.passes, summary.status), (2, Some(1), Some(OutcomeStatus::Completed)));
+    }
+}
@@ -0,0 +1,232 @@
+//! Detailed file outcomes of very large batches. Once a batch holds `chunk_files` outcomes they
+//! are appended to a JSON-lines file in the app data directory instead of piling up in the report,
+//! which keeps its per-selection summaries and says how many outcomes the file holds. The UI pages
+//! through them with `get_operation_outcomes`. The files name every wiped path, so they are
+//! destroyed when the app exits, and any a crash left behind are destroyed at the next start.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::drive_wear;
+use crate::error::BitBurnError;
+use crate::operations::OperationRegistry;
+use crate::report::{FileWipeOutcome, RootGrouping, WipeReport};
+use crate::{log_event, BatchContext};
+use bitburn_core::{secure_wipe_file, CancellationToken, WipeAlgorithm, WipeProgress};
+
+pub const OUTCOMES_DIR: &str = "outcomes";
+const OUTCOMES_EXTENSION: &str = "jsonl";
+/// Most outcomes one `get_operation_outcomes` call returns.
+pub const MAX_PAGE: usize = 5_000;
+
+/// Where a report's outcomes went when the batch spilled them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SpilledOutcomes {
+    /// Outcomes in the file, in the order the batch recorded them.
+    pub total: usize,
+    /// SHA-256 of the file, so a signed report also vouches for the outcomes it does not list.
+    pub sha256: String,
+}
+
+/// One page of a batch's file outcomes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutcomePage {
+    pub total: usize,
+    pub offset: usize,
+    pub outcomes: Vec<Value>,
+}
+
+/// Overwrite and delete an outcome file; a plain delete if that fails, since the paths must go either way.
+fn destroy(path: &Path) {
+    if secure_wipe_file(path, 1, &WipeAlgorithm::NistClear, &CancellationToken::new(), |_: WipeProgress| {}).is_err() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Where outcome files live. Without an app data directory batches keep every outcome in memory.
+pub struct OutcomeStore {
+    dir: Option<PathBuf>,
+}
+
+impl OutcomeStore {
+    /// Store rooted at `dir`, destroying files left there by a run that did not exit cleanly.
+    pub fn new(dir: PathBuf) -> Self {
+        let store = OutcomeStore { dir: Some(dir) };
+        store.destroy_all();
+        store
+    }
+
+    pub fn unavailable() -> Self {
+        OutcomeStore { dir: None }
+    }
+
+    pub fn path_for(&self, operation_id: &str) -> Result<PathBuf, BitBurnError> {
+        let dir = self.dir.as_ref().ok_or_else(|| BitBurnError::internal("Outcome directory is unavailable"))?;
+        // Ids come back from the frontend; anything but a registry id must not name a file.
+        if operation_id.is_empty() || !operation_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(BitBurnError::OperationNotFound { operation_id: operation_id.to_string() });
+        }
+        Ok(dir.join(format!("{}.{}", operation_id, OUTCOMES_EXTENSION)))
+    }
+
+    /// Up to `limit` outcomes from line `offset` of `operation_id`'s file.
+    pub fn page(&self, operation_id: &str, offset: usize, limit: usize) -> Result<Vec<Value>, BitBurnError> {
+        let path = self.path_for(operation_id)?;
+        let file = File::open(&path).map_err(|e| BitBurnError::io(&e, Some(&path)))?;
+        BufReader::new(file)
+            .lines()
+            .skip(offset)
+            .take(limit)
+            .map(|line| {
+                let line = line.map_err(|e| BitBurnError::io(&e, Some(&path)))?;
+                serde_json::from_str(&line).map_err(|e| BitBurnError::internal(e.to_string()))
+            })
+            .collect()
+    }
+
+    fn destroy_all(&self) {
+        let Some(entries) = self.dir.as_ref().and_then(|dir| fs::read_dir(dir).ok()) else {
+            return;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_some_and(|ext| ext == OUTCOMES_EXTENSION) {
+                destroy(&path);
+            }
+        }
+    }
+}
+
+impl Drop for OutcomeStore {
+    fn drop(&mut self) {
+        self.destroy_all();
+    }
+}
+
+/// Build the managed outcome store rooted in the app data directory.
+pub fn init_outcome_store<R: Runtime>(app: &AppHandle<R>) -> OutcomeStore {
+    match app.path().app_data_dir() {
+        Ok(dir) => OutcomeStore::new(dir.join(OUTCOMES_DIR)),
+        Err(_) => {
+            log_event("outcome_dir_unavailable", json!({"action": "batches keep every outcome in memory"}));
+            OutcomeStore::unavailable()
+        }
+    }
+}
+
+/// Writes a running batch's outcomes out a chunk at a time.
+pub struct OutcomeSpill {
+    /// `None` when there is nowhere to write, or writing failed.
+    path: Option<PathBuf>,
+    writer: Option<BufWriter<File>>,
+    chunk_files: usize,
+    total: usize,
+    hasher: Sha256,
+}
+
+impl OutcomeSpill {
+    pub fn new<R: Runtime>(app: &AppHandle<R>, operation_id: &str, chunk_files: usize) -> Self {
+        OutcomeSpill {
+            path: app.try_state::<OutcomeStore>().and_then(|store| store.path_for(operation_id).ok()),
+            writer: None,
+            chunk_files: chunk_files.max(1),
+            total: 0,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// Write out the report's outcomes once it holds `chunk_files` of them. Their counts stay with
+    /// `grouping`, and the drive wear they caused is recorded now.
+    pub fn spill_if_full<R: Runtime>(
+        &mut self,
+        ctx: &BatchContext<R>,
+        report: &mut WipeReport,
+        grouping: &mut RootGrouping,
+    ) {
+        if self.path.is_none() || report.files.len() < self.chunk_files {
+            return;
+        }
+        let spilled = WipeReport { files: grouping.spill(report), ..Default::default() };
+        report.wiped_files.clear();
+        drive_wear::record_finished(&ctx.app_handle, &spilled, None);
+        self.write(&spilled.files, report);
+    }
+
+    /// Once anything was written out, write out the rest too and leave the report with its
+    /// summaries and where the outcomes are.
+    pub fn finish(mut self, report: &mut WipeReport) {
+        if self.total == 0 {
+            return;
+        }
+        if self.path.is_some() {
+            let files = std::mem::take(&mut report.files);
+            self.write(&files, report);
+        }
+        report.wiped_files.clear();
+        for root in &mut report.roots {
+            root.files.clear();
+        }
+        if let Some(mut writer) = self.writer.take() {
+            if let Err(e) = writer.flush() {
+                report.warnings.push(format!("Some file outcomes could not be saved: {}", e));
+            }
+        }
+        report.outcomes = Some(SpilledOutcomes { total: self.total, sha256: hex::encode(self.hasher.finalize()) });
+    }
+
+    fn write(&mut self, files: &[FileWipeOutcome], report: &mut WipeReport) {
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+        let written = (|| -> std::io::Result<()> {
+            if self.writer.is_none() {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                self.writer = Some(BufWriter::new(File::create(&path)?));
+            }
+            let writer = self.writer.as_mut().expect("writer was just opened");
+            for file in files {
+                let mut line = serde_json::to_vec(file).map_err(std::io::Error::other)?;
+                line.push(b'\n');
+                writer.write_all(&line)?;
+                self.hasher.update(&line);
+                self.total += 1;
+            }
+            Ok(())
+        })();
+        if let Err(e) = written {
+            log_event("outcome_spill_failed", json!({"path": path.to_string_lossy(), "message": e.to_string()}));
+            report.warnings.push(format!("Some file outcomes could not be saved: {}", e));
+            self.path = None;
+        }
+    }
+}
+
+/// Outcomes of a finished or running batch, `limit` at a time from `offset`, whether the report
+/// still holds them or the batch spilled them to disk.
+#[tauri::command]
+pub async fn get_operation_outcomes(
+    registry: State<'_, OperationRegistry>,
+    store: State<'_, OutcomeStore>,
+    operation_id: String,
+    offset: usize,
+    limit: usize,
+) -> Result<OutcomePage, BitBurnError> {
+    let report = registry
+        .report(&operation_id)
+        .ok_or_else(|| BitBurnError::OperationNotFound { operation_id: operation_id.clone() })?;
+    let limit = limit.min(MAX_PAGE);
+    let (total, outcomes) = match &report.outcomes {
+        Some(spilled) => (spilled.total, store.page(&operation_id, offset, limit)?),
+        None => {
+            let outcomes = report.files.iter().skip(offset).take(limit).map(|file| json!(file)).collect();
+            (report.files.len(), outcomes)
+        }
+    };
+    Ok(OutcomePage { total, offset, outcomes })
+}
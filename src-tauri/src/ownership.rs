@@ -0,0 +1,521 @@
+//! Taking over files an elevated wipe is still refused. An administrator can be denied a file in
+//! another user's profile by its ACL; with `take_ownership` chosen, a file whose open is refused
+//! is given to the Administrators group, which is granted access, and the open is tried once
+//! more. On Unix root becomes the owner instead. The owner it had is recorded in the file's
+//! outcome so the takeover can be audited. The takeover goes through a handle on the file itself,
+//! opened without following links, so a link put in its place after the refusal cannot turn it
+//! onto another file. If the retry is still refused the file stays, so the owner and permissions
+//! it had are put back. Without the choice an access-denied file simply fails.
+
+use bitburn_core::WipeError;
+use serde_json::json;
+use std::io;
+use std::path::Path;
+
+use crate::log_event;
+
+/// ACL calls made by a takeover, behind a trait so tests can stand in for them.
+pub(crate) trait AclLayer {
+    /// What a takeover replaced, kept until the retry shows whether it must be put back.
+    type Saved;
+    /// The file's owner: a SID string such as `S-1-5-21-…-1001` on Windows, a uid on Unix.
+    fn owner(&self, path: &Path) -> io::Result<String>;
+    /// Make the administrators (Windows) or root (Unix) the owner and give them write access,
+    /// through a handle that refuses a link in the file's place. Returns what was replaced.
+    fn take_ownership(&self, path: &Path) -> io::Result<Self::Saved>;
+    /// Put back the owner and permissions a takeover replaced, through the same handle.
+    fn restore(&self, saved: Self::Saved) -> io::Result<()>;
+}
+
+pub(crate) struct SystemAcl;
+
+#[cfg(windows)]
+impl AclLayer for SystemAcl {
+    type Saved = SavedSecurity;
+
+    fn owner(&self, path: &Path) -> io::Result<String> {
+        use windows_sys::Win32::Foundation::{LocalFree, ERROR_SUCCESS};
+        use windows_sys::Win32::Security::Authorization::{ConvertSidToStringSidW, GetNamedSecurityInfoW, SE_FILE_OBJECT};
+        use windows_sys::Win32::Security::OWNER_SECURITY_INFORMATION;
+
+        let wide = wide_path(path);
+        let mut owner = std::ptr::null_mut();
+        let mut descriptor = std::ptr::null_mut();
+        let status = unsafe {
+            GetNamedSecurityInfoW(
+                wide.as_ptr(),
+                SE_FILE_OBJECT,
+                OWNER_SECURITY_INFORMATION,
+                &mut owner,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &mut descriptor,
+            )
+        };
+        if status != ERROR_SUCCESS {
+            return Err(io::Error::from_raw_os_error(status as i32));
+        }
+        let mut text: *mut u16 = std::ptr::null_mut();
+        let converted = unsafe { ConvertSidToStringSidW(owner, &mut text) } != 0;
+        let result = if converted {
+            let len = (0..).take_while(|&i| unsafe { *text.add(i) } != 0).count();
+            let sid = String::from_utf16_lossy(unsafe { std::slice::from_raw_parts(text, len) });
+            unsafe { LocalFree(text.cast()) };
+            Ok(sid)
+        } else {
+            Err(io::Error::last_os_error())
+        };
+        unsafe { LocalFree(descriptor) };
+        result
+    }
+
+    fn take_ownership(&self, path: &Path) -> io::Result<SavedSecurity> {
+        use std::os::windows::fs::OpenOptionsExt;
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::Foundation::{LocalFree, ERROR_SUCCESS};
+        use windows_sys::Win32::Security::Authorization::{
+            GetSecurityInfo, SetEntriesInAclW, SetSecurityInfo, EXPLICIT_ACCESS_W, NO_MULTIPLE_TRUSTEE, SET_ACCESS,
+            SE_FILE_OBJECT, TRUSTEE_IS_GROUP, TRUSTEE_IS_SID, TRUSTEE_W,
+        };
+        use windows_sys::Win32::Security::{
+            CreateWellKnownSid, WinBuiltinAdministratorsSid, DACL_SECURITY_INFORMATION, OWNER_SECURITY_INFORMATION,
+            PROTECTED_DACL_SECURITY_INFORMATION,
+        };
+        use windows_sys::Win32::Storage::FileSystem::{
+            FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT, FILE_READ_ATTRIBUTES, FILE_SHARE_DELETE,
+            FILE_SHARE_READ, FILE_SHARE_WRITE, READ_CONTROL, WRITE_DAC, WRITE_OWNER,
+        };
+
+        /// `FILE_GENERIC_READ | FILE_GENERIC_WRITE | DELETE`: overwriting reads back, renames and deletes.
+        const WIPE_ACCESS: u32 = 0x0013_019F;
+        /// `SECURITY_MAX_SID_SIZE`.
+        const MAX_SID_SIZE: usize = 68;
+
+        // Taking ownership opens the handle; backup and restore let it read the security the file
+        // had whatever its DACL says, and later give that owner back.
+        for privilege in ["SeTakeOwnershipPrivilege", "SeBackupPrivilege", "SeRestorePrivilege"] {
+            crate::platform::elevation::enable_privilege(privilege)?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .access_mode(READ_CONTROL | WRITE_DAC | WRITE_OWNER | FILE_READ_ATTRIBUTES)
+            .share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE)
+            .custom_flags(FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT)
+            .open(path)?;
+        if file.metadata()?.file_type().is_symlink() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "the file was replaced by a link"));
+        }
+        let mut descriptor = std::ptr::null_mut();
+        let status = unsafe {
+            GetSecurityInfo(
+                file.as_raw_handle(),
+                SE_FILE_OBJECT,
+                OWNER_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &mut descriptor,
+            )
+        };
+        if status != ERROR_SUCCESS {
+            return Err(io::Error::from_raw_os_error(status as i32));
+        }
+        let saved = SavedSecurity { file, descriptor };
+
+        let mut admins = [0u8; MAX_SID_SIZE];
+        let mut size = MAX_SID_SIZE as u32;
+        let sid: *mut std::ffi::c_void = admins.as_mut_ptr().cast();
+        if unsafe { CreateWellKnownSid(WinBuiltinAdministratorsSid, std::ptr::null_mut(), sid, &mut size) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let status = unsafe {
+            SetSecurityInfo(
+                saved.file.as_raw_handle(),
+                SE_FILE_OBJECT,
+                OWNER_SECURITY_INFORMATION,
+                sid,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                std::ptr::null(),
+            )
+        };
+        if status != ERROR_SUCCESS {
+            return Err(io::Error::from_raw_os_error(status as i32));
+        }
+
+        // A DACL holding only the administrators' grant, protected from inheritance, so no deny
+        // entry on the file or its folders still applies.
+        let access = EXPLICIT_ACCESS_W {
+            grfAccessPermissions: WIPE_ACCESS,
+            grfAccessMode: SET_ACCESS,
+            grfInheritance: 0,
+            Trustee: TRUSTEE_W {
+                pMultipleTrustee: std::ptr::null_mut(),
+                MultipleTrusteeOperation: NO_MULTIPLE_TRUSTEE,
+                TrusteeForm: TRUSTEE_IS_SID,
+                TrusteeType: TRUSTEE_IS_GROUP,
+                ptstrName: sid.cast(),
+            },
+        };
+        let mut acl = std::ptr::null_mut();
+        let mut status = unsafe { SetEntriesInAclW(1, &access, std::ptr::null(), &mut acl) };
+        if status == ERROR_SUCCESS {
+            status = unsafe {
+                SetSecurityInfo(
+                    saved.file.as_raw_handle(),
+                    SE_FILE_OBJECT,
+                    DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    acl,
+                    std::ptr::null(),
+                )
+            };
+            unsafe { LocalFree(acl.cast()) };
+        }
+        if status != ERROR_SUCCESS {
+            // The owner was already changed; give it back before reporting the failure.
+            let _ = self.restore(saved);
+            return Err(io::Error::from_raw_os_error(status as i32));
+        }
+        Ok(saved)
+    }
+
+    fn restore(&self, saved: SavedSecurity) -> io::Result<()> {
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::Foundation::ERROR_SUCCESS;
+        use windows_sys::Win32::Security::Authorization::{SetSecurityInfo, SE_FILE_OBJECT};
+        use windows_sys::Win32::Security::{
+            GetSecurityDescriptorControl, GetSecurityDescriptorDacl, GetSecurityDescriptorOwner, ACL,
+            DACL_SECURITY_INFORMATION, OWNER_SECURITY_INFORMATION, PROTECTED_DACL_SECURITY_INFORMATION, SE_DACL_PROTECTED,
+            UNPROTECTED_DACL_SECURITY_INFORMATION,
+        };
+
+        let (mut owner, mut owner_defaulted) = (std::ptr::null_mut(), 0);
+        let (mut dacl, mut present, mut dacl_defaulted): (*mut ACL, _, _) = (std::ptr::null_mut(), 0, 0);
+        let (mut control, mut revision) = (0, 0);
+        let read = unsafe {
+            GetSecurityDescriptorOwner(saved.descriptor, &mut owner, &mut owner_defaulted) != 0
+                && GetSecurityDescriptorDacl(saved.descriptor, &mut present, &mut dacl, &mut dacl_defaulted) != 0
+                && GetSecurityDescriptorControl(saved.descriptor, &mut control, &mut revision) != 0
+        };
+        if !read {
+            return Err(io::Error::last_os_error());
+        }
+        // Whether the old DACL inherited from the folder is part of what is put back.
+        let inheritance = if control & SE_DACL_PROTECTED != 0 {
+            PROTECTED_DACL_SECURITY_INFORMATION
+        } else {
+            UNPROTECTED_DACL_SECURITY_INFORMATION
+        };
+        let status = unsafe {
+            SetSecurityInfo(
+                saved.file.as_raw_handle(),
+                SE_FILE_OBJECT,
+                OWNER_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION | inheritance,
+                owner,
+                std::ptr::null_mut(),
+                dacl,
+                std::ptr::null(),
+            )
+        };
+        if status != ERROR_SUCCESS {
+            return Err(io::Error::from_raw_os_error(status as i32));
+        }
+        Ok(())
+    }
+}
+
+/// The owner and DACL a takeover replaced, and the handle it replaced them through.
+#[cfg(windows)]
+pub(crate) struct SavedSecurity {
+    file: std::fs::File,
+    descriptor: windows_sys::Win32::Security::PSECURITY_DESCRIPTOR,
+}
+
+#[cfg(windows)]
+impl Drop for SavedSecurity {
+    fn drop(&mut self) {
+        unsafe { windows_sys::Win32::Foundation::LocalFree(self.descriptor) };
+    }
+}
+
+#[cfg(windows)]
+fn wide_path(path: &Path) -> Vec<u16> {
+    use std::os::windows::ffi::OsStrExt;
+    path.as_os_str().encode_wide().chain(std::iter::once(0)).collect()
+}
+
+#[cfg(unix)]
+impl AclLayer for SystemAcl {
+    type Saved = SavedSecurity;
+
+    fn owner(&self, path: &Path) -> io::Result<String> {
+        use std::os::unix::fs::MetadataExt;
+        Ok(std::fs::symlink_metadata(path)?.uid().to_string())
+    }
+
+    fn take_ownership(&self, path: &Path) -> io::Result<SavedSecurity> {
+        use std::os::unix::fs::{MetadataExt, OpenOptionsExt, PermissionsExt};
+
+        // `O_NOFOLLOW` refuses a link in the file's place; `O_NONBLOCK` keeps a FIFO from hanging the open.
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NOFOLLOW | libc::O_NONBLOCK)
+            .open(path)?;
+        let metadata = file.metadata()?;
+        let saved = SavedSecurity { file, uid: metadata.uid(), gid: metadata.gid(), mode: metadata.mode() & 0o7777 };
+        let taken = std::os::unix::fs::fchown(&saved.file, Some(0), Some(0))
+            .and_then(|()| saved.file.set_permissions(std::fs::Permissions::from_mode(saved.mode | 0o600)));
+        if let Err(e) = taken {
+            let _ = self.restore(saved);
+            return Err(e);
+        }
+        Ok(saved)
+    }
+
+    fn restore(&self, saved: SavedSecurity) -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        std::os::unix::fs::fchown(&saved.file, Some(saved.uid), Some(saved.gid))?;
+        // After the chown, which clears set-id bits.
+        saved.file.set_permissions(std::fs::Permissions::from_mode(saved.mode))
+    }
+}
+
+/// The owner, group and mode a takeover replaced, and the descriptor it replaced them through.
+#[cfg(unix)]
+pub(crate) struct SavedSecurity {
+    file: std::fs::File,
+    uid: u32,
+    gid: u32,
+    mode: u32,
+}
+
+#[cfg(not(any(windows, unix)))]
+impl AclLayer for SystemAcl {
+    type Saved = ();
+
+    fn owner(&self, _path: &Path) -> io::Result<String> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    fn take_ownership(&self, _path: &Path) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    fn restore(&self, _saved: ()) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+/// A takeover that let the retry in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Takeover {
+    /// Who owned the file before; `None` when even that could not be read.
+    pub original_owner: Option<String>,
+}
+
+fn is_access_denied(error: &WipeError) -> bool {
+    matches!(error, WipeError::Io(e) if e.kind() == io::ErrorKind::PermissionDenied)
+}
+
+/// Run `attempt`; if access to `path` is denied and `take_ownership` was chosen, take the file
+/// over through `acl` and run it once more. A retry that still fails puts the file's security
+/// back. The takeover is returned whenever the file is left with the new owner: after a
+/// successful retry, or when putting it back failed. A failed takeover leaves the original error.
+pub(crate) fn with_takeover<T, A: AclLayer>(
+    acl: &A,
+    path: &Path,
+    take_ownership: bool,
+    mut attempt: impl FnMut() -> Result<T, WipeError>,
+) -> (Result<T, WipeError>, Option<Takeover>) {
+    let result = attempt();
+    match &result {
+        Err(error) if take_ownership && is_access_denied(error) => {}
+        _ => return (result, None),
+    }
+    let original_owner = acl.owner(path).ok();
+    let saved = match acl.take_ownership(path) {
+        Ok(saved) => saved,
+        Err(e) => {
+            log_event("ownership_takeover_failed", json!({"path": path.to_string_lossy(), "error": e.to_string()}));
+            return (result, None);
+        }
+    };
+    log_event("ownership_taken", json!({"path": path.to_string_lossy(), "original_owner": original_owner}));
+    let retried = attempt();
+    if retried.is_ok() {
+        return (retried, Some(Takeover { original_owner }));
+    }
+    match acl.restore(saved) {
+        Ok(()) => {
+            log_event("ownership_restored", json!({"path": path.to_string_lossy(), "original_owner": original_owner}));
+            (retried, None)
+        }
+        Err(e) => {
+            log_event("ownership_restore_failed", json!({"path": path.to_string_lossy(), "error": e.to_string()}));
+            (retried, Some(Takeover { original_owner }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+    use std::cell::{Cell, RefCell};
+
+    /// Refuses until `take_ownership` has been called, unless `refuse_takeover` is set, and
+    /// always when `refuse_anyway` is.
+    #[derive(Default)]
+    struct MockAcl {
+        calls: RefCell<Vec<&'static str>>,
+        taken: Cell<bool>,
+        refuse_takeover: bool,
+        refuse_anyway: bool,
+        refuse_restore: bool,
+        unreadable_owner: bool,
+    }
+
+    impl AclLayer for MockAcl {
+        type Saved = ();
+
+        fn owner(&self, _path: &Path) -> io::Result<String> {
+            self.calls.borrow_mut().push("owner");
+            if self.unreadable_owner {
+                return Err(io::ErrorKind::PermissionDenied.into());
+            }
+            Ok("S-1-5-21-1004336348-1177238915-682003330-1001".to_string())
+        }
+
+        fn take_ownership(&self, _path: &Path) -> io::Result<()> {
+            self.calls.borrow_mut().push("take_ownership");
+            if self.refuse_takeover {
+                return Err(io::ErrorKind::PermissionDenied.into());
+            }
+            self.taken.set(true);
+            Ok(())
+        }
+
+        fn restore(&self, _saved: ()) -> io::Result<()> {
+            self.calls.borrow_mut().push("restore");
+            if self.refuse_restore {
+                return Err(io::ErrorKind::PermissionDenied.into());
+            }
+            self.taken.set(false);
+            Ok(())
+        }
+    }
+
+    impl MockAcl {
+        fn open(&self) -> Result<&'static str, WipeError> {
+            if self.taken.get() && !self.refuse_anyway {
+                Ok("opened")
+            } else {
+                Err(WipeError::Io(io::ErrorKind::PermissionDenied.into()))
+            }
+        }
+    }
+
+    const PATH: &str = "C:/Users/alice/Documents/payroll.xlsx";
+
+    #[test]
+    fn a_denied_file_is_taken_over_and_retried_only_when_chosen() {
+        let acl = MockAcl::default();
+        let (result, takeover) = with_takeover(&acl, Path::new(PATH), false, || acl.open());
+        assert!(matches!(result, Err(WipeError::Io(ref e)) if e.kind() == io::ErrorKind::PermissionDenied));
+        assert_eq!(takeover, None);
+        assert!(acl.calls.borrow().is_empty(), "never escalates without the choice");
+
+        let (result, takeover) = with_takeover(&acl, Path::new(PATH), true, || acl.open());
+        assert_eq!(result.unwrap(), "opened");
+        let owner = takeover.unwrap().original_owner.unwrap();
+        assert!(owner.starts_with("S-1-5-21-") && owner.ends_with("-1001"));
+        assert_eq!(*acl.calls.borrow(), ["owner", "take_ownership"]);
+    }
+
+    #[test]
+    fn other_errors_and_failed_takeovers_leave_the_original_error() {
+        let acl = MockAcl::default();
+        let (result, takeover) = with_takeover(&acl, Path::new(PATH), true, || -> Result<(), WipeError> {
+            Err(WipeError::Io(io::ErrorKind::NotFound.into()))
+        });
+        assert!(result.is_err() && takeover.is_none());
+        let (result, _) = with_takeover(&acl, Path::new(PATH), true, || Ok::<_, WipeError>(()));
+        assert!(result.is_ok());
+        assert!(acl.calls.borrow().is_empty());
+
+        let refusing = MockAcl { refuse_takeover: true, ..Default::default() };
+        let attempts = Cell::new(0);
+        let (result, takeover) = with_takeover(&refusing, Path::new(PATH), true, || {
+            attempts.set(attempts.get() + 1);
+            refusing.open()
+        });
+        assert!(result.is_err() && takeover.is_none());
+        assert_eq!(attempts.get(), 1, "no retry without ownership");
+
+        // Ownership is still taken, and recorded, when the old owner cannot be read.
+        let unreadable = MockAcl { unreadable_owner: true, ..Default::default() };
+        let (result, takeover) = with_takeover(&unreadable, Path::new(PATH), true, || unreadable.open());
+        assert!(result.is_ok());
+        assert_eq!(takeover, Some(Takeover { original_owner: None }));
+    }
+
+    #[test]
+    fn a_retry_still_refused_puts_the_file_back() {
+        let acl = MockAcl { refuse_anyway: true, ..Default::default() };
+        let (result, takeover) = with_takeover(&acl, Path::new(PATH), true, || acl.open());
+        assert!(result.is_err());
+        assert_eq!(takeover, None, "the file keeps its owner");
+        assert_eq!(*acl.calls.borrow(), ["owner", "take_ownership", "restore"]);
+        assert!(!acl.taken.get());
+
+        // A file that could not be put back is still reported as taken over.
+        let stuck = MockAcl { refuse_anyway: true, refuse_restore: true, ..Default::default() };
+        let (result, takeover) = with_takeover(&stuck, Path::new(PATH), true, || stuck.open());
+        assert!(result.is_err());
+        assert!(takeover.unwrap().original_owner.is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_link_in_the_files_place_is_not_taken_over() {
+        let dir = create_test_dir().unwrap();
+        let target = dir.join("target.txt");
+        std::fs::write(&target, b"other").unwrap();
+        let link = dir.join("denied.txt");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let error = SystemAcl.take_ownership(&link).err().unwrap();
+        assert_eq!(error.raw_os_error(), Some(libc::ELOOP));
+        cleanup_test_dir(&dir);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn an_administrator_denied_by_an_ace_takes_the_file_over() {
+        use std::fs;
+
+        if !crate::platform::elevation::is_elevated() {
+            return;
+        }
+        let dir = create_test_dir().unwrap();
+        let file = dir.join("denied.txt");
+        fs::write(&file, b"secret").unwrap();
+        let denied = std::process::Command::new("icacls.exe")
+            .arg(&file)
+            .args(["/deny", "*S-1-5-32-544:(W,D)"])
+            .status()
+            .unwrap();
+        assert!(denied.success());
+        let open = || fs::OpenOptions::new().write(true).open(&file).map_err(WipeError::Io);
+        assert!(open().is_err(), "the deny entry applies to administrators");
+
+        let (result, takeover) = with_takeover(&SystemAcl, &file, true, open);
+        assert!(result.is_ok());
+        assert!(takeover.unwrap().original_owner.is_some_and(|sid| sid.starts_with("S-1-")));
+        drop(result);
+        cleanup_test_dir(&dir);
+    }
+}
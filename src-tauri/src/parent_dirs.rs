@@ -0,0 +1,230 @@
+//! Directories left behind by a batch: their entries are flushed so deletions are durable before
+//! success is reported, and, when the user asks for it, their timestamps are put back so the
+//! modification time does not record when the wipe happened.
+
+use serde_json::json;
+use std::fs::{self, File, FileTimes};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::log_event;
+use bitburn_core::{DeletedParents, DirectorySync};
+
+/// Timestamps of a directory taken before anything inside it was deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DirectoryTimes {
+    pub accessed: SystemTime,
+    pub modified: SystemTime,
+}
+
+/// Directory calls made by `ParentDirectories`, behind a trait so tests can observe them. Its
+/// `sync_dir` makes a directory's entries durable: `FlushFileBuffers` on Windows, `fsync` on Unix.
+pub(crate) trait DirectoryFs: DirectorySync {
+    fn times(&self, dir: &Path) -> io::Result<DirectoryTimes>;
+    fn set_times(&self, dir: &Path, times: DirectoryTimes) -> io::Result<()>;
+}
+
+pub(crate) struct RealDirectoryFs;
+
+impl DirectoryFs for RealDirectoryFs {
+    fn times(&self, dir: &Path) -> io::Result<DirectoryTimes> {
+        let metadata = fs::metadata(dir)?;
+        Ok(DirectoryTimes {
+            accessed: metadata.accessed()?,
+            modified: metadata.modified()?,
+        })
+    }
+
+    fn set_times(&self, dir: &Path, times: DirectoryTimes) -> io::Result<()> {
+        open_directory(dir)?.set_times(FileTimes::new().set_accessed(times.accessed).set_modified(times.modified))
+    }
+}
+
+impl DirectorySync for RealDirectoryFs {
+    fn sync_dir(&self, dir: &Path) -> io::Result<()> {
+        open_directory(dir)?.sync_all()
+    }
+}
+
+/// Directories can only be opened with backup semantics on Windows, and need write access for
+/// `SetFileTime` and `FlushFileBuffers`.
+#[cfg(windows)]
+fn open_directory(dir: &Path) -> io::Result<File> {
+    use std::os::windows::fs::OpenOptionsExt;
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(dir)
+}
+
+#[cfg(not(windows))]
+fn open_directory(dir: &Path) -> io::Result<File> {
+    File::open(dir)
+}
+
+/// Parents of everything a batch deletes, synced through `bitburn_core::DeletedParents`, with the
+/// timestamps each had when it was first touched.
+pub(crate) struct ParentDirectories<F: DirectoryFs = RealDirectoryFs> {
+    parents: DeletedParents<F>,
+    preserve_times: bool,
+    times: Vec<(PathBuf, DirectoryTimes)>,
+}
+
+impl ParentDirectories {
+    pub fn new(preserve_times: bool) -> Self {
+        ParentDirectories::with_fs(RealDirectoryFs, preserve_times)
+    }
+}
+
+impl<F: DirectoryFs> ParentDirectories<F> {
+    pub fn with_fs(fs: F, preserve_times: bool) -> Self {
+        ParentDirectories {
+            parents: DeletedParents::with_sync(fs),
+            preserve_times,
+            times: Vec::new(),
+        }
+    }
+
+    /// Call before `path` is deleted; the first call for a parent records its timestamps.
+    pub fn before_delete(&mut self, path: &Path) {
+        if self.parents.deleted(path) {
+            if let Some(parent) = path.parent() {
+                self.record_times(parent);
+            }
+        }
+    }
+
+    /// Call before everything inside `dir` is removed while `dir` itself stays.
+    pub fn before_emptying(&mut self, dir: &Path) {
+        if self.parents.emptied(dir) {
+            self.record_times(dir);
+        }
+    }
+
+    fn record_times(&mut self, parent: &Path) {
+        if !self.preserve_times {
+            return;
+        }
+        match self.parents.sync().times(parent) {
+            Ok(times) => self.times.push((parent.to_path_buf(), times)),
+            Err(e) => {
+                log_event("parent_times_unreadable", json!({"path": parent.to_string_lossy(), "message": e.to_string()}));
+            }
+        }
+    }
+
+    /// Restore recorded timestamps, then sync every parent that still exists, deepest first.
+    /// Returns warnings for whatever failed.
+    pub fn finish(self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for (parent, times) in &self.times {
+            // Removed along with a wiped folder.
+            if !parent.is_dir() {
+                continue;
+            }
+            if let Err(e) = self.parents.sync().set_times(parent, *times) {
+                warnings.push(format!("Could not restore the timestamps of {}: {}", parent.display(), e));
+            }
+        }
+        warnings.extend(self.parents.finish());
+        for warning in &warnings {
+            log_event("parent_directory_warning", json!({"message": warning}));
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::time::{Duration, UNIX_EPOCH};
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    #[derive(Default)]
+    struct RecordingFs {
+        calls: RefCell<Vec<String>>,
+        fail_set_times: bool,
+    }
+
+    impl DirectoryFs for &RecordingFs {
+        fn times(&self, dir: &Path) -> io::Result<DirectoryTimes> {
+            self.calls.borrow_mut().push(format!("times {}", dir.display()));
+            Ok(DirectoryTimes { accessed: UNIX_EPOCH, modified: UNIX_EPOCH })
+        }
+
+        fn set_times(&self, dir: &Path, _times: DirectoryTimes) -> io::Result<()> {
+            self.calls.borrow_mut().push(format!("set_times {}", dir.display()));
+            if self.fail_set_times {
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, "read-only"));
+            }
+            Ok(())
+        }
+
+    }
+
+    impl DirectorySync for &RecordingFs {
+        fn sync_dir(&self, dir: &Path) -> io::Result<()> {
+            self.calls.borrow_mut().push(format!("flush {}", dir.display()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn parent_mtime_is_the_same_after_deleting_inside_it() {
+        let dir = create_test_dir().unwrap();
+        let victim = dir.join("secret.txt");
+        fs::write(&victim, b"data").unwrap();
+        let old = SystemTime::now() - Duration::from_secs(90 * 24 * 60 * 60);
+        RealDirectoryFs.set_times(&dir, DirectoryTimes { accessed: old, modified: old }).unwrap();
+        let before = fs::metadata(&dir).unwrap().modified().unwrap();
+
+        let mut parents = ParentDirectories::new(true);
+        parents.before_delete(&victim);
+        fs::remove_file(&victim).unwrap();
+        assert_ne!(fs::metadata(&dir).unwrap().modified().unwrap(), before, "the deletion moved the mtime");
+        assert!(parents.finish().is_empty());
+
+        assert_eq!(fs::metadata(&dir).unwrap().modified().unwrap(), before);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn every_surviving_parent_is_flushed_and_times_only_touched_when_asked() {
+        let dir = create_test_dir().unwrap();
+        let nested = dir.join("nested");
+        fs::create_dir_all(&nested).unwrap();
+        let gone = dir.join("gone");
+
+        let recording = RecordingFs::default();
+        let mut parents = ParentDirectories::with_fs(&recording, false);
+        parents.before_delete(&dir.join("a.txt"));
+        parents.before_delete(&nested.join("b.txt"));
+        parents.before_delete(&dir.join("c.txt"));
+        parents.before_delete(&gone.join("d.txt"));
+        assert!(parents.finish().is_empty());
+        assert_eq!(
+            recording.calls.take(),
+            vec![format!("flush {}", nested.display()), format!("flush {}", dir.display())]
+        );
+
+        let failing = RecordingFs { fail_set_times: true, ..Default::default() };
+        let mut parents = ParentDirectories::with_fs(&failing, true);
+        parents.before_delete(&dir.join("a.txt"));
+        let warnings = parents.finish();
+        assert_eq!(warnings.len(), 1, "a failed restore is a warning: {:?}", warnings);
+        assert_eq!(
+            failing.calls.take(),
+            vec![
+                format!("times {}", dir.display()),
+                format!("set_times {}", dir.display()),
+                format!("flush {}", dir.display()),
+            ],
+            "the flush still happens"
+        );
+        cleanup_test_dir(dir);
+    }
+}
@@ -0,0 +1,263 @@
+//! Expansion of the paths scripts hand to BitBurn on the command line and in manifests: `%VAR%`
+//! on Windows, `$VAR`, `${VAR}` and `~` on Unix, taken from the process environment, with `/`
+//! turned into `\` on Windows and relative paths resolved against a base. A literal path that
+//! already exists is taken as it is, so a file really named `50%off%.txt` is not mangled. Paths
+//! from drag and drop and the file dialogs are always real paths and never come through here.
+
+use std::path::Path;
+
+use crate::error::BitBurnError;
+
+/// Whose rules a path is expanded by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    Windows,
+    Unix,
+}
+
+impl Syntax {
+    pub const NATIVE: Syntax = if cfg!(windows) { Syntax::Windows } else { Syntax::Unix };
+
+    fn separator(self) -> char {
+        match self {
+            Syntax::Windows => '\\',
+            Syntax::Unix => '/',
+        }
+    }
+
+    /// `C:\x`, `C:x`, `\x` and `\\server\x` on Windows; `/x` on Unix.
+    fn is_absolute(self, path: &str) -> bool {
+        match self {
+            Syntax::Windows => path.starts_with('\\') || path.chars().nth(1) == Some(':'),
+            Syntax::Unix => path.starts_with('/'),
+        }
+    }
+}
+
+/// What a relative path is resolved against.
+#[derive(Debug, Clone, Copy)]
+pub enum RelativeBase<'a> {
+    /// The working directory of the command line that named the path, or the folder holding the
+    /// manifest that listed it.
+    Dir(&'a Path),
+    /// Context-menu payloads: file managers always send absolute paths, so a relative one is refused.
+    Reject,
+}
+
+/// Expand `raw` by the rules of this platform from the process environment.
+pub fn expand(raw: &str, base: RelativeBase) -> Result<String, BitBurnError> {
+    let literal = resolve(raw.trim(), base, Syntax::NATIVE)?;
+    if Path::new(&literal).exists() {
+        return Ok(literal);
+    }
+    expand_with(raw, base, Syntax::NATIVE, |name| std::env::var(name).ok())
+}
+
+/// Expand the variables in `raw` through `var`, normalize its separators and resolve it against
+/// `base`. A variable `var` does not know is an error naming it.
+pub fn expand_with(
+    raw: &str,
+    base: RelativeBase,
+    syntax: Syntax,
+    var: impl Fn(&str) -> Option<String>,
+) -> Result<String, BitBurnError> {
+    let raw = raw.trim();
+    let lookup = |name: &str| {
+        var(name).ok_or_else(|| BitBurnError::UndefinedVariable { path: raw.to_string(), variable: name.to_string() })
+    };
+    let expanded = match syntax {
+        Syntax::Windows => expand_percent(raw, lookup)?,
+        Syntax::Unix => expand_dollar(&expand_tilde(raw, lookup)?, lookup)?,
+    };
+    resolve(&expanded, base, syntax)
+}
+
+/// Refuse a relative path, for inputs that have no base to resolve it against.
+pub fn require_absolute(raw: &str) -> Result<(), BitBurnError> {
+    resolve(raw.trim(), RelativeBase::Reject, Syntax::NATIVE).map(|_| ())
+}
+
+/// `%NAME%` on Windows; `%%` is a literal `%`, as is a `%` with no closing one.
+fn expand_percent(raw: &str, lookup: impl Fn(&str) -> Result<String, BitBurnError>) -> Result<String, BitBurnError> {
+    let mut expanded = String::new();
+    let mut rest = raw;
+    while let Some(start) = rest.find('%') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('%') {
+            Some(0) => {
+                expanded.push('%');
+                rest = &after[1..];
+            }
+            Some(end) if !after[..end].contains(['\\', '/']) => {
+                expanded.push_str(&lookup(&after[..end])?);
+                rest = &after[end + 1..];
+            }
+            _ => {
+                expanded.push('%');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// A leading `~` or `~/` is `$HOME`; `~user` is left as it is.
+fn expand_tilde(raw: &str, lookup: impl Fn(&str) -> Result<String, BitBurnError>) -> Result<String, BitBurnError> {
+    match raw.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => Ok(format!("{}{}", lookup("HOME")?, rest)),
+        _ => Ok(raw.to_string()),
+    }
+}
+
+/// `$NAME` and `${NAME}` on Unix; a `$` not followed by a name is a literal `$`.
+fn expand_dollar(raw: &str, lookup: impl Fn(&str) -> Result<String, BitBurnError>) -> Result<String, BitBurnError> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut expanded = String::new();
+    let mut rest = raw;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let braced = after.strip_prefix('{').and_then(|inner| inner.find('}').map(|end| (&inner[..end], &inner[end + 1..])));
+        let bare_len = after.find(|c: char| !is_name_char(c)).unwrap_or(after.len());
+        match braced {
+            Some((name, tail)) if !name.is_empty() && name.chars().all(is_name_char) => {
+                expanded.push_str(&lookup(name)?);
+                rest = tail;
+            }
+            _ if bare_len > 0 && !after.starts_with(|c: char| c.is_ascii_digit()) => {
+                expanded.push_str(&lookup(&after[..bare_len])?);
+                rest = &after[bare_len..];
+            }
+            _ => {
+                expanded.push('$');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Normalize the separators of `path`, dropping empty and `.` components, and put `base` in
+/// front of it when it is relative.
+fn resolve(path: &str, base: RelativeBase, syntax: Syntax) -> Result<String, BitBurnError> {
+    let separator = syntax.separator();
+    let path = match syntax {
+        Syntax::Windows => path.replace('/', "\\"),
+        Syntax::Unix => path.to_string(),
+    };
+    let joined = if path.is_empty() || syntax.is_absolute(&path) {
+        path
+    } else {
+        match base {
+            RelativeBase::Dir(dir) => {
+                let dir = dir.to_string_lossy();
+                format!("{}{}{}", dir.trim_end_matches(separator), separator, path)
+            }
+            RelativeBase::Reject => return Err(BitBurnError::RelativePathNotAllowed { path }),
+        }
+    };
+
+    // Keep the leading separators: one for a rooted path, two for a UNC share.
+    let body = joined.trim_start_matches(separator);
+    let mut normalized = joined[..joined.len() - body.len()].to_string();
+    let components: Vec<&str> = body.split(separator).filter(|c| !c.is_empty() && *c != ".").collect();
+    normalized.push_str(&components.join(&separator.to_string()));
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    fn env(name: &str) -> Option<String> {
+        match name {
+            "TEMP" => Some("C:\\Users\\me\\AppData\\Local\\Temp".to_string()),
+            "USERPROFILE" => Some("C:\\Users\\me".to_string()),
+            "HOME" => Some("/home/me".to_string()),
+            "SCRATCH" => Some("/mnt/scratch".to_string()),
+            _ => None,
+        }
+    }
+
+    fn windows(raw: &str, base: RelativeBase) -> Result<String, BitBurnError> {
+        expand_with(raw, base, Syntax::Windows, env)
+    }
+
+    fn unix(raw: &str, base: RelativeBase) -> Result<String, BitBurnError> {
+        expand_with(raw, base, Syntax::Unix, env)
+    }
+
+    #[test]
+    fn windows_paths_expand_percent_variables_and_backslash_their_separators() {
+        let cwd = RelativeBase::Dir(Path::new("D:\\jobs\\"));
+        assert_eq!(windows("%TEMP%\\export.csv", cwd).unwrap(), "C:\\Users\\me\\AppData\\Local\\Temp\\export.csv");
+        assert_eq!(windows("%USERPROFILE%/Documents//old.docx", cwd).unwrap(), "C:\\Users\\me\\Documents\\old.docx");
+        assert_eq!(windows("C:\\100%% done\\a.txt", cwd).unwrap(), "C:\\100% done\\a.txt");
+        assert_eq!(windows("C:\\50%\\b.txt", cwd).unwrap(), "C:\\50%\\b.txt");
+        assert_eq!(windows(".\\out/dump.bin", cwd).unwrap(), "D:\\jobs\\out\\dump.bin");
+        assert_eq!(windows("\\\\server\\share\\c.txt", cwd).unwrap(), "\\\\server\\share\\c.txt");
+        // Unix syntax means nothing here.
+        assert_eq!(windows("~/$HOME", cwd).unwrap(), "D:\\jobs\\~\\$HOME");
+    }
+
+    #[test]
+    fn unix_paths_expand_dollar_variables_and_the_home_directory() {
+        let cwd = RelativeBase::Dir(Path::new("/work"));
+        assert_eq!(unix("~/scratch/dump.bin", cwd).unwrap(), "/home/me/scratch/dump.bin");
+        assert_eq!(unix("~", cwd).unwrap(), "/home/me");
+        assert_eq!(unix("$SCRATCH/a.bin", cwd).unwrap(), "/mnt/scratch/a.bin");
+        assert_eq!(unix("${SCRATCH}_old/b.bin", cwd).unwrap(), "/mnt/scratch_old/b.bin");
+        assert_eq!(unix("/tmp/price$/$5.txt", cwd).unwrap(), "/tmp/price$/$5.txt");
+        assert_eq!(unix("./logs//today.log", cwd).unwrap(), "/work/logs/today.log");
+        assert_eq!(unix("~bob/notes", cwd).unwrap(), "/work/~bob/notes");
+        // Windows syntax means nothing here, and a backslash is part of a file name.
+        assert_eq!(unix("/tmp/%TEMP%\\x", cwd).unwrap(), "/tmp/%TEMP%\\x");
+    }
+
+    #[test]
+    fn an_undefined_variable_is_an_error_naming_it() {
+        let cwd = RelativeBase::Dir(Path::new("/work"));
+        assert_eq!(
+            windows("%NOPE%\\a.txt", cwd),
+            Err(BitBurnError::UndefinedVariable { path: "%NOPE%\\a.txt".into(), variable: "NOPE".into() })
+        );
+        assert_eq!(
+            unix("${NOPE}/a.txt", cwd),
+            Err(BitBurnError::UndefinedVariable { path: "${NOPE}/a.txt".into(), variable: "NOPE".into() })
+        );
+        assert_eq!(
+            unix("$NOPE/a.txt", cwd),
+            Err(BitBurnError::UndefinedVariable { path: "$NOPE/a.txt".into(), variable: "NOPE".into() })
+        );
+        let homeless = expand_with("~/a", cwd, Syntax::Unix, |_| None);
+        assert_eq!(homeless, Err(BitBurnError::UndefinedVariable { path: "~/a".into(), variable: "HOME".into() }));
+    }
+
+    #[test]
+    fn relative_paths_are_refused_without_a_base() {
+        assert_eq!(
+            windows("reports\\q1.xlsx", RelativeBase::Reject),
+            Err(BitBurnError::RelativePathNotAllowed { path: "reports\\q1.xlsx".into() })
+        );
+        assert_eq!(windows("C:\\reports\\q1.xlsx", RelativeBase::Reject).unwrap(), "C:\\reports\\q1.xlsx");
+        assert_eq!(
+            unix("reports/q1.xlsx", RelativeBase::Reject),
+            Err(BitBurnError::RelativePathNotAllowed { path: "reports/q1.xlsx".into() })
+        );
+        assert_eq!(unix("$SCRATCH/q1.xlsx", RelativeBase::Reject).unwrap(), "/mnt/scratch/q1.xlsx");
+    }
+
+    #[test]
+    fn an_existing_literal_path_is_not_expanded() {
+        let dir = create_test_dir().unwrap();
+        let odd = dir.join("50%UNDEFINED_BITBURN_VAR%$UNDEFINED_BITBURN_VAR.txt");
+        std::fs::write(&odd, b"x").unwrap();
+        let literal = odd.to_string_lossy().to_string();
+        assert_eq!(expand(&literal, RelativeBase::Reject).unwrap(), literal);
+        cleanup_test_dir(&dir);
+    }
+}
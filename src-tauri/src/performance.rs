@@ -0,0 +1,342 @@
+//! Timing statistics over recent operations, to notice when a release makes wiping slower.
+//! Reports carry each file's duration and write rate and each batch's phase times. As each
+//! operation finishes those timings, without its paths, are appended to a history in the app data
+//! directory, so the medians and 95th percentiles (by the nearest-rank method) span restarts.
+//! A record keeps at most `SAMPLES_PER_OPERATION` of each timing, spread over its range, and the
+//! history is written on a thread of its own, so a large batch slows neither the operation's end
+//! nor the writes after it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::report::{FileWipeStatus, OperationTimings, WipeReport};
+use crate::settings::write_file_atomic;
+
+const HISTORY_FILE_NAME: &str = "performance_history.json";
+
+/// Operations the history keeps; the oldest is dropped first.
+pub const HISTORY_CAPACITY: usize = 256;
+
+/// Samples of each per-file timing a record keeps; larger batches keep an even spread of theirs.
+pub const SAMPLES_PER_OPERATION: usize = 1024;
+
+/// Operations summarised when the caller does not say how many.
+pub const DEFAULT_OPERATIONS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Distribution {
+    pub samples: usize,
+    pub median: u64,
+    pub p95: u64,
+}
+
+impl Distribution {
+    /// `None` without samples.
+    pub fn of(mut values: Vec<u64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable();
+        Some(Distribution {
+            samples: values.len(),
+            median: nearest_rank(&values, 50),
+            p95: nearest_rank(&values, 95),
+        })
+    }
+}
+
+/// The smallest value at least `percent` of `sorted` is no greater than.
+fn nearest_rank(sorted: &[u64], percent: usize) -> u64 {
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PerformanceSummary {
+    pub operations: usize,
+    /// Files wiped in full; failed and cancelled ones would skew the numbers.
+    pub files: usize,
+    pub file_duration_ms: Option<Distribution>,
+    pub file_bytes_per_second: Option<Distribution>,
+    pub pass_ms: Option<Distribution>,
+    pub scan_ms: Option<Distribution>,
+    pub wipe_ms: Option<Distribution>,
+    pub cleanup_ms: Option<Distribution>,
+}
+
+/// The timings of one finished operation, as the history keeps them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OperationTimingRecord {
+    pub operation_id: String,
+    /// Seconds since the Unix epoch.
+    pub finished_at: u64,
+    /// Files wiped in full; failed and cancelled ones would skew the numbers.
+    pub files: usize,
+    /// Up to `SAMPLES_PER_OPERATION` values each, from those files.
+    pub file_duration_ms: Vec<u64>,
+    pub file_bytes_per_second: Vec<u64>,
+    pub pass_ms: Vec<u64>,
+    pub timings: Option<OperationTimings>,
+}
+
+impl OperationTimingRecord {
+    /// Operations whose outcomes were spilled to disk contribute their phase times only.
+    pub fn of(report: &WipeReport, finished_at: u64) -> Self {
+        let files: Vec<_> = report
+            .files
+            .iter()
+            .flat_map(|file| file.with_companions())
+            .filter(|file| file.status == FileWipeStatus::Success)
+            .collect();
+        OperationTimingRecord {
+            operation_id: report.operation_id.clone(),
+            finished_at,
+            files: files.len(),
+            file_duration_ms: capped(files.iter().map(|file| file.duration_ms).collect()),
+            file_bytes_per_second: capped(files.iter().filter_map(|file| file.bytes_per_second).collect()),
+            pass_ms: capped(files.iter().flat_map(|file| &file.passes.pass_timings).map(|pass| pass.millis).collect()),
+            timings: report.timings,
+        }
+    }
+}
+
+/// `values`, or `SAMPLES_PER_OPERATION` of them evenly spaced once sorted, smallest and largest included.
+fn capped(mut values: Vec<u64>) -> Vec<u64> {
+    if values.len() <= SAMPLES_PER_OPERATION {
+        return values;
+    }
+    values.sort_unstable();
+    let last = values.len() - 1;
+    (0..SAMPLES_PER_OPERATION).map(|i| values[i * last / (SAMPLES_PER_OPERATION - 1)]).collect()
+}
+
+/// Aggregate the timings in `records`.
+pub fn summarize(records: &[OperationTimingRecord]) -> PerformanceSummary {
+    let timings: Vec<_> = records.iter().filter_map(|record| record.timings).collect();
+    let all = |values: fn(&OperationTimingRecord) -> &Vec<u64>| records.iter().flat_map(values).copied().collect::<Vec<_>>();
+    PerformanceSummary {
+        operations: records.len(),
+        files: records.iter().map(|record| record.files).sum(),
+        file_duration_ms: Distribution::of(all(|record| &record.file_duration_ms)),
+        file_bytes_per_second: Distribution::of(all(|record| &record.file_bytes_per_second)),
+        pass_ms: Distribution::of(all(|record| &record.pass_ms)),
+        scan_ms: Distribution::of(timings.iter().map(|timing| timing.scan_ms).collect()),
+        wipe_ms: Distribution::of(timings.iter().map(|timing| timing.wipe_ms).collect()),
+        cleanup_ms: Distribution::of(timings.iter().map(|timing| timing.cleanup_ms).collect()),
+    }
+}
+
+/// The timing history shared through managed state, written back after every operation. Clones
+/// share it.
+#[derive(Clone)]
+pub struct TimingHistory {
+    path: Option<PathBuf>,
+    records: Arc<Mutex<VecDeque<OperationTimingRecord>>>,
+    /// Held from taking a copy of the history to writing it, so writes land in the order taken.
+    saving: Arc<Mutex<()>>,
+}
+
+impl TimingHistory {
+    /// Load the history from `path`, starting empty when the file is missing or unreadable.
+    pub fn load(path: PathBuf) -> Self {
+        let records = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                log_event("performance_history_load_error", json!({"path": path.to_string_lossy(), "message": e.to_string()}));
+                VecDeque::new()
+            }),
+            Err(_) => VecDeque::new(),
+        };
+        TimingHistory { path: Some(path), records: Arc::new(Mutex::new(records)), saving: Arc::default() }
+    }
+
+    /// History that never touches disk; used when the data directory cannot be resolved.
+    pub fn in_memory() -> Self {
+        TimingHistory { path: None, records: Arc::default(), saving: Arc::default() }
+    }
+
+    /// Append `record`, dropping the oldest beyond `HISTORY_CAPACITY`, and persist the history on
+    /// another thread; called as an operation's guard is dropped.
+    pub fn record(&self, record: OperationTimingRecord) {
+        {
+            let mut records = self.lock();
+            records.push_back(record);
+            while records.len() > HISTORY_CAPACITY {
+                records.pop_front();
+            }
+        }
+        if self.path.is_some() {
+            let history = self.clone();
+            std::thread::spawn(move || history.save());
+        }
+    }
+
+    /// Write the history as it is now.
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let _saving = self.saving.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let contents = serde_json::to_vec(&*self.lock());
+        let written = contents
+            .map_err(|e| BitBurnError::internal(e.to_string()))
+            .and_then(|contents| write_file_atomic(path, &contents));
+        if let Err(e) = written {
+            log_event("performance_history_save_error", json!({"message": e.to_string()}));
+        }
+    }
+
+    /// Up to `last_n` records, newest first.
+    pub fn recent(&self, last_n: usize) -> Vec<OperationTimingRecord> {
+        self.lock().iter().rev().take(last_n).cloned().collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<OperationTimingRecord>> {
+        self.records.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Build the managed timing history rooted in the app data directory.
+pub fn init_timing_history<R: Runtime>(app: &AppHandle<R>) -> TimingHistory {
+    match app.path().app_data_dir() {
+        Ok(dir) => TimingHistory::load(dir.join(HISTORY_FILE_NAME)),
+        Err(_) => {
+            log_event("performance_history_path_unavailable", json!({"action": "timings are not remembered"}));
+            TimingHistory::in_memory()
+        }
+    }
+}
+
+/// Add the timings of the operation that produced `report` to the history, if the app keeps one.
+pub fn record_finished<R: Runtime>(app: &AppHandle<R>, report: &WipeReport) {
+    if let Some(history) = app.try_state::<TimingHistory>() {
+        let finished_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        history.record(OperationTimingRecord::of(report, finished_at));
+    }
+}
+
+/// Medians and 95th percentiles over the last `last_n_operations` operations in the history
+/// (`DEFAULT_OPERATIONS` when not given).
+#[tauri::command]
+pub async fn get_performance_summary(
+    history: State<'_, TimingHistory>,
+    last_n_operations: Option<usize>,
+) -> Result<PerformanceSummary, BitBurnError> {
+    Ok(summarize(&history.recent(last_n_operations.unwrap_or(DEFAULT_OPERATIONS))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::FileWipeOutcome;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+    use bitburn_core::{FileWipeProgressRecord, PassTiming};
+
+    fn file(status: FileWipeStatus, duration_ms: u64, pass_millis: &[u64]) -> FileWipeOutcome {
+        let passes = FileWipeProgressRecord {
+            pass_timings: pass_millis
+                .iter()
+                .enumerate()
+                .map(|(index, &millis)| PassTiming { pass: index as u32 + 1, millis, bytes: 1000 })
+                .collect(),
+            ..FileWipeProgressRecord::new(pass_millis.len() as u32)
+        };
+        FileWipeOutcome {
+            path: "C:\\data\\file".to_string(),
+            status,
+            algorithm: None,
+            flash_media: false,
+            recalled_from_remote: false,
+            cow_filesystem: None,
+            nocow_applied: None,
+            ownership_taken: false,
+            original_owner: None,
+            link: None,
+            exclusive_access: true,
+            duration_ms,
+            bytes_per_second: passes.bytes_per_second(),
+            passes,
+            companions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn medians_and_p95_use_the_nearest_rank() {
+        assert_eq!(Distribution::of(Vec::new()), None);
+        assert_eq!(Distribution::of(vec![7]), Some(Distribution { samples: 1, median: 7, p95: 7 }));
+        let hundred = Distribution::of((1..=100).rev().collect()).unwrap();
+        assert_eq!((hundred.median, hundred.p95), (50, 95));
+        // Ranks round up: the 3rd of 5 is the median, the 5th the 95th percentile.
+        assert_eq!(Distribution::of(vec![40, 10, 30, 20, 1000]), Some(Distribution { samples: 5, median: 30, p95: 1000 }));
+    }
+
+    #[test]
+    fn only_fully_wiped_files_and_recorded_phases_count() {
+        let mut first = WipeReport::new("op-1", None);
+        first.files = vec![file(FileWipeStatus::Success, 100, &[30, 30, 40]), file(FileWipeStatus::Error, 9000, &[9000])];
+        first.files[0].companions.push(file(FileWipeStatus::Success, 300, &[300]));
+        first.timings = Some(OperationTimings { scan_ms: 5, wipe_ms: 400, cleanup_ms: 2 });
+        let mut second = WipeReport::new("op-2", None);
+        second.files = vec![file(FileWipeStatus::Success, 200, &[200])];
+        // A free-space wipe: no files and no phase times.
+        let third = WipeReport::new("op-3", None);
+
+        let records: Vec<_> = [first, second, third].iter().map(|report| OperationTimingRecord::of(report, 0)).collect();
+        let summary = summarize(&records);
+        assert_eq!((summary.operations, summary.files), (3, 3));
+        assert_eq!(summary.file_duration_ms, Some(Distribution { samples: 3, median: 200, p95: 300 }));
+        assert_eq!(summary.pass_ms.map(|pass| (pass.samples, pass.median)), Some((5, 40)));
+        // 3000 bytes in 100 ms, 1000 in 300 ms and 1000 in 200 ms.
+        assert_eq!(summary.file_bytes_per_second.map(|rate| (rate.median, rate.p95)), Some((5000, 30000)));
+        assert_eq!(summary.wipe_ms, Some(Distribution { samples: 1, median: 400, p95: 400 }));
+        assert_eq!(summarize(&[]), PerformanceSummary::default());
+    }
+
+    #[test]
+    fn the_history_survives_a_restart_and_keeps_the_newest_operations() {
+        let dir = create_test_dir().unwrap();
+        let path = dir.join(HISTORY_FILE_NAME);
+
+        let history = TimingHistory::load(path.clone());
+        for n in 0..HISTORY_CAPACITY + 2 {
+            let mut report = WipeReport::new(format!("op-{}", n), None);
+            report.files = vec![file(FileWipeStatus::Success, n as u64, &[n as u64])];
+            history.record(OperationTimingRecord::of(&report, n as u64));
+        }
+        // Wait for the writes started in the background; this one is of the final history.
+        history.save();
+
+        let reloaded = TimingHistory::load(path);
+        let recent = reloaded.recent(3);
+        let ids: Vec<_> = recent.iter().map(|record| record.operation_id.as_str()).collect();
+        assert_eq!(ids, ["op-257", "op-256", "op-255"]);
+        assert_eq!(reloaded.recent(usize::MAX).len(), HISTORY_CAPACITY);
+        assert_eq!(summarize(&recent).file_duration_ms, Some(Distribution { samples: 3, median: 256, p95: 257 }));
+        cleanup_test_dir(&dir);
+    }
+
+    #[test]
+    fn a_large_batch_keeps_an_even_spread_of_its_timings() {
+        let mut report = WipeReport::new("op-large", None);
+        report.files = (1..=10_000).map(|n| file(FileWipeStatus::Success, n, &[n])).collect();
+        let record = OperationTimingRecord::of(&report, 0);
+        assert_eq!(record.files, 10_000);
+        assert_eq!(record.file_duration_ms.len(), SAMPLES_PER_OPERATION);
+        assert_eq!(record.pass_ms.len(), SAMPLES_PER_OPERATION);
+        assert_eq!((record.file_duration_ms[0], record.file_duration_ms[SAMPLES_PER_OPERATION - 1]), (1, 10_000));
+
+        let summary = summarize(&[record]);
+        assert_eq!(summary.files, 10_000);
+        let duration = summary.file_duration_ms.unwrap();
+        assert_eq!(duration.samples, SAMPLES_PER_OPERATION);
+        assert!(duration.median.abs_diff(5_000) <= 10 && duration.p95.abs_diff(9_500) <= 10, "{:?}", duration);
+    }
+}
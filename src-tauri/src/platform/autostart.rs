@@ -2,6 +2,8 @@ use serde_json::json;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+use crate::error::BitBurnError;
+
 /// CLI flag injected into the Run key so autostart launches stay hidden.
 pub const AUTOSTART_FLAG: &str = "--bitburn-autostart";
 
@@ -45,7 +47,7 @@ fn write_autostart(exe_path: &Path) -> Result<(), AutostartError> {
 }
 
 #[cfg(windows)]
-fn remove_autostart() -> Result<(), AutostartError> {
+pub(crate) fn remove_autostart() -> Result<(), AutostartError> {
     let hkcu = RegKey::predef(HKEY_CURRENT_USER);
     if let Ok(key) = hkcu.open_subkey_with_flags(RUN_KEY, KEY_WRITE) {
         let _ = key.delete_value("BitBurn");
@@ -94,61 +96,55 @@ fn resolve_executable_path() -> Result<PathBuf, AutostartError> {
 
 /// Enable BitBurn autostart on Windows by writing a Run key entry.
 #[tauri::command]
-pub async fn register_autostart() -> Result<crate::WipeResult, String> {
+pub async fn register_autostart() -> Result<crate::WipeResult, BitBurnError> {
     #[cfg(windows)]
     {
-        let exe_path = resolve_executable_path().map_err(|e| e.to_string())?;
-        write_autostart(&exe_path).map_err(|e| e.to_string())?;
+        let exe_path = resolve_executable_path()?;
+        write_autostart(&exe_path)?;
         crate::log_event("autostart_register", json!({"status": "success"}));
 
-        return Ok(crate::WipeResult {
-            success: true,
-            message: "Autostart enabled".to_string(),
-        });
+        return Ok(crate::WipeResult::success(crate::messages::AUTOSTART_ENABLED.text()));
     }
 
     #[cfg(not(windows))]
     {
-        Ok(crate::WipeResult {
-            success: false,
-            message: "Autostart not supported on this platform".to_string(),
-        })
+        Ok(crate::WipeResult::failure_with_message(
+            AutostartError::UnsupportedPlatform.into(),
+            crate::messages::AUTOSTART_UNSUPPORTED.text(),
+        ))
     }
 }
 
 /// Disable BitBurn autostart by removing the Run key entry.
 #[tauri::command]
-pub async fn unregister_autostart() -> Result<crate::WipeResult, String> {
+pub async fn unregister_autostart() -> Result<crate::WipeResult, BitBurnError> {
     #[cfg(windows)]
     {
-        remove_autostart().map_err(|e| e.to_string())?;
+        remove_autostart()?;
         crate::log_event("autostart_unregister", json!({"status": "success"}));
 
-        return Ok(crate::WipeResult {
-            success: true,
-            message: "Autostart disabled".to_string(),
-        });
+        return Ok(crate::WipeResult::success(crate::messages::AUTOSTART_DISABLED.text()));
     }
 
     #[cfg(not(windows))]
     {
-        Ok(crate::WipeResult {
-            success: false,
-            message: "Autostart not supported on this platform".to_string(),
-        })
+        Ok(crate::WipeResult::failure_with_message(
+            AutostartError::UnsupportedPlatform.into(),
+            crate::messages::AUTOSTART_UNSUPPORTED.text(),
+        ))
     }
 }
 
 /// Report whether autostart is currently enabled.
 #[tauri::command]
-pub async fn get_autostart_status() -> Result<crate::AutostartStatus, String> {
+pub async fn get_autostart_status() -> Result<crate::AutostartStatus, BitBurnError> {
     #[cfg(windows)]
     {
-        let enabled = is_autostart_enabled().map_err(|e| e.to_string())?;
+        let enabled = is_autostart_enabled()?;
         let message = if enabled {
-            "Autostart is enabled".to_string()
+            crate::messages::AUTOSTART_IS_ENABLED.text()
         } else {
-            "Autostart is disabled".to_string()
+            crate::messages::AUTOSTART_IS_DISABLED.text()
         };
 
         return Ok(crate::AutostartStatus { enabled, message });
@@ -158,7 +154,7 @@ pub async fn get_autostart_status() -> Result<crate::AutostartStatus, String> {
     {
         Ok(crate::AutostartStatus {
             enabled: false,
-            message: "Autostart not supported on this platform".to_string(),
+            message: crate::messages::AUTOSTART_UNSUPPORTED.text(),
         })
     }
 }
@@ -170,6 +166,6 @@ mod tests {
     fn autostart_is_unavailable_on_non_windows() {
         let status = tauri::async_runtime::block_on(super::get_autostart_status()).expect("command should return result");
         assert!(!status.enabled);
-        assert!(status.message.contains("not supported") || status.message.contains("disabled"));
+        assert_eq!(status.message.code, "autostart_unsupported");
     }
 }
@@ -1,21 +1,50 @@
+use bitburn_core::SymlinkPolicy;
 use serde_json::json;
 use std::collections::HashSet;
+#[cfg(any(windows, test))]
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::async_runtime::{spawn_blocking, JoinHandle};
 use tauri::{AppHandle, Emitter, Manager};
 use thiserror::Error;
 
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::path_expansion::RelativeBase;
+use crate::platform::elevation::LaunchContext;
+
+/// Sanitization running longer than this reports `context_sanitize_progress`, and again each
+/// time this much more has passed.
+const SANITIZE_PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
 /// Payload delivered to the frontend when a context-menu wipe is invoked.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ContextWipePayload {
     pub paths: Vec<String>,
     pub invalid: Vec<String>,
     pub source: String,
+    /// Entries of `paths` that are volume roots or mount points; wiping one needs the typed confirmation.
+    pub volume_roots: Vec<String>,
+    /// Set when the instance that received the request runs with different privileges from the
+    /// one the file manager launched.
+    pub elevation_mismatch: Option<ElevationMismatch>,
+    /// Hands the selection back to `run_context_wipe`; set when the payload is sent to the frontend.
+    pub token: Option<String>,
+    /// `--yes` was on the command line: confirms the wipe when no dialog can be shown to ask.
+    pub assume_yes: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct ElevationMismatch {
+    pub sender: LaunchContext,
+    pub receiver: LaunchContext,
 }
 
 #[derive(Debug, Error)]
 pub enum ContextMenuError {
-    #[cfg(not(windows))]
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
     #[error("context menu not supported on this platform")]
     UnsupportedPlatform,
     #[error("missing executable path")]
@@ -23,6 +52,12 @@ pub enum ContextMenuError {
     #[cfg(windows)]
     #[error("registry error: {0}")]
     Registry(String),
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    #[error("file manager integration error: {0}")]
+    Service(#[from] std::io::Error),
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    #[error("home directory not found")]
+    MissingHomeDir,
 }
 
 #[cfg(windows)]
@@ -37,43 +72,226 @@ fn context_menu_keys() -> (String, String) {
     (file_key, folder_key)
 }
 
+/// The folder verb that wipes the free space of the folder's drive.
 #[cfg(windows)]
-fn write_context_menu_for_target(root_key: &str, exe_path: &Path) -> Result<(), ContextMenuError> {
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+fn free_space_key() -> String {
+    let base = std::env::var("BITBURN_CONTEXT_ROOT").unwrap_or_else(|_| "Software\\Classes".to_string());
+    format!("{}\\Directory\\shell\\BitBurnFreeSpace", base)
+}
+
+/// One registry write made while registering the Explorer menu. Registration is planned as a
+/// list of these so a failure part-way can be unwound and reported by step.
+#[cfg(any(windows, test))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RegistryWrite {
+    CreateKey { key: String },
+    SetValue { key: String, name: &'static str, value: String },
+}
 
-    // Clear existing tree first to ensure a clean state
-    let _ = hkcu.delete_subkey_all(root_key);
-
-    let (bitburn_key, _) = hkcu
-        .create_subkey(root_key)
-        .map_err(|e| ContextMenuError::Registry(e.to_string()))?;
-    bitburn_key
-        .set_value("MUIVerb", &"BitBurn")
-        .map_err(|e| ContextMenuError::Registry(e.to_string()))?;
-    bitburn_key
-        .set_value("Icon", &exe_path.display().to_string())
-        .map_err(|e| ContextMenuError::Registry(e.to_string()))?;
-
-    // Directly bind the root entry to the BitBurn context-wipe command so clicking the
-    // top-level menu item launches the app instead of a submenu.
-    let command_path = format!("{}\\command", root_key);
-    let (command_key, _) = hkcu
-        .create_subkey(&command_path)
-        .map_err(|e| ContextMenuError::Registry(e.to_string()))?;
-    let command_value = format!("\"{}\" --context-wipe \"%V\"", exe_path.display());
-    command_key
-        .set_value("", &command_value)
-        .map_err(|e| ContextMenuError::Registry(e.to_string()))?;
+#[cfg(any(windows, test))]
+impl fmt::Display for RegistryWrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryWrite::CreateKey { key } => write!(f, "creating {}", key),
+            RegistryWrite::SetValue { key, name: "", .. } => write!(f, "setting the default value of {}", key),
+            RegistryWrite::SetValue { key, name, .. } => write!(f, "setting {} on {}", name, key),
+        }
+    }
+}
+
+/// Every write that registers the menu under each of `root_keys`, in order.
+#[cfg(any(windows, test))]
+fn context_menu_writes(root_keys: &[&str], exe_path: &Path) -> Vec<RegistryWrite> {
+    // Bind the root entry directly to the context-wipe command so clicking the top-level menu
+    // item launches the app instead of opening a submenu.
+    let command_value = format!(
+        "\"{}\" {} --context-wipe \"%V\"",
+        exe_path.display(),
+        LaunchContext::file_manager().to_arg()
+    );
+    root_keys
+        .iter()
+        .flat_map(|root| {
+            let command_key = format!("{}\\command", root);
+            [
+                RegistryWrite::CreateKey { key: root.to_string() },
+                RegistryWrite::SetValue { key: root.to_string(), name: "MUIVerb", value: "BitBurn".to_string() },
+                RegistryWrite::SetValue { key: root.to_string(), name: "Icon", value: exe_path.display().to_string() },
+                RegistryWrite::CreateKey { key: command_key.clone() },
+                RegistryWrite::SetValue { key: command_key, name: "", value: command_value.clone() },
+            ]
+        })
+        .collect()
+}
+
+/// What is wrong with a registered verb, as far as can be told from its registry values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub enum VerbProblem {
+    /// The verb's key is missing.
+    Missing,
+    /// The key is there but holds no command.
+    NoCommand,
+    /// The command runs a program that is not there.
+    ExecutableMissing,
+    /// The command does not pass the selection with `%V`.
+    NoPlaceholder,
+}
+
+/// One registered menu verb as read back from the registry.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct VerbStatus {
+    /// `files`, `folders` or `free_space`.
+    pub verb: String,
+    pub key: String,
+    /// The verb's command line exactly as registered.
+    pub command: Option<String>,
+    pub icon: Option<String>,
+    /// The program the command runs.
+    pub executable: Option<String>,
+    pub executable_exists: bool,
+    pub has_placeholder: bool,
+    /// The first problem found; `None` when the verb should work.
+    pub problem: Option<VerbProblem>,
+}
+
+/// The program a registered command line starts: its quoted first part, or up to the first space.
+#[cfg(any(windows, test))]
+fn command_executable(command: &str) -> Option<&str> {
+    let command = command.trim_start();
+    match command.strip_prefix('"') {
+        Some(rest) => rest.split('"').next(),
+        None => command.split_whitespace().next(),
+    }
+    .filter(|executable| !executable.is_empty())
+}
+
+/// Read each of `verbs`, a name and its root key, through `value(key, name)`, checking the
+/// programs they run with `exists`.
+#[cfg(any(windows, test))]
+fn verb_statuses(
+    verbs: &[(&str, &str)],
+    value: impl Fn(&str, &str) -> Option<String>,
+    key_exists: impl Fn(&str) -> bool,
+    exists: impl Fn(&Path) -> bool,
+) -> Vec<VerbStatus> {
+    verbs
+        .iter()
+        .map(|(verb, key)| {
+            let command = value(&format!("{}\\command", key), "");
+            let executable = command.as_deref().and_then(command_executable).map(str::to_string);
+            let executable_exists = executable.as_deref().is_some_and(|executable| exists(Path::new(executable)));
+            let has_placeholder = command.as_deref().is_some_and(|command| command.contains("%V"));
+            let problem = if !key_exists(key) {
+                Some(VerbProblem::Missing)
+            } else if command.as_deref().is_none_or(|command| command.trim().is_empty()) {
+                Some(VerbProblem::NoCommand)
+            } else if !executable_exists {
+                Some(VerbProblem::ExecutableMissing)
+            } else if !has_placeholder {
+                Some(VerbProblem::NoPlaceholder)
+            } else {
+                None
+            };
+            VerbStatus {
+                verb: verb.to_string(),
+                key: key.to_string(),
+                command,
+                icon: value(key, "Icon"),
+                executable,
+                executable_exists,
+                has_placeholder,
+                problem,
+            }
+        })
+        .collect()
+}
+
+/// The writes that register the folder verb under `key` that wipes the free space of the
+/// selected folder's drive.
+#[cfg(any(windows, test))]
+fn free_space_verb_writes(key: &str, exe_path: &Path) -> Vec<RegistryWrite> {
+    let command_key = format!("{}\\command", key);
+    let command_value = format!(
+        "\"{}\" {} {} \"%V\"",
+        exe_path.display(),
+        LaunchContext::file_manager().to_arg(),
+        crate::folder_free_space::FREE_SPACE_FOR_FLAG
+    );
+    vec![
+        RegistryWrite::CreateKey { key: key.to_string() },
+        RegistryWrite::SetValue { key: key.to_string(), name: "MUIVerb", value: "Wipe free space on this drive".to_string() },
+        RegistryWrite::SetValue { key: key.to_string(), name: "Icon", value: exe_path.display().to_string() },
+        RegistryWrite::CreateKey { key: command_key.clone() },
+        RegistryWrite::SetValue { key: command_key, name: "", value: command_value },
+    ]
+}
 
+/// The registration step that failed and the error it failed with.
+#[cfg(any(windows, test))]
+#[derive(Debug)]
+struct FailedWrite {
+    step: String,
+    message: String,
+}
+
+#[cfg(any(windows, test))]
+impl fmt::Display for FailedWrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed: {}", self.step, self.message)
+    }
+}
+
+/// Make `writes` in order. The first failure runs `roll_back` before returning, so a half-written
+/// registration never stays behind to confuse `is_context_menu_enabled` or the next attempt.
+#[cfg(any(windows, test))]
+fn write_all_or_roll_back(
+    writes: &[RegistryWrite],
+    mut write: impl FnMut(&RegistryWrite) -> std::io::Result<()>,
+    roll_back: impl FnOnce(),
+) -> Result<(), FailedWrite> {
+    for step in writes {
+        if let Err(e) = write(step) {
+            roll_back();
+            return Err(FailedWrite { step: step.to_string(), message: e.to_string() });
+        }
+    }
     Ok(())
 }
 
+#[cfg(windows)]
+fn apply_registry_write(hkcu: &RegKey, write: &RegistryWrite) -> std::io::Result<()> {
+    match write {
+        RegistryWrite::CreateKey { key } => hkcu.create_subkey(key).map(|_| ()),
+        RegistryWrite::SetValue { key, name, value } => hkcu.create_subkey(key)?.0.set_value(*name, value),
+    }
+}
+
 #[cfg(windows)]
 pub fn enable_context_menu(exe_path: &Path) -> Result<(), ContextMenuError> {
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    enable_context_menu_with(exe_path, |write| apply_registry_write(&hkcu, write))
+}
+
+/// `enable_context_menu` with every registry write going through `write`, so tests can fail one.
+#[cfg(windows)]
+pub(crate) fn enable_context_menu_with(
+    exe_path: &Path,
+    write: impl FnMut(&RegistryWrite) -> std::io::Result<()>,
+) -> Result<(), ContextMenuError> {
     let (file_key, folder_key) = context_menu_keys();
-    write_context_menu_for_target(&file_key, exe_path)?;
-    write_context_menu_for_target(&folder_key, exe_path)?;
-    Ok(())
+    // Start from a clean state so nothing from an earlier registration survives.
+    let _ = disable_context_menu();
+    let mut writes = context_menu_writes(&[&file_key, &folder_key], exe_path);
+    writes.extend(free_space_verb_writes(&free_space_key(), exe_path));
+    write_all_or_roll_back(&writes, write, || {
+        let _ = disable_context_menu();
+    })
+    .map_err(|failed| {
+        log_event("context_menu_register_failed", json!({"step": failed.step, "message": failed.message}));
+        ContextMenuError::Registry(failed.to_string())
+    })
 }
 
 #[cfg(windows)]
@@ -82,6 +300,22 @@ pub fn disable_context_menu() -> Result<(), ContextMenuError> {
     let (file_key, folder_key) = context_menu_keys();
     let _ = hkcu.delete_subkey_all(file_key);
     let _ = hkcu.delete_subkey_all(folder_key);
+    let _ = hkcu.delete_subkey_all(free_space_key());
+    Ok(())
+}
+
+/// Remove the menu from the machine-wide classes, where an elevated installer may have put it.
+/// Needs administrator rights.
+#[cfg(windows)]
+pub fn disable_machine_context_menu() -> Result<(), ContextMenuError> {
+    let hklm = RegKey::predef(winreg::enums::HKEY_LOCAL_MACHINE);
+    let (file_key, folder_key) = context_menu_keys();
+    for key in [file_key, folder_key, free_space_key()] {
+        match hklm.delete_subkey_all(&key) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(ContextMenuError::Registry(e.to_string())),
+            _ => {}
+        }
+    }
     Ok(())
 }
 
@@ -95,17 +329,95 @@ pub fn is_context_menu_enabled() -> Result<bool, ContextMenuError> {
             .is_ok())
 }
 
-#[cfg(not(windows))]
+/// The registered verbs read back from the registry, for support to see what Explorer runs.
+#[cfg(windows)]
+pub fn registered_verbs() -> Vec<VerbStatus> {
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let (file_key, folder_key) = context_menu_keys();
+    let free_space_key = free_space_key();
+    verb_statuses(
+        &[("files", &file_key), ("folders", &folder_key), ("free_space", &free_space_key)],
+        |key, name| hkcu.open_subkey_with_flags(key, KEY_READ).ok()?.get_value::<String, _>(name).ok(),
+        |key| hkcu.open_subkey_with_flags(key, KEY_READ).is_ok(),
+        Path::is_file,
+    )
+}
+
+#[cfg(target_os = "macos")]
+use crate::platform::finder_services::{self, WorkflowStatus};
+
+#[cfg(target_os = "macos")]
+fn services_dir() -> Result<PathBuf, ContextMenuError> {
+    finder_services::services_dir().ok_or(ContextMenuError::MissingHomeDir)
+}
+
+#[cfg(target_os = "macos")]
+pub fn enable_context_menu(exe_path: &Path) -> Result<(), ContextMenuError> {
+    finder_services::install_workflow(&services_dir()?, exe_path)?;
+    finder_services::refresh_services_cache();
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+pub fn disable_context_menu() -> Result<(), ContextMenuError> {
+    finder_services::remove_workflow(&services_dir()?)?;
+    finder_services::refresh_services_cache();
+    Ok(())
+}
+
+/// Installed and pointing at the running executable.
+#[cfg(target_os = "macos")]
+pub fn is_context_menu_enabled() -> Result<bool, ContextMenuError> {
+    Ok(context_menu_workflow_status()? == WorkflowStatus::Current)
+}
+
+#[cfg(target_os = "macos")]
+fn context_menu_workflow_status() -> Result<WorkflowStatus, ContextMenuError> {
+    let exe_path = resolve_executable_path()?;
+    Ok(finder_services::workflow_status(&services_dir()?, &exe_path))
+}
+
+#[cfg(target_os = "linux")]
+use crate::platform::linux_file_managers::{self, IntegrationStatus};
+
+#[cfg(target_os = "linux")]
+fn data_home() -> Result<PathBuf, ContextMenuError> {
+    linux_file_managers::data_home().ok_or(ContextMenuError::MissingHomeDir)
+}
+
+#[cfg(target_os = "linux")]
+pub fn enable_context_menu(exe_path: &Path) -> Result<(), ContextMenuError> {
+    Ok(linux_file_managers::install(&data_home()?, exe_path)?)
+}
+
+#[cfg(target_os = "linux")]
+pub fn disable_context_menu() -> Result<(), ContextMenuError> {
+    Ok(linux_file_managers::uninstall(&data_home()?)?)
+}
+
+/// Nautilus and Dolphin entries are both installed and point at the running executable.
+#[cfg(target_os = "linux")]
+pub fn is_context_menu_enabled() -> Result<bool, ContextMenuError> {
+    Ok(context_menu_integration_status()? == IntegrationStatus::Current)
+}
+
+#[cfg(target_os = "linux")]
+fn context_menu_integration_status() -> Result<IntegrationStatus, ContextMenuError> {
+    let exe_path = resolve_executable_path()?;
+    Ok(linux_file_managers::status(&data_home()?, &exe_path))
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
 pub fn enable_context_menu(_: &Path) -> Result<(), ContextMenuError> {
     Err(ContextMenuError::UnsupportedPlatform)
 }
 
-#[cfg(not(windows))]
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
 pub fn disable_context_menu() -> Result<(), ContextMenuError> {
     Err(ContextMenuError::UnsupportedPlatform)
 }
 
-#[cfg(not(windows))]
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
 pub fn is_context_menu_enabled() -> Result<bool, ContextMenuError> {
     Err(ContextMenuError::UnsupportedPlatform)
 }
@@ -124,6 +436,13 @@ pub(crate) fn collect_context_paths(args: &[String]) -> Vec<String> {
                 continue;
             }
 
+            // Send To and `%F` pass one path per argument; take those verbatim so
+            // names containing `;` survive.
+            if Path::new(entry).exists() {
+                results.push(entry.clone());
+                continue;
+            }
+
             // Windows `%V` may deliver multiple selections in a single argument
             // separated by newlines, pipes, or semicolons. Split generously.
             for part in entry
@@ -137,69 +456,153 @@ pub(crate) fn collect_context_paths(args: &[String]) -> Vec<String> {
     results
 }
 
-pub(crate) fn sanitize_context_paths(raw_paths: Vec<String>) -> ContextWipePayload {
+/// Standard sanitization for a single user-supplied target.
+/// Rejects network paths, missing paths, symlinks and BitBurn's own files, and returns the canonical path string.
+pub(crate) fn sanitize_target_path(raw: &str) -> Result<String, BitBurnError> {
+    sanitize_target_path_with(raw, SymlinkPolicy::Reject)
+}
+
+/// `sanitize_target_path` with symbolic links handled as `policy` says. A link the policy accepts
+/// stays a link in the result, with only its folder resolved; under `delete_link_only` it may dangle.
+pub(crate) fn sanitize_target_path_with(raw: &str, policy: SymlinkPolicy) -> Result<String, BitBurnError> {
+    let trimmed = raw.trim();
+
+    if trimmed.starts_with("\\\\") {
+        return Err(BitBurnError::NetworkPathNotSupported { path: trimmed.to_string() });
+    }
+
+    let candidate = PathBuf::from(trimmed);
+    let dangling_link = policy == SymlinkPolicy::DeleteLinkOnly && bitburn_core::is_symlink(&candidate);
+    if !candidate.exists() && !dangling_link {
+        return Err(BitBurnError::PathNotFound { path: trimmed.to_string() });
+    }
+
+    let canonical = match fs::symlink_metadata(&candidate) {
+        Ok(metadata) if metadata.file_type().is_symlink() => {
+            crate::symlinks::resolve(&candidate, policy)?;
+            crate::symlinks::resolved_link(&candidate)
+        }
+        Ok(_) => {
+            let canonical = candidate
+                .canonicalize()
+                .unwrap_or_else(|_| candidate.clone());
+            crate::self_protection::check(&canonical)?;
+            canonical
+        }
+        Err(err) => return Err(BitBurnError::io(&err, Some(&candidate))),
+    };
+
+    canonical
+        .to_str()
+        .map(|val| val.to_string())
+        .ok_or_else(|| BitBurnError::InvalidPathEncoding { path: trimmed.to_string() })
+}
+
+/// Sanitize paths from a context-menu invocation whose `argv` recorded `sender`, on the calling thread.
+#[cfg(test)]
+pub(crate) fn sanitize_context_paths(raw_paths: Vec<String>, sender: Option<LaunchContext>) -> ContextWipePayload {
+    sanitize_forwarded_paths(raw_paths, sender, LaunchContext::current(), sanitize_target_path, |_| {})
+}
+
+/// Sanitize paths forwarded from the instance the file manager launched. Paths are resolved again
+/// here, through `probe`, because the two instances may not see the same drives; when their
+/// privileges differ a path that cannot be reached is reported as a mismatch, not as missing.
+/// `progress` is told how many raw paths have been handled before each further one.
+pub(crate) fn sanitize_forwarded_paths(
+    raw_paths: Vec<String>,
+    sender: Option<LaunchContext>,
+    receiver: LaunchContext,
+    probe: impl Fn(&str) -> Result<String, BitBurnError>,
+    mut progress: impl FnMut(usize),
+) -> ContextWipePayload {
+    let mismatch = sender
+        .filter(|sender| *sender != receiver)
+        .map(|sender| ElevationMismatch { sender, receiver });
     let mut seen = HashSet::new();
     let mut valid = Vec::new();
     let mut invalid = Vec::new();
+    let roots = crate::volume_root::VolumeRoots::from_system();
 
-    for raw in raw_paths {
-        let trimmed = raw.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-
-        if trimmed.starts_with("\\\\") {
-            invalid.push(format!("Network paths are not supported: {}", trimmed));
-            continue;
+    for (index, raw) in raw_paths.into_iter().enumerate() {
+        if index > 0 {
+            progress(index);
         }
-
-        let candidate = PathBuf::from(trimmed);
-        if !candidate.exists() {
-            invalid.push(format!("Path not found: {}", trimmed));
+        if raw.trim().is_empty() {
             continue;
         }
 
-        match fs::symlink_metadata(&candidate) {
-            Ok(metadata) => {
-                if metadata.file_type().is_symlink() {
-                    invalid.push(format!("Symbolic links are not supported: {}", trimmed));
-                    continue;
+        match probe(&raw) {
+            Ok(canonical_str) => {
+                if seen.insert(canonical_str.clone()) {
+                    valid.push(canonical_str);
                 }
             }
-            Err(err) => {
-                invalid.push(format!("Failed to inspect {}: {}", trimmed, err));
-                continue;
-            }
-        }
-
-        let canonical = candidate
-            .canonicalize()
-            .unwrap_or_else(|_| candidate.clone());
-
-        let canonical_str = match canonical.to_str() {
-            Some(val) => val.to_string(),
-            None => {
-                invalid.push(format!("Unsupported path encoding: {}", trimmed));
-                continue;
+            Err(BitBurnError::PathNotFound { path } | BitBurnError::AccessDenied { path }) if mismatch.is_some() => {
+                invalid.push(BitBurnError::ElevationContextMismatch { path }.to_string());
             }
-        };
-
-        if seen.insert(canonical_str.clone()) {
-            valid.push(canonical_str);
+            Err(err) => invalid.push(err.to_string()),
         }
     }
 
+    let volume_roots = valid
+        .iter()
+        .filter(|path| Path::new(path).is_dir() && roots.is_volume_root(Path::new(path)))
+        .cloned()
+        .collect();
+    if let Some(mismatch) = &mismatch {
+        log_event("context_elevation_mismatch", json!({"sender": mismatch.sender, "receiver": mismatch.receiver}));
+    }
     ContextWipePayload {
         paths: valid,
         invalid,
         source: "context-menu".to_string(),
+        volume_roots,
+        elevation_mismatch: mismatch,
+        token: None,
+        assume_yes: false,
     }
 }
 
-pub fn dispatch_context_wipe(app: &AppHandle, payload: ContextWipePayload) {
+/// Announce `context_invocation_received` through `emit` at once, then sanitize `raw_paths` on a
+/// blocking task so the file manager that forwarded them is not kept waiting, reporting
+/// `context_sanitize_progress` while it takes long, and hand the payload to `deliver`.
+pub(crate) fn sanitize_in_background<P, E, D>(
+    raw_paths: Vec<String>,
+    sender: Option<LaunchContext>,
+    probe: P,
+    emit: E,
+    deliver: D,
+) -> JoinHandle<()>
+where
+    P: Fn(&str) -> Result<String, BitBurnError> + Send + 'static,
+    E: Fn(&str, serde_json::Value) + Send + 'static,
+    D: FnOnce(ContextWipePayload) + Send + 'static,
+{
+    let raw_count = raw_paths.len();
+    emit("context_invocation_received", json!({"raw_count": raw_count}));
+    spawn_blocking(move || {
+        let started = Instant::now();
+        let mut next_report = SANITIZE_PROGRESS_INTERVAL;
+        let payload = sanitize_forwarded_paths(raw_paths, sender, LaunchContext::current(), probe, |processed| {
+            let elapsed = started.elapsed();
+            if elapsed >= next_report {
+                next_report = elapsed + SANITIZE_PROGRESS_INTERVAL;
+                emit("context_sanitize_progress", json!({"processed": processed, "raw_count": raw_count}));
+            }
+        });
+        deliver(payload);
+    })
+}
+
+pub fn dispatch_context_wipe(app: &AppHandle, mut payload: ContextWipePayload) {
     if payload.paths.is_empty() && payload.invalid.is_empty() {
         return;
     }
+    if let Some(buffered) = app.try_state::<crate::context_policy::ContextPayloads>() {
+        if !payload.paths.is_empty() {
+            payload.token = Some(buffered.buffer(payload.clone()));
+        }
+    }
 
     let handle = app.clone();
     tauri::async_runtime::spawn(async move {
@@ -211,20 +614,64 @@ pub fn dispatch_context_wipe(app: &AppHandle, payload: ContextWipePayload) {
     });
 }
 
-pub fn handle_context_invocation(app: &AppHandle, argv: &[String]) {
+/// Act on the arguments of a launch, or of a second launch forwarded with its working directory
+/// `cwd`, which relative `--paths-from` and `--wipe` arguments are resolved against.
+pub fn handle_context_invocation(app: &AppHandle, argv: &[String], cwd: &Path) {
+    if let Some(folder) = crate::folder_free_space::free_space_arg(argv) {
+        crate::folder_free_space::dispatch(app, &folder, cwd);
+        return;
+    }
+    let assume_yes = argv.iter().any(|arg| arg == crate::glob_targets::YES_FLAG);
+    if let Some(manifest_path) = crate::manifest::manifest_path_arg(argv) {
+        let mut payload = match crate::path_expansion::expand(&manifest_path, RelativeBase::Dir(cwd)) {
+            Ok(manifest_path) => crate::manifest::manifest_context_payload(Path::new(&manifest_path)),
+            Err(err) => ContextWipePayload {
+                paths: Vec::new(),
+                invalid: vec![err.to_string()],
+                source: crate::manifest::manifest_source(Path::new(&manifest_path)),
+                volume_roots: Vec::new(),
+                elevation_mismatch: None,
+                token: None,
+                assume_yes: false,
+            },
+        };
+        payload.assume_yes = assume_yes;
+        dispatch_context_wipe(app, payload);
+        return;
+    }
+
+    let patterns = crate::glob_targets::wipe_arg_patterns(argv);
+    if !patterns.is_empty() {
+        let payload = crate::glob_targets::glob_context_payload(&patterns, cwd);
+        dispatch_context_wipe(app, ContextWipePayload { assume_yes, ..payload });
+        return;
+    }
+
     let raw_paths = collect_context_paths(argv);
     if raw_paths.is_empty() {
         return;
     }
 
-    let payload = sanitize_context_paths(raw_paths);
-    dispatch_context_wipe(app, payload);
+    let (emitter, receiver) = (app.clone(), app.clone());
+    sanitize_in_background(
+        raw_paths,
+        LaunchContext::from_argv(argv),
+        |raw: &str| crate::path_expansion::require_absolute(raw).and_then(|_| sanitize_target_path(raw)),
+        move |event, payload| {
+            let _ = emitter.emit(event, payload);
+        },
+        move |payload| crate::context_policy::handle_context_payload(&receiver, payload),
+    );
 }
 
 pub fn process_cli_side_effects<F>(argv: &[String], mut log_event: F) -> Option<i32>
 where
     F: FnMut(&str, serde_json::Value),
 {
+    if argv.iter().any(|a| a == crate::uninstall::UNINSTALL_CLEANUP_FLAG) {
+        return Some(crate::uninstall::run_cli(&mut log_event));
+    }
+
     #[cfg(windows)]
     {
         if argv.iter().any(|a| a == "--register-context-menu") {
@@ -259,110 +706,369 @@ where
         }
     }
 
-    #[cfg(not(windows))]
-    {
-        let _ = log_event;
-        let _ = argv;
-    }
-
     None
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{context_menu_writes, free_space_verb_writes, verb_statuses, write_all_or_roll_back, RegistryWrite, VerbProblem};
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+    use std::thread;
+
+    #[test]
+    fn failed_registration_write_leaves_no_keys_behind() {
+        let (file_key, folder_key) = ("Classes\\*\\shell\\BitBurn", "Classes\\Directory\\shell\\BitBurn");
+        let writes = context_menu_writes(&[file_key, folder_key], Path::new("C:/BitBurn/BitBurn.exe"));
+        let registry: RefCell<BTreeMap<String, BTreeMap<&str, String>>> = RefCell::default();
+
+        // The Directory key write throws after the `*` entry was fully written.
+        let failed = write_all_or_roll_back(
+            &writes,
+            |write| match write {
+                RegistryWrite::CreateKey { key } if key.starts_with(folder_key) => {
+                    Err(std::io::Error::other("access is denied"))
+                }
+                RegistryWrite::CreateKey { key } => {
+                    registry.borrow_mut().entry(key.clone()).or_default();
+                    Ok(())
+                }
+                RegistryWrite::SetValue { key, name, value } => {
+                    registry.borrow_mut().entry(key.clone()).or_default().insert(name, value.clone());
+                    Ok(())
+                }
+            },
+            || registry.borrow_mut().retain(|key, _| !key.starts_with(file_key) && !key.starts_with(folder_key)),
+        )
+        .expect_err("the Directory write should fail");
+
+        let registry = registry.into_inner();
+        assert!(registry.is_empty(), "stray keys: {:?}", registry.keys());
+        assert_eq!(failed.step, format!("creating {}", folder_key));
+        assert!(failed.to_string().contains("access is denied"));
+    }
+
+    #[test]
+    fn verb_status_pinpoints_a_corrupted_command_and_repair_fixes_it() {
+        type Registry = BTreeMap<String, BTreeMap<&'static str, String>>;
+        let (file_key, folder_key) = ("Classes\\*\\shell\\BitBurn", "Classes\\Directory\\shell\\BitBurn");
+        let exe = Path::new("C:/BitBurn/BitBurn.exe");
+        let register = |registry: &mut Registry| {
+            registry.clear();
+            let writes = context_menu_writes(&[file_key, folder_key], exe);
+            let written: Result<(), super::FailedWrite> = write_all_or_roll_back(
+                &writes,
+                |write| {
+                    match write {
+                        RegistryWrite::CreateKey { key } => registry.entry(key.clone()).or_default(),
+                        RegistryWrite::SetValue { key, name, value } => {
+                            registry.entry(key.clone()).or_default().insert(name, value.clone());
+                            return Ok(());
+                        }
+                    };
+                    Ok(())
+                },
+                || {},
+            );
+            written.unwrap();
+        };
+        let statuses = |registry: &Registry| {
+            verb_statuses(
+                &[("files", file_key), ("folders", folder_key)],
+                |key, name| registry.get(key)?.get(name).cloned(),
+                |key| registry.contains_key(key),
+                |path| path == exe,
+            )
+        };
+        let mut registry = Registry::new();
+        register(&mut registry);
+        let healthy = statuses(&registry);
+        assert!(healthy.iter().all(|verb| verb.problem.is_none()), "{:?}", healthy);
+        assert_eq!(healthy[0].executable.as_deref(), Some("C:/BitBurn/BitBurn.exe"));
+        assert_eq!(healthy[0].icon.as_deref(), Some("C:/BitBurn/BitBurn.exe"));
+
+        // The folder verb loses its placeholder; the file verb is untouched.
+        let folder_command = format!("{}\\command", folder_key);
+        registry.get_mut(&folder_command).unwrap().insert("", "\"C:/BitBurn/BitBurn.exe\" --context-wipe".to_string());
+        let broken = statuses(&registry);
+        assert_eq!(broken[0].problem, None);
+        assert_eq!((broken[1].verb.as_str(), broken[1].problem), ("folders", Some(VerbProblem::NoPlaceholder)));
+        assert!(broken[1].executable_exists && !broken[1].has_placeholder);
+
+        // Pointing at a program that has gone, or dropping the command, is named as such.
+        registry.get_mut(&folder_command).unwrap().insert("", "\"D:/Old/BitBurn.exe\" --context-wipe \"%V\"".to_string());
+        assert_eq!(statuses(&registry)[1].problem, Some(VerbProblem::ExecutableMissing));
+        registry.remove(&folder_command);
+        assert_eq!(statuses(&registry)[1].problem, Some(VerbProblem::NoCommand));
+        registry.retain(|key, _| !key.starts_with(file_key));
+        assert_eq!(statuses(&registry)[0].problem, Some(VerbProblem::Missing));
+
+        register(&mut registry);
+        assert_eq!(statuses(&registry), healthy);
+    }
+
+    #[test]
+    fn invocation_is_announced_before_a_slow_sanitization_finishes() {
+        use super::sanitize_in_background;
+        use std::sync::{Arc, Mutex};
+        use std::time::{Duration, Instant};
+
+        type Events = Vec<(String, serde_json::Value, Instant)>;
+        type Delivered = (Vec<String>, Instant);
+        let events: Arc<Mutex<Events>> = Arc::default();
+        let delivered: Arc<Mutex<Option<Delivered>>> = Arc::default();
+        let raw: Vec<String> = (0..5).map(|i| format!("C:\\selection\\{}.txt", i)).collect();
+        let (recorded, into) = (events.clone(), delivered.clone());
+        let handle = sanitize_in_background(
+            raw,
+            None,
+            |raw: &str| {
+                thread::sleep(Duration::from_millis(100));
+                Ok(raw.to_string())
+            },
+            move |event, payload| recorded.lock().unwrap().push((event.to_string(), payload, Instant::now())),
+            move |payload| *into.lock().unwrap() = Some((payload.paths, Instant::now())),
+        );
+
+        // Returned at once, with the announcement made and the paths still being checked.
+        let first = events.lock().unwrap().first().cloned().expect("announced before returning");
+        assert_eq!((first.0.as_str(), &first.1), ("context_invocation_received", &serde_json::json!({"raw_count": 5})));
+        assert!(delivered.lock().unwrap().is_none());
 
-    #[cfg(not(windows))]
+        tauri::async_runtime::block_on(handle).unwrap();
+        let (paths, delivered_at) = delivered.lock().unwrap().take().expect("the payload is delivered");
+        assert_eq!(paths.len(), 5);
+        assert!(first.2 + Duration::from_millis(400) <= delivered_at);
+        let events = events.lock().unwrap();
+        assert!(events.len() > 1, "a sanitization of half a second reports progress");
+        for (event, payload, _) in &events[1..] {
+            assert_eq!(event, "context_sanitize_progress");
+            assert_eq!(payload["raw_count"], 5);
+            assert!((1..5).contains(&payload["processed"].as_u64().unwrap()), "{}", payload);
+        }
+    }
+
+    #[test]
+    fn registration_binds_both_entries_to_the_context_wipe_command() {
+        let writes = context_menu_writes(&["F", "D"], Path::new("/opt/BitBurn"));
+        let commands: Vec<_> = writes
+            .iter()
+            .filter_map(|write| match write {
+                RegistryWrite::SetValue { key, name: "", value } => Some((key.as_str(), value.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].0, "F\\command");
+        assert_eq!(commands[1].0, "D\\command");
+        assert!(commands[0].1.starts_with("\"/opt/BitBurn\" ") && commands[0].1.ends_with("--context-wipe \"%V\""));
+
+        let free_space = free_space_verb_writes("D\\shell\\BitBurnFreeSpace", Path::new("/opt/BitBurn"));
+        let command = free_space.iter().find_map(|write| match write {
+            RegistryWrite::SetValue { key, name: "", value } if key.ends_with("\\command") => Some(value.as_str()),
+            _ => None,
+        });
+        assert!(command.is_some_and(|command| command.ends_with("--free-space-for \"%V\"")), "{:?}", command);
+    }
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
     #[test]
     fn register_context_menu_is_unavailable_on_non_windows() {
         let result = tauri::async_runtime::block_on(super::register_context_menu()).expect("command should return result");
         assert!(!result.success);
-        assert!(result.message.contains("not available"));
+        assert_eq!(result.message.code, "context_menu_unavailable");
     }
 
-    #[cfg(not(windows))]
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
     #[test]
     fn unregister_context_menu_is_unavailable_on_non_windows() {
         let result = tauri::async_runtime::block_on(super::unregister_context_menu()).expect("command should return result");
         assert!(!result.success);
-        assert!(result.message.contains("not available"));
+        assert_eq!(result.message.code, "context_menu_unavailable");
     }
 
-    #[cfg(not(windows))]
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
     #[test]
     fn get_context_menu_status_is_unavailable_on_non_windows() {
         let status = tauri::async_runtime::block_on(super::get_context_menu_status()).expect("command should return result");
         assert!(!status.enabled);
-        assert!(status.message.contains("not available"));
+        assert_eq!(status.message.code, "context_menu_unavailable");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn linux_commands_round_trip_through_xdg_data_home() {
+        use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+        let data_home = create_test_dir().unwrap();
+        std::env::set_var("XDG_DATA_HOME", &data_home);
+
+        let status = tauri::async_runtime::block_on(super::get_context_menu_status()).unwrap();
+        assert!(!status.enabled);
+
+        let result = tauri::async_runtime::block_on(super::register_context_menu()).unwrap();
+        assert!(result.success);
+        assert!(data_home.join("kio").join("servicemenus").is_dir());
+        let status = tauri::async_runtime::block_on(super::get_context_menu_status()).unwrap();
+        assert!(status.enabled, "{}", status.message.text);
+
+        let result = tauri::async_runtime::block_on(super::unregister_context_menu()).unwrap();
+        assert!(result.success);
+        let status = tauri::async_runtime::block_on(super::get_context_menu_status()).unwrap();
+        assert!(!status.enabled);
+
+        std::env::remove_var("XDG_DATA_HOME");
+        cleanup_test_dir(data_home);
     }
 }
 
-/// Register the Explorer context menu entries (Windows only).
+/// Register the Explorer, Finder or Nautilus/Dolphin context menu entries.
 #[tauri::command]
-pub async fn register_context_menu() -> Result<crate::WipeResult, String> {
+pub async fn register_context_menu() -> Result<crate::WipeResult, BitBurnError> {
     #[cfg(windows)]
     {
-        let exe_path = resolve_executable_path().map_err(|e| e.to_string())?;
-        enable_context_menu(&exe_path).map_err(|e| e.to_string())?;
+        let exe_path = resolve_executable_path()?;
+        enable_context_menu(&exe_path)?;
         crate::log_event("context_menu_register", json!({"status": "success"}));
 
-        return Ok(crate::WipeResult {
-            success: true,
-            message: "Context menu registered for files and folders".to_string(),
-        });
+        return Ok(crate::WipeResult::success(crate::messages::CONTEXT_MENU_REGISTERED.text()));
     }
 
-    #[cfg(not(windows))]
+    #[cfg(target_os = "macos")]
     {
-        Ok(crate::WipeResult {
-            success: false,
-            message: "Context menu not available on this platform".to_string(),
-        })
+        let exe_path = resolve_executable_path()?;
+        enable_context_menu(&exe_path)?;
+        crate::log_event("context_menu_register", json!({"status": "success", "platform": "macos"}));
+
+        return Ok(crate::WipeResult::success(crate::messages::QUICK_ACTION_INSTALLED.text()));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let exe_path = resolve_executable_path()?;
+        enable_context_menu(&exe_path)?;
+        crate::log_event("context_menu_register", json!({"status": "success", "platform": "linux"}));
+
+        Ok(crate::WipeResult::success(crate::messages::FILE_MANAGER_INSTALLED.text()))
+    }
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+    {
+        Ok(crate::WipeResult::failure_with_message(
+            ContextMenuError::UnsupportedPlatform.into(),
+            crate::messages::CONTEXT_MENU_UNAVAILABLE.text(),
+        ))
     }
 }
 
-/// Unregister the Explorer context menu entries (Windows only).
+/// Remove the context menu entries installed by `register_context_menu`.
 #[tauri::command]
-pub async fn unregister_context_menu() -> Result<crate::WipeResult, String> {
+pub async fn unregister_context_menu() -> Result<crate::WipeResult, BitBurnError> {
     #[cfg(windows)]
     {
-        disable_context_menu().map_err(|e| e.to_string())?;
+        disable_context_menu()?;
         crate::log_event("context_menu_unregister", json!({"status": "success"}));
 
-        return Ok(crate::WipeResult {
-            success: true,
-            message: "Context menu removed".to_string(),
-        });
+        return Ok(crate::WipeResult::success(crate::messages::CONTEXT_MENU_REMOVED.text()));
     }
 
-    #[cfg(not(windows))]
+    #[cfg(target_os = "macos")]
     {
-        Ok(crate::WipeResult {
-            success: false,
-            message: "Context menu not available on this platform".to_string(),
-        })
+        disable_context_menu()?;
+        crate::log_event("context_menu_unregister", json!({"status": "success", "platform": "macos"}));
+
+        return Ok(crate::WipeResult::success(crate::messages::QUICK_ACTION_REMOVED.text()));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        disable_context_menu()?;
+        crate::log_event("context_menu_unregister", json!({"status": "success", "platform": "linux"}));
+
+        Ok(crate::WipeResult::success(crate::messages::FILE_MANAGER_REMOVED.text()))
+    }
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+    {
+        Ok(crate::WipeResult::failure_with_message(
+            ContextMenuError::UnsupportedPlatform.into(),
+            crate::messages::CONTEXT_MENU_UNAVAILABLE.text(),
+        ))
     }
 }
 
-/// Report whether the Explorer context menu entries are currently installed.
+/// Report whether the context menu entries are installed and current.
 #[tauri::command]
-pub async fn get_context_menu_status() -> Result<crate::ContextMenuStatus, String> {
+pub async fn get_context_menu_status() -> Result<crate::ContextMenuStatus, BitBurnError> {
     #[cfg(windows)]
     {
-        let enabled = is_context_menu_enabled().map_err(|e| e.to_string())?;
+        let enabled = is_context_menu_enabled()?;
         let message = if enabled {
-            "Context menu is registered".to_string()
+            crate::messages::CONTEXT_MENU_ENABLED.text()
         } else {
-            "Context menu is not registered".to_string()
+            crate::messages::CONTEXT_MENU_DISABLED.text()
+        };
+
+        return Ok(crate::ContextMenuStatus { enabled, stale: false, message, verbs: registered_verbs() });
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let status = context_menu_workflow_status()?;
+        let stale = matches!(status, WorkflowStatus::Stale { .. });
+        let (enabled, message) = match status {
+            WorkflowStatus::Current => (true, crate::messages::QUICK_ACTION_CURRENT.text()),
+            WorkflowStatus::Missing => (false, crate::messages::QUICK_ACTION_MISSING.text()),
+            WorkflowStatus::Stale { embedded } => (
+                false,
+                crate::messages::QUICK_ACTION_STALE.render(json!({
+                    "location": embedded.as_deref().unwrap_or("an unknown location")
+                })),
+            ),
         };
 
-        return Ok(crate::ContextMenuStatus { enabled, message });
+        return Ok(crate::ContextMenuStatus { enabled, stale, message, verbs: Vec::new() });
     }
 
-    #[cfg(not(windows))]
+    #[cfg(target_os = "linux")]
+    {
+        let status = context_menu_integration_status()?;
+        let stale = matches!(status, IntegrationStatus::Stale { .. });
+        let (enabled, message) = match status {
+            IntegrationStatus::Current => (true, crate::messages::FILE_MANAGER_CURRENT.text()),
+            IntegrationStatus::Missing => (false, crate::messages::FILE_MANAGER_MISSING.text()),
+            IntegrationStatus::Stale { embedded } if embedded.is_empty() => (false, crate::messages::FILE_MANAGER_INCOMPLETE.text()),
+            IntegrationStatus::Stale { embedded } => (
+                false,
+                crate::messages::FILE_MANAGER_STALE.render(json!({"locations": embedded.join(", ")})),
+            ),
+        };
+
+        Ok(crate::ContextMenuStatus { enabled, stale, message, verbs: Vec::new() })
+    }
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
     {
         Ok(crate::ContextMenuStatus {
             enabled: false,
-            message: "Context menu not available on this platform".to_string(),
+            stale: false,
+            message: crate::messages::CONTEXT_MENU_UNAVAILABLE.text(),
+            verbs: Vec::new(),
         })
     }
 }
+
+/// Rewrite every context-menu entry from scratch for this copy of BitBurn, whatever state the
+/// old ones are in, and report the result.
+#[tauri::command]
+pub async fn repair_context_menu() -> Result<crate::ContextMenuStatus, BitBurnError> {
+    #[cfg(any(windows, target_os = "macos", target_os = "linux"))]
+    {
+        let exe_path = resolve_executable_path()?;
+        let _ = disable_context_menu();
+        enable_context_menu(&exe_path)?;
+        crate::log_event("context_menu_repaired", json!({"executable": exe_path.display().to_string()}));
+    }
+    get_context_menu_status().await
+}
@@ -0,0 +1,155 @@
+/// Whether the current process runs with administrator (Windows) or root (Unix) rights.
+#[cfg(windows)]
+pub fn is_elevated() -> bool {
+    // Reports true only for an elevated token, not merely membership of Administrators.
+    unsafe { windows_sys::Win32::UI::Shell::IsUserAnAdmin() != 0 }
+}
+
+#[cfg(unix)]
+pub fn is_elevated() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(not(any(windows, unix)))]
+pub fn is_elevated() -> bool {
+    false
+}
+
+/// Enable privilege `name` (e.g. `SeTakeOwnershipPrivilege`) in this process's token. Elevated
+/// administrators hold several that are off until a program turns them on.
+#[cfg(windows)]
+pub fn enable_privilege(name: &str) -> std::io::Result<()> {
+    use std::io;
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_NOT_ALL_ASSIGNED, LUID};
+    use windows_sys::Win32::Security::{
+        AdjustTokenPrivileges, LookupPrivilegeValueW, LUID_AND_ATTRIBUTES, SE_PRIVILEGE_ENABLED, TOKEN_ADJUST_PRIVILEGES,
+        TOKEN_PRIVILEGES, TOKEN_QUERY,
+    };
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    let name: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+    let mut token = std::ptr::null_mut();
+    if unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_ADJUST_PRIVILEGES | TOKEN_QUERY, &mut token) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut luid = LUID { LowPart: 0, HighPart: 0 };
+    let adjusted = unsafe {
+        LookupPrivilegeValueW(std::ptr::null(), name.as_ptr(), &mut luid) != 0 && {
+            let privileges = TOKEN_PRIVILEGES {
+                PrivilegeCount: 1,
+                Privileges: [LUID_AND_ATTRIBUTES { Luid: luid, Attributes: SE_PRIVILEGE_ENABLED }],
+            };
+            AdjustTokenPrivileges(token, 0, &privileges, 0, std::ptr::null_mut(), std::ptr::null_mut()) != 0
+                && GetLastError() != ERROR_NOT_ALL_ASSIGNED
+        }
+    };
+    let error = io::Error::last_os_error();
+    unsafe { CloseHandle(token) };
+    if adjusted {
+        Ok(())
+    } else {
+        Err(error)
+    }
+}
+
+/// Command-line flag that registered menu entries pass ahead of `--context-wipe`, recording who
+/// the file manager launching them runs as.
+pub const SENDER_CONTEXT_FLAG: &str = "--sender-context=";
+
+/// Privileges a BitBurn instance runs with. Two instances that differ see different mapped
+/// drives (Windows) or home directories and permissions (Unix).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct LaunchContext {
+    pub elevated: bool,
+    /// Real user id; `None` on Windows, where the integrity level is what differs.
+    pub uid: Option<u32>,
+}
+
+impl LaunchContext {
+    /// This process.
+    pub fn current() -> Self {
+        LaunchContext {
+            elevated: is_elevated(),
+            uid: current_uid(),
+        }
+    }
+
+    /// What a file manager the menu entries are registered with runs as: the desktop user, never
+    /// elevated, even when registration happens from an elevated instance.
+    pub fn file_manager() -> Self {
+        LaunchContext {
+            elevated: false,
+            uid: desktop_uid(),
+        }
+    }
+
+    /// The `--sender-context=` argument for a registered command line.
+    pub fn to_arg(self) -> String {
+        let level = if self.elevated { "elevated" } else { "standard" };
+        match self.uid {
+            Some(uid) => format!("{}{},uid:{}", SENDER_CONTEXT_FLAG, level, uid),
+            None => format!("{}{}", SENDER_CONTEXT_FLAG, level),
+        }
+    }
+
+    /// The context recorded in forwarded `argv`; `None` for entries registered before the flag
+    /// existed or an unreadable value.
+    pub fn from_argv(argv: &[String]) -> Option<Self> {
+        let value = argv.iter().find_map(|arg| arg.strip_prefix(SENDER_CONTEXT_FLAG))?;
+        let mut parts = value.split(',');
+        let elevated = match parts.next()? {
+            "elevated" => true,
+            "standard" => false,
+            _ => return None,
+        };
+        let uid = match parts.next() {
+            Some(part) => Some(part.strip_prefix("uid:")?.parse().ok()?),
+            None => None,
+        };
+        Some(LaunchContext { elevated, uid })
+    }
+}
+
+#[cfg(unix)]
+fn current_uid() -> Option<u32> {
+    Some(unsafe { libc::getuid() })
+}
+
+#[cfg(not(unix))]
+fn current_uid() -> Option<u32> {
+    None
+}
+
+/// The user behind `sudo` or `pkexec` when registering from an elevated instance.
+#[cfg(unix)]
+fn desktop_uid() -> Option<u32> {
+    ["SUDO_UID", "PKEXEC_UID"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok()?.parse().ok())
+        .or_else(current_uid)
+}
+
+#[cfg(not(unix))]
+fn desktop_uid() -> Option<u32> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sender_context_round_trips_through_argv() {
+        for context in [
+            LaunchContext { elevated: false, uid: None },
+            LaunchContext { elevated: true, uid: None },
+            LaunchContext { elevated: false, uid: Some(1000) },
+        ] {
+            let argv = vec!["BitBurn.exe".to_string(), context.to_arg(), "--context-wipe".to_string(), "C:/a.txt".to_string()];
+            assert_eq!(LaunchContext::from_argv(&argv), Some(context));
+        }
+        assert_eq!(LaunchContext::from_argv(&["BitBurn.exe".to_string(), "--context-wipe".to_string()]), None);
+        assert_eq!(LaunchContext::from_argv(&["--sender-context=admin".to_string()]), None);
+        assert_eq!(LaunchContext::from_argv(&["--sender-context=standard,uid:me".to_string()]), None);
+    }
+}
@@ -0,0 +1,350 @@
+//! Finder Quick Action ("Shred with BitBurn") installed as an Automator
+//! workflow bundle under `~/Library/Services`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::platform::elevation::LaunchContext;
+
+/// Name of the generated bundle; also the label Finder shows under Quick Actions.
+pub const WORKFLOW_NAME: &str = "Shred with BitBurn";
+const COMMAND_KEY: &str = "<key>COMMAND_STRING</key>";
+
+/// Installation state of the Quick Action relative to the running executable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkflowStatus {
+    Missing,
+    Current,
+    /// Installed, but launches a different (moved or deleted) BitBurn binary.
+    Stale { embedded: Option<String> },
+}
+
+/// `~/Library/Services`, overridable with `BITBURN_SERVICES_DIR` like the
+/// Windows registry root is with `BITBURN_CONTEXT_ROOT`.
+pub fn services_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("BITBURN_SERVICES_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library").join("Services"))
+}
+
+pub fn workflow_path(services_dir: &Path) -> PathBuf {
+    services_dir.join(format!("{}.workflow", WORKFLOW_NAME))
+}
+
+/// Quote `value` for a POSIX shell.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Shell step run by the Quick Action; Finder passes the selection as arguments.
+pub fn shell_command(exe_path: &Path) -> String {
+    format!(
+        "{} {} --context-wipe \"$@\"",
+        shell_quote(&exe_path.to_string_lossy()),
+        LaunchContext::file_manager().to_arg()
+    )
+}
+
+/// `Contents/Info.plist`: registers the service for files and folders in Finder.
+pub fn info_plist() -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>NSServices</key>
+	<array>
+		<dict>
+			<key>NSMenuItem</key>
+			<dict>
+				<key>default</key>
+				<string>{name}</string>
+			</dict>
+			<key>NSMessage</key>
+			<string>runWorkflowAsService</string>
+			<key>NSRequiredContext</key>
+			<dict>
+				<key>NSApplicationIdentifier</key>
+				<string>com.apple.finder</string>
+			</dict>
+			<key>NSSendFileTypes</key>
+			<array>
+				<string>public.item</string>
+			</array>
+		</dict>
+	</array>
+</dict>
+</plist>
+"#,
+        name = WORKFLOW_NAME
+    )
+}
+
+/// `Contents/document.wflow`: a single "Run Shell Script" action with input passed as arguments.
+pub fn document_wflow(exe_path: &Path) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>AMApplicationBuild</key>
+	<string>521</string>
+	<key>AMApplicationVersion</key>
+	<string>2.10</string>
+	<key>AMDocumentVersion</key>
+	<string>2</string>
+	<key>actions</key>
+	<array>
+		<dict>
+			<key>action</key>
+			<dict>
+				<key>AMAccepts</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Optional</key>
+					<true/>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.string</string>
+					</array>
+				</dict>
+				<key>AMActionVersion</key>
+				<string>2.0.3</string>
+				<key>AMApplication</key>
+				<array>
+					<string>Automator</string>
+				</array>
+				<key>AMProvides</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.string</string>
+					</array>
+				</dict>
+				<key>ActionBundlePath</key>
+				<string>/System/Library/Automator/Run Shell Script.action</string>
+				<key>ActionName</key>
+				<string>Run Shell Script</string>
+				<key>ActionParameters</key>
+				<dict>
+					{command_key}
+					<string>{command}</string>
+					<key>CheckedForUserDefaultShell</key>
+					<true/>
+					<key>inputMethod</key>
+					<integer>1</integer>
+					<key>shell</key>
+					<string>/bin/sh</string>
+					<key>source</key>
+					<string></string>
+				</dict>
+				<key>BundleIdentifier</key>
+				<string>com.apple.RunShellScript</string>
+				<key>CFBundleVersion</key>
+				<string>2.0.3</string>
+				<key>CanShowSelectedItemsWhenRun</key>
+				<false/>
+				<key>CanShowWhenRun</key>
+				<true/>
+				<key>Class Name</key>
+				<string>RunShellScriptAction</string>
+				<key>InputUUID</key>
+				<string>6B7A2F0E-3C11-4F4B-9D0A-2B6B1C1E5A01</string>
+				<key>OutputUUID</key>
+				<string>6B7A2F0E-3C11-4F4B-9D0A-2B6B1C1E5A02</string>
+				<key>UUID</key>
+				<string>6B7A2F0E-3C11-4F4B-9D0A-2B6B1C1E5A03</string>
+				<key>isViewVisible</key>
+				<integer>0</integer>
+			</dict>
+		</dict>
+	</array>
+	<key>connectors</key>
+	<dict/>
+	<key>workflowMetaData</key>
+	<dict>
+		<key>applicationBundleIDsByPath</key>
+		<dict/>
+		<key>applicationPaths</key>
+		<array/>
+		<key>inputTypeIdentifier</key>
+		<string>com.apple.Automator.fileSystemObject</string>
+		<key>outputTypeIdentifier</key>
+		<string>com.apple.Automator.nothing</string>
+		<key>presentationMode</key>
+		<integer>15</integer>
+		<key>processesInput</key>
+		<false/>
+		<key>serviceInputTypeIdentifier</key>
+		<string>com.apple.Automator.fileSystemObject</string>
+		<key>serviceOutputTypeIdentifier</key>
+		<string>com.apple.Automator.nothing</string>
+		<key>serviceProcessesInput</key>
+		<false/>
+		<key>workflowTypeIdentifier</key>
+		<string>com.apple.Automator.servicesMenu</string>
+	</dict>
+</dict>
+</plist>
+"#,
+        command_key = COMMAND_KEY,
+        command = xml_escape(&shell_command(exe_path))
+    )
+}
+
+/// Executable path embedded in a generated `document.wflow`, if it can be recovered.
+pub fn embedded_executable(document: &str) -> Option<String> {
+    let after_key = &document[document.find(COMMAND_KEY)? + COMMAND_KEY.len()..];
+    let start = after_key.find("<string>")? + "<string>".len();
+    let end = after_key[start..].find("</string>")? + start;
+    let command = xml_unescape(&after_key[start..end]);
+
+    // Undo `shell_quote`: a single-quoted word where `'\''` stands for a quote.
+    let quoted = command.strip_prefix('\'')?;
+    let mut path = String::new();
+    let mut rest = quoted;
+    loop {
+        let close = rest.find('\'')?;
+        path.push_str(&rest[..close]);
+        rest = &rest[close + 1..];
+        match rest.strip_prefix(r"\''") {
+            Some(remaining) => {
+                path.push('\'');
+                rest = remaining;
+            }
+            None => return Some(path),
+        }
+    }
+}
+
+/// Write the bundle, replacing any previous copy.
+pub fn install_workflow(services_dir: &Path, exe_path: &Path) -> io::Result<PathBuf> {
+    let bundle = workflow_path(services_dir);
+    if bundle.exists() {
+        fs::remove_dir_all(&bundle)?;
+    }
+    let contents = bundle.join("Contents");
+    fs::create_dir_all(&contents)?;
+    fs::write(contents.join("Info.plist"), info_plist())?;
+    fs::write(contents.join("document.wflow"), document_wflow(exe_path))?;
+    Ok(bundle)
+}
+
+pub fn remove_workflow(services_dir: &Path) -> io::Result<()> {
+    match fs::remove_dir_all(workflow_path(services_dir)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+pub fn workflow_status(services_dir: &Path, exe_path: &Path) -> WorkflowStatus {
+    let document = workflow_path(services_dir).join("Contents").join("document.wflow");
+    let Ok(contents) = fs::read_to_string(document) else {
+        return WorkflowStatus::Missing;
+    };
+    let embedded = embedded_executable(&contents);
+    if embedded.as_deref() == Some(&*exe_path.to_string_lossy()) {
+        WorkflowStatus::Current
+    } else {
+        WorkflowStatus::Stale { embedded }
+    }
+}
+
+/// Ask the pasteboard server to rescan services so the Quick Action appears
+/// without logging out. Best effort: failures are ignored.
+#[cfg(target_os = "macos")]
+pub fn refresh_services_cache() {
+    let _ = std::process::Command::new("/System/Library/CoreServices/pbs")
+        .arg("-update")
+        .status();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    #[test]
+    fn services_dir_honours_override() {
+        std::env::set_var("BITBURN_SERVICES_DIR", "/tmp/BitBurnServicesOverride");
+        assert_eq!(services_dir(), Some(PathBuf::from("/tmp/BitBurnServicesOverride")));
+        std::env::remove_var("BITBURN_SERVICES_DIR");
+    }
+
+    #[test]
+    fn info_plist_registers_a_finder_service_for_any_item() {
+        let plist = info_plist();
+        assert!(plist.contains("<string>Shred with BitBurn</string>"));
+        assert!(plist.contains("<string>runWorkflowAsService</string>"));
+        assert!(plist.contains("<string>com.apple.finder</string>"));
+        assert!(plist.contains("<string>public.item</string>"));
+    }
+
+    #[test]
+    fn workflow_runs_the_app_with_selected_paths_as_arguments() {
+        let exe = Path::new("/Applications/BitBurn.app/Contents/MacOS/BitBurn");
+        let document = document_wflow(exe);
+        assert!(document.contains(&format!(
+            "<string>'/Applications/BitBurn.app/Contents/MacOS/BitBurn' {} --context-wipe &quot;$@&quot;</string>",
+            LaunchContext::file_manager().to_arg()
+        )));
+        assert!(document.contains("<key>inputMethod</key>\n\t\t\t\t\t<integer>1</integer>"));
+        assert!(document.contains("<string>com.apple.Automator.servicesMenu</string>"));
+        assert_eq!(embedded_executable(&document).as_deref(), Some(exe.to_str().unwrap()));
+    }
+
+    #[test]
+    fn awkward_executable_paths_round_trip() {
+        let exe = Path::new("/Users/o'neil/Apps & Tools/<BitBurn>.app/Contents/MacOS/BitBurn");
+        let document = document_wflow(exe);
+        assert!(!document.contains("<BitBurn>"));
+        assert_eq!(embedded_executable(&document).as_deref(), Some(exe.to_str().unwrap()));
+        assert_eq!(embedded_executable("<plist/>"), None);
+    }
+
+    #[test]
+    fn status_detects_missing_current_and_stale_bundles() {
+        let dir = create_test_dir().unwrap();
+        let old_exe = Path::new("/Volumes/BitBurn/BitBurn.app/Contents/MacOS/BitBurn");
+        let exe = Path::new("/Applications/BitBurn.app/Contents/MacOS/BitBurn");
+        assert_eq!(workflow_status(&dir, exe), WorkflowStatus::Missing);
+
+        install_workflow(&dir, old_exe).unwrap();
+        assert_eq!(
+            workflow_status(&dir, exe),
+            WorkflowStatus::Stale {
+                embedded: Some(old_exe.to_string_lossy().to_string())
+            }
+        );
+
+        install_workflow(&dir, exe).unwrap();
+        assert_eq!(workflow_status(&dir, exe), WorkflowStatus::Current);
+        assert!(workflow_path(&dir).join("Contents").join("Info.plist").is_file());
+
+        remove_workflow(&dir).unwrap();
+        remove_workflow(&dir).unwrap();
+        assert_eq!(workflow_status(&dir, exe), WorkflowStatus::Missing);
+        cleanup_test_dir(dir);
+    }
+}
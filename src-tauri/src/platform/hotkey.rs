@@ -0,0 +1,164 @@
+//! Accelerators for system-wide keyboard shortcuts, checked here and registered through
+//! tauri-plugin-global-shortcut.
+
+use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
+
+use crate::error::BitBurnError;
+
+/// Key combination such as `Ctrl+Alt+Shift+B`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkey {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// The Windows key (Super on Linux, Command on macOS).
+    pub meta: bool,
+    pub key: HotkeyKey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyKey {
+    /// `A`-`Z` or `0`-`9`, upper-cased.
+    Character(char),
+    /// `F1`-`F24`.
+    Function(u8),
+    Escape,
+    Space,
+    Pause,
+}
+
+impl Hotkey {
+    /// Parse `+`-separated modifiers followed by one key. At least one modifier is required so a
+    /// plain key is never taken away from every other program.
+    pub fn parse(accelerator: &str) -> Result<Self, BitBurnError> {
+        let invalid = |reason: &str| BitBurnError::InvalidConfiguration {
+            message: format!("Invalid shortcut \"{}\": {}", accelerator, reason),
+        };
+        let mut hotkey = Hotkey {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            meta: false,
+            key: HotkeyKey::Escape,
+        };
+        let mut key = None;
+        for part in accelerator.split('+').map(str::trim) {
+            if key.is_some() {
+                return Err(invalid("the key must come last"));
+            }
+            let modifier = match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut hotkey.ctrl,
+                "alt" | "option" => &mut hotkey.alt,
+                "shift" => &mut hotkey.shift,
+                "win" | "super" | "meta" | "cmd" | "command" => &mut hotkey.meta,
+                _ => {
+                    key = Some(parse_key(part).ok_or_else(|| invalid(&format!("unknown key \"{}\"", part)))?);
+                    continue;
+                }
+            };
+            if *modifier {
+                return Err(invalid(&format!("{} is repeated", part)));
+            }
+            *modifier = true;
+        }
+        hotkey.key = key.ok_or_else(|| invalid("no key given"))?;
+        if !(hotkey.ctrl || hotkey.alt || hotkey.shift || hotkey.meta) {
+            return Err(invalid("at least one modifier is required"));
+        }
+        Ok(hotkey)
+    }
+
+    /// The key combination as the global-shortcut plugin registers it.
+    pub fn shortcut(&self) -> Shortcut {
+        let mut modifiers = Modifiers::empty();
+        for (held, flag) in [
+            (self.ctrl, Modifiers::CONTROL),
+            (self.alt, Modifiers::ALT),
+            (self.shift, Modifiers::SHIFT),
+            (self.meta, Modifiers::SUPER),
+        ] {
+            if held {
+                modifiers |= flag;
+            }
+        }
+        Shortcut::new(Some(modifiers), self.key.code())
+    }
+}
+
+impl HotkeyKey {
+    fn code(self) -> Code {
+        const LETTERS: [Code; 26] = [
+            Code::KeyA, Code::KeyB, Code::KeyC, Code::KeyD, Code::KeyE, Code::KeyF, Code::KeyG,
+            Code::KeyH, Code::KeyI, Code::KeyJ, Code::KeyK, Code::KeyL, Code::KeyM, Code::KeyN,
+            Code::KeyO, Code::KeyP, Code::KeyQ, Code::KeyR, Code::KeyS, Code::KeyT, Code::KeyU,
+            Code::KeyV, Code::KeyW, Code::KeyX, Code::KeyY, Code::KeyZ,
+        ];
+        const DIGITS: [Code; 10] = [
+            Code::Digit0, Code::Digit1, Code::Digit2, Code::Digit3, Code::Digit4,
+            Code::Digit5, Code::Digit6, Code::Digit7, Code::Digit8, Code::Digit9,
+        ];
+        const FUNCTIONS: [Code; 24] = [
+            Code::F1, Code::F2, Code::F3, Code::F4, Code::F5, Code::F6, Code::F7, Code::F8,
+            Code::F9, Code::F10, Code::F11, Code::F12, Code::F13, Code::F14, Code::F15, Code::F16,
+            Code::F17, Code::F18, Code::F19, Code::F20, Code::F21, Code::F22, Code::F23, Code::F24,
+        ];
+        match self {
+            HotkeyKey::Character(c @ 'A'..='Z') => LETTERS[(c as u8 - b'A') as usize],
+            HotkeyKey::Character(c) => DIGITS[(c as u8 - b'0') as usize],
+            HotkeyKey::Function(n) => FUNCTIONS[usize::from(n) - 1],
+            HotkeyKey::Escape => Code::Escape,
+            HotkeyKey::Space => Code::Space,
+            HotkeyKey::Pause => Code::Pause,
+        }
+    }
+}
+
+fn parse_key(part: &str) -> Option<HotkeyKey> {
+    let upper = part.to_ascii_uppercase();
+    let mut chars = upper.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphanumeric() => return Some(HotkeyKey::Character(c)),
+        _ => {}
+    }
+    match upper.as_str() {
+        "ESC" | "ESCAPE" => Some(HotkeyKey::Escape),
+        "SPACE" => Some(HotkeyKey::Space),
+        "PAUSE" | "BREAK" => Some(HotkeyKey::Pause),
+        _ => upper
+            .strip_prefix('F')
+            .and_then(|n| n.parse::<u8>().ok())
+            .filter(|n| (1..=24).contains(n))
+            .map(HotkeyKey::Function),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accelerators_parse_case_insensitively() {
+        let hotkey = Hotkey::parse("Ctrl+Alt+Shift+B").unwrap();
+        assert_eq!(
+            hotkey,
+            Hotkey { ctrl: true, alt: true, shift: true, meta: false, key: HotkeyKey::Character('B') }
+        );
+        assert_eq!(Hotkey::parse("control + shift + b").unwrap().key, HotkeyKey::Character('B'));
+        assert_eq!(Hotkey::parse("Win+F12").unwrap().key, HotkeyKey::Function(12));
+        assert_eq!(Hotkey::parse("Alt+Esc").unwrap().shortcut(), Shortcut::new(Some(Modifiers::ALT), Code::Escape));
+        assert_eq!(Hotkey::parse("Alt+F1").unwrap().shortcut().key, Code::F1);
+        assert_eq!(
+            Hotkey::parse("Ctrl+Alt+Shift+B").unwrap().shortcut(),
+            "Ctrl+Alt+Shift+B".parse::<Shortcut>().unwrap(),
+        );
+        assert_eq!(Hotkey::parse("Win+7").unwrap().shortcut().key, Code::Digit7);
+    }
+
+    #[test]
+    fn malformed_accelerators_are_rejected() {
+        for accelerator in ["B", "Ctrl+Alt", "Ctrl+Ctrl+B", "Ctrl+B+Alt", "Ctrl+Home", "Ctrl+F25", ""] {
+            let err = Hotkey::parse(accelerator).unwrap_err();
+            assert_eq!(err.code(), "invalid_configuration", "{}", accelerator);
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! "Shred with BitBurn" entries for Linux file managers: a Nautilus script and
+//! a KDE (Dolphin) service menu, both under `$XDG_DATA_HOME`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::platform::elevation::LaunchContext;
+
+const ENTRY_NAME: &str = "Shred with BitBurn";
+const SERVICE_MENU_FILE: &str = "bitburn-shred.desktop";
+/// Marker line recording which executable an installed entry launches.
+const SCRIPT_MARKER: &str = "# BitBurn-Executable: ";
+const DESKTOP_MARKER: &str = "X-BitBurn-Executable=";
+
+/// Installation state across all supported file managers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrationStatus {
+    Missing,
+    Current,
+    /// Some entries are missing or launch a different BitBurn binary.
+    Stale { embedded: Vec<String> },
+}
+
+/// `$XDG_DATA_HOME`, falling back to `~/.local/share`.
+#[cfg(target_os = "linux")]
+pub fn data_home() -> Option<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")))
+}
+
+pub fn nautilus_script_path(data_home: &Path) -> PathBuf {
+    data_home.join("nautilus").join("scripts").join(ENTRY_NAME)
+}
+
+pub fn service_menu_path(data_home: &Path) -> PathBuf {
+    data_home.join("kio").join("servicemenus").join(SERVICE_MENU_FILE)
+}
+
+/// Quote `value` for a POSIX shell.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Quote one `Exec=` argument per the Desktop Entry spec: reserved characters are
+/// backslash-escaped inside double quotes, then backslashes are escaped again for
+/// the string value, and `%` is doubled so it is not read as a field code.
+fn desktop_exec_quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted.replace('\\', "\\\\").replace('%', "%%")
+}
+
+/// Nautilus passes absolute paths for the selection in an environment variable,
+/// newline separated, which `--context-wipe` already splits on.
+pub fn nautilus_script(exe_path: &Path) -> String {
+    let exe = exe_path.to_string_lossy();
+    format!(
+        "#!/bin/sh\n{marker}{exe}\nif [ -n \"$NAUTILUS_SCRIPT_SELECTED_FILE_PATHS\" ]; then\n    exec {quoted} {sender} --context-wipe \"$NAUTILUS_SCRIPT_SELECTED_FILE_PATHS\"\nfi\nexec {quoted} {sender} --context-wipe \"$@\"\n",
+        marker = SCRIPT_MARKER,
+        sender = LaunchContext::file_manager().to_arg(),
+        exe = exe.replace('\n', " "),
+        quoted = shell_quote(&exe),
+    )
+}
+
+pub fn service_menu(exe_path: &Path) -> String {
+    let exe = exe_path.to_string_lossy();
+    format!(
+        "[Desktop Entry]\nType=Service\nMimeType=all/all;\nX-KDE-ServiceTypes=KonqPopupMenu/Plugin\nActions=shredWithBitBurn;\n{marker}{exe}\n\n[Desktop Action shredWithBitBurn]\nName={name}\nIcon=edit-delete-shred\nExec={quoted} {sender} --context-wipe %F\n",
+        marker = DESKTOP_MARKER,
+        sender = LaunchContext::file_manager().to_arg(),
+        exe = exe.replace('\n', " "),
+        name = ENTRY_NAME,
+        quoted = desktop_exec_quote(&exe),
+    )
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Write `contents` to `path`, creating the standard XDG folders when the file manager never has.
+fn install_file(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)?;
+    // Nautilus only lists executable scripts; Plasma 6 requires the same of service menus.
+    make_executable(path)
+}
+
+pub fn install(data_home: &Path, exe_path: &Path) -> io::Result<()> {
+    install_file(&nautilus_script_path(data_home), &nautilus_script(exe_path))?;
+    install_file(&service_menu_path(data_home), &service_menu(exe_path))
+}
+
+pub fn uninstall(data_home: &Path) -> io::Result<()> {
+    for path in [nautilus_script_path(data_home), service_menu_path(data_home)] {
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Executable recorded in an installed entry, or `None` when the file is absent.
+/// Entries without a readable marker report an empty string.
+fn embedded_executable(path: &Path, marker: &str) -> Option<String> {
+    let contents = fs::read_to_string(path).ok()?;
+    Some(
+        contents
+            .lines()
+            .find_map(|line| line.strip_prefix(marker))
+            .unwrap_or_default()
+            .to_string(),
+    )
+}
+
+pub fn status(data_home: &Path, exe_path: &Path) -> IntegrationStatus {
+    let installed = [
+        embedded_executable(&nautilus_script_path(data_home), SCRIPT_MARKER),
+        embedded_executable(&service_menu_path(data_home), DESKTOP_MARKER),
+    ];
+    if installed.iter().all(Option::is_none) {
+        return IntegrationStatus::Missing;
+    }
+
+    let exe = exe_path.to_string_lossy();
+    if installed.iter().all(|entry| entry.as_deref() == Some(&*exe)) {
+        return IntegrationStatus::Current;
+    }
+    let mut embedded: Vec<String> = installed
+        .into_iter()
+        .flatten()
+        .filter(|entry| *entry != exe)
+        .collect();
+    embedded.dedup();
+    IntegrationStatus::Stale { embedded }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    #[test]
+    fn generated_entries_pass_selection_to_context_wipe() {
+        let exe = Path::new("/opt/BitBurn/bitburn");
+        let sender = LaunchContext::file_manager().to_arg();
+        let script = nautilus_script(exe);
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains(&format!(
+            "exec '/opt/BitBurn/bitburn' {} --context-wipe \"$NAUTILUS_SCRIPT_SELECTED_FILE_PATHS\"",
+            sender
+        )));
+
+        let menu = service_menu(exe);
+        assert!(menu.contains("X-KDE-ServiceTypes=KonqPopupMenu/Plugin"));
+        assert!(menu.contains(&format!("Exec=\"/opt/BitBurn/bitburn\" {} --context-wipe %F", sender)));
+        assert!(menu.contains("Name=Shred with BitBurn"));
+    }
+
+    #[test]
+    fn exec_arguments_are_escaped_per_desktop_entry_spec() {
+        assert_eq!(desktop_exec_quote("/a b/bit$burn"), "\"/a b/bit\\\\$burn\"");
+        assert_eq!(desktop_exec_quote("/100%/bitburn"), "\"/100%%/bitburn\"");
+        assert_eq!(shell_quote("/o'neil/bitburn"), "'/o'\\''neil/bitburn'");
+    }
+
+    #[test]
+    fn install_status_uninstall_round_trip() {
+        let data_home = create_test_dir().unwrap();
+        let exe = Path::new("/opt/BitBurn/bitburn");
+        assert_eq!(status(&data_home, exe), IntegrationStatus::Missing);
+
+        install(&data_home, exe).unwrap();
+        assert!(nautilus_script_path(&data_home).is_file());
+        assert!(service_menu_path(&data_home).is_file());
+        assert_eq!(status(&data_home, exe), IntegrationStatus::Current);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(nautilus_script_path(&data_home)).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+
+        uninstall(&data_home).unwrap();
+        uninstall(&data_home).unwrap();
+        assert_eq!(status(&data_home, exe), IntegrationStatus::Missing);
+        cleanup_test_dir(data_home);
+    }
+
+    #[test]
+    fn moved_executable_or_partial_install_is_stale() {
+        let data_home = create_test_dir().unwrap();
+        let old_exe = Path::new("/home/user/Downloads/bitburn");
+        let exe = Path::new("/opt/BitBurn/bitburn");
+        install(&data_home, old_exe).unwrap();
+        assert_eq!(
+            status(&data_home, exe),
+            IntegrationStatus::Stale {
+                embedded: vec![old_exe.to_string_lossy().to_string()]
+            }
+        );
+
+        install(&data_home, exe).unwrap();
+        fs::remove_file(service_menu_path(&data_home)).unwrap();
+        assert_eq!(status(&data_home, exe), IntegrationStatus::Stale { embedded: Vec::new() });
+        cleanup_test_dir(data_home);
+    }
+}
@@ -1,2 +1,10 @@
 pub mod context_menu;
 pub mod autostart;
+pub mod elevation;
+pub mod hotkey;
+pub mod send_to;
+pub mod shadow_copies;
+#[cfg(any(target_os = "macos", test))]
+pub mod finder_services;
+#[cfg(any(target_os = "linux", test))]
+pub mod linux_file_managers;
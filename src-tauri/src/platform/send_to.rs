@@ -0,0 +1,314 @@
+//! "Send To" flyout integration on Windows: a shortcut in the user's SendTo folder
+//! that launches BitBurn with `--context-wipe` and the selected items as arguments.
+//! Explorer runs unelevated, which the shortcut records for the receiving instance.
+
+#[cfg(windows)]
+use serde_json::json;
+#[cfg(any(windows, test))]
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::error::BitBurnError;
+
+#[cfg(any(windows, test))]
+pub const SHORTCUT_NAME: &str = "BitBurn (Secure Wipe).lnk";
+#[cfg(any(windows, test))]
+const SHORTCUT_ARGUMENTS: &str = "--sender-context=standard --context-wipe";
+
+#[derive(Debug, Error)]
+pub enum SendToError {
+    #[cfg(not(windows))]
+    #[error("Send To shortcut not supported on this platform")]
+    UnsupportedPlatform,
+    #[cfg(windows)]
+    #[error("missing executable path")]
+    MissingExecutablePath,
+    #[cfg(windows)]
+    #[error("APPDATA is not set")]
+    MissingAppData,
+    #[cfg(windows)]
+    #[error("shortcut error: {0}")]
+    Shortcut(String),
+}
+
+/// State of the Send To shortcut relative to the running executable.
+#[cfg(any(windows, test))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShortcutStatus {
+    Missing,
+    Current,
+    Stale { target: String },
+}
+
+/// `%AppData%\Microsoft\Windows\SendTo`.
+#[cfg(any(windows, test))]
+pub fn sendto_dir(app_data: &Path) -> PathBuf {
+    app_data.join("Microsoft").join("Windows").join("SendTo")
+}
+
+#[cfg(any(windows, test))]
+pub fn shortcut_path(app_data: &Path) -> PathBuf {
+    sendto_dir(app_data).join(SHORTCUT_NAME)
+}
+
+/// Quote a value as a PowerShell single-quoted string literal.
+#[cfg(any(windows, test))]
+pub(crate) fn ps_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Script creating the shortcut through the `WScript.Shell` COM object.
+#[cfg(any(windows, test))]
+fn create_shortcut_script(link: &Path, exe_path: &Path) -> String {
+    let exe = exe_path.to_string_lossy();
+    format!(
+        "$link = (New-Object -ComObject WScript.Shell).CreateShortcut({link})\n\
+         $link.TargetPath = {exe}\n\
+         $link.Arguments = {args}\n\
+         $link.IconLocation = {icon}\n\
+         $link.Description = 'Securely wipe the selected items with BitBurn'\n\
+         $link.Save()\n",
+        link = ps_quote(&link.to_string_lossy()),
+        exe = ps_quote(&exe),
+        args = ps_quote(SHORTCUT_ARGUMENTS),
+        icon = ps_quote(&format!("{},0", exe)),
+    )
+}
+
+/// Script printing the shortcut's target and arguments on separate lines.
+#[cfg(any(windows, test))]
+fn read_shortcut_script(link: &Path) -> String {
+    format!(
+        "[Console]::OutputEncoding = [Text.Encoding]::UTF8\n\
+         $link = (New-Object -ComObject WScript.Shell).CreateShortcut({link})\n\
+         Write-Output $link.TargetPath\n\
+         Write-Output $link.Arguments\n",
+        link = ps_quote(&link.to_string_lossy()),
+    )
+}
+
+/// Compare the `read_shortcut_script` output, `None` when no shortcut exists, against
+/// the running executable. Windows paths are case-insensitive, so the target is compared ignoring case.
+#[cfg(any(windows, test))]
+fn classify_shortcut(output: Option<&str>, exe_path: &Path) -> ShortcutStatus {
+    let Some(output) = output else {
+        return ShortcutStatus::Missing;
+    };
+    let mut lines = output.lines().map(str::trim);
+    let target = lines.next().unwrap_or_default();
+    let arguments = lines.next().unwrap_or_default();
+    let exe = exe_path.to_string_lossy();
+    if target.eq_ignore_ascii_case(&exe) && arguments == SHORTCUT_ARGUMENTS {
+        ShortcutStatus::Current
+    } else {
+        ShortcutStatus::Stale { target: target.to_string() }
+    }
+}
+
+#[cfg(windows)]
+fn app_data_dir() -> Result<PathBuf, SendToError> {
+    std::env::var_os("APPDATA")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .ok_or(SendToError::MissingAppData)
+}
+
+#[cfg(windows)]
+fn run_powershell(script: &str) -> Result<String, SendToError> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = std::process::Command::new("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-ExecutionPolicy", "Bypass", "-Command", script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| SendToError::Shortcut(format!("failed to start PowerShell: {}", e)))?;
+    if !output.status.success() {
+        return Err(SendToError::Shortcut(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(windows)]
+pub fn create_shortcut(exe_path: &Path) -> Result<(), SendToError> {
+    let link = shortcut_path(&app_data_dir()?);
+    if let Some(dir) = link.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| SendToError::Shortcut(e.to_string()))?;
+    }
+    run_powershell(&create_shortcut_script(&link, exe_path)).map(|_| ())
+}
+
+#[cfg(windows)]
+pub fn remove_shortcut() -> Result<(), SendToError> {
+    match std::fs::remove_file(shortcut_path(&app_data_dir()?)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(SendToError::Shortcut(e.to_string())),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(windows)]
+pub fn shortcut_status(exe_path: &Path) -> Result<ShortcutStatus, SendToError> {
+    let link = shortcut_path(&app_data_dir()?);
+    let output = if link.is_file() {
+        Some(run_powershell(&read_shortcut_script(&link))?)
+    } else {
+        None
+    };
+    Ok(classify_shortcut(output.as_deref(), exe_path))
+}
+
+#[cfg(windows)]
+fn resolve_executable_path() -> Result<PathBuf, SendToError> {
+    std::env::current_exe().map_err(|_| SendToError::MissingExecutablePath)
+}
+
+/// Add "BitBurn (Secure Wipe)" to the Explorer Send To flyout.
+#[tauri::command]
+pub async fn register_sendto_shortcut() -> Result<crate::WipeResult, BitBurnError> {
+    #[cfg(windows)]
+    {
+        let exe_path = resolve_executable_path()?;
+        tauri::async_runtime::spawn_blocking(move || create_shortcut(&exe_path))
+            .await
+            .map_err(|e| BitBurnError::internal(e.to_string()))??;
+        crate::log_event("sendto_register", json!({"status": "success"}));
+
+        return Ok(crate::WipeResult::success(crate::messages::SENDTO_CREATED.text()));
+    }
+
+    #[cfg(not(windows))]
+    {
+        Ok(crate::WipeResult::failure_with_message(
+            SendToError::UnsupportedPlatform.into(),
+            crate::messages::SENDTO_UNAVAILABLE.text(),
+        ))
+    }
+}
+
+/// Remove the Send To shortcut created by `register_sendto_shortcut`.
+#[tauri::command]
+pub async fn unregister_sendto_shortcut() -> Result<crate::WipeResult, BitBurnError> {
+    #[cfg(windows)]
+    {
+        remove_shortcut()?;
+        crate::log_event("sendto_unregister", json!({"status": "success"}));
+
+        return Ok(crate::WipeResult::success(crate::messages::SENDTO_REMOVED.text()));
+    }
+
+    #[cfg(not(windows))]
+    {
+        Ok(crate::WipeResult::failure_with_message(
+            SendToError::UnsupportedPlatform.into(),
+            crate::messages::SENDTO_UNAVAILABLE.text(),
+        ))
+    }
+}
+
+/// Report whether the Send To shortcut exists and targets this copy of BitBurn.
+#[tauri::command]
+pub async fn get_sendto_status() -> Result<crate::ContextMenuStatus, BitBurnError> {
+    #[cfg(windows)]
+    {
+        let exe_path = resolve_executable_path()?;
+        let status = tauri::async_runtime::spawn_blocking(move || shortcut_status(&exe_path))
+            .await
+            .map_err(|e| BitBurnError::internal(e.to_string()))??;
+        let stale = matches!(status, ShortcutStatus::Stale { .. });
+        let (enabled, message) = match status {
+            ShortcutStatus::Current => (true, crate::messages::SENDTO_CURRENT.text()),
+            ShortcutStatus::Missing => (false, crate::messages::SENDTO_MISSING.text()),
+            ShortcutStatus::Stale { target } => (false, crate::messages::SENDTO_STALE.render(json!({"target": target}))),
+        };
+
+        return Ok(crate::ContextMenuStatus { enabled, stale, message, verbs: Vec::new() });
+    }
+
+    #[cfg(not(windows))]
+    {
+        Ok(crate::ContextMenuStatus {
+            enabled: false,
+            stale: false,
+            message: crate::messages::SENDTO_UNAVAILABLE.text(),
+            verbs: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcut_lives_in_roaming_sendto_folder() {
+        let app_data = Path::new("C:/Users/alex/AppData/Roaming");
+        assert_eq!(
+            shortcut_path(app_data),
+            app_data
+                .join("Microsoft")
+                .join("Windows")
+                .join("SendTo")
+                .join("BitBurn (Secure Wipe).lnk")
+        );
+    }
+
+    #[test]
+    fn create_script_sets_target_arguments_and_icon() {
+        let script = create_shortcut_script(
+            Path::new("C:/Users/o'neil/SendTo/BitBurn (Secure Wipe).lnk"),
+            Path::new("C:/Program Files/BitBurn/bitburn.exe"),
+        );
+        assert!(script.contains("CreateShortcut('C:/Users/o''neil/SendTo/BitBurn (Secure Wipe).lnk')"));
+        assert!(script.contains("$link.TargetPath = 'C:/Program Files/BitBurn/bitburn.exe'"));
+        assert!(script.contains("$link.Arguments = '--sender-context=standard --context-wipe'"));
+        assert!(script.contains("$link.IconLocation = 'C:/Program Files/BitBurn/bitburn.exe,0'"));
+        assert!(read_shortcut_script(Path::new("C:/x.lnk")).contains("Write-Output $link.TargetPath"));
+    }
+
+    #[test]
+    fn shortcut_arguments_record_an_unelevated_sender() {
+        let argv: Vec<String> = SHORTCUT_ARGUMENTS.split(' ').map(str::to_string).collect();
+        assert_eq!(
+            crate::platform::elevation::LaunchContext::from_argv(&argv),
+            Some(crate::platform::elevation::LaunchContext { elevated: false, uid: None })
+        );
+    }
+
+    #[test]
+    fn moved_executable_makes_shortcut_stale() {
+        let exe = Path::new("C:\\Program Files\\BitBurn\\bitburn.exe");
+        assert_eq!(
+            classify_shortcut(
+                Some("c:\\program files\\bitburn\\BitBurn.exe\r\n--sender-context=standard --context-wipe\r\n"),
+                exe
+            ),
+            ShortcutStatus::Current
+        );
+        assert!(
+            matches!(
+                classify_shortcut(Some("C:\\Program Files\\BitBurn\\bitburn.exe\r\n--context-wipe\r\n"), exe),
+                ShortcutStatus::Stale { .. }
+            ),
+            "shortcuts from before the sender context was recorded are recreated"
+        );
+        assert_eq!(
+            classify_shortcut(Some("C:\\Downloads\\bitburn.exe\r\n--context-wipe\r\n"), exe),
+            ShortcutStatus::Stale { target: "C:\\Downloads\\bitburn.exe".to_string() }
+        );
+        assert_eq!(classify_shortcut(None, exe), ShortcutStatus::Missing);
+        assert!(matches!(
+            classify_shortcut(Some("C:\\Program Files\\BitBurn\\bitburn.exe\r\n\r\n"), exe),
+            ShortcutStatus::Stale { .. }
+        ));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn register_sendto_shortcut_is_unavailable_on_non_windows() {
+        let result = tauri::async_runtime::block_on(super::register_sendto_shortcut()).expect("command should return result");
+        assert!(!result.success);
+        assert_eq!(result.message.code, "sendto_unavailable");
+    }
+}
@@ -0,0 +1,299 @@
+//! Volume Shadow Copy pre-flight for free-space wipes. Shadow copies keep old versions of
+//! deleted files that a free-space fill never touches, so they are counted before the wipe
+//! and, when the user opts in, deleted. Enumeration parses `vssadmin` output, which is
+//! English-only; other display languages report the copies as unchecked.
+
+use serde::Serialize;
+#[cfg(windows)]
+use serde_json::json;
+use std::path::Path;
+
+use crate::error::BitBurnError;
+
+/// Shadow copies found on the volume about to be wiped, sent as `free_space_preflight`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ShadowCopyInfo {
+    /// Drive the shadow copies belong to, e.g. `C:`; empty on non-Windows builds.
+    pub volume: String,
+    pub count: usize,
+    /// Storage used by all shadow copies of the volume, when `vssadmin` reports it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_bytes: Option<u64>,
+    /// Why enumeration failed; listing shadow copies usually needs administrator rights.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What the wipe did about shadow copies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShadowCopyAction {
+    /// The volume has no shadow copies.
+    NoneFound,
+    /// Shadow copies exist and were left in place.
+    Kept,
+    Deleted,
+    /// Shadow copies could not be enumerated.
+    Unchecked,
+}
+
+/// Shadow-copy section of a free-space wipe report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShadowCopyReport {
+    pub present: bool,
+    pub count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_bytes: Option<u64>,
+    pub action: ShadowCopyAction,
+}
+
+impl ShadowCopyReport {
+    pub fn new(info: &ShadowCopyInfo, action: ShadowCopyAction) -> Self {
+        ShadowCopyReport {
+            present: info.count > 0,
+            count: info.count,
+            used_bytes: info.used_bytes,
+            action,
+        }
+    }
+}
+
+/// Decide what to do about the shadow copies in `info` before the fill starts.
+/// Deletion needs an elevated process, so opting in without one fails up front
+/// instead of after the drive has been filled.
+pub fn plan_shadow_copies(
+    info: &ShadowCopyInfo,
+    delete_requested: bool,
+    elevated: bool,
+) -> Result<ShadowCopyAction, BitBurnError> {
+    if delete_requested && !elevated && (info.count > 0 || info.error.is_some()) {
+        return Err(BitBurnError::ElevationRequired {
+            reason: format!("deleting shadow copies of {}", info.volume),
+        });
+    }
+    Ok(match (info.error.is_some(), info.count, delete_requested) {
+        (true, _, _) => ShadowCopyAction::Unchecked,
+        (false, 0, _) => ShadowCopyAction::NoneFound,
+        (false, _, false) => ShadowCopyAction::Kept,
+        (false, _, true) => ShadowCopyAction::Deleted,
+    })
+}
+
+/// Drive letter of `path` as `vssadmin` expects it, e.g. `C:` for `C:\`.
+#[cfg(any(windows, test))]
+pub fn volume_of(path: &Path) -> Option<String> {
+    let text = path.to_string_lossy();
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), Some(':')) if letter.is_ascii_alphabetic() => Some(format!("{}:", letter.to_ascii_uppercase())),
+        _ => None,
+    }
+}
+
+/// Count the shadow copies of `volume` in `vssadmin list shadows` output.
+#[cfg(any(windows, test))]
+pub fn parse_shadow_list(output: &str, volume: &str) -> usize {
+    let prefix = format!("({})", volume.to_ascii_uppercase());
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Original Volume:"))
+        .filter(|original| original.trim().to_ascii_uppercase().starts_with(&prefix))
+        .count()
+}
+
+/// Sum the "Used Shadow Copy Storage space" lines of `vssadmin list shadowstorage` output.
+#[cfg(any(windows, test))]
+pub fn parse_used_storage(output: &str) -> Option<u64> {
+    let sizes: Vec<u64> = output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Used Shadow Copy Storage space:"))
+        .filter_map(parse_size)
+        .collect();
+    if sizes.is_empty() {
+        None
+    } else {
+        Some(sizes.iter().sum())
+    }
+}
+
+/// Parse sizes such as `1.23 GB (2%)` or `4,096 bytes`.
+#[cfg(any(windows, test))]
+fn parse_size(text: &str) -> Option<u64> {
+    let mut parts = text.split_whitespace();
+    let number: f64 = parts.next()?.replace(',', "").parse().ok()?;
+    let multiplier = match parts.next()?.to_ascii_uppercase().as_str() {
+        "B" | "BYTES" => 1u64,
+        "KB" => 1 << 10,
+        "MB" => 1 << 20,
+        "GB" => 1 << 30,
+        "TB" => 1 << 40,
+        "PB" => 1 << 50,
+        _ => return None,
+    };
+    Some((number * multiplier as f64).round() as u64)
+}
+
+#[cfg(windows)]
+fn run_vssadmin(args: &[&str]) -> Result<String, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = std::process::Command::new("vssadmin.exe")
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("failed to start vssadmin: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if output.status.success() {
+        Ok(stdout)
+    } else {
+        // vssadmin prints its errors to stdout.
+        Err(stdout.lines().find(|line| line.starts_with("Error")).unwrap_or("vssadmin failed").trim().to_string())
+    }
+}
+
+/// Enumerate the shadow copies of the volume containing `path`.
+#[cfg(windows)]
+pub fn inspect_volume(path: &Path) -> ShadowCopyInfo {
+    let Some(volume) = volume_of(path) else {
+        return ShadowCopyInfo::default();
+    };
+    let mut info = ShadowCopyInfo {
+        volume: volume.clone(),
+        ..Default::default()
+    };
+    let for_arg = format!("/for={}", volume);
+    match run_vssadmin(&["list", "shadows", &for_arg]) {
+        Ok(output) => info.count = parse_shadow_list(&output, &volume),
+        Err(message) => info.error = Some(message),
+    }
+    if info.count > 0 {
+        info.used_bytes = run_vssadmin(&["list", "shadowstorage", &for_arg])
+            .ok()
+            .and_then(|output| parse_used_storage(&output));
+    }
+    crate::log_event(
+        "shadow_copy_preflight",
+        json!({"volume": info.volume, "count": info.count, "used_bytes": info.used_bytes, "error": info.error}),
+    );
+    info
+}
+
+#[cfg(not(windows))]
+pub fn inspect_volume(_path: &Path) -> ShadowCopyInfo {
+    ShadowCopyInfo::default()
+}
+
+/// Delete every shadow copy of `volume`. Requires an elevated process.
+#[cfg(windows)]
+pub fn delete_shadow_copies(volume: &str) -> Result<(), BitBurnError> {
+    run_vssadmin(&["delete", "shadows", &format!("/for={}", volume), "/all", "/quiet"])
+        .map_err(|message| BitBurnError::ShadowCopy { message })?;
+    crate::log_event("shadow_copies_deleted", json!({"volume": volume}));
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn delete_shadow_copies(_volume: &str) -> Result<(), BitBurnError> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIST_SHADOWS: &str = r"vssadmin 1.1 - Volume Shadow Copy Service administrative command-line tool
+(C) Copyright 2001-2013 Microsoft Corp.
+
+Contents of shadow copy set ID: {b5946137-7b9f-4925-af80-51abd60b20d5}
+   Contained 1 shadow copies at creation time: 2/5/2024 10:07:25 AM
+      Shadow Copy ID: {a6e51a4b-7b36-4c11-8b5e-7f95b5cd1c2d}
+         Original Volume: (C:)\\?\Volume{0f3c1c1e-0000-0000-0000-100000000000}\
+         Shadow Copy Volume: \\?\GLOBALROOT\Device\HarddiskVolumeShadowCopy1
+         Originating Machine: DESKTOP
+         Type: ClientAccessibleWriters
+
+Contents of shadow copy set ID: {c1d2e3f4-0000-0000-0000-000000000000}
+   Contained 1 shadow copies at creation time: 3/5/2024 9:00:00 AM
+      Shadow Copy ID: {d4e5f6a7-0000-0000-0000-000000000000}
+         Original Volume: (C:)\\?\Volume{0f3c1c1e-0000-0000-0000-100000000000}\
+         Shadow Copy Volume: \\?\GLOBALROOT\Device\HarddiskVolumeShadowCopy2
+
+Contents of shadow copy set ID: {e5f6a7b8-0000-0000-0000-000000000000}
+   Contained 1 shadow copies at creation time: 3/5/2024 9:00:00 AM
+      Shadow Copy ID: {f6a7b8c9-0000-0000-0000-000000000000}
+         Original Volume: (D:)\\?\Volume{1a2b3c4d-0000-0000-0000-100000000000}\
+";
+
+    const LIST_STORAGE: &str = r"vssadmin 1.1 - Volume Shadow Copy Service administrative command-line tool
+
+Shadow Copy Storage association
+   For volume: (C:)\\?\Volume{0f3c1c1e-0000-0000-0000-100000000000}\
+   Shadow Copy Storage volume: (C:)\\?\Volume{0f3c1c1e-0000-0000-0000-100000000000}\
+   Used Shadow Copy Storage space: 1.5 GB (2%)
+   Allocated Shadow Copy Storage space: 2 GB (3%)
+   Maximum Shadow Copy Storage space: 10 GB (12%)
+";
+
+    fn info(count: usize, error: Option<&str>) -> ShadowCopyInfo {
+        ShadowCopyInfo {
+            volume: "C:".to_string(),
+            count,
+            used_bytes: None,
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn shadow_list_counts_only_the_target_volume() {
+        assert_eq!(parse_shadow_list(LIST_SHADOWS, "C:"), 2);
+        assert_eq!(parse_shadow_list(LIST_SHADOWS, "d:"), 1);
+        assert_eq!(parse_shadow_list(LIST_SHADOWS, "E:"), 0);
+        assert_eq!(parse_shadow_list("No items found that satisfy the query.\r\n", "C:"), 0);
+    }
+
+    #[test]
+    fn used_storage_parses_units_and_ignores_allocated_space() {
+        assert_eq!(parse_used_storage(LIST_STORAGE), Some(3 * (1 << 29)));
+        assert_eq!(parse_used_storage("   Used Shadow Copy Storage space: 4,096 bytes\r\n"), Some(4096));
+        assert_eq!(parse_used_storage("No items found that satisfy the query."), None);
+    }
+
+    #[test]
+    fn volume_is_the_drive_letter() {
+        assert_eq!(volume_of(Path::new("c:\\")), Some("C:".to_string()));
+        assert_eq!(volume_of(Path::new("D:\\data")), Some("D:".to_string()));
+        assert_eq!(volume_of(Path::new("/mnt/data")), None);
+    }
+
+    #[test]
+    fn plan_keeps_or_deletes_only_when_asked() {
+        assert_eq!(plan_shadow_copies(&info(0, None), false, false), Ok(ShadowCopyAction::NoneFound));
+        assert_eq!(plan_shadow_copies(&info(0, None), true, false), Ok(ShadowCopyAction::NoneFound));
+        assert_eq!(plan_shadow_copies(&info(2, None), false, false), Ok(ShadowCopyAction::Kept));
+        assert_eq!(plan_shadow_copies(&info(2, None), true, true), Ok(ShadowCopyAction::Deleted));
+        assert_eq!(
+            plan_shadow_copies(&info(0, Some("Error: access denied")), false, false),
+            Ok(ShadowCopyAction::Unchecked)
+        );
+    }
+
+    #[test]
+    fn deleting_without_elevation_is_refused_before_the_wipe() {
+        for unelevated in [info(2, None), info(0, Some("Error: access denied"))] {
+            assert!(matches!(
+                plan_shadow_copies(&unelevated, true, false),
+                Err(BitBurnError::ElevationRequired { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn report_states_presence_and_action() {
+        let report = ShadowCopyReport::new(&info(2, None), ShadowCopyAction::Kept);
+        assert_eq!(
+            serde_json::to_value(report).unwrap(),
+            serde_json::json!({"present": true, "count": 2, "action": "kept"})
+        );
+    }
+}
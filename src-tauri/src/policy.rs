@@ -0,0 +1,370 @@
+//! Machine-wide policy for locked-down installs, e.g. technician workstations that may only wipe
+//! the free space of removable drives. An administrator places `policy.json` in a location only
+//! they can write; BitBurn reads it at startup and again every minute, and never writes it.
+//! A policy file that exists but cannot be read or parsed blocks every destructive command.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::capabilities::{self, DriveMedia};
+use crate::diagnostics;
+use crate::error::BitBurnError;
+use crate::flash_media;
+use crate::settings::SettingsStore;
+use crate::{log_event, WipeAlgorithm};
+
+const POLICY_FILE_NAME: &str = "policy.json";
+
+/// How often the policy file is read again.
+const REVALIDATE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Names `disabled_commands` accepts for BitBurn's destructive commands. File wipes started from
+/// the context menu, manifests, presets and retention rules all count as `wipe_files`.
+pub const WIPE_FILES: &str = "wipe_files";
+pub const WIPE_FREE_SPACE: &str = "wipe_free_space";
+pub const WIPE_FILE_RANGE: &str = "wipe_file_range";
+
+/// What the administrator allows. Every field is optional; an empty policy allows everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    pub disabled_commands: Vec<String>,
+    /// Algorithms that may be used; `None` allows all of them.
+    pub allowed_algorithms: Option<Vec<WipeAlgorithm>>,
+    pub min_passes: Option<u32>,
+    /// Only files and free space on removable volumes may be wiped.
+    pub removable_targets_only: bool,
+    /// Settings fields, as named in the settings file, the user may not change.
+    pub locked_settings: Vec<String>,
+}
+
+fn blocked(rule: &str, detail: String) -> BitBurnError {
+    BitBurnError::BlockedByPolicy { rule: rule.to_string(), detail }
+}
+
+impl Policy {
+    pub fn check_command(&self, command: &str) -> Result<(), BitBurnError> {
+        if self.disabled_commands.iter().any(|disabled| disabled == command) {
+            return Err(blocked("disabled_commands", format!("{} is disabled", command)));
+        }
+        Ok(())
+    }
+
+    pub fn check_algorithm(&self, algorithm: &WipeAlgorithm, passes: u32) -> Result<(), BitBurnError> {
+        if let Some(allowed) = &self.allowed_algorithms {
+            if !allowed.contains(algorithm) {
+                return Err(blocked("allowed_algorithms", format!("{} is not allowed", algorithm.display_name())));
+            }
+        }
+        match self.min_passes {
+            Some(min) if passes < min => {
+                Err(blocked("min_passes", format!("{} passes requested, at least {} required", passes, min)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Refuse the first of `paths` that `is_removable` says is not on a removable volume.
+    pub fn check_targets<F>(&self, paths: &[String], mut is_removable: F) -> Result<(), BitBurnError>
+    where
+        F: FnMut(&Path) -> bool,
+    {
+        if !self.removable_targets_only {
+            return Ok(());
+        }
+        match paths.iter().find(|path| !is_removable(Path::new(path))) {
+            Some(path) => Err(blocked("removable_targets_only", format!("{} is not on a removable volume", path))),
+            None => Ok(()),
+        }
+    }
+
+    /// `preferred` if the policy allows it, otherwise the first allowed algorithm. Background
+    /// wipes use this, so a user default the policy forbids cannot stop them from running.
+    pub fn default_algorithm(&self, preferred: WipeAlgorithm) -> WipeAlgorithm {
+        match &self.allowed_algorithms {
+            Some(allowed) if !allowed.contains(&preferred) => allowed.first().cloned().unwrap_or(preferred),
+            _ => preferred,
+        }
+    }
+}
+
+/// Whether `path` is on a removable volume, asking the storage stack and falling back to the
+/// drive list. Unknown volumes are treated as fixed.
+pub fn is_removable(path: &Path) -> bool {
+    if flash_media::device_descriptor(path).is_some_and(|device| device.removable) {
+        return true;
+    }
+    let drives = capabilities::list_drives().unwrap_or_default();
+    capabilities::drive_holding(&drives, &path.to_string_lossy()).is_some_and(|drive| drive.media == DriveMedia::Removable)
+}
+
+/// Machine-wide location of the policy file.
+pub fn policy_path() -> Option<PathBuf> {
+    #[cfg(windows)]
+    {
+        let program_data = std::env::var_os("ProgramData").unwrap_or_else(|| "C:\\ProgramData".into());
+        Some(PathBuf::from(program_data).join("BitBurn").join(POLICY_FILE_NAME))
+    }
+    #[cfg(target_os = "linux")]
+    {
+        Some(PathBuf::from("/etc/bitburn").join(POLICY_FILE_NAME))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Some(PathBuf::from("/Library/Application Support/BitBurn").join(POLICY_FILE_NAME))
+    }
+    #[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// The policy in force, or why the policy file could not be used.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectivePolicy {
+    /// Where the policy was read from; `None` when no policy file exists.
+    pub source: Option<String>,
+    pub policy: Policy,
+    /// The policy file exists but is unusable, so destructive commands are blocked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn read_policy(path: &Path) -> EffectivePolicy {
+    let source = Some(path.to_string_lossy().to_string());
+    let parsed = fs::read_to_string(path).map_err(|e| (e.kind() == io::ErrorKind::NotFound, e.to_string())).and_then(
+        |contents| serde_json::from_str::<Policy>(&contents).map_err(|e| (false, e.to_string())),
+    );
+    match parsed {
+        Ok(policy) => EffectivePolicy { source, policy, error: None },
+        Err((true, _)) => EffectivePolicy { source: None, policy: Policy::default(), error: None },
+        Err((false, message)) => EffectivePolicy { source, policy: Policy::default(), error: Some(message) },
+    }
+}
+
+/// Managed state: the policy as last read.
+pub struct PolicyStore {
+    path: Option<PathBuf>,
+    current: Mutex<EffectivePolicy>,
+}
+
+impl PolicyStore {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let current = match &path {
+            Some(path) => read_policy(path),
+            None => EffectivePolicy { source: None, policy: Policy::default(), error: None },
+        };
+        PolicyStore { path, current: Mutex::new(current) }
+    }
+
+    /// Read the policy file again. Returns whether anything changed.
+    pub fn reload(&self) -> bool {
+        let Some(path) = &self.path else {
+            return false;
+        };
+        let fresh = read_policy(path);
+        let mut current = self.lock();
+        if *current == fresh {
+            return false;
+        }
+        *current = fresh;
+        true
+    }
+
+    pub fn effective(&self) -> EffectivePolicy {
+        self.lock().clone()
+    }
+
+    /// The policy to enforce. An unusable policy file blocks instead of allowing everything.
+    pub fn policy(&self) -> Result<Policy, BitBurnError> {
+        let current = self.lock();
+        match &current.error {
+            Some(message) => Err(blocked("invalid_policy", message.clone())),
+            None => Ok(current.policy.clone()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, EffectivePolicy> {
+        self.current.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The policy to enforce in the running app; everything is allowed without a policy store.
+pub fn current<R: Runtime>(app: &AppHandle<R>) -> Result<Policy, BitBurnError> {
+    app.try_state::<PolicyStore>().map_or(Ok(Policy::default()), |store| store.policy())
+}
+
+/// Check a destructive command against the policy before it does anything.
+pub fn check_wipe<R: Runtime>(
+    app: &AppHandle<R>,
+    command: &str,
+    algorithm: &WipeAlgorithm,
+    passes: u32,
+    paths: &[String],
+) -> Result<(), BitBurnError> {
+    let policy = current(app)?;
+    policy.check_command(command)?;
+    policy.check_algorithm(algorithm, passes)?;
+    policy.check_targets(paths, is_removable).inspect_err(|e| {
+        log_event("policy_blocked", json!({"command": command, "message": e.to_string()}));
+    })
+}
+
+fn apply_locks<R: Runtime>(app: &AppHandle<R>, effective: &EffectivePolicy) {
+    if let Some(settings) = app.try_state::<SettingsStore>() {
+        settings.set_locked(effective.policy.locked_settings.clone());
+    }
+}
+
+/// Build the managed policy store and apply its setting locks.
+pub fn init_policy<R: Runtime>(app: &AppHandle<R>) -> PolicyStore {
+    let store = PolicyStore::load(policy_path());
+    let effective = store.effective();
+    if let Some(error) = &effective.error {
+        log_event("policy_invalid", json!({"source": effective.source, "message": error}));
+    } else if effective.source.is_some() {
+        log_event("policy_loaded", json!({"source": effective.source}));
+    }
+    apply_locks(app, &effective);
+    store
+}
+
+/// Re-read the policy file every minute, so an administrator's change applies without a restart.
+pub fn spawn_revalidation<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(REVALIDATE_INTERVAL);
+        let Some(store) = app.try_state::<PolicyStore>() else {
+            continue;
+        };
+        if store.reload() {
+            let effective = store.effective();
+            log_event("policy_reloaded", json!({"source": effective.source, "error": effective.error}));
+            apply_locks(&app, &effective);
+            diagnostics::emit(&app, "policy_changed", effective);
+        }
+    });
+}
+
+/// The policy in force, so the frontend can grey out what it blocks.
+#[tauri::command]
+pub async fn get_effective_policy(store: State<'_, PolicyStore>) -> Result<EffectivePolicy, BitBurnError> {
+    Ok(store.effective())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    fn rule_of(result: Result<(), BitBurnError>) -> String {
+        match result {
+            Err(BitBurnError::BlockedByPolicy { rule, .. }) => rule,
+            other => panic!("expected a policy block, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn disabled_commands_are_blocked() {
+        let policy = Policy { disabled_commands: vec![WIPE_FILES.to_string(), WIPE_FILE_RANGE.to_string()], ..Default::default() };
+        assert_eq!(rule_of(policy.check_command(WIPE_FILES)), "disabled_commands");
+        assert_eq!(rule_of(policy.check_command(WIPE_FILE_RANGE)), "disabled_commands");
+        assert!(policy.check_command(WIPE_FREE_SPACE).is_ok());
+        assert!(Policy::default().check_command(WIPE_FILES).is_ok());
+    }
+
+    #[test]
+    fn algorithms_and_pass_counts_are_restricted() {
+        let policy = Policy {
+            allowed_algorithms: Some(vec![WipeAlgorithm::NistPurge, WipeAlgorithm::Random]),
+            min_passes: Some(3),
+            ..Default::default()
+        };
+        assert!(policy.check_algorithm(&WipeAlgorithm::NistPurge, 3).is_ok());
+        assert!(policy.check_algorithm(&WipeAlgorithm::Random, 7).is_ok());
+        assert_eq!(rule_of(policy.check_algorithm(&WipeAlgorithm::Gutmann, 35)), "allowed_algorithms");
+        assert_eq!(rule_of(policy.check_algorithm(&WipeAlgorithm::Random, 2)), "min_passes");
+
+        let min_only = Policy { min_passes: Some(3), ..Default::default() };
+        assert_eq!(rule_of(min_only.check_algorithm(&WipeAlgorithm::NistClear, 1)), "min_passes");
+        assert!(min_only.check_algorithm(&WipeAlgorithm::Gutmann, 35).is_ok());
+    }
+
+    #[test]
+    fn targets_must_be_on_removable_volumes() {
+        let policy = Policy { removable_targets_only: true, ..Default::default() };
+        let removable = |path: &Path| path.to_string_lossy().starts_with("E:\\");
+        let paths = |list: &[&str]| list.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        assert!(policy.check_targets(&paths(&["E:\\", "E:\\photos"]), removable).is_ok());
+        let refused = policy.check_targets(&paths(&["E:\\photos", "C:\\Users\\me\\a.txt"]), removable);
+        assert_eq!(
+            refused,
+            Err(BitBurnError::BlockedByPolicy {
+                rule: "removable_targets_only".into(),
+                detail: "C:\\Users\\me\\a.txt is not on a removable volume".into(),
+            })
+        );
+        assert!(Policy::default().check_targets(&paths(&["C:\\"]), removable).is_ok());
+    }
+
+    #[test]
+    fn policy_takes_precedence_over_user_settings() {
+        let policy = Policy {
+            allowed_algorithms: Some(vec![WipeAlgorithm::NistClear]),
+            locked_settings: vec!["default_algorithm".to_string(), "sign_reports".to_string()],
+            ..Default::default()
+        };
+        let settings = SettingsStore::in_memory(Settings { default_algorithm: Some(WipeAlgorithm::Gutmann), ..Default::default() });
+        settings.set_locked(policy.locked_settings.clone());
+
+        // The user's default is forbidden, so background wipes fall back to an allowed algorithm.
+        assert_eq!(policy.default_algorithm(settings.get().default_algorithm()), WipeAlgorithm::NistClear);
+        assert_eq!(policy.default_algorithm(WipeAlgorithm::NistClear), WipeAlgorithm::NistClear);
+
+        let refused = settings.update(|s| s.sign_reports = true);
+        assert_eq!(
+            refused.map(|_| ()),
+            Err(BitBurnError::BlockedByPolicy { rule: "locked_settings".into(), detail: "sign_reports is locked".into() })
+        );
+        assert!(!settings.get().sign_reports);
+        // Unlocked fields still change, and a locked one may be written back unchanged.
+        settings.update(|s| s.obfuscate_file_names = true).expect("not locked");
+        settings.update(|s| s.sign_reports = false).expect("unchanged");
+        settings.set_locked(Vec::new());
+        settings.update(|s| s.sign_reports = true).expect("lock lifted");
+    }
+
+    #[test]
+    fn policy_file_is_read_revalidated_and_fails_closed() {
+        let path = create_test_dir().unwrap().join(POLICY_FILE_NAME);
+        let missing = PolicyStore::load(Some(path.clone()));
+        assert_eq!(missing.effective().source, None);
+        assert_eq!(missing.policy(), Ok(Policy::default()));
+
+        fs::write(&path, r#"{"disabled_commands": ["wipe_files"], "allowed_algorithms": ["NistPurge"]}"#).unwrap();
+        assert!(missing.reload());
+        assert!(!missing.reload(), "an unchanged file is not a change");
+        let policy = missing.policy().unwrap();
+        assert_eq!(policy.disabled_commands, vec![WIPE_FILES]);
+        assert_eq!(policy.allowed_algorithms, Some(vec![WipeAlgorithm::NistPurge]));
+
+        // A typo must not quietly lift the restrictions.
+        fs::write(&path, r#"{"disabled_comands": ["wipe_files"]}"#).unwrap();
+        assert!(missing.reload());
+        assert_eq!(missing.policy().map(|_| ()).map_err(|e| e.code()), Err("blocked_by_policy"));
+        assert!(missing.effective().error.is_some());
+
+        fs::remove_file(&path).unwrap();
+        assert!(missing.reload());
+        assert_eq!(missing.policy(), Ok(Policy::default()));
+        cleanup_test_dir(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,639 @@
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{Manager, Runtime};
+use walkdir::WalkDir;
+
+use crate::consent;
+use crate::error::BitBurnError;
+use crate::lock_scan::LockPolicy;
+use crate::messages;
+use crate::operations::OperationRegistry;
+use crate::platform::elevation::is_elevated;
+use crate::{log_event, start_file_wipe, BatchOptions, WipeAlgorithm, WipeResult};
+
+/// Platform whose well-known locations a preset resolves to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresetPlatform {
+    Windows,
+    MacOs,
+    Linux,
+}
+
+impl PresetPlatform {
+    pub fn current() -> Self {
+        if cfg!(windows) {
+            PresetPlatform::Windows
+        } else if cfg!(target_os = "macos") {
+            PresetPlatform::MacOs
+        } else {
+            PresetPlatform::Linux
+        }
+    }
+}
+
+/// Per-user locations presets are resolved against. Built from the process
+/// environment at runtime and fabricated by tests.
+#[derive(Debug, Clone)]
+pub struct PresetEnvironment {
+    pub platform: PresetPlatform,
+    /// `%USERPROFILE%` on Windows, `$HOME` elsewhere.
+    pub profile: Option<PathBuf>,
+    pub temp: Option<PathBuf>,
+    /// `%LOCALAPPDATA%` (Windows only).
+    pub local_app_data: Option<PathBuf>,
+    /// `~/Library/Caches` on macOS, `$XDG_CACHE_HOME` or `~/.cache` on Linux.
+    pub cache_home: Option<PathBuf>,
+    /// `%SystemRoot%` (Windows only).
+    pub system_root: Option<PathBuf>,
+    pub elevated: bool,
+}
+
+impl PresetEnvironment {
+    pub fn current() -> Self {
+        let platform = PresetPlatform::current();
+        let env_path = |name: &str| {
+            std::env::var_os(name)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        let profile = match platform {
+            PresetPlatform::Windows => env_path("USERPROFILE"),
+            _ => env_path("HOME"),
+        };
+        let cache_home = match platform {
+            PresetPlatform::Windows => None,
+            PresetPlatform::MacOs => profile.as_ref().map(|home| home.join("Library").join("Caches")),
+            PresetPlatform::Linux => {
+                env_path("XDG_CACHE_HOME").or_else(|| profile.as_ref().map(|home| home.join(".cache")))
+            }
+        };
+
+        PresetEnvironment {
+            platform,
+            profile,
+            temp: Some(std::env::temp_dir()),
+            local_app_data: env_path("LOCALAPPDATA"),
+            cache_home,
+            system_root: env_path("SystemRoot"),
+            elevated: is_elevated(),
+        }
+    }
+
+    /// Whether `path` belongs to the current user: same owner as the home
+    /// directory (or the effective uid) on Unix, inside the user profile on Windows.
+    fn belongs_to_user(&self, path: &Path, metadata: &fs::Metadata) -> bool {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let _ = path;
+            let user = self
+                .profile
+                .as_ref()
+                .and_then(|home| fs::metadata(home).ok())
+                .map(|home| home.uid())
+                .unwrap_or_else(|| unsafe { libc::geteuid() });
+            metadata.uid() == user
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = metadata;
+            self.profile
+                .as_ref()
+                .is_some_and(|profile| path.starts_with(profile))
+        }
+    }
+}
+
+/// A curated cleanup target with its resolved contents.
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupPreset {
+    pub id: String,
+    pub label: String,
+    pub description: String,
+    /// Entries that `wipe_preset` will feed to the batch pipeline.
+    pub paths: Vec<String>,
+    pub size_bytes: u64,
+    pub file_count: u64,
+    pub requires_elevation: bool,
+    /// False when the preset needs elevation the process does not have; `paths` is empty then.
+    pub available: bool,
+}
+
+struct PresetSpec {
+    id: &'static str,
+    label: &'static str,
+    description: &'static str,
+}
+
+const PRESETS: &[PresetSpec] = &[
+    PresetSpec {
+        id: "user_temp",
+        label: "Temporary files",
+        description: "Your temporary folder",
+    },
+    PresetSpec {
+        id: "system_temp",
+        label: "Windows temporary files",
+        description: "C:\\Windows\\Temp (requires administrator rights)",
+    },
+    PresetSpec {
+        id: "edge_cache",
+        label: "Microsoft Edge cache",
+        description: "Cached web content for every Edge profile",
+    },
+    PresetSpec {
+        id: "chrome_cache",
+        label: "Google Chrome cache",
+        description: "Cached web content for every Chrome profile",
+    },
+    PresetSpec {
+        id: "firefox_cache",
+        label: "Firefox cache",
+        description: "Cached web content for every Firefox profile",
+    },
+    PresetSpec {
+        id: "thumbnail_cache",
+        label: "Thumbnail cache",
+        description: "Image previews kept by the file manager",
+    },
+];
+
+/// Cache folders inside each Chromium profile (`Default`, `Profile 1`, ...).
+const CHROMIUM_CACHE_DIRS: [&str; 3] = ["Cache", "Code Cache", "GPUCache"];
+
+fn is_chromium_profile(name: &str) -> bool {
+    name == "Default" || name.starts_with("Profile ")
+}
+
+/// Existing, non-symlink directory owned by the current user.
+fn user_dir(env: &PresetEnvironment, path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|metadata| metadata.is_dir() && env.belongs_to_user(path, &metadata))
+        .unwrap_or(false)
+}
+
+/// Direct children of `dir` accepted by `filter`, skipping symlinks and
+/// entries owned by other users. Sorted for stable output.
+fn user_children<F>(env: &PresetEnvironment, dir: &Path, filter: F) -> Vec<PathBuf>
+where
+    F: Fn(&str, &fs::Metadata) -> bool,
+{
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut children: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let metadata = fs::symlink_metadata(&path).ok()?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let keep = !metadata.file_type().is_symlink()
+                && env.belongs_to_user(&path, &metadata)
+                && filter(&name, &metadata);
+            keep.then_some(path)
+        })
+        .collect();
+    children.sort();
+    children
+}
+
+fn chromium_caches(env: &PresetEnvironment, user_data: &Path) -> Option<Vec<PathBuf>> {
+    if !user_dir(env, user_data) {
+        return None;
+    }
+
+    let caches: Vec<PathBuf> = user_children(env, user_data, |name, metadata| {
+        metadata.is_dir() && is_chromium_profile(name)
+    })
+    .into_iter()
+    .flat_map(|profile| CHROMIUM_CACHE_DIRS.iter().map(move |dir| profile.join(dir)))
+    .filter(|cache| user_dir(env, cache))
+    .collect();
+
+    (!caches.is_empty()).then_some(caches)
+}
+
+fn firefox_caches(env: &PresetEnvironment, profiles: &Path) -> Option<Vec<PathBuf>> {
+    if !user_dir(env, profiles) {
+        return None;
+    }
+
+    let caches: Vec<PathBuf> = user_children(env, profiles, |_, metadata| metadata.is_dir())
+        .into_iter()
+        .map(|profile| profile.join("cache2"))
+        .filter(|cache| user_dir(env, cache))
+        .collect();
+
+    (!caches.is_empty()).then_some(caches)
+}
+
+/// Contents of `dir`, keeping the folder itself in place.
+fn dir_contents(env: &PresetEnvironment, dir: &Path) -> Option<Vec<PathBuf>> {
+    user_dir(env, dir).then(|| user_children(env, dir, |_, _| true))
+}
+
+/// Entries a preset clears, or `None` when it does not apply to this machine
+/// (wrong platform, browser not installed, location missing).
+fn preset_targets(env: &PresetEnvironment, id: &str) -> Option<Vec<PathBuf>> {
+    let platform = env.platform;
+    let local = env.local_app_data.as_deref();
+    let cache = env.cache_home.as_deref();
+
+    match id {
+        "user_temp" => dir_contents(env, env.temp.as_deref()?),
+        "system_temp" => {
+            if platform != PresetPlatform::Windows {
+                return None;
+            }
+            let temp = env.system_root.as_deref()?.join("Temp");
+            if !temp.is_dir() {
+                return None;
+            }
+            if !env.elevated {
+                return Some(Vec::new());
+            }
+            // Machine-wide folder: ownership is not per-user, elevation is the gate.
+            let mut children: Vec<PathBuf> = fs::read_dir(&temp)
+                .ok()?
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().map(|t| !t.is_symlink()).unwrap_or(false))
+                .map(|entry| entry.path())
+                .collect();
+            children.sort();
+            Some(children)
+        }
+        "edge_cache" => match platform {
+            PresetPlatform::Windows => chromium_caches(env, &local?.join("Microsoft").join("Edge").join("User Data")),
+            PresetPlatform::MacOs => chromium_caches(env, &cache?.join("Microsoft Edge")),
+            PresetPlatform::Linux => chromium_caches(env, &cache?.join("microsoft-edge")),
+        },
+        "chrome_cache" => match platform {
+            PresetPlatform::Windows => chromium_caches(env, &local?.join("Google").join("Chrome").join("User Data")),
+            PresetPlatform::MacOs => chromium_caches(env, &cache?.join("Google").join("Chrome")),
+            PresetPlatform::Linux => chromium_caches(env, &cache?.join("google-chrome")),
+        },
+        "firefox_cache" => match platform {
+            PresetPlatform::Windows => firefox_caches(env, &local?.join("Mozilla").join("Firefox").join("Profiles")),
+            PresetPlatform::MacOs => firefox_caches(env, &cache?.join("Firefox").join("Profiles")),
+            PresetPlatform::Linux => firefox_caches(env, &cache?.join("mozilla").join("firefox")),
+        },
+        "thumbnail_cache" => match platform {
+            PresetPlatform::Windows => {
+                let explorer = local?.join("Microsoft").join("Windows").join("Explorer");
+                user_dir(env, &explorer).then(|| {
+                    user_children(env, &explorer, |name, metadata| {
+                        metadata.is_file()
+                            && name.to_ascii_lowercase().starts_with("thumbcache_")
+                            && name.to_ascii_lowercase().ends_with(".db")
+                    })
+                })
+            }
+            PresetPlatform::Linux => dir_contents(env, &cache?.join("thumbnails")),
+            PresetPlatform::MacOs => None,
+        },
+        _ => None,
+    }
+}
+
+/// Total size and number of regular files below `paths` (symlinks are not followed).
+fn measure(paths: &[PathBuf]) -> (u64, u64) {
+    paths
+        .iter()
+        .flat_map(|path| WalkDir::new(path).into_iter().filter_map(|e| e.ok()))
+        .filter(|entry| entry.file_type().is_file())
+        .fold((0, 0), |(size, count), entry| {
+            let len = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            (size + len, count + 1)
+        })
+}
+
+/// Resolve one preset for `env`, or `None` when it is unknown or not offered here.
+pub fn resolve_preset(env: &PresetEnvironment, id: &str) -> Option<CleanupPreset> {
+    let spec = PRESETS.iter().find(|spec| spec.id == id)?;
+    let targets = preset_targets(env, id)?;
+    let requires_elevation = id == "system_temp";
+    let (size_bytes, file_count) = measure(&targets);
+
+    Some(CleanupPreset {
+        id: spec.id.to_string(),
+        label: spec.label.to_string(),
+        description: spec.description.to_string(),
+        paths: targets
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect(),
+        size_bytes,
+        file_count,
+        requires_elevation,
+        available: !requires_elevation || env.elevated,
+    })
+}
+
+/// Every preset that applies to `env`, in display order.
+pub fn resolve_presets(env: &PresetEnvironment) -> Vec<CleanupPreset> {
+    PRESETS
+        .iter()
+        .filter_map(|spec| resolve_preset(env, spec.id))
+        .collect()
+}
+
+/// List cleanup presets for this machine with their current size and file count.
+#[tauri::command]
+pub async fn list_cleanup_presets() -> Result<Vec<CleanupPreset>, BitBurnError> {
+    spawn_blocking(|| resolve_presets(&PresetEnvironment::current()))
+        .await
+        .map_err(|e| BitBurnError::internal(format!("list_cleanup_presets task join error: {}", e)))
+}
+
+/// Wipe everything a preset currently resolves to, through the same pre-flight checks, volume
+/// scheduling and crash journal as a selection. Files held open by another process (typically a
+/// running browser) are skipped and listed in the report. The locations it resolves to are
+/// confirmed through `consent_token` or a prompt shown now.
+#[tauri::command]
+pub async fn wipe_preset<R: Runtime>(
+    window: tauri::Window<R>,
+    registry: tauri::State<'_, OperationRegistry>,
+    preset_id: String,
+    algorithm: WipeAlgorithm,
+    passes: u32,
+    consent_token: Option<String>,
+) -> Result<WipeResult, BitBurnError> {
+    let lookup_id = preset_id.clone();
+    let preset = spawn_blocking(move || resolve_preset(&PresetEnvironment::current(), &lookup_id))
+        .await
+        .map_err(|e| BitBurnError::internal(format!("wipe_preset task join error: {}", e)))?
+        .ok_or_else(|| BitBurnError::UnknownPreset { preset_id: preset_id.clone() })?;
+    wipe_resolved_preset(window, &registry, preset, algorithm, passes, consent_token).await
+}
+
+async fn wipe_resolved_preset<R: Runtime>(
+    window: tauri::Window<R>,
+    registry: &OperationRegistry,
+    preset: CleanupPreset,
+    algorithm: WipeAlgorithm,
+    passes: u32,
+    consent_token: Option<String>,
+) -> Result<WipeResult, BitBurnError> {
+    if !preset.available {
+        return Err(BitBurnError::ElevationRequired {
+            reason: format!("{} requires administrator rights", preset.label),
+        });
+    }
+
+    log_event(
+        "wipe_preset_start",
+        json!({"preset": preset.id, "entries": preset.paths.len(), "files": preset.file_count, "algorithm": format!("{:?}", algorithm), "passes": passes}),
+    );
+    let prompt = messages::CONFIRM_PRESET_WIPE.render(json!({
+        "files": preset.file_count,
+        "preset": preset.label,
+        "algorithm": format!("{:?}", algorithm),
+        "paths": consent::list_targets(&preset.paths),
+    }));
+    let consent =
+        consent::confirm_targets_async(window.app_handle().clone(), consent_token, prompt.text, preset.paths.clone())
+            .await?;
+    let options = BatchOptions {
+        source: Some(format!("preset:{}", preset.id)),
+        skip_in_use: true,
+        lock_policy: Some(LockPolicy::SkipLocked),
+        consent: Some(consent),
+        ..Default::default()
+    };
+    start_file_wipe(window.app_handle().clone(), window.label().to_string(), registry, preset.paths, passes, algorithm, options)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    fn write(path: &Path, bytes: &[u8]) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, bytes).unwrap();
+    }
+
+    fn environment(platform: PresetPlatform, root: &Path) -> PresetEnvironment {
+        PresetEnvironment {
+            platform,
+            profile: Some(root.to_path_buf()),
+            temp: Some(root.join("Temp")),
+            local_app_data: Some(root.join("AppData").join("Local")),
+            cache_home: Some(root.join(".cache")),
+            system_root: Some(root.join("Windows")),
+            elevated: false,
+        }
+    }
+
+    fn ids(presets: &[CleanupPreset]) -> Vec<&str> {
+        presets.iter().map(|p| p.id.as_str()).collect()
+    }
+
+    #[test]
+    fn windows_browser_caches_cover_each_detected_profile() {
+        let root = create_test_dir().unwrap();
+        let local = root.join("AppData").join("Local");
+        let edge = local.join("Microsoft").join("Edge").join("User Data");
+        write(&edge.join("Default").join("Cache").join("Cache_Data").join("f_000001"), b"12345");
+        write(&edge.join("Profile 2").join("Code Cache").join("js").join("index"), b"123");
+        write(&edge.join("System Profile").join("Cache").join("data_0"), b"ignored");
+        write(&edge.join("Local State"), b"{}");
+        let firefox = local.join("Mozilla").join("Firefox").join("Profiles");
+        write(&firefox.join("abcd.default-release").join("cache2").join("entries").join("E1"), b"xx");
+        fs::create_dir_all(firefox.join("efgh.dev-edition")).unwrap();
+
+        let env = environment(PresetPlatform::Windows, &root);
+        let presets = resolve_presets(&env);
+        assert_eq!(ids(&presets), vec!["edge_cache", "firefox_cache"]);
+
+        let edge_preset = &presets[0];
+        assert_eq!(
+            edge_preset.paths,
+            vec![
+                edge.join("Default").join("Cache").to_string_lossy().to_string(),
+                edge.join("Profile 2").join("Code Cache").to_string_lossy().to_string(),
+            ]
+        );
+        assert_eq!(edge_preset.size_bytes, 8);
+        assert_eq!(edge_preset.file_count, 2);
+
+        let firefox_preset = &presets[1];
+        assert_eq!(
+            firefox_preset.paths,
+            vec![firefox.join("abcd.default-release").join("cache2").to_string_lossy().to_string()]
+        );
+        assert_eq!(firefox_preset.file_count, 1);
+        cleanup_test_dir(root);
+    }
+
+    #[test]
+    fn windows_thumbnail_preset_only_selects_thumbcache_databases() {
+        let root = create_test_dir().unwrap();
+        let explorer = root
+            .join("AppData")
+            .join("Local")
+            .join("Microsoft")
+            .join("Windows")
+            .join("Explorer");
+        write(&explorer.join("thumbcache_256.db"), b"thumb");
+        write(&explorer.join("ThumbCache_idx.db"), b"idx");
+        write(&explorer.join("iconcache_16.db"), b"icon");
+
+        let env = environment(PresetPlatform::Windows, &root);
+        let preset = resolve_preset(&env, "thumbnail_cache").expect("explorer folder exists");
+        assert_eq!(
+            preset.paths,
+            vec![
+                explorer.join("ThumbCache_idx.db").to_string_lossy().to_string(),
+                explorer.join("thumbcache_256.db").to_string_lossy().to_string(),
+            ]
+        );
+        assert_eq!(preset.size_bytes, 8);
+        cleanup_test_dir(root);
+    }
+
+    #[test]
+    fn linux_layout_resolves_xdg_cache_locations() {
+        let root = create_test_dir().unwrap();
+        let cache = root.join(".cache");
+        write(&cache.join("google-chrome").join("Default").join("Cache").join("data_1"), b"abc");
+        write(&cache.join("mozilla").join("firefox").join("x1.default").join("cache2").join("doomed"), b"d");
+        write(&cache.join("thumbnails").join("normal").join("a.png"), b"png");
+        write(&cache.join("thumbnails").join("large").join("b.png"), b"png!");
+
+        let env = environment(PresetPlatform::Linux, &root);
+        let presets = resolve_presets(&env);
+        assert_eq!(ids(&presets), vec!["chrome_cache", "firefox_cache", "thumbnail_cache"]);
+
+        let thumbnails = presets.last().unwrap();
+        assert_eq!(
+            thumbnails.paths,
+            vec![
+                cache.join("thumbnails").join("large").to_string_lossy().to_string(),
+                cache.join("thumbnails").join("normal").to_string_lossy().to_string(),
+            ]
+        );
+        assert_eq!((thumbnails.size_bytes, thumbnails.file_count), (7, 2));
+        cleanup_test_dir(root);
+    }
+
+    #[test]
+    fn temp_preset_lists_contents_but_keeps_the_folder() {
+        let root = create_test_dir().unwrap();
+        write(&root.join("Temp").join("setup.log"), b"log");
+        write(&root.join("Temp").join("extract").join("payload.bin"), b"payload");
+
+        let env = environment(PresetPlatform::Linux, &root);
+        let preset = resolve_preset(&env, "user_temp").expect("temp folder exists");
+        assert_eq!(preset.paths.len(), 2);
+        assert!(!preset.paths.contains(&root.join("Temp").to_string_lossy().to_string()));
+        assert_eq!((preset.size_bytes, preset.file_count), (10, 2));
+        cleanup_test_dir(root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_entries_are_never_resolved() {
+        let root = create_test_dir().unwrap();
+        let outside = root.join("outside");
+        write(&outside.join("precious.txt"), b"keep");
+        fs::create_dir_all(root.join("Temp")).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("Temp").join("link")).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join(".cache")).unwrap();
+
+        let env = environment(PresetPlatform::Linux, &root);
+        let temp = resolve_preset(&env, "user_temp").expect("temp folder exists");
+        assert!(temp.paths.is_empty());
+        assert!(resolve_preset(&env, "thumbnail_cache").is_none());
+        cleanup_test_dir(root);
+    }
+
+    #[test]
+    fn missing_locations_and_unknown_ids_are_not_offered() {
+        let root = create_test_dir().unwrap();
+        let env = environment(PresetPlatform::MacOs, &root);
+        assert!(resolve_presets(&env).is_empty());
+        assert!(resolve_preset(&env, "recycle_bin").is_none());
+        cleanup_test_dir(root);
+    }
+
+    #[test]
+    fn system_temp_is_elevation_aware() {
+        let root = create_test_dir().unwrap();
+        write(&root.join("Windows").join("Temp").join("MpCmdRun.log"), b"defender");
+
+        let mut env = environment(PresetPlatform::Windows, &root);
+        let locked = resolve_preset(&env, "system_temp").expect("Windows\\Temp exists");
+        assert!(locked.requires_elevation);
+        assert!(!locked.available);
+        assert!(locked.paths.is_empty());
+
+        env.elevated = true;
+        let unlocked = resolve_preset(&env, "system_temp").unwrap();
+        assert!(unlocked.available);
+        assert_eq!(unlocked.paths.len(), 1);
+        assert_eq!(unlocked.file_count, 1);
+
+        env.platform = PresetPlatform::Linux;
+        assert!(resolve_preset(&env, "system_temp").is_none());
+        cleanup_test_dir(root);
+    }
+
+    #[test]
+    fn a_preset_wipe_needs_a_consent_to_exactly_the_locations_it_resolved() {
+        use crate::consent::{ConsentOrigin, ConsentStore};
+        use crate::settings::{Settings, SettingsStore};
+        use std::time::SystemTime;
+
+        let root = create_test_dir().unwrap();
+        let cache = root.join(".cache").join("thumbnails");
+        write(&cache.join("normal").join("a.png"), b"thumb");
+        let preset = CleanupPreset {
+            id: "thumbnail_cache".to_string(),
+            label: "Thumbnail cache".to_string(),
+            description: String::new(),
+            paths: vec![cache.to_string_lossy().to_string()],
+            size_bytes: 5,
+            file_count: 1,
+            requires_elevation: false,
+            available: true,
+        };
+
+        let app = tauri::test::mock_app();
+        app.manage(OperationRegistry::default());
+        app.manage(ConsentStore::default());
+        app.manage(SettingsStore::in_memory(Settings { confirm_before_wipe: true, ..Default::default() }));
+        let window = tauri::WebviewWindowBuilder::new(&app, "main", Default::default()).build().unwrap();
+        let registry = app.state::<OperationRegistry>();
+        let wipe = |token: &str| {
+            tauri::async_runtime::block_on(wipe_resolved_preset(
+                window.as_ref().window(),
+                &registry,
+                preset.clone(),
+                WipeAlgorithm::NistClear,
+                1,
+                Some(token.to_string()),
+            ))
+        };
+        let store = app.state::<ConsentStore>();
+
+        let fewer = vec![cache.join("normal").to_string_lossy().to_string()];
+        let elsewhere = store.record("Wipe 1 item?", &fewer, true, ConsentOrigin::Dialog, SystemTime::now());
+        let refused = wipe(&elsewhere.token);
+        assert!(matches!(refused, Err(BitBurnError::ConfirmationRequired { reason }) if reason.contains("selection")));
+        assert!(cache.join("normal").join("a.png").exists(), "nothing is wiped without consent");
+
+        let consent = store.record("Wipe 1 item?", &preset.paths, true, ConsentOrigin::Dialog, SystemTime::now());
+        let result = wipe(&consent.token).unwrap();
+        assert!(result.success, "{:?}", result.message);
+        assert_eq!(result.report.and_then(|report| report.consent), Some(consent));
+        assert!(!cache.exists());
+        cleanup_test_dir(root);
+    }
+}
@@ -0,0 +1,326 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::diagnostics::OperationDiagnostics;
+use crate::WipeProgress;
+
+/// `wipe_progress` events per second per operation when the settings do not say otherwise.
+pub const DEFAULT_EVENTS_PER_SECOND: u32 = 20;
+
+pub(crate) const PROGRESS_EVENT: &str = "wipe_progress";
+
+/// Payload of a coalesced `wipe_progress` event: the latest snapshot plus what changed since the last one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoalescedProgress {
+    pub operation_id: String,
+    #[serde(flatten)]
+    pub progress: WipeProgress,
+    /// Files finished since the previous `wipe_progress` event.
+    pub files_completed: u64,
+    /// Progress updates folded into this event.
+    pub coalesced_updates: u64,
+}
+
+type Sink = Box<dyn Fn(&str, Value) + Send + Sync>;
+
+#[derive(Default)]
+struct Slot {
+    latest: Option<WipeProgress>,
+    files_completed: u64,
+    coalesced_updates: u64,
+    dirty: bool,
+    finished: bool,
+}
+
+struct Shared {
+    operation_id: String,
+    slot: Mutex<Slot>,
+    wake: Condvar,
+    sink: Sink,
+    diagnostics: OperationDiagnostics,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Slot> {
+        self.slot.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Emit the pending snapshot, if any. Callers hold the slot lock so events leave in order.
+    fn flush(&self, slot: &mut Slot) {
+        if !slot.dirty {
+            return;
+        }
+        slot.dirty = false;
+        let Some(progress) = slot.latest.clone() else {
+            // Completions without a snapshot yet ride along with the first one.
+            return;
+        };
+        // Every update but the one sent was superseded before it went out.
+        self.diagnostics.events_coalesced(slot.coalesced_updates.saturating_sub(1));
+        let payload = CoalescedProgress {
+            operation_id: self.operation_id.clone(),
+            progress,
+            files_completed: std::mem::take(&mut slot.files_completed),
+            coalesced_updates: std::mem::take(&mut slot.coalesced_updates),
+        };
+        if let Ok(payload) = serde_json::to_value(payload) {
+            self.send(PROGRESS_EVENT, payload);
+        }
+    }
+
+    fn send(&self, event: &str, payload: Value) {
+        self.diagnostics.event_emitted();
+        (self.sink)(event, payload);
+    }
+}
+
+/// Folds an operation's progress updates into one `latest snapshot` slot that a single emitter
+/// thread sends at most `events_per_second` times a second, so thousands of small files do not
+/// flood the IPC bridge. Critical events go out at once via `emit_now`, after any pending snapshot.
+pub struct ProgressCoalescer {
+    shared: Arc<Shared>,
+    emitter: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ProgressCoalescer {
+    pub fn new<S>(operation_id: impl Into<String>, events_per_second: u32, sink: S) -> Self
+    where
+        S: Fn(&str, Value) + Send + Sync + 'static,
+    {
+        Self::with_diagnostics(operation_id, events_per_second, OperationDiagnostics::default(), sink)
+    }
+
+    /// Like `new`, counting the events sent and coalesced away in `diagnostics`.
+    pub fn with_diagnostics<S>(
+        operation_id: impl Into<String>,
+        events_per_second: u32,
+        diagnostics: OperationDiagnostics,
+        sink: S,
+    ) -> Self
+    where
+        S: Fn(&str, Value) + Send + Sync + 'static,
+    {
+        let shared = Arc::new(Shared {
+            operation_id: operation_id.into(),
+            slot: Mutex::new(Slot::default()),
+            wake: Condvar::new(),
+            sink: Box::new(sink),
+            diagnostics,
+        });
+        let interval = Duration::from_secs(1) / events_per_second.max(1);
+        let emitter_shared = shared.clone();
+        let emitter = thread::Builder::new()
+            .name("progress-emitter".to_string())
+            .spawn(move || run_emitter(&emitter_shared, interval))
+            .ok();
+        ProgressCoalescer {
+            shared,
+            emitter: Mutex::new(emitter),
+        }
+    }
+
+    /// Replace the latest snapshot; it is sent on the emitter's next tick.
+    pub fn update(&self, progress: WipeProgress) {
+        let mut slot = self.shared.lock();
+        slot.latest = Some(progress);
+        slot.coalesced_updates += 1;
+        slot.dirty = true;
+        self.wake_or_flush(&mut slot);
+    }
+
+    /// Count a finished file towards the next event's `files_completed`.
+    pub fn file_completed(&self) {
+        let mut slot = self.shared.lock();
+        slot.files_completed += 1;
+        slot.dirty = true;
+        self.wake_or_flush(&mut slot);
+    }
+
+    /// Send `progress` immediately, for phase changes the UI must not miss.
+    pub fn phase_change(&self, progress: WipeProgress) {
+        let mut slot = self.shared.lock();
+        slot.latest = Some(progress);
+        slot.coalesced_updates += 1;
+        slot.dirty = true;
+        self.shared.flush(&mut slot);
+    }
+
+    /// Send a critical event without coalescing, after flushing any pending snapshot.
+    pub fn emit_now(&self, event: &str, payload: Value) {
+        let mut slot = self.shared.lock();
+        self.shared.flush(&mut slot);
+        self.shared.send(event, payload);
+    }
+
+    /// Stop the emitter and send the final state. Updates after this are sent straight away.
+    pub fn finish(&self) {
+        {
+            let mut slot = self.shared.lock();
+            slot.finished = true;
+            self.shared.wake.notify_all();
+        }
+        let emitter = self.emitter.lock().unwrap_or_else(|p| p.into_inner()).take();
+        if let Some(emitter) = emitter {
+            let _ = emitter.join();
+        }
+        let mut slot = self.shared.lock();
+        self.shared.flush(&mut slot);
+    }
+
+    fn wake_or_flush(&self, slot: &mut Slot) {
+        if slot.finished {
+            self.shared.flush(slot);
+        } else {
+            self.shared.wake.notify_one();
+        }
+    }
+}
+
+impl Drop for ProgressCoalescer {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+fn run_emitter(shared: &Shared, interval: Duration) {
+    let mut slot = shared.lock();
+    loop {
+        slot = shared
+            .wake
+            .wait_while(slot, |s| !s.dirty && !s.finished)
+            .unwrap_or_else(|p| p.into_inner());
+        if slot.finished {
+            return;
+        }
+        shared.flush(&mut slot);
+        // Sit out the rest of the interval; `finish` cuts the wait short.
+        slot = shared
+            .wake
+            .wait_timeout_while(slot, interval, |s| !s.finished)
+            .unwrap_or_else(|p| p.into_inner())
+            .0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Instant;
+
+    type Emitted = Arc<Mutex<Vec<(String, Value)>>>;
+
+    fn recording(events_per_second: u32) -> (ProgressCoalescer, Emitted) {
+        let emitted: Emitted = Arc::default();
+        let sink = emitted.clone();
+        let coalescer = ProgressCoalescer::new("op-1", events_per_second, move |event: &str, payload| {
+            sink.lock().unwrap().push((event.to_string(), payload));
+        });
+        (coalescer, emitted)
+    }
+
+    fn progress(bytes_processed: u64) -> WipeProgress {
+        let mut progress = WipeProgress::new(1, 10_000, "Random");
+        progress.update(bytes_processed, "Random data");
+        progress
+    }
+
+    #[test]
+    fn thousands_of_updates_are_coalesced_to_the_rate_limit() {
+        let (coalescer, emitted) = recording(20);
+        let started = Instant::now();
+        for i in 1..=10_000u64 {
+            coalescer.update(progress(i));
+            coalescer.file_completed();
+            if i % 500 == 0 {
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        coalescer.finish();
+        let elapsed = started.elapsed();
+
+        let emitted = emitted.lock().unwrap();
+        let allowed = (elapsed.as_secs_f64() * 20.0).ceil() as usize + 2;
+        assert!(emitted.len() <= allowed, "{} events in {:?}", emitted.len(), elapsed);
+        assert!(emitted.len() < 100);
+
+        let (event, last) = emitted.last().unwrap();
+        assert_eq!(event, "wipe_progress");
+        assert_eq!(last["bytes_processed"], json!(10_000), "the final state must be flushed");
+        assert_eq!(last["operation_id"], json!("op-1"));
+        let files: u64 = emitted.iter().map(|(_, p)| p["files_completed"].as_u64().unwrap()).sum();
+        let updates: u64 = emitted.iter().map(|(_, p)| p["coalesced_updates"].as_u64().unwrap()).sum();
+        assert_eq!(files, 10_000);
+        assert_eq!(updates, 10_000);
+    }
+
+    #[test]
+    fn critical_events_bypass_coalescing_after_the_pending_snapshot() {
+        let (coalescer, emitted) = recording(1);
+        coalescer.update(progress(1));
+        // Let the first snapshot go out so the emitter is inside its one-second hold-off.
+        thread::sleep(Duration::from_millis(50));
+        coalescer.update(progress(2));
+        coalescer.emit_now("wipe_file_failed", json!({"path": "a.txt"}));
+        coalescer.phase_change(progress(3));
+
+        let events: Vec<(String, Value)> = emitted.lock().unwrap().clone();
+        let summary: Vec<(&str, Value)> = events
+            .iter()
+            .map(|(event, payload)| (event.as_str(), payload.get("bytes_processed").cloned().unwrap_or(json!(null))))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("wipe_progress", json!(1)),
+                ("wipe_progress", json!(2)),
+                ("wipe_file_failed", json!(null)),
+                ("wipe_progress", json!(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn finish_flushes_and_later_updates_are_sent_directly() {
+        let (coalescer, emitted) = recording(1);
+        coalescer.update(progress(1));
+        coalescer.update(progress(2));
+        coalescer.finish();
+        let flushed = emitted.lock().unwrap().last().unwrap().1["bytes_processed"].clone();
+        assert_eq!(flushed, json!(2));
+
+        coalescer.update(progress(3));
+        assert_eq!(emitted.lock().unwrap().last().unwrap().1["bytes_processed"], json!(3));
+    }
+
+    #[test]
+    fn superseded_snapshots_are_counted_and_the_first_warns() {
+        let totals = crate::diagnostics::Diagnostics::default();
+        let diagnostics = OperationDiagnostics::default();
+        let warnings = Arc::new(Mutex::new(0));
+        let seen = warnings.clone();
+        diagnostics.connect(totals.clone(), move |_| *seen.lock().unwrap() += 1);
+        let emitted: Emitted = Arc::default();
+        let sink = emitted.clone();
+        let coalescer = ProgressCoalescer::with_diagnostics("op-1", 1, diagnostics.clone(), move |event: &str, payload| {
+            sink.lock().unwrap().push((event.to_string(), payload));
+        });
+        for i in 1..=100u64 {
+            coalescer.update(progress(i));
+        }
+        coalescer.emit_now("wipe_file_failed", json!({"path": "a.txt"}));
+        coalescer.finish();
+
+        let sent = emitted.lock().unwrap().len() as u64;
+        let counters = diagnostics.snapshot();
+        assert_eq!(counters.events_emitted, sent);
+        let progress_events = sent - 1;
+        assert_eq!(counters.events_coalesced, 100 - progress_events);
+        assert!(counters.events_coalesced > 0);
+        assert_eq!(totals.snapshot(), counters);
+        assert_eq!(*warnings.lock().unwrap(), 1);
+    }
+}
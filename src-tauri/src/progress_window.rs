@@ -0,0 +1,219 @@
+//! The floating progress pill: a small frameless, always-on-top window bound to one operation,
+//! for keeping an eye on a wipe while the main window is minimized. It only watches; closing it
+//! leaves the operation running.
+
+use serde_json::json;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime, State, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+use crate::diagnostics;
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::operations::OperationRegistry;
+
+pub const PROGRESS_WINDOW_LABEL: &str = "progress";
+
+/// How long the pill stays up after its operation finishes, so the final state can be read.
+pub const AUTO_CLOSE_AFTER: Duration = Duration::from_secs(4);
+
+/// How often the watcher checks whether the bound operation is still running.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+const WINDOW_WIDTH: f64 = 340.0;
+const WINDOW_HEIGHT: f64 = 84.0;
+
+/// Managed state: the operation the progress window is showing, if it is open.
+#[derive(Debug, Default)]
+pub struct ProgressWindow {
+    bound: Mutex<Option<String>>,
+}
+
+impl ProgressWindow {
+    pub fn bound(&self) -> Option<String> {
+        self.lock().clone()
+    }
+
+    fn bind(&self, operation_id: &str) {
+        *self.lock() = Some(operation_id.to_string());
+    }
+
+    fn unbind(&self) {
+        *self.lock() = None;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<String>> {
+        self.bound.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+/// Windows an operation's events go to: the one that started it, plus the progress window while
+/// it is bound to that operation.
+pub fn event_targets(origin: &str, bound: Option<&str>, operation_id: &str) -> Vec<String> {
+    let mut targets = vec![origin.to_string()];
+    if bound == Some(operation_id) && origin != PROGRESS_WINDOW_LABEL {
+        targets.push(PROGRESS_WINDOW_LABEL.to_string());
+    }
+    targets
+}
+
+/// `event_targets` for the running app.
+pub fn targets_for<R: Runtime>(app: &AppHandle<R>, origin: &str, operation_id: &str) -> Vec<String> {
+    let bound = app.try_state::<ProgressWindow>().and_then(|state| state.bound());
+    event_targets(origin, bound.as_deref(), operation_id)
+}
+
+/// What the watcher of a shown progress window does on each tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WatchStep {
+    Wait,
+    Close,
+    /// The window was closed or bound to another operation; this watcher has nothing left to do.
+    Stop,
+}
+
+/// Decide the watcher's next step. `finished_at` remembers when the operation was first seen
+/// finished, so the window closes `AUTO_CLOSE_AFTER` later.
+pub(crate) fn watch_step(
+    bound: Option<&str>,
+    watched: &str,
+    active: bool,
+    finished_at: &mut Option<Instant>,
+    now: Instant,
+) -> WatchStep {
+    if bound != Some(watched) {
+        return WatchStep::Stop;
+    }
+    if active {
+        *finished_at = None;
+        return WatchStep::Wait;
+    }
+    let finished = *finished_at.get_or_insert(now);
+    if now.duration_since(finished) >= AUTO_CLOSE_AFTER {
+        WatchStep::Close
+    } else {
+        WatchStep::Wait
+    }
+}
+
+fn close_progress_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(state) = app.try_state::<ProgressWindow>() {
+        state.unbind();
+    }
+    if let Some(window) = app.get_webview_window(PROGRESS_WINDOW_LABEL) {
+        let _ = window.close();
+    }
+}
+
+fn watch_operation<R: Runtime>(app: AppHandle<R>, registry: OperationRegistry, operation_id: String) {
+    let watched = operation_id.clone();
+    let spawned = thread::Builder::new().name("progress-window".to_string()).spawn(move || {
+        let mut finished_at = None;
+        loop {
+            let bound = app.try_state::<ProgressWindow>().and_then(|state| state.bound());
+            let active = registry.get(&operation_id).is_some();
+            match watch_step(bound.as_deref(), &operation_id, active, &mut finished_at, Instant::now()) {
+                WatchStep::Wait => thread::sleep(WATCH_INTERVAL),
+                WatchStep::Close => {
+                    log_event("progress_window_auto_closed", json!({"operation_id": operation_id}));
+                    close_progress_window(&app);
+                    return;
+                }
+                WatchStep::Stop => return,
+            }
+        }
+    });
+    if let Err(e) = spawned {
+        log_event("progress_window_watch_failed", json!({"operation_id": watched, "message": e.to_string()}));
+    }
+}
+
+/// Show the progress window for `operation_id`, creating it or rebinding the open one. It closes
+/// itself a few seconds after the operation finishes.
+#[tauri::command]
+pub async fn show_progress_window(
+    app: AppHandle,
+    state: State<'_, ProgressWindow>,
+    registry: State<'_, OperationRegistry>,
+    operation_id: String,
+) -> Result<(), BitBurnError> {
+    if registry.get(&operation_id).is_none() {
+        return Err(BitBurnError::OperationNotFound { operation_id });
+    }
+    state.bind(&operation_id);
+
+    if let Some(window) = app.get_webview_window(PROGRESS_WINDOW_LABEL) {
+        diagnostics::emit(window.app_handle(), "progress_window_bound", json!({"operation_id": operation_id}));
+        let _ = window.show();
+    } else {
+        let url = format!("index.html?view=progress&operation={}", operation_id);
+        let window = WebviewWindowBuilder::new(&app, PROGRESS_WINDOW_LABEL, WebviewUrl::App(url.into()))
+            .title("BitBurn progress")
+            .inner_size(WINDOW_WIDTH, WINDOW_HEIGHT)
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .focused(false)
+            .build()
+            .map_err(|e| BitBurnError::internal(format!("progress window could not be created: {}", e)))?;
+        let app_handle = app.clone();
+        window.on_window_event(move |event| {
+            if let WindowEvent::Destroyed = event {
+                if let Some(state) = app_handle.try_state::<ProgressWindow>() {
+                    state.unbind();
+                }
+            }
+        });
+    }
+
+    log_event("progress_window_shown", json!({"operation_id": operation_id}));
+    watch_operation(app, registry.inner().clone(), operation_id);
+    Ok(())
+}
+
+/// Close the progress window. The operation it was showing keeps running.
+#[tauri::command]
+pub async fn hide_progress_window(app: AppHandle) -> Result<(), BitBurnError> {
+    close_progress_window(&app);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_reach_the_progress_window_only_for_its_operation() {
+        assert_eq!(event_targets("main", None, "op-1"), vec!["main"]);
+        assert_eq!(event_targets("main", Some("op-1"), "op-1"), vec!["main", PROGRESS_WINDOW_LABEL]);
+        assert_eq!(event_targets("main", Some("op-2"), "op-1"), vec!["main"]);
+        // An operation started from the pill itself is not sent there twice.
+        assert_eq!(event_targets(PROGRESS_WINDOW_LABEL, Some("op-1"), "op-1"), vec![PROGRESS_WINDOW_LABEL]);
+    }
+
+    #[test]
+    fn window_closes_a_few_seconds_after_its_operation_finishes() {
+        let start = Instant::now();
+        let mut finished_at = None;
+
+        assert_eq!(watch_step(Some("op-1"), "op-1", true, &mut finished_at, start), WatchStep::Wait);
+        assert_eq!(finished_at, None);
+
+        let done = start + Duration::from_secs(10);
+        assert_eq!(watch_step(Some("op-1"), "op-1", false, &mut finished_at, done), WatchStep::Wait);
+        assert_eq!(finished_at, Some(done));
+        let almost = done + AUTO_CLOSE_AFTER - Duration::from_millis(1);
+        assert_eq!(watch_step(Some("op-1"), "op-1", false, &mut finished_at, almost), WatchStep::Wait);
+        assert_eq!(watch_step(Some("op-1"), "op-1", false, &mut finished_at, done + AUTO_CLOSE_AFTER), WatchStep::Close);
+    }
+
+    #[test]
+    fn watcher_stops_when_the_window_is_closed_or_rebound() {
+        let now = Instant::now();
+        let mut finished_at = Some(now - AUTO_CLOSE_AFTER * 2);
+        assert_eq!(watch_step(None, "op-1", false, &mut finished_at, now), WatchStep::Stop);
+        assert_eq!(watch_step(Some("op-2"), "op-1", false, &mut finished_at, now), WatchStep::Stop);
+    }
+}
@@ -0,0 +1,167 @@
+use bitburn_core::SymlinkPolicy;
+use std::path::{Path, PathBuf};
+
+use crate::error::BitBurnError;
+use crate::platform::context_menu::sanitize_target_path_with;
+use crate::self_protection;
+
+/// Operating-system locations BitBurn refuses to wipe, together with
+/// everything beneath them.
+#[cfg(unix)]
+const SYSTEM_TREES: &[&str] = &[
+    "/bin", "/boot", "/dev", "/etc", "/lib", "/lib64", "/proc", "/sbin", "/sys", "/usr", "/System", "/Library",
+];
+
+/// Protected locations resolved for the current machine.
+#[derive(Debug, Clone, Default)]
+pub struct ProtectedPaths {
+    /// Never wiped, nor anything inside them.
+    trees: Vec<PathBuf>,
+    /// Never wiped as a whole; their contents may be.
+    exact: Vec<PathBuf>,
+}
+
+impl ProtectedPaths {
+    pub fn new(trees: Vec<PathBuf>, exact: Vec<PathBuf>) -> Self {
+        ProtectedPaths { trees, exact }
+    }
+
+    /// System folders plus the current user's home directory.
+    pub fn current() -> Self {
+        #[cfg(windows)]
+        let (trees, home) = {
+            let trees = ["SystemRoot", "ProgramFiles", "ProgramFiles(x86)"]
+                .iter()
+                .filter_map(std::env::var_os)
+                .map(PathBuf::from)
+                .collect();
+            (trees, std::env::var_os("USERPROFILE"))
+        };
+
+        #[cfg(not(windows))]
+        let (trees, home) = (
+            SYSTEM_TREES.iter().map(PathBuf::from).collect(),
+            std::env::var_os("HOME"),
+        );
+
+        ProtectedPaths::new(trees, home.map(PathBuf::from).into_iter().collect())
+    }
+
+    /// Whether `path` is a filesystem root, a protected folder, or lies inside a protected tree.
+    pub fn is_protected(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        if path.parent().is_none() {
+            return true;
+        }
+        self.trees.iter().any(|tree| path.starts_with(normalize(tree)))
+            || self.exact.iter().any(|exact| path == normalize(exact))
+    }
+
+    pub fn check(&self, path: &Path) -> Result<(), BitBurnError> {
+        if self.is_protected(path) {
+            Err(BitBurnError::ProtectedPath {
+                path: path.to_string_lossy().to_string(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Check a path a command took from something stored, such as a report, a journal or a resume
+/// list, or was handed directly rather than through a selection: sanitized as a selected path
+/// is, with links handled as `symlinks` says, then refused if it is protected or one of BitBurn's
+/// own files. Returns the path to wipe.
+pub fn sanitize_stored_target(raw: &str, symlinks: SymlinkPolicy, protected: &ProtectedPaths) -> Result<String, BitBurnError> {
+    let path = sanitize_target_path_with(raw, symlinks)?;
+    protected.check(Path::new(&path))?;
+    self_protection::check(Path::new(&path))?;
+    Ok(path)
+}
+
+/// `sanitize_stored_target` over `raws`: the paths to wipe, and why each of the others was refused.
+pub fn sanitize_stored_targets(raws: &[String], symlinks: SymlinkPolicy) -> (Vec<String>, Vec<BitBurnError>) {
+    let protected = ProtectedPaths::current();
+    let mut targets = Vec::new();
+    let mut refused = Vec::new();
+    for raw in raws {
+        match sanitize_stored_target(raw, symlinks, &protected) {
+            Ok(path) => targets.push(path),
+            Err(e) => refused.push(e),
+        }
+    }
+    (targets, refused)
+}
+
+/// Comparison form of a path: case-folded on Windows, where the filesystem is case-insensitive.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(path.to_string_lossy().to_lowercase())
+    } else {
+        path.components().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> ProtectedPaths {
+        ProtectedPaths::new(
+            vec![PathBuf::from("/usr"), PathBuf::from("/opt/system")],
+            vec![PathBuf::from("/home/alex")],
+        )
+    }
+
+    #[test]
+    fn trees_protect_themselves_and_their_contents() {
+        let protected = sample();
+        assert!(protected.is_protected(Path::new("/usr")));
+        assert!(protected.is_protected(Path::new("/usr/lib/libc.so")));
+        assert!(protected.is_protected(Path::new("/opt/system/")));
+        assert!(!protected.is_protected(Path::new("/usrdata/file")));
+    }
+
+    #[test]
+    fn exact_entries_only_protect_the_folder_itself() {
+        let protected = sample();
+        assert!(protected.is_protected(Path::new("/home/alex")));
+        assert!(!protected.is_protected(Path::new("/home/alex/Downloads/sensitive")));
+    }
+
+    #[test]
+    fn filesystem_roots_are_always_protected() {
+        let protected = ProtectedPaths::default();
+        assert!(protected.is_protected(Path::new("/")));
+        assert_eq!(protected.check(Path::new("/")).unwrap_err().code(), "protected_path");
+        assert!(protected.check(Path::new("/tmp/scratch")).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stored_targets_are_sanitized_and_protected_ones_refused() {
+        use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+        let dir = create_test_dir().unwrap();
+        let file = dir.join("partial.bin");
+        std::fs::write(&file, b"secret").unwrap();
+        let link = dir.join("link.bin");
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(&file, &link).unwrap();
+
+        let dotted = format!("{}/../{}/partial.bin", dir.display(), dir.file_name().unwrap().to_string_lossy());
+        let raws: Vec<String> = [dotted.as_str(), "/etc/passwd", "/", "\\\\server\\share\\a.txt", "/no/such/file", link.to_str().unwrap()]
+            .iter()
+            .map(|raw| raw.to_string())
+            .collect();
+        let (targets, refused) = sanitize_stored_targets(&raws, SymlinkPolicy::Reject);
+        assert_eq!(targets, [file.canonicalize().unwrap().to_string_lossy().to_string()]);
+        let codes: Vec<&str> = refused.iter().map(BitBurnError::code).collect();
+        assert_eq!(
+            codes,
+            // The root holds BitBurn's own files, which sanitizing already refuses.
+            ["protected_path", "self_protection", "network_path_not_supported", "path_not_found", "symlink_not_supported"]
+        );
+        cleanup_test_dir(dir);
+    }
+}
@@ -0,0 +1,167 @@
+//! Wiping a byte range inside a file, e.g. a credential blob at a known offset, while the rest
+//! of the file stays usable. The file is never truncated or deleted.
+
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{Manager, Runtime};
+
+use crate::consent;
+use crate::error::BitBurnError;
+use crate::messages::{CONFIRM_RANGE_WIPE, RANGE_WIPED};
+use crate::operations::{OperationGuard, OperationKind, OperationRegistry};
+use crate::protected::{self, ProtectedPaths};
+use crate::{log_event, policy, BatchContext, WipeAlgorithm, WipeResult};
+use bitburn_core::{CancellationToken, SymlinkPolicy};
+
+/// Ranges being wiped right now, per file, so two wipes never write the same bytes at once.
+#[derive(Default)]
+pub struct RangeLocks {
+    active: Arc<Mutex<HashMap<PathBuf, Vec<Range<u64>>>>>,
+}
+
+/// A claimed range, released when dropped.
+pub struct RangeClaim {
+    active: Arc<Mutex<HashMap<PathBuf, Vec<Range<u64>>>>>,
+    file: PathBuf,
+    range: Range<u64>,
+}
+
+impl RangeLocks {
+    /// Claim `length` bytes of `path` from `offset`, failing with `RangeInUse` if any of them are
+    /// already claimed. Paths are canonicalized so two spellings of one file share their claims.
+    pub fn claim(&self, path: &Path, offset: u64, length: u64) -> Result<RangeClaim, BitBurnError> {
+        let file = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let range = offset..offset.saturating_add(length);
+        let mut active = self.active.lock().unwrap_or_else(|p| p.into_inner());
+        let claimed = active.entry(file.clone()).or_default();
+        if claimed.iter().any(|other| other.start < range.end && range.start < other.end) {
+            return Err(BitBurnError::RangeInUse {
+                path: path.to_string_lossy().to_string(),
+                offset,
+                length,
+            });
+        }
+        claimed.push(range.clone());
+        Ok(RangeClaim {
+            active: self.active.clone(),
+            file,
+            range,
+        })
+    }
+}
+
+impl Drop for RangeClaim {
+    fn drop(&mut self) {
+        let mut active = self.active.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(claimed) = active.get_mut(&self.file) {
+            claimed.retain(|range| *range != self.range);
+            if claimed.is_empty() {
+                active.remove(&self.file);
+            }
+        }
+    }
+}
+
+/// Overwrite `length` bytes of `path` from `offset` with `algorithm`, clamped to the end of the
+/// file. Progress covers the range only. Overlapping a range still being wiped is rejected. The
+/// path is sanitized as a selection is, protected files and BitBurn's own are refused, and the
+/// file is confirmed through `consent_token` or a prompt shown now.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn wipe_file_range<R: Runtime>(
+    window: tauri::Window<R>,
+    registry: tauri::State<'_, OperationRegistry>,
+    path: String,
+    offset: u64,
+    length: u64,
+    algorithm: WipeAlgorithm,
+    passes: Option<u32>,
+    consent_token: Option<String>,
+) -> Result<WipeResult, BitBurnError> {
+    let passes = passes.unwrap_or(algorithm.default_passes());
+    let path = protected::sanitize_stored_target(&path, SymlinkPolicy::Reject, &ProtectedPaths::current())
+        .inspect_err(|e| log_event("wipe_range_refused", json!({"path": path, "code": e.code()})))?;
+    policy::check_wipe(window.app_handle(), policy::WIPE_FILE_RANGE, &algorithm, passes, std::slice::from_ref(&path))?;
+    let target = PathBuf::from(&path);
+    let prompt = CONFIRM_RANGE_WIPE.render(json!({
+        "length": length,
+        "path": path,
+        "offset": offset,
+        "algorithm": format!("{:?}", algorithm),
+    }));
+    let consent = consent::confirm_targets_async(window.app_handle().clone(), consent_token, prompt.text, vec![path.clone()]).await?;
+    let claim = window.state::<RangeLocks>().claim(&target, offset, length)?;
+
+    let app_handle = window.app_handle().clone();
+    let operation = OperationGuard::begin(
+        &app_handle,
+        &registry,
+        OperationKind::WipeFiles,
+        format!("{} ({} bytes at offset {})", path, length, offset),
+    );
+    operation.record_algorithm(&algorithm, passes);
+    let ctx = BatchContext::new(app_handle, window.label().to_string(), operation);
+
+    let join_result = spawn_blocking(move || {
+        let _claim = claim;
+        let _log_scope = ctx.operation.enter_log_scope();
+        log_event(
+            "wipe_range_start",
+            json!({"operation_id": ctx.operation.id(), "path": path, "offset": offset, "length": length, "algorithm": format!("{:?}", algorithm), "passes": passes, "consent": consent.token}),
+        );
+
+        let cancel = CancellationToken::from(ctx.operation.cancel_flag());
+        let progress = ctx.progress_emitter(None, |fraction| fraction * 100.0);
+        let result = match bitburn_core::wipe_file_range(&target, offset, length, passes, &algorithm, &cancel, progress) {
+            Ok(wiped) => {
+                log_event("wipe_range_complete", json!({"path": path, "offset": offset, "length": wiped}));
+                WipeResult::success(RANGE_WIPED.render(json!({"path": path, "offset": offset, "length": wiped})))
+            }
+            Err(e) => {
+                let error = BitBurnError::from_wipe_error(e, &target);
+                log_event("wipe_range_failed", json!({"path": path, "offset": offset, "message": error.to_string()}));
+                WipeResult::failure(error)
+            }
+        };
+        ctx.progress.finish();
+        ctx.operation.record_result(&result);
+        result
+    })
+    .await
+    .map_err(|e| BitBurnError::internal(format!("wipe_file_range task join error: {}", e)))?;
+
+    Ok(join_result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_ranges_of_one_file_are_rejected_until_released() {
+        let locks = RangeLocks::default();
+        let file = Path::new("/no/such/dir/vault.bin");
+
+        let first = locks.claim(file, 100, 50).expect("first claim");
+        for (offset, length) in [(100, 50), (90, 11), (149, 10), (0, 1000)] {
+            assert!(
+                matches!(locks.claim(file, offset, length), Err(BitBurnError::RangeInUse { .. })),
+                "{}+{} overlaps 100..150",
+                offset,
+                length
+            );
+        }
+        // Adjacent ranges and other files are free.
+        let _before = locks.claim(file, 50, 50).expect("ends where the first starts");
+        let _after = locks.claim(file, 150, 10).expect("starts where the first ends");
+        let _other = locks.claim(Path::new("/no/such/dir/other.bin"), 100, 50).expect("different file");
+
+        drop(first);
+        locks.claim(file, 120, 10).expect("released range can be claimed again");
+    }
+}
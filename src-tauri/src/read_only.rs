@@ -0,0 +1,304 @@
+//! Pre-flight check for read-only and write-protected volumes. Without it, a batch pointed at a
+//! locked SD card fails every file on it with its own PermissionDenied; with it, each such volume
+//! is reported once and its files are never attempted. Nothing is written to find out: the
+//! volume's flags say whether it is read-only, and on Unix an access check says whether this
+//! user may write in a folder, which is reported as access denied rather than as a read-only volume.
+
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use sysinfo::{DiskExt, System, SystemExt};
+use tauri::Runtime;
+
+use crate::error::BitBurnError;
+use crate::volume_root::comparable;
+use crate::{log_event, BatchContext};
+
+/// Why nothing can be written in a directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteRefusal {
+    /// The volume is mounted read-only or write-protected.
+    ReadOnlyVolume,
+    /// The volume takes writes, but not from this user in this directory.
+    PermissionDenied,
+}
+
+/// The volume flag says read-only; nothing else about the directory matters.
+#[cfg(unix)]
+fn volume_flagged_read_only(path: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(c_path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) == 0 && stat.f_flag & libc::ST_RDONLY != 0 }
+}
+
+#[cfg(windows)]
+fn volume_flagged_read_only(path: &Path) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{GetVolumeInformationW, GetVolumePathNameW};
+
+    /// `FILE_READ_ONLY_VOLUME` in the file system flags.
+    const READ_ONLY_VOLUME: u32 = 0x0008_0000;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut root = [0u16; 261];
+    let mut flags = 0u32;
+    unsafe {
+        GetVolumePathNameW(wide.as_ptr(), root.as_mut_ptr(), root.len() as u32) != 0
+            && GetVolumeInformationW(
+                root.as_ptr(),
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &mut flags,
+                std::ptr::null_mut(),
+                0,
+            ) != 0
+            && flags & READ_ONLY_VOLUME != 0
+    }
+}
+
+#[cfg(not(any(windows, unix)))]
+fn volume_flagged_read_only(_path: &Path) -> bool {
+    false
+}
+
+/// Whether this user may create files in `dir`, asked of the kernel without creating one.
+/// `None` when the answer is not conclusive.
+#[cfg(unix)]
+fn may_write_in(dir: &Path) -> Option<bool> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(dir.as_os_str().as_bytes()).ok()?;
+    // `AT_EACCESS` checks the effective ids, which are the ones a write would use.
+    if unsafe { libc::faccessat(libc::AT_FDCWD, c_path.as_ptr(), libc::W_OK, libc::AT_EACCESS) } == 0 {
+        return Some(true);
+    }
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::EACCES) => Some(false),
+        _ => None,
+    }
+}
+
+/// Windows grants folder access through ACLs that are only settled by trying; a refused file
+/// fails on its own there.
+#[cfg(not(unix))]
+fn may_write_in(_dir: &Path) -> Option<bool> {
+    None
+}
+
+/// Why nothing can be written in `dir`, if so: its volume is flagged read-only, or this user may
+/// not write there. Anything inconclusive counts as writable.
+pub fn write_refusal(dir: &Path) -> Option<WriteRefusal> {
+    if volume_flagged_read_only(dir) {
+        return Some(WriteRefusal::ReadOnlyVolume);
+    }
+    match may_write_in(dir) {
+        Some(false) => Some(WriteRefusal::PermissionDenied),
+        _ => None,
+    }
+}
+
+/// Directory a root's writes go to: a folder itself, a file's parent.
+pub(crate) fn write_location(root: &Path) -> PathBuf {
+    match root.parent() {
+        Some(parent) if !root.is_dir() && !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => root.to_path_buf(),
+    }
+}
+
+/// The mount point among `mounts` that `path` lives under, the deepest one when they nest.
+pub(crate) fn volume_of(path: &Path, mounts: &[PathBuf]) -> PathBuf {
+    let candidate = comparable(path);
+    mounts
+        .iter()
+        .filter(|mount| candidate.starts_with(mount))
+        .max_by_key(|mount| mount.components().count())
+        .cloned()
+        .unwrap_or_else(|| candidate.ancestors().last().unwrap_or(&candidate).to_path_buf())
+}
+
+/// Mount points of the disks sysinfo reports, in the form `volume_of` compares against.
+pub(crate) fn mount_points() -> Vec<PathBuf> {
+    let mut sys = System::new();
+    sys.refresh_disks_list();
+    sys.disks().iter().map(|disk| comparable(disk.mount_point())).collect()
+}
+
+/// Split `roots` into those to wipe and the errors to report for the rest: one `VolumeReadOnly`
+/// per volume where `refusal` found every directory the roots on it would write to on a read-only
+/// volume, and an `AccessDenied` for each other root whose directory this user may not write in.
+/// A volume with any writable target directory goes ahead, so one protected folder cannot fail a
+/// whole drive.
+pub fn split_read_only<F>(roots: Vec<String>, mounts: &[PathBuf], mut refusal: F) -> (Vec<String>, Vec<BitBurnError>)
+where
+    F: FnMut(&Path) -> Option<WriteRefusal>,
+{
+    let mut volumes: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+    for root in &roots {
+        let path = Path::new(root);
+        if path.exists() {
+            volumes.entry(volume_of(path, mounts)).or_default().push(root.clone());
+        }
+    }
+
+    let mut refused = Vec::new();
+    let mut skipped: Vec<String> = Vec::new();
+    for (volume, on_volume) in volumes {
+        let mut refusals: BTreeMap<PathBuf, Option<WriteRefusal>> = BTreeMap::new();
+        for root in &on_volume {
+            refusals.entry(write_location(Path::new(root))).or_insert_with_key(|dir| refusal(dir));
+        }
+        if refusals.values().all(|why| *why == Some(WriteRefusal::ReadOnlyVolume)) {
+            skipped.extend(on_volume.iter().cloned());
+            refused.push(BitBurnError::VolumeReadOnly {
+                volume: volume.to_string_lossy().to_string(),
+                paths: on_volume,
+            });
+            continue;
+        }
+        for root in on_volume {
+            if refusals[&write_location(Path::new(&root))] == Some(WriteRefusal::PermissionDenied) {
+                refused.push(BitBurnError::AccessDenied { path: root.clone() });
+                skipped.push(root);
+            }
+        }
+    }
+
+    let writable = roots.into_iter().filter(|root| !skipped.contains(root)).collect();
+    (writable, refused)
+}
+
+/// Check the volumes of a batch's roots before the batch starts. Returns the roots to wipe and
+/// the failures to report in place of the rest.
+pub(crate) fn preflight<R: Runtime>(ctx: &BatchContext<R>, roots: Vec<String>) -> (Vec<String>, Vec<BitBurnError>) {
+    let (writable, refused) = split_read_only(roots, &mount_points(), write_refusal);
+    for error in &refused {
+        match error {
+            BitBurnError::VolumeReadOnly { volume, paths } => log_event(
+                "volume_read_only",
+                json!({"operation_id": ctx.operation.id(), "volume": volume, "count": paths.len()}),
+            ),
+            BitBurnError::AccessDenied { path } => {
+                log_event("write_access_denied", json!({"operation_id": ctx.operation.id(), "path": path}))
+            }
+            _ => {}
+        }
+    }
+    (writable, refused)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::OperationRegistry;
+    use crate::test_support::{batch_context, cleanup_test_dir, create_test_dir};
+    use crate::{run_wipe_batch, BatchOptions, WipeAlgorithm};
+    use std::fs;
+
+    #[test]
+    fn a_read_only_volume_fails_once_and_its_files_are_not_attempted() {
+        let dir = create_test_dir().unwrap();
+        let (card, disk) = (dir.join("card"), dir.join("disk"));
+        fs::create_dir_all(card.join("DCIM")).unwrap();
+        fs::create_dir_all(&disk).unwrap();
+        let photos: Vec<PathBuf> = (0..50).map(|i| card.join(format!("IMG_{:04}.jpg", i))).collect();
+        for photo in &photos {
+            fs::write(photo, b"photo").unwrap();
+        }
+        fs::write(card.join("DCIM").join("thumb.db"), b"thumbs").unwrap();
+        let notes = disk.join("notes.txt");
+        fs::write(&notes, b"notes").unwrap();
+
+        let mut roots: Vec<String> = photos.iter().map(|p| p.to_string_lossy().to_string()).collect();
+        roots.push(card.join("DCIM").to_string_lossy().to_string());
+        roots.push(notes.to_string_lossy().to_string());
+        // `card` stands in for a write-protected SD card mounted inside the temp directory.
+        let mounts = vec![comparable(&card), comparable(&disk)];
+        let card_root = comparable(&card);
+        let mut probed = Vec::new();
+        let (writable, refused) = split_read_only(roots, &mounts, |dir| {
+            probed.push(dir.to_path_buf());
+            comparable(dir).starts_with(&card_root).then_some(WriteRefusal::ReadOnlyVolume)
+        });
+
+        assert_eq!(writable, vec![notes.to_string_lossy().to_string()]);
+        assert_eq!(refused.len(), 1);
+        assert!(matches!(&refused[0], BitBurnError::VolumeReadOnly { volume, paths } if *volume == card_root.to_string_lossy() && paths.len() == 51));
+        assert_eq!(probed.len(), 3, "one check per distinct directory: {:?}", probed);
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let ctx = batch_context(&app, &registry, "read-only test");
+        let options = BatchOptions {
+            read_only_failures: refused,
+            ..Default::default()
+        };
+        let result = run_wipe_batch(&ctx, writable.into_iter().map(Ok), 1, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(!result.success);
+        assert_eq!(result.failures.len(), 1);
+        assert_eq!(result.failures[0].error.code(), "volume_read_only");
+        assert_eq!(result.report.unwrap().files_wiped, 1);
+        assert!(!notes.exists());
+        assert!(photos.iter().all(|photo| fs::read(photo).unwrap() == b"photo"));
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn a_writable_directory_keeps_its_volume_in_the_batch() {
+        let dir = create_test_dir().unwrap();
+        let (locked, open) = (dir.join("locked"), dir.join("open"));
+        fs::create_dir_all(&locked).unwrap();
+        fs::create_dir_all(&open).unwrap();
+        let roots = vec![locked.to_string_lossy().to_string(), open.to_string_lossy().to_string()];
+
+        let (writable, refused) =
+            split_read_only(roots.clone(), &[comparable(&dir)], |dir| (dir == locked).then_some(WriteRefusal::ReadOnlyVolume));
+        assert_eq!(writable, roots);
+        assert!(refused.is_empty());
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn a_folder_this_user_may_not_write_in_is_access_denied_not_a_read_only_volume() {
+        let dir = create_test_dir().unwrap();
+        let (private, open) = (dir.join("private"), dir.join("open"));
+        fs::create_dir_all(&private).unwrap();
+        fs::create_dir_all(&open).unwrap();
+        let roots = vec![private.to_string_lossy().to_string(), open.to_string_lossy().to_string()];
+
+        let (writable, refused) = split_read_only(roots.clone(), &[comparable(&dir)], |dir| {
+            (dir == private).then_some(WriteRefusal::PermissionDenied)
+        });
+        assert_eq!(writable, [roots[1].clone()]);
+        assert_eq!(refused, [BitBurnError::AccessDenied { path: roots[0].clone() }]);
+
+        // Denied everywhere on the volume is still not a read-only volume.
+        let (writable, refused) = split_read_only(roots.clone(), &[comparable(&dir)], |_| Some(WriteRefusal::PermissionDenied));
+        assert!(writable.is_empty());
+        assert!(refused.iter().all(|error| error.code() == "access_denied"));
+        cleanup_test_dir(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_access_is_checked_without_writing_anything() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = create_test_dir().unwrap();
+        assert_eq!(write_refusal(&dir), None);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0, "nothing is created to find out");
+
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o555)).unwrap();
+        // Root ignores directory permissions, so only other users are refused.
+        let expected = if unsafe { libc::geteuid() } == 0 { None } else { Some(WriteRefusal::PermissionDenied) };
+        assert_eq!(write_refusal(&dir), expected);
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        cleanup_test_dir(dir);
+    }
+}
@@ -0,0 +1,407 @@
+//! Algorithm recommendation for the selected targets. `recommend` is a pure function over a
+//! `TargetProfile`, so the rules can be checked case by case; `recommend_algorithm` assembles one
+//! profile per volume from the drive, flash-media and encryption probes and merges the results.
+//! `recommend_tasks` keeps them apart instead, giving each path its own volume's recommendation
+//! as a mixed batch for `wipe_files`.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::capabilities::{self, DriveCapability, DriveMedia};
+use crate::context_policy::estimate_targets;
+use crate::copy_on_write::CowFileSystem;
+use crate::error::BitBurnError;
+use crate::flash_media;
+use crate::wipe_tasks::WipeTask;
+use crate::WipeAlgorithm;
+
+/// Above this many bytes on a spinning disk, Purge's three passes cost hours more than Clear
+/// without a practical gain on modern drives.
+pub const LARGE_TARGET_BYTES: u64 = 100 * 1024 * 1024 * 1024;
+
+/// File systems served by another machine.
+const NETWORK_FILE_SYSTEMS: &[&str] =
+    &["nfs", "nfs4", "cifs", "smbfs", "smb2", "smb3", "afpfs", "9p", "fuse.sshfs", "webdav"];
+
+/// What is known about where a set of targets is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetProfile {
+    pub media: DriveMedia,
+    /// Full-volume encryption; `None` when it could not be determined.
+    pub encrypted: Option<bool>,
+    pub file_system: String,
+    pub network: bool,
+    /// USB flash drive or memory card, see `flash_media::is_flash_media`.
+    pub flash: bool,
+    /// Bytes of the targets on this volume.
+    pub target_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Recommendation {
+    pub algorithm: WipeAlgorithm,
+    pub passes: u32,
+    pub reasons: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl Recommendation {
+    fn of(algorithm: WipeAlgorithm) -> Self {
+        Recommendation { passes: algorithm.default_passes(), algorithm, reasons: Vec::new(), warnings: Vec::new() }
+    }
+}
+
+/// Recommend an algorithm for targets stored as `profile` describes.
+pub fn recommend(profile: &TargetProfile) -> Recommendation {
+    let encrypted = profile.encrypted == Some(true);
+    let mut recommendation = if profile.network {
+        let mut r = Recommendation::of(WipeAlgorithm::NistClear);
+        r.reasons.push("Network share: the server decides where writes land, so extra passes only add traffic.".into());
+        r.warnings.push(
+            "Overwriting over the network cannot be verified on the server's disks; \
+             wipe on the server itself where possible."
+                .into(),
+        );
+        r
+    } else if profile.flash || profile.media == DriveMedia::Removable {
+        let mut r = Recommendation::of(WipeAlgorithm::NistClear);
+        r.reasons
+            .push("Flash drive or memory card: wear levelling remaps writes, so one pass is as good as many.".into());
+        if !encrypted {
+            r.warnings.push(
+                "Old copies can survive in remapped blocks; \
+                 wipe the whole device, or encrypt it and wipe its free space."
+                    .into(),
+            );
+        }
+        r
+    } else {
+        match profile.media {
+            DriveMedia::Ssd => {
+                let mut r = Recommendation::of(WipeAlgorithm::NistClear);
+                r.reasons.push(
+                    "SSD: wear levelling remaps writes, \
+                     so multiple passes add wear without reaching more of the old data."
+                        .into(),
+                );
+                if encrypted {
+                    r.reasons.push(
+                        "The volume is encrypted: old blocks only hold ciphertext. \
+                         Follow up with a free space wipe so TRIM can release them."
+                            .into(),
+                    );
+                } else {
+                    r.warnings.push(
+                        "Without full-volume encryption, old copies can survive in remapped blocks; \
+                         use the drive's secure erase for complete removal."
+                            .into(),
+                    );
+                }
+                r
+            }
+            DriveMedia::Hdd if encrypted => {
+                let mut r = Recommendation::of(WipeAlgorithm::NistClear);
+                r.reasons.push(
+                    "Hard disk with full-volume encryption: the sectors only hold ciphertext, so one pass is enough."
+                        .into(),
+                );
+                r
+            }
+            DriveMedia::Hdd if profile.target_bytes > LARGE_TARGET_BYTES => {
+                let mut r = Recommendation::of(WipeAlgorithm::NistClear);
+                r.reasons.push(
+                    "Hard disk: overwrites land in place, and one pass is enough for modern drives under NIST 800-88."
+                        .into(),
+                );
+                r.reasons.push("The selection is large; three passes would take about three times as long.".into());
+                r
+            }
+            DriveMedia::Hdd => {
+                let mut r = Recommendation::of(WipeAlgorithm::NistPurge);
+                r.reasons.push(
+                    "Hard disk: overwrites land in place, \
+                     and NIST 800-88 Purge's three passes cover each sector with different patterns."
+                        .into(),
+                );
+                r
+            }
+            // Removable media is handled with flash above.
+            DriveMedia::Unknown | DriveMedia::Removable => {
+                let mut r = Recommendation::of(WipeAlgorithm::NistPurge);
+                r.reasons.push("The drive type could not be detected; NIST 800-88 Purge is the safe default.".into());
+                r
+            }
+        }
+    };
+
+    if CowFileSystem::from_name(&profile.file_system).is_some() {
+        let encryption = if encrypted {
+            "though the volume's encryption keeps what survives unreadable"
+        } else {
+            "or turn on full-disk encryption so what survives is unreadable"
+        };
+        recommendation.warnings.push(format!(
+            "{} is copy-on-write: overwrites go to new blocks and the original data can survive; \
+             wipe the drive's free space afterwards, {}.",
+            profile.file_system, encryption
+        ));
+    }
+    recommendation
+}
+
+/// Merge the recommendations for several volumes: the one with the most passes wins, and every
+/// reason and warning is kept once.
+pub fn combine(recommendations: Vec<Recommendation>) -> Option<Recommendation> {
+    let mut merged = recommendations.iter().max_by_key(|r| r.passes)?.clone();
+    merged.reasons.clear();
+    merged.warnings.clear();
+    for recommendation in recommendations {
+        for reason in recommendation.reasons {
+            if !merged.reasons.contains(&reason) {
+                merged.reasons.push(reason);
+            }
+        }
+        for warning in recommendation.warnings {
+            if !merged.warnings.contains(&warning) {
+                merged.warnings.push(warning);
+            }
+        }
+    }
+    Some(merged)
+}
+
+/// Whether the block device holding `path` is a dm-crypt (LUKS) mapping.
+#[cfg(target_os = "linux")]
+fn volume_encrypted(path: &Path) -> Option<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dev = std::fs::metadata(path).ok()?.dev();
+    let sys = format!("/sys/dev/block/{}:{}/dm/uuid", libc::major(dev), libc::minor(dev));
+    match std::fs::read_to_string(sys) {
+        Ok(uuid) => Some(uuid.starts_with("CRYPT-")),
+        // Not a device-mapper device, so not dm-crypt; other encryption goes unnoticed.
+        Err(_) => None,
+    }
+}
+
+/// No encryption probe exists for this platform yet.
+#[cfg(not(target_os = "linux"))]
+fn volume_encrypted(_path: &Path) -> Option<bool> {
+    None
+}
+
+fn is_network_path(path: &str, drive: Option<&DriveCapability>) -> bool {
+    path.starts_with("\\\\")
+        || drive.is_some_and(|drive| NETWORK_FILE_SYSTEMS.contains(&drive.file_system.to_ascii_lowercase().as_str()))
+}
+
+/// A task per path carrying the recommendation for its volume, in the order of `paths`.
+/// `volumes` pairs the paths on each volume with what was recommended there.
+pub fn tasks_for(paths: &[String], volumes: &[(Vec<String>, Recommendation)]) -> Vec<WipeTask> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let (_, recommendation) = volumes.iter().find(|(on_volume, _)| on_volume.contains(path))?;
+            Some(WipeTask { path: path.clone(), algorithm: recommendation.algorithm.clone(), passes: recommendation.passes })
+        })
+        .collect()
+}
+
+/// One profile per volume the paths are stored on.
+fn profiles_for(paths: &[String], drives: &[DriveCapability]) -> Vec<TargetProfile> {
+    volumes_for(paths, drives).into_iter().map(|(_, profile)| profile).collect()
+}
+
+/// The paths on each volume, with the volume's profile.
+fn volumes_for(paths: &[String], drives: &[DriveCapability]) -> Vec<(Vec<String>, TargetProfile)> {
+    let mut by_volume: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for path in paths {
+        let volume = if path.starts_with("\\\\") {
+            path.clone()
+        } else {
+            capabilities::drive_holding(drives, path).map(|drive| drive.mount_point.clone()).unwrap_or_default()
+        };
+        by_volume.entry(volume).or_default().push(path.clone());
+    }
+
+    by_volume
+        .into_values()
+        .map(|paths| {
+            let first = Path::new(&paths[0]);
+            let drive = capabilities::drive_holding(drives, &paths[0]);
+            let network = is_network_path(&paths[0], drive);
+            let profile = TargetProfile {
+                media: drive.map(|drive| drive.media).unwrap_or(DriveMedia::Unknown),
+                encrypted: if network { None } else { volume_encrypted(first) },
+                file_system: drive.map(|drive| drive.file_system.clone()).unwrap_or_default(),
+                network,
+                flash: !network
+                    && flash_media::device_descriptor(first).as_ref().is_some_and(flash_media::is_flash_media),
+                target_bytes: estimate_targets(&paths, false).bytes,
+            };
+            (paths, profile)
+        })
+        .collect()
+}
+
+/// Recommend an algorithm and pass count for wiping `paths`, which may be files, folders or a
+/// drive root, with the reasons behind it and anything overwriting cannot guarantee there.
+#[tauri::command]
+pub async fn recommend_algorithm(paths: Vec<String>) -> Result<Recommendation, BitBurnError> {
+    capabilities::run_blocking(move || {
+        // Without the drive list every target is profiled as an unknown drive.
+        let drives = capabilities::list_drives().unwrap_or_default();
+        let recommendations = profiles_for(&paths, &drives).iter().map(recommend).collect();
+        Ok(combine(recommendations).unwrap_or_else(|| Recommendation::of(WipeAlgorithm::NistPurge)))
+    })
+    .await
+}
+
+/// `recommend_algorithm` for each volume of `paths` on its own: a task per path with the
+/// algorithm and passes recommended for its volume, ready for `wipe_files`.
+#[tauri::command]
+pub async fn recommend_tasks(paths: Vec<String>) -> Result<Vec<WipeTask>, BitBurnError> {
+    capabilities::run_blocking(move || {
+        let drives = capabilities::list_drives().unwrap_or_default();
+        let volumes: Vec<_> =
+            volumes_for(&paths, &drives).into_iter().map(|(on_volume, profile)| (on_volume, recommend(&profile))).collect();
+        Ok(tasks_for(&paths, &volumes))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(media: DriveMedia) -> TargetProfile {
+        TargetProfile {
+            media,
+            encrypted: None,
+            file_system: "ntfs".to_string(),
+            network: false,
+            flash: false,
+            target_bytes: 1024 * 1024,
+        }
+    }
+
+    #[test]
+    fn recommendations_are_stable_across_the_profile_matrix() {
+        use WipeAlgorithm::{NistClear, NistPurge};
+
+        let large = LARGE_TARGET_BYTES + 1;
+        // (media, encrypted, network, flash, target_bytes) => (algorithm, reasons, warnings)
+        let matrix = [
+            ((DriveMedia::Ssd, None, false, false, 0), (NistClear, 1, 1)),
+            ((DriveMedia::Ssd, Some(false), false, false, 0), (NistClear, 1, 1)),
+            ((DriveMedia::Ssd, Some(true), false, false, 0), (NistClear, 2, 0)),
+            ((DriveMedia::Ssd, Some(true), false, false, large), (NistClear, 2, 0)),
+            ((DriveMedia::Hdd, None, false, false, 0), (NistPurge, 1, 0)),
+            ((DriveMedia::Hdd, Some(false), false, false, 0), (NistPurge, 1, 0)),
+            ((DriveMedia::Hdd, Some(true), false, false, 0), (NistClear, 1, 0)),
+            ((DriveMedia::Hdd, None, false, false, large), (NistClear, 2, 0)),
+            ((DriveMedia::Hdd, Some(true), false, false, large), (NistClear, 1, 0)),
+            ((DriveMedia::Unknown, None, false, false, 0), (NistPurge, 1, 0)),
+            ((DriveMedia::Unknown, None, false, false, large), (NistPurge, 1, 0)),
+            ((DriveMedia::Removable, None, false, false, 0), (NistClear, 1, 1)),
+            ((DriveMedia::Removable, Some(true), false, false, 0), (NistClear, 1, 0)),
+            ((DriveMedia::Hdd, None, false, true, 0), (NistClear, 1, 1)),
+            ((DriveMedia::Ssd, None, false, true, 0), (NistClear, 1, 1)),
+            ((DriveMedia::Unknown, None, true, false, 0), (NistClear, 1, 1)),
+            ((DriveMedia::Hdd, None, true, true, large), (NistClear, 1, 1)),
+        ];
+        for ((media, encrypted, network, flash, target_bytes), (algorithm, reasons, warnings)) in matrix {
+            let profile = TargetProfile { encrypted, network, flash, target_bytes, ..profile(media) };
+            let recommendation = recommend(&profile);
+            assert_eq!(recommendation.algorithm, algorithm, "{:?}", profile);
+            assert_eq!(recommendation.passes, algorithm.default_passes(), "{:?}", profile);
+            assert_eq!(recommendation.reasons.len(), reasons, "{:?}: {:?}", profile, recommendation.reasons);
+            assert_eq!(recommendation.warnings.len(), warnings, "{:?}: {:?}", profile, recommendation.warnings);
+            assert_eq!(recommend(&profile), recommendation, "recommendations are deterministic");
+        }
+    }
+
+    #[test]
+    fn copy_on_write_file_systems_add_a_warning() {
+        for file_system in ["btrfs", "APFS", "ReFS", "zfs"] {
+            let profile = TargetProfile { file_system: file_system.to_string(), ..profile(DriveMedia::Hdd) };
+            let recommendation = recommend(&profile);
+            assert_eq!(recommendation.algorithm, WipeAlgorithm::NistPurge);
+            assert_eq!(recommendation.warnings.len(), 1, "{}", file_system);
+            assert!(recommendation.warnings[0].starts_with(file_system));
+            assert!(recommendation.warnings[0].contains("full-disk encryption"));
+        }
+        for file_system in ["ntfs", "ext4", "exfat", ""] {
+            let profile = TargetProfile { file_system: file_system.to_string(), ..profile(DriveMedia::Hdd) };
+            let recommendation = recommend(&profile);
+            assert!(recommendation.warnings.is_empty(), "{}", file_system);
+        }
+    }
+
+    #[test]
+    fn combining_volumes_keeps_the_strongest_and_every_note_once() {
+        let ssd = recommend(&profile(DriveMedia::Ssd));
+        let hdd = recommend(&profile(DriveMedia::Hdd));
+        let combined = combine(vec![ssd.clone(), hdd.clone(), ssd.clone()]).unwrap();
+
+        assert_eq!((combined.algorithm, combined.passes), (WipeAlgorithm::NistPurge, 3));
+        assert_eq!(combined.reasons, [ssd.reasons.clone(), hdd.reasons].concat());
+        assert_eq!(combined.warnings, ssd.warnings);
+        assert_eq!(combine(Vec::new()), None);
+    }
+
+    #[test]
+    fn each_path_gets_the_recommendation_for_its_own_volume() {
+        let ssd = recommend(&profile(DriveMedia::Ssd));
+        let hdd = recommend(&profile(DriveMedia::Hdd));
+        let paths: Vec<String> = ["C:\\work\\notes.txt", "E:\\archive", "C:\\work\\draft.docx"].map(String::from).to_vec();
+        let volumes = vec![(vec![paths[0].clone(), paths[2].clone()], ssd), (vec![paths[1].clone()], hdd)];
+
+        let tasks = tasks_for(&paths, &volumes);
+        let planned: Vec<_> = tasks.iter().map(|task| (task.path.as_str(), task.algorithm.clone(), task.passes)).collect();
+        assert_eq!(
+            planned,
+            [
+                ("C:\\work\\notes.txt", WipeAlgorithm::NistClear, 1),
+                ("E:\\archive", WipeAlgorithm::NistPurge, 3),
+                ("C:\\work\\draft.docx", WipeAlgorithm::NistClear, 1),
+            ]
+        );
+        assert!(crate::wipe_tasks::validate(&tasks).is_ok());
+    }
+
+    #[test]
+    fn paths_are_grouped_by_the_deepest_drive_holding_them() {
+        let drive = |mount_point: &str, media, file_system: &str| DriveCapability {
+            mount_point: mount_point.to_string(),
+            name: String::new(),
+            file_system: file_system.to_string(),
+            media,
+            total_bytes: 0,
+            available_bytes: 0,
+            last_free_space_wipe: None,
+        };
+        let drives = [
+            drive("/", DriveMedia::Ssd, "ext4"),
+            drive("/mnt/archive", DriveMedia::Hdd, "ext4"),
+            drive("/mnt/share", DriveMedia::Unknown, "cifs"),
+        ];
+        let paths: Vec<String> = ["/mnt/archive/a", "/home/me/b", "/mnt/archive/c", "/mnt/share/d", "\\\\server\\e"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+
+        let profiles = profiles_for(&paths, &drives);
+        let summary: Vec<_> = profiles.iter().map(|p| (p.media, p.file_system.as_str(), p.network)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (DriveMedia::Ssd, "ext4", false),
+                (DriveMedia::Hdd, "ext4", false),
+                (DriveMedia::Unknown, "cifs", true),
+                (DriveMedia::Unknown, "", true),
+            ]
+        );
+    }
+}
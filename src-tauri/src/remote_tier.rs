@@ -0,0 +1,130 @@
+//! Files whose data lives in remote or archive storage: HSM-migrated files, OneDrive and other
+//! cloud placeholders, macOS dataless files. Opening one recalls its data, so overwriting it
+//! downloads the whole file and destroys only the local copy while the remote one stays. Batches
+//! refuse them before they are opened unless the user allows the recall, and then say that the
+//! remote copy may persist.
+
+use serde_json::json;
+use std::fs::Metadata;
+use std::path::Path;
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::messages;
+use crate::report::WipeReport;
+
+/// The data has been moved to offline storage, e.g. by hierarchical storage management.
+pub const FILE_ATTRIBUTE_OFFLINE: u32 = 0x0000_1000;
+/// Opening the file recalls it; set on files whose whole contents are remote.
+pub const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x0004_0000;
+/// Reading the file's data recalls it; set on cloud placeholders that are not yet downloaded.
+pub const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x0040_0000;
+
+const REMOTE_ATTRIBUTES: u32 =
+    FILE_ATTRIBUTE_OFFLINE | FILE_ATTRIBUTE_RECALL_ON_OPEN | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS;
+
+/// The file's attributes, as the Windows `FILE_ATTRIBUTE_*` flags above. Reading them does not
+/// recall anything.
+#[cfg(windows)]
+pub fn attributes(metadata: &Metadata) -> u32 {
+    use std::os::windows::fs::MetadataExt;
+    metadata.file_attributes()
+}
+
+/// macOS marks files evicted to iCloud as dataless, the same situation as a recall-on-access placeholder.
+#[cfg(target_os = "macos")]
+pub fn attributes(metadata: &Metadata) -> u32 {
+    use std::os::macos::fs::MetadataExt;
+    /// `SF_DATALESS` in `st_flags`.
+    const SF_DATALESS: u32 = 0x4000_0000;
+    if metadata.st_flags() & SF_DATALESS != 0 {
+        FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS
+    } else {
+        0
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+pub fn attributes(_metadata: &Metadata) -> u32 {
+    0
+}
+
+/// Whether a file with `attributes` has its data in remote or archive storage.
+pub fn is_remote(attributes: u32) -> bool {
+    attributes & REMOTE_ATTRIBUTES != 0
+}
+
+/// Whether a file with `attributes` may be wiped: `Ok(true)` if that recalls its data first,
+/// which only `allow_recall` permits.
+pub fn decide(path: &Path, attributes: u32, allow_recall: bool) -> Result<bool, BitBurnError> {
+    match (is_remote(attributes), allow_recall) {
+        (false, _) => Ok(false),
+        (true, true) => Ok(true),
+        (true, false) => Err(BitBurnError::RemoteTieredFile { path: path.to_string_lossy().to_string() }),
+    }
+}
+
+/// `decide` for the file at `path` as it is now. A file whose attributes cannot be read is
+/// treated as local; opening it will fail on its own if it is gone.
+pub fn check(path: &Path, allow_recall: bool) -> Result<bool, BitBurnError> {
+    let attributes = std::fs::symlink_metadata(path).map(|metadata| attributes(&metadata)).unwrap_or(0);
+    let decided = decide(path, attributes, allow_recall);
+    if let Err(e) = &decided {
+        log_event("remote_tiered_file_refused", json!({"path": path.to_string_lossy(), "code": e.code()}));
+    }
+    decided
+}
+
+/// Mark the outcome just recorded for `path` as recalled and warn that a remote copy may persist.
+pub fn mark_recalled(report: &mut WipeReport, path: &Path) {
+    let path = path.to_string_lossy().to_string();
+    if let Some(outcome) = report.files.last_mut().filter(|outcome| outcome.path == path) {
+        outcome.recalled_from_remote = true;
+    }
+    log_event("remote_tiered_file_recalled", json!({"path": path}));
+    report.warnings.push(messages::REMOTE_COPY_MAY_PERSIST.render(json!({"path": path})).text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context_policy::TargetEstimate;
+
+    /// Attribute sets seen on real files.
+    const ARCHIVE: u32 = 0x20;
+    const HSM_STUB: u32 = ARCHIVE | FILE_ATTRIBUTE_OFFLINE;
+    const ONEDRIVE_ONLINE_ONLY: u32 = ARCHIVE | 0x0010_0000 | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS;
+    const ONEDRIVE_ALWAYS_KEEP: u32 = ARCHIVE | 0x0008_0000;
+    const ARCHIVE_TIER: u32 = FILE_ATTRIBUTE_RECALL_ON_OPEN;
+
+    #[test]
+    fn only_files_with_remote_data_are_refused_without_the_override() {
+        let path = Path::new("C:\\Users\\me\\OneDrive\\tax-2023.pdf");
+        for local in [0, ARCHIVE, ONEDRIVE_ALWAYS_KEEP] {
+            assert_eq!(decide(path, local, false), Ok(false), "{:#x}", local);
+        }
+        for remote in [HSM_STUB, ONEDRIVE_ONLINE_ONLY, ARCHIVE_TIER] {
+            assert_eq!(
+                decide(path, remote, false),
+                Err(BitBurnError::RemoteTieredFile { path: path.to_string_lossy().to_string() }),
+                "{:#x}",
+                remote
+            );
+            assert_eq!(decide(path, remote, true), Ok(true), "{:#x}", remote);
+        }
+    }
+
+    #[test]
+    fn estimates_leave_out_remote_files_unless_recall_is_allowed() {
+        let files = [(ARCHIVE, 100), (HSM_STUB, 4000), (ONEDRIVE_ALWAYS_KEEP, 20), (ONEDRIVE_ONLINE_ONLY, 900)];
+        let estimate = |allow_recall| {
+            let mut estimate = TargetEstimate::default();
+            for (attributes, len) in files {
+                estimate.add(attributes, len, allow_recall);
+            }
+            estimate
+        };
+        assert_eq!(estimate(false), TargetEstimate { files: 2, bytes: 120, remote_tiered: 2 });
+        assert_eq!(estimate(true), TargetEstimate { files: 4, bytes: 5020, remote_tiered: 0 });
+    }
+}
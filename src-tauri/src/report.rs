@@ -0,0 +1,362 @@
+use bitburn_core::{DirectoryOutcome, FileWipeProgressRecord, Verification, WipeAlgorithm};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::cancelled_fill::CancelledFill;
+use crate::consent::Consent;
+use crate::copy_on_write::CowFileSystem;
+use crate::decoy::DecoySummary;
+use crate::error::BitBurnError;
+use crate::memory_budget::MemoryConstraint;
+use crate::named_streams::NamedStreams;
+use crate::free_space::FreeSpaceCoverage;
+use crate::free_space_sample::SampleSummary;
+use crate::outcomes::SpilledOutcomes;
+use crate::platform::shadow_copies::ShadowCopyReport;
+use crate::search_index::SearchIndexPurge;
+use crate::symlinks::LinkRecord;
+
+/// Summary of a finished batch, attached to the `WipeResult` returned to the caller.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WipeReport {
+    pub operation_id: String,
+    /// Where the targets came from when not picked interactively, e.g. `manifest:flagged.csv`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The operation whose failed files this one retried with `retry_failed`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_of: Option<String>,
+    /// Later operations that retried this one's failed files.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub retried_by: Vec<String>,
+    pub files_wiped: usize,
+    /// Paths that were wiped; sent to webhooks only when the file list is requested.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub wiped_files: Vec<String>,
+    /// Entries that were rejected by sanitization and never attempted.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<BitBurnError>,
+    /// Why each failed file or root failed, as in the `failures` of the result.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<BitBurnError>,
+    /// Every selected path in selection order, with the outcomes of the files under it.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<RootReport>,
+    /// One entry per directory root whose files were wiped.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub directories: Vec<DirectoryOutcome>,
+    /// Directory roots that were emptied but kept, with what was removed from inside them.
+    /// Roots not listed here were removed along with their contents.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub preserved_roots: Vec<PreservedRoot>,
+    /// Files and folders whose named data streams were overwritten and deleted before they were removed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub named_streams: Vec<NamedStreams>,
+    /// How far every file the batch tried to overwrite got, whatever became of it. Empty, along
+    /// with `wiped_files` and the files of each root, when the batch spilled them to `outcomes`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileWipeOutcome>,
+    /// Batches of more than `chunk_files` files: the outcomes written to disk as the batch ran,
+    /// paged through with `get_operation_outcomes`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcomes: Option<SpilledOutcomes>,
+    /// Files left partially overwritten by a cancellation or error; they still exist on disk.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub partially_wiped: Vec<PartialWipe>,
+    /// Files whose contents were destroyed but whose wipe was then cancelled or failed, and where they ended up.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub interrupted: Vec<InterruptedFile>,
+    /// Free-space wipes only: whether the volume had shadow copies and what was done about them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shadow_copies: Option<ShadowCopyReport>,
+    /// Free-space wipes only: bytes filled against the volume's free space.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub free_space: Option<FreeSpaceCoverage>,
+    /// Free-space wipes only: the last `sample_free_space` of the path before the wipe, to compare
+    /// with one taken after it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub free_space_sample: Option<SampleSummary>,
+    /// Free-space wipes asked for from a folder: the folder, whose volume's free space was wiped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub free_space_origin: Option<String>,
+    /// Free-space wipes cancelled during the fill: what was written and what was done with it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancelled_fill: Option<CancelledFill>,
+    /// What the user was shown before the wipe and when they confirmed it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consent: Option<Consent>,
+    /// The decoy data written last, identified by its hash.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoy: Option<DecoySummary>,
+    /// How passes were read back, and the seed that picks a sampled pass's chunks again.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification: Option<Verification>,
+    /// With `purge_search_index` on: the folders Windows Search was asked to re-crawl.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub search_index: Vec<SearchIndexPurge>,
+    /// File batches only: where the batch's time went.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<OperationTimings>,
+    /// Set when the memory available at the start left the wipe a smaller buffer than usual.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_constrained: Option<MemoryConstraint>,
+    /// Problems that did not stop the wipe, e.g. a directory timestamp that could not be restored.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Wall-clock milliseconds of a batch's phases.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationTimings {
+    /// Pre-flight checks: read-only volumes, flash media and the lock scan.
+    pub scan_ms: u64,
+    /// Walking and wiping the selection.
+    pub wipe_ms: u64,
+    /// Closing the journal, index purges and writing spilled outcomes after the last file.
+    pub cleanup_ms: u64,
+}
+
+/// One selected file or folder and what became of everything under it. The counts of all roots
+/// add up to the batch's own, apart from read-only and disconnected volumes, which are reported
+/// per volume.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RootReport {
+    /// Position in the selection, as in the `root_index` of progress events.
+    pub root_index: usize,
+    pub path: String,
+    /// Files the batch tried to overwrite, whatever became of them.
+    pub files_attempted: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Size of the files wiped in full.
+    pub bytes_wiped: u64,
+    /// Why the root was turned away before anything under it was touched, e.g. it was missing
+    /// or protected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<BitBurnError>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileWipeOutcome>,
+}
+
+/// Where each selected root's outcomes start in a batch's report, so they can be grouped under it
+/// once the batch ends. Everything a batch records between two roots belongs to the first.
+#[derive(Debug, Default)]
+pub struct RootGrouping {
+    starts: Vec<RootStart>,
+}
+
+#[derive(Debug)]
+struct RootStart {
+    root_index: usize,
+    path: String,
+    files: usize,
+    skipped: usize,
+    failures: usize,
+    error: Option<BitBurnError>,
+    /// Outcomes already handed to `spill`, by count.
+    spilled: OutcomeTally,
+}
+
+#[derive(Debug, Default)]
+struct OutcomeTally {
+    attempted: usize,
+    succeeded: usize,
+    bytes_wiped: u64,
+}
+
+impl OutcomeTally {
+    fn add<'a>(&mut self, files: impl Iterator<Item = &'a FileWipeOutcome>) {
+        for file in files.flat_map(FileWipeOutcome::with_companions) {
+            self.attempted += 1;
+            if file.status == FileWipeStatus::Success {
+                self.succeeded += 1;
+                self.bytes_wiped += file.passes.bytes_of_last_pass;
+            }
+        }
+    }
+}
+
+impl RootGrouping {
+    /// Start attributing to root `root_index`; `failures` is how many the batch has recorded so far.
+    pub fn begin(&mut self, root_index: usize, path: &str, report: &WipeReport, failures: usize) {
+        self.starts.push(RootStart {
+            root_index,
+            path: path.to_string(),
+            files: report.files.len(),
+            skipped: report.skipped.len(),
+            failures,
+            error: None,
+            spilled: OutcomeTally::default(),
+        });
+    }
+
+    /// Take every outcome the report holds, keeping their counts for `group`.
+    pub fn spill(&mut self, report: &mut WipeReport) -> Vec<FileWipeOutcome> {
+        let held = report.files.len();
+        for i in 0..self.starts.len() {
+            let end = self.starts.get(i + 1).map_or(held, |next| next.files);
+            let start = &mut self.starts[i];
+            start.spilled.add(report.files[start.files..end].iter());
+            start.files = 0;
+        }
+        std::mem::take(&mut report.files)
+    }
+
+    /// The current root was turned away as a whole.
+    pub fn reject(&mut self, error: &BitBurnError) {
+        if let Some(start) = self.starts.last_mut() {
+            start.error = Some(error.clone());
+        }
+    }
+
+    /// The roots with everything recorded under them, given the batch's final failure count.
+    pub fn group(&self, report: &WipeReport, failures: usize) -> Vec<RootReport> {
+        self.starts
+            .iter()
+            .enumerate()
+            .map(|(i, start)| {
+                let end = self.starts.get(i + 1);
+                let files = &report.files[start.files..end.map_or(report.files.len(), |next| next.files)];
+                let mut tally = OutcomeTally::default();
+                tally.add(files.iter());
+                RootReport {
+                    root_index: start.root_index,
+                    path: start.path.clone(),
+                    files_attempted: start.spilled.attempted + tally.attempted,
+                    succeeded: start.spilled.succeeded + tally.succeeded,
+                    failed: end.map_or(failures, |next| next.failures) - start.failures,
+                    skipped: end.map_or(report.skipped.len(), |next| next.skipped) - start.skipped,
+                    bytes_wiped: start.spilled.bytes_wiped + tally.bytes_wiped,
+                    error: start.error.clone(),
+                    files: files.to_vec(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// How a file's wipe ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileWipeStatus {
+    Success,
+    Error,
+    Cancelled,
+    Timeout,
+    /// Stopped by `skip_current_file` and deleted without its remaining passes.
+    SkippedByUser,
+}
+
+/// The passes a file received, recorded for every file a batch tried to overwrite.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileWipeOutcome {
+    pub path: String,
+    pub status: FileWipeStatus,
+    /// What the file was overwritten with, which in a mixed batch differs from root to root.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<WipeAlgorithm>,
+    /// On a USB flash drive or memory card, where the overwrite may not reach every copy of the data.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub flash_media: bool,
+    /// Its data was recalled from remote or archive storage to be wiped, so a remote copy may remain.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub recalled_from_remote: bool,
+    /// Stored on a copy-on-write file system, where earlier copies of the data may survive the overwrite.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cow_filesystem: Option<CowFileSystem>,
+    /// Whether Btrfs accepted NOCOW for the file before it was overwritten; only tried on Btrfs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nocow_applied: Option<bool>,
+    /// Access was denied until ownership was taken with `take_ownership`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ownership_taken: bool,
+    /// Owner before the takeover: a SID string on Windows, a uid on Unix.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_owner: Option<String>,
+    /// Reached through, or for `delete_link_only` only, a selected symbolic link.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<LinkRecord>,
+    /// Held open exclusively while it was wiped: unshared on Windows, under an advisory `flock` elsewhere.
+    pub exclusive_access: bool,
+    #[serde(flatten)]
+    pub passes: FileWipeProgressRecord,
+    /// Wall-clock time from opening the file to its removal, whatever became of it.
+    pub duration_ms: u64,
+    /// Average write rate over the passes this run completed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_per_second: Option<u64>,
+    /// Owner, lock, backup and swap files found beside this one and wiped with it.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub companions: Vec<FileWipeOutcome>,
+}
+
+impl FileWipeOutcome {
+    /// This file's outcome followed by those of its companions.
+    pub fn with_companions(&self) -> impl Iterator<Item = &FileWipeOutcome> {
+        std::iter::once(self).chain(&self.companions)
+    }
+}
+
+/// A selected folder left in place, with its permissions and attributes untouched, after
+/// everything inside it was wiped and removed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PreservedRoot {
+    pub path: String,
+    pub subdirectories_removed: usize,
+    /// Entries the wipe left behind (subdirectories, links) that were removed from inside the folder.
+    pub entries_removed: usize,
+}
+
+/// A file whose wipe stopped after it had started overwriting data.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PartialWipe {
+    pub path: String,
+    /// Bytes written in `last_pass`; every earlier pass covered the whole file.
+    pub bytes_overwritten: u64,
+    /// 1-based pass that was running when the wipe stopped.
+    pub last_pass: u32,
+}
+
+/// Final state of a file interrupted after its last overwrite pass.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "disposition", rename_all = "snake_case")]
+pub enum FileDisposition {
+    /// Deletion was completed despite the cancellation.
+    Deleted,
+    /// Still on disk under its original name, with its contents destroyed.
+    Restored,
+    /// Still on disk under another name, which the original name could not be restored from.
+    LeftOnDisk { on_disk_path: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InterruptedFile {
+    /// Path the file had before the wipe started.
+    pub path: String,
+    #[serde(flatten)]
+    pub disposition: FileDisposition,
+}
+
+impl WipeReport {
+    pub fn new(operation_id: impl Into<String>, source: Option<String>) -> Self {
+        WipeReport {
+            operation_id: operation_id.into(),
+            source,
+            ..Default::default()
+        }
+    }
+
+    /// Move the outcomes of `companions`, recorded after the last outcome for `parent`, under it.
+    pub fn nest_companions(&mut self, parent: &str, companions: &[PathBuf]) {
+        let Some(at) = self.files.iter().rposition(|file| file.path == parent) else {
+            return;
+        };
+        let (nested, kept): (Vec<_>, Vec<_>) = self
+            .files
+            .drain(at + 1..)
+            .partition(|file| companions.iter().any(|companion| companion.to_string_lossy() == file.path));
+        self.files.extend(kept);
+        self.files[at].companions.extend(nested);
+    }
+}
@@ -0,0 +1,721 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime};
+use walkdir::WalkDir;
+
+use crate::diagnostics;
+use crate::error::BitBurnError;
+use crate::messages::{self, Message};
+use crate::operations::{OperationGuard, OperationKind, OperationRegistry};
+use crate::protected::ProtectedPaths;
+use crate::settings::SettingsStore;
+use crate::{log_event, run_wipe_batch, BatchContext, BatchOptions, WipeAlgorithm};
+
+/// How often the background task evaluates the rules.
+pub const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Grace period used when the settings do not override it.
+pub const DEFAULT_GRACE_MINUTES: u32 = 10;
+/// How often a pending run re-checks for cancellation while waiting out its grace period.
+const GRACE_POLL: Duration = Duration::from_secs(1);
+/// How often the background task looks at the clock while waiting for the next pass, so a pass
+/// missed while the machine slept runs soon after it wakes.
+const SCHEDULE_POLL: Duration = Duration::from_secs(60);
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Wipe files in `folder` whose modification time is older than `max_age_days`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionRule {
+    pub folder: String,
+    pub max_age_days: u32,
+    pub algorithm: WipeAlgorithm,
+    #[serde(default)]
+    pub recursive: bool,
+}
+
+impl RetentionRule {
+    /// Reject rules that could never run safely; the folder itself need not exist yet.
+    pub fn validate(&self, protected: &ProtectedPaths) -> Result<(), BitBurnError> {
+        let invalid = |message: &str| BitBurnError::InvalidRetentionRule {
+            folder: self.folder.clone(),
+            message: message.to_string(),
+        };
+
+        if self.max_age_days == 0 {
+            return Err(invalid("max_age_days must be at least 1"));
+        }
+        let folder = Path::new(&self.folder);
+        if !folder.is_absolute() {
+            return Err(invalid("folder must be an absolute path"));
+        }
+        protected.check(folder)
+    }
+
+    /// Files last modified before this instant are due for wiping.
+    pub fn cutoff(&self, now: SystemTime) -> SystemTime {
+        now.checked_sub(Duration::from_secs(u64::from(self.max_age_days) * SECONDS_PER_DAY))
+            .unwrap_or(UNIX_EPOCH)
+    }
+}
+
+/// Source of time for the scheduler, injectable so tests do not sleep.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+    fn sleep(&self, duration: Duration);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// Expired files selected for one rule.
+#[derive(Debug, Clone)]
+pub struct RetentionBatch {
+    pub rule: RetentionRule,
+    pub files: Vec<PathBuf>,
+    pub total_bytes: u64,
+}
+
+/// Files whose modification time is before `cutoff`. Symlinks are never
+/// followed, so nothing outside `folder` can be selected, and protected paths are skipped.
+pub fn select_expired(
+    folder: &Path,
+    recursive: bool,
+    cutoff: SystemTime,
+    protected: &ProtectedPaths,
+) -> Result<Vec<(PathBuf, u64)>, BitBurnError> {
+    let metadata = std::fs::symlink_metadata(folder).map_err(|e| BitBurnError::io(&e, Some(folder)))?;
+    if metadata.file_type().is_symlink() {
+        return Err(BitBurnError::SymlinkNotSupported {
+            path: folder.to_string_lossy().to_string(),
+        });
+    }
+    protected.check(folder)?;
+
+    let max_depth = if recursive { usize::MAX } else { 1 };
+    let mut expired: Vec<(PathBuf, u64)> = WalkDir::new(folder)
+        .follow_links(false)
+        .min_depth(1)
+        .max_depth(max_depth)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| !protected.is_protected(entry.path()))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata.modified().ok()?;
+            (modified < cutoff).then(|| (entry.into_path(), metadata.len()))
+        })
+        .collect();
+    expired.sort();
+    Ok(expired)
+}
+
+/// Evaluate every rule at `now`. Rules whose folder cannot be scanned are reported, not fatal.
+pub fn plan_run(
+    rules: &[RetentionRule],
+    now: SystemTime,
+    protected: &ProtectedPaths,
+) -> (Vec<RetentionBatch>, Vec<BitBurnError>) {
+    let mut batches = Vec::new();
+    let mut rejected = Vec::new();
+
+    for rule in rules {
+        let selected = rule.validate(protected).and_then(|_| {
+            select_expired(Path::new(&rule.folder), rule.recursive, rule.cutoff(now), protected)
+        });
+        match selected {
+            Ok(files) if files.is_empty() => {}
+            Ok(files) => batches.push(RetentionBatch {
+                rule: rule.clone(),
+                total_bytes: files.iter().map(|(_, len)| len).sum(),
+                files: files.into_iter().map(|(path, _)| path).collect(),
+            }),
+            Err(err) => rejected.push(err),
+        }
+    }
+
+    (batches, rejected)
+}
+
+/// Whether `path` is still a regular file older than `cutoff`; files touched during
+/// the grace period are left alone.
+fn still_expired(path: &Path, cutoff: SystemTime) -> bool {
+    std::fs::symlink_metadata(path)
+        .ok()
+        .filter(|metadata| metadata.is_file())
+        .and_then(|metadata| metadata.modified().ok())
+        .is_some_and(|modified| modified < cutoff)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionRunState {
+    /// Waiting out the grace period; `cancel_retention_run` can still abort it.
+    Pending,
+    Cancelled,
+    Completed,
+    Failed,
+    NothingToWipe,
+}
+
+/// Summary of the latest evaluation, also sent with the `retention_run_pending` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionRunStatus {
+    pub state: RetentionRunState,
+    pub evaluated_at: u64,
+    /// When the pending wipe starts (seconds since the Unix epoch).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wipe_at: Option<u64>,
+    pub file_count: usize,
+    pub total_bytes: u64,
+    pub folders: Vec<String>,
+    #[serde(flatten)]
+    pub message: Message,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub operation_ids: Vec<String>,
+    /// Rules that could not be evaluated (missing folder, symlink, protected path).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rule_errors: Vec<BitBurnError>,
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub enum GraceOutcome {
+    Elapsed,
+    Cancelled,
+}
+
+/// Managed state shared by the background task and the retention commands.
+#[derive(Default)]
+pub struct RetentionState {
+    last_run: Mutex<Option<RetentionRunStatus>>,
+    pending: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+impl RetentionState {
+    pub fn last_run(&self) -> Option<RetentionRunStatus> {
+        self.last_run.lock().unwrap_or_else(|p| p.into_inner()).clone()
+    }
+
+    fn record(&self, status: RetentionRunStatus) {
+        *self.last_run.lock().unwrap_or_else(|p| p.into_inner()) = Some(status);
+    }
+
+    fn update_state(&self, state: RetentionRunState, message: Message) {
+        let mut last_run = self.last_run.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(status) = last_run.as_mut() {
+            status.state = state;
+            status.message = message;
+        }
+    }
+
+    fn attach_operations(&self, operation_ids: Vec<String>) {
+        if let Some(status) = self.last_run.lock().unwrap_or_else(|p| p.into_inner()).as_mut() {
+            status.operation_ids = operation_ids;
+        }
+    }
+
+    fn is_pending(&self) -> bool {
+        self.pending.lock().unwrap_or_else(|p| p.into_inner()).is_some()
+    }
+
+    /// Record `status` as pending and return the flag that cancels it.
+    fn begin_pending(&self, status: RetentionRunStatus) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        *self.pending.lock().unwrap_or_else(|p| p.into_inner()) = Some(flag.clone());
+        self.record(status);
+        flag
+    }
+
+    fn finish_pending(&self) {
+        *self.pending.lock().unwrap_or_else(|p| p.into_inner()) = None;
+    }
+
+    /// Abort the pending run, or the wipe it already started. Returns false when nothing is running.
+    pub fn cancel(&self) -> bool {
+        match self.pending.lock().unwrap_or_else(|p| p.into_inner()).as_ref() {
+            Some(flag) => {
+                flag.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Block until `grace` has elapsed on `clock` or the pending run is cancelled.
+    /// A cancelled run is recorded and cleared.
+    fn wait_for_grace(&self, clock: &dyn Clock, grace: Duration, cancelled: &AtomicBool) -> GraceOutcome {
+        let deadline = clock.now() + grace;
+        loop {
+            if cancelled.load(Ordering::SeqCst) {
+                self.update_state(RetentionRunState::Cancelled, messages::RETENTION_CANCELLED_BEFORE_START.text());
+                self.finish_pending();
+                return GraceOutcome::Cancelled;
+            }
+            let now = clock.now();
+            let Ok(remaining) = deadline.duration_since(now) else {
+                return GraceOutcome::Elapsed;
+            };
+            if remaining.is_zero() {
+                return GraceOutcome::Elapsed;
+            }
+            clock.sleep(remaining.min(GRACE_POLL));
+        }
+    }
+}
+
+/// Evaluate the configured rules once: announce what will be wiped, wait out the
+/// grace period, then wipe each rule's files as its own operation.
+pub fn run_retention_pass<R: Runtime>(app: &AppHandle<R>, clock: &dyn Clock) {
+    let (Some(settings), Some(state), Some(registry)) = (
+        app.try_state::<SettingsStore>(),
+        app.try_state::<RetentionState>(),
+        app.try_state::<OperationRegistry>(),
+    ) else {
+        return;
+    };
+
+    let settings = settings.get();
+    if settings.retention_rules.is_empty() || state.is_pending() {
+        return;
+    }
+
+    let now = clock.now();
+    let protected = ProtectedPaths::current();
+    let (batches, rule_errors) = plan_run(&settings.retention_rules, now, &protected);
+    let grace = settings.retention_grace_period();
+    let file_count: usize = batches.iter().map(|b| b.files.len()).sum();
+    let mut status = RetentionRunStatus {
+        state: RetentionRunState::NothingToWipe,
+        evaluated_at: unix_seconds(now),
+        wipe_at: None,
+        file_count,
+        total_bytes: batches.iter().map(|b| b.total_bytes).sum(),
+        folders: batches.iter().map(|b| b.rule.folder.clone()).collect(),
+        message: messages::RETENTION_IDLE.text(),
+        operation_ids: Vec::new(),
+        rule_errors,
+    };
+
+    if file_count == 0 {
+        log_event("retention_run_idle", json!({"rules": settings.retention_rules.len(), "rule_errors": status.rule_errors.len()}));
+        state.record(status);
+        return;
+    }
+
+    status.state = RetentionRunState::Pending;
+    status.wipe_at = Some(unix_seconds(now + grace));
+    status.message = messages::RETENTION_PENDING.render(json!({
+        "files": file_count,
+        "folders": status.folders.len(),
+        "minutes": grace.as_secs() / 60,
+    }));
+    let cancelled = state.begin_pending(status.clone());
+    log_event(
+        "retention_run_pending",
+        json!({"files": file_count, "bytes": status.total_bytes, "folders": status.folders, "grace_secs": grace.as_secs()}),
+    );
+    diagnostics::emit(app, "retention_run_pending", status);
+
+    if let GraceOutcome::Cancelled = state.wait_for_grace(clock, grace, &cancelled) {
+        log_event("retention_run_cancelled", json!({"files": file_count}));
+        return;
+    }
+
+    let mut operation_ids = Vec::new();
+    let mut wiped = 0;
+    let mut failed = false;
+    for batch in batches {
+        let cutoff = batch.rule.cutoff(now);
+        let files: Vec<String> = batch
+            .files
+            .iter()
+            .filter(|path| still_expired(path, cutoff))
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+
+        let operation = OperationGuard::begin_with_cancel(
+            app,
+            &registry,
+            OperationKind::WipeFiles,
+            format!("Retention: {} ({} files)", batch.rule.folder, files.len()),
+            cancelled.clone(),
+        );
+        let ctx = BatchContext::new(app.clone(), "main".to_string(), operation);
+        operation_ids.push(ctx.operation.id().to_string());
+
+        let _log_scope = ctx.operation.enter_log_scope();
+        let algorithm = batch.rule.algorithm.clone();
+        let file_total = files.len();
+        let result = run_wipe_batch(
+            &ctx,
+            files.into_iter().map(Ok),
+            file_total,
+            algorithm.default_passes(),
+            &algorithm,
+            BatchOptions {
+                source: Some(format!("retention:{}", batch.rule.folder)),
+                skip_in_use: true,
+                ..Default::default()
+            },
+        );
+        wiped += result.report.as_ref().map(|r| r.files_wiped).unwrap_or(0);
+        failed |= !result.success;
+        if cancelled.load(Ordering::SeqCst) {
+            break;
+        }
+    }
+
+    let (final_state, message) = if cancelled.load(Ordering::SeqCst) {
+        (RetentionRunState::Cancelled, messages::RETENTION_CANCELLED.render(json!({"wiped": wiped})))
+    } else if failed {
+        (RetentionRunState::Failed, messages::RETENTION_FAILED.render(json!({"wiped": wiped})))
+    } else {
+        (RetentionRunState::Completed, messages::RETENTION_COMPLETED.render(json!({"wiped": wiped})))
+    };
+    log_event("retention_run_end", json!({"state": final_state, "wiped": wiped, "operations": operation_ids}));
+    state.update_state(final_state, message);
+    state.attach_operations(operation_ids);
+    state.finish_pending();
+}
+
+/// What the background task does when it looks at the clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleStep {
+    /// Run a pass now.
+    pub run: bool,
+    /// Passes that fell due while the machine slept and are not made up, beyond the one run now.
+    pub missed: u64,
+    /// When the next pass is due.
+    pub next_due: SystemTime,
+}
+
+/// Decide, at `now`, about the pass due at `due` of a schedule repeating every `interval`, all in
+/// UTC. However many passes fell due while the machine slept, one runs and the rest are skipped;
+/// the next one keeps to the schedule. A clock set back by more than an interval moves the next
+/// pass to an interval from `now` rather than waiting for the old time to come round again.
+pub fn schedule_step(due: SystemTime, now: SystemTime, interval: Duration) -> ScheduleStep {
+    let interval_secs = interval.as_secs().max(1);
+    match now.duration_since(due) {
+        Ok(late) => {
+            let elapsed = late.as_secs() / interval_secs;
+            ScheduleStep {
+                run: true,
+                missed: elapsed,
+                next_due: due + Duration::from_secs((elapsed + 1) * interval_secs),
+            }
+        }
+        Err(early) if early.duration() > interval => ScheduleStep { run: false, missed: 0, next_due: now + interval },
+        Err(_) => ScheduleStep { run: false, missed: 0, next_due: due },
+    }
+}
+
+/// Look at `clock` once for the pass due at `due`, running it through `run` if it is due.
+/// Returns when the next pass is due.
+fn schedule_tick(clock: &dyn Clock, due: SystemTime, run: &mut dyn FnMut()) -> SystemTime {
+    let step = schedule_step(due, clock.now(), RETENTION_INTERVAL);
+    if step.missed > 0 {
+        log_event(
+            "retention_passes_missed",
+            json!({"missed": step.missed, "due_at": unix_seconds(due), "next_due_at": unix_seconds(step.next_due)}),
+        );
+    }
+    if step.run {
+        run();
+    }
+    step.next_due
+}
+
+/// Start the background task that evaluates retention rules every hour.
+pub fn spawn_scheduler<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let clock = SystemClock;
+        let mut due = clock.now();
+        loop {
+            due = schedule_tick(&clock, due, &mut || run_retention_pass(&app, &clock));
+            let wait = due.duration_since(clock.now()).unwrap_or_default();
+            clock.sleep(wait.min(SCHEDULE_POLL));
+        }
+    });
+}
+
+/// Return the configured retention rules.
+#[tauri::command]
+pub async fn list_retention_rules(settings: tauri::State<'_, SettingsStore>) -> Result<Vec<RetentionRule>, BitBurnError> {
+    Ok(settings.get().retention_rules)
+}
+
+/// Replace the retention rules and, when given, the grace period before a run starts wiping.
+#[tauri::command]
+pub async fn set_retention_rules(
+    settings: tauri::State<'_, SettingsStore>,
+    rules: Vec<RetentionRule>,
+    grace_period_minutes: Option<u32>,
+) -> Result<Vec<RetentionRule>, BitBurnError> {
+    let protected = ProtectedPaths::current();
+    for rule in &rules {
+        rule.validate(&protected)?;
+    }
+
+    let updated = settings.update(|s| {
+        s.retention_rules = rules;
+        if grace_period_minutes.is_some() {
+            s.retention_grace_minutes = grace_period_minutes;
+        }
+    })?;
+    log_event("retention_rules_updated", json!({"rules": updated.retention_rules.len()}));
+    Ok(updated.retention_rules)
+}
+
+/// Abort the pending (or running) retention wipe. Returns false when no run is pending.
+#[tauri::command]
+pub async fn cancel_retention_run(state: tauri::State<'_, RetentionState>) -> Result<bool, BitBurnError> {
+    let cancelled = state.cancel();
+    log_event("retention_run_cancel_requested", json!({"pending": cancelled}));
+    Ok(cancelled)
+}
+
+/// Status of the most recent retention evaluation, if any ran since launch.
+#[tauri::command]
+pub async fn get_last_retention_run(
+    state: tauri::State<'_, RetentionState>,
+) -> Result<Option<RetentionRunStatus>, BitBurnError> {
+    Ok(state.last_run())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::sync::atomic::AtomicU32;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    const DAY: Duration = Duration::from_secs(SECONDS_PER_DAY);
+
+    fn file_aged(path: &Path, modified: SystemTime) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, b"sensitive").unwrap();
+        File::options().write(true).open(path).unwrap().set_modified(modified).unwrap();
+    }
+
+    fn rule(folder: &Path, max_age_days: u32, recursive: bool) -> RetentionRule {
+        RetentionRule {
+            folder: folder.to_string_lossy().to_string(),
+            max_age_days,
+            algorithm: WipeAlgorithm::NistClear,
+            recursive,
+        }
+    }
+
+    /// Clock that advances only when slept on, optionally cancelling after a number of sleeps.
+    struct ManualClock {
+        now: Mutex<SystemTime>,
+        sleeps: AtomicU32,
+        cancel_after: Option<(u32, Arc<AtomicBool>)>,
+    }
+
+    impl ManualClock {
+        fn new(start: SystemTime, cancel_after: Option<(u32, Arc<AtomicBool>)>) -> Self {
+            ManualClock {
+                now: Mutex::new(start),
+                sleeps: AtomicU32::new(0),
+                cancel_after,
+            }
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> SystemTime {
+            *self.now.lock().unwrap()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            *self.now.lock().unwrap() += duration;
+            let count = self.sleeps.fetch_add(1, Ordering::SeqCst) + 1;
+            if let Some((after, flag)) = &self.cancel_after {
+                if count == *after {
+                    flag.store(true, Ordering::SeqCst);
+                }
+            }
+        }
+    }
+
+    fn pending_status() -> RetentionRunStatus {
+        RetentionRunStatus {
+            state: RetentionRunState::Pending,
+            evaluated_at: 0,
+            wipe_at: Some(600),
+            file_count: 1,
+            total_bytes: 9,
+            folders: vec!["/data".to_string()],
+            message: Message::default(),
+            operation_ids: Vec::new(),
+            rule_errors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn cutoff_selects_only_files_older_than_max_age() {
+        let dir = create_test_dir().unwrap();
+        let now = SystemTime::now();
+        file_aged(&dir.join("old.pdf"), now - DAY * 8);
+        file_aged(&dir.join("fresh.pdf"), now - DAY * 6);
+        file_aged(&dir.join("nested").join("old.txt"), now - DAY * 30);
+
+        let rule = rule(&dir, 7, false);
+        assert_eq!(rule.cutoff(now), now - DAY * 7);
+        let selected = select_expired(&dir, false, rule.cutoff(now), &ProtectedPaths::default()).unwrap();
+        assert_eq!(selected, vec![(dir.join("old.pdf"), 9)]);
+
+        let recursive = select_expired(&dir, true, rule.cutoff(now), &ProtectedPaths::default()).unwrap();
+        let paths: Vec<_> = recursive.into_iter().map(|(p, _)| p).collect();
+        assert_eq!(paths, vec![dir.join("nested").join("old.txt"), dir.join("old.pdf")]);
+        cleanup_test_dir(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_never_followed_out_of_the_folder() {
+        let dir = create_test_dir().unwrap();
+        let outside = create_test_dir().unwrap();
+        let now = SystemTime::now();
+        file_aged(&outside.join("keep.txt"), now - DAY * 100);
+        std::os::unix::fs::symlink(&outside, dir.join("escape")).unwrap();
+        std::os::unix::fs::symlink(outside.join("keep.txt"), dir.join("link.txt")).unwrap();
+
+        let selected = select_expired(&dir, true, now, &ProtectedPaths::default()).unwrap();
+        assert!(selected.is_empty());
+
+        let linked_root = select_expired(&dir.join("escape"), true, now, &ProtectedPaths::default());
+        assert_eq!(linked_root.unwrap_err().code(), "symlink_not_supported");
+        cleanup_test_dir(dir);
+        cleanup_test_dir(outside);
+    }
+
+    #[test]
+    fn protected_paths_are_excluded_and_rejected() {
+        let dir = create_test_dir().unwrap();
+        let now = SystemTime::now();
+        file_aged(&dir.join("vault").join("old.key"), now - DAY * 10);
+        file_aged(&dir.join("old.log"), now - DAY * 10);
+        let protected = ProtectedPaths::new(vec![dir.join("vault")], Vec::new());
+
+        let selected = select_expired(&dir, true, now - DAY, &protected).unwrap();
+        assert_eq!(selected, vec![(dir.join("old.log"), 9)]);
+
+        let (batches, rejected) = plan_run(&[rule(&dir.join("vault"), 1, true)], now, &protected);
+        assert!(batches.is_empty());
+        assert_eq!(rejected[0].code(), "protected_path");
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn plan_groups_files_per_rule_and_reports_bad_rules() {
+        let dir = create_test_dir().unwrap();
+        let now = SystemTime::now();
+        file_aged(&dir.join("a").join("1.bin"), now - DAY * 3);
+        file_aged(&dir.join("a").join("2.bin"), now - DAY * 3);
+        file_aged(&dir.join("b").join("3.bin"), now - DAY);
+
+        let rules = vec![
+            rule(&dir.join("a"), 2, false),
+            rule(&dir.join("b"), 2, false),
+            rule(&dir.join("missing"), 2, false),
+            rule(&dir, 0, false),
+        ];
+        let (batches, rejected) = plan_run(&rules, now, &ProtectedPaths::default());
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].files.len(), 2);
+        assert_eq!(batches[0].total_bytes, 18);
+        let codes: Vec<_> = rejected.iter().map(|e| e.code()).collect();
+        assert_eq!(codes, vec!["path_not_found", "invalid_retention_rule"]);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn grace_period_elapses_on_the_injected_clock() {
+        let state = RetentionState::default();
+        let flag = state.begin_pending(pending_status());
+        let start = UNIX_EPOCH + DAY * 1000;
+        let clock = ManualClock::new(start, None);
+
+        let outcome = state.wait_for_grace(&clock, Duration::from_secs(600), &flag);
+        assert!(matches!(outcome, GraceOutcome::Elapsed));
+        assert_eq!(clock.now(), start + Duration::from_secs(600));
+        assert!(state.is_pending());
+        assert_eq!(state.last_run().unwrap().state, RetentionRunState::Pending);
+    }
+
+    #[test]
+    fn cancelling_during_the_grace_period_aborts_the_run() {
+        let state = RetentionState::default();
+        let flag = state.begin_pending(pending_status());
+        let start = UNIX_EPOCH + DAY * 1000;
+        let clock = ManualClock::new(start, Some((120, flag.clone())));
+
+        let outcome = state.wait_for_grace(&clock, Duration::from_secs(600), &flag);
+        assert!(matches!(outcome, GraceOutcome::Cancelled));
+        assert_eq!(clock.now(), start + Duration::from_secs(120));
+        assert!(!state.is_pending());
+        assert_eq!(state.last_run().unwrap().state, RetentionRunState::Cancelled);
+        assert!(!state.cancel(), "nothing left to cancel");
+    }
+
+    #[test]
+    fn passes_missed_while_asleep_run_once_and_the_schedule_keeps_its_times() {
+        let start = UNIX_EPOCH + DAY * 1000;
+        let clock = ManualClock::new(start, None);
+        let runs = AtomicU32::new(0);
+        let mut run = || {
+            runs.fetch_add(1, Ordering::SeqCst);
+        };
+
+        let mut due = schedule_tick(&clock, start, &mut run);
+        assert_eq!((runs.load(Ordering::SeqCst), due), (1, start + RETENTION_INTERVAL));
+        clock.sleep(Duration::from_secs(59 * 60));
+        due = schedule_tick(&clock, due, &mut run);
+        assert_eq!(runs.load(Ordering::SeqCst), 1, "not due yet");
+
+        // Asleep for five hours and a bit: one pass on waking, the next back on the hour.
+        clock.sleep(Duration::from_secs(5 * 3600 + 600));
+        due = schedule_tick(&clock, due, &mut run);
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert_eq!(due, start + RETENTION_INTERVAL * 7);
+        let step = schedule_step(start + RETENTION_INTERVAL, start + Duration::from_secs(6 * 3600 - 60 + 600), RETENTION_INTERVAL);
+        assert_eq!(step.missed, 5);
+        due = schedule_tick(&clock, due, &mut run);
+        assert_eq!(runs.load(Ordering::SeqCst), 2, "the missed passes are not made up");
+
+        // The clock set back a day: the next pass is an interval away, not a day and an hour.
+        let set_back = clock.now() - DAY;
+        let step = schedule_step(due, set_back, RETENTION_INTERVAL);
+        assert_eq!(step, ScheduleStep { run: false, missed: 0, next_due: set_back + RETENTION_INTERVAL });
+    }
+
+    #[test]
+    fn cancel_command_flags_the_pending_run() {
+        let state = RetentionState::default();
+        assert!(!state.cancel());
+        let flag = state.begin_pending(pending_status());
+        assert!(state.cancel());
+        assert!(flag.load(Ordering::SeqCst));
+    }
+}
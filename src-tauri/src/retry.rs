@@ -0,0 +1,277 @@
+//! Retry the files a finished batch could not wipe, e.g. once the programs holding them are
+//! closed. The failures come from the operation's stored report; files that failed for a reason
+//! a retry cannot change, such as a missing path, are reported again as still failed unless the
+//! caller asks to retry them too; ones a protection rule refused are never retried. The paths come
+//! from a stored report, so they are sanitized again and confirmed like a new selection. The new
+//! operation's report names the one it retries (`retry_of`) and the original's names its retries
+//! (`retried_by`).
+
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashSet;
+use bitburn_core::SymlinkPolicy;
+use std::path::Path;
+use tauri::{Manager, Runtime, State};
+
+use crate::consent;
+use crate::error::BitBurnError;
+use crate::journal::JournalStore;
+use crate::messages;
+use crate::operations::OperationRegistry;
+use crate::protected;
+use crate::report::WipeReport;
+use crate::{log_event, start_file_wipe, BatchOptions, WipeAlgorithm, WipeResult};
+
+/// What a retry of an operation's failures will attempt and what it carries over.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RetryPlan {
+    /// Paths to wipe again, in the order they failed.
+    pub paths: Vec<String>,
+    /// Failures that are not retried, reported again as still failed.
+    pub carried_over: Vec<BitBurnError>,
+}
+
+/// Failures a retry cannot change: the path is gone, refused by a rule, or not a file BitBurn wipes.
+pub fn is_permanent(error: &BitBurnError) -> bool {
+    matches!(
+        error,
+        BitBurnError::PathNotFound { .. }
+            | BitBurnError::SymlinkNotSupported { .. }
+            | BitBurnError::SymlinkChain { .. }
+            | BitBurnError::ProtectedPath { .. }
+            | BitBurnError::SelfProtection { .. }
+            | BitBurnError::NetworkPathNotSupported { .. }
+            | BitBurnError::InvalidPathEncoding { .. }
+            | BitBurnError::UnsupportedFilesystem { .. }
+            | BitBurnError::VolumeRoot { .. }
+            | BitBurnError::FlashMediaRefused { .. }
+            | BitBurnError::RemoteTieredFile { .. }
+            | BitBurnError::BlockedByPolicy { .. }
+            | BitBurnError::ElevationContextMismatch { .. }
+            | BitBurnError::RelativePathNotAllowed { .. }
+            | BitBurnError::UndefinedVariable { .. }
+    )
+}
+
+/// Failures a protection rule decided: retried never, even when permanent ones are.
+pub fn is_protected(error: &BitBurnError) -> bool {
+    matches!(
+        error,
+        BitBurnError::ProtectedPath { .. } | BitBurnError::SelfProtection { .. } | BitBurnError::VolumeRoot { .. }
+    )
+}
+
+/// The paths a failure is about; errors of a whole volume list every item on it.
+pub fn failed_paths(error: &BitBurnError) -> Vec<String> {
+    match error {
+        BitBurnError::FilesLocked { paths }
+        | BitBurnError::FlashMediaRefused { paths }
+        | BitBurnError::VolumeReadOnly { paths, .. }
+        | BitBurnError::VolumeDisconnected { paths, .. } => paths.clone(),
+        BitBurnError::Io { path, .. } => path.iter().cloned().collect(),
+        BitBurnError::PathNotFound { path }
+        | BitBurnError::SymlinkNotSupported { path }
+        | BitBurnError::SymlinkChain { path }
+        | BitBurnError::AccessDenied { path }
+        | BitBurnError::ProtectedPath { path }
+        | BitBurnError::VerificationFailed { path, .. }
+        | BitBurnError::FileInUse { path, .. }
+        | BitBurnError::UnsupportedFilesystem { path, .. }
+        | BitBurnError::NetworkPathNotSupported { path }
+        | BitBurnError::InvalidPathEncoding { path }
+        | BitBurnError::Timeout { path, .. }
+        | BitBurnError::DirectoryActive { path, .. }
+        | BitBurnError::SelfProtection { path }
+        | BitBurnError::VolumeRoot { path }
+        | BitBurnError::ElevationContextMismatch { path }
+        | BitBurnError::RemoteTieredFile { path }
+        | BitBurnError::UndefinedVariable { path, .. }
+        | BitBurnError::InActiveUse { path, .. }
+        | BitBurnError::RelativePathNotAllowed { path } => vec![path.clone()],
+        _ => Vec::new(),
+    }
+}
+
+/// Pick the files of `report` to wipe again: its failures and the files it skipped because they
+/// were in use. Permanent failures are carried over unless `include_permanent`, protected ones
+/// always, and files that no longer exist are carried over as not found.
+pub fn plan_retry(report: &WipeReport, include_permanent: bool, exists: impl Fn(&str) -> bool) -> RetryPlan {
+    let locked_skips = report.skipped.iter().filter(|skip| matches!(skip, BitBurnError::FileInUse { .. }));
+    let mut plan = RetryPlan::default();
+    let mut seen = HashSet::new();
+    for error in report.failures.iter().chain(locked_skips) {
+        if is_protected(error) || (is_permanent(error) && !include_permanent) {
+            plan.carried_over.push(error.clone());
+            continue;
+        }
+        for path in failed_paths(error) {
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+            if exists(&path) {
+                plan.paths.push(path);
+            } else {
+                plan.carried_over.push(BitBurnError::PathNotFound { path });
+            }
+        }
+    }
+    plan
+}
+
+/// Wipe again the files operation `operation_id` failed on or skipped as in use, as a new
+/// operation. Its stored report must still be held, i.e. it is among the last finished ones. The
+/// files are sanitized as a selection is, and confirmed through `consent_token` or a prompt shown now.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn retry_failed<R: Runtime>(
+    window: tauri::Window<R>,
+    registry: State<'_, OperationRegistry>,
+    operation_id: String,
+    passes: u32,
+    algorithm: WipeAlgorithm,
+    include_permanent: Option<bool>,
+    consent_token: Option<String>,
+) -> Result<WipeResult, BitBurnError> {
+    let report = registry
+        .report(&operation_id)
+        .ok_or_else(|| BitBurnError::OperationNotFound { operation_id: operation_id.clone() })?;
+    let mut plan = plan_retry(&report, include_permanent.unwrap_or(false), |path| Path::new(path).exists());
+    let (paths, refused) = protected::sanitize_stored_targets(&plan.paths, SymlinkPolicy::Reject);
+    plan.carried_over.extend(refused);
+    log_event(
+        "retry_failed",
+        json!({"operation_id": operation_id, "retried": paths.len(), "carried_over": plan.carried_over.len()}),
+    );
+    let prompt = messages::CONFIRM_RETRY_WIPE.render(json!({
+        "count": paths.len(),
+        "algorithm": format!("{:?}", algorithm),
+        "paths": consent::list_targets(&paths),
+    }));
+    let consent =
+        consent::confirm_targets_async(window.app_handle().clone(), consent_token, prompt.text, paths.clone()).await?;
+    let options = BatchOptions {
+        source: Some("retry".to_string()),
+        retry_of: Some(operation_id),
+        carried_failures: plan.carried_over,
+        checkpoints: window.state::<JournalStore>().take_retained(&paths),
+        consent: Some(consent),
+        ..Default::default()
+    };
+    start_file_wipe(window.app_handle().clone(), window.label().to_string(), &registry, paths, passes, algorithm, options)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored_report() -> WipeReport {
+        let mut report = WipeReport::new("op-1", None);
+        report.failures = vec![
+            BitBurnError::FileInUse { path: "C:\\data\\ledger.xlsx".to_string(), pids: vec![4120] },
+            BitBurnError::AccessDenied { path: "C:\\data\\notes.docx".to_string() },
+            BitBurnError::ProtectedPath { path: "C:\\Windows\\win.ini".to_string() },
+            BitBurnError::PathNotFound { path: "C:\\data\\gone.txt".to_string() },
+            BitBurnError::FilesLocked { paths: vec!["C:\\data\\a.pst".to_string(), "C:\\data\\ledger.xlsx".to_string()] },
+            BitBurnError::Timeout { path: "C:\\data\\moved.iso".to_string(), seconds: 60 },
+            BitBurnError::Cancelled,
+        ];
+        report.skipped = vec![
+            BitBurnError::FileInUse { path: "C:\\data\\mail.ost".to_string(), pids: Vec::new() },
+            BitBurnError::SkippedByUser { path: "C:\\data\\huge.vhdx".to_string(), passes_completed: 1 },
+        ];
+        report
+    }
+
+    #[test]
+    fn locked_and_transient_failures_are_retried_and_permanent_ones_carried_over() {
+        let plan = plan_retry(&stored_report(), false, |path| path != "C:\\data\\moved.iso");
+        assert_eq!(
+            plan.paths,
+            ["C:\\data\\ledger.xlsx", "C:\\data\\notes.docx", "C:\\data\\a.pst", "C:\\data\\mail.ost"]
+        );
+        let carried: Vec<(&str, Vec<String>)> =
+            plan.carried_over.iter().map(|error| (error.code(), failed_paths(error))).collect();
+        assert_eq!(
+            carried,
+            [
+                ("protected_path", vec!["C:\\Windows\\win.ini".to_string()]),
+                ("path_not_found", vec!["C:\\data\\gone.txt".to_string()]),
+                // Gone since the first run, so there is nothing left to retry.
+                ("path_not_found", vec!["C:\\data\\moved.iso".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn permanent_failures_are_retried_on_request_if_they_still_exist() {
+        let mut report = stored_report();
+        report.failures.push(BitBurnError::UnsupportedFilesystem { path: "C:\\data\\on-ftp.bin".to_string(), filesystem: "ftpfs".to_string() });
+        let plan = plan_retry(&report, true, |path| path != "C:\\data\\gone.txt");
+        assert!(plan.paths.contains(&"C:\\data\\on-ftp.bin".to_string()));
+        // A protection rule's refusal is never retried, even on request.
+        assert!(!plan.paths.contains(&"C:\\Windows\\win.ini".to_string()));
+        assert_eq!(
+            plan.carried_over,
+            [
+                BitBurnError::ProtectedPath { path: "C:\\Windows\\win.ini".to_string() },
+                BitBurnError::PathNotFound { path: "C:\\data\\gone.txt".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn protected_self_and_volume_root_failures_are_never_retried() {
+        let mut report = WipeReport::new("op-2", None);
+        report.failures = vec![
+            BitBurnError::ProtectedPath { path: "C:\\Windows\\win.ini".to_string() },
+            BitBurnError::SelfProtection { path: "C:\\Program Files\\BitBurn\\BitBurn.exe".to_string() },
+            BitBurnError::VolumeRoot { path: "D:\\".to_string() },
+        ];
+        let plan = plan_retry(&report, true, |_| true);
+        assert!(plan.paths.is_empty(), "{:?}", plan.paths);
+        assert_eq!(plan.carried_over, report.failures);
+    }
+
+    #[test]
+    fn a_retry_wipes_the_locked_file_and_links_both_reports() {
+        use crate::operations::OperationKind;
+        use crate::test_support::{batch_context, cleanup_test_dir, create_test_dir};
+        use crate::run_wipe_batch;
+
+        let dir = create_test_dir().unwrap();
+        let locked = dir.join("ledger.xlsx");
+        std::fs::write(&locked, b"secret").unwrap();
+        let locked = locked.to_string_lossy().to_string();
+
+        let app = tauri::test::mock_app();
+        let registry = OperationRegistry::default();
+        let original = registry.register(OperationKind::WipeFiles, "C:\\data").operation_id;
+        let mut stored = WipeReport::new(original.clone(), None);
+        stored.failures = vec![
+            BitBurnError::FileInUse { path: locked.clone(), pids: vec![4120] },
+            BitBurnError::ProtectedPath { path: "C:\\Windows\\win.ini".to_string() },
+        ];
+        registry.attach_report(&original, stored);
+        registry.unregister(&original);
+
+        let plan = plan_retry(&registry.report(&original).unwrap(), false, |path| Path::new(path).exists());
+        assert_eq!(plan.paths, std::slice::from_ref(&locked));
+        let ctx = batch_context(&app, &registry, "retry test");
+        let options = BatchOptions {
+            retry_of: Some(original.clone()),
+            carried_failures: plan.carried_over,
+            ..Default::default()
+        };
+        let result = run_wipe_batch(&ctx, plan.paths.into_iter().map(Ok), 1, 1, &WipeAlgorithm::NistClear, options);
+
+        assert!(!Path::new(&locked).exists());
+        let report = result.report.as_ref().unwrap();
+        assert_eq!(report.retry_of.as_deref(), Some(original.as_str()));
+        assert_eq!(report.files_wiped, 1);
+        // The protected file is reported as still failed rather than dropped.
+        assert_eq!(report.failures, [BitBurnError::ProtectedPath { path: "C:\\Windows\\win.ini".to_string() }]);
+        assert_eq!(registry.report(&original).unwrap().retried_by, [ctx.operation.id().to_string()]);
+        cleanup_test_dir(dir);
+    }
+}
@@ -0,0 +1,399 @@
+//! Which wipes run at once. File and free-space wipes used to start the moment they were asked
+//! for, so a folder wipe and a free-space fill of the same drive fought over it while nothing let
+//! wipes of separate drives share the time. Each job now names the volumes it touches: jobs on
+//! separate volumes run side by side, up to `max_parallel_jobs`, and a job sharing a volume with
+//! one running or queued ahead of it waits its turn, still cancellable on its own. While jobs run,
+//! the tray tooltip and the taskbar button show their combined progress.
+
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::context_policy::{queued_tooltip, ContextWipeQueue};
+use crate::error::BitBurnError;
+use crate::operations::{OperationRegistry, OperationSnapshot};
+use crate::settings::SettingsStore;
+use crate::{diagnostics, log_event, read_only};
+
+pub const DEFAULT_MAX_PARALLEL_JOBS: usize = 2;
+pub const MAX_PARALLEL_JOBS: usize = 8;
+const WAITING_PHASE: &str = "Waiting for another wipe on the same drive";
+const WAIT_POLL: Duration = Duration::from_millis(200);
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+pub type Volumes = BTreeSet<PathBuf>;
+
+/// A job whose volumes are unknown may be on any of them.
+fn overlaps(a: &Volumes, b: &Volumes) -> bool {
+    a.is_empty() || b.is_empty() || !a.is_disjoint(b)
+}
+
+/// Running and waiting jobs, the waiting ones in the order they were submitted.
+#[derive(Debug, Default)]
+pub struct JobQueue {
+    running: Vec<(String, Volumes)>,
+    waiting: VecDeque<(String, Volumes)>,
+    max_parallel: usize,
+    /// Whether a thread is showing the running jobs' progress.
+    monitored: bool,
+}
+
+impl JobQueue {
+    /// Queue `job` on `volumes` and start what may start; returns the jobs started, in order.
+    pub fn submit(&mut self, job: &str, volumes: Volumes, max_parallel: usize) -> Vec<String> {
+        self.max_parallel = max_parallel.clamp(1, MAX_PARALLEL_JOBS);
+        self.waiting.push_back((job.to_string(), volumes));
+        self.dispatch()
+    }
+
+    /// Take `job` out, running or waiting, and start what that lets start.
+    pub fn finish(&mut self, job: &str) -> Vec<String> {
+        self.running.retain(|(id, _)| id != job);
+        self.waiting.retain(|(id, _)| id != job);
+        self.dispatch()
+    }
+
+    pub fn is_running(&self, job: &str) -> bool {
+        self.running.iter().any(|(id, _)| id == job)
+    }
+
+    pub fn running(&self) -> Vec<String> {
+        self.running.iter().map(|(id, _)| id.clone()).collect()
+    }
+
+    pub fn waiting(&self) -> usize {
+        self.waiting.len()
+    }
+
+    /// The running jobs and those queued ahead of `job` that it shares a volume with.
+    fn blockers(&self, job: &str) -> Vec<String> {
+        let Some(position) = self.waiting.iter().position(|(id, _)| id == job) else {
+            return Vec::new();
+        };
+        let volumes = &self.waiting[position].1;
+        self.running
+            .iter()
+            .chain(self.waiting.iter().take(position))
+            .filter(|(_, other)| overlaps(volumes, other))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Start waiting jobs in order while there is room. A job waits while it shares a volume with
+    /// a running job or one queued ahead of it, so jobs on one volume keep their order, but a job
+    /// on another volume may pass them.
+    fn dispatch(&mut self) -> Vec<String> {
+        let mut started = Vec::new();
+        let mut held: Vec<Volumes> = Vec::new();
+        let mut index = 0;
+        while index < self.waiting.len() && self.running.len() < self.max_parallel.max(1) {
+            let volumes = &self.waiting[index].1;
+            let blocked = self.running.iter().map(|(_, other)| other).chain(&held).any(|other| overlaps(volumes, other));
+            if blocked {
+                held.push(volumes.clone());
+                index += 1;
+            } else if let Some(job) = self.waiting.remove(index) {
+                started.push(job.0.clone());
+                self.running.push(job);
+            }
+        }
+        started
+    }
+}
+
+/// The job queue shared through managed state; clones refer to the same queue.
+#[derive(Clone, Default)]
+pub struct JobScheduler {
+    inner: Arc<(Mutex<JobQueue>, Condvar)>,
+}
+
+impl JobScheduler {
+    fn lock(&self) -> MutexGuard<'_, JobQueue> {
+        self.inner.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Wait until `job` may run on `volumes`; `None` once `cancelled` is set while it waits.
+    /// `on_wait` is told which jobs it waits for if it cannot start at once.
+    pub fn acquire(
+        &self,
+        job: &str,
+        volumes: Volumes,
+        max_parallel: usize,
+        cancelled: &AtomicBool,
+        on_wait: impl FnOnce(Vec<String>),
+    ) -> Option<JobSlot> {
+        let mut queue = self.lock();
+        queue.submit(job, volumes, max_parallel);
+        if !queue.is_running(job) {
+            let blockers = queue.blockers(job);
+            drop(queue);
+            on_wait(blockers);
+            queue = self.lock();
+        }
+        while !queue.is_running(job) {
+            if cancelled.load(Ordering::SeqCst) {
+                if !queue.finish(job).is_empty() {
+                    self.inner.1.notify_all();
+                }
+                return None;
+            }
+            queue = self
+                .inner
+                .1
+                .wait_timeout(queue, WAIT_POLL)
+                .map(|(queue, _)| queue)
+                .unwrap_or_else(|poisoned| poisoned.into_inner().0);
+        }
+        Some(JobSlot { scheduler: Some(self.clone()), job: job.to_string() })
+    }
+
+    /// Whether the caller should start the progress thread: true once until it stops.
+    fn start_monitor(&self) -> bool {
+        let mut queue = self.lock();
+        !std::mem::replace(&mut queue.monitored, true)
+    }
+
+    /// The running jobs and how many wait, or `None` when none run, which stops the progress thread.
+    fn monitor_state(&self) -> Option<(Vec<String>, usize)> {
+        let mut queue = self.lock();
+        if queue.running.is_empty() {
+            queue.monitored = false;
+            return None;
+        }
+        Some((queue.running(), queue.waiting()))
+    }
+}
+
+/// A running job's place; dropping it lets the jobs waiting on its volumes start.
+pub struct JobSlot {
+    scheduler: Option<JobScheduler>,
+    job: String,
+}
+
+impl Drop for JobSlot {
+    fn drop(&mut self) {
+        if let Some(scheduler) = &self.scheduler {
+            scheduler.lock().finish(&self.job);
+            scheduler.inner.1.notify_all();
+        }
+    }
+}
+
+/// The volumes `paths` are on.
+pub fn volumes_of<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Volumes {
+    let mounts = read_only::mount_points();
+    paths.into_iter().map(|path| read_only::volume_of(path.as_ref(), &mounts)).collect()
+}
+
+/// How many jobs are running now, counting at least the caller's own.
+pub fn running_jobs<R: Runtime>(app: &AppHandle<R>) -> usize {
+    app.try_state::<JobScheduler>().map_or(1, |scheduler| scheduler.lock().running.len().max(1))
+}
+
+/// Wait for `operation_id`'s turn on `volumes`, shown as waiting meanwhile; `None` once it is
+/// cancelled while it waits. Runs at once where no scheduler is managed.
+pub fn acquire<R: Runtime>(app: &AppHandle<R>, operation_id: &str, volumes: Volumes, cancelled: &AtomicBool) -> Option<JobSlot> {
+    let Some(scheduler) = app.try_state::<JobScheduler>().map(|scheduler| scheduler.inner().clone()) else {
+        return Some(JobSlot { scheduler: None, job: operation_id.to_string() });
+    };
+    let max_parallel = app
+        .try_state::<SettingsStore>()
+        .map_or(DEFAULT_MAX_PARALLEL_JOBS, |settings| settings.get().max_parallel_jobs());
+    let slot = scheduler.acquire(operation_id, volumes.clone(), max_parallel, cancelled, |waiting_for| {
+        let payload = json!({"operation_id": operation_id, "volumes": volumes, "waiting_for": waiting_for});
+        log_event("job_waiting", payload.clone());
+        if let Some(registry) = app.try_state::<OperationRegistry>() {
+            registry.update(operation_id, WAITING_PHASE, 0.0);
+        }
+        diagnostics::emit(app, "job_waiting", payload);
+    })?;
+    log_event("job_started", json!({"operation_id": operation_id}));
+    if scheduler.start_monitor() {
+        let app = app.clone();
+        std::thread::spawn(move || monitor(app, scheduler));
+    }
+    Some(slot)
+}
+
+/// The combined progress of the running jobs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobsProgress {
+    /// Operation ids of the running jobs, in the order they started.
+    pub running: Vec<String>,
+    pub waiting: usize,
+    /// Mean of the running jobs' overall percentages.
+    pub percentage: f32,
+}
+
+impl JobsProgress {
+    pub fn of(running: &[OperationSnapshot], waiting: usize) -> Self {
+        let percentage = if running.is_empty() {
+            0.0
+        } else {
+            running.iter().map(|snapshot| snapshot.overall_percentage).sum::<f32>() / running.len() as f32
+        };
+        Self {
+            running: running.iter().map(|snapshot| snapshot.operation_id.clone()).collect(),
+            waiting,
+            percentage,
+        }
+    }
+
+    pub fn tooltip(&self) -> String {
+        let percent = self.percentage.floor() as u32;
+        let running = match self.running.len() {
+            1 => format!("BitBurn - wiping, {}%", percent),
+            n => format!("BitBurn - {} wipes running, {}%", n, percent),
+        };
+        match self.waiting {
+            0 => running,
+            n => format!("{}, {} waiting", running, n),
+        }
+    }
+}
+
+fn monitor<R: Runtime>(app: AppHandle<R>, scheduler: JobScheduler) {
+    loop {
+        std::thread::sleep(PROGRESS_INTERVAL);
+        let Some((running, waiting)) = scheduler.monitor_state() else {
+            show(&app, None);
+            return;
+        };
+        let snapshots: Vec<_> = app
+            .try_state::<OperationRegistry>()
+            .map(|registry| running.iter().filter_map(|id| registry.get(id)).collect())
+            .unwrap_or_default();
+        show(&app, Some(&JobsProgress::of(&snapshots, waiting)));
+    }
+}
+
+/// Show `progress` on the tray and taskbar, or clear them when no job runs.
+fn show<R: Runtime>(app: &AppHandle<R>, progress: Option<&JobsProgress>) {
+    let tooltip = match progress {
+        Some(progress) => progress.tooltip(),
+        None => queued_tooltip(app.try_state::<ContextWipeQueue>().map_or(0, |queue| queue.jobs().len())),
+    };
+    crate::ui::set_tray_tooltip(app, &tooltip);
+    if let Some(window) = app.get_webview_window("main") {
+        let state = match progress {
+            Some(progress) => ProgressBarState {
+                status: Some(ProgressBarStatus::Normal),
+                progress: Some(progress.percentage as u64),
+            },
+            None => ProgressBarState { status: Some(ProgressBarStatus::None), progress: None },
+        };
+        let _ = window.set_progress_bar(state);
+    }
+    if let Some(progress) = progress {
+        diagnostics::emit(app, "jobs_progress", progress);
+    }
+}
+
+/// Set how many jobs on separate volumes may run at once; `None` restores the default of two.
+#[tauri::command]
+pub async fn set_max_parallel_jobs(settings: State<'_, SettingsStore>, max_parallel_jobs: Option<usize>) -> Result<usize, BitBurnError> {
+    let updated = settings.update(|s| s.max_parallel_jobs = max_parallel_jobs)?;
+    let effective = updated.max_parallel_jobs();
+    log_event("max_parallel_jobs_configured", json!({"max_parallel_jobs": effective}));
+    Ok(effective)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn on(volumes: &[&str]) -> Volumes {
+        volumes.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn jobs_on_separate_volumes_start_together_and_shared_volumes_queue() {
+        let mut queue = JobQueue::default();
+        assert_eq!(queue.submit("files-c", on(&["C:\\"]), 2), ["files-c"]);
+        // Same volume: waits behind the running job.
+        assert!(queue.submit("free-c", on(&["C:\\"]), 2).is_empty());
+        assert_eq!(queue.submit("free-d", on(&["D:\\"]), 2), ["free-d"]);
+        // Room is used up even though E: is free.
+        assert!(queue.submit("files-e", on(&["E:\\"]), 2).is_empty());
+        assert_eq!(queue.blockers("free-c"), ["files-c"]);
+
+        // free-c still waits for files-c, so files-e takes the room free-d left.
+        assert_eq!(queue.finish("free-d"), ["files-e"]);
+        assert_eq!(queue.finish("files-c"), ["free-c"]);
+        assert_eq!(queue.running(), ["files-e", "free-c"]);
+    }
+
+    #[test]
+    fn a_job_never_passes_an_earlier_one_on_its_volume() {
+        let mut queue = JobQueue::default();
+        assert_eq!(queue.submit("a", on(&["C:\\", "D:\\"]), 3), ["a"]);
+        assert!(queue.submit("b", on(&["D:\\"]), 3).is_empty());
+        assert!(queue.submit("c", on(&["C:\\"]), 3).is_empty());
+        // Still behind "b" on D: once "a" is done.
+        assert!(queue.submit("d", on(&["D:\\", "E:\\"]), 3).is_empty());
+        assert_eq!(queue.blockers("d"), ["a", "b"]);
+        assert_eq!(queue.finish("a"), ["b", "c"]);
+        assert_eq!(queue.finish("b"), ["d"]);
+
+        // Unknown volumes overlap everything.
+        let mut queue = JobQueue::default();
+        assert_eq!(queue.submit("known", on(&["C:\\"]), 4), ["known"]);
+        assert!(queue.submit("unknown", Volumes::new(), 4).is_empty());
+        assert!(queue.submit("other", on(&["D:\\"]), 4).is_empty());
+        assert_eq!(queue.finish("known"), ["unknown"]);
+        assert_eq!(queue.finish("unknown"), ["other"]);
+    }
+
+    #[test]
+    fn a_waiting_job_is_cancelled_alone_and_frees_its_place() {
+        let scheduler = JobScheduler::default();
+        let running = scheduler.acquire("first", on(&["C:\\"]), 1, &AtomicBool::new(false), |_| panic!("nothing runs")).unwrap();
+
+        let (waiting_tx, waiting_rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let scheduler = scheduler.clone();
+            let cancel = cancel.clone();
+            std::thread::spawn(move || {
+                scheduler
+                    .acquire("second", on(&["C:\\"]), 1, &cancel, |blockers| waiting_tx.send(blockers).unwrap())
+                    .is_some()
+            })
+        };
+        assert_eq!(waiting_rx.recv().unwrap(), ["first"]);
+        cancel.store(true, Ordering::SeqCst);
+        assert!(!waiter.join().unwrap(), "the cancelled job never ran");
+        assert!(scheduler.lock().is_running("first"));
+        assert_eq!(scheduler.lock().waiting(), 0);
+
+        drop(running);
+        assert!(scheduler.lock().running().is_empty());
+        let next = scheduler.acquire("third", on(&["C:\\"]), 1, &AtomicBool::new(false), |_| panic!("C: is free"));
+        assert!(next.is_some());
+    }
+
+    #[test]
+    fn progress_is_the_mean_of_the_running_jobs() {
+        let snapshot = |id: &str, percentage: f32| OperationSnapshot {
+            operation_id: id.to_string(),
+            kind: crate::operations::OperationKind::WipeFiles,
+            started_at: 0,
+            current_phase: String::new(),
+            overall_percentage: percentage,
+            target_summary: String::new(),
+        };
+        let single = JobsProgress::of(&[snapshot("a", 40.5)], 0);
+        assert_eq!(single.tooltip(), "BitBurn - wiping, 40%");
+        let both = JobsProgress::of(&[snapshot("a", 20.0), snapshot("b", 70.0)], 1);
+        assert_eq!(both.percentage, 45.0);
+        assert_eq!(both.running, ["a", "b"]);
+        assert_eq!(both.tooltip(), "BitBurn - 2 wipes running, 45%, 1 waiting");
+    }
+}
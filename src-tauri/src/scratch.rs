@@ -0,0 +1,341 @@
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use sysinfo::{DiskExt, System, SystemExt};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::error::BitBurnError;
+use crate::free_space;
+use crate::log_event;
+use bitburn_core::{DirectorySync, PlatformDirectorySync};
+
+/// Every scratch directory starts with this, so ones left by a crash can be found and removed.
+pub const SCRATCH_PREFIX: &str = ".bitburn-scratch-";
+
+static CACHE_ROOT: OnceLock<PathBuf> = OnceLock::new();
+/// Scratch directories owned by this process; anything else with the prefix is an orphan.
+static LIVE: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+fn with_live<T>(f: impl FnOnce(&mut HashSet<PathBuf>) -> T) -> T {
+    let mut live = LIVE.lock().unwrap_or_else(|p| p.into_inner());
+    f(live.get_or_insert_with(HashSet::new))
+}
+
+/// A uniquely named directory for temporary files, removed with everything in it when dropped.
+#[derive(Debug)]
+pub struct ScratchDir {
+    path: PathBuf,
+    abandoned: bool,
+}
+
+impl ScratchDir {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Give up the directory without trying to remove it, for a volume that has disappeared.
+    /// If the volume comes back, the directory is listed among its orphaned temp files.
+    pub fn abandon(mut self) {
+        self.abandoned = true;
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        if self.abandoned {
+            log_event("scratch_abandoned", json!({"path": self.path.to_string_lossy()}));
+        } else if let Err(e) = fs::remove_dir_all(&self.path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log_event("scratch_cleanup_failed", json!({"path": self.path.to_string_lossy(), "message": e.to_string()}));
+            }
+        }
+        with_live(|live| live.remove(&self.path));
+    }
+}
+
+/// Scratch space in the app cache directory with at least `bytes` available.
+#[allow(dead_code)] // for features whose scratch files need not share a volume with anything
+pub fn ensure_scratch_space(bytes: u64) -> Result<ScratchDir, BitBurnError> {
+    ensure_scratch_space_on(&cache_root(), bytes)
+}
+
+/// Scratch space inside `parent`, for work that must stay on that volume (e.g. so a rename into
+/// place cannot cross volumes), with at least `bytes` available there.
+pub fn ensure_scratch_space_on(parent: &Path, bytes: u64) -> Result<ScratchDir, BitBurnError> {
+    fs::create_dir_all(parent).map_err(|e| BitBurnError::io(&e, Some(parent)))?;
+    if let Some(available) = available_bytes(parent) {
+        if available < bytes {
+            return Err(BitBurnError::InsufficientScratchSpace {
+                path: parent.to_string_lossy().to_string(),
+                required: bytes,
+                available,
+            });
+        }
+    }
+
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let path = parent.join(format!("{}{}-{:x}-{:08x}", SCRATCH_PREFIX, std::process::id(), nanos, rand::random::<u32>()));
+    // Registered before it exists so a concurrent sweep never takes it for an orphan.
+    with_live(|live| live.insert(path.clone()));
+    match fs::create_dir(&path) {
+        Ok(()) => Ok(ScratchDir { path, abandoned: false }),
+        Err(e) => {
+            with_live(|live| live.remove(&path));
+            Err(BitBurnError::io(&e, Some(&path)))
+        }
+    }
+}
+
+/// Write `contents` to `dest` by staging them in scratch space beside it and renaming the staged
+/// file into place, so a failed or interrupted write never leaves a partial file at `dest`.
+pub fn write_via_scratch(dest: &Path, contents: &[u8]) -> Result<(), BitBurnError> {
+    stage_and_rename(dest, contents, false)
+}
+
+/// `write_via_scratch`, but the contents and the rename have reached the disk when it returns, for
+/// records that must survive a crash straight afterwards.
+pub fn write_durably_via_scratch(dest: &Path, contents: &[u8]) -> Result<(), BitBurnError> {
+    stage_and_rename(dest, contents, true)
+}
+
+fn stage_and_rename(dest: &Path, contents: &[u8], durable: bool) -> Result<(), BitBurnError> {
+    let parent = dest.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let scratch = ensure_scratch_space_on(parent, contents.len() as u64)?;
+    let staged = scratch.path().join(dest.file_name().unwrap_or_default());
+    let mut file = fs::File::create(&staged).map_err(|e| BitBurnError::io(&e, Some(&staged)))?;
+    file.write_all(contents)
+        .and_then(|()| if durable { file.sync_all() } else { Ok(()) })
+        .map_err(|e| BitBurnError::io(&e, Some(&staged)))?;
+    drop(file);
+    fs::rename(&staged, dest).map_err(|e| BitBurnError::io(&e, Some(dest)))?;
+    if durable {
+        PlatformDirectorySync.sync_dir(parent).map_err(|e| BitBurnError::io(&e, Some(parent)))?;
+    }
+    Ok(())
+}
+
+/// Create a new temp file at `path`, in a scratch directory, opened exclusively for reading and
+/// writing and readable by its owner only. On Windows it is also hidden and marked temporary,
+/// which keeps it out of Explorer and tells the cache manager it need not be kept. A file already
+/// at `path` is an error rather than reused, so nobody can plant one with a laxer ACL.
+pub fn create_temp_file(path: &Path) -> std::io::Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.read(true).write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        use windows_sys::Win32::Storage::FileSystem::{FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_TEMPORARY};
+        options.attributes(FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_TEMPORARY);
+    }
+    let file = bitburn_core::open_exclusive(&mut options, path)?;
+    if let Err(e) = restrict_to_owner(&file) {
+        drop(file);
+        let _ = fs::remove_file(path);
+        return Err(e);
+    }
+    Ok(file)
+}
+
+/// Give an open file a protected DACL granting its owner full access and nobody else anything.
+/// Set through the handle, so it applies to the file that was opened whatever the path now is.
+#[cfg(windows)]
+pub fn restrict_to_owner(file: &fs::File) -> std::io::Result<()> {
+    use std::io;
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::{GetLastError, LocalFree, ERROR_SUCCESS};
+    use windows_sys::Win32::Security::Authorization::{
+        ConvertStringSecurityDescriptorToSecurityDescriptorW, SetSecurityInfo, SDDL_REVISION_1, SE_FILE_OBJECT,
+    };
+    use windows_sys::Win32::Security::{
+        GetSecurityDescriptorDacl, ACL, DACL_SECURITY_INFORMATION, PROTECTED_DACL_SECURITY_INFORMATION,
+    };
+
+    // `OW` is the owner-rights SID, so the grant follows the file's owner.
+    let sddl: Vec<u16> = "D:P(A;;FA;;;OW)".encode_utf16().chain(Some(0)).collect();
+    let mut descriptor = std::ptr::null_mut();
+    if unsafe {
+        ConvertStringSecurityDescriptorToSecurityDescriptorW(sddl.as_ptr(), SDDL_REVISION_1, &mut descriptor, std::ptr::null_mut())
+    } == 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    let (mut present, mut defaulted) = (0, 0);
+    let mut dacl: *mut ACL = std::ptr::null_mut();
+    let status = if unsafe { GetSecurityDescriptorDacl(descriptor, &mut present, &mut dacl, &mut defaulted) } == 0 {
+        unsafe { GetLastError() }
+    } else {
+        unsafe {
+            SetSecurityInfo(
+                file.as_raw_handle(),
+                SE_FILE_OBJECT,
+                DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                dacl,
+                std::ptr::null(),
+            )
+        }
+    };
+    unsafe { LocalFree(descriptor) };
+    if status != ERROR_SUCCESS {
+        return Err(io::Error::from_raw_os_error(status as i32));
+    }
+    Ok(())
+}
+
+/// Make an open file readable and writable by its owner only, through the descriptor.
+#[cfg(unix)]
+pub fn restrict_to_owner(file: &fs::File) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    file.set_permissions(fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(any(windows, unix)))]
+pub fn restrict_to_owner(_file: &fs::File) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Space this process may write on the volume holding `dir`: the platform query first (it honours
+/// quotas), sysinfo's figure for the containing mount otherwise.
+fn available_bytes(dir: &Path) -> Option<u64> {
+    if let Some(space) = free_space::query_free_space(dir) {
+        return Some(space.fill_estimate());
+    }
+    let mut sys = System::new();
+    sys.refresh_disks_list();
+    sys.disks()
+        .iter()
+        .filter(|disk| dir.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+/// Scratch directories in `parent` that no live `ScratchDir` owns.
+pub fn orphans_in(parent: &Path) -> Vec<PathBuf> {
+    with_live(|live| orphans_excluding(parent, live))
+}
+
+fn orphans_excluding(parent: &Path, live: &HashSet<PathBuf>) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(SCRATCH_PREFIX))
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .map(|entry| entry.path())
+        .filter(|path| !live.contains(path))
+        .collect()
+}
+
+/// Remove scratch directories left in `parent` by a run that crashed before its guards dropped.
+pub fn sweep_orphans(parent: &Path) -> usize {
+    orphans_in(parent)
+        .into_iter()
+        .filter(|orphan| match fs::remove_dir_all(orphan) {
+            Ok(()) => {
+                log_event("scratch_orphan_removed", json!({"path": orphan.to_string_lossy()}));
+                true
+            }
+            Err(e) => {
+                log_event("scratch_orphan_remove_failed", json!({"path": orphan.to_string_lossy(), "message": e.to_string()}));
+                false
+            }
+        })
+        .count()
+}
+
+fn cache_root() -> PathBuf {
+    CACHE_ROOT
+        .get_or_init(|| std::env::temp_dir().join("BitBurn"))
+        .clone()
+}
+
+/// Use the app cache directory for scratch space and sweep orphans there and at every drive root
+/// (where free-space fills put theirs) in the background.
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    if let Ok(cache) = app.path().app_cache_dir() {
+        let _ = CACHE_ROOT.set(cache.join("scratch"));
+    }
+    let cache = cache_root();
+    std::thread::spawn(move || {
+        let mut sys = System::new();
+        sys.refresh_disks_list();
+        let removed: usize = std::iter::once(cache)
+            .chain(sys.disks().iter().map(|disk| disk.mount_point().to_path_buf()))
+            .map(|parent| sweep_orphans(&parent))
+            .sum();
+        if removed > 0 {
+            log_event("scratch_sweep", json!({"removed": removed}));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    #[test]
+    fn refuses_when_the_volume_lacks_the_requested_space() {
+        let parent = create_test_dir().unwrap();
+        let err = ensure_scratch_space_on(&parent, u64::MAX).unwrap_err();
+        assert!(matches!(err, BitBurnError::InsufficientScratchSpace { required: u64::MAX, .. }), "{:?}", err);
+        assert!(orphans_excluding(&parent, &HashSet::new()).is_empty(), "nothing is created on failure");
+        cleanup_test_dir(parent);
+    }
+
+    #[test]
+    fn dropping_the_guard_removes_the_directory_and_its_contents() {
+        let parent = create_test_dir().unwrap();
+        let scratch = ensure_scratch_space_on(&parent, 1).unwrap();
+        let path = scratch.path().to_path_buf();
+        fs::create_dir(path.join("nested")).unwrap();
+        fs::write(path.join("nested").join("staged.json"), b"{}").unwrap();
+        assert!(orphans_in(&parent).is_empty(), "a live scratch directory is not an orphan");
+
+        drop(scratch);
+        assert!(!path.exists());
+        cleanup_test_dir(parent);
+    }
+
+    #[test]
+    fn staged_writes_land_at_the_destination_without_leftovers() {
+        let parent = create_test_dir().unwrap();
+        fs::create_dir_all(&parent).unwrap();
+        let dest = parent.join("report.json");
+        write_via_scratch(&dest, b"{\"ok\": true}").unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"{\"ok\": true}");
+        assert_eq!(fs::read_dir(&parent).unwrap().count(), 1, "the scratch directory is gone");
+        cleanup_test_dir(parent);
+    }
+
+    #[test]
+    fn a_leaked_guard_is_swept_by_the_next_run() {
+        let parent = create_test_dir().unwrap();
+        let leaked = ensure_scratch_space_on(&parent, 1).unwrap();
+        let path = leaked.path().to_path_buf();
+        fs::write(path.join(".temp_wipe_file"), vec![0u8; 4096]).unwrap();
+        // The process dies without running destructors.
+        std::mem::forget(leaked);
+        let unrelated = parent.join("keep-me");
+        fs::create_dir(&unrelated).unwrap();
+
+        // A fresh process owns no scratch directories yet.
+        with_live(|live| live.remove(&path));
+        assert_eq!(orphans_in(&parent), vec![path.clone()]);
+        assert_eq!(sweep_orphans(&parent), 1);
+        assert!(!path.exists());
+        assert!(unrelated.exists());
+        cleanup_test_dir(parent);
+    }
+}
@@ -0,0 +1,345 @@
+//! Windows Search keeps the names and content snippets of indexed files in its catalog
+//! (`Windows.edb`), so a wiped document can still turn up in a search. `get_search_index_status`
+//! tells which targets lie under an indexed location before a wipe; with `purge_search_index` on,
+//! a batch then asks the indexer to re-crawl the folders it wiped in, which drops the entries of
+//! files that are gone. Crawl scopes come from the indexer's rules in the registry. Other
+//! platforms have no such index, so nothing is reported as indexed and nothing is purged.
+
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::report::WipeReport;
+use crate::settings::SettingsStore;
+
+/// One crawl scope rule of the system index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrawlRule {
+    /// `file:///C:\Users\*`; `*` matches anything, separators included.
+    pub url: String,
+    pub include: bool,
+    /// Added by the user or an application rather than shipped with Windows; wins a tie with a
+    /// default rule of the same specificity.
+    pub user: bool,
+}
+
+impl CrawlRule {
+    /// The rule as a lowercase path pattern, or `None` for scopes other than the file system.
+    fn pattern(&self) -> Option<String> {
+        let url = self.url.to_lowercase().replace('/', "\\");
+        let path = url.strip_prefix("file:\\\\\\").or_else(|| url.strip_prefix("file:"))?;
+        Some(if path.ends_with('*') { path.to_string() } else { format!("{}*", path) })
+    }
+
+    fn specificity(&self) -> usize {
+        self.pattern().map_or(0, |pattern| pattern.chars().filter(|c| *c != '*').count())
+    }
+
+    fn covers(&self, path: &str) -> bool {
+        let path = format!("{}\\", path.to_lowercase().replace('/', "\\").trim_end_matches('\\'));
+        self.pattern().is_some_and(|pattern| wildcard_match(&pattern, &path))
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` stands for any run of characters.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// The rule deciding whether `path` is indexed: the most specific one covering it.
+pub fn covering_rule<'a>(rules: &'a [CrawlRule], path: &str) -> Option<&'a CrawlRule> {
+    rules.iter().filter(|rule| rule.covers(path)).max_by_key(|rule| (rule.specificity(), rule.user))
+}
+
+/// Whether one target lies under an indexed location, and the scope that includes it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchIndexStatus {
+    pub path: String,
+    pub indexed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+pub fn index_status(rules: &[CrawlRule], paths: &[String]) -> Vec<SearchIndexStatus> {
+    paths
+        .iter()
+        .map(|path| {
+            let scope = covering_rule(rules, path).filter(|rule| rule.include).map(|rule| rule.url.clone());
+            SearchIndexStatus { path: path.clone(), indexed: scope.is_some(), scope }
+        })
+        .collect()
+}
+
+/// The folders to re-crawl after wiping `wiped`: the parent of each indexed entry, once each.
+pub fn affected_folders(rules: &[CrawlRule], wiped: &[String]) -> Vec<String> {
+    let mut folders: Vec<String> = Vec::new();
+    for status in index_status(rules, wiped).into_iter().filter(|status| status.indexed) {
+        let folder = status.path.rfind(['\\', '/']).map_or(status.path.as_str(), |end| &status.path[..end]).to_string();
+        if !folders.iter().any(|known| known.eq_ignore_ascii_case(&folder)) {
+            folders.push(folder);
+        }
+    }
+    folders
+}
+
+/// Whether the indexer accepted the re-crawl of one folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchIndexPurge {
+    pub folder: String,
+    pub requested: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The crawl scope rules of the system index; none while the Windows Search service is disabled.
+#[cfg(windows)]
+pub fn crawl_rules() -> Vec<CrawlRule> {
+    use winreg::enums::{HKEY_LOCAL_MACHINE, KEY_READ};
+    use winreg::RegKey;
+
+    const SERVICE: &str = r"SYSTEM\CurrentControlSet\Services\WSearch";
+    const SCOPES: &str = r"SOFTWARE\Microsoft\Windows Search\CrawlScopeManager\Windows\SystemIndex";
+    const SERVICE_DISABLED: u32 = 4;
+
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let start: Option<u32> = hklm.open_subkey_with_flags(SERVICE, KEY_READ).and_then(|key| key.get_value("Start")).ok();
+    if start.is_none_or(|start| start == SERVICE_DISABLED) {
+        return Vec::new();
+    }
+    let mut rules = Vec::new();
+    for (set, user) in [("DefaultRules", false), ("WorkingSetRules", true)] {
+        let Ok(set) = hklm.open_subkey_with_flags(format!(r"{}\{}", SCOPES, set), KEY_READ) else {
+            continue;
+        };
+        for name in set.enum_keys().flatten() {
+            let Ok(rule) = set.open_subkey_with_flags(&name, KEY_READ) else {
+                continue;
+            };
+            let (Ok(url), Ok(include)) = (rule.get_value::<String, _>("URL"), rule.get_value::<u32, _>("Include")) else {
+                continue;
+            };
+            rules.push(CrawlRule { url, include: include != 0, user });
+        }
+    }
+    rules
+}
+
+#[cfg(not(windows))]
+pub fn crawl_rules() -> Vec<CrawlRule> {
+    Vec::new()
+}
+
+/// Ask the system index to re-crawl `folders`, through its `ISearchCatalogManager`.
+#[cfg(windows)]
+fn reindex(folders: &[String]) -> Vec<SearchIndexPurge> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = std::process::Command::new("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-ExecutionPolicy", "Bypass", "-Command", &reindex_script(folders)])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output();
+    let stdout = match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).to_string(),
+        Ok(output) => return not_requested(folders, String::from_utf8_lossy(&output.stderr).trim()),
+        Err(e) => return not_requested(folders, &format!("failed to start PowerShell: {}", e)),
+    };
+    folders
+        .iter()
+        .map(|folder| {
+            let line = stdout.lines().find(|line| line.split('\t').nth(1) == Some(folder.as_str()));
+            match line.map(|line| line.splitn(3, '\t').collect::<Vec<_>>()).as_deref() {
+                Some(["ok", ..]) => SearchIndexPurge { folder: folder.clone(), requested: true, error: None },
+                Some([_, _, error]) => SearchIndexPurge { folder: folder.clone(), requested: false, error: Some(error.to_string()) },
+                _ => SearchIndexPurge { folder: folder.clone(), requested: false, error: Some("no answer from the indexer".into()) },
+            }
+        })
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn reindex(folders: &[String]) -> Vec<SearchIndexPurge> {
+    not_requested(folders, "Windows Search is only available on Windows")
+}
+
+fn not_requested(folders: &[String], error: &str) -> Vec<SearchIndexPurge> {
+    folders
+        .iter()
+        .map(|folder| SearchIndexPurge { folder: folder.clone(), requested: false, error: Some(error.to_string()) })
+        .collect()
+}
+
+/// The interfaces have no type library, so they are declared in C# for PowerShell to call; only
+/// the methods up to the ones used are listed, in vtable order. Prints one `ok` or `error` line
+/// per folder, tab-separated.
+#[cfg(any(windows, test))]
+fn reindex_script(folders: &[String]) -> String {
+    use crate::platform::send_to::ps_quote;
+
+    let folders: Vec<String> = folders.iter().map(|folder| ps_quote(folder)).collect();
+    format!(
+        r#"$ErrorActionPreference = 'Stop'
+Add-Type -TypeDefinition @'
+using System;
+using System.Runtime.InteropServices;
+[ComImport, Guid("AB310581-AC80-11D1-8DF3-00C04FB6EF50"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+public interface ISearchCatalogManager {{
+    [return: MarshalAs(UnmanagedType.LPWStr)] string get_Name();
+    IntPtr GetParameter([MarshalAs(UnmanagedType.LPWStr)] string name);
+    void SetParameter([MarshalAs(UnmanagedType.LPWStr)] string name, IntPtr value);
+    void GetCatalogStatus(out int status, out int pausedReason);
+    void Reset();
+    void Reindex();
+    void ReindexMatchingURLs([MarshalAs(UnmanagedType.LPWStr)] string pattern);
+}}
+[ComImport, Guid("AB310581-AC80-11D1-8DF3-00C04FB6EF69"), InterfaceType(ComInterfaceType.InterfaceIsIUnknown)]
+public interface ISearchManager {{
+    [return: MarshalAs(UnmanagedType.LPWStr)] string GetIndexerVersionStr();
+    void GetIndexerVersion(out uint major, out uint minor);
+    IntPtr GetParameter([MarshalAs(UnmanagedType.LPWStr)] string name);
+    void SetParameter([MarshalAs(UnmanagedType.LPWStr)] string name, IntPtr value);
+    [return: MarshalAs(UnmanagedType.LPWStr)] string get_ProxyName();
+    [return: MarshalAs(UnmanagedType.LPWStr)] string get_BypassList();
+    void SetProxy(int usage, int bypassLocal, uint port, [MarshalAs(UnmanagedType.LPWStr)] string proxy, [MarshalAs(UnmanagedType.LPWStr)] string bypassList);
+    ISearchCatalogManager GetCatalog([MarshalAs(UnmanagedType.LPWStr)] string catalog);
+}}
+public static class BitBurnSearchIndex {{
+    public static ISearchCatalogManager SystemIndex() {{
+        var manager = (ISearchManager)Activator.CreateInstance(Type.GetTypeFromCLSID(new Guid("7D096C5F-AC08-4F1F-BEB7-5C22C517CE39")));
+        return manager.GetCatalog("SystemIndex");
+    }}
+}}
+'@
+$catalog = [BitBurnSearchIndex]::SystemIndex()
+foreach ($folder in @({folders})) {{
+    try {{ $catalog.ReindexMatchingURLs('file:///' + $folder + '\*'); "ok`t$folder" }}
+    catch {{ "error`t$folder`t$($_.Exception.Message)" }}
+}}
+"#,
+        folders = folders.join(", ")
+    )
+}
+
+/// With `purge_search_index` on, ask the indexer to re-crawl the folders `report` wiped indexed
+/// entries in, and record per folder whether it accepted.
+pub fn purge_finished<R: Runtime>(app: &AppHandle<R>, report: &mut WipeReport) {
+    let enabled = app.try_state::<SettingsStore>().is_some_and(|settings| settings.get().purge_search_index);
+    if !enabled {
+        return;
+    }
+    let wiped: Vec<String> = report.roots.iter().filter(|root| root.succeeded > 0).map(|root| root.path.clone()).collect();
+    let folders = affected_folders(&crawl_rules(), &wiped);
+    if folders.is_empty() {
+        return;
+    }
+    report.search_index = reindex(&folders);
+    log_event(
+        "search_index_purge",
+        json!({
+            "folders": folders.len(),
+            "requested": report.search_index.iter().filter(|purge| purge.requested).count(),
+        }),
+    );
+}
+
+/// Which of `paths` lie under locations Windows Search indexes.
+#[tauri::command]
+pub async fn get_search_index_status(paths: Vec<String>) -> Result<Vec<SearchIndexStatus>, BitBurnError> {
+    crate::capabilities::run_blocking(move || Ok(index_status(&crawl_rules(), &paths))).await
+}
+
+/// Re-crawl the folders of indexed files after each batch.
+#[tauri::command]
+pub async fn set_purge_search_index(settings: State<'_, SettingsStore>, enabled: bool) -> Result<bool, BitBurnError> {
+    let updated = settings.update(|s| s.purge_search_index = enabled)?;
+    log_event("purge_search_index_configured", json!({"enabled": updated.purge_search_index}));
+    Ok(updated.purge_search_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(url: &str, include: bool, user: bool) -> CrawlRule {
+        CrawlRule { url: url.to_string(), include, user }
+    }
+
+    /// Scopes as a fresh Windows 11 install and a user's own choices leave them.
+    fn rules() -> Vec<CrawlRule> {
+        vec![
+            rule("file:///C:\\Users\\*", true, false),
+            rule("file:///C:\\Users\\*\\AppData\\*", false, false),
+            rule("file:///C:\\ProgramData\\Microsoft\\Windows\\Start Menu\\Programs\\*", true, false),
+            rule("iehistory://{S-1-5-21}\\*", true, false),
+            rule("file:///D:\\Archive\\", true, true),
+            rule("file:///C:\\Users\\me\\Private\\*", false, true),
+            rule("file:///C:\\Users\\me\\Private\\Shared\\*", true, false),
+        ]
+    }
+
+    fn indexed(path: &str) -> bool {
+        index_status(&rules(), &[path.to_string()])[0].indexed
+    }
+
+    #[test]
+    fn the_most_specific_crawl_scope_decides() {
+        assert!(indexed("C:\\Users\\me\\Documents\\tax-2023.pdf"));
+        assert!(indexed("c:/users/me/documents/tax-2023.pdf"), "paths match case-insensitively, either separator");
+        assert!(!indexed("C:\\Users\\me\\AppData\\Local\\cache.bin"), "the AppData exclusion is more specific");
+        assert!(indexed("D:\\Archive\\2019\\scan.tif"), "a scope without a wildcard covers what is under it");
+        assert!(indexed("D:\\Archive"));
+        assert!(!indexed("D:\\Archived\\scan.tif"));
+        assert!(!indexed("E:\\backup.zip"));
+        assert!(!indexed("C:\\Users\\me\\Private\\diary.txt"), "the user's exclusion applies");
+        assert!(indexed("C:\\Users\\me\\Private\\Shared\\notes.txt"), "a more specific inclusion beats it");
+
+        let status = &index_status(&rules(), &["C:\\Users\\me\\a.docx".to_string()])[0];
+        assert_eq!(status.scope.as_deref(), Some("file:///C:\\Users\\*"));
+        assert!(index_status(&[], &["C:\\Users\\me\\a.docx".to_string()]).iter().all(|status| !status.indexed));
+    }
+
+    #[test]
+    fn a_user_rule_wins_a_tie_with_a_default_rule() {
+        let tied = vec![rule("file:///C:\\Data\\*", true, false), rule("file:///C:\\Data\\*", false, true)];
+        assert!(!index_status(&tied, &["C:\\Data\\a.txt".to_string()])[0].indexed);
+    }
+
+    #[test]
+    fn each_folder_holding_indexed_wipes_is_recrawled_once() {
+        let wiped = vec![
+            "C:\\Users\\me\\Documents\\a.docx".to_string(),
+            "C:\\Users\\me\\documents\\b.docx".to_string(),
+            "C:\\Users\\me\\Documents\\Old".to_string(),
+            "C:\\Users\\me\\AppData\\Local\\c.tmp".to_string(),
+            "E:\\d.txt".to_string(),
+        ];
+        assert_eq!(affected_folders(&rules(), &wiped), vec!["C:\\Users\\me\\Documents".to_string()]);
+    }
+
+    #[test]
+    fn the_reindex_script_quotes_every_folder() {
+        let script = reindex_script(&["C:\\Users\\me\\O'Brien".to_string(), "D:\\Archive".to_string()]);
+        assert!(script.contains("@('C:\\Users\\me\\O''Brien', 'D:\\Archive')"));
+        assert!(script.contains("ReindexMatchingURLs"));
+    }
+}
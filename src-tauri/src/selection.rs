@@ -0,0 +1,180 @@
+//! Overlapping selections. Picking `C:\data` in the context menu and dragging in
+//! `C:\data\reports\q1.pdf` used to wipe q1.pdf with its folder and then fail the second entry
+//! with "path not found". A batch's roots are now compared before it starts: exact duplicates
+//! are merged and entries inside another selected folder are dropped. Entries are compared by
+//! their resolved parent folder plus their own name, so a selected link is not mistaken for the
+//! folder it points at, and without regard to case or slash direction on Windows. The entries
+//! that remain keep the spelling they were selected with.
+
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+use crate::log_event;
+use crate::self_protection::lexically_resolved;
+
+/// Why an entry was left out of the batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeReason {
+    /// The same path was selected again.
+    Duplicate,
+    /// The path is inside a selected folder, which wipes it anyway.
+    Inside,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MergedSelection {
+    pub path: String,
+    /// The selected entry that covers it.
+    pub covered_by: String,
+    pub reason: MergeReason,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizedSelection {
+    /// The entries to wipe, in the order they were selected.
+    pub paths: Vec<String>,
+    pub merged: Vec<MergedSelection>,
+}
+
+/// `path` with its parent folders resolved through `canonicalize` and its last component as given.
+/// Paths that cannot be resolved have their `.` and `..` segments folded instead.
+fn resolved(path: &Path, canonicalize: &impl Fn(&Path) -> Option<PathBuf>) -> PathBuf {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => {
+            canonicalize(parent).unwrap_or_else(|| lexically_resolved(parent)).join(name)
+        }
+        _ => canonicalize(path).unwrap_or_else(|| lexically_resolved(path)),
+    }
+}
+
+/// What two selections are compared by: without the `\\?\` prefix canonical Windows paths carry,
+/// and with case and slashes folded where the file system ignores case.
+fn comparison_key(path: &Path, case_insensitive: bool) -> PathBuf {
+    let text = path.to_string_lossy();
+    let text = text.strip_prefix(r"\\?\").unwrap_or(&text);
+    if case_insensitive {
+        lexically_resolved(Path::new(&text.replace('\\', "/").to_lowercase()))
+    } else {
+        PathBuf::from(text)
+    }
+}
+
+fn normalize_with(
+    paths: Vec<String>,
+    case_insensitive: bool,
+    canonicalize: impl Fn(&Path) -> Option<PathBuf>,
+) -> NormalizedSelection {
+    let entries: Vec<(String, PathBuf)> = paths
+        .into_iter()
+        .map(|path| {
+            let key = comparison_key(&resolved(Path::new(&path), &canonicalize), case_insensitive);
+            (path, key)
+        })
+        .collect();
+    let mut normalized = NormalizedSelection::default();
+    let mut kept: Vec<usize> = Vec::new();
+    for (index, (path, key)) in entries.iter().enumerate() {
+        let duplicate = kept.iter().find(|&&other| entries[other].1 == *key);
+        // A folder selected after something inside it still covers it, so look at every entry.
+        let container = entries
+            .iter()
+            .position(|(_, other)| other != key && key.starts_with(other));
+        let (covered_by, reason) = match (duplicate, container) {
+            (_, Some(container)) => (container, MergeReason::Inside),
+            (Some(&first), None) => (first, MergeReason::Duplicate),
+            (None, None) => {
+                kept.push(index);
+                continue;
+            }
+        };
+        normalized.merged.push(MergedSelection {
+            path: path.clone(),
+            covered_by: entries[covered_by].0.clone(),
+            reason,
+        });
+    }
+    normalized.paths = kept.into_iter().map(|index| entries[index].0.clone()).collect();
+    normalized
+}
+
+/// Merge duplicate entries of `paths` and drop those inside another selected folder.
+pub fn normalize(paths: Vec<String>) -> NormalizedSelection {
+    normalize_with(paths, cfg!(windows), |path| path.canonicalize().ok())
+}
+
+/// `normalize` `paths` and note in the operation log what was merged.
+pub fn normalize_logged(paths: Vec<String>, operation_id: &str) -> Vec<String> {
+    let normalized = normalize(paths);
+    if !normalized.merged.is_empty() {
+        let count = normalized.merged.len();
+        log_event(
+            "selection_normalized",
+            json!({
+                "operation_id": operation_id,
+                "message": format!("{} redundant selection{} merged", count, if count == 1 { "" } else { "s" }),
+                "merged": normalized.merged,
+            }),
+        );
+    }
+    normalized.paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+    use std::fs;
+
+    fn windows_style(paths: &[&str]) -> NormalizedSelection {
+        normalize_with(paths.iter().map(|path| path.to_string()).collect(), true, |_| None)
+    }
+
+    #[test]
+    fn entries_inside_a_selected_folder_are_dropped() {
+        let normalized = windows_style(&[r"C:\data\reports\q1.pdf", r"C:\data", r"C:\database", r"C:\data\notes"]);
+        assert_eq!(normalized.paths, [r"C:\data", r"C:\database"]);
+        let merged: Vec<_> = normalized.merged.iter().map(|m| (m.path.as_str(), m.covered_by.as_str(), &m.reason)).collect();
+        assert_eq!(
+            merged,
+            [
+                (r"C:\data\reports\q1.pdf", r"C:\data", &MergeReason::Inside),
+                (r"C:\data\notes", r"C:\data", &MergeReason::Inside),
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicates_differing_in_case_and_slashes_are_merged_on_windows() {
+        let normalized = windows_style(&[r"C:\Data\Q1.pdf", "c:/data/q1.PDF", r"C:\Data\.\Q1.pdf", r"C:\Data\Q2.pdf"]);
+        assert_eq!(normalized.paths, [r"C:\Data\Q1.pdf", r"C:\Data\Q2.pdf"]);
+        assert_eq!(normalized.merged.len(), 2);
+        assert!(normalized.merged.iter().all(|m| m.reason == MergeReason::Duplicate && m.covered_by == r"C:\Data\Q1.pdf"));
+
+        // Case matters where the file system says it does.
+        let distinct = normalize_with(vec!["/srv/Data".into(), "/srv/data".into()], false, |_| None);
+        assert_eq!(distinct.paths.len(), 2);
+    }
+
+    #[test]
+    fn a_file_and_its_parent_on_disk_leave_only_the_parent() {
+        let dir = create_test_dir().unwrap();
+        fs::create_dir_all(dir.join("reports")).unwrap();
+        let file = dir.join("reports").join("q1.pdf");
+        fs::write(&file, b"q1").unwrap();
+        // Reached through `..`, so only resolving the path shows it is inside the folder.
+        let roundabout = dir.join("reports").join("..").join("reports").join("q1.pdf");
+
+        let paths = vec![
+            roundabout.to_string_lossy().to_string(),
+            dir.join("reports").to_string_lossy().to_string(),
+            file.to_string_lossy().to_string(),
+        ];
+        let normalized = normalize(paths.clone());
+        assert_eq!(normalized.paths, [paths[1].clone()]);
+        assert_eq!(normalized.merged.len(), 2);
+        assert_eq!(normalize_logged(vec![paths[2].clone(), paths[2].clone()], "op-test"), [paths[2].clone()]);
+        cleanup_test_dir(&dir);
+    }
+}
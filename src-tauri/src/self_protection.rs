@@ -0,0 +1,221 @@
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::error::BitBurnError;
+use crate::protected::normalize;
+
+/// Where BitBurn itself lives and keeps its state.
+pub trait SelfPathProvider {
+    fn executable(&self) -> Option<PathBuf>;
+    /// Config, data and log directories.
+    fn app_dirs(&self) -> Vec<PathBuf>;
+    /// BitBurn-owned files in use outside the directories above.
+    fn open_files(&self) -> Vec<PathBuf>;
+}
+
+/// Paths of the running process, without app directories (those need an `AppHandle`).
+pub struct ProcessPaths;
+
+impl SelfPathProvider for ProcessPaths {
+    fn executable(&self) -> Option<PathBuf> {
+        std::env::current_exe().ok()
+    }
+
+    fn app_dirs(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    fn open_files(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+}
+
+/// Paths of the running app, resolved through Tauri's path API.
+pub struct AppPaths<'a, R: Runtime>(pub &'a AppHandle<R>);
+
+impl<R: Runtime> SelfPathProvider for AppPaths<'_, R> {
+    fn executable(&self) -> Option<PathBuf> {
+        ProcessPaths.executable()
+    }
+
+    fn app_dirs(&self) -> Vec<PathBuf> {
+        let path = self.0.path();
+        [path.app_config_dir(), path.app_data_dir(), path.app_local_data_dir(), path.app_log_dir()]
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect()
+    }
+
+    fn open_files(&self) -> Vec<PathBuf> {
+        crate::settings::settings_path(self.0).into_iter().chain(crate::policy::policy_path()).collect()
+    }
+}
+
+/// BitBurn's own program and data locations, which no wipe may touch. There is no override.
+#[derive(Debug, Clone, Default)]
+pub struct SelfProtection {
+    /// Never wiped, nor anything inside them, nor any folder containing them.
+    trees: Vec<PathBuf>,
+    /// Never wiped, nor any folder containing them.
+    files: Vec<PathBuf>,
+}
+
+impl SelfProtection {
+    pub fn from_provider(provider: &dyn SelfPathProvider) -> Self {
+        let mut trees = provider.app_dirs();
+        let mut files = provider.open_files();
+        if let Some(exe) = provider.executable() {
+            trees.extend(exe.parent().map(Path::to_path_buf));
+            files.push(exe);
+        }
+        SelfProtection {
+            trees: trees.iter().map(|tree| comparable(tree)).collect(),
+            files: files.iter().map(|file| comparable(file)).collect(),
+        }
+    }
+
+    /// Reject `path` if it is, lies inside, or contains a protected location.
+    /// Paths are canonicalized first so `..` segments and symlinked parents cannot slip past.
+    pub fn check(&self, path: &Path) -> Result<(), BitBurnError> {
+        self.check_comparable(path, comparable(path))
+    }
+
+    fn check_comparable(&self, path: &Path, candidate: PathBuf) -> Result<(), BitBurnError> {
+        let hit = self
+            .trees
+            .iter()
+            .any(|tree| candidate.starts_with(tree) || tree.starts_with(&candidate))
+            || self.files.iter().any(|file| file.starts_with(&candidate));
+        if hit {
+            Err(BitBurnError::SelfProtection {
+                path: path.to_string_lossy().to_string(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+static SELF_PROTECTION: OnceLock<SelfProtection> = OnceLock::new();
+
+/// Resolve the app's own locations once the app directories are known.
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    let _ = SELF_PROTECTION.set(SelfProtection::from_provider(&AppPaths(app)));
+}
+
+/// Check `path` against BitBurn's own locations. Before `init` only the executable and its
+/// directory are known, which covers CLI invocations handled ahead of the app setup.
+pub fn check(path: &Path) -> Result<(), BitBurnError> {
+    SELF_PROTECTION
+        .get_or_init(|| SelfProtection::from_provider(&ProcessPaths))
+        .check(path)
+}
+
+/// Check the symbolic link `link` itself, where it is rather than what it points at, against
+/// BitBurn's own locations.
+pub fn check_link(link: &Path) -> Result<(), BitBurnError> {
+    SELF_PROTECTION
+        .get_or_init(|| SelfProtection::from_provider(&ProcessPaths))
+        .check_comparable(link, normalize(&crate::symlinks::resolved_link(link)))
+}
+
+fn comparable(path: &Path) -> PathBuf {
+    normalize(&path.canonicalize().unwrap_or_else(|_| lexically_resolved(path)))
+}
+
+/// Fold `.` and `..` segments for paths that cannot be canonicalized, e.g. ones that do not exist yet.
+pub(crate) fn lexically_resolved(path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+    use std::fs;
+
+    struct FakeInstall {
+        exe: PathBuf,
+        data: PathBuf,
+        open: PathBuf,
+    }
+
+    impl SelfPathProvider for FakeInstall {
+        fn executable(&self) -> Option<PathBuf> {
+            Some(self.exe.clone())
+        }
+
+        fn app_dirs(&self) -> Vec<PathBuf> {
+            vec![self.data.clone()]
+        }
+
+        fn open_files(&self) -> Vec<PathBuf> {
+            vec![self.open.clone()]
+        }
+    }
+
+    fn fake_install() -> (PathBuf, SelfProtection) {
+        let base = create_test_dir().unwrap();
+        for dir in ["install", "data", "logs", "documents"] {
+            fs::create_dir_all(base.join(dir)).unwrap();
+        }
+        for file in ["install/BitBurn.exe", "install/resources.pak", "data/settings.json", "logs/export.log", "logs/old.log"] {
+            fs::write(base.join(file), b"bitburn").unwrap();
+        }
+        fs::write(base.join("documents/report.docx"), b"user data").unwrap();
+
+        let protection = SelfProtection::from_provider(&FakeInstall {
+            exe: base.join("install").join("BitBurn.exe"),
+            data: base.join("data"),
+            open: base.join("logs").join("export.log"),
+        });
+        (base, protection)
+    }
+
+    fn code(result: Result<(), BitBurnError>) -> &'static str {
+        result.map(|_| "ok").unwrap_or_else(|e| e.code())
+    }
+
+    #[test]
+    fn install_dir_data_dirs_and_open_files_are_rejected() {
+        let (base, protection) = fake_install();
+        for path in ["install/BitBurn.exe", "install/resources.pak", "install", "data/settings.json", "logs/export.log", "logs"] {
+            assert_eq!(code(protection.check(&base.join(path))), "self_protection", "{}", path);
+        }
+        assert_eq!(code(protection.check(&base)), "self_protection", "parents of the install dir are protected");
+
+        assert_eq!(code(protection.check(&base.join("documents/report.docx"))), "ok");
+        assert_eq!(code(protection.check(&base.join("documents"))), "ok");
+        assert_eq!(code(protection.check(&base.join("logs/old.log"))), "ok", "only the open file in logs is protected");
+        cleanup_test_dir(&base);
+    }
+
+    #[test]
+    fn dot_dot_segments_do_not_bypass_the_check() {
+        let (base, protection) = fake_install();
+        let sneaky = base.join("documents").join("..").join("install").join(".").join("BitBurn.exe");
+        assert_eq!(code(protection.check(&sneaky)), "self_protection");
+
+        let missing = base.join("documents").join("..").join("data").join("new.json");
+        assert_eq!(code(protection.check(&missing)), "self_protection");
+        cleanup_test_dir(&base);
+    }
+
+    #[test]
+    fn the_running_executable_is_protected_before_init() {
+        let exe = std::env::current_exe().unwrap();
+        assert_eq!(code(check(&exe)), "self_protection");
+        assert_eq!(code(check(&std::env::temp_dir().join("unrelated.txt"))), "ok");
+    }
+}
@@ -0,0 +1,405 @@
+//! Self-test: wipe a scratch file of known content on a chosen volume with NIST 800-88 Purge and
+//! check what is left, so a user can see the engine work on their own machine and file system
+//! before trusting it with real data. Every check is reported with a pass or fail and, when it
+//! fails, a message saying what to do about it. The scratch folder goes whatever happens.
+
+use bitburn_core::{
+    finish_wipe, open_wipe_target, overwrite_target, secure_wipe_file, CancellationToken, FileWipeProgressRecord,
+    WipeAlgorithm, WipeProgress, WipeTarget,
+};
+use serde::Serialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::async_runtime::spawn_blocking;
+use tauri::State;
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::messages::{self, Message};
+use crate::named_streams;
+use crate::remote_tier;
+use crate::scratch;
+
+pub const SAMPLE_BYTES: u64 = 4 * 1024 * 1024;
+const SAMPLE_FILE: &str = "self-test.bin";
+/// Written over and over to fill the sample, so its content is known.
+const SAMPLE_MARKER: &[u8] = b"BitBurn self-test sample data. ";
+const SAMPLE_STREAM: &str = ":bitburn-self-test";
+const ALGORITHM: WipeAlgorithm = WipeAlgorithm::NistPurge;
+/// The file's data is stored compressed, so what reaches the disk is not what was written.
+const FILE_ATTRIBUTE_COMPRESSED: u32 = 0x0000_0800;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    /// Does not apply to this volume, e.g. named streams off NTFS.
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SelfTestCheck {
+    /// `sample_written`, `wipe_completed`, `verification`, `named_streams`, `file_removed`,
+    /// `directory_entry_removed` or `scratch_removed`.
+    pub name: &'static str,
+    pub status: CheckStatus,
+    /// Why the check failed or was skipped, and what to do about it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<Message>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SelfTestReport {
+    pub target_volume: String,
+    pub algorithm: WipeAlgorithm,
+    pub passes: u32,
+    pub sample_bytes: u64,
+    /// Bytes written by all passes over the time they took.
+    pub bytes_per_second: u64,
+    /// No check failed.
+    pub passed: bool,
+    pub checks: Vec<SelfTestCheck>,
+    pub summary: Message,
+}
+
+impl SelfTestCheck {
+    fn passed(name: &'static str) -> Self {
+        SelfTestCheck { name, status: CheckStatus::Passed, message: None }
+    }
+
+    fn failed(name: &'static str, message: Message) -> Self {
+        SelfTestCheck { name, status: CheckStatus::Failed, message: Some(message) }
+    }
+
+    fn skipped(name: &'static str, message: Message) -> Self {
+        SelfTestCheck { name, status: CheckStatus::Skipped, message: Some(message) }
+    }
+}
+
+/// A file that remembers the SHA-256 of the last complete pass written to it, so the data can be
+/// read back and compared once the passes are done.
+struct VerifiedFile {
+    file: File,
+    hasher: Sha256,
+    last_pass: Option<Vec<u8>>,
+}
+
+impl WipeTarget for VerifiedFile {
+    fn size(&mut self) -> io::Result<u64> {
+        self.file.size()
+    }
+
+    fn rewind(&mut self) -> io::Result<()> {
+        self.hasher = Sha256::new();
+        WipeTarget::rewind(&mut self.file)
+    }
+
+    fn write_chunk(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.hasher.update(buf);
+        self.file.write_chunk(buf)
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.last_pass = Some(self.hasher.clone().finalize().to_vec());
+        self.file.sync()
+    }
+
+    fn truncate(&mut self) -> io::Result<()> {
+        self.file.truncate()
+    }
+}
+
+impl VerifiedFile {
+    /// Whether reading the file back gives exactly the last pass written.
+    fn read_back_matches(&mut self) -> io::Result<bool> {
+        self.file.seek(SeekFrom::Start(0))?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            match self.file.read(&mut buffer)? {
+                0 => break,
+                read => hasher.update(&buffer[..read]),
+            }
+        }
+        Ok(self.last_pass.as_deref() == Some(hasher.finalize().as_slice()))
+    }
+}
+
+fn write_sample(path: &Path, bytes: u64) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    let contents: Vec<u8> = SAMPLE_MARKER.iter().copied().cycle().take(bytes as usize).collect();
+    file.write_all(&contents)?;
+    file.sync_all()?;
+    drop(file);
+    if fs::read(path)? != contents {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "the sample read back differs from what was written"));
+    }
+    Ok(())
+}
+
+/// Give the sample a named stream where the file system has them; `false` where it does not.
+fn add_sample_stream(sample: &Path) -> bool {
+    let mut stream = sample.as_os_str().to_os_string();
+    stream.push(SAMPLE_STREAM);
+    cfg!(windows) && fs::write(&stream, SAMPLE_MARKER).is_ok() && !named_streams::list(sample).is_empty()
+}
+
+/// Overwrite a sample left behind by a check that stopped early, so the folder is removed clean.
+fn discard_sample(sample: &Path) {
+    if sample.exists() {
+        let wiped = secure_wipe_file(sample, 1, &WipeAlgorithm::NistClear, &CancellationToken::new(), |_: WipeProgress| {});
+        if let Err(e) = wiped {
+            log_event("self_test_sample_wipe_failed", json!({"path": sample.to_string_lossy(), "message": e.to_string()}));
+        }
+    }
+}
+
+fn wipe_failed(error: &BitBurnError) -> SelfTestCheck {
+    SelfTestCheck::failed("wipe_completed", messages::SELF_TEST_WIPE_FAILED.render(json!({"error": error.to_string()})))
+}
+
+fn io_message(e: &io::Error, path: &Path) -> Message {
+    Message::from_error(&BitBurnError::io(e, Some(path)))
+}
+
+/// Everything up to and including deleting the sample, pushing a check for each step. Stops at
+/// the first step the rest cannot go on without; `Err` only when cancelled.
+fn wipe_sample(
+    sample: &Path,
+    volume: &str,
+    sample_bytes: u64,
+    cancel: &CancellationToken,
+    checks: &mut Vec<SelfTestCheck>,
+) -> Result<Duration, BitBurnError> {
+    if let Err(e) = write_sample(sample, sample_bytes) {
+        checks.push(SelfTestCheck::failed("sample_written", io_message(&e, sample)));
+        return Ok(Duration::ZERO);
+    }
+    checks.push(SelfTestCheck::passed("sample_written"));
+    let has_stream = add_sample_stream(sample);
+
+    let passes = ALGORITHM.default_passes();
+    let file = match open_wipe_target(sample, passes) {
+        Ok(file) => file,
+        Err(e) => {
+            let error = BitBurnError::from_wipe_error(e, sample);
+            checks.push(wipe_failed(&error));
+            return Ok(Duration::ZERO);
+        }
+    };
+    let compressed = file.metadata().map(|m| remote_tier::attributes(&m) & FILE_ATTRIBUTE_COMPRESSED != 0).unwrap_or(false);
+    let mut target = VerifiedFile { file, hasher: Sha256::new(), last_pass: None };
+    let mut record = FileWipeProgressRecord::new(passes);
+    let started = Instant::now();
+    let rng = &mut rand::thread_rng();
+    let overwritten = overwrite_target(&mut target, passes, &ALGORITHM, rng, cancel, |_: WipeProgress| {}, &mut record);
+    let elapsed = started.elapsed();
+    if let Err(e) = overwritten {
+        match BitBurnError::from_wipe_error(e, sample) {
+            BitBurnError::Cancelled => return Err(BitBurnError::Cancelled),
+            error => {
+                checks.push(wipe_failed(&error));
+                return Ok(elapsed);
+            }
+        }
+    }
+
+    checks.push(if compressed {
+        SelfTestCheck::failed("verification", messages::SELF_TEST_VERIFICATION_UNSUPPORTED.render(json!({"volume": volume})))
+    } else {
+        match target.read_back_matches() {
+            Ok(true) => SelfTestCheck::passed("verification"),
+            Ok(false) => {
+                SelfTestCheck::failed("verification", messages::SELF_TEST_VERIFICATION_MISMATCH.render(json!({"volume": volume})))
+            }
+            Err(e) => SelfTestCheck::failed("verification", io_message(&e, sample)),
+        }
+    });
+
+    checks.push(if !has_stream {
+        SelfTestCheck::skipped("named_streams", messages::SELF_TEST_STREAMS_UNSUPPORTED.render(json!({"volume": volume})))
+    } else {
+        match named_streams::wipe(sample, passes, &ALGORITHM, cancel) {
+            Ok(_) if named_streams::list(sample).is_empty() => SelfTestCheck::passed("named_streams"),
+            Ok(_) => SelfTestCheck::failed("named_streams", messages::SELF_TEST_STREAM_REMAINS.text()),
+            Err(e) => match BitBurnError::from_wipe_error(e, sample) {
+                BitBurnError::Cancelled => return Err(BitBurnError::Cancelled),
+                error => SelfTestCheck::failed("named_streams", Message::from_error(&error)),
+            },
+        }
+    });
+
+    match finish_wipe(target, sample, cancel) {
+        Ok(()) => checks.push(SelfTestCheck::passed("wipe_completed")),
+        Err(e) => match BitBurnError::from_wipe_error(e, sample) {
+            BitBurnError::Cancelled => return Err(BitBurnError::Cancelled),
+            error => checks.push(wipe_failed(&error)),
+        },
+    }
+    Ok(elapsed)
+}
+
+/// Run the self-test with a `sample_bytes` sample in a scratch folder in `target_volume`, a
+/// folder on the volume to test or its root. `Err` when the scratch folder cannot be created or
+/// the test is cancelled; failed checks are part of the report.
+pub fn run(target_volume: &Path, sample_bytes: u64, cancel: &CancellationToken) -> Result<SelfTestReport, BitBurnError> {
+    let volume = target_volume.to_string_lossy().to_string();
+    // Room for the sample and for the scratch file the named-stream check adds.
+    let scratch = scratch::ensure_scratch_space_on(target_volume, sample_bytes * 2)?;
+    let scratch_path = scratch.path().to_path_buf();
+    let sample = scratch_path.join(SAMPLE_FILE);
+    let mut checks = Vec::new();
+
+    let wiped = wipe_sample(&sample, &volume, sample_bytes, cancel, &mut checks);
+    discard_sample(&sample);
+    if wiped.is_ok() && checks.iter().any(|check| check.name == "wipe_completed" && check.status == CheckStatus::Passed) {
+        checks.push(if sample.exists() {
+            let path = sample.to_string_lossy();
+            SelfTestCheck::failed("file_removed", messages::SELF_TEST_FILE_REMAINS.render(json!({"path": path})))
+        } else {
+            SelfTestCheck::passed("file_removed")
+        });
+        let listed = fs::read_dir(&scratch_path)
+            .map(|entries| entries.flatten().any(|entry| entry.file_name() == SAMPLE_FILE))
+            .unwrap_or(false);
+        checks.push(if listed {
+            SelfTestCheck::failed(
+                "directory_entry_removed",
+                messages::SELF_TEST_ENTRY_REMAINS.render(json!({"path": scratch_path.to_string_lossy()})),
+            )
+        } else {
+            SelfTestCheck::passed("directory_entry_removed")
+        });
+    }
+    drop(scratch);
+    let elapsed = wiped?;
+    checks.push(if scratch_path.exists() {
+        let path = scratch_path.to_string_lossy();
+        SelfTestCheck::failed("scratch_removed", messages::SELF_TEST_SCRATCH_REMAINS.render(json!({"path": path})))
+    } else {
+        SelfTestCheck::passed("scratch_removed")
+    });
+
+    let passes = ALGORITHM.default_passes();
+    let failed = checks.iter().filter(|check| check.status == CheckStatus::Failed).count();
+    let summary = match failed {
+        0 => messages::SELF_TEST_PASSED.render(json!({"count": checks.len(), "volume": volume})),
+        _ => messages::SELF_TEST_FAILED.render(json!({"failed": failed, "count": checks.len(), "volume": volume})),
+    };
+    let written = sample_bytes * passes as u64;
+    Ok(SelfTestReport {
+        target_volume: volume,
+        algorithm: ALGORITHM,
+        passes,
+        sample_bytes,
+        bytes_per_second: (written as f64 / elapsed.max(Duration::from_millis(1)).as_secs_f64()) as u64,
+        passed: failed == 0,
+        checks,
+        summary,
+    })
+}
+
+/// The self-test in progress, so `cancel_self_test` can stop it.
+#[derive(Default)]
+pub struct SelfTestState {
+    running: Mutex<Option<CancellationToken>>,
+}
+
+impl SelfTestState {
+    /// Token for a new self-test; one still running is cancelled, as only the latest is wanted.
+    fn begin(&self) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Some(previous) = self.running.lock().unwrap_or_else(|p| p.into_inner()).replace(token.clone()) {
+            previous.cancel();
+        }
+        token
+    }
+
+    fn cancel(&self) -> bool {
+        match self.running.lock().unwrap_or_else(|p| p.into_inner()).take() {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Check the wipe engine on the volume holding `target_volume` (a folder on it, or its root).
+#[tauri::command]
+pub async fn run_self_test(state: State<'_, SelfTestState>, target_volume: String) -> Result<SelfTestReport, BitBurnError> {
+    let cancel = state.begin();
+    let location = PathBuf::from(&target_volume);
+    let task_cancel = cancel.clone();
+    let report = spawn_blocking(move || run(&location, SAMPLE_BYTES, &task_cancel))
+        .await
+        .map_err(|e| BitBurnError::internal(format!("run_self_test join error: {}", e)))?;
+    // Clear the slot unless a newer self-test has already replaced this one.
+    state.running.lock().unwrap_or_else(|p| p.into_inner()).take_if(|running| Arc::ptr_eq(running.flag(), cancel.flag()));
+    let report = report?;
+    log_event(
+        "self_test_finished",
+        json!({
+            "target_volume": target_volume,
+            "passed": report.passed,
+            "checks": report.checks,
+            "bytes_per_second": report.bytes_per_second,
+        }),
+    );
+    Ok(report)
+}
+
+/// Stop the running self-test. Returns false when none is running.
+#[tauri::command]
+pub async fn cancel_self_test(state: State<'_, SelfTestState>) -> Result<bool, BitBurnError> {
+    Ok(state.cancel())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_dir;
+
+    #[test]
+    fn every_check_passes_in_the_temp_dir_and_nothing_is_left_behind() {
+        let parent = create_test_dir().unwrap();
+        let report = run(&parent, 256 * 1024, &CancellationToken::new()).unwrap();
+        let failed: Vec<_> = report.checks.iter().filter(|check| check.status == CheckStatus::Failed).collect();
+        assert!(report.passed, "failed checks: {:?}", failed);
+        let names: Vec<&str> = report.checks.iter().map(|check| check.name).collect();
+        assert_eq!(
+            names,
+            [
+                "sample_written",
+                "verification",
+                "named_streams",
+                "wipe_completed",
+                "file_removed",
+                "directory_entry_removed",
+                "scratch_removed"
+            ]
+        );
+        assert_eq!(report.passes, 3);
+        assert!(report.bytes_per_second > 0);
+        assert_eq!(report.summary.code, "self_test_passed");
+        assert_eq!(fs::read_dir(&parent).unwrap().count(), 0, "scratch artifacts remain");
+        fs::remove_dir(&parent).unwrap();
+    }
+
+    #[test]
+    fn a_cancelled_self_test_still_removes_its_scratch_folder() {
+        let parent = create_test_dir().unwrap();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert_eq!(run(&parent, 256 * 1024, &cancel), Err(BitBurnError::Cancelled));
+        assert_eq!(fs::read_dir(&parent).unwrap().count(), 0, "scratch artifacts remain");
+        fs::remove_dir(&parent).unwrap();
+    }
+}
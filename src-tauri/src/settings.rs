@@ -0,0 +1,317 @@
+use bitburn_core::{VerifyMode, WipePattern, DEFAULT_CHUNK_FILES};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::completion_alert::CompletionAlertSettings;
+use crate::context_policy::ContextMenuBehavior;
+use crate::emergency_stop::{EmergencyShortcut, EmergencyShortcutSettings};
+use crate::error::BitBurnError;
+use crate::lock_scan::LockScanMode;
+use crate::retention::{RetentionRule, DEFAULT_GRACE_MINUTES};
+use crate::webhook::WebhookSettings;
+use crate::wipe_finish::CancelCleanup;
+use crate::{log_event, WipeAlgorithm};
+
+const SETTINGS_FILE_NAME: &str = "settings.json";
+
+/// Saved outer window rectangle (physical pixels) and the monitor it was on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowLayout {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub monitor: Option<String>,
+}
+
+/// Persistent user settings. Unknown or missing fields fall back to defaults
+/// so older settings files keep loading after upgrades.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub window_layout: Option<WindowLayout>,
+    pub webhook: WebhookSettings,
+    pub retention_rules: Vec<RetentionRule>,
+    /// Minutes between announcing a retention run and wiping; `None` uses the default.
+    pub retention_grace_minutes: Option<u32>,
+    /// Append an Ed25519 signature to exported wipe reports.
+    pub sign_reports: bool,
+    /// What a context-menu invocation does: hand off to the UI, wipe straight away, or queue.
+    pub context_menu_behavior: ContextMenuBehavior,
+    /// Algorithm for wipes started without the UI; `None` uses NIST 800-88 Purge.
+    pub default_algorithm: Option<WipeAlgorithm>,
+    /// Seconds without write progress before `wipe_stalled` is emitted; `None` uses 60.
+    pub stall_warning_seconds: Option<u64>,
+    /// Seconds without write progress before a file is abandoned; `None` uses 600, `Some(0)` never times out.
+    pub file_timeout_seconds: Option<u64>,
+    /// Cap on `wipe_progress` events per second per operation; `None` uses 20.
+    pub progress_events_per_second: Option<u32>,
+    /// Rename each file to a random name before deleting it.
+    pub obfuscate_file_names: bool,
+    /// What a cancelled wipe does with a file whose contents are already destroyed.
+    pub cancel_cleanup: CancelCleanup,
+    /// When file batches are checked for files locked by other programs before anything is overwritten.
+    pub lock_scan: LockScanMode,
+    /// Restore the timestamps of directories files were wiped from, so they do not date the wipe.
+    pub preserve_parent_mtime: bool,
+    /// Refuse file wipes on USB sticks and memory cards instead of only warning about them.
+    pub block_file_wipe_on_flash: bool,
+    /// Keep `status.json` in the app data directory up to date for external monitoring tools.
+    pub status_file: bool,
+    /// Bytes written to an SSD over 30 days, including a planned free-space wipe, above which
+    /// the estimate warns about wear; `None` uses 500 GiB.
+    pub wear_warning_bytes: Option<u64>,
+    /// Files at least this big record checkpoints so an interrupted wipe can resume part-way;
+    /// `None` uses 4 GiB, `Some(0)` never checkpoints.
+    pub checkpoint_min_file_bytes: Option<u64>,
+    /// Bytes written between a checkpointed file's checkpoints; `None` uses 1 GiB.
+    pub checkpoint_interval_bytes: Option<u64>,
+    /// Written in a final pass after every algorithm, and used to fill free space, instead of
+    /// leaving zeros or noise behind; `None` writes no decoy.
+    pub decoy_pattern: Option<WipePattern>,
+    /// Refuse file and free-space wipes not started with a consent token from
+    /// `show_confirmation_dialog`, so every report can show the user agreed to it.
+    pub confirm_before_wipe: bool,
+    /// Show the main window again, with the results, when a wipe finishes while it is hidden to the tray.
+    pub reopen_on_completion: bool,
+    /// Flash the taskbar button and play a sound when a wipe finishes while the window is in the background.
+    pub completion_alert: CompletionAlertSettings,
+    /// Also wipe the owner, lock, backup and swap files editors leave beside each selected file.
+    pub companion_files: bool,
+    /// Files a folder walk, the lock scan and a batch's report hold in memory at a time; larger
+    /// selections are handled a chunk at a time. `None` uses 50,000.
+    pub chunk_files: Option<usize>,
+    /// Overwrite the named data streams of folders before removing them; also done whenever
+    /// `obfuscate_file_names` is on.
+    pub wipe_named_streams: bool,
+    /// Ask Windows Search to re-crawl the folders a batch wiped indexed files in, so their names
+    /// and snippets leave the index.
+    pub purge_search_index: bool,
+    /// Global shortcut that cancels every running operation.
+    pub emergency_shortcut: EmergencyShortcutSettings,
+    /// Keep other programs out of each file while it is wiped; `None` does.
+    pub exclusive_lock: Option<bool>,
+    /// Read passes back after writing them, in full, sampled or the last one only; `None` does not.
+    pub verify_mode: Option<VerifyMode>,
+    /// Wipes on separate volumes that may run at once; `None` allows two.
+    pub max_parallel_jobs: Option<usize>,
+}
+
+impl Settings {
+    pub fn retention_grace_period(&self) -> std::time::Duration {
+        let minutes = self.retention_grace_minutes.unwrap_or(DEFAULT_GRACE_MINUTES);
+        std::time::Duration::from_secs(u64::from(minutes) * 60)
+    }
+
+    pub fn chunk_files(&self) -> usize {
+        self.chunk_files.unwrap_or(DEFAULT_CHUNK_FILES).max(1)
+    }
+
+    pub fn max_parallel_jobs(&self) -> usize {
+        self.max_parallel_jobs
+            .unwrap_or(crate::scheduler::DEFAULT_MAX_PARALLEL_JOBS)
+            .clamp(1, crate::scheduler::MAX_PARALLEL_JOBS)
+    }
+
+    pub fn exclusive_lock(&self) -> bool {
+        self.exclusive_lock.unwrap_or(true)
+    }
+
+    pub fn default_algorithm(&self) -> WipeAlgorithm {
+        self.default_algorithm.clone().unwrap_or(WipeAlgorithm::NistPurge)
+    }
+}
+
+/// Settings loaded from disk and shared through managed state.
+/// Every update is written back atomically (temp file + rename).
+pub struct SettingsStore {
+    path: Option<PathBuf>,
+    settings: Mutex<Settings>,
+    /// Fields the machine policy does not let the user change.
+    locked: Mutex<Vec<String>>,
+}
+
+impl SettingsStore {
+    /// Load settings from `path`, using defaults when the file is missing or unreadable.
+    pub fn load(path: PathBuf) -> Self {
+        let settings = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                log_event(
+                    "settings_load_error",
+                    json!({"path": path.to_string_lossy(), "message": e.to_string()}),
+                );
+                Settings::default()
+            }),
+            Err(_) => Settings::default(),
+        };
+
+        SettingsStore {
+            path: Some(path),
+            settings: Mutex::new(settings),
+            locked: Mutex::default(),
+        }
+    }
+
+    /// Store that never touches disk; used when the config directory cannot be resolved.
+    pub fn in_memory(settings: Settings) -> Self {
+        SettingsStore {
+            path: None,
+            settings: Mutex::new(settings),
+            locked: Mutex::default(),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.lock().clone()
+    }
+
+    /// Replace the fields `update` refuses to change.
+    pub fn set_locked(&self, fields: Vec<String>) {
+        *self.locked.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = fields;
+    }
+
+    /// Apply `change` and persist the result. A change to a field the policy locks is refused
+    /// and nothing is written.
+    pub fn update<F>(&self, change: F) -> Result<Settings, BitBurnError>
+    where
+        F: FnOnce(&mut Settings),
+    {
+        let mut settings = self.lock();
+        let mut updated = settings.clone();
+        change(&mut updated);
+        let locked = self.locked.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+        if let Some(field) = changed_field(&settings, &updated, &locked) {
+            return Err(BitBurnError::BlockedByPolicy {
+                rule: "locked_settings".to_string(),
+                detail: format!("{} is locked", field),
+            });
+        }
+        if let Some(path) = &self.path {
+            write_settings_file(path, &updated)?;
+        }
+        *settings = updated.clone();
+        Ok(updated)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Settings> {
+        self.settings.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The first of `fields` whose value differs between `before` and `after`.
+fn changed_field<'a>(before: &Settings, after: &Settings, fields: &'a [String]) -> Option<&'a str> {
+    if fields.is_empty() {
+        return None;
+    }
+    let (before, after) = (serde_json::to_value(before).ok()?, serde_json::to_value(after).ok()?);
+    fields.iter().map(String::as_str).find(|field| before.get(field) != after.get(field))
+}
+
+fn write_settings_file(path: &Path, settings: &Settings) -> Result<(), BitBurnError> {
+    let serialized = serde_json::to_string_pretty(settings)
+        .map_err(|e| BitBurnError::internal(format!("Failed to serialize settings: {}", e)))?;
+    write_file_atomic(path, serialized.as_bytes())
+}
+
+/// Write `contents` next to `path` and rename it into place so readers never see a partial file.
+pub(crate) fn write_file_atomic(path: &Path, contents: &[u8]) -> Result<(), BitBurnError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| BitBurnError::io(&e, Some(parent)))?;
+    }
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    fs::write(&temp_path, contents).map_err(|e| BitBurnError::io(&e, Some(&temp_path)))?;
+    fs::rename(&temp_path, path).map_err(|e| BitBurnError::io(&e, Some(path)))?;
+    Ok(())
+}
+
+/// Location of the settings file inside the app config directory.
+pub fn settings_path<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join(SETTINGS_FILE_NAME))
+}
+
+/// Build the managed settings store for the running app.
+pub fn init_settings<R: Runtime>(app: &AppHandle<R>) -> SettingsStore {
+    match settings_path(app) {
+        Some(path) => SettingsStore::load(path),
+        None => {
+            log_event("settings_path_unavailable", json!({"action": "using in-memory settings"}));
+            SettingsStore::in_memory(Settings::default())
+        }
+    }
+}
+
+/// Current settings, without secrets, and problems with them that only show up at runtime.
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsView {
+    pub settings: Settings,
+    pub issues: Vec<BitBurnError>,
+}
+
+#[tauri::command]
+pub async fn get_settings(
+    settings: State<'_, SettingsStore>,
+    emergency_shortcut: State<'_, EmergencyShortcut>,
+) -> Result<SettingsView, BitBurnError> {
+    let mut current = settings.get();
+    current.webhook.bearer_token = None;
+    Ok(SettingsView {
+        settings: current,
+        issues: emergency_shortcut.problem().into_iter().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    #[test]
+    fn missing_file_loads_defaults() {
+        let path = create_test_dir().unwrap().join(SETTINGS_FILE_NAME);
+        let store = SettingsStore::load(path.clone());
+        assert_eq!(store.get(), Settings::default());
+        cleanup_test_dir(path.parent().unwrap());
+    }
+
+    #[test]
+    fn corrupt_file_loads_defaults() {
+        let path = create_test_dir().unwrap().join(SETTINGS_FILE_NAME);
+        fs::write(&path, "{not json").unwrap();
+
+        let store = SettingsStore::load(path.clone());
+        assert_eq!(store.get(), Settings::default());
+        cleanup_test_dir(path.parent().unwrap());
+    }
+
+    #[test]
+    fn updates_persist_across_loads() {
+        let path = create_test_dir().unwrap().join(SETTINGS_FILE_NAME);
+        let store = SettingsStore::load(path.clone());
+        let layout = WindowLayout {
+            x: 10,
+            y: 20,
+            width: 800,
+            height: 1000,
+            monitor: Some("DISPLAY1".to_string()),
+        };
+        store
+            .update(|s| s.window_layout = Some(layout.clone()))
+            .expect("settings should save");
+
+        let reloaded = SettingsStore::load(path.clone());
+        assert_eq!(reloaded.get().window_layout, Some(layout));
+        assert!(!path.with_extension("json.tmp").exists());
+        cleanup_test_dir(path.parent().unwrap());
+    }
+}
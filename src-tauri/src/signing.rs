@@ -0,0 +1,503 @@
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime, State};
+use zeroize::Zeroizing;
+
+use crate::ed25519;
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::scratch::restrict_to_owner;
+use crate::messages::{self, Message, MessageDef};
+use crate::settings::{write_file_atomic, SettingsStore};
+
+const SIGNING_DIR: &str = "signing";
+const PRIVATE_KEY_FILE: &str = "report_signing.key";
+const KEYRING_FILE: &str = "report_signing_keys.json";
+/// Top-level field holding the detached signature in an exported report.
+pub const SIGNATURE_FIELD: &str = "signature";
+const ALGORITHM: &str = "ed25519";
+
+/// The private key's seed, zeroed when dropped.
+type Seed = Zeroizing<[u8; ed25519::SEED_LEN]>;
+
+/// A public key the app has signed reports with. Retired keys stay in the
+/// keyring so reports signed before a rotation still verify.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublicKeyRecord {
+    pub fingerprint: String,
+    pub public_key: String,
+    pub created_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retired_at: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Keyring {
+    keys: Vec<PublicKeyRecord>,
+}
+
+/// Current signing key plus the retired keys still accepted for verification.
+#[derive(Debug, Clone, Serialize)]
+pub struct SigningKeyInfo {
+    pub enabled: bool,
+    pub current: PublicKeyRecord,
+    pub retired: Vec<PublicKeyRecord>,
+}
+
+/// Result of checking an exported report against the local keyring.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportVerification {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_fingerprint: Option<String>,
+    /// Signed by the key currently in use rather than a retired one.
+    pub current_key: bool,
+    #[serde(flatten)]
+    pub message: Message,
+}
+
+/// `SHA256:` followed by the hex digest of the raw public key.
+pub fn fingerprint(public_key: &[u8]) -> String {
+    format!("SHA256:{}", hex::encode(Sha256::digest(public_key)))
+}
+
+/// Deterministic JSON: object keys sorted, no insignificant whitespace.
+pub fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| format!("{}:{}", Value::String(key.clone()), canonical_json(&map[key])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Bytes covered by the signature: the canonical document without its signature block.
+fn signed_payload(document: &Map<String, Value>) -> Vec<u8> {
+    let mut unsigned = document.clone();
+    unsigned.remove(SIGNATURE_FIELD);
+    canonical_json(&Value::Object(unsigned)).into_bytes()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn signing_error(message: impl Into<String>) -> BitBurnError {
+    BitBurnError::Signing { message: message.into() }
+}
+
+/// Write the private key readable by the owner only. The temp file is created fresh, never
+/// reused, and restricted through its handle before anything is written to it: on Unix with mode
+/// 0600, on Windows with a protected DACL for the owner alone.
+fn write_private_key(path: &Path, contents: &[u8]) -> Result<(), BitBurnError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| BitBurnError::io(&e, Some(parent)))?;
+    }
+    let temp_path = path.with_extension("key.tmp");
+    // Left by a crash mid-write; whoever can write the directory could also have planted it.
+    match fs::remove_file(&temp_path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(BitBurnError::io(&e, Some(&temp_path))),
+        _ => {}
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&temp_path)
+        .map_err(|e| BitBurnError::io(&e, Some(&temp_path)))?;
+    let written = restrict_to_owner(&file)
+        .and_then(|_| file.write_all(contents))
+        .and_then(|_| file.sync_all());
+    drop(file);
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp_path);
+        return Err(BitBurnError::io(&e, Some(&temp_path)));
+    }
+    fs::rename(&temp_path, path).map_err(|e| BitBurnError::io(&e, Some(path)))
+}
+
+/// Report signing key stored in the app data directory, generated on first use.
+pub struct ReportSigner {
+    dir: Option<PathBuf>,
+    lock: Mutex<()>,
+}
+
+impl ReportSigner {
+    pub fn new(dir: PathBuf) -> Self {
+        ReportSigner {
+            dir: Some(dir),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Signer used when the app data directory cannot be resolved; every call fails.
+    pub fn unavailable() -> Self {
+        ReportSigner {
+            dir: None,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn dir(&self) -> Result<&Path, BitBurnError> {
+        self.dir
+            .as_deref()
+            .ok_or_else(|| signing_error("App data directory is unavailable"))
+    }
+
+    fn read_keyring(&self) -> Result<Keyring, BitBurnError> {
+        let path = self.dir()?.join(KEYRING_FILE);
+        match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| signing_error(format!("Corrupt keyring {}: {}", path.display(), e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Keyring::default()),
+            Err(e) => Err(BitBurnError::io(&e, Some(&path))),
+        }
+    }
+
+    fn write_keyring(&self, keyring: &Keyring) -> Result<(), BitBurnError> {
+        let serialized = serde_json::to_string_pretty(keyring)
+            .map_err(|e| BitBurnError::internal(format!("Failed to serialize keyring: {}", e)))?;
+        write_file_atomic(&self.dir()?.join(KEYRING_FILE), serialized.as_bytes())
+    }
+
+    fn read_seed(&self) -> Result<Option<Seed>, BitBurnError> {
+        let path = self.dir()?.join(PRIVATE_KEY_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => Zeroizing::new(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(BitBurnError::io(&e, Some(&path))),
+        };
+        let bytes = Zeroizing::new(hex::decode(contents.trim()).unwrap_or_default());
+        if bytes.len() != ed25519::SEED_LEN {
+            return Err(signing_error(format!("Corrupt private key {}", path.display())));
+        }
+        let mut seed = Seed::new([0u8; ed25519::SEED_LEN]);
+        seed.copy_from_slice(&bytes);
+        Ok(Some(seed))
+    }
+
+    /// Generate a new key, retiring (but keeping) the current public key.
+    fn generate(&self, keyring: &mut Keyring) -> Result<(Seed, PublicKeyRecord), BitBurnError> {
+        let mut seed = Seed::new([0u8; ed25519::SEED_LEN]);
+        rand::rngs::OsRng.fill_bytes(seed.as_mut());
+        let public = ed25519::public_key(&seed);
+        let now = now_secs();
+        for record in keyring.keys.iter_mut().filter(|r| r.retired_at.is_none()) {
+            record.retired_at = Some(now);
+        }
+        let record = PublicKeyRecord {
+            fingerprint: fingerprint(&public),
+            public_key: hex::encode(public),
+            created_at: now,
+            retired_at: None,
+        };
+        keyring.keys.push(record.clone());
+
+        // Publish the public key before replacing the private one so a crash
+        // in between never leaves a signing key without its verifier.
+        self.write_keyring(keyring)?;
+        write_private_key(&self.dir()?.join(PRIVATE_KEY_FILE), Zeroizing::new(hex::encode(&seed[..])).as_bytes())?;
+        log_event("signing_key_generated", json!({"fingerprint": record.fingerprint}));
+        Ok((seed, record))
+    }
+
+    /// Current key, creating one on first use.
+    fn current_key(&self) -> Result<(Seed, PublicKeyRecord), BitBurnError> {
+        let mut keyring = self.read_keyring()?;
+        if let Some(seed) = self.read_seed()? {
+            let expected = fingerprint(&ed25519::public_key(&seed));
+            if let Some(record) = keyring.keys.iter().find(|r| r.fingerprint == expected) {
+                return Ok((seed, record.clone()));
+            }
+            return Err(signing_error("Private key does not match any public key in the keyring"));
+        }
+        self.generate(&mut keyring)
+    }
+
+    pub fn key_info(&self, enabled: bool) -> Result<SigningKeyInfo, BitBurnError> {
+        let _guard = self.lock.lock().unwrap_or_else(|p| p.into_inner());
+        let (_, current) = self.current_key()?;
+        let retired = self
+            .read_keyring()?
+            .keys
+            .into_iter()
+            .filter(|r| r.fingerprint != current.fingerprint)
+            .collect();
+        Ok(SigningKeyInfo { enabled, current, retired })
+    }
+
+    pub fn rotate(&self) -> Result<PublicKeyRecord, BitBurnError> {
+        let _guard = self.lock.lock().unwrap_or_else(|p| p.into_inner());
+        let mut keyring = self.read_keyring()?;
+        let (_, record) = self.generate(&mut keyring)?;
+        Ok(record)
+    }
+
+    /// Append a detached signature over the canonical form of `document`.
+    pub fn sign_document(&self, document: &mut Map<String, Value>) -> Result<(), BitBurnError> {
+        let _guard = self.lock.lock().unwrap_or_else(|p| p.into_inner());
+        let (seed, record) = self.current_key()?;
+        let signature = ed25519::sign(&seed, &signed_payload(document));
+        document.insert(
+            SIGNATURE_FIELD.to_string(),
+            json!({
+                "algorithm": ALGORITHM,
+                "key_fingerprint": record.fingerprint,
+                "value": hex::encode(signature),
+            }),
+        );
+        Ok(())
+    }
+
+    /// Check a signed document against every key in the local keyring.
+    pub fn verify_document(&self, document: &Map<String, Value>) -> Result<ReportVerification, BitBurnError> {
+        let invalid = |fingerprint: Option<String>, message: MessageDef| ReportVerification {
+            valid: false,
+            key_fingerprint: fingerprint,
+            current_key: false,
+            message: message.text(),
+        };
+
+        let Some(block) = document.get(SIGNATURE_FIELD).and_then(Value::as_object) else {
+            return Ok(invalid(None, messages::REPORT_NOT_SIGNED));
+        };
+        let key_fingerprint = block.get("key_fingerprint").and_then(Value::as_str).map(str::to_string);
+        if block.get("algorithm").and_then(Value::as_str) != Some(ALGORITHM) {
+            return Ok(invalid(key_fingerprint, messages::SIGNATURE_ALGORITHM_UNSUPPORTED));
+        }
+        let signature: Option<[u8; ed25519::SIGNATURE_LEN]> = block
+            .get("value")
+            .and_then(Value::as_str)
+            .and_then(|value| hex::decode(value).ok())
+            .and_then(|bytes| bytes.try_into().ok());
+        let Some(signature) = signature else {
+            return Ok(invalid(key_fingerprint, messages::SIGNATURE_MALFORMED));
+        };
+
+        let _guard = self.lock.lock().unwrap_or_else(|p| p.into_inner());
+        let keyring = self.read_keyring()?;
+        let Some(record) = keyring
+            .keys
+            .iter()
+            .find(|r| Some(&r.fingerprint) == key_fingerprint.as_ref())
+        else {
+            return Ok(invalid(key_fingerprint, messages::SIGNING_KEY_UNKNOWN));
+        };
+        let public: [u8; ed25519::PUBLIC_KEY_LEN] = hex::decode(&record.public_key)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| signing_error(format!("Corrupt public key {}", record.fingerprint)))?;
+
+        if !ed25519::verify(&public, &signed_payload(document), &signature) {
+            return Ok(invalid(key_fingerprint, messages::SIGNATURE_MISMATCH));
+        }
+        Ok(ReportVerification {
+            valid: true,
+            key_fingerprint,
+            current_key: record.retired_at.is_none(),
+            message: messages::SIGNATURE_VALID.text(),
+        })
+    }
+}
+
+/// Build the managed signer rooted in the app data directory.
+pub fn init_signer<R: Runtime>(app: &AppHandle<R>) -> ReportSigner {
+    match app.path().app_data_dir() {
+        Ok(dir) => ReportSigner::new(dir.join(SIGNING_DIR)),
+        Err(_) => {
+            log_event("signing_dir_unavailable", json!({"action": "report signing disabled"}));
+            ReportSigner::unavailable()
+        }
+    }
+}
+
+/// Current public key and fingerprint (generated on first call) plus retired keys.
+#[tauri::command]
+pub async fn get_signing_public_key(
+    signer: State<'_, ReportSigner>,
+    settings: State<'_, SettingsStore>,
+) -> Result<SigningKeyInfo, BitBurnError> {
+    signer.key_info(settings.get().sign_reports)
+}
+
+/// Turn signing of exported wipe reports on or off.
+#[tauri::command]
+pub async fn set_report_signing(
+    signer: State<'_, ReportSigner>,
+    settings: State<'_, SettingsStore>,
+    enabled: bool,
+) -> Result<SigningKeyInfo, BitBurnError> {
+    let info = signer.key_info(enabled)?;
+    settings.update(|s| s.sign_reports = enabled)?;
+    log_event("report_signing_configured", json!({"enabled": enabled, "fingerprint": info.current.fingerprint}));
+    Ok(info)
+}
+
+/// Replace the signing key. The old public key is kept so earlier reports still verify.
+#[tauri::command]
+pub async fn rotate_signing_key(
+    signer: State<'_, ReportSigner>,
+    settings: State<'_, SettingsStore>,
+) -> Result<SigningKeyInfo, BitBurnError> {
+    signer.rotate()?;
+    signer.key_info(settings.get().sign_reports)
+}
+
+/// Verify an exported wipe report against the local keyring.
+#[tauri::command]
+pub async fn verify_wipe_report(
+    signer: State<'_, ReportSigner>,
+    path: String,
+) -> Result<ReportVerification, BitBurnError> {
+    let report_path = Path::new(&path);
+    let contents = fs::read_to_string(report_path).map_err(|e| BitBurnError::io(&e, Some(report_path)))?;
+    let document: Map<String, Value> = serde_json::from_str(&contents)
+        .map_err(|e| signing_error(format!("{} is not a wipe report: {}", path, e)))?;
+    let verification = signer.verify_document(&document)?;
+    log_event(
+        "wipe_report_verified",
+        json!({"path": path, "valid": verification.valid, "fingerprint": verification.key_fingerprint}),
+    );
+    Ok(verification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    fn temp_signer() -> (ReportSigner, PathBuf) {
+        let dir = create_test_dir().unwrap();
+        (ReportSigner::new(dir.clone()), dir)
+    }
+
+    fn sample_report() -> Map<String, Value> {
+        let value = json!({
+            "operation_id": "op-7",
+            "report": {"files_wiped": 2, "wiped_files": ["C:/data/a.txt", "C:/data/b.txt"]},
+            "events": [{"event": "wipe_files_end", "fields": {"status": "success"}}],
+        });
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn canonical_json_sorts_keys_and_drops_whitespace() {
+        let value: Value = serde_json::from_str(r#"{ "b": [1, {"z": null, "a": "x"}], "a": true }"#).unwrap();
+        assert_eq!(canonical_json(&value), r#"{"a":true,"b":[1,{"a":"x","z":null}]}"#);
+    }
+
+    #[test]
+    fn sign_and_verify_round_trip_through_a_file() {
+        let (signer, dir) = temp_signer();
+        let mut report = sample_report();
+        signer.sign_document(&mut report).unwrap();
+
+        let serialized = serde_json::to_string_pretty(&report).unwrap();
+        let reparsed: Map<String, Value> = serde_json::from_str(&serialized).unwrap();
+        let verification = signer.verify_document(&reparsed).unwrap();
+        assert!(verification.valid, "{}", verification.message.text);
+        assert!(verification.current_key);
+        assert_eq!(
+            verification.key_fingerprint,
+            Some(signer.key_info(true).unwrap().current.fingerprint)
+        );
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn changing_one_byte_of_the_report_breaks_the_signature() {
+        let (signer, dir) = temp_signer();
+        let mut report = sample_report();
+        signer.sign_document(&mut report).unwrap();
+        let serialized = serde_json::to_string_pretty(&report).unwrap();
+
+        let tampered = serialized.replacen("\"files_wiped\": 2", "\"files_wiped\": 3", 1);
+        assert_ne!(tampered, serialized);
+        let tampered: Map<String, Value> = serde_json::from_str(&tampered).unwrap();
+        let verification = signer.verify_document(&tampered).unwrap();
+        assert!(!verification.valid);
+        assert_eq!(verification.message.code, "signature_mismatch");
+
+        let mut unsigned = sample_report();
+        unsigned.remove(SIGNATURE_FIELD);
+        assert!(!signer.verify_document(&unsigned).unwrap().valid);
+        cleanup_test_dir(dir);
+    }
+
+    #[test]
+    fn rotation_keeps_old_reports_verifiable() {
+        let (signer, dir) = temp_signer();
+        let mut old_report = sample_report();
+        signer.sign_document(&mut old_report).unwrap();
+        let first = signer.key_info(false).unwrap().current;
+
+        let second = signer.rotate().unwrap();
+        assert_ne!(first.fingerprint, second.fingerprint);
+        let info = signer.key_info(false).unwrap();
+        assert_eq!(info.current.fingerprint, second.fingerprint);
+        assert_eq!(info.retired.len(), 1);
+        assert_eq!(info.retired[0].fingerprint, first.fingerprint);
+
+        let verification = signer.verify_document(&old_report).unwrap();
+        assert!(verification.valid);
+        assert!(!verification.current_key);
+
+        let mut new_report = sample_report();
+        signer.sign_document(&mut new_report).unwrap();
+        assert!(signer.verify_document(&new_report).unwrap().current_key);
+        cleanup_test_dir(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_key_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let (signer, dir) = temp_signer();
+        signer.key_info(false).unwrap();
+        let mode = fs::metadata(dir.join(PRIVATE_KEY_FILE)).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        cleanup_test_dir(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_stale_temp_key_is_replaced_not_reused() {
+        use std::os::unix::fs::PermissionsExt;
+        let (signer, dir) = temp_signer();
+        fs::create_dir_all(&dir).unwrap();
+        let temp_path = dir.join(PRIVATE_KEY_FILE).with_extension("key.tmp");
+        fs::write(&temp_path, b"planted").unwrap();
+        fs::set_permissions(&temp_path, fs::Permissions::from_mode(0o666)).unwrap();
+
+        signer.key_info(false).unwrap();
+        assert!(!temp_path.exists());
+        let key = dir.join(PRIVATE_KEY_FILE);
+        assert_eq!(fs::metadata(&key).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_ne!(fs::read(&key).unwrap(), b"planted");
+        cleanup_test_dir(dir);
+    }
+}
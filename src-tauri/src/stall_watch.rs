@@ -0,0 +1,262 @@
+use bitburn_core::CancellationToken;
+#[cfg(test)]
+use bitburn_core::{MemoryTarget, WipeTarget};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::settings::Settings;
+use crate::{WipeError, WipeProgress};
+
+/// Seconds without a completed write before `wipe_stalled` is emitted.
+pub const DEFAULT_STALL_WARNING_SECS: u64 = 60;
+/// Seconds without a completed write before the file is abandoned.
+pub const DEFAULT_FILE_TIMEOUT_SECS: u64 = 10 * 60;
+
+/// How long a file may go without write progress before it is reported and then abandoned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StallPolicy {
+    pub stall_after: Duration,
+    /// `None` waits forever.
+    pub timeout: Option<Duration>,
+    /// How often the coordinator wakes up to check for stalls.
+    pub poll_interval: Duration,
+}
+
+impl Default for StallPolicy {
+    fn default() -> Self {
+        StallPolicy {
+            stall_after: Duration::from_secs(DEFAULT_STALL_WARNING_SECS),
+            timeout: Some(Duration::from_secs(DEFAULT_FILE_TIMEOUT_SECS)),
+            poll_interval: Duration::from_millis(250),
+        }
+    }
+}
+
+impl StallPolicy {
+    pub fn from_settings(settings: &Settings) -> Self {
+        let defaults = StallPolicy::default();
+        StallPolicy {
+            stall_after: settings
+                .stall_warning_seconds
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.stall_after),
+            timeout: match settings.file_timeout_seconds {
+                Some(0) => None,
+                Some(secs) => Some(Duration::from_secs(secs)),
+                None => defaults.timeout,
+            },
+            ..defaults
+        }
+    }
+}
+
+enum WorkerMessage<T> {
+    Progress(Box<WipeProgress>),
+    Done(Result<T, WipeError>),
+}
+
+/// Run `work` on its own thread while this thread watches its progress reports.
+/// Progress is forwarded to `on_progress`; after `stall_after` without any, `on_stall` receives the
+/// idle seconds once per stall. After `timeout` the worker is told to stop and abandoned, so a write
+/// blocked in the kernel cannot hold up the batch; its file handle is dropped when the write returns.
+/// Once `stop_requested` returns true (cancellation, or skipping the current file) the worker is
+/// told to stop, which it must do between chunks.
+pub fn run_watched<T, W, P, S>(
+    policy: StallPolicy,
+    stop_requested: &dyn Fn() -> bool,
+    work: W,
+    mut on_progress: P,
+    mut on_stall: S,
+) -> Result<T, WipeError>
+where
+    T: Send + 'static,
+    W: FnOnce(&CancellationToken, &mut dyn FnMut(WipeProgress)) -> Result<T, WipeError> + Send + 'static,
+    P: FnMut(WipeProgress),
+    S: FnMut(u64),
+{
+    let (sender, receiver) = mpsc::channel();
+    let stop = CancellationToken::new();
+    let worker_stop = stop.clone();
+    thread::Builder::new()
+        .name("wipe-writer".to_string())
+        .spawn(move || {
+            let progress_sender = sender.clone();
+            let mut report = move |progress: WipeProgress| {
+                let _ = progress_sender.send(WorkerMessage::Progress(Box::new(progress)));
+            };
+            let result = work(&worker_stop, &mut report);
+            let _ = sender.send(WorkerMessage::Done(result));
+        })
+        .map_err(WipeError::Io)?;
+
+    let mut last_activity = Instant::now();
+    let mut stall_reported = false;
+    loop {
+        if stop_requested() {
+            stop.cancel();
+        }
+
+        match receiver.recv_timeout(policy.poll_interval) {
+            Ok(WorkerMessage::Progress(progress)) => {
+                last_activity = Instant::now();
+                stall_reported = false;
+                on_progress(*progress);
+            }
+            Ok(WorkerMessage::Done(result)) => return result,
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(WipeError::Io(std::io::Error::other("wipe writer thread exited unexpectedly")));
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+
+        let idle = last_activity.elapsed();
+        if let Some(timeout) = policy.timeout {
+            if idle >= timeout {
+                stop.cancel();
+                return Err(WipeError::Timeout { seconds: idle.as_secs() });
+            }
+        }
+        if !stall_reported && idle >= policy.stall_after {
+            stall_reported = true;
+            on_stall(idle.as_secs());
+        }
+    }
+}
+
+/// Writes normally until `stall_at` bytes, then blocks once for `stall_for`, like a write to a dying disk.
+#[cfg(test)]
+pub(crate) struct StallingTarget {
+    inner: MemoryTarget,
+    stall_at: u64,
+    stall_for: Duration,
+    written: u64,
+}
+
+#[cfg(test)]
+impl StallingTarget {
+    pub fn new(inner: MemoryTarget, stall_at: u64, stall_for: Duration) -> Self {
+        StallingTarget {
+            inner,
+            stall_at,
+            stall_for,
+            written: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+impl WipeTarget for StallingTarget {
+    fn size(&mut self) -> std::io::Result<u64> {
+        self.inner.size()
+    }
+
+    fn rewind(&mut self) -> std::io::Result<()> {
+        self.inner.rewind()
+    }
+
+    fn write_chunk(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        if self.written == self.stall_at {
+            thread::sleep(self.stall_for);
+        }
+        self.written += buf.len() as u64;
+        self.inner.write_chunk(buf)
+    }
+
+    fn sync(&mut self) -> std::io::Result<()> {
+        self.inner.sync()
+    }
+
+    fn resume_at(&mut self, offset: u64) -> std::io::Result<()> {
+        self.inner.resume_at(offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WipeAlgorithm;
+    use bitburn_core::{overwrite_target, FileWipeProgressRecord};
+
+    fn fast_policy(timeout_ms: Option<u64>) -> StallPolicy {
+        StallPolicy {
+            stall_after: Duration::from_millis(50),
+            timeout: timeout_ms.map(Duration::from_millis),
+            poll_interval: Duration::from_millis(10),
+        }
+    }
+
+    fn overwrite<T: WipeTarget + Send + 'static>(
+        mut target: T,
+    ) -> impl FnOnce(&CancellationToken, &mut dyn FnMut(WipeProgress)) -> Result<T, WipeError> + Send + 'static {
+        move |stop, progress| {
+            let mut record = FileWipeProgressRecord::default();
+            overwrite_target(&mut target, 1, &WipeAlgorithm::NistClear, &mut rand::thread_rng(), stop, progress, &mut record)?;
+            Ok(target)
+        }
+    }
+
+    #[test]
+    fn stalled_writer_is_reported_then_abandoned() {
+        let target = StallingTarget::new(
+            MemoryTarget::new(vec![0xAA; 4 * 1024 * 1024]),
+            1024 * 1024,
+            Duration::from_secs(2),
+        );
+        let mut stalls = Vec::new();
+
+        let started = Instant::now();
+        let result = run_watched(fast_policy(Some(300)), &|| false, overwrite(target), |_| {}, |secs| {
+            stalls.push(secs)
+        });
+
+        assert!(matches!(result, Err(WipeError::Timeout { .. })));
+        assert_eq!(stalls.len(), 1, "one stall episode should be reported once");
+        assert!(started.elapsed() < Duration::from_secs(1), "the batch must not wait for the stuck write");
+    }
+
+    #[test]
+    fn healthy_writer_finishes_and_forwards_progress() {
+        let mut progress_reports = 0;
+        let target = run_watched(
+            fast_policy(Some(300)),
+            &|| false,
+            overwrite(MemoryTarget::new(vec![0xAA; 8 * 1024 * 1024])),
+            |_| progress_reports += 1,
+            |_| panic!("healthy writer should not stall"),
+        )
+        .expect("wipe should finish");
+
+        assert!(target.data().iter().all(|&b| b == 0));
+        assert!(progress_reports > 0);
+    }
+
+    #[test]
+    fn zero_timeout_keeps_waiting_for_a_slow_writer() {
+        let target = StallingTarget::new(
+            MemoryTarget::new(vec![0xAA; 2 * 1024 * 1024]),
+            1024 * 1024,
+            Duration::from_millis(200),
+        );
+        let mut stalls = 0;
+
+        let result = run_watched(fast_policy(None), &|| false, overwrite(target), |_| {}, |_| {
+            stalls += 1
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(stalls, 1);
+    }
+
+    #[test]
+    fn policy_reads_settings_with_zero_disabling_the_timeout() {
+        let mut settings = Settings::default();
+        assert_eq!(StallPolicy::from_settings(&settings), StallPolicy::default());
+
+        settings.stall_warning_seconds = Some(5);
+        settings.file_timeout_seconds = Some(0);
+        let policy = StallPolicy::from_settings(&settings);
+        assert_eq!(policy.stall_after, Duration::from_secs(5));
+        assert_eq!(policy.timeout, None);
+    }
+}
@@ -0,0 +1,235 @@
+//! Opt-in `status.json` in the app data directory, for monitoring tools that want to know when
+//! BitBurn is wiping without speaking Tauri IPC. While operations run it holds the registry
+//! snapshot; once the app is idle it is rewritten with an empty list. It is replaced by
+//! temp file + rename at most once per second, so readers never see a partial document.
+
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::operations::{OperationRegistry, OperationSnapshot};
+use crate::settings::SettingsStore;
+
+const STATUS_FILE_NAME: &str = "status.json";
+
+/// Minimum time between two writes of the status file.
+pub const WRITE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the writer thread looks at the registry.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Renames tried before a write is given up until the next change; a reader holding the file
+/// open on Windows makes the rename fail until it lets go.
+const RENAME_ATTEMPTS: u32 = 10;
+const RENAME_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// What `status.json` contains.
+#[derive(Debug, Serialize)]
+pub struct StatusDocument<'a> {
+    pub active: bool,
+    /// Unix seconds of the write.
+    pub updated_at: u64,
+    pub operations: &'a [OperationSnapshot],
+}
+
+/// Keeps one status file in step with the registry.
+#[derive(Debug)]
+pub struct StatusFileWriter {
+    path: PathBuf,
+    /// The operations last written, serialized, so unchanged state is not rewritten.
+    written: Option<String>,
+    written_at: Option<Instant>,
+}
+
+impl StatusFileWriter {
+    pub fn new(path: PathBuf) -> Self {
+        StatusFileWriter { path, written: None, written_at: None }
+    }
+
+    /// Write `operations` if they changed since the last write and `WRITE_INTERVAL` has passed.
+    /// Returns whether the file was written.
+    pub fn refresh(&mut self, operations: &[OperationSnapshot], now: Instant) -> Result<bool, BitBurnError> {
+        let serialized = serde_json::to_string(operations)
+            .map_err(|e| BitBurnError::internal(format!("Failed to serialize status: {}", e)))?;
+        if self.written.as_deref() == Some(serialized.as_str()) {
+            return Ok(false);
+        }
+        if self.written_at.is_some_and(|at| now.duration_since(at) < WRITE_INTERVAL) {
+            return Ok(false);
+        }
+
+        let updated_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let document = StatusDocument { active: !operations.is_empty(), updated_at, operations };
+        let contents = serde_json::to_vec_pretty(&document)
+            .map_err(|e| BitBurnError::internal(format!("Failed to serialize status: {}", e)))?;
+        write_with_retry(&self.path, &contents, |from, to| fs::rename(from, to), RENAME_RETRY_DELAY)?;
+        self.written = Some(serialized);
+        self.written_at = Some(now);
+        Ok(true)
+    }
+
+    /// Remove the file after the feature is turned off; the next `refresh` writes it again.
+    pub fn remove(&mut self) {
+        if self.written.take().is_some() {
+            let _ = fs::remove_file(&self.path);
+        }
+        self.written_at = None;
+    }
+}
+
+/// Write `contents` to a temp file beside `path` and rename it into place, retrying the rename
+/// while a reader holds `path` open.
+fn write_with_retry<F>(path: &Path, contents: &[u8], mut rename: F, delay: Duration) -> Result<(), BitBurnError>
+where
+    F: FnMut(&Path, &Path) -> io::Result<()>,
+{
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| BitBurnError::io(&e, Some(parent)))?;
+    }
+    let temp_path = path.with_file_name(format!("{}.tmp", STATUS_FILE_NAME));
+    fs::write(&temp_path, contents).map_err(|e| BitBurnError::io(&e, Some(&temp_path)))?;
+
+    let mut attempt = 1;
+    loop {
+        match rename(&temp_path, path) {
+            Ok(()) => return Ok(()),
+            Err(_) if attempt < RENAME_ATTEMPTS => {
+                attempt += 1;
+                thread::sleep(delay);
+            }
+            Err(e) => {
+                let _ = fs::remove_file(&temp_path);
+                return Err(BitBurnError::io(&e, Some(path)));
+            }
+        }
+    }
+}
+
+/// Location of the status file inside the app data directory.
+pub fn status_file_path<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join(STATUS_FILE_NAME))
+}
+
+/// Keep the status file up to date for as long as the app runs, while the setting is on.
+pub fn spawn_writer<R: Runtime>(app: &AppHandle<R>) {
+    let Some(path) = status_file_path(app) else {
+        log_event("status_file_unavailable", json!({"action": "no status file is written"}));
+        return;
+    };
+    let app = app.clone();
+    let mut writer = StatusFileWriter::new(path);
+    let mut failing = false;
+    thread::spawn(move || loop {
+        let enabled = app.try_state::<SettingsStore>().is_some_and(|settings| settings.get().status_file);
+        if !enabled {
+            writer.remove();
+        } else if let Some(registry) = app.try_state::<OperationRegistry>() {
+            match writer.refresh(&registry.active(), Instant::now()) {
+                Ok(_) => failing = false,
+                Err(e) if !failing => {
+                    failing = true;
+                    log_event("status_file_write_failed", json!({"message": e.to_string()}));
+                }
+                Err(_) => {}
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    });
+}
+
+/// Where the status file is written when the setting is on.
+#[tauri::command]
+pub async fn get_status_file_path(app: AppHandle) -> Result<String, BitBurnError> {
+    status_file_path(&app)
+        .map(|path| path.to_string_lossy().to_string())
+        .ok_or_else(|| BitBurnError::internal("app data directory is unavailable"))
+}
+
+/// Turn the status file on or off.
+#[tauri::command]
+pub async fn set_status_file(settings: State<'_, SettingsStore>, enabled: bool) -> Result<(), BitBurnError> {
+    settings.update(|s| s.status_file = enabled)?;
+    log_event("status_file_configured", json!({"enabled": enabled}));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::OperationKind;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+    use serde_json::Value;
+    use std::cell::Cell;
+
+    fn read(path: &Path) -> Value {
+        serde_json::from_slice(&fs::read(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn status_file_follows_an_operation_and_is_cleared_when_it_ends() {
+        let path = create_test_dir().unwrap().join(STATUS_FILE_NAME);
+        let registry = OperationRegistry::default();
+        let mut writer = StatusFileWriter::new(path.clone());
+        let start = Instant::now();
+
+        let op = registry.register(OperationKind::WipeFiles, "C:\\drop");
+        assert!(writer.refresh(&registry.active(), start).unwrap());
+        let status = read(&path);
+        assert_eq!(status["active"], true);
+        assert_eq!(status["operations"][0]["operation_id"], op.operation_id.as_str());
+        assert_eq!(status["operations"][0]["kind"], "wipe_files");
+        assert_eq!(status["operations"][0]["started_at"], op.started_at);
+
+        registry.update(&op.operation_id, "Pass 2 of 3", 40.0);
+        // Changes within a second of the last write wait for the next one.
+        assert!(!writer.refresh(&registry.active(), start + Duration::from_millis(500)).unwrap());
+        assert_eq!(read(&path)["operations"][0]["overall_percentage"], 0.0);
+        assert!(writer.refresh(&registry.active(), start + WRITE_INTERVAL).unwrap());
+        assert_eq!(read(&path)["operations"][0]["current_phase"], "Pass 2 of 3");
+        assert_eq!(read(&path)["operations"][0]["overall_percentage"], 40.0);
+        // Nothing changed, so nothing is rewritten.
+        assert!(!writer.refresh(&registry.active(), start + WRITE_INTERVAL * 3).unwrap());
+
+        registry.unregister(&op.operation_id);
+        assert!(writer.refresh(&registry.active(), start + WRITE_INTERVAL * 4).unwrap());
+        let status = read(&path);
+        assert_eq!(status["active"], false);
+        assert_eq!(status["operations"], json!([]));
+
+        writer.remove();
+        assert!(!path.exists());
+        cleanup_test_dir(path.parent().unwrap());
+    }
+
+    #[test]
+    fn rename_is_retried_while_a_reader_holds_the_file() {
+        let path = create_test_dir().unwrap().join(STATUS_FILE_NAME);
+        let attempts = Cell::new(0);
+        let held_open = |from: &Path, to: &Path| {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 3 {
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, "in use"));
+            }
+            fs::rename(from, to)
+        };
+
+        write_with_retry(&path, b"{}", held_open, Duration::ZERO).unwrap();
+        assert_eq!(attempts.get(), 3);
+        assert_eq!(fs::read(&path).unwrap(), b"{}");
+
+        let never = |_: &Path, _: &Path| Err(io::Error::new(io::ErrorKind::PermissionDenied, "in use"));
+        let refused = write_with_retry(&path, b"[]", never, Duration::ZERO);
+        assert_eq!(refused.unwrap_err().code(), "access_denied");
+        // The last good document stays and no temp file is left behind.
+        assert_eq!(fs::read(&path).unwrap(), b"{}");
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+        cleanup_test_dir(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,268 @@
+//! Symbolic links the user selected. Every entry point used to refuse them outright; the per-wipe
+//! `symlink_policy` can now delete just the link, its name scrambled first, or follow it one level
+//! and wipe what it points at. A followed target must not be another link and goes through the
+//! protected-path and self-protection checks, wherever it is, so a link cannot lead a wipe into a
+//! system folder or BitBurn's own files; nor may the link itself lie in one, since deleting it
+//! changes its folder. `reject` stays the default. `sanitize_paths` runs the
+//! rules the context menu applies, so the UI can check a selection before offering to wipe it.
+
+use bitburn_core::{FileWipeProgressRecord, SymlinkPolicy};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::platform::context_menu::sanitize_target_path_with;
+use crate::protected::ProtectedPaths;
+use crate::report::{FileWipeOutcome, FileWipeStatus};
+
+/// The selected link a file outcome came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkRecord {
+    pub link: String,
+    /// What the link pointed at: resolved when it was followed, as stored in the link otherwise.
+    pub target: String,
+    pub policy: SymlinkPolicy,
+}
+
+/// What a batch does with a link its policy accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SelectedLink {
+    /// Delete the link and nothing else.
+    Delete,
+    /// Wipe this target, then delete the link.
+    Follow(PathBuf),
+}
+
+/// Apply `policy` to the selected link `link`.
+pub(crate) fn resolve(link: &Path, policy: SymlinkPolicy) -> Result<SelectedLink, BitBurnError> {
+    resolve_with(link, policy, &ProtectedPaths::current())
+}
+
+fn resolve_with(link: &Path, policy: SymlinkPolicy, protected: &ProtectedPaths) -> Result<SelectedLink, BitBurnError> {
+    match policy {
+        SymlinkPolicy::Reject => Err(BitBurnError::SymlinkNotSupported { path: link.to_string_lossy().to_string() }),
+        SymlinkPolicy::DeleteLinkOnly => {
+            check_link_location(link, protected)?;
+            Ok(SelectedLink::Delete)
+        }
+        SymlinkPolicy::FollowTarget => {
+            check_link_location(link, protected)?;
+            let target = bitburn_core::link_target(link).map_err(|e| BitBurnError::from_wipe_error(e, link))?;
+            let target = target.canonicalize().map_err(|e| BitBurnError::io(&e, Some(&target)))?;
+            protected.check(&target)?;
+            crate::self_protection::check(&target)?;
+            Ok(SelectedLink::Follow(target))
+        }
+    }
+}
+
+/// Either policy deletes the link, which changes the folder it is in, so the link itself must not
+/// be protected or one of BitBurn's own files, whatever it points at.
+fn check_link_location(link: &Path, protected: &ProtectedPaths) -> Result<(), BitBurnError> {
+    protected.check(&resolved_link(link))?;
+    crate::self_protection::check_link(link)
+}
+
+/// `link` with its folder canonicalized but the link itself kept, where `canonicalize` would
+/// put its target in its place.
+pub(crate) fn resolved_link(link: &Path) -> PathBuf {
+    match (link.parent().and_then(|parent| parent.canonicalize().ok()), link.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => link.to_path_buf(),
+    }
+}
+
+/// Delete the selected link `link` under `delete_link_only`, recorded as a file outcome with no passes.
+pub(crate) fn delete_selected(link: &Path) -> Result<FileWipeOutcome, BitBurnError> {
+    let started = std::time::Instant::now();
+    let target = fs::read_link(link).map(|target| target.to_string_lossy().to_string()).unwrap_or_default();
+    bitburn_core::delete_link(link).map_err(|e| BitBurnError::from_wipe_error(e, link))?;
+    let link = link.to_string_lossy().to_string();
+    log_event("symlink_deleted", json!({"link": link, "target": target}));
+    Ok(FileWipeOutcome {
+        path: link.clone(),
+        status: FileWipeStatus::Success,
+        algorithm: None,
+        flash_media: false,
+        recalled_from_remote: false,
+        cow_filesystem: None,
+        nocow_applied: None,
+        ownership_taken: false,
+        original_owner: None,
+        link: Some(LinkRecord { link, target, policy: SymlinkPolicy::DeleteLinkOnly }),
+        exclusive_access: false,
+        passes: FileWipeProgressRecord::default(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        bytes_per_second: None,
+        companions: Vec::new(),
+    })
+}
+
+/// After the batch has been through the target of `link`: note the link on the target's outcome
+/// and, once the target is gone, delete the link so it does not dangle. A folder emptied in place
+/// keeps its link.
+pub(crate) fn finish_followed(link: &Path, target: &Path, files: &mut [FileWipeOutcome]) -> Result<(), BitBurnError> {
+    let record = LinkRecord {
+        link: link.to_string_lossy().to_string(),
+        target: target.to_string_lossy().to_string(),
+        policy: SymlinkPolicy::FollowTarget,
+    };
+    if let Some(outcome) = files.iter_mut().rev().find(|outcome| Path::new(&outcome.path) == target) {
+        outcome.link = Some(record.clone());
+    }
+    if fs::symlink_metadata(target).is_ok() {
+        log_event("symlink_kept", json!({"link": record.link, "target": record.target}));
+        return Ok(());
+    }
+    bitburn_core::delete_link(link).map_err(|e| BitBurnError::from_wipe_error(e, link))?;
+    log_event("symlink_followed", json!({"link": record.link, "target": record.target}));
+    Ok(())
+}
+
+/// A path `sanitize_paths` turned away.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RejectedPath {
+    pub path: String,
+    pub error: BitBurnError,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SanitizedPaths {
+    /// The accepted paths as the wipe commands take them, each once, in the order given.
+    pub paths: Vec<String>,
+    pub rejected: Vec<RejectedPath>,
+}
+
+fn sanitize_all(raw_paths: Vec<String>, policy: SymlinkPolicy) -> SanitizedPaths {
+    let mut sanitized = SanitizedPaths::default();
+    let mut seen = HashSet::new();
+    for raw in raw_paths.into_iter().filter(|raw| !raw.trim().is_empty()) {
+        match crate::path_expansion::require_absolute(&raw).and_then(|_| sanitize_target_path_with(&raw, policy)) {
+            Ok(path) => {
+                if seen.insert(path.clone()) {
+                    sanitized.paths.push(path);
+                }
+            }
+            Err(error) => sanitized.rejected.push(RejectedPath { path: raw, error }),
+        }
+    }
+    sanitized
+}
+
+/// Check `paths` the way a context-menu selection is checked, with symbolic links handled as
+/// `symlink_policy` says (`reject` when unset), without touching anything.
+#[tauri::command]
+pub async fn sanitize_paths(paths: Vec<String>, symlink_policy: Option<SymlinkPolicy>) -> Result<SanitizedPaths, BitBurnError> {
+    let policy = symlink_policy.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || sanitize_all(paths, policy))
+        .await
+        .map_err(|e| BitBurnError::internal(format!("sanitize_paths task join error: {}", e)))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+    use std::os::unix::fs::symlink;
+
+    /// A scratch folder holding `data/secret.txt`, `data/folder/inner.txt` and a `protected` tree.
+    fn scratch() -> (PathBuf, ProtectedPaths) {
+        let dir = create_test_dir().unwrap();
+        fs::create_dir_all(dir.join("data").join("folder")).unwrap();
+        fs::create_dir_all(dir.join("protected")).unwrap();
+        fs::write(dir.join("data").join("secret.txt"), b"secret").unwrap();
+        fs::write(dir.join("data").join("folder").join("inner.txt"), b"inner").unwrap();
+        fs::write(dir.join("protected").join("system.cfg"), b"system").unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let protected = ProtectedPaths::new(vec![dir.join("protected")], Vec::new());
+        (dir, protected)
+    }
+
+    #[test]
+    fn each_policy_resolves_file_and_folder_links() {
+        let (dir, protected) = scratch();
+        symlink(dir.join("data").join("secret.txt"), dir.join("file_link")).unwrap();
+        symlink(dir.join("data").join("folder"), dir.join("folder_link")).unwrap();
+        symlink(dir.join("file_link"), dir.join("chained")).unwrap();
+
+        for link in ["file_link", "folder_link"] {
+            let link = dir.join(link);
+            assert!(matches!(
+                resolve_with(&link, SymlinkPolicy::Reject, &protected),
+                Err(BitBurnError::SymlinkNotSupported { .. })
+            ));
+            assert_eq!(resolve_with(&link, SymlinkPolicy::DeleteLinkOnly, &protected), Ok(SelectedLink::Delete));
+        }
+        let follow = |name: &str| resolve_with(&dir.join(name), SymlinkPolicy::FollowTarget, &protected);
+        assert_eq!(follow("file_link"), Ok(SelectedLink::Follow(dir.join("data").join("secret.txt"))));
+        assert_eq!(follow("folder_link"), Ok(SelectedLink::Follow(dir.join("data").join("folder"))));
+        assert!(matches!(follow("chained"), Err(BitBurnError::SymlinkChain { .. })));
+        cleanup_test_dir(&dir);
+    }
+
+    #[test]
+    fn a_link_into_a_protected_tree_is_never_followed() {
+        let (dir, protected) = scratch();
+        symlink(dir.join("protected").join("system.cfg"), dir.join("config_link")).unwrap();
+        symlink(dir.join("protected"), dir.join("system_link")).unwrap();
+
+        for name in ["config_link", "system_link"] {
+            let link = dir.join(name);
+            assert!(matches!(
+                resolve_with(&link, SymlinkPolicy::FollowTarget, &protected),
+                Err(BitBurnError::ProtectedPath { .. })
+            ));
+            // Removing the link alone is still allowed and leaves the protected files as they were.
+            assert_eq!(resolve_with(&link, SymlinkPolicy::DeleteLinkOnly, &protected), Ok(SelectedLink::Delete));
+            let outcome = delete_selected(&link).unwrap();
+            assert_eq!(outcome.link.unwrap().policy, SymlinkPolicy::DeleteLinkOnly);
+            assert!(fs::symlink_metadata(&link).is_err());
+        }
+        assert_eq!(fs::read(dir.join("protected").join("system.cfg")).unwrap(), b"system");
+        cleanup_test_dir(&dir);
+    }
+
+    #[test]
+    fn a_link_inside_a_protected_tree_is_not_deleted() {
+        let (dir, protected) = scratch();
+        let link = dir.join("protected").join("data_link");
+        symlink(dir.join("data").join("secret.txt"), &link).unwrap();
+
+        for policy in [SymlinkPolicy::DeleteLinkOnly, SymlinkPolicy::FollowTarget] {
+            assert!(matches!(resolve_with(&link, policy, &protected), Err(BitBurnError::ProtectedPath { .. })));
+        }
+        assert!(link.is_symlink());
+        cleanup_test_dir(&dir);
+    }
+
+    #[test]
+    fn sanitizing_keeps_accepted_links_as_links() {
+        let (dir, _) = scratch();
+        let link = dir.join("file_link");
+        symlink(dir.join("data").join("secret.txt"), &link).unwrap();
+        symlink(dir.join("gone"), dir.join("dangling")).unwrap();
+        let paths = vec![
+            link.to_string_lossy().to_string(),
+            dir.join("dangling").to_string_lossy().to_string(),
+            dir.join("data").to_string_lossy().to_string(),
+            "relative.txt".to_string(),
+        ];
+
+        let rejected = sanitize_all(paths.clone(), SymlinkPolicy::Reject);
+        assert_eq!(rejected.paths, [paths[2].clone()]);
+        let codes: Vec<_> = rejected.rejected.iter().map(|rejected| rejected.error.code()).collect();
+        assert_eq!(codes, ["symlink_not_supported", "path_not_found", "relative_path_not_allowed"]);
+
+        // Only deleting the link makes sense for one whose target is gone.
+        let deleted = sanitize_all(paths.clone(), SymlinkPolicy::DeleteLinkOnly);
+        assert_eq!(deleted.paths, [paths[0].clone(), paths[1].clone(), paths[2].clone()]);
+        let followed = sanitize_all(paths.clone(), SymlinkPolicy::FollowTarget);
+        assert_eq!(followed.paths, [paths[0].clone(), paths[2].clone()]);
+        assert_eq!(followed.rejected[0].error.code(), "path_not_found");
+        cleanup_test_dir(&dir);
+    }
+}
@@ -0,0 +1,46 @@
+//! Fixtures shared by the unit tests of every module.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::test::MockRuntime;
+use tauri::App;
+
+use crate::operations::{OperationGuard, OperationKind, OperationRegistry};
+use crate::BatchContext;
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// Creates an empty directory under the system temp directory that no other
+/// test, in this run or a concurrent one, will be handed.
+pub fn create_test_dir() -> io::Result<PathBuf> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let test_dir = std::env::temp_dir().join(format!(
+        "BitBurn_test_{}_{}_{}",
+        std::process::id(),
+        nanos,
+        NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&test_dir)?;
+    Ok(test_dir)
+}
+
+/// Removes a directory made by `create_test_dir` and everything under it. Cleanup is best effort:
+/// a directory the test already removed, or one it left unremovable, is not a failure.
+pub fn cleanup_test_dir(dir: impl AsRef<Path>) {
+    let _ = fs::remove_dir_all(dir);
+}
+
+/// A file-wipe batch on `app`'s "main" window, registered in `registry` under `label`.
+pub fn batch_context(app: &App<MockRuntime>, registry: &OperationRegistry, label: &str) -> BatchContext<MockRuntime> {
+    BatchContext::new(
+        app.handle().clone(),
+        "main".to_string(),
+        OperationGuard::begin(app.handle(), registry, OperationKind::WipeFiles, label),
+    )
+}
@@ -0,0 +1,178 @@
+//! The system's light/dark theme and accent colour. The tray icon switches between a dark glyph
+//! for light taskbars and a light one for dark taskbars, and the webview learns the theme from
+//! `get_system_theme` and follows `theme_changed`. Windows sets the apps and taskbar themes
+//! separately (`AppsUseLightTheme` and `SystemUsesLightTheme`), so both are read from the
+//! registry along with the accent; elsewhere both follow the main window and there is no accent.
+
+use serde::Serialize;
+use serde_json::json;
+use std::sync::Mutex;
+use tauri::image::Image;
+use tauri::{AppHandle, Manager};
+
+use crate::diagnostics;
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::ui;
+
+#[cfg(windows)]
+const PERSONALIZE_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize";
+#[cfg(windows)]
+const DWM_KEY: &str = "Software\\Microsoft\\Windows\\DWM";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+    Light,
+    Dark,
+}
+
+impl From<tauri::Theme> for ThemeMode {
+    fn from(theme: tauri::Theme) -> Self {
+        match theme {
+            tauri::Theme::Dark => ThemeMode::Dark,
+            _ => ThemeMode::Light,
+        }
+    }
+}
+
+/// Payload of `get_system_theme` and `theme_changed`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SystemTheme {
+    /// What windows, and so the webview, should use.
+    pub apps: ThemeMode,
+    /// The taskbar's theme, which the tray icon has to stand out against.
+    pub taskbar: ThemeMode,
+    /// Accent colour as `#rrggbb`, where the system has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+}
+
+/// The theme a `*UseLightTheme` registry value stands for; a missing value is light, as on a
+/// fresh install.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn mode_from_light_flag(value: Option<u32>) -> ThemeMode {
+    match value {
+        Some(0) => ThemeMode::Dark,
+        _ => ThemeMode::Light,
+    }
+}
+
+/// `#rrggbb` from the DWM `AccentColor` value, which is stored as `0xAABBGGRR`.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn accent_from_dword(value: u32) -> String {
+    let [red, green, blue, _alpha] = value.to_le_bytes();
+    format!("#{:02x}{:02x}{:02x}", red, green, blue)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayIconVariant {
+    /// A near-black glyph, for light taskbars.
+    DarkGlyph,
+    /// A white glyph, for dark taskbars.
+    LightGlyph,
+}
+
+/// The tray icon that stays visible on a taskbar in `taskbar` theme.
+pub fn tray_icon_variant(taskbar: ThemeMode) -> TrayIconVariant {
+    match taskbar {
+        ThemeMode::Light => TrayIconVariant::DarkGlyph,
+        ThemeMode::Dark => TrayIconVariant::LightGlyph,
+    }
+}
+
+pub fn tray_icon(variant: TrayIconVariant) -> Image<'static> {
+    match variant {
+        TrayIconVariant::DarkGlyph => tauri::include_image!("./icons/tray-light-theme.png"),
+        TrayIconVariant::LightGlyph => tauri::include_image!("./icons/tray-dark-theme.png"),
+    }
+}
+
+#[cfg(windows)]
+pub fn detect(_app: &AppHandle) -> SystemTheme {
+    use winreg::{enums::{HKEY_CURRENT_USER, KEY_READ}, RegKey};
+
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let personalize = hkcu.open_subkey_with_flags(PERSONALIZE_KEY, KEY_READ).ok();
+    let light_flag = |name: &str| personalize.as_ref().and_then(|key| key.get_value::<u32, _>(name).ok());
+    let accent = hkcu
+        .open_subkey_with_flags(DWM_KEY, KEY_READ)
+        .ok()
+        .and_then(|key| key.get_value::<u32, _>("AccentColor").ok())
+        .map(accent_from_dword);
+    SystemTheme {
+        apps: mode_from_light_flag(light_flag("AppsUseLightTheme")),
+        taskbar: mode_from_light_flag(light_flag("SystemUsesLightTheme")),
+        accent,
+    }
+}
+
+#[cfg(not(windows))]
+pub fn detect(app: &AppHandle) -> SystemTheme {
+    let mode = app
+        .get_webview_window("main")
+        .and_then(|window| window.theme().ok())
+        .map(ThemeMode::from)
+        .unwrap_or(ThemeMode::Light);
+    SystemTheme { apps: mode, taskbar: mode, accent: None }
+}
+
+/// The theme last sent to the webview, so a settings broadcast that changed nothing is dropped.
+#[derive(Default)]
+pub struct ThemeState {
+    current: Mutex<Option<SystemTheme>>,
+}
+
+/// Re-read the theme after the window reports a change, swap the tray icon and emit
+/// `theme_changed` if it differs from what the webview has.
+pub fn system_theme_changed(app: &AppHandle) {
+    let theme = detect(app);
+    if let Some(state) = app.try_state::<ThemeState>() {
+        let mut current = state.current.lock().unwrap_or_else(|p| p.into_inner());
+        if current.as_ref() == Some(&theme) {
+            return;
+        }
+        *current = Some(theme.clone());
+    }
+    ui::update_tray_icon_for_theme(app, &theme);
+    log_event("system_theme_changed", json!({"theme": theme}));
+    diagnostics::emit(app, "theme_changed", theme);
+}
+
+/// The system theme and accent, for the webview to match at startup.
+#[tauri::command]
+pub async fn get_system_theme(app: AppHandle, state: tauri::State<'_, ThemeState>) -> Result<SystemTheme, BitBurnError> {
+    let theme = detect(&app);
+    *state.current.lock().unwrap_or_else(|p| p.into_inner()) = Some(theme.clone());
+    Ok(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn light_theme_flags_are_parsed() {
+        assert_eq!(mode_from_light_flag(Some(0)), ThemeMode::Dark);
+        assert_eq!(mode_from_light_flag(Some(1)), ThemeMode::Light);
+        assert_eq!(mode_from_light_flag(None), ThemeMode::Light);
+        assert_eq!(ThemeMode::from(tauri::Theme::Dark), ThemeMode::Dark);
+        assert_eq!(ThemeMode::from(tauri::Theme::Light), ThemeMode::Light);
+    }
+
+    #[test]
+    fn accent_colour_is_read_from_abgr() {
+        assert_eq!(accent_from_dword(0xffd77800), "#0078d7");
+        assert_eq!(accent_from_dword(0x00000000), "#000000");
+    }
+
+    #[test]
+    fn the_tray_icon_contrasts_with_the_taskbar() {
+        assert_eq!(tray_icon_variant(ThemeMode::Light), TrayIconVariant::DarkGlyph);
+        assert_eq!(tray_icon_variant(ThemeMode::Dark), TrayIconVariant::LightGlyph);
+        let light = tray_icon(TrayIconVariant::LightGlyph);
+        assert_eq!((light.width(), light.height()), (32, 32));
+        // Every visible pixel of the glyph for dark taskbars is white.
+        assert!(light.rgba().chunks(4).filter(|pixel| pixel[3] > 0).all(|pixel| pixel[..3] == [255, 255, 255]));
+    }
+}
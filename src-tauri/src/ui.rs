@@ -1,16 +1,25 @@
 use serde_json::json;
-use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use tauri::{
     async_runtime::spawn,
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    AppHandle, Emitter, Manager, WindowEvent,
+    AppHandle, Listener, Manager, Runtime, State, WebviewWindow, WindowEvent,
 };
 
 use crate::{
+    diagnostics,
+    error::BitBurnError,
     get_autostart_status,
     get_context_menu_status,
     log_event,
+    messages::{self, Message},
+    operations::OperationRegistry,
+    settings::{SettingsStore, WindowLayout},
+    theme::{self, SystemTheme},
+    WipeResult,
     platform::{
         autostart::{register_autostart, unregister_autostart},
         context_menu::{register_context_menu, unregister_context_menu},
@@ -18,39 +27,256 @@ use crate::{
 };
 
 /// Initialize window behavior and system tray for the application.
-/// - Centers and resizes the main window to 80% height of the current monitor.
-/// - Hooks close requests to hide the window instead of quitting.
+/// - Restores the saved window layout, or centers and resizes the main window to 80% height of the current monitor.
+/// - Hooks close requests to hide the window instead of quitting and persists the layout on move/resize/hide.
 /// - Builds a tray icon with a Quit menu and click-to-toggle visibility.
+/// - Brings the hidden window back with the results when a wipe finishes, if `reopen_on_completion` is set.
+///
+/// Without a tray icon a hidden window could not be brought back, so the app runs window-only:
+/// it starts visible and closing the window quits.
 pub fn init_ui(app: &AppHandle, launch_hidden: bool) -> tauri::Result<()> {
-    setup_window(app, launch_hidden)?;
-    build_tray(app)?;
+    let tray_available = match build_tray(app) {
+        Ok(()) => true,
+        Err(e) => {
+            log_event("tray_unavailable", json!({"message": e.to_string(), "mode": "window_only"}));
+            false
+        }
+    };
+    setup_window(app, launch_hidden && tray_available, tray_available)?;
+    reopen_on_completion(app);
     Ok(())
 }
 
-fn setup_window(app: &AppHandle, launch_hidden: bool) -> tauri::Result<()> {
+/// Whether a finished operation brings the main window back: only with the setting on, the window
+/// hidden to the tray, and nothing else still running to keep it busy in the background.
+pub(crate) fn should_reopen(reopen_on_completion: bool, window_visible: bool, operations_running: bool) -> bool {
+    reopen_on_completion && !window_visible && !operations_running
+}
+
+fn reopen_on_completion(app: &AppHandle) {
+    let app_handle = app.clone();
+    app.listen_any("operation_finished", move |event| {
+        let enabled = app_handle.try_state::<SettingsStore>().is_some_and(|s| s.get().reopen_on_completion);
+        let running = app_handle.try_state::<OperationRegistry>().is_some_and(|r| r.is_active());
+        let Some(window) = app_handle.get_webview_window("main") else {
+            return;
+        };
+        if !should_reopen(enabled, window.is_visible().unwrap_or(true), running) {
+            return;
+        }
+        let operation_id = serde_json::from_str::<serde_json::Value>(event.payload())
+            .ok()
+            .and_then(|snapshot| snapshot["operation_id"].as_str().map(str::to_string));
+        log_event("window_reopened_on_completion", json!({"operation_id": operation_id}));
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        diagnostics::emit_to(&app_handle, "main", "show_results", json!({"operation_id": operation_id}));
+    });
+}
+
+/// Bring the window back with the results when a wipe finishes while it is hidden to the tray.
+#[tauri::command]
+pub async fn set_reopen_on_completion(settings: State<'_, SettingsStore>, enabled: bool) -> Result<bool, BitBurnError> {
+    let updated = settings.update(|s| s.reopen_on_completion = enabled)?;
+    log_event("reopen_on_completion_configured", json!({"enabled": updated.reopen_on_completion}));
+    Ok(updated.reopen_on_completion)
+}
+
+/// Delay after the last move/resize before the layout is written to disk.
+const LAYOUT_SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+/// Minimum part of a restored window that must remain on a monitor's work area.
+const MIN_VISIBLE_WIDTH: u32 = 100;
+const MIN_VISIBLE_HEIGHT: u32 = 50;
+
+/// Screen rectangle in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ScreenRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Monitor identity and usable work area (excluding taskbars/docks).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MonitorArea {
+    pub name: Option<String>,
+    pub work_area: ScreenRect,
+}
+
+fn intersection_size(a: &ScreenRect, b: &ScreenRect) -> (u32, u32) {
+    let left = i64::from(a.x).max(i64::from(b.x));
+    let top = i64::from(a.y).max(i64::from(b.y));
+    let right = (i64::from(a.x) + i64::from(a.width)).min(i64::from(b.x) + i64::from(b.width));
+    let bottom = (i64::from(a.y) + i64::from(a.height)).min(i64::from(b.y) + i64::from(b.height));
+    if right <= left || bottom <= top {
+        return (0, 0);
+    }
+    ((right - left) as u32, (bottom - top) as u32)
+}
+
+/// Decide whether a saved layout can be restored onto the currently attached monitors.
+/// The saved monitor must still exist, and enough of the window (including its top edge,
+/// where the title bar lives) must land inside a monitor's work area to be grabbed.
+pub(crate) fn is_layout_restorable(layout: &WindowLayout, monitors: &[MonitorArea]) -> bool {
+    if layout.width == 0 || layout.height == 0 {
+        return false;
+    }
+
+    if let Some(saved_monitor) = &layout.monitor {
+        if !monitors.iter().any(|m| m.name.as_ref() == Some(saved_monitor)) {
+            return false;
+        }
+    }
+
+    let window = ScreenRect {
+        x: layout.x,
+        y: layout.y,
+        width: layout.width,
+        height: layout.height,
+    };
+
+    monitors.iter().any(|monitor| {
+        let area = &monitor.work_area;
+        let top_edge_inside =
+            i64::from(window.y) >= i64::from(area.y) && i64::from(window.y) < i64::from(area.y) + i64::from(area.height);
+        let (visible_width, visible_height) = intersection_size(&window, area);
+        top_edge_inside
+            && visible_width >= MIN_VISIBLE_WIDTH.min(window.width)
+            && visible_height >= MIN_VISIBLE_HEIGHT.min(window.height)
+    })
+}
+
+fn capture_window_layout(window: &WebviewWindow) -> Option<WindowLayout> {
+    if window.is_minimized().unwrap_or(false) {
+        return None;
+    }
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+    Some(WindowLayout {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        monitor,
+    })
+}
+
+fn save_window_layout(window: &WebviewWindow) {
+    let Some(layout) = capture_window_layout(window) else {
+        return;
+    };
+    let Some(store) = window.app_handle().try_state::<SettingsStore>() else {
+        return;
+    };
+    if store.get().window_layout.as_ref() == Some(&layout) {
+        return;
+    }
+    if let Err(e) = store.update(|settings| settings.window_layout = Some(layout)) {
+        log_event("window_layout_save_error", json!({"message": e.to_string()}));
+    }
+}
+
+fn schedule_layout_save(window: &WebviewWindow, generation: &Arc<AtomicU64>) {
+    let ticket = generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let generation = generation.clone();
+    let window = window.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        std::thread::sleep(LAYOUT_SAVE_DEBOUNCE);
+        if generation.load(Ordering::SeqCst) == ticket {
+            save_window_layout(&window);
+        }
+    });
+}
+
+fn restore_window_layout(window: &WebviewWindow) -> bool {
+    let Some(store) = window.app_handle().try_state::<SettingsStore>() else {
+        return false;
+    };
+    let Some(layout) = store.get().window_layout else {
+        return false;
+    };
+
+    let monitors: Vec<MonitorArea> = window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|monitor| {
+            let work_area = monitor.work_area();
+            MonitorArea {
+                name: monitor.name().cloned(),
+                work_area: ScreenRect {
+                    x: work_area.position.x,
+                    y: work_area.position.y,
+                    width: work_area.size.width,
+                    height: work_area.size.height,
+                },
+            }
+        })
+        .collect();
+
+    if !is_layout_restorable(&layout, &monitors) {
+        log_event(
+            "window_layout_discarded",
+            json!({"monitor": layout.monitor, "x": layout.x, "y": layout.y}),
+        );
+        return false;
+    }
+
+    let _ = window.set_size(tauri::Size::Physical(tauri::PhysicalSize {
+        width: layout.width,
+        height: layout.height,
+    }));
+    let _ = window.set_position(tauri::Position::Physical(tauri::PhysicalPosition {
+        x: layout.x,
+        y: layout.y,
+    }));
+    true
+}
+
+fn apply_default_layout(window: &WebviewWindow) {
+    let _ = window.center();
+    if let Ok(Some(monitor)) = window.current_monitor() {
+        let monitor_size = monitor.size();
+        let window_height = (monitor_size.height as f64 * 0.80) as u32;
+        if let Ok(size) = window.outer_size() {
+            let _ = window.set_size(tauri::Size::Physical(tauri::PhysicalSize {
+                width: size.width,
+                height: window_height,
+            }));
+        }
+        let _ = window.center();
+    }
+}
+
+fn setup_window(app: &AppHandle, launch_hidden: bool, hide_on_close: bool) -> tauri::Result<()> {
     if let Some(window) = app.get_webview_window("main") {
         let window_clone = window.clone();
-        window.on_window_event(move |event| {
-            if let WindowEvent::CloseRequested { api, .. } = event {
-                if window_clone.hide().is_ok() {
+        let save_generation = Arc::new(AtomicU64::new(0));
+        window.on_window_event(move |event| match event {
+            WindowEvent::CloseRequested { api, .. } => {
+                save_window_layout(&window_clone);
+                if hide_on_close && window_clone.hide().is_ok() {
                     api.prevent_close();
                 }
             }
+            WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
+                schedule_layout_save(&window_clone, &save_generation);
+            }
+            WindowEvent::ThemeChanged(_) => theme::system_theme_changed(window_clone.app_handle()),
+            _ => {}
         });
 
         let window_clone = window.clone();
         tauri::async_runtime::spawn(async move {
-            let _ = window_clone.center();
-            if let Ok(Some(monitor)) = window_clone.current_monitor() {
-                let monitor_size = monitor.size();
-                let window_height = (monitor_size.height as f64 * 0.80) as u32;
-                if let Ok(size) = window_clone.outer_size() {
-                    let _ = window_clone.set_size(tauri::Size::Physical(tauri::PhysicalSize {
-                        width: size.width,
-                        height: window_height,
-                    }));
-                }
-                let _ = window_clone.center();
+            if !restore_window_layout(&window_clone) {
+                apply_default_layout(&window_clone);
             }
             if launch_hidden {
                 let _ = window_clone.hide();
@@ -64,6 +290,46 @@ fn setup_window(app: &AppHandle, launch_hidden: bool) -> tauri::Result<()> {
     Ok(())
 }
 
+/// Forget the saved window layout and snap the main window back to the default placement.
+#[tauri::command]
+pub async fn reset_window_layout(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+) -> Result<WipeResult, BitBurnError> {
+    settings.update(|s| s.window_layout = None)?;
+    if let Some(window) = app.get_webview_window("main") {
+        apply_default_layout(&window);
+    }
+    log_event("window_layout_reset", json!({"status": "success"}));
+    Ok(WipeResult::success(messages::WINDOW_LAYOUT_RESET.text()))
+}
+
+const TRAY_ID: &str = "main";
+
+/// Replace the tray tooltip, e.g. to show how many context-menu wipes are queued.
+pub fn set_tray_tooltip<R: Runtime>(app: &AppHandle<R>, tooltip: &str) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+/// Swap the tray icon for the variant that stands out on a taskbar in `theme`.
+pub fn update_tray_icon_for_theme(app: &AppHandle, theme: &SystemTheme) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_icon(Some(theme::tray_icon(theme::tray_icon_variant(theme.taskbar))));
+    }
+}
+
+/// Payload of `tray_context_menu_update` / `tray_autostart_update`.
+fn tray_update_payload(success: bool, message: &Message) -> serde_json::Value {
+    json!({
+        "success": success,
+        "message": message.text,
+        "message_code": message.code,
+        "params": message.params,
+    })
+}
+
 fn build_tray(app: &AppHandle) -> tauri::Result<()> {
     let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
     let toggle_item = MenuItem::with_id(
@@ -84,20 +350,7 @@ fn build_tray(app: &AppHandle) -> tauri::Result<()> {
 
     let menu = Menu::with_items(app, &[&toggle_item, &autostart_item, &quit_item])?;
 
-    let icon = match app.default_window_icon() {
-        Some(icon) => icon.clone(),
-        None => {
-            log_event(
-                "tray_icon_missing",
-                json!({"reason": "default_window_icon returned None", "action": "failing tray init"}),
-            );
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                "Tray icon missing; configure bundle icon to enable system tray",
-            )
-            .into());
-        }
-    };
+    let icon = theme::tray_icon(theme::tray_icon_variant(theme::detect(app).taskbar));
 
     {
         let toggle_item = toggle_item.clone();
@@ -123,7 +376,7 @@ fn build_tray(app: &AppHandle) -> tauri::Result<()> {
         });
     }
 
-    TrayIconBuilder::new()
+    TrayIconBuilder::with_id(TRAY_ID)
         .icon(icon)
         .menu(&menu)
         .show_menu_on_left_click(false)
@@ -132,7 +385,17 @@ fn build_tray(app: &AppHandle) -> tauri::Result<()> {
             let autostart_item = autostart_item.clone();
             move |app, event| {
             match event.id.as_ref() {
-                "quit" => app.exit(0),
+                "quit" => {
+                    if let Some(registry) = app.try_state::<OperationRegistry>() {
+                        if registry.is_active() {
+                            log_event(
+                                "quit_with_active_operations",
+                                json!({"operations": registry.active().len()}),
+                            );
+                        }
+                    }
+                    app.exit(0)
+                }
                 "toggle_context_menu" => {
                     let app_handle = app.clone();
                     let toggle_item = toggle_item.clone();
@@ -150,23 +413,25 @@ fn build_tray(app: &AppHandle) -> tauri::Result<()> {
                                     let _ = toggle_item.set_text("Enable Explorer Context Menu");
                                     log_event(
                                         "tray_context_menu_unregister",
-                                        json!({"status": res.success, "message": res.message}),
+                                        json!({"status": res.success, "message": res.message.text}),
                                     );
-                                    let _ = app_handle.emit_to(
+                                    diagnostics::emit_to(
+                                        &app_handle,
                                         "main",
                                         "tray_context_menu_update",
-                                        json!({"success": res.success, "message": res.message}),
+                                        tray_update_payload(res.success, &res.message),
                                     );
                                 }
                                 Err(e) => {
                                     log_event(
                                         "tray_context_menu_unregister",
-                                        json!({"status": false, "message": e}),
+                                        json!({"status": false, "message": e.to_string()}),
                                     );
-                                    let _ = app_handle.emit_to(
+                                    diagnostics::emit_to(
+                                        &app_handle,
                                         "main",
                                         "tray_context_menu_update",
-                                        json!({"success": false, "message": e}),
+                                        tray_update_payload(false, &Message::from_error(&e)),
                                     );
                                 }
                             }
@@ -177,23 +442,25 @@ fn build_tray(app: &AppHandle) -> tauri::Result<()> {
                                     let _ = toggle_item.set_text("Disable Explorer Context Menu");
                                     log_event(
                                         "tray_context_menu_register",
-                                        json!({"status": res.success, "message": res.message}),
+                                        json!({"status": res.success, "message": res.message.text}),
                                     );
-                                    let _ = app_handle.emit_to(
+                                    diagnostics::emit_to(
+                                        &app_handle,
                                         "main",
                                         "tray_context_menu_update",
-                                        json!({"success": res.success, "message": res.message}),
+                                        tray_update_payload(res.success, &res.message),
                                     );
                                 }
                                 Err(e) => {
                                     log_event(
                                         "tray_context_menu_register",
-                                        json!({"status": false, "message": e}),
+                                        json!({"status": false, "message": e.to_string()}),
                                     );
-                                    let _ = app_handle.emit_to(
+                                    diagnostics::emit_to(
+                                        &app_handle,
                                         "main",
                                         "tray_context_menu_update",
-                                        json!({"success": false, "message": e}),
+                                        tray_update_payload(false, &Message::from_error(&e)),
                                     );
                                 }
                             }
@@ -217,23 +484,25 @@ fn build_tray(app: &AppHandle) -> tauri::Result<()> {
                                     let _ = autostart_item.set_text("Enable Autostart with Windows");
                                     log_event(
                                         "autostart_unregister",
-                                        json!({"status": res.success, "message": res.message}),
+                                        json!({"status": res.success, "message": res.message.text}),
                                     );
-                                    let _ = app_handle.emit_to(
+                                    diagnostics::emit_to(
+                                        &app_handle,
                                         "main",
                                         "tray_autostart_update",
-                                        json!({"success": res.success, "message": res.message}),
+                                        tray_update_payload(res.success, &res.message),
                                     );
                                 }
                                 Err(e) => {
                                     log_event(
                                         "autostart_unregister",
-                                        json!({"status": false, "message": e}),
+                                        json!({"status": false, "message": e.to_string()}),
                                     );
-                                    let _ = app_handle.emit_to(
+                                    diagnostics::emit_to(
+                                        &app_handle,
                                         "main",
                                         "tray_autostart_update",
-                                        json!({"success": false, "message": e}),
+                                        tray_update_payload(false, &Message::from_error(&e)),
                                     );
                                 }
                             }
@@ -244,23 +513,25 @@ fn build_tray(app: &AppHandle) -> tauri::Result<()> {
                                     let _ = autostart_item.set_text("Disable Autostart with Windows");
                                     log_event(
                                         "autostart_register",
-                                        json!({"status": res.success, "message": res.message}),
+                                        json!({"status": res.success, "message": res.message.text}),
                                     );
-                                    let _ = app_handle.emit_to(
+                                    diagnostics::emit_to(
+                                        &app_handle,
                                         "main",
                                         "tray_autostart_update",
-                                        json!({"success": res.success, "message": res.message}),
+                                        tray_update_payload(res.success, &res.message),
                                     );
                                 }
                                 Err(e) => {
                                     log_event(
                                         "autostart_register",
-                                        json!({"status": false, "message": e}),
+                                        json!({"status": false, "message": e.to_string()}),
                                     );
-                                    let _ = app_handle.emit_to(
+                                    diagnostics::emit_to(
+                                        &app_handle,
                                         "main",
                                         "tray_autostart_update",
-                                        json!({"success": false, "message": e}),
+                                        tray_update_payload(false, &Message::from_error(&e)),
                                     );
                                 }
                             }
@@ -282,6 +553,7 @@ fn build_tray(app: &AppHandle) -> tauri::Result<()> {
                 if let Some(window) = app.get_webview_window("main") {
                     match window.is_visible() {
                         Ok(true) => {
+                            save_window_layout(&window);
                             let _ = window.hide();
                         }
                         Ok(false) => {
@@ -296,3 +568,97 @@ fn build_tray(app: &AppHandle) -> tauri::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, x: i32, y: i32, width: u32, height: u32) -> MonitorArea {
+        MonitorArea {
+            name: Some(name.to_string()),
+            work_area: ScreenRect { x, y, width, height },
+        }
+    }
+
+    fn layout(x: i32, y: i32, monitor: Option<&str>) -> WindowLayout {
+        WindowLayout {
+            x,
+            y,
+            width: 800,
+            height: 1000,
+            monitor: monitor.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn layout_on_saved_monitor_is_restorable() {
+        let monitors = vec![monitor("DISPLAY1", 0, 0, 1920, 1040)];
+        assert!(is_layout_restorable(&layout(100, 20, Some("DISPLAY1")), &monitors));
+    }
+
+    #[test]
+    fn layout_on_secondary_monitor_left_of_primary_is_restorable() {
+        let monitors = vec![
+            monitor("DISPLAY1", 0, 0, 1920, 1040),
+            monitor("DISPLAY2", -2560, 0, 2560, 1400),
+        ];
+        assert!(is_layout_restorable(&layout(-2000, 100, Some("DISPLAY2")), &monitors));
+    }
+
+    #[test]
+    fn layout_is_rejected_when_saved_monitor_is_gone() {
+        let monitors = vec![monitor("DISPLAY1", 0, 0, 1920, 1040)];
+        assert!(!is_layout_restorable(&layout(100, 20, Some("DISPLAY3")), &monitors));
+    }
+
+    #[test]
+    fn layout_is_rejected_when_completely_off_screen() {
+        let monitors = vec![monitor("DISPLAY1", 0, 0, 1920, 1040)];
+        assert!(!is_layout_restorable(&layout(3000, 20, None), &monitors));
+        assert!(!is_layout_restorable(&layout(-900, 20, None), &monitors));
+    }
+
+    #[test]
+    fn layout_is_rejected_when_only_a_sliver_is_visible() {
+        let monitors = vec![monitor("DISPLAY1", 0, 0, 1920, 1040)];
+        // Only 40px of the window's width overlaps the work area.
+        assert!(!is_layout_restorable(&layout(1880, 20, Some("DISPLAY1")), &monitors));
+        // Partially off the right edge but still comfortably grabbable.
+        assert!(is_layout_restorable(&layout(1500, 20, Some("DISPLAY1")), &monitors));
+    }
+
+    #[test]
+    fn layout_is_rejected_when_title_bar_is_above_the_work_area() {
+        let monitors = vec![monitor("DISPLAY1", 0, 40, 1920, 1000)];
+        assert!(!is_layout_restorable(&layout(100, 0, Some("DISPLAY1")), &monitors));
+        assert!(is_layout_restorable(&layout(100, 40, Some("DISPLAY1")), &monitors));
+    }
+
+    #[test]
+    fn layout_in_gap_between_monitors_is_rejected() {
+        let monitors = vec![
+            monitor("DISPLAY1", 0, 0, 1920, 1080),
+            monitor("DISPLAY2", 1920, 500, 1920, 1080),
+        ];
+        // Top edge sits above DISPLAY2's work area and to the right of DISPLAY1.
+        assert!(!is_layout_restorable(&layout(2000, 100, None), &monitors));
+    }
+
+    #[test]
+    fn only_a_window_hidden_with_the_setting_on_reopens_when_the_last_operation_finishes() {
+        assert!(should_reopen(true, false, false));
+        assert!(!should_reopen(true, true, false), "already visible");
+        assert!(!should_reopen(true, false, true), "another wipe is still running");
+        assert!(!should_reopen(false, false, false), "setting off");
+        assert!(!should_reopen(false, true, false));
+        assert!(!should_reopen(false, false, true));
+    }
+
+    #[test]
+    fn zero_sized_layout_is_rejected() {
+        let monitors = vec![monitor("DISPLAY1", 0, 0, 1920, 1040)];
+        let mut saved = layout(100, 20, None);
+        saved.width = 0;
+        assert!(!is_layout_restorable(&saved, &monitors));
+    }
+}
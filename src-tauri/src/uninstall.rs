@@ -0,0 +1,249 @@
+//! `--uninstall-cleanup`: removes everything BitBurn leaves outside its install folder, for the
+//! installer's uninstall step or to run by hand. The file manager and autostart entries are
+//! unregistered, and BitBurn's config, data, cache and log directories are deleted. Every file
+//! in them is overwritten first, since logs, journals and history name the paths that were
+//! wiped. Anything already gone counts as done, so running it twice is harmless. The
+//! machine-wide `policy.json` belongs to the administrator and is left alone.
+
+use bitburn_core::{secure_wipe_file, CancellationToken, WipeAlgorithm, WipeProgress};
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::platform::context_menu;
+
+pub const UNINSTALL_CLEANUP_FLAG: &str = "--uninstall-cleanup";
+
+/// The bundle identifier Tauri names the app's directories after.
+const APP_IDENTIFIER: &str = "com.swatto.bitburn";
+
+/// What a cleanup removed and what it could not.
+#[derive(Debug, Default, Serialize)]
+pub struct CleanupSummary {
+    /// Integrations unregistered, e.g. `context_menu`; listed even when they were not installed.
+    pub unregistered: Vec<String>,
+    /// App directories deleted.
+    pub removed_dirs: Vec<String>,
+    /// Files overwritten before their directory was deleted.
+    pub wiped_files: usize,
+    pub failures: Vec<String>,
+}
+
+/// A system entry to remove, and how.
+pub struct Integration {
+    pub name: &'static str,
+    pub remove: fn() -> Result<(), String>,
+}
+
+/// BitBurn's per-user directories, where Tauri's path API puts them for the bundle identifier.
+/// The flag is handled before the app starts, so there is no `AppHandle` to ask.
+pub fn app_dirs() -> Vec<PathBuf> {
+    let env_dir = |name: &str| std::env::var_os(name).filter(|dir| !dir.is_empty()).map(PathBuf::from);
+    let home = env_dir("HOME");
+    let roots: Vec<Option<PathBuf>> = if cfg!(windows) {
+        vec![env_dir("APPDATA"), env_dir("LOCALAPPDATA")]
+    } else if cfg!(target_os = "macos") {
+        let library = home.map(|home| home.join("Library"));
+        ["Application Support", "Caches", "Logs"]
+            .iter()
+            .map(|sub| library.as_ref().map(|library| library.join(sub)))
+            .collect()
+    } else {
+        let xdg = |name: &str, fallback: &[&str]| {
+            env_dir(name).or_else(|| home.as_ref().map(|home| fallback.iter().fold(home.clone(), |dir, part| dir.join(part))))
+        };
+        vec![
+            xdg("XDG_CONFIG_HOME", &[".config"]),
+            xdg("XDG_DATA_HOME", &[".local", "share"]),
+            xdg("XDG_CACHE_HOME", &[".cache"]),
+        ]
+    };
+    let mut dirs: Vec<PathBuf> = roots.into_iter().flatten().map(|root| root.join(APP_IDENTIFIER)).collect();
+    dirs.dedup();
+    dirs
+}
+
+/// The entries this platform can have registered.
+pub fn integrations() -> Vec<Integration> {
+    let context_menu = Integration {
+        name: "context_menu",
+        remove: || context_menu::disable_context_menu().map_err(|e| e.to_string()),
+    };
+    std::iter::once(context_menu).chain(windows_integrations()).collect()
+}
+
+#[cfg(windows)]
+fn windows_integrations() -> Vec<Integration> {
+    let machine_context_menu = Integration {
+        name: "machine_context_menu",
+        remove: || context_menu::disable_machine_context_menu().map_err(|e| e.to_string()),
+    };
+    crate::platform::elevation::is_elevated()
+        .then_some(machine_context_menu)
+        .into_iter()
+        .chain([
+            Integration {
+                name: "autostart",
+                remove: || crate::platform::autostart::remove_autostart().map_err(|e| e.to_string()),
+            },
+            Integration {
+                name: "send_to",
+                remove: || crate::platform::send_to::remove_shortcut().map_err(|e| e.to_string()),
+            },
+        ])
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn windows_integrations() -> Vec<Integration> {
+    Vec::new()
+}
+
+/// Unregister `integrations` and delete `dirs`, overwriting each file in them first.
+pub fn cleanup(integrations: &[Integration], dirs: &[PathBuf]) -> CleanupSummary {
+    let mut summary = CleanupSummary::default();
+    for integration in integrations {
+        match (integration.remove)() {
+            Ok(()) => summary.unregistered.push(integration.name.to_string()),
+            Err(e) => summary.failures.push(format!("{}: {}", integration.name, e)),
+        }
+    }
+    for dir in dirs {
+        if fs::symlink_metadata(dir).is_err() {
+            continue;
+        }
+        summary.wiped_files += wipe_files_in(dir, &mut summary.failures);
+        match fs::remove_dir_all(dir) {
+            Ok(()) => summary.removed_dirs.push(dir.to_string_lossy().into_owned()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => summary.failures.push(format!("{}: {}", dir.display(), e)),
+        }
+    }
+    summary
+}
+
+/// Overwrite and delete every file under `dir`, returning how many were overwritten. Links are
+/// left for `remove_dir_all`, which deletes the link and not what it points at.
+fn wipe_files_in(dir: &Path, failures: &mut Vec<String>) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut wiped = 0;
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => wiped += wipe_files_in(&path, failures),
+            Ok(kind) if kind.is_file() => {
+                match secure_wipe_file(&path, 1, &WipeAlgorithm::NistClear, &CancellationToken::new(), |_: WipeProgress| {}) {
+                    Ok(()) => wiped += 1,
+                    // Still deleted with the directory; the failure says it was not overwritten.
+                    Err(e) => failures.push(format!("{}: {}", path.display(), e)),
+                }
+            }
+            _ => {}
+        }
+    }
+    wiped
+}
+
+/// Run the cleanup for this user and print what it did. Exits 0 when everything is gone.
+pub fn run_cli<F>(log_event: &mut F) -> i32
+where
+    F: FnMut(&str, serde_json::Value),
+{
+    let summary = cleanup(&integrations(), &app_dirs());
+    log_event("uninstall_cleanup", json!({"summary": summary}));
+    for name in &summary.unregistered {
+        println!("Removed {}", name);
+    }
+    for dir in &summary.removed_dirs {
+        println!("Deleted {}", dir);
+    }
+    println!("Overwrote {} files", summary.wiped_files);
+    for failure in &summary.failures {
+        eprintln!("Failed: {}", failure);
+    }
+    if summary.failures.is_empty() {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    fn app_data_dir() -> PathBuf {
+        let dir = create_test_dir().unwrap().join(APP_IDENTIFIER);
+        fs::create_dir_all(dir.join("logs").join("audit")).unwrap();
+        fs::create_dir_all(dir.join("journal")).unwrap();
+        fs::write(dir.join("settings.json"), br#"{"retention_rules": []}"#).unwrap();
+        fs::write(dir.join("status.json"), b"{}").unwrap();
+        fs::write(dir.join("logs").join("audit").join("audit-000001.jsonl"), b"C:\\secret\\plans.docx").unwrap();
+        fs::write(dir.join("journal").join("op-1.json"), b"[]").unwrap();
+        dir
+    }
+
+    #[test]
+    fn cleanup_removes_the_app_directories_and_is_idempotent() {
+        let dir = app_data_dir();
+        let missing = dir.with_file_name("never_created");
+        let integrations = [Integration { name: "context_menu", remove: || Ok(()) }];
+
+        let summary = cleanup(&integrations, &[dir.clone(), missing.clone()]);
+        assert!(summary.failures.is_empty(), "{:?}", summary.failures);
+        assert_eq!(summary.unregistered, ["context_menu"]);
+        assert_eq!(summary.wiped_files, 4);
+        assert_eq!(summary.removed_dirs, [dir.to_string_lossy()]);
+        assert!(!dir.exists());
+
+        let again = cleanup(&integrations, &[dir.clone(), missing]);
+        assert!(again.failures.is_empty());
+        assert_eq!((again.wiped_files, again.removed_dirs.len()), (0, 0));
+        cleanup_test_dir(dir.parent().unwrap());
+    }
+
+    #[test]
+    fn a_failing_integration_is_reported_and_the_rest_still_runs() {
+        let dir = app_data_dir();
+        let integrations = [
+            Integration { name: "autostart", remove: || Err("access is denied".to_string()) },
+            Integration { name: "send_to", remove: || Ok(()) },
+        ];
+        let summary = cleanup(&integrations, std::slice::from_ref(&dir));
+        assert_eq!(summary.failures, ["autostart: access is denied"]);
+        assert_eq!(summary.unregistered, ["send_to"]);
+        assert!(!dir.exists());
+        cleanup_test_dir(dir.parent().unwrap());
+    }
+
+    #[test]
+    fn app_dirs_are_named_after_the_bundle_identifier() {
+        let dirs = app_dirs();
+        assert!(!dirs.is_empty());
+        assert!(dirs.iter().all(|dir| dir.ends_with(APP_IDENTIFIER)));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn cleanup_unregisters_the_context_menu_under_the_override_root() {
+        let temp_root = format!("Software\\Classes\\BitBurnUninstallTest_{}", std::process::id());
+        std::env::set_var("BITBURN_CONTEXT_ROOT", &temp_root);
+        context_menu::enable_context_menu(Path::new("C:/BitBurn/BitBurn.exe")).unwrap();
+        assert!(context_menu::is_context_menu_enabled().unwrap());
+
+        let integrations: Vec<Integration> = integrations().into_iter().filter(|i| i.name == "context_menu").collect();
+        let summary = cleanup(&integrations, &[]);
+        assert!(summary.failures.is_empty(), "{:?}", summary.failures);
+        assert!(!context_menu::is_context_menu_enabled().unwrap());
+        assert!(cleanup(&integrations, &[]).failures.is_empty(), "a second run finds nothing to remove");
+
+        let hkcu = winreg::RegKey::predef(winreg::enums::HKEY_CURRENT_USER);
+        let _ = hkcu.delete_subkey_all(&temp_root);
+        std::env::remove_var("BITBURN_CONTEXT_ROOT");
+    }
+}
@@ -0,0 +1,34 @@
+//! Read-back verification of file wipes, for audits that ask for proof each pass was written as
+//! intended. The read-back goes through the same handle and the OS cache, so it shows what the
+//! file holds after each synced pass, not what the medium stores. Reading back all 35 Gutmann
+//! passes doubles an already long wipe, so besides `full` a wipe can verify a random percentage of
+//! each pass's chunks (`sampled`) or only the last pass (`final_only`). The chunks a sampled pass
+//! reads follow from a seed drawn per operation and kept in the report, so the sample can be
+//! reproduced; a file resumed from a checkpoint keeps the seed its first run drew (its record's
+//! `verification_seed`). A chunk that reads back wrong fails the file.
+
+use bitburn_core::{Verification, VerifyMode};
+use serde_json::json;
+use tauri::State;
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::settings::{Settings, SettingsStore};
+
+/// The verification the settings ask for, with a fresh seed; `None` when wipes are not verified.
+pub fn from_settings(settings: &Settings) -> Option<Verification> {
+    settings.verify_mode.map(|mode| Verification { mode, seed: rand::random() })
+}
+
+/// Choose how file wipes read their passes back; `None` turns verification off.
+#[tauri::command]
+pub async fn set_verify_mode(settings: State<'_, SettingsStore>, mode: Option<VerifyMode>) -> Result<Option<VerifyMode>, BitBurnError> {
+    if mode.is_some_and(|mode| !mode.is_valid()) {
+        return Err(BitBurnError::InvalidConfiguration {
+            message: "The sampled percentage must be above 0 and at most 100".to_string(),
+        });
+    }
+    let updated = settings.update(|s| s.verify_mode = mode)?;
+    log_event("verify_mode_configured", json!({"mode": updated.verify_mode}));
+    Ok(updated.verify_mode)
+}
@@ -0,0 +1,326 @@
+//! The most recent free-space wipe of each volume, kept in the app data directory so "when did I
+//! last wipe free space on D:?" has an answer. Volumes are told apart by their serial number
+//! where the platform has one, so a drive that comes back under another letter, or a stick
+//! plugged in again, keeps its history; elsewhere the mount point stands in for it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::capabilities::{self, DriveCapability};
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::settings::write_file_atomic;
+use crate::{WipeAlgorithm, WipeResult};
+
+const HISTORY_FILE_NAME: &str = "volume_history.json";
+
+/// A free-space estimate notes a completed wipe of the volume younger than this.
+pub const RECENT_WIPE_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How a volume is recognised from one wipe to the next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolumeIdentity {
+    /// File system serial or UUID; `None` where it cannot be read.
+    pub serial: Option<String>,
+    pub mount_point: String,
+}
+
+impl VolumeIdentity {
+    pub fn of(drive: &DriveCapability) -> Self {
+        VolumeIdentity { serial: volume_serial(drive), mount_point: drive.mount_point.clone() }
+    }
+
+    /// The volume holding `path`, as far as the drive list knows it.
+    pub fn holding(path: &str) -> Self {
+        let drives = capabilities::list_drives().unwrap_or_default();
+        match capabilities::drive_holding(&drives, path) {
+            Some(drive) => VolumeIdentity::of(drive),
+            None => VolumeIdentity { serial: None, mount_point: path.to_string() },
+        }
+    }
+
+    fn key(&self) -> String {
+        match &self.serial {
+            Some(serial) => format!("serial:{}", serial),
+            None => format!("mount:{}", self.mount_point),
+        }
+    }
+}
+
+/// The volume serial number Windows shows as `1A2B-3C4D`.
+#[cfg(windows)]
+fn volume_serial(drive: &DriveCapability) -> Option<String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetVolumeInformationW;
+
+    let mut root = drive.mount_point.clone();
+    if !root.ends_with('\\') {
+        root.push('\\');
+    }
+    let wide: Vec<u16> = std::ffi::OsStr::new(&root).encode_wide().chain(std::iter::once(0)).collect();
+    let mut serial = 0u32;
+    let null = std::ptr::null_mut();
+    let found = unsafe { GetVolumeInformationW(wide.as_ptr(), null, 0, &mut serial, null.cast(), null.cast(), null, 0) };
+    (found != 0).then(|| format!("{:04X}-{:04X}", serial >> 16, serial & 0xFFFF))
+}
+
+/// The file system UUID udev links to the volume's device.
+#[cfg(target_os = "linux")]
+fn volume_serial(drive: &DriveCapability) -> Option<String> {
+    let device = fs::canonicalize(&drive.name).ok()?;
+    fs::read_dir("/dev/disk/by-uuid")
+        .ok()?
+        .flatten()
+        .find(|entry| fs::canonicalize(entry.path()).is_ok_and(|target| target == device))
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn volume_serial(_drive: &DriveCapability) -> Option<String> {
+    None
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WipeStatus {
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// One free-space wipe as `get_volume_wipe_history` and the drive list report it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FreeSpaceWipeRecord {
+    /// Where the volume was mounted when it was wiped.
+    pub mount_point: String,
+    pub serial: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub finished_at: u64,
+    pub algorithm: WipeAlgorithm,
+    pub passes: u32,
+    pub bytes_written: u64,
+    pub status: WipeStatus,
+}
+
+/// The persisted record, keyed by volume serial, or by mount point where there is none.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WipeHistory {
+    pub volumes: BTreeMap<String, FreeSpaceWipeRecord>,
+}
+
+impl WipeHistory {
+    pub fn record(&mut self, record: FreeSpaceWipeRecord) {
+        let identity = VolumeIdentity { serial: record.serial.clone(), mount_point: record.mount_point.clone() };
+        self.volumes.insert(identity.key(), record);
+    }
+
+    pub fn last_wipe(&self, identity: &VolumeIdentity) -> Option<&FreeSpaceWipeRecord> {
+        self.volumes.get(&identity.key())
+    }
+
+    /// Whether the volume's free space was last wiped to completion less than `cooldown` ago, so
+    /// wiping it again now would mostly rewrite space that holds nothing recoverable.
+    pub fn wiped_within(&self, identity: &VolumeIdentity, cooldown: Duration, now: SystemTime) -> bool {
+        let now = now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        self.last_wipe(identity).is_some_and(|record| {
+            record.status == WipeStatus::Completed && now.saturating_sub(record.finished_at) < cooldown.as_secs()
+        })
+    }
+
+    /// `drives` with the last free-space wipe of each filled in.
+    pub fn annotate(&self, drives: Vec<DriveCapability>) -> Vec<DriveCapability> {
+        drives
+            .into_iter()
+            .map(|mut drive| {
+                drive.last_free_space_wipe = self.last_wipe(&VolumeIdentity::of(&drive)).cloned();
+                drive
+            })
+            .collect()
+    }
+}
+
+/// The history shared through managed state, written back after every change.
+pub struct HistoryStore {
+    path: Option<PathBuf>,
+    history: Mutex<WipeHistory>,
+}
+
+impl HistoryStore {
+    /// Load the history from `path`, starting empty when the file is missing or unreadable.
+    pub fn load(path: PathBuf) -> Self {
+        let history = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                log_event("volume_history_load_error", json!({"path": path.to_string_lossy(), "message": e.to_string()}));
+                WipeHistory::default()
+            }),
+            Err(_) => WipeHistory::default(),
+        };
+        HistoryStore { path: Some(path), history: Mutex::new(history) }
+    }
+
+    /// Store that never touches disk; used when the data directory cannot be resolved.
+    pub fn in_memory() -> Self {
+        HistoryStore { path: None, history: Mutex::new(WipeHistory::default()) }
+    }
+
+    pub fn history(&self) -> WipeHistory {
+        self.lock().clone()
+    }
+
+    /// Replace the volume's last wipe with `record` and persist the history.
+    pub fn record(&self, record: FreeSpaceWipeRecord) {
+        let mut history = self.lock();
+        history.record(record);
+        if let Some(path) = &self.path {
+            let written = serde_json::to_vec_pretty(&*history)
+                .map_err(|e| BitBurnError::internal(e.to_string()))
+                .and_then(|contents| write_file_atomic(path, &contents));
+            if let Err(e) = written {
+                log_event("volume_history_save_error", json!({"message": e.to_string()}));
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, WipeHistory> {
+        self.history.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Build the managed history store rooted in the app data directory.
+pub fn init_history_store<R: Runtime>(app: &AppHandle<R>) -> HistoryStore {
+    match app.path().app_data_dir() {
+        Ok(dir) => HistoryStore::load(dir.join(HISTORY_FILE_NAME)),
+        Err(_) => {
+            log_event("volume_history_path_unavailable", json!({"action": "free-space wipes are not remembered"}));
+            HistoryStore::in_memory()
+        }
+    }
+}
+
+/// Remember how the free-space wipe of `path` ended. Nothing is recorded for a wipe refused before
+/// it started writing, such as one of a path that is not a volume root or a stale estimate.
+pub fn record_finished<R: Runtime>(
+    app: &AppHandle<R>,
+    path: &str,
+    algorithm: &WipeAlgorithm,
+    passes: u32,
+    result: &WipeResult,
+) {
+    let Some(store) = app.try_state::<HistoryStore>() else {
+        return;
+    };
+    let report = result.report.as_ref();
+    let (status, bytes_written) = match (report.and_then(|r| r.free_space.as_ref()), report.and_then(|r| r.cancelled_fill.as_ref())) {
+        (Some(coverage), _) if result.success => (WipeStatus::Completed, coverage.total_bytes_written),
+        (_, Some(fill)) => (WipeStatus::Cancelled, fill.bytes_written),
+        _ => match result.error.as_ref().map(BitBurnError::code) {
+            Some("cancelled") => (WipeStatus::Cancelled, 0),
+            Some(
+                "not_drive_root" | "path_not_found" | "network_path_not_supported" | "volume_read_only" | "blocked_by_policy"
+                | "stale_estimate" | "estimate_not_found" | "confirmation_required",
+            ) => return,
+            _ => (WipeStatus::Failed, 0),
+        },
+    };
+    let identity = VolumeIdentity::holding(path);
+    let finished_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    log_event(
+        "volume_wipe_recorded",
+        json!({"mount_point": identity.mount_point, "serial": identity.serial, "status": status, "bytes_written": bytes_written}),
+    );
+    store.record(FreeSpaceWipeRecord {
+        mount_point: identity.mount_point,
+        serial: identity.serial,
+        finished_at,
+        algorithm: algorithm.clone(),
+        passes,
+        bytes_written,
+        status,
+    });
+}
+
+/// The last free-space wipe of the volume holding `mount_point`, wherever it was mounted then.
+#[tauri::command]
+pub async fn get_volume_wipe_history(
+    store: State<'_, HistoryStore>,
+    mount_point: String,
+) -> Result<Option<FreeSpaceWipeRecord>, BitBurnError> {
+    let identity = capabilities::run_blocking(move || Ok(VolumeIdentity::holding(&mount_point))).await?;
+    Ok(store.history().last_wipe(&identity).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn wipe(serial: Option<&str>, mount_point: &str, finished_at: u64, status: WipeStatus) -> FreeSpaceWipeRecord {
+        FreeSpaceWipeRecord {
+            mount_point: mount_point.to_string(),
+            serial: serial.map(str::to_string),
+            finished_at,
+            algorithm: WipeAlgorithm::NistClear,
+            passes: 1,
+            bytes_written: 64 * 1024 * 1024 * 1024,
+            status,
+        }
+    }
+
+    fn identity(serial: Option<&str>, mount_point: &str) -> VolumeIdentity {
+        VolumeIdentity { serial: serial.map(str::to_string), mount_point: mount_point.to_string() }
+    }
+
+    #[test]
+    fn a_volume_keeps_its_history_under_another_drive_letter() {
+        let mut history = WipeHistory::default();
+        history.record(wipe(Some("1A2B-3C4D"), "E:\\", 1_000, WipeStatus::Completed));
+        history.record(wipe(None, "/mnt/nfs", 2_000, WipeStatus::Failed));
+
+        // The same stick reinserted as F:, and a different one that took its old letter.
+        let moved = history.last_wipe(&identity(Some("1A2B-3C4D"), "F:\\")).expect("found by serial");
+        assert_eq!((moved.mount_point.as_str(), moved.finished_at), ("E:\\", 1_000));
+        assert!(history.last_wipe(&identity(Some("9999-0000"), "E:\\")).is_none());
+        // Without a serial the mount point is all there is to go on.
+        assert_eq!(history.last_wipe(&identity(None, "/mnt/nfs")).unwrap().status, WipeStatus::Failed);
+
+        // A later wipe of the volume replaces the earlier one.
+        history.record(wipe(Some("1A2B-3C4D"), "F:\\", 5_000, WipeStatus::Cancelled));
+        assert_eq!(history.volumes.len(), 2);
+        assert_eq!(history.last_wipe(&identity(Some("1A2B-3C4D"), "E:\\")).unwrap().finished_at, 5_000);
+    }
+
+    #[test]
+    fn only_a_completed_wipe_inside_the_cooldown_counts_as_recent() {
+        let now = UNIX_EPOCH + Duration::from_secs(100 * DAY);
+        let volume = identity(Some("1A2B-3C4D"), "D:\\");
+        let mut history = WipeHistory::default();
+        assert!(!history.wiped_within(&volume, Duration::from_secs(7 * DAY), now));
+
+        history.record(wipe(Some("1A2B-3C4D"), "D:\\", 97 * DAY, WipeStatus::Completed));
+        assert!(history.wiped_within(&volume, Duration::from_secs(7 * DAY), now));
+        assert!(!history.wiped_within(&volume, Duration::from_secs(2 * DAY), now), "three days old is stale");
+
+        history.record(wipe(Some("1A2B-3C4D"), "D:\\", 99 * DAY, WipeStatus::Cancelled));
+        assert!(!history.wiped_within(&volume, Duration::from_secs(7 * DAY), now), "a cancelled wipe covers nothing");
+    }
+
+    #[test]
+    fn history_survives_a_restart() {
+        let dir = create_test_dir().unwrap();
+        let path = dir.join(HISTORY_FILE_NAME);
+
+        HistoryStore::load(path.clone()).record(wipe(Some("1A2B-3C4D"), "E:\\", 1_000, WipeStatus::Completed));
+        let reloaded = HistoryStore::load(path).history();
+        assert_eq!(reloaded.last_wipe(&identity(Some("1A2B-3C4D"), "G:\\")).unwrap().bytes_written, 64 * 1024 * 1024 * 1024);
+        cleanup_test_dir(&dir);
+    }
+}
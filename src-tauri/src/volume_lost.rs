@@ -0,0 +1,165 @@
+//! Drives pulled out and volumes dismounted in the middle of a wipe. Without this, a yanked USB
+//! stick fails every remaining file with its own I/O error; with it, the first failure that shows
+//! the volume is gone marks it lost, the files still to come on it are set aside untouched, and the
+//! lot is reported as one `VolumeDisconnected`.
+
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime};
+
+use crate::diagnostics;
+use crate::error::BitBurnError;
+use crate::read_only::{mount_points, volume_of};
+use crate::{log_event, BatchContext, WipeError, WipeResult};
+
+/// What a failure said about its volume.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Loss {
+    /// The first sign that this volume is gone.
+    New(String),
+    /// The volume was already known to be gone.
+    Known,
+}
+
+/// Volumes a batch has lost, and the items on each that were not wiped.
+#[derive(Debug, Default)]
+pub struct LostVolumes {
+    /// Taken when the batch starts; a volume that has gone away is no longer listed.
+    mounts: Vec<PathBuf>,
+    lost: BTreeMap<PathBuf, Vec<String>>,
+}
+
+impl LostVolumes {
+    pub fn new(mounts: Vec<PathBuf>) -> Self {
+        LostVolumes { mounts, lost: BTreeMap::new() }
+    }
+
+    pub fn from_system() -> Self {
+        LostVolumes::new(mount_points())
+    }
+
+    /// Whether `err`, raised for `path`, shows that its volume is gone: the OS says the device is
+    /// gone, or the volume's mount point has disappeared. If so, `path` joins the volume's
+    /// untouched items.
+    pub fn record_failure(&mut self, path: &Path, err: &io::Error) -> Option<Loss> {
+        let volume = volume_of(path, &self.mounts);
+        if let Some(untouched) = self.lost.get_mut(&volume) {
+            untouched.push(path.to_string_lossy().to_string());
+            return Some(Loss::Known);
+        }
+        if !bitburn_core::is_device_gone(err.raw_os_error()) && volume.exists() {
+            return None;
+        }
+        let name = volume.to_string_lossy().to_string();
+        self.lost.insert(volume, vec![path.to_string_lossy().to_string()]);
+        Some(Loss::New(name))
+    }
+
+    /// Set `path` aside, without touching it, if its volume is already known to be gone.
+    pub fn set_aside(&mut self, path: &Path) -> bool {
+        match self.lost.get_mut(&volume_of(path, &self.mounts)) {
+            Some(untouched) => {
+                untouched.push(path.to_string_lossy().to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_lost(&self, path: &Path) -> bool {
+        self.lost.contains_key(&volume_of(path, &self.mounts))
+    }
+
+    /// One `VolumeDisconnected` per lost volume.
+    pub fn into_errors(self) -> Vec<BitBurnError> {
+        self.lost
+            .into_iter()
+            .map(|(volume, paths)| BitBurnError::VolumeDisconnected { volume: volume.to_string_lossy().to_string(), paths })
+            .collect()
+    }
+}
+
+/// Check a batch file's failure against `lost`, telling the UI the first time a volume is found
+/// gone. Returns whether the failure is accounted for by a lost volume.
+pub(crate) fn note_failure<R: Runtime>(ctx: &BatchContext<R>, lost: &mut LostVolumes, path: &Path, err: &WipeError) -> bool {
+    let WipeError::Io(err) = err else {
+        return false;
+    };
+    match lost.record_failure(path, err) {
+        Some(Loss::New(volume)) => {
+            let payload = json!({"operation_id": ctx.operation.id(), "volume": volume, "message": err.to_string()});
+            log_event("volume_lost", payload.clone());
+            ctx.emit_critical("volume_lost", payload);
+            true
+        }
+        Some(Loss::Known) => true,
+        None => false,
+    }
+}
+
+/// End a free-space wipe whose volume went away while it ran.
+pub(crate) fn free_space_lost<R: Runtime>(
+    app: &AppHandle<R>,
+    window_label: &str,
+    operation_id: &str,
+    volume: &Path,
+    err: &dyn std::fmt::Display,
+) -> WipeResult {
+    let volume = volume.to_string_lossy().to_string();
+    let payload = json!({"operation_id": operation_id, "volume": volume, "message": err.to_string()});
+    log_event("volume_lost", payload.clone());
+    diagnostics::emit_to(app, window_label, "volume_lost", payload);
+    WipeResult::failure(BitBurnError::VolumeDisconnected { volume, paths: Vec::new() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::volume_root::comparable;
+
+    #[cfg(windows)]
+    const DEVICE_NOT_CONNECTED: i32 = 1167;
+    #[cfg(unix)]
+    const DEVICE_NOT_CONNECTED: i32 = libc::ENODEV;
+
+    #[cfg(any(windows, unix))]
+    #[test]
+    fn a_lost_volume_groups_its_remaining_items_and_leaves_others_alone() {
+        let base = comparable(&std::env::temp_dir());
+        let stick = base.join("bitburn_missing_usb_stick");
+        let mut lost = LostVolumes::new(vec![base.clone(), stick.clone()]);
+        let unplugged = io::Error::from_raw_os_error(DEVICE_NOT_CONNECTED);
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+
+        // An ordinary failure on a volume that is still there is not a loss.
+        assert_eq!(lost.record_failure(&base.join("locked.txt"), &denied), None);
+        assert!(!lost.set_aside(&base.join("next.txt")));
+
+        assert_eq!(
+            lost.record_failure(&base.join("first.bin"), &unplugged),
+            Some(Loss::New(base.to_string_lossy().to_string()))
+        );
+        assert!(lost.set_aside(&base.join("second.bin")));
+        assert_eq!(lost.record_failure(&base.join("third.bin"), &denied), Some(Loss::Known));
+
+        // A mount point that no longer exists is lost whatever the error says.
+        assert_eq!(
+            lost.record_failure(&stick.join("photo.jpg"), &denied),
+            Some(Loss::New(stick.to_string_lossy().to_string()))
+        );
+        assert!(lost.is_lost(&stick.join("DCIM")));
+
+        let errors = lost.into_errors();
+        assert_eq!(errors.len(), 2, "one error per volume: {:?}", errors);
+        let paths_on = |volume: &Path| {
+            errors.iter().find_map(|error| match error {
+                BitBurnError::VolumeDisconnected { volume: v, paths } if v.as_str() == volume.to_string_lossy() => Some(paths.len()),
+                _ => None,
+            })
+        };
+        assert_eq!(paths_on(&base), Some(3));
+        assert_eq!(paths_on(&stick), Some(1));
+    }
+}
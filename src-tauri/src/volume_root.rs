@@ -0,0 +1,120 @@
+//! Volume roots and mount points passed as folders to wipe. Walking one would destroy the whole
+//! volume file by file, which is what the free-space and whole-drive features exist for.
+
+use std::path::{Path, PathBuf};
+use sysinfo::{DiskExt, System, SystemExt};
+
+use crate::error::BitBurnError;
+use crate::protected::normalize;
+
+/// Mount points a requested folder is compared against.
+#[derive(Debug, Clone, Default)]
+pub struct VolumeRoots {
+    mount_points: Vec<PathBuf>,
+}
+
+impl VolumeRoots {
+    pub fn new(mount_points: impl IntoIterator<Item = PathBuf>) -> Self {
+        VolumeRoots {
+            mount_points: mount_points.into_iter().map(|mount| comparable(&mount)).collect(),
+        }
+    }
+
+    /// Mount points of the disks sysinfo reports, the same list drive validation matches against.
+    pub fn from_system() -> Self {
+        let mut sys = System::new();
+        sys.refresh_disks_list();
+        VolumeRoots::new(sys.disks().iter().map(|disk| disk.mount_point().to_path_buf()))
+    }
+
+    /// Whether `path` is a filesystem root (`/`, `C:\`) or a volume mounted below one.
+    pub fn is_volume_root(&self, path: &Path) -> bool {
+        let candidate = comparable(path);
+        candidate.parent().is_none() || self.mount_points.contains(&candidate) || crosses_device(path)
+    }
+
+    pub fn check(&self, path: &Path) -> Result<(), BitBurnError> {
+        if self.is_volume_root(path) {
+            Err(BitBurnError::VolumeRoot {
+                path: path.to_string_lossy().to_string(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Reject `path` if it is the root of a volume on this machine.
+pub fn check(path: &Path) -> Result<(), BitBurnError> {
+    VolumeRoots::from_system().check(path)
+}
+
+/// Canonical paths on Windows carry a `\\?\` prefix that mount points from sysinfo do not.
+pub(crate) fn comparable(path: &Path) -> PathBuf {
+    let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let text = resolved.to_string_lossy();
+    normalize(Path::new(text.strip_prefix(r"\\?\").unwrap_or(&text)))
+}
+
+/// sysinfo leaves out bind mounts and virtual filesystems; a folder on a different device from
+/// its parent is mounted there all the same.
+#[cfg(unix)]
+fn crosses_device(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Some(parent) = path.parent() else {
+        return false;
+    };
+    match (std::fs::metadata(path), std::fs::metadata(parent)) {
+        (Ok(own), Ok(parent)) => own.is_dir() && own.dev() != parent.dev(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn crosses_device(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+    use std::fs;
+
+    fn deep_dir() -> PathBuf {
+        let dir = create_test_dir()
+            .unwrap()
+            .join("projects")
+            .join("archive");
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn filesystem_roots_are_refused() {
+        let roots = VolumeRoots::default();
+        let root = if cfg!(windows) { Path::new("C:\\") } else { Path::new("/") };
+        assert!(roots.is_volume_root(root));
+        let err = roots.check(root).unwrap_err();
+        assert_eq!(err.code(), "volume_root");
+    }
+
+    #[test]
+    fn nested_mount_points_are_refused() {
+        let mount = deep_dir();
+        let roots = VolumeRoots::new([PathBuf::from("/"), mount.clone()]);
+        assert!(roots.is_volume_root(&mount));
+        assert!(roots.is_volume_root(&mount.join(".")), "the same folder spelled differently");
+        assert!(!roots.is_volume_root(mount.parent().unwrap()));
+        cleanup_test_dir(mount.parent().unwrap().parent().unwrap());
+    }
+
+    #[test]
+    fn ordinary_deep_directories_are_allowed() {
+        let dir = deep_dir();
+        assert!(!VolumeRoots::from_system().is_volume_root(&dir));
+        assert_eq!(check(&dir), Ok(()));
+        cleanup_test_dir(dir.parent().unwrap().parent().unwrap());
+    }
+}
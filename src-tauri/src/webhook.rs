@@ -0,0 +1,530 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::error::BitBurnError;
+use crate::messages;
+use crate::operations::{OperationOutcome, OperationSnapshot};
+use crate::report::WipeReport;
+use crate::settings::SettingsStore;
+use crate::{log_event, WipeResult};
+
+/// Webhook sink notified when an operation finishes. Stored in the settings file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    pub enabled: bool,
+    pub webhook_url: String,
+    pub bearer_token: Option<String>,
+    pub timeout_secs: u64,
+    pub include_file_list: bool,
+    /// Required to deliver over plain `http://`; HTTPS certificates are always validated.
+    pub allow_insecure: bool,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        WebhookSettings {
+            enabled: false,
+            webhook_url: String::new(),
+            bearer_token: None,
+            timeout_secs: 10,
+            include_file_list: false,
+            allow_insecure: false,
+        }
+    }
+}
+
+impl WebhookSettings {
+    pub fn validate(&self) -> Result<(), BitBurnError> {
+        let invalid = |message: &str| BitBurnError::InvalidConfiguration {
+            message: format!("webhook: {}", message),
+        };
+
+        if !self.enabled {
+            return Ok(());
+        }
+        let url = self.webhook_url.trim();
+        if url.starts_with("http://") {
+            if !self.allow_insecure {
+                return Err(invalid("plain HTTP URLs require allow_insecure"));
+            }
+        } else if !url.starts_with("https://") {
+            return Err(invalid("URL must start with https://"));
+        }
+        if url.len() <= "https://".len() {
+            return Err(invalid("URL is missing a host"));
+        }
+        if self.timeout_secs == 0 {
+            return Err(invalid("timeout_secs must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// Retry schedule for deliveries: `attempts` tries, doubling the delay after each failure.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Outcome of the most recent delivery, shown by `get_webhook_status`.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookAttempt {
+    pub operation_id: String,
+    pub attempted_at: u64,
+    pub delivered: bool,
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookStatus {
+    pub enabled: bool,
+    pub webhook_url: String,
+    pub last_attempt: Option<WebhookAttempt>,
+}
+
+/// Managed state remembering the last delivery attempt.
+#[derive(Clone, Default)]
+pub struct WebhookNotifier {
+    last_attempt: Arc<Mutex<Option<WebhookAttempt>>>,
+}
+
+impl WebhookNotifier {
+    fn record(&self, attempt: WebhookAttempt) {
+        *self.last_attempt.lock().unwrap_or_else(|p| p.into_inner()) = Some(attempt);
+    }
+
+    fn last_attempt(&self) -> Option<WebhookAttempt> {
+        self.last_attempt.lock().unwrap_or_else(|p| p.into_inner()).clone()
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// JSON summary posted for a finished operation. The file list is only included on request.
+pub fn build_payload(
+    snapshot: &OperationSnapshot,
+    outcome: Option<&OperationOutcome>,
+    report: Option<&WipeReport>,
+    include_file_list: bool,
+) -> Value {
+    let mut payload = json!({
+        "event": "operation_finished",
+        "operation_id": snapshot.operation_id,
+        "kind": snapshot.kind,
+        "target_summary": snapshot.target_summary,
+        "started_at": snapshot.started_at,
+        "finished_at": now_secs(),
+        "status": outcome.map(|o| o.status),
+        "message": outcome.map(|o| o.message.text.clone()),
+        "message_code": outcome.map(|o| o.message.code.clone()),
+        "params": outcome.map(|o| o.message.params.clone()),
+        "error_code": outcome.and_then(|o| o.error_code.clone()),
+        "failures": outcome.map(|o| o.failure_count).unwrap_or(0),
+        "files_wiped": report.map(|r| r.files_wiped).unwrap_or(0),
+        "skipped": report.map(|r| r.skipped.len()).unwrap_or(0),
+        "source": report.and_then(|r| r.source.clone()),
+    });
+
+    if include_file_list {
+        payload["files"] = json!(report.map(|r| r.wiped_files.clone()).unwrap_or_default());
+    }
+    payload
+}
+
+/// Escape a value for a double-quoted curl config entry.
+fn curl_config_quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn null_device() -> &'static str {
+    if cfg!(windows) {
+        "NUL"
+    } else {
+        "/dev/null"
+    }
+}
+
+/// The curl config for one delivery: URL, headers and the body itself, so none of them appear
+/// in the process list or on disk.
+fn curl_config(settings: &WebhookSettings, body: &str) -> String {
+    let mut config = format!("url = {}\n", curl_config_quote(settings.webhook_url.trim()));
+    config.push_str("header = \"Content-Type: application/json\"\n");
+    if let Some(token) = settings.bearer_token.as_deref().filter(|t| !t.is_empty()) {
+        config.push_str(&format!("header = {}\n", curl_config_quote(&format!("Authorization: Bearer {}", token))));
+    }
+    config.push_str(&format!("data-binary = {}\n", curl_config_quote(body)));
+    config
+}
+
+/// POST `body` once via the system curl, with everything about the request passed on its stdin.
+/// Returns the HTTP status code.
+fn post_once(settings: &WebhookSettings, body: &str) -> Result<u16, String> {
+    let config = curl_config(settings, body);
+    let protocols = if settings.allow_insecure { "=http,https" } else { "=https" };
+    let mut command = Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--request", "POST"])
+        .args(["--proto", protocols, "--proto-redir", protocols])
+        .args(["--max-time", &settings.timeout_secs.to_string()])
+        .args(["--output", null_device(), "--write-out", "%{http_code}"])
+        .args(["--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("failed to start curl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(config.as_bytes())
+            .map_err(|e| format!("failed to pass request to curl: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("curl did not finish: {}", e))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u16>()
+        .map_err(|_| "curl returned no HTTP status".to_string())
+}
+
+/// Deliver `payload` with retries and exponential backoff.
+pub fn deliver(settings: &WebhookSettings, operation_id: &str, payload: &Value, policy: RetryPolicy) -> WebhookAttempt {
+    let mut attempt = WebhookAttempt {
+        operation_id: operation_id.to_string(),
+        attempted_at: now_secs(),
+        delivered: false,
+        attempts: 0,
+        http_status: None,
+        error: None,
+    };
+
+    let body = payload.to_string();
+    let mut backoff = policy.initial_backoff;
+    for try_index in 0..policy.attempts.max(1) {
+        if try_index > 0 {
+            std::thread::sleep(backoff);
+            backoff *= 2;
+        }
+        attempt.attempts = try_index + 1;
+        match post_once(settings, &body) {
+            Ok(status) if (200..300).contains(&status) => {
+                attempt.delivered = true;
+                attempt.http_status = Some(status);
+                attempt.error = None;
+                break;
+            }
+            Ok(status) => {
+                attempt.http_status = Some(status);
+                attempt.error = Some(format!("HTTP {}", status));
+            }
+            Err(e) => {
+                attempt.http_status = None;
+                attempt.error = Some(e);
+            }
+        }
+    }
+
+    attempt
+}
+
+/// Post the summary of a finished operation on a background thread if a webhook is configured.
+pub fn notify_operation_finished<R: Runtime>(
+    app: &AppHandle<R>,
+    snapshot: &OperationSnapshot,
+    outcome: Option<OperationOutcome>,
+    report: Option<WipeReport>,
+) {
+    let Some(settings) = app.try_state::<SettingsStore>().map(|s| s.get().webhook) else {
+        return;
+    };
+    if !settings.enabled {
+        return;
+    }
+    let Some(notifier) = app.try_state::<WebhookNotifier>().map(|n| n.inner().clone()) else {
+        return;
+    };
+
+    if let Err(e) = settings.validate() {
+        log_event("webhook_skipped", json!({"operation_id": snapshot.operation_id, "message": e.to_string()}));
+        return;
+    }
+
+    let payload = build_payload(snapshot, outcome.as_ref(), report.as_ref(), settings.include_file_list);
+    let operation_id = snapshot.operation_id.clone();
+    std::thread::spawn(move || {
+        let attempt = deliver(&settings, &operation_id, &payload, RetryPolicy::default());
+        log_event(
+            "webhook_delivery",
+            json!({
+                "operation_id": operation_id,
+                "delivered": attempt.delivered,
+                "attempts": attempt.attempts,
+                "http_status": attempt.http_status,
+                "error": attempt.error,
+            }),
+        );
+        notifier.record(attempt);
+    });
+}
+
+/// Current webhook configuration (without the token) and the outcome of the last delivery.
+#[tauri::command]
+pub async fn get_webhook_status(
+    settings: State<'_, SettingsStore>,
+    notifier: State<'_, WebhookNotifier>,
+) -> Result<WebhookStatus, BitBurnError> {
+    let webhook = settings.get().webhook;
+    Ok(WebhookStatus {
+        enabled: webhook.enabled,
+        webhook_url: webhook.webhook_url,
+        last_attempt: notifier.last_attempt(),
+    })
+}
+
+/// Validate and save the webhook configuration.
+#[tauri::command]
+pub async fn configure_webhook(
+    settings: State<'_, SettingsStore>,
+    config: WebhookSettings,
+) -> Result<WipeResult, BitBurnError> {
+    config.validate()?;
+    settings.update(|s| s.webhook = config)?;
+    log_event("webhook_configured", json!({"status": "success"}));
+    Ok(WipeResult::success(messages::WEBHOOK_SAVED.text()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::{OperationKind, OutcomeStatus};
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    struct CapturedRequest {
+        headers: Vec<String>,
+        body: String,
+    }
+
+    /// Minimal HTTP server answering each connection with the next status in `statuses`.
+    fn mock_server(statuses: Vec<u16>) -> (String, JoinHandle<Vec<CapturedRequest>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut captured = Vec::new();
+            for status in statuses {
+                let (stream, _) = listener.accept().expect("accept");
+                let mut reader = BufReader::new(stream);
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end().to_string();
+                    if line.is_empty() {
+                        break;
+                    }
+                    headers.push(line);
+                }
+                let length = headers
+                    .iter()
+                    .find_map(|h| h.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                    .unwrap_or(0);
+                let mut body = vec![0u8; length];
+                reader.read_exact(&mut body).unwrap();
+                let mut stream = reader.into_inner();
+                write!(stream, "HTTP/1.1 {} Mock\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).unwrap();
+                captured.push(CapturedRequest {
+                    headers,
+                    body: String::from_utf8(body).unwrap(),
+                });
+            }
+            captured
+        });
+        (url, handle)
+    }
+
+    fn settings_for(url: &str) -> WebhookSettings {
+        WebhookSettings {
+            enabled: true,
+            webhook_url: url.to_string(),
+            bearer_token: Some("s3cret".to_string()),
+            timeout_secs: 5,
+            include_file_list: false,
+            allow_insecure: true,
+        }
+    }
+
+    fn fast_retries() -> RetryPolicy {
+        RetryPolicy {
+            attempts: 3,
+            initial_backoff: Duration::from_millis(10),
+        }
+    }
+
+    fn sample_snapshot() -> OperationSnapshot {
+        OperationSnapshot {
+            operation_id: "op-1".to_string(),
+            kind: OperationKind::WipeFiles,
+            started_at: 1,
+            current_phase: "Done".to_string(),
+            overall_percentage: 100.0,
+            target_summary: "C:/data".to_string(),
+        }
+    }
+
+    fn sample_report() -> WipeReport {
+        let mut report = WipeReport::new("op-1", Some("manifest:list.txt".to_string()));
+        report.files_wiped = 2;
+        report.wiped_files = vec!["C:/data/a".to_string(), "C:/data/b".to_string()];
+        report
+    }
+
+    fn sample_outcome() -> OperationOutcome {
+        OperationOutcome {
+            status: OutcomeStatus::Completed,
+            message: messages::FILES_WIPED.render(json!({"count": 2})),
+            error_code: None,
+            failure_count: 0,
+        }
+    }
+
+    #[test]
+    fn payload_omits_file_list_unless_requested() {
+        let without = build_payload(&sample_snapshot(), Some(&sample_outcome()), Some(&sample_report()), false);
+        assert!(without.get("files").is_none());
+        assert_eq!(without["status"], "completed");
+        assert_eq!(without["files_wiped"], 2);
+        assert_eq!(without["kind"], "wipe_files");
+
+        let with = build_payload(&sample_snapshot(), Some(&sample_outcome()), Some(&sample_report()), true);
+        assert_eq!(with["files"], json!(["C:/data/a", "C:/data/b"]));
+    }
+
+    #[test]
+    fn delivers_payload_with_bearer_token() {
+        let (url, server) = mock_server(vec![200]);
+        let payload = build_payload(&sample_snapshot(), Some(&sample_outcome()), Some(&sample_report()), false);
+        let attempt = deliver(&settings_for(&url), "op-1", &payload, fast_retries());
+
+        assert!(attempt.delivered, "delivery failed: {:?}", attempt.error);
+        assert_eq!(attempt.attempts, 1);
+        assert_eq!(attempt.http_status, Some(200));
+
+        let requests = server.join().unwrap();
+        let request = &requests[0];
+        assert!(request.headers[0].starts_with("POST /hook"));
+        assert!(request.headers.iter().any(|h| h == "Authorization: Bearer s3cret"));
+        assert!(request.headers.iter().any(|h| h == "Content-Type: application/json"));
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["operation_id"], "op-1");
+        assert_eq!(body["event"], "operation_finished");
+    }
+
+    #[test]
+    fn retries_until_success() {
+        let (url, server) = mock_server(vec![500, 503, 204]);
+        let attempt = deliver(&settings_for(&url), "op-1", &json!({}), fast_retries());
+        assert!(attempt.delivered);
+        assert_eq!(attempt.attempts, 3);
+        assert_eq!(attempt.http_status, Some(204));
+        assert_eq!(server.join().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn gives_up_after_three_attempts() {
+        let (url, server) = mock_server(vec![500, 500, 500]);
+        let attempt = deliver(&settings_for(&url), "op-1", &json!({}), fast_retries());
+        assert!(!attempt.delivered);
+        assert_eq!(attempt.attempts, 3);
+        assert_eq!(attempt.http_status, Some(500));
+        assert_eq!(attempt.error.as_deref(), Some("HTTP 500"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn plain_http_requires_allow_insecure() {
+        let mut settings = settings_for("http://collector.local/hook");
+        assert!(settings.validate().is_ok());
+        settings.allow_insecure = false;
+        assert_eq!(settings.validate().unwrap_err().code(), "invalid_configuration");
+
+        settings.webhook_url = "https://collector.local/hook".to_string();
+        assert!(settings.validate().is_ok());
+        settings.webhook_url = "ftp://collector.local".to_string();
+        assert!(settings.validate().is_err());
+
+        settings.enabled = false;
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn curl_config_values_are_escaped() {
+        assert_eq!(curl_config_quote("a\"b\\c"), "\"a\\\"b\\\\c\"");
+    }
+
+    #[test]
+    fn the_body_travels_in_the_config_not_a_file() {
+        let settings = WebhookSettings {
+            webhook_url: "https://hooks.example/wipe".into(),
+            bearer_token: Some("s3cret".into()),
+            ..Default::default()
+        };
+        let body = json!({"message": "Wiped \"C:\\payroll\""}).to_string();
+        let config = curl_config(&settings, &body);
+        let data = config.lines().find_map(|line| line.strip_prefix("data-binary = ")).unwrap();
+        assert_eq!(data, curl_config_quote(&body));
+        assert!(config.contains("Authorization: Bearer s3cret"));
+        assert!(!config.contains('@'), "nothing is read from a file");
+    }
+}
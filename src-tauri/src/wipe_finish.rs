@@ -0,0 +1,339 @@
+use bitburn_core::{cancelled_wipe_error, WipeTarget};
+use rand::distributions::{Alphanumeric, DistString};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::BitBurnError;
+use crate::log_event;
+use crate::messages;
+use crate::report::{FileDisposition, InterruptedFile, WipeReport};
+use crate::settings::Settings;
+use crate::WipeError;
+
+/// What to do with a file whose contents are already destroyed when the wipe is cancelled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelCleanup {
+    /// Finish truncating and deleting it; nothing recoverable is left to keep.
+    #[default]
+    FinishDeleting,
+    /// Stop, putting the original name back if the file had been renamed.
+    RollBack,
+}
+
+/// Steps after the last overwrite pass, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinishStep {
+    Truncate,
+    Rename,
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FinishOptions {
+    /// Rename the file to a random name before deleting it, so the directory entry stops naming it.
+    pub obfuscate_name: bool,
+    pub on_cancel: CancelCleanup,
+}
+
+impl FinishOptions {
+    pub fn from_settings(settings: &Settings) -> Self {
+        FinishOptions {
+            obfuscate_name: settings.obfuscate_file_names,
+            on_cancel: settings.cancel_cleanup,
+        }
+    }
+}
+
+/// Where the original names of renamed files are kept until the files are gone, so a crash
+/// between the rename and the delete cannot lose track of one.
+pub trait RenameLog {
+    /// Record that `original` is about to be renamed to `on_disk`. The rename only goes ahead once
+    /// this has returned `Ok`, by which time the record must be on disk.
+    fn renaming(&self, original: &Path, on_disk: &Path) -> Result<(), BitBurnError>;
+
+    /// `original` is deleted or back under its own name.
+    fn settled(&self, original: &Path);
+}
+
+/// The original path and the name the file currently has on disk, kept from before any rename
+/// until the file is gone, so an interrupted finish can always say where the file ended up.
+struct Transaction<'a> {
+    original: &'a Path,
+    on_disk: PathBuf,
+    names: Option<&'a dyn RenameLog>,
+}
+
+impl Transaction<'_> {
+    /// Without a record of the original name the file keeps it and is deleted under it; the
+    /// warning saying so is returned.
+    fn rename_to_random(&mut self) -> Result<Option<String>, WipeError> {
+        let random = self
+            .original
+            .with_file_name(Alphanumeric.sample_string(&mut rand::thread_rng(), 16));
+        // The reason is the same for every file that fails the same way, so the warnings merge into
+        // one; the error itself, which may name the path, only goes to the log.
+        let recorded = match self.names {
+            Some(names) => names
+                .renaming(self.original, &random)
+                .map_err(|e| ("the crash journal could not be written", e.to_string())),
+            None => {
+                let reason = "there was no crash journal to record the original names in";
+                Err((reason, reason.to_string()))
+            }
+        };
+        if let Err((reason, message)) = recorded {
+            log_event("file_name_kept", json!({"path": self.original.to_string_lossy(), "message": message}));
+            return Ok(Some(messages::FILE_NAMES_KEPT.render(json!({"reason": reason})).text));
+        }
+        fs::rename(&self.on_disk, &random).map_err(WipeError::Io)?;
+        self.on_disk = random;
+        Ok(None)
+    }
+
+    /// Put the original name back; reports where the file was left if that fails.
+    fn roll_back(&mut self) -> FileDisposition {
+        if self.on_disk != self.original {
+            if fs::rename(&self.on_disk, self.original).is_err() {
+                return self.left_on_disk();
+            }
+            self.on_disk = self.original.to_path_buf();
+        }
+        self.settled();
+        FileDisposition::Restored
+    }
+
+    fn delete(&mut self) -> Result<(), WipeError> {
+        fs::remove_file(&self.on_disk).map_err(WipeError::Io)?;
+        self.settled();
+        Ok(())
+    }
+
+    fn settled(&self) {
+        if let Some(names) = self.names {
+            names.settled(self.original);
+        }
+    }
+
+    fn left_on_disk(&self) -> FileDisposition {
+        FileDisposition::LeftOnDisk {
+            on_disk_path: self.on_disk.to_string_lossy().to_string(),
+        }
+    }
+
+    fn record(&self, disposition: FileDisposition, interrupted: &mut Vec<InterruptedFile>) {
+        interrupted.push(InterruptedFile {
+            path: self.original.to_string_lossy().to_string(),
+            disposition,
+        });
+    }
+}
+
+/// Truncate, optionally rename, and delete a file whose every pass has been written.
+/// Cancellation is checked before each step. `FinishDeleting` completes the deletion and returns
+/// `Ok`; `RollBack` restores the original name and returns the cancellation error. Either way, and
+/// whenever a step fails, the file's final disposition is pushed to `report.interrupted`.
+/// The file is only renamed once `names` has recorded its original name; one that keeps its name
+/// for want of a record is a warning in `report.warnings`, given once per reason.
+/// `before_step` runs ahead of each step so tests can cancel at a chosen point.
+pub fn finish_overwritten<T: WipeTarget>(
+    target: T,
+    path: &Path,
+    cancelled: &AtomicBool,
+    options: FinishOptions,
+    names: Option<&dyn RenameLog>,
+    report: &mut WipeReport,
+    before_step: &mut dyn FnMut(FinishStep),
+) -> Result<(), WipeError> {
+    let mut transaction = Transaction {
+        original: path,
+        on_disk: path.to_path_buf(),
+        names,
+    };
+    let mut target = Some(target);
+    let mut cancel_seen = false;
+    let mut steps = vec![FinishStep::Truncate];
+    if options.obfuscate_name {
+        steps.push(FinishStep::Rename);
+    }
+    steps.push(FinishStep::Delete);
+
+    for step in steps {
+        before_step(step);
+        if !cancel_seen && cancelled.load(Ordering::SeqCst) {
+            cancel_seen = true;
+            if options.on_cancel == CancelCleanup::RollBack {
+                let disposition = transaction.roll_back();
+                transaction.record(disposition, &mut report.interrupted);
+                return Err(cancelled_wipe_error());
+            }
+        }
+
+        let outcome = match step {
+            // The handle is released afterwards so the file can be renamed and deleted on Windows.
+            FinishStep::Truncate => match target.take() {
+                Some(mut target) => target.truncate().map_err(WipeError::Io),
+                None => Ok(()),
+            },
+            FinishStep::Rename => transaction.rename_to_random().map(|kept| {
+                if let Some(warning) = kept.filter(|warning| !report.warnings.contains(warning)) {
+                    report.warnings.push(warning);
+                }
+            }),
+            FinishStep::Delete => transaction.delete(),
+        };
+        if let Err(e) = outcome {
+            let disposition = transaction.roll_back();
+            transaction.record(disposition, &mut report.interrupted);
+            return Err(e);
+        }
+    }
+
+    if cancel_seen {
+        transaction.record(FileDisposition::Deleted, &mut report.interrupted);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{cleanup_test_dir, create_test_dir};
+    use std::collections::BTreeMap;
+    use std::fs::OpenOptions;
+    use std::sync::Mutex;
+
+    /// Records names in memory, checking each is recorded while the file still has its own.
+    #[derive(Default)]
+    struct Names {
+        renamed: Mutex<BTreeMap<PathBuf, PathBuf>>,
+        refuse: bool,
+    }
+
+    impl RenameLog for Names {
+        fn renaming(&self, original: &Path, on_disk: &Path) -> Result<(), BitBurnError> {
+            assert!(original.exists() && !on_disk.exists(), "recorded after the rename");
+            if self.refuse {
+                return Err(BitBurnError::internal(format!("journal unavailable for {}", original.display())));
+            }
+            self.renamed.lock().unwrap().insert(original.to_path_buf(), on_disk.to_path_buf());
+            Ok(())
+        }
+
+        fn settled(&self, original: &Path) {
+            self.renamed.lock().unwrap().remove(original);
+        }
+    }
+
+    fn scratch_file() -> (PathBuf, fs::File) {
+        let path = create_test_dir().unwrap().join("secret.txt");
+        fs::write(&path, b"already overwritten").unwrap();
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        (path, file)
+    }
+
+    fn entries(path: &Path) -> Vec<PathBuf> {
+        fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect()
+    }
+
+    fn cancel_at(
+        step: FinishStep,
+        on_cancel: CancelCleanup,
+    ) -> (PathBuf, Result<(), WipeError>, Vec<InterruptedFile>) {
+        let (path, file) = scratch_file();
+        let cancelled = AtomicBool::new(false);
+        let mut report = WipeReport::default();
+        let options = FinishOptions { obfuscate_name: true, on_cancel };
+        let names = Names::default();
+        let result = finish_overwritten(file, &path, &cancelled, options, Some(&names), &mut report, &mut |at| {
+            if at == step {
+                cancelled.store(true, Ordering::SeqCst);
+            }
+        });
+        assert!(names.renamed.lock().unwrap().is_empty(), "{:?}: a settled file keeps no record", step);
+        (path, result, report.interrupted)
+    }
+
+    #[test]
+    fn finish_deleting_removes_the_file_and_reports_it_at_every_cancel_point() {
+        for step in [FinishStep::Truncate, FinishStep::Rename, FinishStep::Delete] {
+            let (path, result, interrupted) = cancel_at(step, CancelCleanup::FinishDeleting);
+            assert!(result.is_ok(), "{:?}", step);
+            assert!(entries(&path).is_empty(), "{:?} left {:?}", step, entries(&path));
+            assert_eq!(interrupted.len(), 1);
+            assert_eq!(interrupted[0].path, path.to_string_lossy());
+            assert_eq!(interrupted[0].disposition, FileDisposition::Deleted);
+            cleanup_test_dir(path.parent().unwrap());
+        }
+    }
+
+    #[test]
+    fn roll_back_restores_the_original_name_at_every_cancel_point() {
+        for step in [FinishStep::Truncate, FinishStep::Rename, FinishStep::Delete] {
+            let (path, result, interrupted) = cancel_at(step, CancelCleanup::RollBack);
+            assert!(matches!(result, Err(WipeError::Io(ref e)) if e.kind() == std::io::ErrorKind::Interrupted));
+            assert_eq!(entries(&path), vec![path.clone()], "{:?}: only the original name may remain", step);
+            assert_eq!(interrupted.len(), 1);
+            assert_eq!(interrupted[0].disposition, FileDisposition::Restored);
+            let truncated = fs::metadata(&path).unwrap().len() == 0;
+            assert_eq!(truncated, step != FinishStep::Truncate, "{:?}", step);
+            cleanup_test_dir(path.parent().unwrap());
+        }
+    }
+
+    #[test]
+    fn uncancelled_finish_reports_nothing() {
+        let (path, file) = scratch_file();
+        let mut report = WipeReport::default();
+        let options = FinishOptions { obfuscate_name: true, ..Default::default() };
+        let names = Names::default();
+        finish_overwritten(file, &path, &AtomicBool::new(false), options, Some(&names), &mut report, &mut |_| {})
+            .unwrap();
+        assert!(entries(&path).is_empty());
+        assert!(report.interrupted.is_empty());
+        assert!(report.warnings.is_empty());
+        cleanup_test_dir(path.parent().unwrap());
+    }
+
+    #[test]
+    fn the_original_name_is_recorded_before_the_rename_or_the_file_keeps_it_with_a_warning() {
+        for names in [Some(Names::default()), Some(Names { refuse: true, ..Default::default() }), None] {
+            let (path, file) = scratch_file();
+            let options = FinishOptions { obfuscate_name: true, ..Default::default() };
+            let recorded = names.as_ref().is_some_and(|names| !names.refuse);
+            let mut report = WipeReport::default();
+            let mut on_disk_before_delete = Vec::new();
+            finish_overwritten(
+                file,
+                &path,
+                &AtomicBool::new(false),
+                options,
+                names.as_ref().map(|names| names as &dyn RenameLog),
+                &mut report,
+                &mut |step| {
+                    if step == FinishStep::Delete {
+                        on_disk_before_delete = entries(&path);
+                        if let Some(names) = &names {
+                            let renamed = names.renamed.lock().unwrap();
+                            assert_eq!(renamed.get(&path), on_disk_before_delete.first().filter(|_| recorded));
+                        }
+                    }
+                },
+            )
+            .unwrap();
+            assert_eq!(on_disk_before_delete.len(), 1);
+            assert_eq!(on_disk_before_delete[0] != path, recorded, "renamed only once recorded");
+            assert_eq!(report.warnings.len(), usize::from(!recorded), "a kept name is reported");
+            let path_text = path.to_string_lossy();
+            assert!(report.warnings.iter().all(|w| !w.contains(&*path_text)), "one warning fits every kept name");
+            assert!(entries(&path).is_empty());
+            cleanup_test_dir(path.parent().unwrap());
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Mixed batches. A selection spread over an SSD and an external HDD wants NIST Clear on one and
+//! Purge on the other, which used to take two operations and two confirmations. `wipe_files`
+//! also takes a list of `WipeTask`s, each a path with its own algorithm and passes, and runs them
+//! as one operation with one confirmation, one progress stream and one report, in which every
+//! file records the algorithm it was overwritten with. `recommend_tasks` builds such a list from
+//! the recommendation for each volume.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::BitBurnError;
+use crate::selection::{self, MergeReason};
+use crate::WipeAlgorithm;
+
+/// One root of a mixed batch and how it is wiped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WipeTask {
+    pub path: String,
+    pub algorithm: WipeAlgorithm,
+    pub passes: u32,
+}
+
+/// What `wipe_files` was asked to wipe, in either form: the roots, the algorithm and passes of
+/// the batch, and for a mixed batch the tasks giving each root its own. The batch's algorithm
+/// and passes are then the first task's.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WipeRequest {
+    pub paths: Vec<String>,
+    pub algorithm: WipeAlgorithm,
+    pub passes: u32,
+    pub tasks: Vec<WipeTask>,
+}
+
+fn invalid(message: impl Into<String>) -> BitBurnError {
+    BitBurnError::InvalidConfiguration { message: message.into() }
+}
+
+/// Accept either `paths`, `algorithm` and `passes`, or `tasks` alone.
+pub fn request(
+    paths: Option<Vec<String>>,
+    passes: Option<u32>,
+    algorithm: Option<WipeAlgorithm>,
+    tasks: Option<Vec<WipeTask>>,
+) -> Result<WipeRequest, BitBurnError> {
+    match (tasks, paths, passes, algorithm) {
+        (None, Some(paths), Some(passes), Some(algorithm)) => Ok(WipeRequest { paths, algorithm, passes, tasks: Vec::new() }),
+        (Some(tasks), None, None, None) => {
+            validate(&tasks)?;
+            let (algorithm, passes) = (tasks[0].algorithm.clone(), tasks[0].passes);
+            let paths = tasks.iter().map(|task| task.path.clone()).collect();
+            Ok(WipeRequest { paths, algorithm, passes, tasks })
+        }
+        (Some(_), ..) => Err(invalid("Give either tasks or paths with an algorithm and passes, not both")),
+        _ => Err(invalid("Paths, an algorithm and passes are all needed without tasks")),
+    }
+}
+
+/// Refuse tasks that cannot run together: none at all, a task without passes, or a path asked
+/// for twice, or inside a folder asked for, with a different algorithm or pass count. A batch
+/// wipes such a path once, so one of the two would not be honoured.
+pub fn validate(tasks: &[WipeTask]) -> Result<(), BitBurnError> {
+    if tasks.is_empty() {
+        return Err(invalid("There are no tasks to run"));
+    }
+    if let Some(task) = tasks.iter().find(|task| task.passes == 0) {
+        return Err(BitBurnError::InvalidPasses { passes: task.passes });
+    }
+    let how = |path: &str| {
+        tasks.iter().filter(move |task| task.path == path).map(|task| (&task.algorithm, task.passes)).collect::<Vec<_>>()
+    };
+    let normalized = selection::normalize(tasks.iter().map(|task| task.path.clone()).collect());
+    for merged in &normalized.merged {
+        let ways = [how(&merged.path), how(&merged.covered_by)].concat();
+        if ways.iter().any(|way| *way != ways[0]) {
+            return Err(invalid(match merged.reason {
+                MergeReason::Duplicate => format!("{} is asked for twice with different algorithms or passes", merged.path),
+                MergeReason::Inside => format!(
+                    "{} is inside {}, which is asked for with a different algorithm or passes",
+                    merged.path, merged.covered_by
+                ),
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// The algorithm and passes `tasks` give the root selected as `path`.
+pub fn for_root<'a>(tasks: &'a [WipeTask], path: &str) -> Option<(&'a WipeAlgorithm, u32)> {
+    tasks.iter().find(|task| task.path == path).map(|task| (&task.algorithm, task.passes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(path: &str, algorithm: WipeAlgorithm, passes: u32) -> WipeTask {
+        WipeTask { path: path.to_string(), algorithm, passes }
+    }
+
+    #[test]
+    fn a_request_takes_one_form_or_the_other() {
+        let uniform = request(Some(vec!["/a".into()]), Some(1), Some(WipeAlgorithm::NistClear), None).unwrap();
+        assert_eq!((uniform.algorithm, uniform.passes, uniform.tasks.len()), (WipeAlgorithm::NistClear, 1, 0));
+
+        let tasks = vec![task("/ssd/a", WipeAlgorithm::NistClear, 1), task("/hdd/b", WipeAlgorithm::NistPurge, 3)];
+        let mixed = request(None, None, None, Some(tasks.clone())).unwrap();
+        assert_eq!(mixed.paths, ["/ssd/a", "/hdd/b"]);
+        assert_eq!((mixed.algorithm, mixed.passes), (WipeAlgorithm::NistClear, 1));
+        assert_eq!(for_root(&mixed.tasks, "/hdd/b"), Some((&WipeAlgorithm::NistPurge, 3)));
+        assert_eq!(for_root(&mixed.tasks, "/elsewhere"), None);
+
+        let both = request(Some(vec!["/a".into()]), Some(1), Some(WipeAlgorithm::NistClear), Some(tasks));
+        assert!(matches!(both, Err(BitBurnError::InvalidConfiguration { .. })));
+        assert!(matches!(request(Some(vec!["/a".into()]), None, None, None), Err(BitBurnError::InvalidConfiguration { .. })));
+    }
+
+    #[test]
+    fn tasks_that_would_wipe_a_path_two_ways_are_refused() {
+        assert!(matches!(validate(&[]), Err(BitBurnError::InvalidConfiguration { .. })));
+        assert!(matches!(validate(&[task("/a", WipeAlgorithm::Random, 0)]), Err(BitBurnError::InvalidPasses { passes: 0 })));
+
+        let clear = |path: &str| task(path, WipeAlgorithm::NistClear, 1);
+        let purge = |path: &str| task(path, WipeAlgorithm::NistPurge, 3);
+        assert!(validate(&[clear("/data/a"), purge("/data/b")]).is_ok());
+        // Overlaps that agree are merged as usual.
+        assert!(validate(&[clear("/data"), clear("/data/a"), clear("/data")]).is_ok());
+        for tasks in [
+            vec![clear("/data/a"), purge("/data/a")],
+            vec![purge("/data"), clear("/data/reports/q1.pdf")],
+            vec![clear("/data/reports/q1.pdf"), purge("/data")],
+            vec![clear("/data/a"), task("/data/a", WipeAlgorithm::NistClear, 2)],
+        ] {
+            assert!(matches!(validate(&tasks), Err(BitBurnError::InvalidConfiguration { .. })), "{:?}", tasks);
+        }
+    }
+}
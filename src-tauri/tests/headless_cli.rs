@@ -0,0 +1,153 @@
+//! Runs `BitBurn --headless` and checks the JSON-lines stream it writes to stdout.
+
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let unique = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+    let dir = std::env::temp_dir().join(format!("BitBurn_headless_{}_{}", name, unique));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_BitBurn")).arg("--headless").args(args).output().expect("BitBurn runs")
+}
+
+/// Every stdout line, parsed; fails on anything that is not a JSON object with a `type`.
+fn lines(output: &Output) -> Vec<Value> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            let value: Value = serde_json::from_str(line).unwrap_or_else(|e| panic!("not JSON ({}): {}", e, line));
+            assert!(value["type"].is_string(), "no type: {}", line);
+            value
+        })
+        .collect()
+}
+
+/// Check a line carries the fields its type promises.
+fn assert_schema(line: &Value) {
+    let has = |field: &str| !line[field].is_null();
+    assert!(line["operation_id"].is_string(), "{}", line);
+    match line["type"].as_str().unwrap() {
+        "start" => {
+            assert!(line["paths"].is_array() && line["passes"].is_u64() && has("algorithm"), "{}", line)
+        }
+        "progress" => {
+            let percentage = line["overall_percentage"].as_f64().unwrap();
+            assert!((0.0..=100.0).contains(&percentage), "{}", line);
+            assert!(line["phase"].is_string() && line["files_completed"].is_u64(), "{}", line);
+            assert!(line.get("bytes_per_second").is_some(), "{}", line);
+        }
+        "file" => assert!(line["path"].is_string() && line["status"].is_string(), "{}", line),
+        "result" => assert!(line["success"].is_boolean() && line["message"].is_string(), "{}", line),
+        other => panic!("unknown line type {}", other),
+    }
+}
+
+#[test]
+fn json_output_streams_start_progress_files_and_result_in_order() {
+    let dir = scratch_dir("json");
+    let file = dir.join("secret.bin");
+    fs::write(&file, vec![7u8; 256 * 1024]).unwrap();
+    let folder = dir.join("folder");
+    fs::create_dir(&folder).unwrap();
+    fs::write(folder.join("inner.txt"), b"inner").unwrap();
+
+    let output = run(&[
+        "--output",
+        "json",
+        "--algorithm",
+        "NistClear",
+        "--wipe",
+        &file.to_string_lossy(),
+        &folder.to_string_lossy(),
+    ]);
+    assert_eq!(output.status.code(), Some(0), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let lines = lines(&output);
+    lines.iter().for_each(assert_schema);
+
+    let types: Vec<&str> = lines.iter().map(|line| line["type"].as_str().unwrap()).collect();
+    assert_eq!(types.first(), Some(&"start"));
+    assert_eq!(types.last(), Some(&"result"));
+    assert_eq!(types.iter().filter(|t| **t == "start" || **t == "result").count(), 2);
+    let operation_id = &lines[0]["operation_id"];
+    assert!(lines.iter().all(|line| &line["operation_id"] == operation_id));
+    assert_eq!(lines[0]["passes"], 1);
+    assert_eq!(lines[0]["algorithm"], "NistClear");
+
+    let files: Vec<&Value> = lines.iter().filter(|line| line["type"] == "file").collect();
+    assert_eq!(files.len(), 2);
+    assert!(files.iter().all(|line| line["status"] == "success"));
+    assert_eq!(files[0]["path"], file.to_string_lossy().as_ref());
+
+    let result = lines.last().unwrap();
+    assert_eq!(result["success"], true);
+    assert_eq!(result["report"]["files_wiped"], 2);
+    assert_eq!(result["report"]["source"], "headless");
+    assert!(!file.exists() && !folder.exists());
+    // Log events never reach stdout.
+    assert!(String::from_utf8_lossy(&output.stderr).contains("wipe_files_end"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn quiet_output_prints_only_the_result_line() {
+    let dir = scratch_dir("quiet");
+    let file = dir.join("a.txt");
+    fs::write(&file, b"contents").unwrap();
+    let missing = dir.join("missing.txt");
+
+    let output = run(&["--output", "quiet", "--wipe", &file.to_string_lossy(), &missing.to_string_lossy()]);
+    assert_eq!(output.status.code(), Some(1));
+    let lines = lines(&output);
+    assert_eq!(lines.len(), 1);
+    assert_schema(&lines[0]);
+    assert_eq!(lines[0]["type"], "result");
+    assert_eq!(lines[0]["success"], false);
+    assert_eq!(lines[0]["report"]["files_wiped"], 1);
+    assert_eq!(lines[0]["failures"].as_array().map(Vec::len), Some(1));
+    assert!(!file.exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn bad_arguments_end_with_a_result_line_and_exit_code_two() {
+    let output = run(&["--output", "json", "--passes", "three", "--wipe", "/nonexistent"]);
+    assert_eq!(output.status.code(), Some(2));
+    let lines = lines(&output);
+    assert_eq!(lines.len(), 1);
+    assert_schema(&lines[0]);
+    assert_eq!(lines[0]["error"]["code"], "invalid_arguments");
+}
+
+#[test]
+fn relative_paths_and_environment_variables_are_expanded() {
+    let dir = scratch_dir("expand");
+    fs::write(dir.join("relative.txt"), b"relative").unwrap();
+    fs::write(dir.join("variable.txt"), b"variable").unwrap();
+    let (variable, undefined) = if cfg!(windows) {
+        ("%BITBURN_TEST_DIR%\\variable.txt", "%BITBURN_UNDEFINED_VAR%\\x.txt")
+    } else {
+        ("$BITBURN_TEST_DIR/variable.txt", "${BITBURN_UNDEFINED_VAR}/x.txt")
+    };
+
+    let output = Command::new(env!("CARGO_BIN_EXE_BitBurn"))
+        .current_dir(&dir)
+        .env("BITBURN_TEST_DIR", &dir)
+        .args(["--headless", "--output", "quiet", "--wipe", "relative.txt", variable, undefined])
+        .output()
+        .expect("BitBurn runs");
+    assert_eq!(output.status.code(), Some(1), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let result = &lines(&output)[0];
+    assert_eq!(result["report"]["files_wiped"], 2);
+    let failures = result["failures"].as_array().unwrap();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0]["message_code"], "undefined_variable");
+    assert_eq!(failures[0]["params"]["variable"], "BITBURN_UNDEFINED_VAR");
+    assert!(!dir.join("relative.txt").exists() && !dir.join("variable.txt").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -12,10 +12,18 @@ vi.mock("@tauri-apps/plugin-dialog", () => ({
   open: vi.fn(),
 }));
 
+// Handlers registered on `new Window(...)`, so a test can deliver a backend event.
+const { windowListeners } = vi.hoisted(() => ({
+  windowListeners: new Map<string, (event: { payload: unknown }) => void>(),
+}));
+
 vi.mock("@tauri-apps/api/window", () => {
   // Create a mock Window class that can be instantiated
   class MockWindow {
-    listen = vi.fn(() => Promise.resolve(() => {}));
+    listen = vi.fn((event: string, handler: (event: { payload: unknown }) => void) => {
+      windowListeners.set(event, handler);
+      return Promise.resolve(() => {});
+    });
     emit = vi.fn();
     onDragDropEvent = vi.fn(() => Promise.resolve(() => {}));
   }
@@ -40,6 +48,7 @@ describe("BitBurn UI Tests", () => {
   beforeEach(() => {
     vi.clearAllMocks();
     localStorage.clear();
+    windowListeners.clear();
 
     // Setup default mocks
     mockGetCurrent.mockReturnValue({
@@ -90,7 +99,10 @@ describe("BitBurn UI Tests", () => {
         screen.getByRole("option", { name: /NIST 800-88 Purge/i }),
       ).toBeInTheDocument();
       expect(
-        screen.getByRole("option", { name: /Gutmann/i }),
+        screen.getByRole("option", { name: /^Gutmann \(/i }),
+      ).toBeInTheDocument();
+      expect(
+        screen.getByRole("option", { name: /Gutmann Lite/i }),
       ).toBeInTheDocument();
       expect(
         screen.getByRole("option", { name: /Random/i }),
@@ -210,6 +222,17 @@ describe("BitBurn UI Tests", () => {
       });
     });
 
+    it("should display correct pass count for Gutmann Lite", async () => {
+      render(<App />);
+
+      const select = screen.getByRole("combobox");
+      await userEvent.selectOptions(select, "GutmannLite");
+
+      await waitFor(() => {
+        expect(screen.getByText("13")).toBeInTheDocument();
+      });
+    });
+
     it("should show input field for Random algorithm", async () => {
       render(<App />);
 
@@ -580,8 +603,9 @@ describe("BitBurn UI Tests", () => {
     it("should show confirmation and start wipe operation", async () => {
       mockOpen.mockResolvedValue(["C:\\test\\file1.txt"] as any);
       mockInvoke
+        .mockResolvedValueOnce([]) // get_active_operations
         .mockResolvedValueOnce(100) // get_file_size
-        .mockResolvedValueOnce(true) // confirmation
+        .mockResolvedValueOnce({ token: "consent-1", confirmed: true }) // confirmation
         .mockResolvedValueOnce({ success: true, message: "Wipe completed" }); // wipe result
 
       render(<App />);
@@ -618,8 +642,9 @@ describe("BitBurn UI Tests", () => {
       });
 
       mockInvoke
+        .mockResolvedValueOnce([]) // get_active_operations
         .mockResolvedValueOnce(100) // get_file_size
-        .mockResolvedValueOnce(true) // confirmation
+        .mockResolvedValueOnce({ token: "consent-1", confirmed: true }) // confirmation
         .mockReturnValueOnce(wipePromise as any); // wipe - keep pending
 
       render(<App />);
@@ -682,8 +707,9 @@ describe("BitBurn UI Tests", () => {
       });
 
       mockInvoke
+        .mockResolvedValueOnce([]) // get_active_operations
         .mockResolvedValueOnce(100) // get_file_size
-        .mockResolvedValueOnce(true) // confirmation
+        .mockResolvedValueOnce({ token: "consent-1", confirmed: true }) // confirmation
         .mockReturnValueOnce(wipePromise as any); // wipe result - keep pending
 
       // Setup mock AFTER clearAllMocks in beforeEach
@@ -760,8 +786,9 @@ describe("BitBurn UI Tests", () => {
       });
 
       mockInvoke
+        .mockResolvedValueOnce([]) // get_active_operations
         .mockResolvedValueOnce(100) // get_file_size
-        .mockResolvedValueOnce(true) // confirmation
+        .mockResolvedValueOnce({ token: "consent-1", confirmed: true }) // confirmation
         .mockReturnValueOnce(wipePromise as any); // wipe result
 
       // Setup mock AFTER clearAllMocks in beforeEach
@@ -836,8 +863,9 @@ describe("BitBurn UI Tests", () => {
     it("should display success message after successful wipe", async () => {
       mockOpen.mockResolvedValue(["C:\\test\\file1.txt"] as any);
       mockInvoke
+        .mockResolvedValueOnce([]) // get_active_operations
         .mockResolvedValueOnce(100) // get_file_size
-        .mockResolvedValueOnce(true) // confirmation
+        .mockResolvedValueOnce({ token: "consent-1", confirmed: true }) // confirmation
         .mockResolvedValueOnce({
           success: true,
           message: "Wipe completed successfully!",
@@ -875,8 +903,9 @@ describe("BitBurn UI Tests", () => {
     it("should display error message after failed wipe", async () => {
       mockOpen.mockResolvedValue(["C:\\test\\file1.txt"] as any);
       mockInvoke
+        .mockResolvedValueOnce([]) // get_active_operations
         .mockResolvedValueOnce(100) // get_file_size
-        .mockResolvedValueOnce(true) // confirmation
+        .mockResolvedValueOnce({ token: "consent-1", confirmed: true }) // confirmation
         .mockResolvedValueOnce({ success: false, message: "Wipe failed!" });
 
       render(<App />);
@@ -905,6 +934,35 @@ describe("BitBurn UI Tests", () => {
         { timeout: 3000 },
       );
     });
+
+    it("should describe a structured error a command rejects with", async () => {
+      mockOpen.mockResolvedValue(["C:\\test\\file1.txt"] as any);
+      mockInvoke
+        .mockResolvedValueOnce([]) // get_active_operations
+        .mockResolvedValueOnce(100) // get_file_size
+        .mockResolvedValueOnce({ token: "consent-1", confirmed: true }) // confirmation
+        .mockRejectedValueOnce({ code: "access_denied", path: "C:\\test\\file1.txt" });
+
+      render(<App />);
+
+      await userEvent.click(screen.getByText("Wipe Files/Folders"));
+      await userEvent.click(screen.getByText("Select Files"));
+      await waitFor(() => {
+        expect(
+          screen.getByText("Securely Wipe Selected Items"),
+        ).toBeInTheDocument();
+      });
+      await userEvent.click(screen.getByText("Securely Wipe Selected Items"));
+
+      await waitFor(() => {
+        expect(
+          screen.getByText(
+            /Access denied \(path: C:\\test\\file1\.txt\)/,
+          ),
+        ).toBeInTheDocument();
+      });
+      expect(screen.queryByText(/object Object/)).not.toBeInTheDocument();
+    });
   });
 
   describe("Free Space Wiping", () => {
@@ -927,8 +985,9 @@ describe("BitBurn UI Tests", () => {
     it("should validate drive path before wiping", async () => {
       mockOpen.mockResolvedValue("C:\\" as any);
       mockInvoke
+        .mockResolvedValueOnce([]) // get_active_operations
         .mockResolvedValueOnce({ success: true, message: "Valid drive" }) // validation
-        .mockResolvedValueOnce(true) // confirmation
+        .mockResolvedValueOnce({ token: "consent-1", confirmed: true }) // confirmation
         .mockResolvedValueOnce({ success: true, message: "Free space wiped" });
 
       render(<App />);
@@ -945,6 +1004,59 @@ describe("BitBurn UI Tests", () => {
     });
   });
 
+  describe("Free Space From a Folder", () => {
+    it("confirms the folder's volume and wipes it through free_space_wipe_for_path", async () => {
+      mockInvoke.mockImplementation(async (command: string) => {
+        switch (command) {
+          case "estimate_free_space_wipe":
+            return { token: "estimate-1", total_bytes: 1024, eta_min_seconds: 60, eta_max_seconds: 120 };
+          case "show_confirmation_dialog":
+            return { token: "consent-1", confirmed: true };
+          case "free_space_wipe_for_path":
+            return { success: true, message: "Free space wiped" };
+          default:
+            return undefined;
+        }
+      });
+
+      render(<App />);
+
+      await waitFor(() => {
+        expect(windowListeners.has("free_space_for_path_request")).toBe(true);
+      });
+      windowListeners.get("free_space_for_path_request")!({
+        payload: { path: "/data/reports", volume: "/data" },
+      });
+
+      await waitFor(() => {
+        expect(mockInvoke).toHaveBeenCalledWith(
+          "free_space_wipe_for_path",
+          expect.objectContaining({ path: "/data/reports", consentToken: "consent-1" }),
+        );
+      });
+      expect(mockInvoke).toHaveBeenCalledWith(
+        "show_confirmation_dialog",
+        expect.objectContaining({ path: "/data" }),
+      );
+      expect(mockInvoke).not.toHaveBeenCalledWith("execute_free_space_wipe", expect.anything());
+      expect(await screen.findByText(/Free space wiped/)).toBeInTheDocument();
+    });
+
+    it("reports a folder whose volume cannot be wiped without asking", async () => {
+      render(<App />);
+
+      await waitFor(() => {
+        expect(windowListeners.has("free_space_for_path_request")).toBe(true);
+      });
+      windowListeners.get("free_space_for_path_request")!({
+        payload: { path: "/net/share", error: { code: "not_drive_root", path: "/net/share" } },
+      });
+
+      expect(await screen.findByText(/Cannot wipe the free space for \/net\/share: not drive root/)).toBeInTheDocument();
+      expect(mockInvoke).not.toHaveBeenCalledWith("show_confirmation_dialog", expect.anything());
+    });
+  });
+
   describe("Cancel Operation", () => {
     it("should show cancel button during operation", async () => {
       mockOpen.mockResolvedValue(["C:\\test\\file1.txt"] as any);
@@ -963,8 +1075,9 @@ describe("BitBurn UI Tests", () => {
       });
 
       mockInvoke
+        .mockResolvedValueOnce([]) // get_active_operations
         .mockResolvedValueOnce(100) // get_file_size
-        .mockResolvedValueOnce(true) // confirmation
+        .mockResolvedValueOnce({ token: "consent-1", confirmed: true }) // confirmation
         .mockReturnValueOnce(wipePromise as any); // wipe result - keep pending
 
       // Setup mock AFTER clearAllMocks in beforeEach
@@ -1023,6 +1136,104 @@ describe("BitBurn UI Tests", () => {
     });
   });
 
+  describe("Reload Recovery", () => {
+    const running = {
+      operation_id: "op-1",
+      kind: "wipe_files",
+      started_at: 1,
+      current_phase: "zeros",
+      overall_percentage: 40,
+      target_summary: "C:\\test\\file1.txt",
+    };
+    const progress = {
+      current_pass: 2,
+      total_passes: 3,
+      bytes_processed: 500,
+      total_bytes: 1000,
+      current_algorithm: "NistPurge",
+      current_pattern: "zeros",
+      percentage: 50,
+    };
+
+    it("should reattach to a running operation and cancel it by id", async () => {
+      mockInvoke.mockImplementation(async (command: string) => {
+        switch (command) {
+          case "get_active_operations":
+            return [running];
+          case "get_interrupted_operations":
+            return [];
+          default:
+            return undefined;
+        }
+      });
+
+      render(<App />);
+
+      await waitFor(() => {
+        expect(windowListeners.has("wipe_progress")).toBe(true);
+      });
+      // The reattached page hides the mode picker while the wipe runs.
+      await waitFor(() => {
+        expect(screen.queryByText("Wipe Files/Folders")).not.toBeInTheDocument();
+      });
+      windowListeners.get("wipe_progress")!({ payload: progress });
+
+      const cancelButton = await screen.findByText("Cancel Operation");
+      expect(cancelButton).toBeEnabled();
+      expect(screen.getByText("NistPurge")).toBeInTheDocument();
+
+      await userEvent.click(cancelButton);
+
+      expect(mockInvoke).toHaveBeenCalledWith("cancel_operation", {
+        operationId: "op-1",
+      });
+      expect(await screen.findByText(/Operation cancelled by user/)).toBeInTheDocument();
+      expect(screen.queryByText("Cancel Operation")).not.toBeInTheDocument();
+    });
+
+    it("should not cancel before the backend has registered the wipe", async () => {
+      mockOpen.mockResolvedValue(["C:\\test\\file1.txt"] as any);
+      mockInvoke.mockImplementation(async (command: string) => {
+        switch (command) {
+          case "get_active_operations":
+          case "get_interrupted_operations":
+            return [];
+          case "get_file_size":
+            return 100;
+          case "show_confirmation_dialog":
+            return { token: "consent-1", confirmed: true };
+          case "wipe_files":
+            return new Promise(() => {});
+          default:
+            return undefined;
+        }
+      });
+
+      render(<App />);
+
+      await userEvent.click(screen.getByText("Wipe Files/Folders"));
+      await userEvent.click(screen.getByText("Select Files"));
+      await userEvent.click(await screen.findByText("Securely Wipe Selected Items"));
+
+      await waitFor(() => {
+        expect(mockInvoke).toHaveBeenCalledWith("wipe_files", expect.anything());
+      });
+      windowListeners.get("wipe_progress")!({ payload: progress });
+
+      const cancelButton = await screen.findByText("Cancel Operation");
+      expect(cancelButton).toBeDisabled();
+      fireEvent.click(cancelButton);
+
+      expect(mockInvoke).not.toHaveBeenCalledWith("cancel_operation", expect.anything());
+      expect(screen.getByText("NistPurge")).toBeInTheDocument();
+
+      windowListeners.get("operation_registered")!({ payload: { ...running, operation_id: "op-2" } });
+      await waitFor(() => {
+        expect(screen.getByText("Cancel Operation")).toBeEnabled();
+      });
+    });
+  });
+
   describe("Accessibility", () => {
     it("should have proper labels for form controls", () => {
       render(<App />);
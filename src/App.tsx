@@ -1,6 +1,6 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { open } from "@tauri-apps/plugin-dialog";
+import { ask, open } from "@tauri-apps/plugin-dialog";
 import { Window } from "@tauri-apps/api/window";
 import { Event } from "@tauri-apps/api/event";
 
@@ -20,23 +20,198 @@ interface WipeProgress {
   current_pattern: string;
   percentage: number;
   estimated_total_bytes?: number;
+  bytes_per_second?: number;
+  estimated_seconds_remaining?: number;
+  // Present while a folder is being walked.
+  current_directory?: string;
+  directory_files_done?: number;
+  directory_files_total?: number;
+  files_done?: number;
+  files_total?: number;
+  tree_changed?: boolean;
+  // Which selected path a file batch is on, counting from 0.
+  root_index?: number;
+  // Set on the event where a free-space fill revised how much it expects to write.
+  estimate_adjusted?: { previous_bytes: number; estimate_bytes: number };
+}
+
+// The answer to show_confirmation_dialog; the token proves it to the wipe commands.
+interface Consent {
+  token: string;
+  confirmed: boolean;
+}
+
+interface OperationSnapshot {
+  operation_id: string;
+  kind: "wipe_files" | "free_space_wipe";
+  started_at: number;
+  current_phase: string;
+  overall_percentage: number;
+  target_summary: string;
+}
+
+interface PartialWipe {
+  path: string;
+  bytes_overwritten: number;
+  last_pass: number;
+}
+
+// The parts of a wipe report that link an operation to its failures and retries.
+interface WipeReportLinks {
+  operation_id?: string;
+  retry_of?: string;
+  retried_by?: string[];
+  failures?: { code: string }[];
+  skipped?: { code: string }[];
+}
+
+interface InterruptedOperation {
+  operation_id: string;
+  started_at: number;
+  updated_at: number;
+  algorithm: string;
+  passes: number;
+  total: number;
+  completed: number;
+  remaining: string[];
+  // Files the crash left under the random name they were given to be deleted.
+  left_renamed?: { path: string; on_disk_path: string }[];
 }
 
 interface ContextWipePayload {
   paths: string[];
   invalid: string[];
   source: string;
+  volume_roots?: string[];
+  elevation_mismatch?: ElevationMismatch | null;
+  token?: string | null;
+}
+
+// Refusal from a command whose native dialog could not be shown; the window asks `prompt` instead
+// and answers it by `nonce`, the backend's record of what the prompt was for.
+interface DialogUnavailable {
+  code: "dialog_unavailable";
+  reason: string;
+  prompt: string;
+  nonce: string;
+}
+
+const isDialogUnavailable = (error: unknown): error is DialogUnavailable =>
+  (error as { code?: string } | null)?.code === "dialog_unavailable";
+
+// Commands reject with a serialized `BitBurnError`: its `code` and structured fields, and a
+// `message` only for some codes. Turns one, or anything else thrown, into text for the result.
+const describeError = (error: unknown): string => {
+  if (typeof error === "string") return error;
+  if (error instanceof Error) return error.message;
+  const { code, message, ...fields } = (error ?? {}) as {
+    code?: string;
+    message?: string;
+    [field: string]: unknown;
+  };
+  if (!code) return String(error);
+  const words = code.replace(/_/g, " ");
+  const summary = message ?? words.charAt(0).toUpperCase() + words.slice(1);
+  const details = Object.entries(fields)
+    .filter(([, value]) => value !== null && value !== undefined && value !== "")
+    .map(([name, value]) => {
+      const text = Array.isArray(value)
+        ? value.join(", ")
+        : typeof value === "object"
+          ? JSON.stringify(value)
+          : String(value);
+      return `${name.replace(/_/g, " ")}: ${text}`;
+    });
+  return details.length > 0 ? `${summary} (${details.join("; ")})` : summary;
+};
+
+// Refusal from a command whose own confirmation prompt the user answered No to.
+const isDeclined = (error: unknown) => {
+  const refusal = error as { code?: string; reason?: string } | null;
+  return refusal?.code === "confirmation_required" && refusal.reason === "the wipe was declined";
+};
+
+interface ContextWipeRun {
+  status: "completed" | "declined" | "nothing_to_wipe";
+  invalid: string[];
+  result?: { success?: boolean; message?: string };
+}
+
+interface LaunchContext {
+  elevated: boolean;
+  uid: number | null;
+}
+
+interface ElevationMismatch {
+  sender: LaunchContext;
+  receiver: LaunchContext;
+}
+
+const describeLaunchContext = (context: LaunchContext) =>
+  (context.elevated ? "elevated" : "standard") +
+  (context.uid !== null ? ` (uid ${context.uid})` : "");
+
+interface FreeSpaceEstimate {
+  token: string;
+  total_bytes: number;
+  eta_min_seconds: number;
+  eta_max_seconds: number;
+  // The SSD would pass its 30-day write warning level with this wipe.
+  wear_warning?: { message: string };
+}
+
+// A folder the file manager asked to have its volume's free space wiped; `error` says why not.
+interface FreeSpaceRequest {
+  path: string;
+  volume?: string;
+  error?: { code: string };
+}
+
+interface EffectivePolicy {
+  source: string | null;
+  policy: {
+    disabled_commands: string[];
+    allowed_algorithms: string[] | null;
+    min_passes: number | null;
+    removable_targets_only: boolean;
+    locked_settings: string[];
+  };
+  // Set when the policy file exists but is unusable; every wipe is then blocked.
+  error?: string;
 }
 
 const MAX_FILE_SIZE = 1024 * 1024 * 1024 * 10; // 10GB warning threshold
 
+const formatBytes = (bytes: number) => {
+  const units = ["B", "KB", "MB", "GB", "TB", "PB"];
+  let value = bytes;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit += 1;
+  }
+  return `${value.toFixed(unit === 0 ? 0 : 1)} ${units[unit]}`;
+};
+
+const formatHours = (seconds: number) =>
+  seconds < 3600 ? `${Math.max(1, Math.round(seconds / 60))} min` : `${Math.round(seconds / 3600)} h`;
+
 function App() {
   const [selectedPaths, setSelectedPaths] = useState<string[]>([]);
   const [passes, setPasses] = useState<number>(3);
   const [algorithm, setAlgorithm] = useState<
-    "NistClear" | "NistPurge" | "Gutmann" | "Random"
+    "NistClear" | "NistPurge" | "Gutmann" | "GutmannLite" | "Random"
   >("NistPurge");
   const [isWiping, setIsWiping] = useState(false);
+  // Backend recommendation for the current selection, applied as the default algorithm.
+  const [recommendation, setRecommendation] = useState<{
+    algorithm: typeof algorithm;
+    passes: number;
+    reasons: string[];
+    warnings: string[];
+  } | null>(null);
+  // Machine policy set by an administrator; blocked commands and algorithms are greyed out.
+  const [policy, setPolicy] = useState<EffectivePolicy | null>(null);
   const [result, setResult] = useState<{
     success: boolean;
     message: string;
@@ -49,10 +224,57 @@ function App() {
   const [operationMode, setOperationMode] = useState<
     "initial" | "files" | "freespace"
   >("initial");
-  const [abortController, setAbortController] =
-    useState<AbortController | null>(null);
   const [isContextMode, setIsContextMode] = useState(false);
   const [contextInvalidPaths, setContextInvalidPaths] = useState<string[]>([]);
+  const [contextVolumeRoots, setContextVolumeRoots] = useState<string[]>([]);
+  // Token and paths of the context-menu selection shown, so an unchanged selection runs as one backend call.
+  const [contextPayload, setContextPayload] = useState<{
+    token: string;
+    paths: string[];
+  } | null>(null);
+  const [contextElevationMismatch, setContextElevationMismatch] =
+    useState<ElevationMismatch | null>(null);
+  const [activeOperationId, setActiveOperationId] = useState<string | null>(
+    null,
+  );
+  // Set when the backend reopened the window for a finished wipe; the result gets focus once shown.
+  const [focusResults, setFocusResults] = useState(false);
+  // Set when this page picked up an operation started before a reload, so no
+  // pending invoke will report its result.
+  const [reattached, setReattached] = useState(false);
+  // Files a cancelled or failed wipe left half-overwritten.
+  const [partialWipes, setPartialWipes] = useState<PartialWipe[]>([]);
+  // The last file wipe that left failed or in-use files, which `retry_failed` can wipe again.
+  const [retryable, setRetryable] = useState<{ operationId: string; failures: number } | null>(null);
+  // Targets on USB flash drives or memory cards, where overwriting may not reach every copy.
+  const [flashWarning, setFlashWarning] = useState<{
+    paths: string[];
+    message: string;
+  } | null>(null);
+  // Batches a crash or forced quit left unfinished, found in their journals at startup.
+  const [interruptedOps, setInterruptedOps] = useState<InterruptedOperation[]>(
+    [],
+  );
+  const [deleteShadowCopies, setDeleteShadowCopies] = useState(false);
+  // Empty the selected folders but leave the folders themselves in place.
+  const [preserveRoots, setPreserveRoots] = useState(false);
+  const [companionFiles, setCompanionFiles] = useState(false);
+  // Recall and wipe files stored in OneDrive or an archive tier instead of refusing them.
+  const [allowRecall, setAllowRecall] = useState(false);
+  // Volume root the backend refused to walk as a folder, waiting for the typed confirmation.
+  const [volumeRootConfirm, setVolumeRootConfirm] = useState<string | null>(null);
+  const [volumeRootTyped, setVolumeRootTyped] = useState("");
+  // Volume roots already confirmed for the wipe being retried; each is confirmed on its own.
+  const [confirmedVolumeRoots, setConfirmedVolumeRoots] = useState<string[]>([]);
+  // Token from the last confirmation dialog, reused when the wipe is retried after a refusal.
+  const [consentToken, setConsentToken] = useState<string | null>(null);
+  // A free-space wipe the file manager asked for, handled once the current settings are in scope.
+  const [freeSpaceRequest, setFreeSpaceRequest] = useState<FreeSpaceRequest | null>(null);
+  // Confirmation asked in the window because the native dialog could not be shown.
+  const [windowConfirm, setWindowConfirm] = useState<{
+    prompt: string;
+    answer: (confirmed: boolean) => void;
+  } | null>(null);
 
   useEffect(() => {
     document.documentElement.setAttribute("data-theme", theme);
@@ -104,6 +326,59 @@ function App() {
     };
   }, [isWiping]);
 
+  useEffect(() => {
+    let unlistenPolicy: (() => void) | undefined;
+
+    async function setupPolicy() {
+      const window = new Window("main");
+      unlistenPolicy = await window.listen<EffectivePolicy>(
+        "policy_changed",
+        (event: Event<EffectivePolicy>) => {
+          setPolicy(event.payload);
+        },
+      );
+      try {
+        setPolicy(await invoke<EffectivePolicy>("get_effective_policy"));
+      } catch (error) {
+        console.error("Failed to load policy:", error);
+      }
+    }
+
+    setupPolicy();
+    return () => {
+      if (unlistenPolicy) {
+        unlistenPolicy();
+      }
+    };
+  }, []);
+
+  // A policy file that cannot be read blocks everything, so the UI does too.
+  const commandBlocked = (command: string) =>
+    !!policy?.error || !!policy?.policy.disabled_commands.includes(command);
+  const algorithmBlocked = (candidate: typeof algorithm) =>
+    !!policy?.policy.allowed_algorithms &&
+    !policy.policy.allowed_algorithms.includes(candidate);
+
+  useEffect(() => {
+    if (selectedPaths.length === 0) {
+      setRecommendation(null);
+      return;
+    }
+    let stale = false;
+    invoke<NonNullable<typeof recommendation>>("recommend_algorithm", {
+      paths: selectedPaths,
+    })
+      .then((recommended) => {
+        if (stale) return;
+        setRecommendation(recommended);
+        setAlgorithm(recommended.algorithm);
+      })
+      .catch((error) => console.error("Algorithm recommendation failed:", error));
+    return () => {
+      stale = true;
+    };
+  }, [selectedPaths]);
+
   useEffect(() => {
     switch (algorithm) {
       case "NistClear":
@@ -115,6 +390,9 @@ function App() {
       case "Gutmann":
         setPasses(35);
         break;
+      case "GutmannLite":
+        setPasses(13);
+        break;
       // For Random, keep the user-selected value
     }
   }, [algorithm]);
@@ -141,8 +419,239 @@ function App() {
     };
   }, []);
 
+  useEffect(() => {
+    let unlistenRegistered: (() => void) | undefined;
+    let unlistenFinished: (() => void) | undefined;
+
+    async function trackOperations() {
+      const window = new Window("main");
+      unlistenRegistered = await window.listen<OperationSnapshot>(
+        "operation_registered",
+        (event: Event<OperationSnapshot>) => {
+          setActiveOperationId(event.payload.operation_id);
+        },
+      );
+      unlistenFinished = await window.listen<OperationSnapshot>(
+        "operation_finished",
+        (event: Event<OperationSnapshot>) => {
+          setActiveOperationId((current) =>
+            current === event.payload.operation_id ? null : current,
+          );
+        },
+      );
+
+      // The webview may have reloaded mid-wipe; the backend keeps running, so
+      // pick the operation back up and let progress events repaint it.
+      try {
+        const active = await invoke<OperationSnapshot[]>(
+          "get_active_operations",
+        );
+        if (Array.isArray(active) && active.length > 0) {
+          const latest = active[active.length - 1];
+          setActiveOperationId(latest.operation_id);
+          setOperationMode(
+            latest.kind === "free_space_wipe" ? "freespace" : "files",
+          );
+          setReattached(true);
+          setIsWiping(true);
+        }
+      } catch (error) {
+        console.error("Error restoring active operations:", error);
+      }
+    }
+
+    trackOperations();
+
+    return () => {
+      if (unlistenRegistered) {
+        unlistenRegistered();
+      }
+      if (unlistenFinished) {
+        unlistenFinished();
+      }
+    };
+  }, []);
+
+  useEffect(() => {
+    let unlistenPartial: (() => void) | undefined;
+
+    async function setupPartialListener() {
+      const window = new Window("main");
+      unlistenPartial = await window.listen<{
+        operation_id: string;
+        partially_wiped: PartialWipe[];
+      }>("partial_wipe_detected", (event) => {
+        setPartialWipes(event.payload.partially_wiped);
+      });
+    }
+
+    setupPartialListener();
+
+    return () => {
+      if (unlistenPartial) {
+        unlistenPartial();
+      }
+    };
+  }, []);
+
+  useEffect(() => {
+    let unlistenFlash: (() => void) | undefined;
+
+    async function setupFlashListener() {
+      const window = new Window("main");
+      unlistenFlash = await window.listen<{
+        operation_id: string;
+        paths: string[];
+        recommendation: { message: string };
+      }>("flash_media_warning", (event) => {
+        setFlashWarning({
+          paths: event.payload.paths,
+          message: event.payload.recommendation.message,
+        });
+      });
+    }
+
+    setupFlashListener();
+
+    return () => {
+      if (unlistenFlash) {
+        unlistenFlash();
+      }
+    };
+  }, []);
+
+  useEffect(() => {
+    let unlistenStalled: (() => void) | undefined;
+
+    async function setupStallListener() {
+      const window = new Window("main");
+      unlistenStalled = await window.listen<{
+        operation_id: string;
+        path: string;
+        seconds: number;
+      }>("wipe_stalled", (event) => {
+        setResult({
+          success: false,
+          message: `No write progress on ${event.payload.path} for ${event.payload.seconds}s; the drive may be failing.`,
+        });
+      });
+    }
+
+    setupStallListener();
+
+    return () => {
+      if (unlistenStalled) {
+        unlistenStalled();
+      }
+    };
+  }, []);
+
+  useEffect(() => {
+    let unlistenVolumeLost: (() => void) | undefined;
+
+    async function setupVolumeLostListener() {
+      const window = new Window("main");
+      unlistenVolumeLost = await window.listen<{
+        operation_id: string;
+        volume: string;
+        message: string;
+      }>("volume_lost", (event) => {
+        setResult({
+          success: false,
+          message: `${event.payload.volume} was disconnected during the wipe (${event.payload.message}). Nothing more on it is being wiped; reconnect it and wipe the remaining items again.`,
+        });
+      });
+    }
+
+    setupVolumeLostListener();
+
+    return () => {
+      if (unlistenVolumeLost) {
+        unlistenVolumeLost();
+      }
+    };
+  }, []);
+
+  useEffect(() => {
+    let unlistenShowResults: (() => void) | undefined;
+
+    async function setupShowResultsListener() {
+      const window = new Window("main");
+      unlistenShowResults = await window.listen<{ operation_id?: string }>("show_results", () => {
+        setFocusResults(true);
+      });
+    }
+
+    setupShowResultsListener();
+
+    return () => {
+      if (unlistenShowResults) {
+        unlistenShowResults();
+      }
+    };
+  }, []);
+
+  useEffect(() => {
+    if (focusResults && result) {
+      document.getElementById("wipe-result")?.focus();
+      setFocusResults(false);
+    }
+  }, [focusResults, result]);
+
+  useEffect(() => {
+    let unlistenEmergency: (() => void) | undefined;
+
+    async function setupEmergencyListener() {
+      const window = new Window("main");
+      unlistenEmergency = await window.listen<{
+        operations: (OperationSnapshot & {
+          stopped: boolean;
+          partially_wiped: PartialWipe[];
+        })[];
+      }>("emergency_stop", (event) => {
+        const { operations } = event.payload;
+        const stillStopping = operations.filter((op) => !op.stopped).length;
+        setPartialWipes(operations.flatMap((op) => op.partially_wiped));
+        setResult({
+          success: false,
+          message:
+            operations.length === 0
+              ? "Emergency stop: nothing was running."
+              : `Emergency stop: cancelled ${operations.length} operation(s)` +
+                (stillStopping > 0
+                  ? `; ${stillStopping} still winding down.`
+                  : "."),
+        });
+      });
+    }
+
+    setupEmergencyListener();
+
+    return () => {
+      if (unlistenEmergency) {
+        unlistenEmergency();
+      }
+    };
+  }, []);
+
+  useEffect(() => {
+    invoke("get_interrupted_operations")
+      .then((ops) => setInterruptedOps(ops as InterruptedOperation[]))
+      .catch((error) =>
+        console.error("Error loading interrupted operations:", error),
+      );
+  }, []);
+
+  useEffect(() => {
+    if (reattached && activeOperationId === null) {
+      setReattached(false);
+      showResult(true, "Operation finished");
+    }
+  }, [reattached, activeOperationId]);
+
   useEffect(() => {
     let unlistenContext: (() => void) | undefined;
+    let unlistenInvocation: (() => void) | undefined;
 
     const setupContextListener = async () => {
       try {
@@ -158,8 +667,9 @@ function App() {
               setIsContextMode(false);
               setResult({
                 success: false,
-                message:
-                  "No valid local files or folders were provided from the context menu",
+                message: payload.elevation_mismatch
+                  ? `None of the selected items are reachable from this BitBurn, which runs ${describeLaunchContext(payload.elevation_mismatch.receiver)} while the request came from a ${describeLaunchContext(payload.elevation_mismatch.sender)} session`
+                  : "No valid local files or folders were provided from the context menu",
               });
               setTimeout(() => setResult(null), 3000);
               return;
@@ -167,12 +677,38 @@ function App() {
 
             setSelectedPaths(unique);
             setContextInvalidPaths(payload.invalid || []);
+            setContextVolumeRoots(payload.volume_roots || []);
+            setContextPayload(
+              payload.token ? { token: payload.token, paths: unique } : null,
+            );
+            setContextElevationMismatch(payload.elevation_mismatch || null);
             setOperationMode("files");
             setIsContextMode(true);
             setIsWiping(false);
             setResult(null);
           },
         );
+        // Shown while the forwarded paths are still being checked; the request replaces it.
+        const received = await window.listen<{ raw_count: number }>(
+          "context_invocation_received",
+          (event) =>
+            setResult({
+              success: true,
+              message: `Checking ${event.payload.raw_count} item(s) from the context menu...`,
+            }),
+        );
+        const progress = await window.listen<{ processed: number; raw_count: number }>(
+          "context_sanitize_progress",
+          (event) =>
+            setResult({
+              success: true,
+              message: `Checked ${event.payload.processed} of ${event.payload.raw_count} item(s) from the context menu...`,
+            }),
+        );
+        unlistenInvocation = () => {
+          received();
+          progress();
+        };
       } catch (error) {
         console.error("Error setting up context listener:", error);
       }
@@ -184,9 +720,38 @@ function App() {
       if (unlistenContext) {
         unlistenContext();
       }
+      if (unlistenInvocation) {
+        unlistenInvocation();
+      }
     };
   }, []);
 
+  useEffect(() => {
+    let unlistenFreeSpace: (() => void) | undefined;
+
+    const setupFreeSpaceListener = async () => {
+      const window = new Window("main");
+      unlistenFreeSpace = await window.listen<FreeSpaceRequest>(
+        "free_space_for_path_request",
+        (event) => setFreeSpaceRequest(event.payload),
+      );
+    };
+
+    setupFreeSpaceListener();
+
+    return () => {
+      if (unlistenFreeSpace) {
+        unlistenFreeSpace();
+      }
+    };
+  }, []);
+
+  useEffect(() => {
+    if (!freeSpaceRequest) return;
+    setFreeSpaceRequest(null);
+    handleFreeSpaceRequest(freeSpaceRequest);
+  }, [freeSpaceRequest]);
+
   const handleFileSelect = async () => {
     try {
       const selected = await open({
@@ -235,7 +800,7 @@ function App() {
       }
     } catch (error) {
       console.error("Error selecting files:", error);
-      showResult(false, `Error selecting files: ${error}`);
+      showResult(false, `Error selecting files: ${describeError(error)}`);
     }
   };
 
@@ -279,7 +844,7 @@ function App() {
       }
     } catch (error) {
       console.error("Error selecting folders:", error);
-      showResult(false, `Error selecting folders: ${error}`);
+      showResult(false, `Error selecting folders: ${describeError(error)}`);
     }
   };
 
@@ -290,7 +855,9 @@ function App() {
       case "NistPurge":
         return "NIST 800-88 Purge method - 3 passes with zeros, ones, and random data (Recommended)";
       case "Gutmann":
-        return "Peter Gutmann's 35-pass algorithm - Maximum security for magnetic media (Very slow)";
+        return "Peter Gutmann's 35-pass algorithm - Only meaningful for 1990s-era MFM/RLL drives (Very slow)";
+      case "GutmannLite":
+        return "Gutmann's random and 0x55/0xAA/0x92-series passes (13 passes) - Drops the encoding-specific passes no modern drive needs; prefer it over full Gutmann";
       case "Random":
         return `${passes} passes of cryptographically secure random data (Custom)`;
     }
@@ -306,43 +873,191 @@ function App() {
       setSelectedPaths([]);
       setOperationMode("initial");
       setWipeProgress(null);
-      setAbortController(null);
       setIsContextMode(false);
       setContextInvalidPaths([]);
+      setContextVolumeRoots([]);
+      setContextElevationMismatch(null);
+      setContextPayload(null);
     }, 3000);
   };
 
+  // Ask the refused dialog's prompt in the window and record the answer as the backend records a dialog's.
+  const confirmInWindow = async ({ prompt, nonce }: DialogUnavailable) => {
+    const confirmed = await new Promise<boolean>((answer) =>
+      setWindowConfirm({ prompt, answer }),
+    );
+    setWindowConfirm(null);
+    return (await invoke("record_webview_confirmation", {
+      nonce,
+      confirmed,
+    })) as Consent;
+  };
+
+  // Run a command that confirms the targets it works out itself. Where its dialog cannot be shown
+  // the window asks instead and the command runs again with that answer; `null` when declined.
+  const withTargetConsent = async <T,>(
+    run: (consentToken?: string) => Promise<T>,
+  ): Promise<T | null> => {
+    try {
+      return await run();
+    } catch (error) {
+      if (isDeclined(error)) return null;
+      if (!isDialogUnavailable(error)) throw error;
+      const consent = await confirmInWindow(error);
+      return consent.confirmed ? run(consent.token) : null;
+    }
+  };
+
+  // The native confirmation dialog, or the window's own where it cannot be shown.
+  const confirmWipe = async (
+    path: string,
+    description: string,
+  ): Promise<Consent> => {
+    try {
+      return (await invoke("show_confirmation_dialog", {
+        path,
+        algorithm,
+        description,
+      })) as Consent;
+    } catch (error) {
+      if (!isDialogUnavailable(error)) throw error;
+      return confirmInWindow(error);
+    }
+  };
+
+  // The backend estimates, confirms and wipes the buffered selection itself.
+  const runContextWipe = async (token: string) => {
+    setIsContextMode(false);
+    setResult(null);
+    setWipeProgress(null);
+    setContextPayload(null);
+    setIsWiping(true);
+    const request = { payloadToken: token, algorithm, passes };
+    let run: ContextWipeRun;
+    try {
+      run = (await invoke("run_context_wipe", request)) as ContextWipeRun;
+    } catch (error) {
+      if (!isDialogUnavailable(error)) throw error;
+      // The selection stays buffered under the same token for the retry.
+      const consent = await confirmInWindow(error);
+      if (!consent.confirmed) {
+        showResult(false, "Operation cancelled by user");
+        return;
+      }
+      run = (await invoke("run_context_wipe", {
+        ...request,
+        consentToken: consent.token,
+      })) as ContextWipeRun;
+    }
+    setIsWiping(false);
+    if (run.status === "declined") {
+      showResult(false, "Operation cancelled by user");
+    } else if (run.status === "nothing_to_wipe") {
+      showResult(false, run.invalid.join("\n") || "Nothing left to wipe");
+    } else {
+      showResult(
+        run.result?.success ?? false,
+        run.result?.message ?? "No response from wipe operation",
+      );
+    }
+  };
+
   const handleWipe = async () => {
     if (selectedPaths.length === 0) return;
 
     try {
+      const unchangedContextSelection =
+        isContextMode &&
+        contextPayload !== null &&
+        contextVolumeRoots.length === 0 &&
+        selectedPaths.length === contextPayload.paths.length &&
+        selectedPaths.every((path) => contextPayload.paths.includes(path));
+      if (unchangedContextSelection) {
+        await runContextWipe(contextPayload.token);
+        return;
+      }
+
       setIsContextMode(false);
       setResult(null);
       setWipeProgress(null);
 
-      const confirmed = await invoke("show_confirmation_dialog", {
-        path: selectedPaths.join("\n"),
-        algorithm,
-        description: getAlgorithmDescription(),
-      });
+      // Timing a small sample on the target volume is best-effort; the dialog works without it.
+      const calibration = (await invoke("calibrate_algorithms", {
+        path: selectedPaths[0],
+        paths: selectedPaths,
+        passes,
+      }).catch(() => null)) as { summary: string } | null;
+
+      const consent = await confirmWipe(
+        selectedPaths.join("\n"),
+        calibration
+          ? `${getAlgorithmDescription()}\n\nEstimated time: ${calibration.summary}`
+          : getAlgorithmDescription(),
+      );
 
-      if (!confirmed) {
+      if (!consent.confirmed) {
         showResult(false, "Operation cancelled by user");
         return;
       }
 
+      setConsentToken(consent.token);
+      await runFileWipe({ consent_token: consent.token });
+    } catch (error) {
+      console.error("Error during wipe operation:", error);
+      showResult(false, `Error during wipe operation: ${describeError(error)}`);
+    }
+  };
+
+  // Start wipe_files and answer the pre-flight refusals that need another decision from the user.
+  const runFileWipe = async (confirmation: {
+    consent_token?: string;
+    volume_roots?: string[];
+  }) => {
+    try {
       setIsWiping(true);
-      const controller = new AbortController();
-      setAbortController(controller);
 
-      const result = await invoke("wipe_files", {
+      let result = await invoke("wipe_files", {
         paths: selectedPaths,
         passes,
         algorithm,
+        choices: { preserve_roots: preserveRoots, companion_files: companionFiles, allow_recall: allowRecall },
+        confirmation,
       });
 
+      // The pre-flight scan found files held open by other programs; nothing was overwritten yet.
+      const locked = (result as { error?: { code: string; paths?: string[] } })?.error;
+      if (locked?.code === "files_locked") {
+        const lockedPaths = locked.paths ?? [];
+        const skip = await ask(
+          `${lockedPaths.length} file(s) are in use by other programs:\n\n${lockedPaths
+            .slice(0, 10)
+            .join("\n")}\n\nSkip them and wipe the rest?`,
+          { title: "Files in use", kind: "warning", okLabel: "Skip locked", cancelLabel: "Don't skip" },
+        );
+        // Declining both ends the wipe; nothing has been overwritten yet.
+        const wait =
+          !skip &&
+          (await ask("Wait for the files to be closed, then wipe everything?", {
+            title: "Files in use",
+            kind: "warning",
+            okLabel: "Wait and retry",
+            cancelLabel: "Cancel wipe",
+          }));
+        if (!skip && !wait) {
+          showResult(false, "Operation cancelled by user");
+          return;
+        }
+        result = await invoke("wipe_files", {
+          paths: selectedPaths,
+          passes,
+          algorithm,
+          choices: { preserve_roots: preserveRoots, companion_files: companionFiles, allow_recall: allowRecall },
+          confirmation,
+          lockPolicy: skip ? "skip_locked" : "wait_and_retry",
+        });
+      }
+
       setIsWiping(false);
-      setAbortController(null);
 
       // Guard against undefined result
       if (!result) {
@@ -350,16 +1065,75 @@ function App() {
         return;
       }
 
-      const { success = false, message = "Unknown error" } = result as {
+      // A selected folder is a whole volume; only the typed confirmation lets it through.
+      const refused = (result as { error?: { code: string; path?: string } })?.error;
+      if (refused?.code === "volume_root" && refused.path) {
+        setVolumeRootTyped("");
+        setConfirmedVolumeRoots(confirmation.volume_roots ?? []);
+        setVolumeRootConfirm(refused.path);
+        return;
+      }
+
+      const { success = false, message = "Unknown error", report } = result as {
         success?: boolean;
         message?: string;
+        report?: WipeReportLinks;
       };
+      noteRetryable(report);
       showResult(success, message);
     } catch (error) {
       console.error("Error during wipe operation:", error);
       setIsWiping(false);
-      setAbortController(null);
-      showResult(false, `Error during wipe operation: ${error}`);
+      showResult(false, `Error during wipe operation: ${describeError(error)}`);
+    }
+  };
+
+  const handleConfirmVolumeRoot = async () => {
+    const root = volumeRootConfirm;
+    if (!root) return;
+    setVolumeRootConfirm(null);
+    setVolumeRootTyped("");
+    await runFileWipe({
+      consent_token: consentToken ?? undefined,
+      volume_roots: [...confirmedVolumeRoots, root],
+    });
+  };
+
+  const handleSelfTest = async () => {
+    try {
+      setResult(null);
+      const selected = await open({
+        directory: true,
+        multiple: false,
+        title: "Select Volume to Self-Test",
+        defaultPath: navigator.userAgent.includes("Windows") ? "C:\\" : "/",
+        buttonLabel: "Run Self-Test",
+      });
+      if (!selected) return;
+
+      setIsWiping(true);
+      const report = (await invoke("run_self_test", {
+        targetVolume: selected as string,
+      })) as {
+        passed: boolean;
+        bytes_per_second: number;
+        summary: { message: string };
+        checks: { name: string; status: string; message?: { message: string } }[];
+      };
+      const failures = report.checks
+        .filter((check) => check.status === "failed")
+        .map((check) => `${check.name}: ${check.message?.message ?? "failed"}`);
+      showResult(
+        report.passed,
+        [
+          report.summary.message,
+          `Throughput: ${formatBytes(report.bytes_per_second)}/s`,
+          ...failures,
+        ].join("\n"),
+      );
+    } catch (error) {
+      console.error("Error during self-test:", error);
+      showResult(false, `Error during self-test: ${describeError(error)}`);
     }
   };
 
@@ -373,7 +1147,8 @@ function App() {
         directory: true,
         multiple: false,
         title: "Select Drive to Wipe Free Space",
-        defaultPath: "C:\\",
+        // A drive root on Windows; elsewhere any mount point is accepted.
+        defaultPath: navigator.userAgent.includes("Windows") ? "C:\\" : "/",
         buttonLabel: "Select Drive",
       });
 
@@ -394,47 +1169,120 @@ function App() {
         return;
       }
 
-      const confirmed = await invoke("show_confirmation_dialog", {
-        path,
-        algorithm,
-        description: getAlgorithmDescription(),
-      });
+      await wipeFreeSpace(path);
+    } catch (error) {
+      console.error("Error during free space wipe:", error);
+      setIsWiping(false);
+      showResult(false, `Error during free space wipe: ${describeError(error)}`);
+    }
+  };
 
-      if (!confirmed) {
-        showResult(false, "Operation cancelled by user");
-        return;
-      }
+  // A folder's volume, sent from the file manager: confirmed and wiped like a picked drive.
+  const handleFreeSpaceRequest = async (request: FreeSpaceRequest) => {
+    setResult(null);
+    setWipeProgress(null);
+    setOperationMode("freespace");
+    if (request.error || !request.volume) {
+      const reason = (request.error?.code ?? "no_volume").replace(/_/g, " ");
+      showResult(false, `Cannot wipe the free space for ${request.path}: ${reason}`);
+      return;
+    }
+    try {
+      await wipeFreeSpace(request.volume, request.path);
+    } catch (error) {
+      console.error("Error during free space wipe:", error);
+      setIsWiping(false);
+      showResult(false, `Error during free space wipe: ${describeError(error)}`);
+    }
+  };
 
-      setIsWiping(true);
-      const controller = new AbortController();
-      setAbortController(controller);
+  // Estimate, confirm and wipe the free space of `path`. `folder`, when set, is the folder the
+  // wipe was asked for from, which `free_space_wipe_for_path` resolves to the same volume.
+  const wipeFreeSpace = async (path: string, folder?: string) => {
+    const estimate = (await invoke("estimate_free_space_wipe", {
+      path,
+      algorithm,
+      passes,
+    })) as FreeSpaceEstimate;
+
+    const consent = await confirmWipe(
+      path,
+      `${folder ? `Asked for from ${folder}.\n\n` : ""}${getAlgorithmDescription()}\n\nThis will write ~${formatBytes(
+        estimate.total_bytes,
+      )} and take roughly ${formatHours(estimate.eta_min_seconds)}–${formatHours(
+        estimate.eta_max_seconds,
+      )}.${estimate.wear_warning ? `\n\n${estimate.wear_warning.message}` : ""}`,
+    );
+
+    if (!consent.confirmed) {
+      showResult(false, "Operation cancelled by user");
+      return;
+    }
 
-      const result = await invoke("execute_free_space_wipe", {
-        path,
-        algorithm,
-        passes,
-      });
+    setIsWiping(true);
+
+    const result = folder
+      ? await invoke("free_space_wipe_for_path", {
+          path: folder,
+          algorithm,
+          passes,
+          deleteShadowCopies,
+          consentToken: consent.token,
+        })
+      : await invoke("execute_free_space_wipe", {
+          path,
+          algorithm,
+          passes,
+          deleteShadowCopies,
+          estimate: { token: estimate.token },
+          consentToken: consent.token,
+        });
 
-      setIsWiping(false);
-      setAbortController(null);
+    setIsWiping(false);
 
-      // Guard against undefined result
-      if (!result) {
-        showResult(false, "No response from free space wipe operation");
-        return;
-      }
+    // Guard against undefined result
+    if (!result) {
+      showResult(false, "No response from free space wipe operation");
+      return;
+    }
 
-      const { success = false, message = "Unknown error" } = result as {
-        success?: boolean;
-        message?: string;
+    const {
+      success = false,
+      message = "Unknown error",
+      report,
+    } = result as {
+      success?: boolean;
+      message?: string;
+      report?: {
+        operation_id?: string;
+        shadow_copies?: { count: number; action: string };
+        cancelled_fill?: { bytes_written: number; passes_requested: number; passes_received: number };
       };
-      showResult(success, message);
-    } catch (error) {
-      console.error("Error during free space wipe:", error);
+    };
+    // Cancelled during the fill: the space is still held until the user says how to release it.
+    const cancelledFill = report?.cancelled_fill;
+    let outcome = message;
+    if (cancelledFill && report?.operation_id) {
+      const finish = await ask(
+        `The wipe was cancelled after filling ${(cancelledFill.bytes_written / 1024 / 1024).toFixed(1)} MB, ` +
+          `which has had ${cancelledFill.passes_received} of ${cancelledFill.passes_requested} passes.\n\n` +
+          "Finish a minimal wipe (one zero pass over what was written) before releasing the space?",
+        { title: "Wipe cancelled", kind: "warning", okLabel: "Finish minimal wipe", cancelLabel: "Release space now" },
+      );
+      setIsWiping(finish);
+      const finalized = (await invoke("finalize_cancelled_free_space", {
+        operationId: report.operation_id,
+        action: finish ? "finish_minimal_wipe" : "release_space",
+      })) as { success?: boolean; message?: string } | undefined;
       setIsWiping(false);
-      setAbortController(null);
-      showResult(false, `Error during free space wipe: ${error}`);
+      outcome = finalized?.message ?? message;
     }
+    const shadow = report?.shadow_copies;
+    const shadowNote =
+      shadow && shadow.action === "kept"
+        ? ` ${shadow.count} shadow copies still hold older versions of files on this drive.`
+        : "";
+    showResult(success, outcome + shadowNote);
   };
 
   const handlePassesChange = (e: React.ChangeEvent<HTMLInputElement>) => {
@@ -451,17 +1299,126 @@ function App() {
   };
 
   const handleCancel = async () => {
-    if (isWiping) {
-      try {
-        const window = new Window("main");
-        await window.emit("cancel_operation");
-        setIsWiping(false);
-        setWipeProgress(null);
+    // Until the backend registers the wipe there is no id to cancel by, and
+    // clearing the progress view would leave the wipe running unseen.
+    if (!isWiping || !activeOperationId) {
+      return;
+    }
+    try {
+      await invoke("cancel_operation", { operationId: activeOperationId });
+      setReattached(false);
+      setIsWiping(false);
+      setWipeProgress(null);
+      showResult(false, "Operation cancelled by user");
+    } catch (error) {
+      console.error("Error cancelling operation:", error);
+      showResult(false, `Error cancelling operation: ${describeError(error)}`);
+    }
+  };
+
+  const noteRetryable = (report?: WipeReportLinks) => {
+    const failures = (report?.failures?.length ?? 0) + (report?.skipped ?? []).filter((skip) => skip.code === "file_in_use").length;
+    setRetryable(report?.operation_id && failures > 0 ? { operationId: report.operation_id, failures } : null);
+  };
+
+  const handleRetryFailed = async () => {
+    if (!retryable) return;
+    const operationId = retryable.operationId;
+    setRetryable(null);
+    try {
+      setResult(null);
+      setWipeProgress(null);
+      setOperationMode("files");
+      setIsWiping(true);
+      const result = (await withTargetConsent((consentToken) =>
+        invoke("retry_failed", { operationId, passes, algorithm, consentToken }),
+      )) as { success?: boolean; message?: string; report?: WipeReportLinks } | null | undefined;
+      setIsWiping(false);
+      if (result === null) {
+        setRetryable(retryable);
         showResult(false, "Operation cancelled by user");
-      } catch (error) {
-        console.error("Error cancelling operation:", error);
-        showResult(false, `Error cancelling operation: ${error}`);
+        return;
+      }
+      noteRetryable(result?.report);
+      showResult(
+        result?.success ?? false,
+        `Retry of ${result?.report?.retry_of ?? operationId}: ${result?.message ?? "No response from wipe operation"}`,
+      );
+    } catch (error) {
+      console.error("Error retrying failed files:", error);
+      setIsWiping(false);
+      showResult(false, `Error retrying failed files: ${describeError(error)}`);
+    }
+  };
+
+  const handleResumePartial = async () => {
+    const pending = partialWipes;
+    const paths = pending.map((partial) => partial.path);
+    setPartialWipes([]);
+    try {
+      setResult(null);
+      setWipeProgress(null);
+      setOperationMode("files");
+      setIsWiping(true);
+      const result = (await withTargetConsent((consentToken) =>
+        invoke("resume_partial_wipes", { paths, passes, algorithm, consentToken }),
+      )) as { success?: boolean; message?: string } | null | undefined;
+      setIsWiping(false);
+      if (result === null) {
+        setPartialWipes(pending);
+        showResult(false, "Operation cancelled by user");
+        return;
       }
+      showResult(
+        result?.success ?? false,
+        result?.message ?? "No response from wipe operation",
+      );
+    } catch (error) {
+      console.error("Error resuming partial wipes:", error);
+      setIsWiping(false);
+      showResult(false, `Error resuming partial wipes: ${describeError(error)}`);
+    }
+  };
+
+  const handleResumeInterrupted = async (operationId: string) => {
+    const pending = interruptedOps;
+    setInterruptedOps((ops) =>
+      ops.filter((op) => op.operation_id !== operationId),
+    );
+    try {
+      setResult(null);
+      setWipeProgress(null);
+      setOperationMode("files");
+      setIsWiping(true);
+      const result = (await withTargetConsent((consentToken) =>
+        invoke("resume_interrupted_operation", { operationId, consentToken }),
+      )) as { success?: boolean; message?: string } | null | undefined;
+      setIsWiping(false);
+      if (result === null) {
+        // Declined: the journal is kept, so the operation can still be resumed or discarded.
+        setInterruptedOps(pending);
+        showResult(false, "Operation cancelled by user");
+        return;
+      }
+      showResult(
+        result?.success ?? false,
+        result?.message ?? "No response from wipe operation",
+      );
+    } catch (error) {
+      console.error("Error resuming interrupted operation:", error);
+      setIsWiping(false);
+      showResult(false, `Error resuming interrupted operation: ${describeError(error)}`);
+    }
+  };
+
+  const handleDiscardInterrupted = async (operationId: string) => {
+    setInterruptedOps((ops) =>
+      ops.filter((op) => op.operation_id !== operationId),
+    );
+    try {
+      await invoke("discard_interrupted_operation", { operationId });
+    } catch (error) {
+      showResult(false, `Error discarding interrupted operation: ${describeError(error)}`);
     }
   };
 
@@ -539,16 +1496,44 @@ function App() {
                   className="select select-bordered w-full text-lg text-center"
                   disabled={isWiping}
                 >
-                  <option value="NistClear">NIST 800-88 Clear (Quick)</option>
-                  <option value="NistPurge">
+                  <option value="NistClear" disabled={algorithmBlocked("NistClear")}>
+                    NIST 800-88 Clear (Quick)
+                  </option>
+                  <option value="NistPurge" disabled={algorithmBlocked("NistPurge")}>
                     NIST 800-88 Purge (Recommended)
                   </option>
-                  <option value="Gutmann">Gutmann (Very Slow)</option>
-                  <option value="Random">Random Passes (Custom)</option>
+                  <option value="GutmannLite" disabled={algorithmBlocked("GutmannLite")}>
+                    Gutmann Lite (Slow)
+                  </option>
+                  <option value="Gutmann" disabled={algorithmBlocked("Gutmann")}>
+                    Gutmann (Very Slow)
+                  </option>
+                  <option value="Random" disabled={algorithmBlocked("Random")}>
+                    Random Passes (Custom)
+                  </option>
                 </select>
+                {policy?.source && (
+                  <div className="text-xs text-gray-400 text-center mt-2">
+                    {policy.error
+                      ? `The policy at ${policy.source} could not be read, so wiping is blocked: ${policy.error}`
+                      : `Some options are restricted by the policy at ${policy.source}`}
+                  </div>
+                )}
                 <div className="text-sm text-gray-400 text-center mt-2">
                   {getAlgorithmDescription()}
                 </div>
+                {recommendation && (
+                  <div className="text-xs text-gray-400 text-center mt-2 space-y-1">
+                    {recommendation.reasons.map((reason) => (
+                      <div key={reason}>{reason}</div>
+                    ))}
+                    {recommendation.warnings.map((warning) => (
+                      <div key={warning} className="text-warning">
+                        {warning}
+                      </div>
+                    ))}
+                  </div>
+                )}
               </div>
 
               <div className="form-control">
@@ -587,17 +1572,82 @@ function App() {
               <button
                 className="btn bg-[#3730a3] hover:bg-[#312e81] text-white border-none"
                 onClick={() => setOperationMode("files")}
+                disabled={commandBlocked("wipe_files")}
               >
                 Wipe Files/Folders
               </button>
               <button
                 className="btn bg-[#f97316] hover:bg-[#ea580c] text-white border-none"
                 onClick={handleWipeFreeSpace}
+                disabled={commandBlocked("wipe_free_space")}
               >
                 Wipe Drive Free Space
               </button>
+              <button
+                className="btn btn-outline"
+                onClick={handleSelfTest}
+                disabled={commandBlocked("run_self_test")}
+              >
+                Self-Test
+              </button>
             </div>
           )}
+          {operationMode === "initial" && !isWiping && (
+            <label className="label cursor-pointer justify-center gap-2 mt-2">
+              <input
+                type="checkbox"
+                className="checkbox checkbox-sm"
+                checked={deleteShadowCopies}
+                onChange={(e) => setDeleteShadowCopies(e.target.checked)}
+              />
+              <span className="label-text text-sm">
+                Delete shadow copies before wiping free space (requires
+                administrator)
+              </span>
+            </label>
+          )}
+
+          {operationMode === "files" && !isWiping && (
+            <label className="label cursor-pointer justify-center gap-2 mb-2">
+              <input
+                type="checkbox"
+                className="checkbox checkbox-sm"
+                checked={preserveRoots}
+                onChange={(e) => setPreserveRoots(e.target.checked)}
+              />
+              <span className="label-text text-sm">
+                Keep selected folders (wipe their contents only)
+              </span>
+            </label>
+          )}
+
+          {operationMode === "files" && !isWiping && (
+            <label className="label cursor-pointer justify-center gap-2 mb-2">
+              <input
+                type="checkbox"
+                className="checkbox checkbox-sm"
+                checked={companionFiles}
+                onChange={(e) => setCompanionFiles(e.target.checked)}
+              />
+              <span className="label-text text-sm">
+                Also wipe lock, backup and swap files left beside selected files
+              </span>
+            </label>
+          )}
+
+          {operationMode === "files" && !isWiping && (
+            <label className="label cursor-pointer justify-center gap-2 mb-2">
+              <input
+                type="checkbox"
+                className="checkbox checkbox-sm"
+                checked={allowRecall}
+                onChange={(e) => setAllowRecall(e.target.checked)}
+              />
+              <span className="label-text text-sm">
+                Recall and wipe cloud or archived files (copies in remote storage may persist)
+              </span>
+            </label>
+          )}
 
           {/* Drop Zone - Only visible in files mode */}
           {operationMode === "files" && !isWiping && (
@@ -710,6 +1760,8 @@ function App() {
                       onClick={() => {
                         setIsContextMode(false);
                         setContextInvalidPaths([]);
+                        setContextVolumeRoots([]);
+                        setContextElevationMismatch(null);
                       }}
                     >
                       ✕
@@ -721,6 +1773,31 @@ function App() {
                     {contextInvalidPaths.length > 0 ? " (some items were skipped)" : ""}.
                   </p>
 
+                  {contextElevationMismatch && (
+                    <div className="alert alert-warning text-sm">
+                      This BitBurn runs{" "}
+                      {describeLaunchContext(contextElevationMismatch.receiver)}{" "}
+                      but the request came from a{" "}
+                      {describeLaunchContext(contextElevationMismatch.sender)}{" "}
+                      session. Mapped drives and user folders can differ
+                      between the two; items it cannot reach are listed as
+                      skipped.
+                    </div>
+                  )}
+
+                  {contextVolumeRoots.length > 0 && (
+                    <div className="alert alert-error text-sm">
+                      <div className="font-semibold">
+                        Whole volumes selected - every file on them will be destroyed
+                      </div>
+                      <ul className="list-disc list-inside space-y-1">
+                        {contextVolumeRoots.map((root) => (
+                          <li key={root} className="break-all">{root}</li>
+                        ))}
+                      </ul>
+                    </div>
+                  )}
+
                   {contextInvalidPaths.length > 0 && (
                     <div className="alert alert-warning text-sm">
                       <div className="font-semibold">Skipped items</div>
@@ -756,6 +1833,7 @@ function App() {
                         >
                           <option value="NistClear">NIST 800-88 Clear (Quick)</option>
                           <option value="NistPurge">NIST 800-88 Purge (Recommended)</option>
+                          <option value="GutmannLite">Gutmann Lite (Slow)</option>
                           <option value="Gutmann">Gutmann (Very Slow)</option>
                           <option value="Random">Random Passes (Custom)</option>
                         </select>
@@ -796,6 +1874,8 @@ function App() {
                       onClick={() => {
                         setIsContextMode(false);
                         setContextInvalidPaths([]);
+                        setContextVolumeRoots([]);
+                        setContextElevationMismatch(null);
                         setSelectedPaths([]);
                         setOperationMode("initial");
                       }}
@@ -858,6 +1938,23 @@ function App() {
                 </span>
               </div>
 
+              {wipeProgress.current_directory && (
+                <div className="text-center text-xs text-gray-400 mb-2 break-all">
+                  Working inside {wipeProgress.current_directory} (file{" "}
+                  {Math.min(
+                    (wipeProgress.directory_files_done ?? 0) + 1,
+                    wipeProgress.directory_files_total ?? 0,
+                  ).toLocaleString()}{" "}
+                  of {(wipeProgress.directory_files_total ?? 0).toLocaleString()} here,{" "}
+                  {Math.min(
+                    (wipeProgress.files_done ?? 0) + 1,
+                    wipeProgress.files_total ?? 0,
+                  ).toLocaleString()}{" "}
+                  of {(wipeProgress.files_total ?? 0).toLocaleString()}
+                  {wipeProgress.tree_changed ? " - folder changed since the scan" : ""})
+                </div>
+              )}
+
               {(wipeProgress.total_bytes > 0 ||
                 wipeProgress.estimated_total_bytes) && (
                 <div className="text-center text-sm mb-4">
@@ -879,22 +1976,214 @@ function App() {
               )}
 
               {/* Cancel Button */}
-              <div className="text-center mt-4">
+              <div className="text-center mt-4 flex justify-center gap-2">
                 <button
                   className="btn btn-error btn-sm"
                   onClick={handleCancel}
-                  disabled={!abortController}
+                  disabled={!activeOperationId}
                 >
                   Cancel Operation
                 </button>
+                {activeOperationId && operationMode === "files" && (
+                  <button
+                    className="btn btn-warning btn-sm"
+                    title="Delete the current file without finishing its passes and continue with the rest"
+                    onClick={() =>
+                      invoke("skip_current_file", {
+                        operationId: activeOperationId,
+                      }).catch((error) =>
+                        console.error("Error skipping file:", error),
+                      )
+                    }
+                  >
+                    Skip File
+                  </button>
+                )}
+                {activeOperationId && (
+                  <button
+                    className="btn btn-ghost btn-sm"
+                    title="Keep a small always-on-top progress window in a corner"
+                    onClick={() =>
+                      invoke("show_progress_window", {
+                        operationId: activeOperationId,
+                      }).catch((error) =>
+                        console.error("Error showing progress window:", error),
+                      )
+                    }
+                  >
+                    Pop Out
+                  </button>
+                )}
+              </div>
+            </div>
+          )}
+
+          {/* Native dialog unavailable (session 0, Server Core, some RDP sessions) - ask here instead */}
+          {windowConfirm && (
+            <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/60 px-4">
+              <div className="card bg-base-200 w-full max-w-lg shadow-2xl">
+                <div className="card-body space-y-4">
+                  <h3 className="text-xl font-semibold">⚠️ WARNING ⚠️</h3>
+                  <p className="text-gray-300 whitespace-pre-line break-words">
+                    {windowConfirm.prompt}
+                  </p>
+                  <div className="flex justify-end gap-3">
+                    <button
+                      className="btn btn-ghost btn-sm"
+                      onClick={() => windowConfirm.answer(false)}
+                    >
+                      No
+                    </button>
+                    <button
+                      className="btn btn-error btn-sm"
+                      onClick={() => windowConfirm.answer(true)}
+                    >
+                      Yes
+                    </button>
+                  </div>
+                </div>
               </div>
             </div>
           )}
 
+          {/* Volume root refused by the backend - wiping it needs the path typed out */}
+          {volumeRootConfirm && !isWiping && (
+            <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/60 px-4">
+              <div className="card bg-base-200 w-full max-w-lg shadow-2xl">
+                <div className="card-body space-y-4">
+                  <h3 className="text-xl font-semibold">Wipe an entire volume?</h3>
+                  <p className="text-gray-300 break-all">
+                    {volumeRootConfirm} is the root of a volume. Wiping it as a folder destroys
+                    every file on it one by one. To clear unused space, use Wipe Free Space instead.
+                  </p>
+                  <p className="text-sm text-gray-400">Type the path exactly to continue:</p>
+                  <input
+                    type="text"
+                    className="input input-bordered w-full"
+                    value={volumeRootTyped}
+                    onChange={(e) => setVolumeRootTyped(e.target.value)}
+                    placeholder={volumeRootConfirm}
+                  />
+                  <div className="flex justify-end gap-3">
+                    <button
+                      className="btn btn-ghost btn-sm"
+                      onClick={() => {
+                        setVolumeRootConfirm(null);
+                        showResult(false, "Operation cancelled by user");
+                      }}
+                    >
+                      Cancel
+                    </button>
+                    <button
+                      className="btn btn-error btn-sm"
+                      disabled={volumeRootTyped !== volumeRootConfirm}
+                      onClick={handleConfirmVolumeRoot}
+                    >
+                      Wipe Entire Volume
+                    </button>
+                  </div>
+                </div>
+              </div>
+            </div>
+          )}
+
+          {/* Files the last wipe failed on or skipped as in use - offer to retry exactly those */}
+          {retryable && !isWiping && (
+            <div className="alert alert-warning mt-4 w-full max-w-lg flex flex-col items-start">
+              <span>
+                {retryable.failures} file(s) could not be wiped. Close the
+                programs using them and retry.
+              </span>
+              <button
+                className="btn btn-warning btn-sm"
+                onClick={handleRetryFailed}
+              >
+                Retry failed files
+              </button>
+            </div>
+          )}
+
+          {/* Partially wiped files - offer to wipe them again from pass 1 */}
+          {partialWipes.length > 0 && !isWiping && (
+            <div className="alert alert-warning mt-4 w-full max-w-lg flex flex-col items-start">
+              <span>
+                {partialWipes.length} file(s) were only partially overwritten
+                and still exist:
+              </span>
+              <ul className="text-xs break-all">
+                {partialWipes.map((partial) => (
+                  <li key={partial.path}>
+                    {partial.path} (pass {partial.last_pass})
+                  </li>
+                ))}
+              </ul>
+              <button
+                className="btn btn-warning btn-sm"
+                onClick={handleResumePartial}
+              >
+                Resume wiping these files
+              </button>
+            </div>
+          )}
+
+          {/* Targets on flash media - the wipe ran, but may not have reached every copy */}
+          {flashWarning && (
+            <div className="alert alert-warning mt-4 w-full max-w-lg flex flex-col items-start">
+              <span>{flashWarning.message}</span>
+              <ul className="text-xs break-all">
+                {flashWarning.paths.map((path) => (
+                  <li key={path}>{path}</li>
+                ))}
+              </ul>
+              <button
+                className="btn btn-ghost btn-sm"
+                onClick={() => setFlashWarning(null)}
+              >
+                Dismiss
+              </button>
+            </div>
+          )}
+
+          {/* Batches interrupted by a crash - resume the rest or discard the journal */}
+          {interruptedOps.length > 0 && !isWiping && (
+            <div className="alert alert-warning mt-4 w-full max-w-lg flex flex-col items-start">
+              {interruptedOps.map((op) => (
+                <div key={op.operation_id} className="w-full">
+                  <span>
+                    A wipe started {new Date(op.started_at).toLocaleString()}{" "}
+                    did not finish: {op.completed} of {op.total} item(s) done,{" "}
+                    {op.remaining.length} left.
+                  </span>
+                  {(op.left_renamed ?? []).map((file) => (
+                    <div key={file.path} className="text-sm">
+                      {file.path} is still on disk as {file.on_disk_path}.
+                    </div>
+                  ))}
+                  <div className="flex gap-2 mt-2">
+                    <button
+                      className="btn btn-warning btn-sm"
+                      onClick={() => handleResumeInterrupted(op.operation_id)}
+                    >
+                      Resume
+                    </button>
+                    <button
+                      className="btn btn-ghost btn-sm"
+                      onClick={() => handleDiscardInterrupted(op.operation_id)}
+                    >
+                      Discard
+                    </button>
+                  </div>
+                </div>
+              ))}
+            </div>
+          )}
+
           {/* Result Message - Positioned above warning footer */}
           {result && (
             <div className="fixed bottom-[60px] left-1/2 transform -translate-x-1/2 z-50 w-auto min-w-[300px] max-w-[90%]">
               <div
+                id="wipe-result"
+                tabIndex={-1}
                 className={`alert ${result.success ? "alert-success" : "alert-error"} shadow-lg flex justify-center`}
               >
                 <div className="w-full text-center px-4">{result.message}</div>
@@ -0,0 +1,89 @@
+import { useState, useEffect, useRef } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { Event } from "@tauri-apps/api/event";
+
+interface PillProgress {
+  operation_id: string;
+  current_pass: number;
+  total_passes: number;
+  current_pattern: string;
+  percentage: number;
+}
+
+// The floating progress window: one operation's progress, nothing else.
+// Closing it only hides the view; the wipe keeps running.
+function ProgressPill() {
+  const [operationId, setOperationId] = useState<string | null>(
+    new URLSearchParams(window.location.search).get("operation"),
+  );
+  const [progress, setProgress] = useState<PillProgress | null>(null);
+  const [finished, setFinished] = useState(false);
+  const boundRef = useRef(operationId);
+  boundRef.current = operationId;
+
+  useEffect(() => {
+    const current = getCurrentWindow();
+    const unlisteners = [
+      current.listen<PillProgress>(
+        "wipe_progress",
+        (event: Event<PillProgress>) => setProgress(event.payload),
+      ),
+      current.listen<{ operation_id: string }>(
+        "progress_window_bound",
+        (event: Event<{ operation_id: string }>) => {
+          setOperationId(event.payload.operation_id);
+          setProgress(null);
+          setFinished(false);
+        },
+      ),
+      current.listen<{ operation_id: string }>(
+        "operation_finished",
+        (event: Event<{ operation_id: string }>) => {
+          if (boundRef.current === event.payload.operation_id) {
+            setFinished(true);
+          }
+        },
+      ),
+    ];
+    return () => {
+      unlisteners.forEach((pending) => pending.then((unlisten) => unlisten()));
+    };
+  }, []);
+
+  const shown =
+    progress && progress.operation_id === operationId ? progress : null;
+  const percentage = finished ? 100 : (shown?.percentage ?? 0);
+
+  return (
+    <div
+      className="h-screen bg-base-200 text-base-content px-3 py-2 flex flex-col justify-center select-none"
+      data-tauri-drag-region
+    >
+      <div className="flex items-center justify-between text-xs" data-tauri-drag-region>
+        <span className="truncate" data-tauri-drag-region>
+          {finished
+            ? "Finished"
+            : shown
+              ? `Pass ${shown.current_pass}/${shown.total_passes} - ${shown.current_pattern}`
+              : "Waiting for progress..."}
+        </span>
+        <button
+          className="btn btn-ghost btn-xs"
+          title="Close this window; the wipe keeps running"
+          onClick={() => invoke("hide_progress_window")}
+        >
+          ✕
+        </button>
+      </div>
+      <progress
+        className="progress progress-primary w-full mt-1"
+        value={percentage}
+        max={100}
+      />
+      <span className="text-xs text-right">{percentage.toFixed(1)}%</span>
+    </div>
+  );
+}
+
+export default ProgressPill;
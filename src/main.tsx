@@ -1,10 +1,11 @@
 import React from 'react'
 import ReactDOM from 'react-dom/client'
 import App from './App'
+import ProgressPill from './ProgressPill'
 import './index.css'
 
 ReactDOM.createRoot(document.getElementById('root') as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    {new URLSearchParams(window.location.search).get('view') === 'progress' ? <ProgressPill /> : <App />}
   </React.StrictMode>,
 ) 
\ No newline at end of file